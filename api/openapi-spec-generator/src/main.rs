@@ -4,7 +4,7 @@
 mod fake_context;
 
 use anyhow::Result;
-use aptos_api::get_api_service;
+use aptos_api::{get_api_service, get_api_service_v2};
 use clap::{Parser, ValueEnum};
 use fake_context::get_fake_context;
 use std::{path::PathBuf, sync::Arc};
@@ -15,6 +15,12 @@ pub enum OutputFormat {
     Yaml,
 }
 
+#[derive(ValueEnum, Clone, Debug)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
 #[derive(Clone, Debug, Parser)]
 pub struct OutputArgs {
     /// By default, the spec is written to stdout. If this is provided, the
@@ -41,16 +47,33 @@ impl OutputArgs {
 pub struct Args {
     #[clap(flatten)]
     pub output_args: OutputArgs,
+
+    /// What version of the API to output the spec of.
+    #[clap(long, ignore_case = true, value_enum, default_value_t = ApiVersion::V1)]
+    pub api_version: ApiVersion,
 }
 
 pub fn main() -> Result<()> {
     let args = Args::parse();
 
-    let api_service = get_api_service(Arc::new(get_fake_context()));
+    let context = Arc::new(get_fake_context());
 
-    let spec = match args.output_args.format {
-        OutputFormat::Json => api_service.spec(),
-        OutputFormat::Yaml => api_service.spec_yaml(),
+    // The services of the versions have different types, so they can't be matched together.
+    let spec = match args.api_version {
+        ApiVersion::V1 => {
+            let api_service = get_api_service(context);
+            match args.output_args.format {
+                OutputFormat::Json => api_service.spec(),
+                OutputFormat::Yaml => api_service.spec_yaml(),
+            }
+        },
+        ApiVersion::V2 => {
+            let api_service = get_api_service_v2(context);
+            match args.output_args.format {
+                OutputFormat::Json => api_service.spec(),
+                OutputFormat::Yaml => api_service.spec_yaml(),
+            }
+        },
     };
     args.output_args.write(&spec)
 }
@@ -1,13 +1,13 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_api_types::mime_types::BCS;
+use aptos_api_types::mime_types::{BCS, JSON};
 use poem::{web::Accept, FromRequest, Request, RequestBody, Result};
 
 /// Accept types from input headers
 ///
 /// Determines the output type of each API
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum AcceptType {
     /// Convert and resolve types to JSON
     Json,
@@ -37,3 +37,25 @@ fn parse_accept(accept: &Accept) -> Result<AcceptType> {
     // Default to returning content as JSON.
     Ok(AcceptType::Json)
 }
+
+/// Accept types of the v2 API, which returns BCS unless JSON is explicitly
+/// accepted and BCS isn't
+#[derive(PartialEq, Eq, Debug)]
+pub struct BcsFirstAcceptType(pub AcceptType);
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for BcsFirstAcceptType {
+    async fn from_request(request: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let accept = Accept::from_request_without_body(request).await?;
+        Ok(Self(parse_accept_bcs_first(&accept)))
+    }
+}
+
+fn parse_accept_bcs_first(accept: &Accept) -> AcceptType {
+    let accepts = |mime_type: &str| accept.0.iter().any(|mime| mime.as_ref() == mime_type);
+    if accepts(JSON) && !accepts(BCS) {
+        AcceptType::Json
+    } else {
+        AcceptType::Bcs
+    }
+}
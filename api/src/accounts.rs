@@ -7,16 +7,19 @@ use crate::{
     context::Context,
     failpoint::fail_point_poem,
     page::determine_limit,
+    resource_filter::ResourceFilter,
     response::{
-        account_not_found, resource_not_found, struct_field_not_found, BadRequestError,
-        BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404, InternalError,
+        account_not_found, api_forbidden, resource_not_found, struct_field_not_found,
+        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
+        InternalError,
     },
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    AccountData, Address, AptosErrorCode, AsConverter, LedgerInfo, MoveModuleBytecode,
-    MoveModuleId, MoveResource, MoveStructTag, StateKeyWrapper, U64,
+    AccountData, Address, AptosErrorCode, AsConverter, LedgerInfo, MoveFunctionVisibility,
+    MoveModuleBytecode, MoveModuleFunctions, MoveModuleId, MoveResource, MoveStructTag,
+    StateKeyWrapper, U64,
 };
 use aptos_types::{
     access_path::AccessPath,
@@ -83,6 +86,9 @@ impl AccountsApi {
     ///
     /// The Aptos nodes prune account state history, via a configurable time window.
     /// If the requested ledger version has been pruned, the server responds with a 410.
+    ///
+    /// With a filter, only the matching resources are returned and counted in the limit, and
+    /// the next page is given by the cursor in the X-Aptos-Cursor header.
     #[oai(
         path = "/accounts/:address/resources",
         method = "get",
@@ -109,10 +115,22 @@ impl AccountsApi {
         ///
         /// If not provided, defaults to default page size.
         limit: Query<Option<u16>>,
+        /// Only the resources of this module, e.g. `0x1::coin`, of this struct with any type
+        /// arguments, e.g. `0x1::coin::CoinStore`, or of this struct tag, e.g.
+        /// `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+        filter: Query<Option<String>>,
     ) -> BasicResultWith404<Vec<MoveResource>> {
         fail_point_poem("endpoint_get_account_resources")?;
         self.context
             .check_api_output_enabled("Get account resources", &accept_type)?;
+        let filter = filter
+            .0
+            .as_deref()
+            .map(ResourceFilter::new)
+            .transpose()
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
         let account = Account::new(
             self.context.clone(),
             address.0,
@@ -120,7 +138,7 @@ impl AccountsApi {
             start.0.map(StateKey::from),
             limit.0,
         )?;
-        account.resources(&accept_type)
+        account.resources(&accept_type, filter.as_ref())
     }
 
     /// Get account modules
@@ -169,6 +187,54 @@ impl AccountsApi {
         )?;
         account.modules(&accept_type)
     }
+
+    /// Get account ABIs
+    ///
+    /// Retrieves the ABIs of the entry and view functions of all the modules of an account
+    /// at a specific ledger version, grouped by module, so that they don't have to be
+    /// parsed from every module. The modules without any of these functions are left out.
+    /// If the ledger version is not specified in the request, the latest ledger version is used.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/abis",
+        method = "get",
+        operation_id = "get_account_abis",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_abis(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+        /// Only the functions with this visibility
+        ///
+        /// If not provided, the functions of all visibilities are returned
+        visibility: Query<Option<MoveFunctionVisibility>>,
+    ) -> BasicResultWith404<Vec<MoveModuleFunctions>> {
+        fail_point_poem("endpoint_get_account_abis")?;
+        if AcceptType::Bcs == accept_type {
+            return Err(api_forbidden(
+                "Get account ABIs",
+                "Only JSON is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get account ABIs", &accept_type)?;
+        let account = Account::new(
+            self.context.clone(),
+            address.0,
+            ledger_version.0,
+            None,
+            None,
+        )?;
+        account.abis(visibility.0)
+    }
 }
 
 /// A struct representing Account related lookups for resources and modules
@@ -307,7 +373,11 @@ impl Account {
     ///
     /// Note: For the BCS response, if results are being returned in pages, i.e. with the
     /// `start` and `limit` query parameters, the results will only be sorted within each page.
-    pub fn resources(self, accept_type: &AcceptType) -> BasicResultWith404<Vec<MoveResource>> {
+    pub fn resources(
+        self,
+        accept_type: &AcceptType,
+        filter: Option<&ResourceFilter>,
+    ) -> BasicResultWith404<Vec<MoveResource>> {
         // check account exists
         self.verify_account_or_object_resource()?;
         let max_account_resources_page_size = self.context.max_account_resources_page_size();
@@ -324,6 +394,7 @@ impl Account {
                     max_account_resources_page_size,
                     &self.latest_ledger_info,
                 )? as u64,
+                filter,
             )
             .context("Failed to get resources from storage")
             .map_err(|err| {
@@ -447,6 +518,76 @@ impl Account {
         }
     }
 
+    /// Retrieves the entry and view functions of all the modules of the account
+    pub fn abis(
+        self,
+        visibility: Option<MoveFunctionVisibility>,
+    ) -> BasicResultWith404<Vec<MoveModuleFunctions>> {
+        // check account exists
+        self.verify_account_or_object_resource()?;
+        let max_account_modules_page_size = self.context.max_account_modules_page_size();
+        let mut abis = Vec::new();
+        let mut start = None;
+        loop {
+            let (modules, next_state_key) = self
+                .context
+                .get_modules_by_pagination(
+                    self.address.into(),
+                    start.as_ref(),
+                    self.ledger_version,
+                    max_account_modules_page_size as u64,
+                )
+                .context("Failed to get modules from storage")
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &self.latest_ledger_info,
+                    )
+                })?;
+            for (module_id, module) in modules {
+                // Modules with invalid bytecode have no ABI
+                let abi = match MoveModuleBytecode::new(module)
+                    .try_parse_abi()
+                    .context("Failed to parse move module ABI")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &self.latest_ledger_info,
+                        )
+                    })?
+                    .abi
+                {
+                    Some(abi) => abi,
+                    None => continue,
+                };
+                let functions: Vec<_> = abi
+                    .exposed_functions
+                    .into_iter()
+                    .filter(|function| {
+                        (function.is_entry || function.is_view)
+                            && visibility
+                                .as_ref()
+                                .map_or(true, |visibility| function.visibility == *visibility)
+                    })
+                    .collect();
+                if !functions.is_empty() {
+                    abis.push(MoveModuleFunctions {
+                        module: module_id.into(),
+                        functions,
+                    });
+                }
+            }
+            match next_state_key {
+                Some(next_state_key) => start = Some(next_state_key),
+                None => break,
+            }
+        }
+
+        BasicResponse::try_from_json((abis, &self.latest_ledger_info, BasicResponseStatus::Ok))
+    }
+
     /// Retrieves an event key from a [`MoveStructTag`] and a [`Identifier`] field name
     ///
     /// e.g. If there's the `CoinStore` module, it has a field named `withdraw_events` for
@@ -0,0 +1,308 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rate limits the requests by their API key, so that the operators of public nodes can offer
+//! different levels of access without a separate gateway. The requests without an API key share
+//! the anonymous rate limit, if there is one.
+
+use crate::metrics::API_KEY_REQUESTS;
+use anyhow::{format_err, Context as AnyhowContext, Result};
+use aptos_api_types::{AptosError, AptosErrorCode, AptosErrorDetails, RateLimitExceededDetails};
+use aptos_config::config::{ApiKeysConfig, ApiRateLimitConfig};
+use poem::{
+    handler,
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    web::Data,
+    Endpoint, IntoResponse, Middleware, Request, Response,
+};
+use poem_openapi::payload::Json;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+const ANONYMOUS: &str = "anonymous";
+/// The usage of a key can be read after it exceeded its rate limit
+pub const USAGE_PATH: &str = "/v1/api_key/usage";
+
+/// Usage of an API key since the node started
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ApiKeyUsage {
+    pub name: String,
+    pub requests_per_second: u32,
+    pub burst: u32,
+    /// Requests that can be sent right away
+    pub available_requests: u32,
+    pub requests: u64,
+    pub throttled_requests: u64,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+struct RateLimiter {
+    name: String,
+    rate_limit: ApiRateLimitConfig,
+    bucket: Mutex<TokenBucket>,
+    requests: AtomicU64,
+    throttled_requests: AtomicU64,
+}
+
+impl RateLimiter {
+    fn new(name: String, rate_limit: ApiRateLimitConfig, now: Instant) -> Self {
+        Self {
+            name,
+            bucket: Mutex::new(TokenBucket {
+                tokens: rate_limit.burst as f64,
+                refilled_at: now,
+            }),
+            rate_limit,
+            requests: AtomicU64::new(0),
+            throttled_requests: AtomicU64::new(0),
+        }
+    }
+
+    fn refill(&self, bucket: &mut TokenBucket, now: Instant) {
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = f64::min(
+            bucket.tokens + elapsed.as_secs_f64() * self.rate_limit.requests_per_second as f64,
+            self.rate_limit.burst as f64,
+        );
+        bucket.refilled_at = now;
+    }
+
+    /// Takes a request from the bucket, or returns how long to wait for one
+    fn try_acquire(&self, now: Instant) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            API_KEY_REQUESTS
+                .with_label_values(&[&self.name, "allowed"])
+                .inc();
+            Ok(())
+        } else {
+            self.throttled_requests.fetch_add(1, Ordering::Relaxed);
+            API_KEY_REQUESTS
+                .with_label_values(&[&self.name, "throttled"])
+                .inc();
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.rate_limit.requests_per_second as f64,
+            ))
+        }
+    }
+
+    fn usage(&self, now: Instant) -> ApiKeyUsage {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket, now);
+        ApiKeyUsage {
+            name: self.name.clone(),
+            requests_per_second: self.rate_limit.requests_per_second,
+            burst: self.rate_limit.burst,
+            available_requests: bucket.tokens as u32,
+            requests: self.requests.load(Ordering::Relaxed),
+            throttled_requests: self.throttled_requests.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn error_response(status: StatusCode, error: AptosError) -> Response {
+    let mut response = Json(error).into_response();
+    response.set_status(status);
+    response
+}
+
+/// The rate limiters of the configured API keys
+pub struct ApiKeys {
+    enabled: bool,
+    header: HeaderName,
+    keys: HashMap<String, RateLimiter>,
+    anonymous: Option<RateLimiter>,
+}
+
+impl ApiKeys {
+    pub fn new(config: &ApiKeysConfig) -> Result<Self> {
+        let now = Instant::now();
+        Ok(Self {
+            enabled: config.enabled,
+            header: HeaderName::from_str(&config.header).context("Invalid API key header")?,
+            keys: config
+                .keys
+                .iter()
+                .map(|api_key| {
+                    (
+                        api_key.key.clone(),
+                        RateLimiter::new(api_key.name.clone(), api_key.rate_limit.clone(), now),
+                    )
+                })
+                .collect(),
+            anonymous: config
+                .anonymous_rate_limit
+                .clone()
+                .map(|rate_limit| RateLimiter::new(ANONYMOUS.to_string(), rate_limit, now)),
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn header(&self) -> &HeaderName {
+        &self.header
+    }
+
+    /// The rate limiter of the request, or the response rejecting it if its API key is missing
+    /// or unknown
+    fn rate_limiter(&self, req: &Request) -> Result<&RateLimiter, Response> {
+        match req.headers().get(&self.header) {
+            Some(key) => key
+                .to_str()
+                .ok()
+                .and_then(|key| self.keys.get(key))
+                .ok_or_else(|| {
+                    error_response(
+                        StatusCode::UNAUTHORIZED,
+                        AptosError::new_with_error_code(
+                            format!("Unknown API key in the {} header", self.header),
+                            AptosErrorCode::InvalidApiKey,
+                        ),
+                    )
+                }),
+            None => self.anonymous.as_ref().ok_or_else(|| {
+                error_response(
+                    StatusCode::UNAUTHORIZED,
+                    AptosError::new_with_error_code(
+                        format!("An API key is required in the {} header", self.header),
+                        AptosErrorCode::InvalidApiKey,
+                    ),
+                )
+            }),
+        }
+    }
+}
+
+/// This middleware rejects the requests without a valid API key, unless there is an anonymous
+/// rate limit, and the requests exceeding the rate limit of their key.
+pub struct ApiKeyRateLimit {
+    api_keys: Arc<ApiKeys>,
+}
+
+impl ApiKeyRateLimit {
+    pub fn new(api_keys: Arc<ApiKeys>) -> Self {
+        Self { api_keys }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ApiKeyRateLimit {
+    type Output = ApiKeyRateLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ApiKeyRateLimitEndpoint {
+            inner: ep,
+            api_keys: self.api_keys.clone(),
+        }
+    }
+}
+
+/// Endpoint for ApiKeyRateLimit middleware.
+pub struct ApiKeyRateLimitEndpoint<E> {
+    inner: E,
+    api_keys: Arc<ApiKeys>,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for ApiKeyRateLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+        if req.uri().path() != USAGE_PATH {
+            let rate_limiter = match self.api_keys.rate_limiter(&req) {
+                Ok(rate_limiter) => rate_limiter,
+                Err(response) => return Ok(response),
+            };
+            if let Err(wait) = rate_limiter.try_acquire(Instant::now()) {
+                let retry_after_secs = wait.as_secs_f64().ceil() as u64;
+                let error = AptosError::new_with_error_code(
+                    format!(
+                        "The rate limit of {} requests per second of the API key {} is exceeded",
+                        rate_limiter.rate_limit.requests_per_second, rate_limiter.name
+                    ),
+                    AptosErrorCode::RateLimitExceeded,
+                )
+                .with_details(AptosErrorDetails::RateLimitExceeded(
+                    RateLimitExceededDetails {
+                        retry_after_secs: retry_after_secs.into(),
+                    },
+                ));
+                let mut response = error_response(StatusCode::TOO_MANY_REQUESTS, error);
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+                return Ok(response);
+            }
+        }
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+/// Returns the usage of the API key of the request
+#[handler]
+pub fn api_key_usage_poem(api_keys: Data<&Arc<ApiKeys>>, req: &Request) -> poem::Result<Response> {
+    if !api_keys.enabled() {
+        return Err(poem::Error::from(format_err!(
+            "API keys are not enabled at a config level"
+        )));
+    }
+    Ok(match api_keys.rate_limiter(req) {
+        Ok(rate_limiter) => poem::web::Json(rate_limiter.usage(Instant::now())).into_response(),
+        Err(response) => response,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let now = Instant::now();
+        let rate_limiter = RateLimiter::new(
+            "test".to_string(),
+            ApiRateLimitConfig {
+                requests_per_second: 2,
+                burst: 3,
+            },
+            now,
+        );
+        for _ in 0..3 {
+            rate_limiter.try_acquire(now).unwrap();
+        }
+        assert_eq!(
+            rate_limiter.try_acquire(now),
+            Err(Duration::from_millis(500))
+        );
+
+        // Half a second refills a request, but no more than the burst
+        let later = now + Duration::from_millis(500);
+        rate_limiter.try_acquire(later).unwrap();
+        assert!(rate_limiter.try_acquire(later).is_err());
+        let much_later = later + Duration::from_secs(60);
+        assert_eq!(rate_limiter.usage(much_later), ApiKeyUsage {
+            name: "test".to_string(),
+            requests_per_second: 2,
+            burst: 3,
+            available_requests: 3,
+            requests: 4,
+            throttled_requests: 2,
+        });
+    }
+}
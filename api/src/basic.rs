@@ -9,10 +9,11 @@ use crate::{
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
-use aptos_api_types::AptosErrorCode;
+use aptos_api_types::{AptosErrorCode, LedgerInfo, U64};
 use poem_openapi::{param::Query, payload::Html, Object, OpenApi};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     ops::Sub,
     sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
@@ -20,11 +21,19 @@ use std::{
 
 const OPEN_API_HTML: &str = include_str!("../doc/spec.html");
 
+/// Gauge of the network connections of the node, labelled by network and direction
+const CONNECTIONS_METRIC: &str = "aptos_connections";
+
 // Generate error and response types
 generate_success_response!(HealthCheckResponse, (200, Ok));
 generate_error_response!(HealthCheckError, (503, ServiceUnavailable), (500, Internal));
 pub type HealthCheckResult<T> = poem::Result<HealthCheckResponse<T>, HealthCheckError>;
 
+// The report of an unhealthy node is returned with a 503, so that load balancers eject it
+generate_success_response!(NodeHealthResponse, (200, Ok), (503, ServiceUnavailable));
+generate_error_response!(NodeHealthError, (500, Internal));
+pub type NodeHealthResult<T> = poem::Result<NodeHealthResponse<T>, NodeHealthError>;
+
 /// Basic API does healthchecking and shows the OpenAPI spec
 pub struct BasicApi {
     pub context: Arc<Context>,
@@ -44,6 +53,87 @@ impl HealthCheckSuccess {
     }
 }
 
+/// Number of peers connected to the node on a network
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize, Object)]
+pub struct ConnectedPeers {
+    /// Network of the connections, e.g. `public`
+    pub network_id: String,
+    pub inbound: U64,
+    pub outbound: U64,
+}
+
+/// Detailed health of the node
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize, Object)]
+pub struct NodeHealth {
+    /// Whether all the health checks passed
+    pub healthy: bool,
+    /// Reasons the node is unhealthy, if any
+    pub failures: Vec<String>,
+    pub ledger_version: U64,
+    pub ledger_timestamp: U64,
+    /// Seconds between the timestamp of the latest ledger info and the current time
+    pub sync_lag_secs: U64,
+    /// Oldest version whose transactions aren't pruned
+    pub oldest_ledger_version: U64,
+    /// Oldest block height whose transactions aren't pruned
+    pub oldest_block_height: U64,
+    /// Oldest version whose state isn't pruned
+    pub oldest_state_version: Option<U64>,
+    /// Number of versions of transactions kept before they are pruned
+    pub ledger_prune_window: Option<U64>,
+    /// Number of versions of state kept before it is pruned
+    pub state_prune_window: Option<U64>,
+    pub connected_peers: Vec<ConnectedPeers>,
+    /// Build information of the node, e.g. `build_commit_hash`
+    pub build_info: BTreeMap<String, String>,
+}
+
+/// Sums the connections of the node per network from its metrics, as the API has no handle on
+/// the networks themselves
+fn connected_peers() -> Vec<ConnectedPeers> {
+    let mut connections = BTreeMap::<String, (u64, u64)>::new();
+    for family in aptos_metrics_core::gather() {
+        if family.get_name() != CONNECTIONS_METRIC {
+            continue;
+        }
+        for metric in family.get_metric() {
+            let label = |name: &str| {
+                metric
+                    .get_label()
+                    .iter()
+                    .find(|label| label.get_name() == name)
+                    .map(|label| label.get_value().to_string())
+                    .unwrap_or_default()
+            };
+            let count = metric.get_gauge().get_value().max(0.0) as u64;
+            let (inbound, outbound) = connections.entry(label("network_id")).or_default();
+            match label("direction").as_str() {
+                "inbound" => *inbound += count,
+                "outbound" => *outbound += count,
+                _ => {},
+            }
+        }
+    }
+    connections
+        .into_iter()
+        .map(|(network_id, (inbound, outbound))| ConnectedPeers {
+            network_id,
+            inbound: inbound.into(),
+            outbound: outbound.into(),
+        })
+        .collect()
+}
+
+/// Seconds the latest ledger info is behind the current time
+fn sync_lag_secs(ledger_info: &LedgerInfo) -> anyhow::Result<u64> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("Failed to determine the current unix time")?;
+    Ok(now
+        .saturating_sub(Duration::from_micros(ledger_info.timestamp()))
+        .as_secs())
+}
+
 #[OpenApi]
 impl BasicApi {
     /// Show OpenAPI explorer
@@ -117,4 +207,101 @@ impl BasicApi {
             &accept_type,
         ))
     }
+
+    /// Check detailed node health
+    ///
+    /// Reports the sync lag, the pruning windows, the connected peers and the
+    /// build of the node. This endpoint returns a 200 if all the following
+    /// thresholds are met, and a 503 with the same report otherwise, so that
+    /// load balancers can eject the nodes that fall behind:
+    ///
+    /// - The latest ledger info is at most `max_sync_lag_secs` behind the
+    /// current time.
+    /// - The node has at least `min_connected_peers` connected peers across
+    /// all its networks.
+    ///
+    /// The thresholds default to the `api.health_check_max_sync_lag_secs` and
+    /// `api.health_check_min_connected_peers` configs of the node, and are not
+    /// checked if neither is set.
+    #[oai(
+        path = "/-/health",
+        method = "get",
+        operation_id = "node_health",
+        tag = "ApiTags::General"
+    )]
+    async fn node_health(
+        &self,
+        accept_type: AcceptType,
+        /// Threshold in seconds that the server can be behind to be considered healthy
+        max_sync_lag_secs: Query<Option<u64>>,
+        /// Minimum number of connected peers for the server to be considered healthy
+        min_connected_peers: Query<Option<u64>>,
+    ) -> NodeHealthResult<NodeHealth> {
+        let ledger_info = self
+            .context
+            .get_latest_ledger_info_wrapped()
+            .map_err(|err| {
+                NodeHealthError::internal_with_code_no_info(err, AptosErrorCode::InternalError)
+            })?;
+        let sync_lag_secs = sync_lag_secs(&ledger_info).map_err(|err| {
+            NodeHealthError::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+        })?;
+        let connected_peers = connected_peers();
+
+        let mut failures = vec![];
+        if let Some(max_sync_lag_secs) = max_sync_lag_secs
+            .0
+            .or_else(|| self.context.health_check_max_sync_lag_secs())
+        {
+            if sync_lag_secs > max_sync_lag_secs {
+                failures.push(format!(
+                    "The latest ledger info is {} seconds behind, more than {} seconds",
+                    sync_lag_secs, max_sync_lag_secs
+                ));
+            }
+        }
+        if let Some(min_connected_peers) = min_connected_peers
+            .0
+            .or_else(|| self.context.health_check_min_connected_peers())
+        {
+            let total: u64 = connected_peers
+                .iter()
+                .map(|peers| peers.inbound.0 + peers.outbound.0)
+                .sum();
+            if total < min_connected_peers {
+                failures.push(format!(
+                    "The node has {} connected peers, fewer than {}",
+                    total, min_connected_peers
+                ));
+            }
+        }
+
+        let db = &self.context.db;
+        let node_health = NodeHealth {
+            healthy: failures.is_empty(),
+            failures,
+            ledger_version: ledger_info.ledger_version,
+            ledger_timestamp: ledger_info.ledger_timestamp,
+            sync_lag_secs: sync_lag_secs.into(),
+            oldest_ledger_version: ledger_info.oldest_ledger_version,
+            oldest_block_height: ledger_info.oldest_block_height,
+            oldest_state_version: db.get_first_state_kv_version().ok().map(U64::from),
+            ledger_prune_window: db
+                .get_ledger_prune_window()
+                .ok()
+                .map(|window| U64::from(window as u64)),
+            state_prune_window: db
+                .get_state_kv_prune_window()
+                .ok()
+                .map(|window| U64::from(window as u64)),
+            connected_peers,
+            build_info: aptos_build_info::get_build_information(),
+        };
+        let status = if node_health.healthy {
+            NodeHealthResponseStatus::Ok
+        } else {
+            NodeHealthResponseStatus::ServiceUnavailable
+        };
+        NodeHealthResponse::try_from_rust_value((node_health, &ledger_info, status, &accept_type))
+    }
 }
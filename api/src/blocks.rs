@@ -5,10 +5,14 @@ use crate::{
     accept_type::AcceptType,
     context::Context,
     failpoint::fail_point_poem,
-    response::{BasicResponse, BasicResponseStatus, BasicResultWith404},
+    response::{
+        BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404, InternalError,
+    },
+    response_cache::CachedResponse,
     ApiTags,
 };
-use aptos_api_types::{BcsBlock, Block, LedgerInfo};
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{AptosErrorCode, BcsBlock, Block, LedgerInfo};
 use poem_openapi::{
     param::{Path, Query},
     OpenApi,
@@ -103,13 +107,31 @@ impl BlocksApi {
         with_transactions: bool,
     ) -> BasicResultWith404<Block> {
         let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        // The heights out of the ledger history aren't looked up, so that they return their errors
+        if block_height >= latest_ledger_info.oldest_block_height.0
+            && block_height <= latest_ledger_info.block_height.0
+        {
+            let cached = self.context.response_caches().blocks.get(&(
+                block_height,
+                with_transactions,
+                accept_type,
+            ));
+            if let Some(cached) = cached {
+                return Self::respond(cached, &latest_ledger_info);
+            }
+        }
         let bcs_block = self.context.get_block_by_height(
             block_height,
             &latest_ledger_info,
             with_transactions,
         )?;
 
-        self.render_bcs_block(&accept_type, latest_ledger_info, bcs_block)
+        self.render_bcs_block(
+            accept_type,
+            latest_ledger_info,
+            bcs_block,
+            with_transactions,
+        )
     }
 
     fn get_by_version(
@@ -122,18 +144,36 @@ impl BlocksApi {
         let bcs_block =
             self.context
                 .get_block_by_version(version, &latest_ledger_info, with_transactions)?;
+        // The block is already read, but its conversion can still be skipped
+        let cached = self.context.response_caches().blocks.get(&(
+            bcs_block.block_height,
+            with_transactions,
+            accept_type,
+        ));
+        if let Some(cached) = cached {
+            return Self::respond(cached, &latest_ledger_info);
+        }
 
-        self.render_bcs_block(&accept_type, latest_ledger_info, bcs_block)
+        self.render_bcs_block(
+            accept_type,
+            latest_ledger_info,
+            bcs_block,
+            with_transactions,
+        )
     }
 
-    /// Renders a [`BcsBlock`] into a [`Block`] if it's a JSON accept type
+    /// Renders a [`BcsBlock`] into a [`Block`] if it's a JSON accept type, and caches it, as the
+    /// blocks returned by the context are fully committed
     fn render_bcs_block(
         &self,
-        accept_type: &AcceptType,
+        accept_type: AcceptType,
         latest_ledger_info: LedgerInfo,
         bcs_block: BcsBlock,
+        with_transactions: bool,
     ) -> BasicResultWith404<Block> {
-        match accept_type {
+        let block_height = bcs_block.block_height;
+        let size = bcs::serialized_size(&bcs_block).unwrap_or(usize::MAX);
+        let rendered = match accept_type {
             AcceptType::Json => {
                 let transactions = if let Some(inner) = bcs_block.transactions {
                     Some(self.context.render_transactions_sequential(
@@ -144,19 +184,47 @@ impl BlocksApi {
                 } else {
                     None
                 };
-                let block = Block {
+                CachedResponse::Json(Block {
                     block_height: bcs_block.block_height.into(),
                     block_hash: bcs_block.block_hash.into(),
                     block_timestamp: bcs_block.block_timestamp.into(),
                     first_version: bcs_block.first_version.into(),
                     last_version: bcs_block.last_version.into(),
                     transactions,
-                };
-                BasicResponse::try_from_json((block, &latest_ledger_info, BasicResponseStatus::Ok))
+                })
+            },
+            AcceptType::Bcs => CachedResponse::Bcs(
+                bcs::to_bytes(&bcs_block)
+                    .context("Failed to serialize block")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &latest_ledger_info,
+                        )
+                    })?,
+            ),
+        };
+        self.context.response_caches().blocks.insert(
+            (block_height, with_transactions, accept_type),
+            rendered.clone(),
+            size,
+        );
+
+        Self::respond(rendered, &latest_ledger_info)
+    }
+
+    fn respond(
+        rendered: CachedResponse<Block>,
+        latest_ledger_info: &LedgerInfo,
+    ) -> BasicResultWith404<Block> {
+        match rendered {
+            CachedResponse::Json(block) => {
+                BasicResponse::try_from_json((block, latest_ledger_info, BasicResponseStatus::Ok))
             },
-            AcceptType::Bcs => BasicResponse::try_from_bcs((
-                bcs_block,
-                &latest_ledger_info,
+            CachedResponse::Bcs(bytes) => BasicResponse::try_from_encoded((
+                bytes,
+                latest_ledger_info,
                 BasicResponseStatus::Ok,
             )),
         }
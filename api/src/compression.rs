@@ -0,0 +1,199 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_api_types::mime_types::{BCS, JSON};
+use flate2::{write::GzEncoder, Compression};
+use hyper::Method;
+use poem::{
+    http::{header, HeaderValue},
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+use std::io::Write;
+
+/// The encodings the responses can be compressed with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Encoding {
+    Zstd,
+    Gzip,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Zstd => "zstd",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    fn encode(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Encoding::Zstd => zstd::bulk::compress(data, zstd::DEFAULT_COMPRESSION_LEVEL),
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            },
+        }
+    }
+}
+
+/// Picks the encoding of an `Accept-Encoding` header with the highest quality, preferring zstd
+/// when they are equal. An encoding without a quality has a quality of 1, and `*` stands for
+/// the encodings that aren't listed.
+fn negotiate(accept_encoding: &str) -> Option<Encoding> {
+    let (mut zstd, mut gzip, mut wildcard) = (None, None, None);
+    for item in accept_encoding.split(',') {
+        let mut params = item.split(';');
+        let name = params.next().unwrap_or_default().trim();
+        let quality = match params.find_map(|param| param.trim().strip_prefix("q=")) {
+            Some(quality) => match quality.trim().parse::<f32>() {
+                Ok(quality) => quality,
+                Err(_) => continue,
+            },
+            None => 1.0,
+        };
+        if name.eq_ignore_ascii_case("zstd") {
+            zstd = Some(quality);
+        } else if name.eq_ignore_ascii_case("gzip") || name.eq_ignore_ascii_case("x-gzip") {
+            gzip = Some(quality);
+        } else if name == "*" {
+            wildcard = Some(quality);
+        }
+    }
+
+    let zstd = zstd.or(wildcard).unwrap_or_default();
+    let gzip = gzip.or(wildcard).unwrap_or_default();
+    if zstd > 0.0 && zstd >= gzip {
+        Some(Encoding::Zstd)
+    } else if gzip > 0.0 {
+        Some(Encoding::Gzip)
+    } else {
+        None
+    }
+}
+
+/// Only the JSON and BCS payloads are compressed, which leaves out the event stream
+fn is_compressible(response: &Response) -> bool {
+    if response.headers().contains_key(header::CONTENT_ENCODING) {
+        return false;
+    }
+    match response.content_type() {
+        Some(content_type) => {
+            let essence = content_type.split(';').next().unwrap_or_default().trim();
+            essence == JSON || essence == BCS
+        },
+        None => false,
+    }
+}
+
+/// This middleware compresses the responses with zstd or gzip, as negotiated with the
+/// `Accept-Encoding` header of the request. The bodies smaller than `min_size` bytes are sent
+/// as they are, as compressing them doesn't save much.
+pub struct ResponseCompression {
+    min_size: usize,
+}
+
+impl ResponseCompression {
+    pub fn new(min_size: usize) -> Self {
+        Self { min_size }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ResponseCompression {
+    type Output = ResponseCompressionEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ResponseCompressionEndpoint {
+            inner: ep,
+            min_size: self.min_size,
+        }
+    }
+}
+
+/// Endpoint for ResponseCompression middleware.
+pub struct ResponseCompressionEndpoint<E> {
+    inner: E,
+    min_size: usize,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for ResponseCompressionEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let encoding = if req.method() == Method::HEAD {
+            None
+        } else {
+            req.headers()
+                .get(header::ACCEPT_ENCODING)
+                .and_then(|value| value.to_str().ok())
+                .and_then(negotiate)
+        };
+        let mut response = self.inner.call(req).await?.into_response();
+        let encoding = match encoding {
+            Some(encoding) if is_compressible(&response) => encoding,
+            _ => return Ok(response),
+        };
+
+        let body = response.take_body().into_bytes().await?;
+        response
+            .headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+        if body.len() < self.min_size {
+            response.set_body(body);
+            return Ok(response);
+        }
+        match encoding.encode(&body) {
+            Ok(compressed) => {
+                response.headers_mut().remove(header::CONTENT_LENGTH);
+                response.headers_mut().insert(
+                    header::CONTENT_ENCODING,
+                    HeaderValue::from_static(encoding.name()),
+                );
+                response.set_body(compressed);
+            },
+            // Compressing to memory doesn't fail, but the body can be sent as it is anyway
+            Err(_) => response.set_body(body),
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate("gzip, deflate, br, zstd"), Some(Encoding::Zstd));
+        assert_eq!(negotiate("gzip, deflate, br"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("zstd;q=0.5, gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("gzip;q=0.8, zstd;q=0.8"), Some(Encoding::Zstd));
+        assert_eq!(negotiate("zstd;q=0, gzip;q=0"), None);
+        assert_eq!(negotiate("*"), Some(Encoding::Zstd));
+        assert_eq!(negotiate("zstd;q=0, *;q=0.1"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("identity"), None);
+        assert_eq!(negotiate(""), None);
+    }
+
+    #[test]
+    fn test_encode() {
+        let data = br#"[{"type":"user_transaction"},{"type":"user_transaction"}]"#.repeat(100);
+        let compressed = Encoding::Zstd.encode(&data).unwrap();
+        assert!(compressed.len() < data.len());
+        assert_eq!(
+            zstd::stream::decode_all(compressed.as_slice()).unwrap(),
+            data
+        );
+
+        let compressed = Encoding::Gzip.encode(&data).unwrap();
+        assert!(compressed.len() < data.len());
+        let mut decompressed = vec![];
+        std::io::Read::read_to_end(
+            &mut flate2::read::GzDecoder::new(compressed.as_slice()),
+            &mut decompressed,
+        )
+        .unwrap();
+        assert_eq!(decompressed, data);
+    }
+}
@@ -4,16 +4,20 @@
 
 use crate::{
     accept_type::AcceptType,
+    page::Page,
+    resource_filter::ResourceFilter,
     response::{
         bcs_api_disabled, block_not_found_by_height, block_not_found_by_version,
-        block_pruned_by_height, json_api_disabled, version_not_found, version_pruned,
-        ForbiddenError, InternalError, NotFoundError, ServiceUnavailableError, StdApiError,
+        block_pruned_by_height, json_api_disabled, state_version_pruned, version_not_found,
+        version_pruned, BadRequestError, ForbiddenError, GoneError, InternalError, NotFoundError,
+        ServiceUnavailableError, StdApiError,
     },
+    response_cache::ResponseCaches,
 };
 use anyhow::{bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_api_types::{
-    AptosErrorCode, AsConverter, BcsBlock, GasEstimation, LedgerInfo, ResourceGroup,
-    TransactionOnChainData,
+    AptosErrorCode, AsConverter, BcsBlock, EventOrder, GasEstimation, GasPricePercentile,
+    LedgerInfo, ResourceGroup, TransactionOnChainData,
 };
 use aptos_config::config::{NodeConfig, RoleType};
 use aptos_crypto::HashValue;
@@ -40,6 +44,7 @@ use aptos_types::{
         state_key::{StateKey, StateKeyInner},
         state_key_prefix::StateKeyPrefix,
         state_value::StateValue,
+        table::TableHandle,
     },
     transaction::{SignedTransaction, TransactionWithProof, Version},
 };
@@ -67,6 +72,7 @@ pub struct Context {
     gas_schedule_cache: Arc<RwLock<GasScheduleCache>>,
     gas_estimation_cache: Arc<RwLock<GasEstimationCache>>,
     gas_limit_cache: Arc<RwLock<GasLimitCache>>,
+    response_caches: Arc<ResponseCaches>,
 }
 
 impl std::fmt::Debug for Context {
@@ -86,7 +92,6 @@ impl Context {
             chain_id,
             db,
             mp_sender,
-            node_config,
             gas_schedule_cache: Arc::new(RwLock::new(GasScheduleCache {
                 last_updated_epoch: None,
                 gas_schedule_params: None,
@@ -96,11 +101,14 @@ impl Context {
                 last_updated_time: None,
                 estimation: None,
                 min_inclusion_prices: BTreeMap::new(),
+                recent_min_inclusion_prices: vec![],
             })),
             gas_limit_cache: Arc::new(RwLock::new(GasLimitCache {
                 last_updated_epoch: None,
                 block_gas_limit: None,
             })),
+            response_caches: Arc::new(ResponseCaches::new(&node_config.api)),
+            node_config,
         }
     }
 
@@ -140,6 +148,7 @@ impl Context {
     ) -> Result<(LedgerInfo, u64, DbStateView), E> {
         let (latest_ledger_info, requested_ledger_version) =
             self.get_latest_ledger_info_and_verify_lookup_version(requested_ledger_version)?;
+        self.ensure_state_not_pruned(requested_ledger_version, &latest_ledger_info)?;
 
         let state_view = self
             .state_view_at_version(requested_ledger_version)
@@ -150,6 +159,35 @@ impl Context {
         Ok((latest_ledger_info, requested_ledger_version, state_view))
     }
 
+    /// The state values are pruned separately from the ledger, so the state at a version can be
+    /// gone even if the version is within the ledger history.
+    fn ensure_state_not_pruned<E: StdApiError>(
+        &self,
+        version: Version,
+        ledger_info: &LedgerInfo,
+    ) -> Result<(), E> {
+        let first_state_kv_version = self
+            .db
+            .get_first_state_kv_version()
+            .context("Failed to retrieve oldest state version in DB")
+            .map_err(|e| E::internal_with_code(e, AptosErrorCode::InternalError, ledger_info))?;
+        if version < first_state_kv_version {
+            return Err(state_version_pruned(
+                version,
+                first_state_kv_version,
+                self.db.get_state_kv_prune_window().ok(),
+                ledger_info,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builds the error for a version whose ledger data has been pruned, reporting the prune
+    /// window.
+    pub fn version_pruned<E: GoneError>(&self, version: Version, ledger_info: &LedgerInfo) -> E {
+        version_pruned(version, self.db.get_ledger_prune_window().ok(), ledger_info)
+    }
+
     pub fn state_view_at_version(&self, version: Version) -> Result<DbStateView> {
         self.db.state_view_at_version(Some(version))
     }
@@ -170,10 +208,50 @@ impl Context {
         self.node_config.api.failpoints_enabled
     }
 
+    pub fn event_stream_enabled(&self) -> bool {
+        self.node_config.api.event_stream_enabled
+    }
+
+    pub fn compression_enabled(&self) -> bool {
+        self.node_config.api.compression_enabled
+    }
+
+    pub fn compression_min_size(&self) -> usize {
+        self.node_config.api.compression_min_size
+    }
+
     pub fn max_submit_transaction_batch_size(&self) -> usize {
         self.node_config.api.max_submit_transaction_batch_size
     }
 
+    pub fn max_table_items_page_size(&self) -> u16 {
+        self.node_config.api.max_table_items_page_size
+    }
+
+    pub fn max_view_function_batch_size(&self) -> usize {
+        self.node_config.api.max_view_function_batch_size
+    }
+
+    pub fn max_transactions_by_hashes_batch_size(&self) -> usize {
+        self.node_config.api.max_transactions_by_hashes_batch_size
+    }
+
+    pub fn max_submit_and_wait_timeout_secs(&self) -> u64 {
+        self.node_config.api.max_submit_and_wait_timeout_secs
+    }
+
+    pub fn response_caches(&self) -> &ResponseCaches {
+        &self.response_caches
+    }
+
+    pub fn health_check_max_sync_lag_secs(&self) -> Option<u64> {
+        self.node_config.api.health_check_max_sync_lag_secs
+    }
+
+    pub fn health_check_min_connected_peers(&self) -> Option<u64> {
+        self.node_config.api.health_check_min_connected_peers
+    }
+
     pub async fn submit_transaction(&self, txn: SignedTransaction) -> Result<SubmissionStatus> {
         let (req_sender, callback) = oneshot::channel();
         self.mp_sender
@@ -260,10 +338,7 @@ impl Context {
                 &latest_ledger_info,
             ));
         } else if requested_ledger_version < latest_ledger_info.oldest_ledger_version.0 {
-            return Err(version_pruned(
-                requested_ledger_version,
-                &latest_ledger_info,
-            ));
+            return Err(self.version_pruned(requested_ledger_version, &latest_ledger_info));
         }
 
         Ok((latest_ledger_info, requested_ledger_version))
@@ -312,12 +387,15 @@ impl Context {
         Ok(kvs)
     }
 
+    /// Only the resources matching the filter are counted in the limit. The resource groups are
+    /// counted as one, and only their resources matching the filter are returned.
     pub fn get_resources_by_pagination(
         &self,
         address: AccountAddress,
         prev_state_key: Option<&StateKey>,
         version: u64,
         limit: u64,
+        filter: Option<&ResourceFilter>,
     ) -> Result<(Vec<(StructTag, Vec<u8>)>, Option<StateKey>)> {
         let account_iter = self.db.get_prefixed_state_value_iterator(
             &StateKeyPrefix::from(address),
@@ -335,7 +413,11 @@ impl Context {
                     StateKeyInner::AccessPath(AccessPath { address: _, path }) => {
                         match Path::try_from(path.as_slice()) {
                             Ok(Path::Resource(struct_tag)) => {
-                                Some(Ok((struct_tag, v.bytes().to_vec())))
+                                if filter.map_or(true, |filter| filter.matches(&struct_tag)) {
+                                    Some(Ok((struct_tag, v.bytes().to_vec())))
+                                } else {
+                                    None
+                                }
                             }
                             // TODO: Consider expanding to Path::Resource
                             Ok(Path::ResourceGroup(struct_tag)) => {
@@ -397,6 +479,7 @@ impl Context {
             .collect::<Result<Vec<Vec<(StructTag, Vec<u8>)>>>>()?
             .into_iter()
             .flatten()
+            .filter(|(struct_tag, _)| filter.map_or(true, |filter| filter.matches(struct_tag)))
             .collect();
 
         let next_key = if let Some((struct_tag, _v)) = resource_iter.next().transpose()? {
@@ -453,6 +536,43 @@ impl Context {
         Ok((kvs, next_key))
     }
 
+    pub fn get_table_items_by_pagination(
+        &self,
+        handle: TableHandle,
+        prev_state_key: Option<&StateKey>,
+        version: u64,
+        limit: u64,
+    ) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, Option<StateKey>)> {
+        let table_iter = self.db.get_prefixed_state_value_iterator(
+            &StateKeyPrefix::from(handle),
+            prev_state_key,
+            version,
+        )?;
+        let mut item_iter = table_iter
+            .map(|res| {
+                let (k, v) = res?;
+                match k.inner() {
+                    StateKeyInner::TableItem { handle: _, key } => {
+                        Ok((key.clone(), v.bytes().to_vec()))
+                    },
+                    _ => {
+                        error!("storage prefix scan return inconsistent key ({:?}) with expected key prefix ({:?}).", k, StateKeyPrefix::from(handle));
+                        Err(format_err!("storage prefix scan return inconsistent key ({:?})", k))
+                    },
+                }
+            })
+            .take(limit as usize + 1);
+        let kvs = item_iter
+            .by_ref()
+            .take(limit as usize)
+            .collect::<Result<_>>()?;
+        let next_key = item_iter
+            .next()
+            .transpose()?
+            .map(|(key, _v)| StateKey::table_item(handle, key));
+        Ok((kvs, next_key))
+    }
+
     // This function should be deprecated. DO NOT USE it.
     // Instead, call either `get_modules_by_pagination` or `get_modules_by_pagination`.
     pub fn get_account_state<E: InternalError>(
@@ -517,7 +637,7 @@ impl Context {
         with_transactions: bool,
     ) -> Result<BcsBlock, E> {
         if version < latest_ledger_info.oldest_ledger_version.0 {
-            return Err(version_pruned(version, latest_ledger_info));
+            return Err(self.version_pruned(version, latest_ledger_info));
         } else if version > latest_ledger_info.version() {
             return Err(version_not_found(version, latest_ledger_info));
         }
@@ -653,6 +773,28 @@ impl Context {
         Ok(txns)
     }
 
+    /// Reads the committed transactions of a page, along with the version of the first one. The
+    /// API versions render them differently, but read them the same way.
+    pub(crate) fn get_transactions_page<E: StdApiError + BadRequestError>(
+        &self,
+        page: &Page,
+        ledger_info: &LedgerInfo,
+    ) -> Result<(u64, Vec<TransactionOnChainData>), E> {
+        let ledger_version = ledger_info.version();
+        let limit = page.limit(ledger_info)?;
+        let start_version = page.compute_start(limit, ledger_version, ledger_info)?;
+        if start_version < ledger_info.oldest_version() {
+            return Err(self.version_pruned(start_version, ledger_info));
+        }
+        let data = self
+            .get_transactions(start_version, limit, ledger_version)
+            .context("Failed to read raw transactions from storage")
+            .map_err(|err| {
+                E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+            })?;
+        Ok((start_version, data))
+    }
+
     pub fn get_transactions(
         &self,
         start_version: u64,
@@ -759,6 +901,27 @@ impl Context {
             .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))
     }
 
+    /// Returns the transactions of an account from the account transaction index, see
+    /// `DbReader::get_account_transaction_versions`.
+    pub fn get_account_transaction_history<E: InternalError>(
+        &self,
+        address: AccountAddress,
+        start_version: u64,
+        limit: u16,
+        ledger_info: &LedgerInfo,
+    ) -> Result<Vec<TransactionOnChainData>, E> {
+        let ledger_version = ledger_info.version();
+        self.db
+            .get_account_transaction_versions(address, start_version, limit as u64, ledger_version)
+            .context("Failed to retrieve account transaction versions")
+            .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))?
+            .into_iter()
+            .map(|version| self.get_transaction_by_version(version, ledger_version))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to retrieve account transactions")
+            .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))
+    }
+
     pub fn get_transaction_by_hash(
         &self,
         hash: HashValue,
@@ -814,35 +977,74 @@ impl Context {
             .map(|h| (txn, h, txn_output).into())
     }
 
+    /// Reads the events of an event stream with sequence numbers from `start` to `end`, both
+    /// inclusive, in the given order. In ascending order without a `start`, the latest events up
+    /// to `end` are returned.
     pub fn get_events(
         &self,
         event_key: &EventKey,
         start: Option<u64>,
+        end: Option<u64>,
+        order: EventOrder,
         limit: u16,
         ledger_version: u64,
     ) -> Result<Vec<EventWithVersion>> {
-        if let Some(start) = start {
-            self.db.get_events(
+        let mut events = match (order, start) {
+            (EventOrder::Ascending, Some(start)) => self.db.get_events(
                 event_key,
                 start,
                 Order::Ascending,
                 limit as u64,
                 ledger_version,
-            )
-        } else {
-            self.db
-                .get_events(
-                    event_key,
-                    u64::MAX,
-                    Order::Descending,
-                    limit as u64,
-                    ledger_version,
-                )
-                .map(|mut result| {
-                    result.reverse();
-                    result
-                })
+            )?,
+            (EventOrder::Ascending, None) => {
+                let mut events =
+                    self.get_events_descending(event_key, end, limit, ledger_version)?;
+                events.reverse();
+                events
+            },
+            (EventOrder::Descending, _) => {
+                self.get_events_descending(event_key, end, limit, ledger_version)?
+            },
+        };
+        events.retain(|event| match event.event.v1() {
+            Ok(event) => {
+                start.map_or(true, |start| event.sequence_number() >= start)
+                    && end.map_or(true, |end| event.sequence_number() <= end)
+            },
+            Err(_) => true,
+        });
+        Ok(events)
+    }
+
+    /// Reads the events of an event stream from `end`, or from the latest event, backwards
+    fn get_events_descending(
+        &self,
+        event_key: &EventKey,
+        end: Option<u64>,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<EventWithVersion>> {
+        let end = end.unwrap_or(u64::MAX);
+        let events = self.db.get_events(
+            event_key,
+            end,
+            Order::Descending,
+            limit as u64,
+            ledger_version,
+        )?;
+        if events.is_empty() && end != u64::MAX {
+            // The storage returns no events when reading backwards from past the latest event,
+            // in which case all the events are before the end.
+            return self.db.get_events(
+                event_key,
+                u64::MAX,
+                Order::Descending,
+                limit as u64,
+                ledger_version,
+            );
         }
+        Ok(events)
     }
 
     fn next_bucket(&self, gas_unit_price: u64) -> u64 {
@@ -863,6 +1065,7 @@ impl Context {
             deprioritized_gas_estimate: Some(min_gas_unit_price),
             gas_estimate: min_gas_unit_price,
             prioritized_gas_estimate: Some(self.next_bucket(min_gas_unit_price)),
+            percentiles: None,
         }
     }
 
@@ -872,12 +1075,12 @@ impl Context {
     {
         if let Some(epoch) = cache.last_updated_epoch {
             if let Some(time) = cache.last_updated_time {
-                if let Some(estimation) = cache.estimation {
+                if let Some(estimation) = &cache.estimation {
                     if epoch == current_epoch
                         && (time.elapsed().as_millis() as u64)
                             < self.node_config.api.gas_estimation.cache_expiration_ms
                     {
-                        return Some(estimation);
+                        return Some(estimation.clone());
                     }
                 }
             }
@@ -941,6 +1144,7 @@ impl Context {
                 deprioritized_gas_estimate: Some(static_override.low),
                 gas_estimate: static_override.market,
                 prioritized_gas_estimate: Some(static_override.aggressive),
+                percentiles: None,
             });
         }
 
@@ -1003,7 +1207,8 @@ impl Context {
         }
         if blocks.is_empty() && !cached_blocks_hit {
             let estimation = self.default_gas_estimation(min_gas_unit_price);
-            self.update_cached_gas_estimation(&mut cache, epoch, estimation);
+            cache.recent_min_inclusion_prices.clear();
+            self.update_cached_gas_estimation(&mut cache, epoch, estimation.clone());
             return Ok(estimation);
         }
         let blocks_len = blocks.len();
@@ -1058,6 +1263,8 @@ impl Context {
             }
         }
 
+        cache.recent_min_inclusion_prices = min_inclusion_prices.clone();
+
         // 3. Get values
         // (1) low
         let low_price = match min_inclusion_prices
@@ -1112,6 +1319,7 @@ impl Context {
             deprioritized_gas_estimate: Some(low_price),
             gas_estimate: market_price,
             prioritized_gas_estimate: Some(aggressive_price),
+            percentiles: None,
         };
         // 4. Update cache
         // GC old entries
@@ -1120,7 +1328,46 @@ impl Context {
                 cache.min_inclusion_prices.pop_first();
             }
         }
-        self.update_cached_gas_estimation(&mut cache, epoch, estimation);
+        self.update_cached_gas_estimation(&mut cache, epoch, estimation.clone());
+        Ok(estimation)
+    }
+
+    /// Estimates the gas unit prices at the percentiles of the min inclusion prices of the last
+    /// `block_history` blocks of the epoch. The blocks are the ones the gas estimation is computed
+    /// from, so there are at most `aggressive_block_history` of them. Without blocks, e.g. when
+    /// the estimation is disabled, the prices are the current estimate.
+    pub fn estimate_gas_price_percentiles<E: InternalError>(
+        &self,
+        ledger_info: &LedgerInfo,
+        block_history: usize,
+        percentiles: &[u8],
+    ) -> Result<GasEstimation, E> {
+        let mut estimation = self.estimate_gas_price(ledger_info)?;
+        let mut prices: Vec<_> = self
+            .gas_estimation_cache
+            .read()
+            .unwrap()
+            .recent_min_inclusion_prices
+            .iter()
+            .take(block_history)
+            .cloned()
+            .collect();
+        prices.sort();
+        estimation.percentiles = Some(
+            percentiles
+                .iter()
+                .map(|percentile| GasPricePercentile {
+                    percentile: *percentile,
+                    gas_unit_price: prices
+                        .get(std::cmp::min(
+                            prices.len() * *percentile as usize / 100,
+                            prices.len().saturating_sub(1),
+                        ))
+                        .cloned()
+                        .unwrap_or(estimation.gas_estimate),
+                })
+                .collect(),
+        );
         Ok(estimation)
     }
 
@@ -1291,6 +1538,8 @@ pub struct GasEstimationCache {
     estimation: Option<GasEstimation>,
     /// (epoch, lookup_version) -> min_inclusion_price
     min_inclusion_prices: BTreeMap<(u64, u64), u64>,
+    /// Min inclusion prices of the blocks of the last estimation, latest first
+    recent_min_inclusion_prices: Vec<u64>,
 }
 
 pub struct GasLimitCache {
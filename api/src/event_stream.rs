@@ -0,0 +1,314 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pushes the events of the newly committed transactions to the clients as server-sent events,
+//! so that they don't have to poll the event endpoints.
+//!
+//! Every event is sent with the id `<transaction version>:<event index>`. A client resumes a
+//! stream where it left off by sending the id of the last event it received in the
+//! `Last-Event-ID` header, which browsers do on their own when they reconnect.
+
+use crate::context::Context;
+use anyhow::{format_err, Context as AnyhowContext, Result};
+use aptos_api_types::{Address, AsConverter, VersionedEvent};
+use aptos_logger::warn;
+use aptos_types::contract_event::{ContractEvent, EventWithVersion};
+use aptos_vm::data_cache::AsMoveResolver;
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::TypeTag,
+    parser::parse_type_tag,
+};
+use poem::{
+    handler,
+    http::{HeaderMap, StatusCode},
+    web::{
+        sse::{Event, SSE},
+        Data, Query,
+    },
+};
+use serde::Deserialize;
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+/// How often the stream checks for newly committed transactions
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// How often a comment is sent to keep the idle connections open
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+const LAST_EVENT_ID: &str = "Last-Event-ID";
+
+#[derive(Deserialize)]
+pub struct EventStreamQuery {
+    /// Only the events of the event handles of this account
+    account: Option<Address>,
+    /// Only the events of this type, e.g. `0x1::coin::DepositEvent`
+    event_type: Option<String>,
+    /// Only the events whose type is defined in this module, e.g. `0x1::coin`
+    module: Option<String>,
+    /// The first transaction version to send the events of, if the stream isn't resumed.
+    /// Defaults to the first version committed after the stream is opened.
+    start_version: Option<u64>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct EventFilter {
+    account: Option<AccountAddress>,
+    event_type: Option<TypeTag>,
+    module: Option<(AccountAddress, Identifier)>,
+}
+
+impl EventFilter {
+    fn new(query: &EventStreamQuery) -> Result<Self> {
+        let event_type = query
+            .event_type
+            .as_deref()
+            .map(parse_type_tag)
+            .transpose()
+            .context("Invalid event_type")?;
+        let module = query
+            .module
+            .as_deref()
+            .map(|module| {
+                let (address, name) = module
+                    .split_once("::")
+                    .ok_or_else(|| format_err!("Expected <address>::<module>"))?;
+                Ok::<_, anyhow::Error>((
+                    AccountAddress::from_hex_literal(address)?,
+                    Identifier::new(name)?,
+                ))
+            })
+            .transpose()
+            .context("Invalid module")?;
+        Ok(Self {
+            account: query.account.map(Into::into),
+            event_type,
+            module,
+        })
+    }
+
+    /// The module events don't belong to an account, so they never match an account
+    fn matches(&self, event: &ContractEvent) -> bool {
+        if let Some(account) = &self.account {
+            if event.event_key().map(|key| key.get_creator_address()) != Some(*account) {
+                return false;
+            }
+        }
+        if let Some(event_type) = &self.event_type {
+            if event.type_tag() != event_type {
+                return false;
+            }
+        }
+        if let Some((address, name)) = &self.module {
+            match event.type_tag() {
+                TypeTag::Struct(struct_tag)
+                    if struct_tag.address == *address && struct_tag.module == *name => {},
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Position of the last event sent on a stream
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct EventCursor {
+    version: u64,
+    event_index: usize,
+}
+
+impl EventCursor {
+    fn is_after(&self, version: u64, event_index: usize) -> bool {
+        (version, event_index) <= (self.version, self.event_index)
+    }
+}
+
+impl FromStr for EventCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (version, event_index) = s
+            .split_once(':')
+            .ok_or_else(|| format_err!("Expected <version>:<event index>"))?;
+        Ok(Self {
+            version: version.parse()?,
+            event_index: event_index.parse()?,
+        })
+    }
+}
+
+impl std::fmt::Display for EventCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.version, self.event_index)
+    }
+}
+
+fn bad_request(error: anyhow::Error) -> poem::Error {
+    poem::Error::from_string(format!("{:#}", error), StatusCode::BAD_REQUEST)
+}
+
+/// Reads the events matching the filter of the transactions from `start_version`, up to a page
+/// of transactions. Returns them with their cursors, and the next version to read.
+fn read_events(
+    context: &Context,
+    filter: &EventFilter,
+    start_version: u64,
+    cursor: Option<EventCursor>,
+) -> Result<(Vec<(EventCursor, VersionedEvent)>, u64)> {
+    let ledger_info = context.get_latest_ledger_info_wrapped()?;
+    let ledger_version = ledger_info.version();
+    if start_version > ledger_version {
+        return Ok((vec![], start_version));
+    }
+    let limit = std::cmp::min(
+        ledger_version - start_version + 1,
+        context.max_transactions_page_size() as u64,
+    ) as u16;
+    let transactions = context.get_transactions(start_version, limit, ledger_version)?;
+
+    let state_view = context.latest_state_view()?;
+    let resolver = state_view.as_move_resolver();
+    let converter = resolver.as_converter(context.db.clone());
+    let mut events = vec![];
+    for transaction in &transactions {
+        for (event_index, event) in transaction.events.iter().enumerate() {
+            if cursor.map_or(false, |cursor| {
+                cursor.is_after(transaction.version, event_index)
+            }) || !filter.matches(event)
+            {
+                continue;
+            }
+            let event = EventWithVersion::new(transaction.version, event.clone());
+            let event = converter
+                .try_into_versioned_events(&[event])?
+                .pop()
+                .expect("One event was converted");
+            events.push((
+                EventCursor {
+                    version: transaction.version,
+                    event_index,
+                },
+                event,
+            ));
+        }
+    }
+    Ok((events, start_version + transactions.len() as u64))
+}
+
+/// Streams the events of the committed transactions matching the filter
+#[handler]
+pub fn stream_events_poem(
+    context: Data<&Arc<Context>>,
+    Query(query): Query<EventStreamQuery>,
+    headers: &HeaderMap,
+) -> poem::Result<SSE> {
+    if !context.event_stream_enabled() {
+        return Err(poem::Error::from(format_err!(
+            "The event stream is not enabled at a config level"
+        )));
+    }
+    let filter = EventFilter::new(&query).map_err(bad_request)?;
+    let cursor = headers
+        .get(LAST_EVENT_ID)
+        .map(|last_event_id| {
+            last_event_id
+                .to_str()
+                .map_err(anyhow::Error::from)
+                .and_then(EventCursor::from_str)
+                .context("Invalid Last-Event-ID")
+        })
+        .transpose()
+        .map_err(bad_request)?;
+    let mut next_version = match (cursor, query.start_version) {
+        (Some(cursor), _) => cursor.version,
+        (None, Some(start_version)) => start_version,
+        (None, None) => context.get_latest_ledger_info_wrapped()?.version() + 1,
+    };
+
+    let context = context.0.clone();
+    let stream = async_stream::stream! {
+        let filter = Arc::new(filter);
+        loop {
+            let (context, filter) = (context.clone(), filter.clone());
+            let result = tokio::task::spawn_blocking(move || {
+                read_events(&context, &filter, next_version, cursor)
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result);
+            match result {
+                Ok((events, version)) => {
+                    for (cursor, event) in events {
+                        match serde_json::to_string(&event) {
+                            Ok(data) => yield Event::message(data).id(cursor.to_string()),
+                            Err(e) => warn!("Failed to serialize event {}: {:?}", cursor, e),
+                        }
+                    }
+                    if version == next_version {
+                        tokio::time::sleep(POLL_INTERVAL).await;
+                    }
+                    next_version = version;
+                },
+                Err(e) => {
+                    // The client resumes from its last event when it reconnects
+                    warn!("Failed to read the events from version {}: {:?}", next_version, e);
+                    yield Event::message(format!("{:#}", e)).event_type("error");
+                    break;
+                },
+            }
+        }
+    };
+    Ok(SSE::new(stream).keep_alive(KEEP_ALIVE_INTERVAL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::event::EventKey;
+
+    #[test]
+    fn test_event_cursor() {
+        let cursor = EventCursor::from_str("12:3").unwrap();
+        assert_eq!(cursor, EventCursor {
+            version: 12,
+            event_index: 3
+        });
+        assert_eq!(cursor.to_string(), "12:3");
+        assert!(cursor.is_after(12, 3));
+        assert!(cursor.is_after(11, 7));
+        assert!(!cursor.is_after(12, 4));
+        assert!(EventCursor::from_str("12").is_err());
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let address = AccountAddress::from_hex_literal("0xa").unwrap();
+        let event = ContractEvent::new_v1(
+            EventKey::new(2, address),
+            0,
+            parse_type_tag("0x1::coin::DepositEvent").unwrap(),
+            vec![],
+        );
+        let filter = |account: Option<&str>, event_type: Option<&str>, module: Option<&str>| {
+            EventFilter::new(&EventStreamQuery {
+                account: account.map(|account| Address::from_str(account).unwrap()),
+                event_type: event_type.map(str::to_string),
+                module: module.map(str::to_string),
+                start_version: None,
+            })
+            .unwrap()
+        };
+
+        assert!(filter(None, None, None).matches(&event));
+        assert!(filter(Some("0xa"), None, None).matches(&event));
+        assert!(!filter(Some("0xb"), None, None).matches(&event));
+        assert!(filter(None, Some("0x1::coin::DepositEvent"), None).matches(&event));
+        assert!(!filter(None, Some("0x1::coin::WithdrawEvent"), None).matches(&event));
+        assert!(filter(Some("0xa"), None, Some("0x1::coin")).matches(&event));
+        assert!(!filter(None, None, Some("0x1::account")).matches(&event));
+        assert!(EventFilter::new(&EventStreamQuery {
+            account: None,
+            event_type: None,
+            module: Some("0x1".to_string()),
+            start_version: None,
+        })
+        .is_err());
+    }
+}
@@ -16,8 +16,8 @@ use crate::{
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    verify_field_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
-    MoveStructTag, VerifyInputWithRecursion, VersionedEvent, U64,
+    verify_field_identifier, Address, AptosErrorCode, AsConverter, EventOrder, IdentifierWrapper,
+    LedgerInfo, MoveStructTag, PageCursor, VerifyInputWithRecursion, VersionedEvent, U64,
 };
 use aptos_types::event::EventKey;
 use aptos_vm::data_cache::AsMoveResolver;
@@ -60,19 +60,33 @@ impl EventsApi {
         ///
         /// If unspecified, by default will retrieve the most recent events
         start: Query<Option<U64>>,
+        /// Last sequence number of events, inclusive.
+        ///
+        /// In descending order, the events are retrieved from this one backwards
+        end: Query<Option<U64>>,
+        /// Cursor returned in the X-Aptos-Cursor header of the previous page,
+        /// to get the next page. Can't be given with `start`, or with `end` in
+        /// descending order.
+        cursor: Query<Option<PageCursor>>,
         /// Max number of events to retrieve.
         ///
         /// If unspecified, defaults to default page size
         limit: Query<Option<u16>>,
+        /// Order of the events, `ascending` by default. In `descending` order,
+        /// the most recent events are retrieved first.
+        order: Query<Option<EventOrder>>,
     ) -> BasicResultWith404<Vec<VersionedEvent>> {
         fail_point_poem("endpoint_get_events_by_event_key")?;
         self.context
             .check_api_output_enabled("Get events by event key", &accept_type)?;
-        let page = Page::new(
+        let order = order.0.unwrap_or(EventOrder::Ascending);
+        let (page, end) = self.page(
             start.0.map(|v| v.0),
+            end.0.map(|v| v.0),
+            cursor.0,
             limit.0,
-            self.context.max_events_page_size(),
-        );
+            order,
+        )?;
 
         // Ensure that account exists
         let account = Account::new(self.context.clone(), address.0, None, None, None)?;
@@ -81,6 +95,8 @@ impl EventsApi {
             account.latest_ledger_info,
             accept_type,
             page,
+            end,
+            order,
             EventKey::new(creation_number.0 .0, address.0.into()),
         )
     }
@@ -112,10 +128,21 @@ impl EventsApi {
         ///
         /// If unspecified, by default will retrieve the most recent
         start: Query<Option<U64>>,
+        /// Last sequence number of events, inclusive.
+        ///
+        /// In descending order, the events are retrieved from this one backwards
+        end: Query<Option<U64>>,
+        /// Cursor returned in the X-Aptos-Cursor header of the previous page,
+        /// to get the next page. Can't be given with `start`, or with `end` in
+        /// descending order.
+        cursor: Query<Option<PageCursor>>,
         /// Max number of events to retrieve.
         ///
         /// If unspecified, defaults to default page size
         limit: Query<Option<u16>>,
+        /// Order of the events, `ascending` by default. In `descending` order,
+        /// the most recent events are retrieved first.
+        order: Query<Option<EventOrder>>,
     ) -> BasicResultWith404<Vec<VersionedEvent>> {
         event_handle
             .0
@@ -132,24 +159,89 @@ impl EventsApi {
         fail_point_poem("endpoint_get_events_by_event_handle")?;
         self.context
             .check_api_output_enabled("Get events by event handle", &accept_type)?;
-        let page = Page::new(
+        let order = order.0.unwrap_or(EventOrder::Ascending);
+        let (page, end) = self.page(
             start.0.map(|v| v.0),
+            end.0.map(|v| v.0),
+            cursor.0,
             limit.0,
-            self.context.max_events_page_size(),
-        );
+            order,
+        )?;
         let account = Account::new(self.context.clone(), address.0, None, None, None)?;
         let key = account.find_event_key(event_handle.0, field_name.0.into())?;
-        self.list(account.latest_ledger_info, accept_type, page, key)
+        self.list(
+            account.latest_ledger_info,
+            accept_type,
+            page,
+            end,
+            order,
+            key,
+        )
     }
 }
 
 impl EventsApi {
+    /// Builds the page of events and its end. The cursor of the previous page continues the
+    /// range in the order of the events, so it gives the start in ascending order and the end
+    /// in descending order.
+    fn page(
+        &self,
+        start: Option<u64>,
+        end: Option<u64>,
+        cursor: Option<PageCursor>,
+        limit: Option<u16>,
+        order: EventOrder,
+    ) -> Result<(Page, Option<u64>), BasicErrorWith404> {
+        let max_page_size = self.context.max_events_page_size();
+        let (page, end) = match order {
+            EventOrder::Ascending => (
+                Page::new_with_cursor::<BasicErrorWith404>(
+                    start,
+                    cursor,
+                    PageCursor::sequence_number,
+                    limit,
+                    max_page_size,
+                )?,
+                end,
+            ),
+            EventOrder::Descending => {
+                let end = match (end, cursor) {
+                    (Some(_), Some(_)) => {
+                        return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                            "Only one of end and cursor can be given in descending order",
+                            AptosErrorCode::InvalidInput,
+                        ));
+                    },
+                    (None, Some(cursor)) => Some(cursor.sequence_number().ok_or_else(|| {
+                        BasicErrorWith404::bad_request_with_code_no_info(
+                            "Given cursor is not a cursor of this endpoint",
+                            AptosErrorCode::InvalidInput,
+                        )
+                    })?),
+                    (end, None) => end,
+                };
+                (Page::new(start, limit, max_page_size), end)
+            },
+        };
+        if let (Some(start), Some(end)) = (page.start_option(), end) {
+            if start > end {
+                return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                    format!("Given start ({}) is higher than the end ({})", start, end),
+                    AptosErrorCode::InvalidInput,
+                ));
+            }
+        }
+        Ok((page, end))
+    }
+
     /// List events from an [`EventKey`]
     fn list(
         &self,
         latest_ledger_info: LedgerInfo,
         accept_type: AcceptType,
         page: Page,
+        end: Option<u64>,
+        order: EventOrder,
         event_key: EventKey,
     ) -> BasicResultWith404<Vec<VersionedEvent>> {
         let ledger_version = latest_ledger_info.version();
@@ -158,6 +250,8 @@ impl EventsApi {
             .get_events(
                 &event_key,
                 page.start_option(),
+                end,
+                order,
                 page.limit(&latest_ledger_info)?,
                 ledger_version,
             )
@@ -169,6 +263,30 @@ impl EventsApi {
                     &latest_ledger_info,
                 )
             })?;
+        let last_sequence_number = events
+            .last()
+            .and_then(|event| event.event.v1().ok())
+            .map(|event| event.sequence_number());
+        let next_sequence_number = match order {
+            EventOrder::Ascending => Some(
+                last_sequence_number
+                    .map(|sequence_number| sequence_number + 1)
+                    .or_else(|| page.start_option())
+                    .unwrap_or(0),
+            ),
+            // There is no next page once the first event of the stream is returned
+            EventOrder::Descending => {
+                last_sequence_number.and_then(|sequence_number| sequence_number.checked_sub(1))
+            },
+        };
+        // Nor once the page reaches the end of the range
+        let next_cursor = next_sequence_number
+            .filter(|sequence_number| {
+                page.start_option()
+                    .map_or(true, |start| *sequence_number >= start)
+                    && end.map_or(true, |end| *sequence_number <= end)
+            })
+            .map(PageCursor::SequenceNumber);
 
         match accept_type {
             AcceptType::Json => {
@@ -193,5 +311,6 @@ impl EventsApi {
                 BasicResponse::try_from_bcs((events, &latest_ledger_info, BasicResponseStatus::Ok))
             },
         }
+        .map(|response| response.with_page_cursor(next_cursor))
     }
 }
@@ -6,26 +6,36 @@ use poem_openapi::Tags;
 
 mod accept_type;
 mod accounts;
+mod api_keys;
 mod basic;
 mod bcs_payload;
 mod blocks;
 mod check_size;
+mod compression;
 pub mod context;
 mod error_converter;
+mod event_stream;
 mod events;
 mod failpoint;
 mod index;
 mod log;
 pub mod metrics;
 mod page;
+mod proofs;
+mod resource_filter;
 mod response;
+mod response_cache;
 mod runtime;
 mod set_failpoints;
 mod state;
+mod state_overrides;
 #[cfg(test)]
 pub mod tests;
+mod transaction_filter;
 mod transactions;
+mod v2;
 mod view_function;
+mod webhooks;
 
 /// API categories for the OpenAPI spec
 #[derive(Tags)]
@@ -44,6 +54,9 @@ pub enum ApiTags {
     /// General information
     General,
 
+    /// Access to proofs for light clients
+    Proofs,
+
     /// Access to tables
     Tables,
 
@@ -59,3 +72,4 @@ pub enum ApiTags {
 pub use context::Context;
 pub use response::BasicError;
 pub use runtime::{attach_poem_to_runtime, bootstrap, get_api_service};
+pub use v2::get_api_service_v2;
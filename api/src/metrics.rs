@@ -62,3 +62,39 @@ pub static REQUEST_SOURCE_CLIENT: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static RESPONSE_CACHE: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_response_cache",
+        "Lookups of the immutable response caches grouped by cache and whether they hit or missed",
+        &["cache", "result"]
+    )
+    .unwrap()
+});
+
+pub static WEBHOOK_DELIVERIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_webhook_deliveries",
+        "Transactions sent to the webhooks grouped by webhook and result (delivered, retried, failed or dropped)",
+        &["webhook", "result"]
+    )
+    .unwrap()
+});
+
+pub static WEBHOOK_CALL_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_api_webhook_call_latency",
+        "Latency of the calls of the webhooks grouped by webhook",
+        &["webhook"]
+    )
+    .unwrap()
+});
+
+pub static API_KEY_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_key_requests",
+        "API requests grouped by the name of their API key and whether they were allowed or throttled",
+        &["api_key", "result"]
+    )
+    .unwrap()
+});
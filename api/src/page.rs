@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::response::BadRequestError;
-use aptos_api_types::{AptosErrorCode, LedgerInfo};
+use aptos_api_types::{AptosErrorCode, LedgerInfo, PageCursor};
 use serde::Deserialize;
 
 const DEFAULT_PAGE_SIZE: u16 = 25;
@@ -24,6 +24,34 @@ impl Page {
         }
     }
 
+    /// Builds the page from either the `start` given by the client or the `cursor` returned
+    /// with the previous page. `cursor_start` gets the start from the cursor, and returns `None`
+    /// for the cursors of the other kinds of endpoints.
+    pub fn new_with_cursor<E: BadRequestError>(
+        start: Option<u64>,
+        cursor: Option<PageCursor>,
+        cursor_start: fn(PageCursor) -> Option<u64>,
+        limit: Option<u16>,
+        max_page_size: u16,
+    ) -> Result<Self, E> {
+        let start = match (start, cursor) {
+            (Some(_), Some(_)) => {
+                return Err(E::bad_request_with_code_no_info(
+                    "Only one of start and cursor can be given",
+                    AptosErrorCode::InvalidInput,
+                ));
+            },
+            (None, Some(cursor)) => Some(cursor_start(cursor).ok_or_else(|| {
+                E::bad_request_with_code_no_info(
+                    "Given cursor is not a cursor of this endpoint",
+                    AptosErrorCode::InvalidInput,
+                )
+            })?),
+            (start, None) => start,
+        };
+        Ok(Self::new(start, limit, max_page_size))
+    }
+
     /// Compute the start of the page for transactions
     pub fn compute_start<E: BadRequestError>(
         &self,
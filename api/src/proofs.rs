@@ -0,0 +1,266 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    accept_type::AcceptType,
+    context::Context,
+    failpoint::fail_point_poem,
+    response::{
+        api_forbidden, version_not_found, BadRequestError, BasicErrorWith404, BasicResponse,
+        BasicResponseStatus, BasicResultWith404, InternalError,
+    },
+    ApiTags,
+};
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{AptosErrorCode, HexEncodedBytes, RawStateValueRequest, U64};
+use aptos_types::state_store::state_key::StateKey;
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use std::sync::Arc;
+
+/// API for the proofs that light clients verify the data of the chain with
+///
+/// The proofs are only served as BCS, as they are checked with the types of
+/// aptos-types. Starting from a trusted waypoint, a client verifies the epoch
+/// change proof to learn the validators of the current epoch, verifies the
+/// signatures of the latest ledger info with them, and then verifies the other
+/// proofs against that ledger info by passing its version as `ledger_version`.
+pub struct ProofsApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl ProofsApi {
+    /// Get ledger info with signatures
+    ///
+    /// Retrieves the latest ledger info, with the signatures of the validators
+    /// of its epoch, as BCS encoded `LedgerInfoWithSignatures`.
+    #[oai(
+        path = "/proofs/ledger_info",
+        method = "get",
+        operation_id = "get_ledger_info_with_signatures",
+        tag = "ApiTags::Proofs"
+    )]
+    async fn get_ledger_info_with_signatures(
+        &self,
+        accept_type: AcceptType,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        fail_point_poem("endpoint_get_ledger_info_with_signatures")?;
+        self.check_bcs(&accept_type, "Get ledger info with signatures")?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let ledger_info_with_signatures = self
+            .context
+            .get_latest_ledger_info_with_signatures()
+            .context("Failed to retrieve latest ledger info")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        BasicResponse::try_from_bcs((
+            ledger_info_with_signatures,
+            &ledger_info,
+            BasicResponseStatus::Ok,
+        ))
+    }
+
+    /// Get transaction info with proof
+    ///
+    /// Retrieves the info of the transaction at a version, with the proof that
+    /// it is in the transaction accumulator at a ledger version, as BCS encoded
+    /// `TransactionInfoWithProof`.
+    ///
+    /// If the version has been pruned, a 410 will be returned.
+    #[oai(
+        path = "/proofs/transaction_info/:txn_version",
+        method = "get",
+        operation_id = "get_transaction_info_with_proof",
+        tag = "ApiTags::Proofs"
+    )]
+    async fn get_transaction_info_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Version of the transaction
+        txn_version: Path<U64>,
+        /// Ledger version the proof is relative to
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        fail_point_poem("endpoint_get_transaction_info_with_proof")?;
+        self.check_bcs(&accept_type, "Get transaction info with proof")?;
+        let (ledger_info, ledger_version) = self
+            .context
+            .get_latest_ledger_info_and_verify_lookup_version::<BasicErrorWith404>(
+                ledger_version.0.map(|v| v.0),
+            )?;
+        let txn_version = txn_version.0 .0;
+        if txn_version > ledger_version {
+            return Err(version_not_found(txn_version, &ledger_info));
+        } else if txn_version < ledger_info.oldest_version() {
+            return Err(self.context.version_pruned(txn_version, &ledger_info));
+        }
+
+        let proof = self
+            .context
+            .db
+            .get_transaction_by_version(txn_version, ledger_version, false)
+            .context("Failed to retrieve transaction with proof")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .proof;
+        BasicResponse::try_from_bcs((proof, &ledger_info, BasicResponseStatus::Ok))
+    }
+
+    /// Get state value with proof
+    ///
+    /// Retrieves the state value of the key provided in the request body at a
+    /// ledger version, with the proof that it is in the state tree at that version,
+    /// as BCS encoded `(Option<StateValue>, SparseMerkleProof)`. A missing value is
+    /// proven as well. The root of the state tree is the `state_checkpoint_hash` of
+    /// the info of the transaction at the version, which must be the end of a block.
+    ///
+    /// If the state at the version has been pruned, a 410 will be returned.
+    #[oai(
+        path = "/proofs/state_value",
+        method = "post",
+        operation_id = "get_state_value_with_proof",
+        tag = "ApiTags::Proofs"
+    )]
+    async fn get_state_value_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Request that carries the BCS encoded state key.
+        request: Json<RawStateValueRequest>,
+        /// Ledger version of the state
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        fail_point_poem("endpoint_get_state_value_with_proof")?;
+        self.check_bcs(&accept_type, "Get state value with proof")?;
+        // Verifies the version is within the ledger and its state isn't pruned
+        let (ledger_info, ledger_version, _) = self
+            .context
+            .state_view::<BasicErrorWith404>(ledger_version.0.map(|v| v.0))?;
+
+        let state_key: StateKey = bcs::from_bytes(&request.0.key.0)
+            .context(format!("Failed deserializing state key: {}", request.0.key))
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+        let value_with_proof = self
+            .context
+            .db
+            .get_state_value_with_proof_by_version(&state_key, ledger_version)
+            .context(format!(
+                "Failed to retrieve state value with proof at version {}",
+                ledger_version
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        BasicResponse::try_from_bcs((value_with_proof, &ledger_info, BasicResponseStatus::Ok))
+    }
+
+    /// Get epoch change proof
+    ///
+    /// Retrieves the ledger infos ending the epochs from `start_epoch` (inclusive)
+    /// to `end_epoch` (exclusive), with the signatures of the validators of each
+    /// epoch, as BCS encoded `EpochChangeProof`. The `more` field of the proof is
+    /// set when it stops before `end_epoch`, in which case the rest is retrieved
+    /// from the last epoch of the proof.
+    #[oai(
+        path = "/proofs/epoch_change",
+        method = "get",
+        operation_id = "get_epoch_change_proof",
+        tag = "ApiTags::Proofs"
+    )]
+    async fn get_epoch_change_proof(
+        &self,
+        accept_type: AcceptType,
+        /// First epoch to retrieve the ending ledger info of
+        start_epoch: Query<U64>,
+        /// Epoch to stop at
+        ///
+        /// If not provided, it will be the current epoch
+        end_epoch: Query<Option<U64>>,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        fail_point_poem("endpoint_get_epoch_change_proof")?;
+        self.check_bcs(&accept_type, "Get epoch change proof")?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let current_epoch = self
+            .context
+            .get_latest_ledger_info_with_signatures()
+            .context("Failed to retrieve latest ledger info")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .ledger_info()
+            .next_block_epoch();
+        let start_epoch = start_epoch.0 .0;
+        let end_epoch = end_epoch.0.map_or(current_epoch, |epoch| epoch.0);
+        if start_epoch >= end_epoch || end_epoch > current_epoch {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Invalid epoch range [{}, {}), the epochs must be increasing and end by the current epoch {}",
+                    start_epoch, end_epoch, current_epoch
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let proof = self
+            .context
+            .db
+            .get_epoch_ending_ledger_infos(start_epoch, end_epoch)
+            .context("Failed to retrieve epoch ending ledger infos")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        BasicResponse::try_from_bcs((proof, &ledger_info, BasicResponseStatus::Ok))
+    }
+}
+
+impl ProofsApi {
+    fn check_bcs(
+        &self,
+        accept_type: &AcceptType,
+        name: &'static str,
+    ) -> Result<(), BasicErrorWith404> {
+        if AcceptType::Json == *accept_type {
+            return Err(api_forbidden(
+                name,
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context.check_api_output_enabled(name, accept_type)
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Filters the resources of an account by their struct tag, so that the clients looking for a
+//! few of them don't page through all the resources of the account.
+
+use anyhow::{bail, Context as AnyhowContext, Result};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+};
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq)]
+pub struct ResourceFilter {
+    address: AccountAddress,
+    module: Identifier,
+    name: Option<Identifier>,
+    type_args: Option<Vec<TypeTag>>,
+}
+
+impl ResourceFilter {
+    /// `filter` is either `<address>::<module>`, `<address>::<module>::<name>` to match the
+    /// resources of any type arguments, or a struct tag with its type arguments
+    pub fn new(filter: &str) -> Result<Self> {
+        if filter.contains('<') {
+            let struct_tag = StructTag::from_str(filter)
+                .with_context(|| format!("Invalid struct tag of the filter {}", filter))?;
+            return Ok(Self {
+                address: struct_tag.address,
+                module: struct_tag.module,
+                name: Some(struct_tag.name),
+                type_args: Some(struct_tag.type_params),
+            });
+        }
+
+        let parts: Vec<_> = filter.split("::").collect();
+        let (address, module, name) = match parts.as_slice() {
+            [address, module] => (address, module, None),
+            [address, module, name] => (address, module, Some(name)),
+            _ => bail!(
+                "Invalid filter {}, expected <address>::<module>, <address>::<module>::<name> or a struct tag",
+                filter
+            ),
+        };
+        Ok(Self {
+            address: AccountAddress::from_hex_literal(address)
+                .context("Invalid address of the filter")?,
+            module: Identifier::new(*module).context("Invalid module of the filter")?,
+            name: name
+                .map(|name| Identifier::new(*name))
+                .transpose()
+                .context("Invalid name of the filter")?,
+            type_args: None,
+        })
+    }
+
+    pub fn matches(&self, struct_tag: &StructTag) -> bool {
+        if struct_tag.address != self.address || struct_tag.module != self.module {
+            return false;
+        }
+        if let Some(name) = &self.name {
+            if struct_tag.name != *name {
+                return false;
+            }
+        }
+        if let Some(type_args) = &self.type_args {
+            if struct_tag.type_params != *type_args {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_filter() {
+        let coin_store =
+            StructTag::from_str("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>").unwrap();
+        let coin_info =
+            StructTag::from_str("0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>").unwrap();
+        let account = StructTag::from_str("0x1::account::Account").unwrap();
+
+        let filter = ResourceFilter::new("0x1::coin").unwrap();
+        assert!(filter.matches(&coin_store));
+        assert!(filter.matches(&coin_info));
+        assert!(!filter.matches(&account));
+
+        let filter = ResourceFilter::new("0x1::coin::CoinStore").unwrap();
+        assert!(filter.matches(&coin_store));
+        assert!(!filter.matches(&coin_info));
+
+        let filter =
+            ResourceFilter::new("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>").unwrap();
+        assert!(filter.matches(&coin_store));
+        let filter = ResourceFilter::new("0x1::coin::CoinStore<0x2::coin::Other>").unwrap();
+        assert!(!filter.matches(&coin_store));
+
+        assert!(ResourceFilter::new("0x1").is_err());
+        assert!(ResourceFilter::new("coin::CoinStore").is_err());
+        assert!(ResourceFilter::new("0x1::coin::CoinStore::x").is_err());
+        assert!(ResourceFilter::new("0x1::coin::CoinStore<").is_err());
+    }
+}
@@ -30,7 +30,10 @@
 // TODO: https://github.com/aptos-labs/aptos-core/issues/2279
 
 use super::{accept_type::AcceptType, bcs_payload::Bcs};
-use aptos_api_types::{Address, AptosError, AptosErrorCode, HashValue, LedgerInfo};
+use aptos_api_types::{
+    Address, AptosError, AptosErrorCode, AptosErrorDetails, BlockPrunedDetails, HashValue,
+    LedgerInfo, VersionPrunedDetails, U64,
+};
 use move_core_types::{
     identifier::{IdentStr, Identifier},
     language_storage::StructTag,
@@ -299,6 +302,7 @@ macro_rules! generate_success_response {
                 #[oai(header = "X-Aptos-Oldest-Block-Height")] u64,
                 /// Cursor to be used for endpoints that support cursor-based
                 /// pagination. Pass this to the `start` field of the endpoint
+                /// (the `cursor` field for the transaction and event endpoints)
                 /// on the next call to get the next page of results.
                 #[oai(header = "X-Aptos-Cursor")] Option<String>,
             ),
@@ -471,6 +475,17 @@ macro_rules! generate_success_response {
                 }
                 self
             }
+
+            pub fn with_page_cursor(mut self, new_cursor: Option<aptos_api_types::PageCursor>) -> Self {
+                match self {
+                    $(
+                    [<$enum_name>]::$name(_, _, _, _, _, _, _, _, ref mut cursor) => {
+                        *cursor = new_cursor.map(|c| c.to_string());
+                    }
+                    )*
+                }
+                self
+            }
         }
         }
     };
@@ -604,12 +619,62 @@ pub fn transaction_not_found_by_hash<E: NotFoundError>(
     )
 }
 
-pub fn version_pruned<E: GoneError>(ledger_version: u64, ledger_info: &LedgerInfo) -> E {
-    E::gone_with_code(
-        format!("Ledger version({}) has been pruned", ledger_version),
+pub fn version_pruned<E: GoneError>(
+    ledger_version: u64,
+    prune_window: Option<usize>,
+    ledger_info: &LedgerInfo,
+) -> E {
+    let error = AptosError::new_with_error_code(
+        format!(
+            "Ledger version({}) has been pruned, the oldest available ledger version is {}{}",
+            ledger_version,
+            ledger_info.oldest_ledger_version.0,
+            format_prune_window(prune_window),
+        ),
+        AptosErrorCode::VersionPruned,
+    )
+    .with_details(version_pruned_details(
+        ledger_info.oldest_ledger_version.0,
+        prune_window,
+    ));
+    E::gone_from_aptos_error(error, ledger_info)
+}
+
+/// State values are pruned separately from the ledger, so the state at a version can be pruned
+/// even if the version itself is not.
+pub fn state_version_pruned<E: GoneError>(
+    ledger_version: u64,
+    oldest_state_version: u64,
+    prune_window: Option<usize>,
+    ledger_info: &LedgerInfo,
+) -> E {
+    let error = AptosError::new_with_error_code(
+        format!(
+            "State at ledger version({}) has been pruned, the oldest ledger version with state available is {}{}",
+            ledger_version,
+            oldest_state_version,
+            format_prune_window(prune_window),
+        ),
         AptosErrorCode::VersionPruned,
-        ledger_info,
     )
+    .with_details(version_pruned_details(oldest_state_version, prune_window));
+    E::gone_from_aptos_error(error, ledger_info)
+}
+
+fn format_prune_window(prune_window: Option<usize>) -> String {
+    prune_window
+        .map(|window| format!(" (prune window: {} versions)", window))
+        .unwrap_or_default()
+}
+
+fn version_pruned_details(
+    oldest_available_version: u64,
+    prune_window: Option<usize>,
+) -> AptosErrorDetails {
+    AptosErrorDetails::VersionPruned(VersionPrunedDetails {
+        oldest_available_version: oldest_available_version.into(),
+        prune_window: prune_window.map(|window| U64::from(window as u64)),
+    })
 }
 
 pub fn account_not_found<E: NotFoundError>(
@@ -722,9 +787,12 @@ pub fn block_not_found_by_version<E: NotFoundError>(
 }
 
 pub fn block_pruned_by_height<E: GoneError>(block_height: u64, ledger_info: &LedgerInfo) -> E {
-    E::gone_with_code(
+    let error = AptosError::new_with_error_code(
         format!("Block({}) has been pruned", block_height),
         AptosErrorCode::BlockPruned,
-        ledger_info,
     )
+    .with_details(AptosErrorDetails::BlockPruned(BlockPrunedDetails {
+        oldest_available_block_height: ledger_info.oldest_block_height,
+    }));
+    E::gone_from_aptos_error(error, ledger_info)
 }
@@ -0,0 +1,166 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caches the responses that can't change once their data is committed, like the transactions,
+//! the blocks, and the resources at a given version, so that the hot queries of the explorers
+//! don't read and convert the same data from the DB over and over.
+
+use crate::{accept_type::AcceptType, metrics::RESPONSE_CACHE};
+use aptos_api_types::{Block, MoveResource, Transaction};
+use aptos_config::config::ApiConfig;
+use aptos_crypto::HashValue;
+use lru::LruCache;
+use move_core_types::{account_address::AccountAddress, language_storage::StructTag};
+use std::{hash::Hash, sync::Mutex};
+
+/// A response body, as JSON before it's serialized, or as BCS
+#[derive(Clone, Debug, PartialEq)]
+pub enum CachedResponse<T> {
+    Json(T),
+    Bcs(Vec<u8>),
+}
+
+/// The transactions can be looked up by version or by hash
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransactionKey {
+    Version(u64),
+    Hash(HashValue),
+}
+
+struct Entries<K, V> {
+    lru: LruCache<K, (V, usize)>,
+    size: usize,
+}
+
+/// An LRU cache bounded by its number of entries and by the size of their values. The size of a
+/// value is given when it's inserted, as the BCS size of its data, which is cheap to compute.
+pub struct ResponseCache<K: Hash + Eq, V: Clone> {
+    name: &'static str,
+    max_entries: usize,
+    max_size: usize,
+    entries: Mutex<Entries<K, V>>,
+}
+
+impl<K: Hash + Eq, V: Clone> ResponseCache<K, V> {
+    /// The cache is disabled if either of the limits is 0
+    pub fn new(name: &'static str, max_entries: usize, max_size: usize) -> Self {
+        Self {
+            name,
+            max_entries,
+            max_size,
+            entries: Mutex::new(Entries {
+                lru: LruCache::unbounded(),
+                size: 0,
+            }),
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.max_entries > 0 && self.max_size > 0
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        if !self.enabled() {
+            return None;
+        }
+        let value = self
+            .entries
+            .lock()
+            .unwrap()
+            .lru
+            .get(key)
+            .map(|(value, _)| value.clone());
+        let result = if value.is_some() { "hit" } else { "miss" };
+        RESPONSE_CACHE.with_label_values(&[self.name, result]).inc();
+        value
+    }
+
+    /// Inserts the value, evicting the least recently used ones until the cache is within its
+    /// limits. A value larger than the whole cache isn't inserted.
+    pub fn insert(&self, key: K, value: V, size: usize) {
+        if !self.enabled() || size > self.max_size {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if let Some((_, replaced_size)) = entries.lru.put(key, (value, size)) {
+            entries.size -= replaced_size;
+        }
+        entries.size += size;
+        while entries.lru.len() > self.max_entries || entries.size > self.max_size {
+            match entries.lru.pop_lru() {
+                Some((_, (_, evicted_size))) => entries.size -= evicted_size,
+                None => break,
+            }
+        }
+    }
+
+    /// The number of entries and their total size
+    pub fn usage(&self) -> (usize, usize) {
+        let entries = self.entries.lock().unwrap();
+        (entries.lru.len(), entries.size)
+    }
+}
+
+/// The caches of the immutable responses, each bounded by the limits of the config
+pub struct ResponseCaches {
+    /// The committed transactions, with their version to check if they were pruned since
+    pub transactions:
+        ResponseCache<(TransactionKey, AcceptType), (u64, CachedResponse<Transaction>)>,
+    /// The blocks by height and whether they include their transactions
+    pub blocks: ResponseCache<(u64, bool, AcceptType), CachedResponse<Block>>,
+    /// The resources requested at an explicit ledger version
+    pub resources:
+        ResponseCache<(AccountAddress, StructTag, u64, AcceptType), CachedResponse<MoveResource>>,
+}
+
+impl ResponseCaches {
+    pub fn new(config: &ApiConfig) -> Self {
+        let (max_entries, max_size) = (
+            config.response_cache_max_entries,
+            config.response_cache_max_bytes,
+        );
+        Self {
+            transactions: ResponseCache::new("transactions", max_entries, max_size),
+            blocks: ResponseCache::new("blocks", max_entries, max_size),
+            resources: ResponseCache::new("resources", max_entries, max_size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_cache_limits() {
+        let cache = ResponseCache::new("test", 3, 100);
+        cache.insert(1, "a", 40);
+        cache.insert(2, "b", 40);
+        assert_eq!(cache.get(&1), Some("a"));
+
+        // Over the size limit, the least recently used entry is evicted
+        cache.insert(3, "c", 40);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.usage(), (2, 80));
+
+        // Over the entries limit
+        cache.insert(4, "d", 10);
+        cache.insert(5, "e", 10);
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.usage(), (3, 60));
+
+        // Replacing an entry replaces its size
+        cache.insert(3, "f", 20);
+        assert_eq!(cache.get(&3), Some("f"));
+        assert_eq!(cache.usage(), (3, 40));
+
+        // A value larger than the cache isn't inserted
+        cache.insert(6, "g", 101);
+        assert_eq!(cache.get(&6), None);
+        assert_eq!(cache.usage(), (3, 40));
+
+        let disabled = ResponseCache::new("test", 0, 100);
+        disabled.insert(1, "a", 1);
+        assert_eq!(disabled.get(&1), None);
+    }
+}
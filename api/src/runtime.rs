@@ -3,10 +3,25 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_size::PostSizeLimit,
-    context::Context, error_converter::convert_error, events::EventsApi, index::IndexApi,
-    log::middleware_log, set_failpoints, state::StateApi, transactions::TransactionsApi,
+    accounts::AccountsApi,
+    api_keys::{self, ApiKeyRateLimit, ApiKeys},
+    basic::BasicApi,
+    blocks::BlocksApi,
+    check_size::PostSizeLimit,
+    compression::ResponseCompression,
+    context::Context,
+    error_converter::convert_error,
+    event_stream,
+    events::EventsApi,
+    index::IndexApi,
+    log::middleware_log,
+    proofs::ProofsApi,
+    set_failpoints,
+    state::StateApi,
+    transactions::TransactionsApi,
+    v2::get_api_service_v2,
     view_function::ViewFunctionApi,
+    webhooks::Webhooks,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::X_APTOS_CLIENT;
@@ -21,7 +36,7 @@ use poem::{
     middleware::Cors,
     EndpointExt, Route, Server,
 };
-use poem_openapi::{ContactObject, LicenseObject, OpenApiService};
+use poem_openapi::{ContactObject, LicenseObject, OpenApi, OpenApiService};
 use std::{net::SocketAddr, sync::Arc};
 use tokio::runtime::{Handle, Runtime};
 
@@ -39,6 +54,12 @@ pub fn bootstrap(
 
     let context = Context::new(chain_id, db, mp_sender, config.clone());
 
+    if config.api.webhooks.enabled {
+        let webhooks = Webhooks::new(&config.api.webhooks, chain_id.id())
+            .context("Failed to set up the webhooks")?;
+        runtime.spawn(webhooks.run(Arc::new(context.clone())));
+    }
+
     attach_poem_to_runtime(runtime.handle(), context, config, false)
         .context("Failed to attach poem to runtime")?;
 
@@ -61,6 +82,7 @@ pub fn get_api_service(
         BlocksApi,
         EventsApi,
         IndexApi,
+        ProofsApi,
         StateApi,
         TransactionsApi,
         ViewFunctionApi,
@@ -84,6 +106,9 @@ pub fn get_api_service(
         IndexApi {
             context: context.clone(),
         },
+        ProofsApi {
+            context: context.clone(),
+        },
         StateApi {
             context: context.clone(),
         },
@@ -93,6 +118,11 @@ pub fn get_api_service(
         ViewFunctionApi { context },
     );
 
+    new_api_service(apis, "/v1")
+}
+
+/// Create the API service of a version of the API, served at `server`
+pub(crate) fn new_api_service<T: OpenApi>(apis: T, server: &str) -> OpenApiService<T, ()> {
     let version = VERSION.to_string();
     let license =
         LicenseObject::new("Apache 2.0").url("https://www.apache.org/licenses/LICENSE-2.0.html");
@@ -101,7 +131,7 @@ pub fn get_api_service(
         .url("https://github.com/aptos-labs/aptos-core");
 
     OpenApiService::new(apis, "Aptos Node API", version.trim())
-        .server("/v1")
+        .server(server)
         .description("The Aptos Node API is a RESTful API for client applications to interact with the Aptos blockchain.")
         .license(license)
         .contact(contact)
@@ -118,12 +148,19 @@ pub fn attach_poem_to_runtime(
     let context = Arc::new(context);
 
     let size_limit = context.content_length_limit();
+    let compression_enabled = context.compression_enabled();
+    let compression_min_size = context.compression_min_size();
+    let api_keys = Arc::new(ApiKeys::new(&config.api.api_keys)?);
 
     let api_service = get_api_service(context.clone());
 
     let spec_json = api_service.spec_endpoint();
     let spec_yaml = api_service.spec_endpoint_yaml();
 
+    let api_service_v2 = get_api_service_v2(context.clone());
+    let spec_json_v2 = api_service_v2.spec_endpoint();
+    let spec_yaml_v2 = api_service_v2.spec_endpoint_yaml();
+
     let mut address = config.api.address;
 
     if random_port {
@@ -174,6 +211,7 @@ pub fn attach_poem_to_runtime(
                 header::HeaderName::from_static(X_APTOS_CLIENT),
                 header::CONTENT_TYPE,
                 header::ACCEPT,
+                api_keys.header().clone(),
             ]);
 
         // Build routes for the API
@@ -189,10 +227,31 @@ pub fn attach_poem_to_runtime(
                     .at(
                         "/set_failpoint",
                         poem::get(set_failpoints::set_failpoint_poem).data(context.clone()),
+                    )
+                    .at(
+                        "/events/stream",
+                        poem::get(event_stream::stream_events_poem).data(context.clone()),
+                    )
+                    .at(
+                        "/api_key/usage",
+                        poem::get(api_keys::api_key_usage_poem).data(api_keys.clone()),
                     ),
             )
+            .nest(
+                "/v2",
+                Route::new()
+                    .nest("/", api_service_v2)
+                    .at("/spec.json", spec_json_v2)
+                    .at("/spec.yaml", spec_yaml_v2),
+            )
+            // The CORS preflight requests are answered before checking their API key.
+            .with_if(api_keys.enabled(), ApiKeyRateLimit::new(api_keys.clone()))
             .with(cors)
             .with(PostSizeLimit::new(size_limit))
+            .with_if(
+                compression_enabled,
+                ResponseCompression::new(compression_min_size),
+            )
             // NOTE: Make sure to keep this after all the `with` middleware.
             .catch_all_error(convert_error)
             .around(middleware_log);
@@ -4,18 +4,21 @@
 use crate::{
     accept_type::AcceptType,
     failpoint::fail_point_poem,
+    page::determine_limit,
     response::{
         api_forbidden, build_not_found, module_not_found, resource_not_found, table_item_not_found,
         BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
         InternalError,
     },
+    response_cache::CachedResponse,
     ApiTags, Context,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper,
     MoveModuleBytecode, MoveResource, MoveStructTag, MoveValue, RawStateValueRequest,
-    RawTableItemRequest, TableItemRequest, VerifyInput, VerifyInputWithRecursion, U64,
+    RawTableItemRequest, StateKeyWrapper, TableItem, TableItemRequest, TableItemsRequest,
+    VerifyInput, VerifyInputWithRecursion, U64,
 };
 use aptos_state_view::TStateView;
 use aptos_types::{
@@ -24,7 +27,8 @@ use aptos_types::{
 };
 use aptos_vm::data_cache::AsMoveResolver;
 use move_core_types::{
-    language_storage::{ModuleId, StructTag},
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag, TypeTag},
     resolver::MoveResolver,
 };
 use poem_openapi::{
@@ -84,6 +88,53 @@ impl StateApi {
         )
     }
 
+    /// Get account resource group
+    ///
+    /// Retrieves all the resources of a resource group from a given account and at a specific
+    /// ledger version, e.g. all the resources of an object with `0x1::object::ObjectGroup`. If the
+    /// ledger version is not specified in the request, the latest ledger version is used.
+    ///
+    /// With BCS, the resource group is returned as is, i.e. a BCS encoded map from the struct
+    /// tags of its resources to their BCS encoded values.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/resource_group/:resource_group",
+        method = "get",
+        operation_id = "get_account_resource_group",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_resource_group(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of the resource group to retrieve e.g. `0x1::object::ObjectGroup`
+        resource_group: Path<MoveStructTag>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<MoveResource>> {
+        resource_group
+            .0
+            .verify(0)
+            .context("'resource_group' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_account_resource_group")?;
+        self.context
+            .check_api_output_enabled("Get account resource group", &accept_type)?;
+        self.resource_group(
+            &accept_type,
+            address.0,
+            resource_group.0,
+            ledger_version.0.map(|inner| inner.0),
+        )
+    }
+
     /// Get account module
     ///
     /// Retrieves an individual module from a given account and at a specific ledger version. If the
@@ -168,6 +219,65 @@ impl StateApi {
         )
     }
 
+    /// Get table items
+    ///
+    /// Get the items of the table identified by {table_handle} in the path at a specific
+    /// ledger version, ordered by the BCS encoding of their keys. The "key_type" and
+    /// "value_type" of the table (TableItemsRequest) are provided in the request body,
+    /// and are required for JSON. If "key_type" is provided, the items whose key isn't
+    /// of that type are skipped. BCS returns the encoded keys and values of the items.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/tables/:table_handle/items",
+        method = "post",
+        operation_id = "get_table_items",
+        tag = "ApiTags::Tables"
+    )]
+    async fn get_table_items(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Table request detailing the key type and value type
+        table_items_request: Json<TableItemsRequest>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+        /// Cursor specifying where to start for pagination
+        ///
+        /// This cursor cannot be derived manually client-side. Instead, you must
+        /// call this endpoint once without this query parameter specified, and
+        /// then use the cursor returned in the X-Aptos-Cursor header in the
+        /// response.
+        start: Query<Option<StateKeyWrapper>>,
+        /// Max number of table items to retrieve
+        ///
+        /// If not provided, defaults to default page size.
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<TableItem>> {
+        table_items_request
+            .0
+            .verify()
+            .context("'table_items_request' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_table_items")?;
+        self.context
+            .check_api_output_enabled("Get table items", &accept_type)?;
+        self.table_items(
+            &accept_type,
+            table_handle.0,
+            table_items_request.0,
+            ledger_version.0,
+            start.0.map(StateKey::from),
+            limit.0,
+        )
+    }
+
     /// Get raw table item
     ///
     /// Get a table item at a specific ledger version from the table identified by {table_handle}
@@ -273,7 +383,27 @@ impl StateApi {
                 BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
             })?;
 
+        // Only the resources at an explicit version are cached, the latest ones can still change
+        let cache_key = ledger_version.map(|version| {
+            (
+                AccountAddress::from(address),
+                resource_type.clone(),
+                version,
+                *accept_type,
+            )
+        });
         let (ledger_info, ledger_version, state_view) = self.context.state_view(ledger_version)?;
+        let cache = &self.context.response_caches().resources;
+        if let Some(cached) = cache_key.as_ref().and_then(|key| cache.get(key)) {
+            return match cached {
+                CachedResponse::Json(resource) => {
+                    BasicResponse::try_from_json((resource, &ledger_info, BasicResponseStatus::Ok))
+                },
+                CachedResponse::Bcs(bytes) => {
+                    BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
+                },
+            };
+        }
         let bytes = state_view
             .as_move_resolver()
             .get_resource(&address.into(), &resource_type)
@@ -307,8 +437,81 @@ impl StateApi {
                         )
                     })?;
 
+                if let Some(key) = cache_key {
+                    cache.insert(key, CachedResponse::Json(resource.clone()), bytes.len());
+                }
                 BasicResponse::try_from_json((resource, &ledger_info, BasicResponseStatus::Ok))
             },
+            AcceptType::Bcs => {
+                if let Some(key) = cache_key {
+                    cache.insert(key, CachedResponse::Bcs(bytes.to_vec()), bytes.len());
+                }
+                BasicResponse::try_from_encoded((
+                    bytes.to_vec(),
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+        }
+    }
+
+    /// Retrieve all the resources of a resource group
+    ///
+    /// JSON: Decode each resource of the group
+    /// BCS: Leave the group as is BCS encoded
+    fn resource_group(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        resource_group: MoveStructTag,
+        ledger_version: Option<u64>,
+    ) -> BasicResultWith404<Vec<MoveResource>> {
+        let resource_group: StructTag = resource_group
+            .try_into()
+            .context("Failed to parse given resource group")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let state_key = StateKey::access_path(AccessPath::resource_group_access_path(
+            address.into(),
+            resource_group.clone(),
+        ));
+
+        let (ledger_info, ledger_version, state_view) = self.context.state_view(ledger_version)?;
+        let bytes = state_view
+            .get_state_value_bytes(&state_key)
+            .context(format!(
+                "Failed to query DB to check for {} at {}",
+                resource_group, address
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .ok_or_else(|| {
+                resource_not_found(address, &resource_group, ledger_version, &ledger_info)
+            })?;
+
+        match accept_type {
+            AcceptType::Json => {
+                let resources = state_view
+                    .as_move_resolver()
+                    .as_converter(self.context.db.clone())
+                    .try_into_resources_from_resource_group(&bytes)
+                    .context("Failed to deserialize resource group data retrieved from DB")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+
+                BasicResponse::try_from_json((resources, &ledger_info, BasicResponseStatus::Ok))
+            },
             AcceptType::Bcs => BasicResponse::try_from_encoded((
                 bytes.to_vec(),
                 &ledger_info,
@@ -464,6 +667,119 @@ impl StateApi {
         }
     }
 
+    /// Retrieve a page of the items of a table for a specific ledger version
+    pub fn table_items(
+        &self,
+        accept_type: &AcceptType,
+        table_handle: Address,
+        table_items_request: TableItemsRequest,
+        ledger_version: Option<U64>,
+        start: Option<StateKey>,
+        limit: Option<u16>,
+    ) -> BasicResultWith404<Vec<TableItem>> {
+        // Parse the key and value types for the table
+        let key_type: Option<TypeTag> = table_items_request
+            .key_type
+            .map(TryInto::try_into)
+            .transpose()
+            .context("Failed to parse key_type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let value_type: Option<TypeTag> = table_items_request
+            .value_type
+            .map(TryInto::try_into)
+            .transpose()
+            .context("Failed to parse value_type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let json_value_type = match (accept_type, &key_type, value_type) {
+            (AcceptType::Json, Some(_), Some(value_type)) => Some(value_type),
+            (AcceptType::Json, _, _) => {
+                return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                    "The key_type and value_type are required to return the table items as JSON",
+                    AptosErrorCode::InvalidInput,
+                ))
+            },
+            (AcceptType::Bcs, _, _) => None,
+        };
+
+        // Retrieve local state
+        let (ledger_info, ledger_version, state_view) = self
+            .context
+            .state_view(ledger_version.map(|inner| inner.0))?;
+
+        let max_table_items_page_size = self.context.max_table_items_page_size();
+        let (items, next_state_key) = self
+            .context
+            .get_table_items_by_pagination(
+                TableHandle(table_handle.into()),
+                start.as_ref(),
+                ledger_version,
+                determine_limit(
+                    limit,
+                    max_table_items_page_size,
+                    max_table_items_page_size,
+                    &ledger_info,
+                )? as u64,
+            )
+            .context("Failed to get table items from storage")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+        // Keep the items with a key of the key type, decoded if it's needed
+        let items: Vec<_> = items
+            .into_iter()
+            .filter_map(|(key, value)| match &key_type {
+                Some(key_type) => converter
+                    .try_into_move_value(key_type, &key)
+                    .ok()
+                    .map(|move_key| (key, value, Some(move_key))),
+                None => Some((key, value, None)),
+            })
+            .collect();
+
+        match json_value_type {
+            Some(value_type) => {
+                let items = items
+                    .into_iter()
+                    .map(|(_, value, move_key)| {
+                        Ok(TableItem {
+                            key: move_key.expect("The keys are decoded with a key type"),
+                            value: converter.try_into_move_value(&value_type, &value)?,
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .context("Failed to deserialize table items retrieved from DB")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+                BasicResponse::try_from_json((items, &ledger_info, BasicResponseStatus::Ok))
+                    .map(|v| v.with_cursor(next_state_key))
+            },
+            None => {
+                let items: Vec<(Vec<u8>, Vec<u8>)> = items
+                    .into_iter()
+                    .map(|(key, value, _)| (key, value))
+                    .collect();
+                BasicResponse::try_from_bcs((items, &ledger_info, BasicResponseStatus::Ok))
+                    .map(|v| v.with_cursor(next_state_key))
+            },
+        }
+    }
+
     /// Retrieve table item for a specific ledger version
     pub fn raw_table_item(
         &self,
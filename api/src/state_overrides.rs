@@ -0,0 +1,142 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lets transactions be simulated on a speculative state, e.g. to preview a swap with a balance
+//! the account doesn't have yet.
+
+use anyhow::{format_err, Context as AnyhowContext, Result};
+use aptos_api_types::{MoveConverter, StateOverrides};
+use aptos_state_view::{StateView, TStateView};
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::{CoinStoreResource, CORE_CODE_ADDRESS},
+    on_chain_config::{CurrentTimeMicroseconds, OnChainConfig},
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    utility_coin::APTOS_COIN_TYPE,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{StructTag, TypeTag},
+    move_resource::MoveStructType,
+    resolver::MoveResolver,
+};
+use std::collections::HashMap;
+
+/// A state view that reads the overridden state values instead of the ones of its base view
+pub struct OverriddenStateView<'a, S> {
+    base_view: &'a S,
+    overrides: HashMap<StateKey, StateValue>,
+}
+
+impl<'a, S: StateView> OverriddenStateView<'a, S> {
+    pub fn new(base_view: &'a S, overrides: HashMap<StateKey, StateValue>) -> Self {
+        Self {
+            base_view,
+            overrides,
+        }
+    }
+}
+
+impl<'a, S: StateView> TStateView for OverriddenStateView<'a, S> {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        match self.overrides.get(state_key) {
+            Some(state_value) => Ok(Some(state_value.clone())),
+            None => self.base_view.get_state_value(state_key),
+        }
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        self.base_view.get_usage()
+    }
+}
+
+fn resource_state_key(address: AccountAddress, struct_tag: StructTag) -> Result<StateKey> {
+    Ok(StateKey::access_path(AccessPath::resource_access_path(
+        address, struct_tag,
+    )?))
+}
+
+/// Builds the state values the overrides replace. The resources are converted with the
+/// converter, and the coin stores whose balance is replaced are read from the state view.
+pub fn state_override_values<S: StateView, R: MoveResolver + ?Sized>(
+    state_view: &S,
+    converter: &MoveConverter<'_, R>,
+    state_overrides: StateOverrides,
+) -> Result<HashMap<StateKey, StateValue>> {
+    let mut values = HashMap::new();
+    for resource in state_overrides.resources {
+        let struct_tag: StructTag = resource
+            .typ
+            .try_into()
+            .context("Invalid resource type to override")?;
+        let data = serde_json::to_value(&resource.data)?;
+        let value = converter
+            .try_into_vm_value(&TypeTag::Struct(Box::new(struct_tag.clone())), data)
+            .with_context(|| format!("Invalid data of the overridden resource {}", struct_tag))?;
+        let bytes = value
+            .simple_serialize()
+            .ok_or_else(|| format_err!("Failed to serialize the resource {}", struct_tag))?;
+        values.insert(
+            resource_state_key(resource.address.into(), struct_tag)?,
+            StateValue::new_legacy(bytes.into()),
+        );
+    }
+
+    for balance in state_overrides.balances {
+        let coin_type = match balance.coin_type {
+            Some(coin_type) => TypeTag::Struct(Box::new(
+                coin_type
+                    .try_into()
+                    .context("Invalid coin type to override the balance of")?,
+            )),
+            None => APTOS_COIN_TYPE.clone(),
+        };
+        let coin_store_tag = StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: CoinStoreResource::MODULE_NAME.to_owned(),
+            name: CoinStoreResource::STRUCT_NAME.to_owned(),
+            type_params: vec![coin_type],
+        };
+        let address: AccountAddress = balance.address.into();
+        let state_key = resource_state_key(address, coin_store_tag.clone())?;
+        // The store may already be overridden as a resource
+        let coin_store: CoinStoreResource = match values.get(&state_key) {
+            Some(state_value) => bcs::from_bytes(state_value.bytes())?,
+            None => {
+                let bytes = state_view
+                    .get_state_value_bytes(&state_key)?
+                    .ok_or_else(|| {
+                        format_err!("Account {} has no {} to override", address, coin_store_tag)
+                    })?;
+                bcs::from_bytes(&bytes)?
+            },
+        };
+        let coin_store = CoinStoreResource::new(
+            balance.amount.0,
+            coin_store.frozen(),
+            coin_store.deposit_events().clone(),
+            coin_store.withdraw_events().clone(),
+        );
+        values.insert(
+            state_key,
+            StateValue::new_legacy(bcs::to_bytes(&coin_store)?.into()),
+        );
+    }
+
+    if let Some(timestamp_usecs) = state_overrides.timestamp_usecs {
+        values.insert(
+            StateKey::access_path(CurrentTimeMicroseconds::access_path()?),
+            StateValue::new_legacy(
+                bcs::to_bytes(&CurrentTimeMicroseconds {
+                    microseconds: timestamp_usecs.0,
+                })?
+                .into(),
+            ),
+        );
+    }
+    Ok(values)
+}
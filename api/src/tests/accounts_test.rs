@@ -5,6 +5,7 @@
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, find_value};
 use aptos_api_types::{MoveModuleBytecode, MoveResource, StateKeyWrapper};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use serde_json::json;
 use std::str::FromStr;
 
@@ -95,6 +96,42 @@ async fn test_account_modules_structs() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_abis() {
+    let mut context = new_test_context(current_function_name!());
+
+    let resp = context.get("/accounts/0x1/abis").await;
+    let abis = resp.as_array().unwrap();
+    assert!(abis.iter().all(|abi| {
+        abi["functions"].as_array().unwrap().iter().all(|function| {
+            function["is_entry"] == json!(true) || function["is_view"] == json!(true)
+        })
+    }));
+    let coin = abis
+        .iter()
+        .find(|abi| abi["module"] == "0x1::coin")
+        .expect("The coin module has entry functions");
+    assert!(coin["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|function| function["name"] == "transfer"));
+    // The modules without entry or view functions are left out
+    assert!(!abis.iter().any(|abi| abi["module"] == "0x1::vector"));
+
+    let resp = context.get("/accounts/0x1/abis?visibility=friend").await;
+    assert!(resp.as_array().unwrap().iter().all(|abi| abi["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|function| function["visibility"] == "friend")));
+
+    context
+        .expect_status_code(400)
+        .get("/accounts/0x1/abis?visibility=internal")
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_resources_by_ledger_version() {
     let mut context = new_test_context(current_function_name!());
@@ -264,6 +301,59 @@ async fn test_get_account_resources_with_pagination() {
     assert_eq!(resources, all_resources[10..].to_vec());
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resources_with_filter() {
+    let context = new_test_context(current_function_name!());
+    let address = "0x1";
+
+    let all_resources: Vec<MoveResource> =
+        serde_json::from_value(context.get(&account_resources(address)).await).unwrap();
+    let coin_resources: Vec<_> = all_resources
+        .into_iter()
+        .filter(|resource| resource.typ.module.0.as_str() == "coin")
+        .collect();
+    assert!(!coin_resources.is_empty());
+
+    // Every page has one of the matching resources, until the last one
+    let mut resources: Vec<MoveResource> = vec![];
+    let mut start = None;
+    loop {
+        let mut path = format!("/v1{}?filter=0x1::coin&limit=1", account_resources(address));
+        if let Some(start) = &start {
+            path.push_str(&format!("&start={}", start));
+        }
+        let resp = context
+            .reply(warp::test::request().method("GET").path(&path))
+            .await;
+        assert_eq!(resp.status(), 200);
+        let page: Vec<MoveResource> = serde_json::from_slice(resp.body()).unwrap();
+        resources.extend(page);
+        match resp.headers().get("X-Aptos-Cursor") {
+            Some(cursor) => start = Some(cursor.to_str().unwrap().to_string()),
+            None => break,
+        }
+    }
+    assert_eq!(resources, coin_resources);
+
+    let struct_tag = coin_resources[0].typ.to_string();
+    let resources: Vec<MoveResource> = serde_json::from_value(
+        context
+            .get(&format!(
+                "{}?filter={}",
+                account_resources(address),
+                utf8_percent_encode(&struct_tag, NON_ALPHANUMERIC)
+            ))
+            .await,
+    )
+    .unwrap();
+    assert_eq!(resources, vec![coin_resources[0].clone()]);
+
+    context
+        .expect_status_code(400)
+        .get(&format!("{}?filter=0x1", account_resources(address)))
+        .await;
+}
+
 // Same as the above test but for modules.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_modules_with_pagination() {
@@ -4,9 +4,10 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::PageCursor;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use serde_json::json;
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 
 static ACCOUNT_ADDRESS: &str = "0xa550c18";
 static CREATION_NUMBER: &str = "0";
@@ -87,6 +88,84 @@ async fn test_get_events_by_account_event_handle() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_with_order_and_range() {
+    let mut context = new_test_context(current_function_name!());
+    let mut root_account = context.root_account().await;
+    for _i in 0..4 {
+        let account = context.gen_account();
+        let txn = context.create_user_account_by(&mut root_account, &account);
+        context.commit_block(&vec![txn]).await;
+    }
+    let path = format!(
+        "/accounts/{}/events/{}/withdraw_events",
+        root_account.address(),
+        utf8_percent_encode(
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+            NON_ALPHANUMERIC
+        )
+    );
+    let sequence_numbers = |resp: serde_json::Value| -> Vec<u64> {
+        resp.as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["sequence_number"].as_str().unwrap().parse().unwrap())
+            .collect()
+    };
+    let latest = *sequence_numbers(context.get(&path).await).last().unwrap();
+    assert!(latest >= 3);
+
+    // The newest events come first, with a cursor to the older ones
+    let req = warp::test::request()
+        .method("GET")
+        .path(&format!("/v1{}?order=descending&limit=2", path));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let cursor = resp
+        .headers()
+        .get("X-Aptos-Cursor")
+        .expect("Cursor header was missing");
+    let cursor = PageCursor::from_str(cursor.to_str().unwrap()).unwrap();
+    assert_eq!(cursor, PageCursor::SequenceNumber(latest - 2));
+    let resp: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(sequence_numbers(resp), vec![latest, latest - 1]);
+
+    let resp = context
+        .get(&format!(
+            "{}?order=descending&start=1&cursor={}",
+            path, cursor
+        ))
+        .await;
+    assert_eq!(
+        sequence_numbers(resp),
+        (1..=latest - 2).rev().collect::<Vec<_>>()
+    );
+
+    // A range of events in either order
+    let resp = context.get(&format!("{}?start=1&end=2", path)).await;
+    assert_eq!(sequence_numbers(resp), vec![1, 2]);
+    let resp = context
+        .get(&format!("{}?order=descending&end=2", path))
+        .await;
+    assert_eq!(sequence_numbers(resp), vec![2, 1, 0]);
+    let resp = context
+        .get(&format!("{}?order=descending&end={}", path, latest + 10))
+        .await;
+    assert_eq!(sequence_numbers(resp)[0], latest);
+
+    context
+        .expect_status_code(400)
+        .get(&format!("{}?start=2&end=1", path))
+        .await;
+    context
+        .expect_status_code(400)
+        .get(&format!(
+            "{}?order=descending&end=1&cursor={}",
+            path, cursor
+        ))
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_events_by_invalid_account_event_handle_struct_address() {
     let mut context = new_test_context(current_function_name!());
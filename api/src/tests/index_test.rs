@@ -43,6 +43,27 @@ async fn test_health_check() {
     assert_eq!(resp.status(), 200)
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_node_health() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context.get("/-/health").await;
+    assert_eq!(resp["healthy"], json!(true));
+    assert_eq!(resp["failures"], json!([]));
+    assert_eq!(resp["oldest_ledger_version"], json!("0"));
+    assert!(resp["build_info"].is_object());
+
+    // The test node has no peers
+    let resp = context
+        .expect_status_code(503)
+        .get("/-/health?min_connected_peers=1")
+        .await;
+    assert_eq!(resp["healthy"], json!(false));
+    assert_eq!(
+        resp["failures"],
+        json!(["The node has 0 connected peers, fewer than 1"])
+    );
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_openapi_spec() {
     let context = new_test_context(current_function_name!());
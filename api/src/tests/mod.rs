@@ -11,6 +11,7 @@ mod invalid_post_request_test;
 mod modules;
 mod multisig_transactions_test;
 mod objects;
+mod proofs_test;
 mod resource_groups;
 mod secp256k1_ecdsa;
 mod state_test;
@@ -0,0 +1,112 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::mime_types::BCS;
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::AccountResource,
+    epoch_change::EpochChangeProof,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{SparseMerkleProof, TransactionInfoWithProof},
+    state_store::{state_key::StateKey, state_value::StateValue},
+};
+use move_core_types::move_resource::MoveStructType;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+async fn get_bcs<T: DeserializeOwned>(context: &TestContext, path: &str) -> T {
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", BCS)
+        .path(&format!("/v1{}", path));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    bcs::from_bytes(resp.body()).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_verify_transaction_info_and_state_value_proofs() {
+    let mut context = new_test_context(current_function_name!());
+    let mut root_account = context.root_account().await;
+    let account = context.gen_account();
+    let txn = context.create_user_account_by(&mut root_account, &account);
+    context.commit_block(&vec![txn]).await;
+
+    let ledger_info: LedgerInfoWithSignatures = get_bcs(&context, "/proofs/ledger_info").await;
+    let ledger_info = ledger_info.ledger_info();
+    let ledger_version = ledger_info.version();
+
+    // The user transaction is followed by the state checkpoint transaction of the block
+    let txn_version = ledger_version - 1;
+    let proof: TransactionInfoWithProof = get_bcs(
+        &context,
+        &format!(
+            "/proofs/transaction_info/{}?ledger_version={}",
+            txn_version, ledger_version
+        ),
+    )
+    .await;
+    proof.verify(ledger_info, txn_version).unwrap();
+
+    // The state at the end of the block is the state checkpoint of the last transaction
+    let proof: TransactionInfoWithProof = get_bcs(
+        &context,
+        &format!(
+            "/proofs/transaction_info/{}?ledger_version={}",
+            ledger_version, ledger_version
+        ),
+    )
+    .await;
+    proof.verify(ledger_info, ledger_version).unwrap();
+    let state_root = proof
+        .transaction_info()
+        .ensure_state_checkpoint_hash()
+        .unwrap();
+
+    let state_key = StateKey::access_path(
+        AccessPath::resource_access_path(account.address(), AccountResource::struct_tag()).unwrap(),
+    );
+    let req = warp::test::request()
+        .method("POST")
+        .header("Accept", BCS)
+        .path(&format!(
+            "/v1/proofs/state_value?ledger_version={}",
+            ledger_version
+        ))
+        .json(&json!({ "key": hex::encode(bcs::to_bytes(&state_key).unwrap()) }));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let (state_value, proof): (Option<StateValue>, SparseMerkleProof) =
+        bcs::from_bytes(resp.body()).unwrap();
+    assert!(state_value.is_some());
+    proof
+        .verify(state_root, state_key.hash(), state_value.as_ref())
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof() {
+    let context = new_test_context(current_function_name!());
+    let proof: EpochChangeProof = get_bcs(&context, "/proofs/epoch_change?start_epoch=0").await;
+    assert!(!proof.more);
+    assert!(!proof.ledger_info_with_sigs.is_empty());
+    assert!(proof.ledger_info_with_sigs[0].ledger_info().ends_epoch());
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/proofs/epoch_change?start_epoch=100")
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_proof_as_json() {
+    let context = new_test_context(current_function_name!());
+    context
+        .expect_status_code(403)
+        .get("/proofs/ledger_info")
+        .await;
+}
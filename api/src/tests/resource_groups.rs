@@ -91,6 +91,41 @@ async fn test_gen_resource_group() {
     let response = context.gen_resource(&user.address(), &secondary).await;
     assert_eq!(response.unwrap()["data"]["value"], 55);
 
+    // Read the whole resource group
+    let group = format!(
+        "{}::{}::{}",
+        admin0.address(),
+        "primary",
+        "ResourceGroupContainer"
+    );
+    let resp = context
+        .get(&format!(
+            "/accounts/{}/resource_group/{}",
+            user.address(),
+            group
+        ))
+        .await;
+    let resources = resp.as_array().unwrap();
+    assert_eq!(resources.len(), 2);
+    for (name, value) in [
+        ("::primary::Primary", json!("35")),
+        ("::secondary::Secondary", json!(55)),
+    ] {
+        let resource = resources
+            .iter()
+            .find(|resource| resource["type"].as_str().unwrap().ends_with(name))
+            .unwrap();
+        assert_eq!(resource["data"]["value"], value);
+    }
+    context
+        .expect_status_code(404)
+        .get(&format!(
+            "/accounts/{}/resource_group/{}",
+            admin1.address(),
+            group
+        ))
+        .await;
+
     let resp = context
         .get(format!("/accounts/{}/transactions", user.address()).as_str())
         .await;
@@ -298,6 +298,60 @@ async fn test_get_table_item() {
     assert_table_item(ctx, &nested_table, "u8", "u8", 2, 3).await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_items() {
+    let mut context = new_test_context(current_function_name!());
+    let ctx = &mut context;
+    let mut account = ctx.gen_account();
+    let acc = &mut account;
+    let txn = ctx.create_user_account(acc).await;
+    ctx.commit_block(&vec![txn.clone()]).await;
+    make_test_tables(ctx, acc).await;
+
+    let tt = ctx
+        .api_get_account_resource(
+            acc.address(),
+            &acc.address().to_hex_literal(),
+            "TableTestData",
+            "TestTables",
+        )
+        .await["data"]
+        .to_owned();
+
+    let items = |table: &Value| {
+        let handle: AccountAddress = table["handle"].as_str().unwrap().parse().unwrap();
+        format!("/tables/{}/items", handle)
+    };
+    let resp = ctx
+        .post(
+            &items(&tt["u64_table"]),
+            json!({"key_type": "u64", "value_type": "u64"}),
+        )
+        .await;
+    assert_eq!(resp, json!([{"key": "1", "value": "1"}]));
+    let resp = ctx
+        .post(
+            &items(&tt["string_table"]),
+            json!({"key_type": "0x1::string::String", "value_type": "0x1::string::String"}),
+        )
+        .await;
+    assert_eq!(resp, json!([{"key": "abc", "value": "abc"}]));
+
+    // The items whose key isn't of the key type are skipped
+    let resp = ctx
+        .post(
+            &items(&tt["u64_table"]),
+            json!({"key_type": "u128", "value_type": "u64"}),
+        )
+        .await;
+    assert_eq!(resp, json!([]));
+
+    // JSON requires both types
+    ctx.expect_status_code(400)
+        .post(&items(&tt["u64_table"]), json!({"key_type": "u64"}))
+        .await;
+}
+
 fn get_account_resource(address: &str, struct_tag: &str) -> String {
     format!("/accounts/{}/resource/{}", address, struct_tag)
 }
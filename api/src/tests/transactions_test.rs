@@ -4,10 +4,13 @@
 
 use super::new_test_context;
 use crate::tests::new_test_context_with_config;
-use aptos_api_test_context::{assert_json, current_function_name, pretty, TestContext};
+use aptos_api_test_context::{
+    assert_json, current_function_name, pretty, ApiSpecificConfig, TestContext,
+};
+use aptos_api_types::{mime_types, BcsTransactionsPage, PageCursor};
 use aptos_config::config::{GasEstimationStaticOverride, NodeConfig};
 use aptos_crypto::{
-    ed25519::Ed25519PrivateKey,
+    ed25519::{Ed25519PrivateKey, Ed25519Signature},
     multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
     PrivateKey, SigningKey, Uniform,
 };
@@ -27,7 +30,7 @@ use move_core_types::{
 use poem_openapi::types::ParseFromJSON;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use serde_json::json;
-use std::{path::PathBuf, time::Duration};
+use std::{path::PathBuf, str::FromStr, time::Duration};
 use tokio::time::sleep;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -103,6 +106,103 @@ async fn test_get_transactions_with_zero_limit() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_with_cursor() {
+    let mut context = new_test_context(current_function_name!());
+    let mut root_account = context.root_account().await;
+    for _i in 0..5 {
+        let account = context.gen_account();
+        let txn = context.create_user_account_by(&mut root_account, &account);
+        context.commit_block(&vec![txn.clone()]).await;
+    }
+
+    // The cursor points to the version after the page
+    let req = warp::test::request()
+        .method("GET")
+        .path("/v1/transactions?start=0&limit=2");
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let cursor = resp
+        .headers()
+        .get("X-Aptos-Cursor")
+        .expect("Cursor header was missing");
+    let cursor = PageCursor::from_str(cursor.to_str().unwrap()).unwrap();
+    assert_eq!(cursor, PageCursor::Version(2));
+
+    let resp = context
+        .get(&format!("/transactions?cursor={}&limit=2", cursor))
+        .await;
+    assert_eq!(resp[0]["version"], "2");
+    assert_eq!(resp[1]["version"], "3");
+
+    // The cursor can't be given with a start, or be the cursor of another kind of endpoint
+    context
+        .expect_status_code(400)
+        .get(&format!("/transactions?cursor={}&start=2", cursor))
+        .await;
+    context
+        .expect_status_code(400)
+        .get(&format!(
+            "/transactions?cursor={}",
+            PageCursor::SequenceNumber(2)
+        ))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_v2() {
+    let mut context = new_test_context(current_function_name!());
+    let mut root_account = context.root_account().await;
+    for _i in 0..3 {
+        let account = context.gen_account();
+        let txn = context.create_user_account_by(&mut root_account, &account);
+        context.commit_block(&vec![txn.clone()]).await;
+    }
+
+    // The test context only proxies the v1 API, so the v2 API is called directly
+    let address = match context.api_specific_config {
+        ApiSpecificConfig::V1(address) => address,
+    };
+    let url = format!(
+        "http://{}/v2/transactions?cursor={}&limit=2",
+        address,
+        PageCursor::Version(0)
+    );
+    let client = reqwest::Client::new();
+
+    // BCS is returned unless JSON is asked for, with the cursor in the body
+    let resp = client.get(&url).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let page: BcsTransactionsPage = bcs::from_bytes(&resp.bytes().await.unwrap()).unwrap();
+    assert_eq!(page.transactions.len(), 2);
+    assert_eq!(page.transactions[1].version, 1);
+    assert_eq!(page.cursor, PageCursor::Version(2));
+
+    let resp = client
+        .get(&url)
+        .header("Accept", mime_types::JSON)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let page: serde_json::Value = serde_json::from_slice(&resp.bytes().await.unwrap()).unwrap();
+    assert_eq!(page["transactions"][0]["type"], "genesis_transaction");
+    assert_eq!(page["transactions"][1]["version"], "1");
+    assert_eq!(page["cursor"], PageCursor::Version(2).to_string());
+
+    // Each version has its own spec
+    let resp = client
+        .get(format!("http://{}/v2/spec.json", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let spec: serde_json::Value = serde_json::from_slice(&resp.bytes().await.unwrap()).unwrap();
+    assert_eq!(spec["servers"][0]["url"], "/v2");
+    assert!(spec["paths"]["/transactions"].is_object());
+    assert!(spec["paths"]["/accounts/{address}"].is_null());
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 #[ignore]
 async fn test_get_transactions_param_limit_exceeds_limit() {
@@ -143,6 +243,45 @@ async fn test_post_bcs_format_transaction() {
         .await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_submit_and_wait_transaction() {
+    let mut context = new_test_context(current_function_name!());
+
+    // The transaction is still pending at the timeout
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    let resp = context
+        .expect_status_code(202)
+        .post_bcs_txn(
+            "/transactions/submit_and_wait?timeout_secs=0",
+            bcs::to_bytes(&txn).unwrap(),
+        )
+        .await;
+    assert_eq!(resp["type"], "pending_transaction");
+    assert_eq!(resp["hash"], txn.committed_hash().to_hex_literal());
+    context.commit_mempool_txns(1).await;
+
+    // The transaction is returned once it is committed
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    let mut committer = context.clone();
+    let commit = tokio::spawn(async move {
+        sleep(Duration::from_millis(500)).await;
+        committer.commit_mempool_txns(1).await;
+    });
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn(
+            "/transactions/submit_and_wait",
+            bcs::to_bytes(&txn).unwrap(),
+        )
+        .await;
+    commit.await.unwrap();
+    assert_eq!(resp["type"], "user_transaction");
+    assert_eq!(resp["success"], true);
+    assert_eq!(resp["hash"], txn.committed_hash().to_hex_literal());
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_post_invalid_bcs_format_transaction() {
     let mut context = new_test_context(current_function_name!());
@@ -532,6 +671,29 @@ async fn test_get_transaction_by_version() {
     assert_json(resp, txns[0].clone())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transaction_from_response_cache() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let by_version = context.get("/transactions/by_version/2").await;
+    // The transaction is cached by version and by hash
+    let cache = &context.context.response_caches().transactions;
+    assert_eq!(cache.usage().0, 2);
+
+    let by_hash = context
+        .get(&format!(
+            "/transactions/by_hash/{}",
+            by_version["hash"].as_str().unwrap()
+        ))
+        .await;
+    assert_eq!(by_hash, by_version);
+    assert_eq!(context.get("/transactions/by_version/2").await, by_version);
+    assert_eq!(cache.usage().0, 2);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_pending_transaction_by_hash() {
     let mut context = new_test_context(current_function_name!());
@@ -567,6 +729,47 @@ async fn test_get_pending_transaction_by_hash() {
     context.check_golden_output(not_found);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_by_hashes() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+    let committed_hash = txn.committed_hash().to_hex_literal();
+    let committed_txn = context
+        .get(&format!("/transactions/by_hash/{}", committed_hash))
+        .await;
+
+    let other_account = context.gen_account();
+    let txn = context.create_user_account(&other_account).await;
+    let pending_txn = context
+        .expect_status_code(202)
+        .post_bcs_txn("/transactions", bcs::to_bytes(&txn).unwrap())
+        .await;
+    let pending_hash = pending_txn["hash"].as_str().unwrap();
+    let missing_hash = "0xdadfeddcca7cb6396c735e9094c76c6e4e9cb3e3ef814730693aed59bd87b31d";
+
+    let resp = context
+        .post(
+            "/transactions/by_hashes",
+            json!([committed_hash, pending_hash, missing_hash]),
+        )
+        .await;
+    let txns = resp.as_array().unwrap();
+    assert_eq!(txns.len(), 3);
+    assert_eq!(txns[0]["hash"], committed_hash);
+    assert_json(txns[0]["transaction"].clone(), committed_txn);
+    assert_eq!(txns[1]["hash"], pending_hash);
+    assert_eq!(txns[1]["transaction"]["type"], "pending_transaction");
+    assert_eq!(txns[2]["hash"], missing_hash);
+    assert!(txns[2]["transaction"].is_null());
+
+    context
+        .expect_status_code(400)
+        .post("/transactions/by_hashes", json!(vec![missing_hash; 101]))
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_signing_message_with_entry_function_payload() {
     let mut context = new_test_context(current_function_name!());
@@ -742,6 +945,74 @@ async fn test_get_account_transactions_filter_transactions_by_limit() {
     assert_eq!(txns.as_array().unwrap().len(), 2);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_with_filters() {
+    let mut context = new_test_context(current_function_name!());
+    let mut root_account = context.root_account().await;
+    let account = context.gen_account();
+    let create_txn = context.create_user_account_by(&mut root_account, &account);
+    let transfer_txn = context.account_transfer(&mut root_account, &account, 10);
+    // Fails as the root account doesn't have that many coins
+    let failed_txn = context.account_transfer(&mut root_account, &account, u64::MAX);
+    context
+        .commit_block(&vec![create_txn, transfer_txn, failed_txn.clone()])
+        .await;
+
+    let txns = context.get("/transactions?start=0&success=false").await;
+    assert_eq!(txns.as_array().unwrap().len(), 1);
+    assert_eq!(
+        txns[0]["hash"],
+        failed_txn.committed_hash().to_hex_literal()
+    );
+    let txns = context
+        .get("/transactions?start=0&transaction_type=user_transaction")
+        .await;
+    assert_eq!(txns.as_array().unwrap().len(), 3);
+    let txns = context
+        .get("/transactions?start=0&function=0x1::aptos_account")
+        .await;
+    assert_eq!(txns.as_array().unwrap().len(), 3);
+    let txns = context
+        .get("/transactions?start=0&function=0x1::aptos_account::transfer&success=true")
+        .await;
+    assert_eq!(txns.as_array().unwrap().len(), 1);
+    assert_eq!(
+        txns[0]["payload"]["function"],
+        "0x1::aptos_account::transfer"
+    );
+
+    let path = format!("/accounts/{}/transactions", root_account.address());
+    let txns = context
+        .get(&format!(
+            "{}?function=0x1::aptos_account::create_account",
+            path
+        ))
+        .await;
+    assert_eq!(txns.as_array().unwrap().len(), 1);
+    let version: u64 = txns[0]["version"].as_str().unwrap().parse().unwrap();
+    let txns = context
+        .get(&format!("{}?min_version={}", path, version + 1))
+        .await;
+    assert_eq!(txns.as_array().unwrap().len(), 2);
+    let txns = context
+        .get(&format!(
+            "{}?min_version={}&success=true",
+            path,
+            version + 1
+        ))
+        .await;
+    assert_eq!(txns.as_array().unwrap().len(), 1);
+
+    context
+        .expect_status_code(400)
+        .get("/transactions?transaction_type=pending_transaction")
+        .await;
+    context
+        .expect_status_code(400)
+        .get(&format!("{}?function=0x1", path))
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_txn_execute_failed_by_invalid_script_payload_bytecode() {
     let context = new_test_context(current_function_name!());
@@ -1372,6 +1643,137 @@ async fn test_gas_estimation_static_override() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_gas_estimation_percentiles() {
+    let context = new_test_context(current_function_name!());
+
+    // Without full blocks, every percentile is the estimate
+    let resp = context
+        .get("/estimate_gas_price?percentiles=50,90&block_history=10")
+        .await;
+    let percentiles = resp["percentiles"].as_array().unwrap();
+    assert_eq!(percentiles.len(), 2);
+    for (percentile, expected) in percentiles.iter().zip([50, 90]) {
+        assert_eq!(percentile["percentile"], expected);
+        assert_eq!(percentile["gas_unit_price"], resp["gas_estimate"]);
+    }
+
+    let resp = context.get("/estimate_gas_price").await;
+    assert!(resp.get("percentiles").is_none());
+    context
+        .expect_status_code(400)
+        .get("/estimate_gas_price?percentiles=50,101")
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_estimate_gas_price_for_transaction() {
+    let mut context = new_test_context(current_function_name!());
+    let mut account = context.gen_account();
+    let receiver = context.gen_account();
+    let txn = context.mint_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+
+    // Estimating the gas of a transaction requires an invalid signature, like simulating it
+    let txn = context.account_transfer(&mut account, &receiver, 100);
+    let txn = SignedTransaction::new(
+        txn.into_raw_transaction(),
+        account.public_key().clone(),
+        Ed25519Signature::dummy_signature(),
+    );
+    let resp = context
+        .post_bcs_txn("/estimate_gas_price", bcs::to_bytes(&txn).unwrap())
+        .await;
+    let gas_used = resp["gas_used"].as_u64().unwrap();
+    assert!(gas_used > 0);
+    // The default safety margin is 20%
+    assert_eq!(
+        resp["max_gas_amount"].as_u64().unwrap(),
+        (gas_used * 120 + 99) / 100
+    );
+    assert_eq!(
+        resp["gas_unit_price"],
+        context.get("/estimate_gas_price").await["gas_estimate"]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulation_with_state_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let receiver = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+
+    let payload = json!({
+        "type": "entry_function_payload",
+        "function": "0x1::aptos_account::transfer",
+        "type_arguments": [],
+        "arguments": [receiver.address().to_hex_literal(), "1000"]
+    });
+    let resp = context
+        .simulate_transaction(&account, payload.clone(), 200)
+        .await;
+    assert!(!resp[0]["success"].as_bool().unwrap(), "{}", pretty(&resp));
+
+    let resp = context
+        .simulate_transaction_with_state_overrides(
+            &account,
+            payload.clone(),
+            json!({
+                "balances": [{
+                    "address": account.address().to_hex_literal(),
+                    "amount": "1000"
+                }]
+            }),
+            200,
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().unwrap(), "{}", pretty(&resp));
+    // The overrides aren't persisted
+    assert_eq!(context.get_apt_balance(account.address()).await, 0);
+
+    // The transaction expires before the overridden time
+    let resp = context
+        .simulate_transaction_with_state_overrides(
+            &account,
+            payload.clone(),
+            json!({
+                "balances": [{
+                    "address": account.address().to_hex_literal(),
+                    "amount": "1000"
+                }],
+                "timestamp_usecs": "16373698888889000000"
+            }),
+            200,
+        )
+        .await;
+    assert!(!resp[0]["success"].as_bool().unwrap(), "{}", pretty(&resp));
+    assert!(
+        resp[0]["vm_status"]
+            .as_str()
+            .unwrap()
+            .contains("TRANSACTION_EXPIRED"),
+        "{}",
+        pretty(&resp)
+    );
+
+    // The receiver has no coin store to override the balance of
+    context
+        .simulate_transaction_with_state_overrides(
+            &account,
+            payload,
+            json!({
+                "balances": [{
+                    "address": receiver.address().to_hex_literal(),
+                    "amount": "1000"
+                }]
+            }),
+            400,
+        )
+        .await;
+}
+
 fn gen_string(len: u64) -> String {
     let mut rng = thread_rng();
     std::iter::repeat(())
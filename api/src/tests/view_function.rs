@@ -3,6 +3,8 @@
 
 use super::new_test_context;
 use aptos_api_test_context::current_function_name;
+use aptos_api_types::{mime_types::BCS, ViewResponseBcs};
+use move_core_types::language_storage::TypeTag;
 use serde_json::json;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -79,6 +81,100 @@ async fn test_versioned_simple_view() {
     context.check_golden_output_no_prune(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let balance_request = json!({
+        "function":"0x1::coin::balance",
+        "arguments": vec![owner.address().to_string()],
+        "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+    });
+    let resp = context
+        .post(
+            "/view/batch",
+            json!([
+                balance_request,
+                {
+                    "function":"0x1::coin::symbol",
+                    "arguments": [],
+                    "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+                },
+            ]),
+        )
+        .await;
+    assert_eq!(resp, json!([["100000"], ["APT"]]));
+
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/view/batch",
+            json!([
+                balance_request,
+                {
+                    "function":"0x1::aptos_account::assert_account_exists",
+                    "arguments": vec![owner.address().to_string()],
+                    "type_arguments": [],
+                },
+            ]),
+        )
+        .await;
+    assert!(resp["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("View function request 1 failed"));
+
+    context
+        .expect_status_code(400)
+        .post("/view/batch", json!(vec![balance_request; 51]))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_bcs() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let balance_request = json!({
+        "function":"0x1::coin::balance",
+        "arguments": vec![owner.address().to_string()],
+        "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+    });
+    let req = warp::test::request()
+        .method("POST")
+        .header("Accept", BCS)
+        .path("/v1/view")
+        .json(&balance_request);
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let output: ViewResponseBcs = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(output, ViewResponseBcs {
+        return_types: vec![TypeTag::U64],
+        values: vec![bcs::to_bytes(&100_000u64).unwrap()],
+    });
+
+    let req = warp::test::request()
+        .method("POST")
+        .header("Accept", BCS)
+        .path("/v1/view/batch")
+        .json(&json!([balance_request, balance_request]));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let outputs: Vec<ViewResponseBcs> = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(outputs, vec![output.clone(), output]);
+}
+
 #[ignore] // TODO: reactivate with real source
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_view_tuple() {
@@ -0,0 +1,180 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Filters the committed transactions of a page on the server, so that the clients looking for
+//! a few of them don't download and discard the rest.
+
+use anyhow::{bail, format_err, Context as AnyhowContext, Result};
+use aptos_api_types::TransactionOnChainData;
+use aptos_types::transaction::{
+    EntryFunction, MultisigTransactionPayload, Transaction, TransactionPayload,
+};
+use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+
+/// The types of committed transactions, named like the `type` of their JSON
+const TRANSACTION_TYPES: [&str; 4] = [
+    "user_transaction",
+    "genesis_transaction",
+    "block_metadata_transaction",
+    "state_checkpoint_transaction",
+];
+
+fn transaction_type(transaction: &Transaction) -> &'static str {
+    match transaction {
+        Transaction::UserTransaction(_) => TRANSACTION_TYPES[0],
+        Transaction::GenesisTransaction(_) => TRANSACTION_TYPES[1],
+        Transaction::BlockMetadata(_) => TRANSACTION_TYPES[2],
+        Transaction::StateCheckpoint(_) => TRANSACTION_TYPES[3],
+    }
+}
+
+/// The entry function called by a user transaction, directly or through a multisig account
+fn entry_function(transaction: &Transaction) -> Option<&EntryFunction> {
+    match transaction.try_as_signed_user_txn()?.payload() {
+        TransactionPayload::EntryFunction(entry_function) => Some(entry_function),
+        TransactionPayload::Multisig(multisig) => match &multisig.transaction_payload {
+            Some(MultisigTransactionPayload::EntryFunction(entry_function)) => Some(entry_function),
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct TransactionFilter {
+    success: Option<bool>,
+    transaction_type: Option<&'static str>,
+    module: Option<(AccountAddress, Identifier)>,
+    function: Option<Identifier>,
+    min_version: Option<u64>,
+}
+
+impl TransactionFilter {
+    /// `function` is either `<address>::<module>` or `<address>::<module>::<function>`
+    pub fn new(
+        success: Option<bool>,
+        transaction_type: Option<&str>,
+        function: Option<&str>,
+        min_version: Option<u64>,
+    ) -> Result<Self> {
+        let transaction_type = transaction_type
+            .map(|transaction_type| {
+                TRANSACTION_TYPES
+                    .iter()
+                    .find(|name| **name == transaction_type)
+                    .copied()
+                    .ok_or_else(|| {
+                        format_err!(
+                            "Invalid transaction_type {}, expected one of {}",
+                            transaction_type,
+                            TRANSACTION_TYPES.join(", ")
+                        )
+                    })
+            })
+            .transpose()?;
+        let (module, function) = match function {
+            Some(function) => {
+                let parts: Vec<_> = function.split("::").collect();
+                let (address, module, name) = match parts.as_slice() {
+                    [address, module] => (address, module, None),
+                    [address, module, name] => (address, module, Some(name)),
+                    _ => bail!(
+                        "Invalid function {}, expected <address>::<module> or <address>::<module>::<function>",
+                        function
+                    ),
+                };
+                let module = (
+                    AccountAddress::from_hex_literal(address)
+                        .context("Invalid address of the function")?,
+                    Identifier::new(*module).context("Invalid module of the function")?,
+                );
+                let name = name
+                    .map(|name| Identifier::new(*name))
+                    .transpose()
+                    .context("Invalid name of the function")?;
+                (Some(module), name)
+            },
+            None => (None, None),
+        };
+        Ok(Self {
+            success,
+            transaction_type,
+            module,
+            function,
+            min_version,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn matches(&self, txn: &TransactionOnChainData) -> bool {
+        if let Some(success) = self.success {
+            if txn.info.status().is_success() != success {
+                return false;
+            }
+        }
+        if let Some(transaction_type) = self.transaction_type {
+            if self::transaction_type(&txn.transaction) != transaction_type {
+                return false;
+            }
+        }
+        if let Some((address, module)) = &self.module {
+            let entry_function = match entry_function(&txn.transaction) {
+                Some(entry_function) => entry_function,
+                None => return false,
+            };
+            if entry_function.module().address() != address
+                || entry_function.module().name() != module.as_ident_str()
+            {
+                return false;
+            }
+            if let Some(function) = &self.function {
+                if entry_function.function() != function.as_ident_str() {
+                    return false;
+                }
+            }
+        }
+        if let Some(min_version) = self.min_version {
+            if txn.version < min_version {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Keeps the transactions of a page that match the filter
+    pub fn apply(&self, data: Vec<TransactionOnChainData>) -> Vec<TransactionOnChainData> {
+        if self.is_empty() {
+            return data;
+        }
+        data.into_iter().filter(|txn| self.matches(txn)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_filter() {
+        assert!(TransactionFilter::new(None, None, None, None)
+            .unwrap()
+            .is_empty());
+        assert!(TransactionFilter::new(None, Some("user_transaction"), None, None).is_ok());
+        assert!(TransactionFilter::new(None, Some("pending_transaction"), None, None).is_err());
+        assert!(TransactionFilter::new(None, None, Some("0x1::coin"), None).is_ok());
+        assert!(TransactionFilter::new(None, None, Some("0x1::coin::transfer"), None).is_ok());
+        assert!(TransactionFilter::new(None, None, Some("0x1"), None).is_err());
+        assert!(TransactionFilter::new(None, None, Some("0x1::coin::transfer::x"), None).is_err());
+        assert!(TransactionFilter::new(None, None, Some("coin::transfer"), None).is_err());
+
+        let filter = TransactionFilter::new(None, None, Some("0x1::coin::transfer"), None).unwrap();
+        assert_eq!(
+            filter.module,
+            Some((AccountAddress::ONE, Identifier::new("coin").unwrap()))
+        );
+        assert_eq!(filter.function, Some(Identifier::new("transfer").unwrap()));
+    }
+}
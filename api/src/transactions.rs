@@ -12,23 +12,32 @@ use crate::{
     page::Page,
     response::{
         api_disabled, api_forbidden, transaction_not_found_by_hash,
-        transaction_not_found_by_version, version_pruned, BadRequestError, BasicError,
-        BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
+        transaction_not_found_by_version, BadRequestError, BasicError, BasicErrorWith404,
+        BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
         InsufficientStorageError, InternalError,
     },
+    response_cache::{CachedResponse, TransactionKey},
+    state_overrides::{state_override_values, OverriddenStateView},
+    transaction_filter::TransactionFilter,
     ApiTags,
 };
 use anyhow::{anyhow, Context as AnyhowContext};
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
-    AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
-    Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
+    AptosErrorDetails, AsConverter, BcsTransactionByHash, EncodeSubmissionRequest, GasEstimation,
+    GasEstimationBcs, HashValue, HexEncodedBytes, LedgerInfo, MoveType, PageCursor,
+    PendingTransaction, SequenceNumberTooOldDetails, SimulateTransactionRequest, StateOverrides,
+    SubmitTransactionRequest, Transaction, TransactionByHash, TransactionData,
+    TransactionGasEstimation, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
     MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
+use aptos_gas_meter::{AptosGasMeter, StandardGasAlgebra, StandardGasMeter};
+use aptos_gas_profiling::GasProfiler;
+use aptos_memory_usage_tracker::MemoryTrackedGasMeter;
 use aptos_types::{
+    account_address::AccountAddress,
     account_config::CoinStoreResource,
     account_view::AccountView,
     mempool_status::MempoolStatusCode,
@@ -44,7 +53,11 @@ use poem_openapi::{
     payload::Json,
     ApiRequest, OpenApi,
 };
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 generate_success_response!(SubmitTransactionResponse, (202, Accepted));
 
@@ -61,6 +74,14 @@ generate_error_response!(
 type SubmitTransactionResult<T> =
     poem::Result<SubmitTransactionResponse<T>, SubmitTransactionError>;
 
+// 200 once the transaction is committed, or 202 if it is still pending at the timeout
+generate_success_response!(SubmitAndWaitResponse, (200, Ok), (202, Accepted));
+
+type SubmitAndWaitResult<T> = poem::Result<SubmitAndWaitResponse<T>, SubmitTransactionError>;
+
+/// How often `/transactions/submit_and_wait` checks if the transaction is committed
+const SUBMIT_AND_WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 generate_success_response!(
     SubmitTransactionsBatchResponse,
     (202, Accepted),
@@ -99,6 +120,40 @@ impl VerifyInput for SubmitTransactionPost {
     }
 }
 
+// We need a custom type here because we use different types for each of the
+// content types possible for the POST data.
+#[derive(ApiRequest, Debug)]
+pub enum SimulateTransactionPost {
+    #[oai(content_type = "application/json")]
+    Json(Json<SimulateTransactionRequest>),
+
+    #[oai(content_type = "application/x.aptos.signed_transaction+bcs")]
+    Bcs(Bcs),
+}
+
+impl VerifyInput for SimulateTransactionPost {
+    fn verify(&self) -> anyhow::Result<()> {
+        match self {
+            SimulateTransactionPost::Json(inner) => inner.0.verify(),
+            SimulateTransactionPost::Bcs(_) => Ok(()),
+        }
+    }
+}
+
+impl SimulateTransactionPost {
+    /// Splits the request into the transaction and the state overrides, which are only
+    /// supported in JSON
+    fn into_parts(self) -> (SubmitTransactionPost, Option<StateOverrides>) {
+        match self {
+            SimulateTransactionPost::Json(inner) => (
+                SubmitTransactionPost::Json(Json(inner.0.submit_transaction_request)),
+                inner.0.state_overrides,
+            ),
+            SimulateTransactionPost::Bcs(data) => (SubmitTransactionPost::Bcs(data), None),
+        }
+    }
+}
+
 // We need a custom type here because we use different types for each of the
 // content types possible for the POST data.
 #[derive(ApiRequest, Debug)]
@@ -141,6 +196,10 @@ impl TransactionsApi {
     ///
     /// If the version has been pruned, then a 410 will be returned.
     ///
+    /// The filters apply to the transactions of the page, so a filtered page can
+    /// have fewer transactions than the limit, or none. The next page is given by
+    /// the cursor in the X-Aptos-Cursor header.
+    ///
     /// To retrieve a pending transaction, use /transactions/by_hash.
     #[oai(
         path = "/transactions",
@@ -155,20 +214,46 @@ impl TransactionsApi {
         ///
         /// If not provided, defaults to showing the latest transactions
         start: Query<Option<U64>>,
+        /// Cursor returned in the X-Aptos-Cursor header of the previous page,
+        /// to get the next page. Can't be given with `start`.
+        ///
+        /// Unlike a start computed by the client, the cursor stays valid while
+        /// transactions are committed, and a 410 is returned once it is pruned.
+        cursor: Query<Option<PageCursor>>,
         /// Max number of transactions to retrieve.
         ///
         /// If not provided, defaults to default page size
         limit: Query<Option<u16>>,
+        /// Only the transactions that succeeded if true, or failed if false
+        success: Query<Option<bool>>,
+        /// Only the transactions of this type, e.g. `user_transaction`
+        transaction_type: Query<Option<String>>,
+        /// Only the transactions calling an entry function of this module, e.g.
+        /// `0x1::coin`, or this entry function, e.g. `0x1::coin::transfer`
+        function: Query<Option<String>>,
+        /// Only the transactions from this ledger version
+        min_version: Query<Option<U64>>,
     ) -> BasicResultWith404<Vec<Transaction>> {
         fail_point_poem("endpoint_get_transactions")?;
         self.context
             .check_api_output_enabled("Get transactions", &accept_type)?;
-        let page = Page::new(
+        let page = Page::new_with_cursor::<BasicErrorWith404>(
             start.0.map(|v| v.0),
+            cursor.0,
+            PageCursor::version,
             limit.0,
             self.context.max_transactions_page_size(),
-        );
-        self.list(&accept_type, page)
+        )?;
+        let filter = TransactionFilter::new(
+            success.0,
+            transaction_type.0.as_deref(),
+            function.0.as_deref(),
+            min_version.0.map(|v| v.0),
+        )
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+        })?;
+        self.list(&accept_type, page, &filter)
     }
 
     /// Get transaction by hash
@@ -205,6 +290,33 @@ impl TransactionsApi {
             .await
     }
 
+    /// Get transactions by hashes
+    ///
+    /// Look up a batch of transactions by their hashes like /transactions/by_hash,
+    /// and return them in the order of the hashes. Each transaction is the committed
+    /// transaction, the pending transaction if it is still in the mempool, or null if
+    /// it isn't found, so a batch doesn't fail because of a missing transaction.
+    ///
+    /// With BCS, the transactions are returned as `BcsTransactionByHash`s.
+    #[oai(
+        path = "/transactions/by_hashes",
+        method = "post",
+        operation_id = "get_transactions_by_hashes",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_transactions_by_hashes(
+        &self,
+        accept_type: AcceptType,
+        /// Hashes of the transactions to retrieve
+        hashes: Json<Vec<HashValue>>,
+    ) -> BasicResult<Vec<TransactionByHash>> {
+        fail_point_poem("endpoint_transactions_by_hashes")?;
+        self.context
+            .check_api_output_enabled("Get transactions by hashes", &accept_type)?;
+        self.get_transactions_by_hashes_inner(&accept_type, hashes.0)
+            .await
+    }
+
     /// Get transaction by version
     ///
     /// Retrieves a transaction by a given version. If the version has been
@@ -235,6 +347,10 @@ impl TransactionsApi {
     ///
     /// If no start version is given, it will start at version 0.
     ///
+    /// The filters apply to the transactions of the page, so a filtered page can
+    /// have fewer transactions than the limit, or none. The next page is given by
+    /// the cursor in the X-Aptos-Cursor header.
+    ///
     /// To retrieve a pending transaction, use /transactions/by_hash.
     #[oai(
         path = "/accounts/:address/transactions",
@@ -251,20 +367,96 @@ impl TransactionsApi {
         ///
         /// If not provided, defaults to showing the latest transactions
         start: Query<Option<U64>>,
+        /// Cursor returned in the X-Aptos-Cursor header of the previous page,
+        /// to get the next page. Can't be given with `start`.
+        cursor: Query<Option<PageCursor>>,
         /// Max number of transactions to retrieve.
         ///
         /// If not provided, defaults to default page size
         limit: Query<Option<u16>>,
+        /// Only the transactions that succeeded if true, or failed if false
+        success: Query<Option<bool>>,
+        /// Only the transactions of this type, e.g. `user_transaction`
+        transaction_type: Query<Option<String>>,
+        /// Only the transactions calling an entry function of this module, e.g.
+        /// `0x1::coin`, or this entry function, e.g. `0x1::coin::transfer`
+        function: Query<Option<String>>,
+        /// Only the transactions from this ledger version
+        min_version: Query<Option<U64>>,
     ) -> BasicResultWith404<Vec<Transaction>> {
         fail_point_poem("endpoint_get_accounts_transactions")?;
         self.context
             .check_api_output_enabled("Get account transactions", &accept_type)?;
-        let page = Page::new(
+        let page = Page::new_with_cursor::<BasicErrorWith404>(
             start.0.map(|v| v.0),
+            cursor.0,
+            PageCursor::sequence_number,
             limit.0,
             self.context.max_transactions_page_size(),
-        );
-        self.list_by_account(&accept_type, page, address.0)
+        )?;
+        let filter = TransactionFilter::new(
+            success.0,
+            transaction_type.0.as_deref(),
+            function.0.as_deref(),
+            min_version.0.map(|v| v.0),
+        )
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+        })?;
+        self.list_by_account(&accept_type, page, address.0, &filter)
+    }
+
+    /// Get account transaction history
+    ///
+    /// Retrieves on-chain committed transactions that were sent by an account,
+    /// signed by it as a secondary signer or fee payer, or that changed its
+    /// resources or modules, in order of version. This requires the account
+    /// transaction index to be enabled on the node, otherwise a 403 will be
+    /// returned. If the start version is too far in the past, a 410 will be
+    /// returned.
+    ///
+    /// If no start version is given, it will start at the oldest version
+    /// available.
+    #[oai(
+        path = "/accounts/:address/transaction_history",
+        method = "get",
+        operation_id = "get_account_transaction_history",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_account_transaction_history(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Ledger version to start list of transactions
+        ///
+        /// If not provided, defaults to the oldest version available
+        start: Query<Option<U64>>,
+        /// Cursor returned in the X-Aptos-Cursor header of the previous page,
+        /// to get the next page. Can't be given with `start`.
+        ///
+        /// Unlike a start computed by the client, the cursor stays valid while
+        /// transactions are committed, and a 410 is returned once it is pruned.
+        cursor: Query<Option<PageCursor>>,
+        /// Max number of transactions to retrieve.
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        fail_point_poem("endpoint_get_account_transaction_history")?;
+        self.context
+            .check_api_output_enabled("Get account transaction history", &accept_type)?;
+        if !self.context.db.account_transaction_index_enabled() {
+            return Err(api_disabled("Get account transaction history"));
+        }
+        let page = Page::new_with_cursor::<BasicErrorWith404>(
+            start.0.map(|v| v.0),
+            cursor.0,
+            PageCursor::version,
+            limit.0,
+            self.context.max_transactions_page_size(),
+        )?;
+        self.list_history_by_account(&accept_type, page, address.0)
     }
 
     /// Submit transaction
@@ -317,6 +509,57 @@ impl TransactionsApi {
             .await
     }
 
+    /// Submit transaction and wait
+    ///
+    /// Submits a transaction like /transactions, then holds the connection until
+    /// the transaction is committed and returns it, instead of polling
+    /// /transactions/by_hash for it.
+    ///
+    /// If the transaction isn't committed before the timeout, a 202 is returned
+    /// with the pending transaction, which can still be looked up by its hash.
+    /// A committed transaction is returned whether it succeeded or not, so check
+    /// its `success` field.
+    #[oai(
+        path = "/transactions/submit_and_wait",
+        method = "post",
+        operation_id = "submit_and_wait_transaction",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn submit_and_wait_transaction(
+        &self,
+        accept_type: AcceptType,
+        data: SubmitTransactionPost,
+        /// Seconds to wait for the transaction to be committed.
+        ///
+        /// If not provided, or higher than the limit of the node, the limit of
+        /// the node is used
+        timeout_secs: Query<Option<u64>>,
+    ) -> SubmitAndWaitResult<Transaction> {
+        data.verify()
+            .context("Submitted transaction invalid'")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_submit_and_wait_transaction")?;
+        if !self.context.node_config.api.transaction_submission_enabled {
+            return Err(api_disabled("Submit and wait transaction"));
+        }
+        self.context
+            .check_api_output_enabled("Submit and wait transaction", &accept_type)?;
+        let max_timeout_secs = self.context.max_submit_and_wait_timeout_secs();
+        let timeout =
+            Duration::from_secs(timeout_secs.0.map_or(max_timeout_secs, |timeout_secs| {
+                timeout_secs.min(max_timeout_secs)
+            }));
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let signed_transaction = self.get_signed_transaction(&ledger_info, data)?;
+        self.create_and_wait(&accept_type, &ledger_info, signed_transaction, timeout)
+            .await
+    }
+
     /// Submit batch transactions
     ///
     /// This allows you to submit multiple transactions.  The response has three outcomes:
@@ -390,10 +633,16 @@ impl TransactionsApi {
     ///
     /// To use this, you must:
     /// - Create a SignedTransaction with a zero-padded signature.
-    /// - Submit a SubmitTransactionRequest containing a UserTransactionRequest containing that signature.
+    /// - Submit a SimulateTransactionRequest containing a UserTransactionRequest containing that signature.
+    ///
+    /// The JSON request can also override the state the transaction is simulated on,
+    /// by replacing resources, coin balances, and the current time of the chain. This
+    /// previews what the transaction would do in a different state, e.g. with a
+    /// balance the account doesn't have yet. The overrides are never persisted.
     ///
     /// To use this endpoint with BCS, you must submit a SignedTransaction
     /// encoded as BCS. See SignedTransaction in types/src/transaction/mod.rs.
+    /// State overrides aren't supported with BCS.
     #[oai(
         path = "/transactions/simulate",
         method = "post",
@@ -412,7 +661,11 @@ impl TransactionsApi {
         /// If set to true, the transaction will use a higher price than the original
         /// estimate.
         estimate_prioritized_gas_unit_price: Query<Option<bool>>,
-        data: SubmitTransactionPost,
+        /// If set to true, the transaction will be simulated under the gas profiler
+        /// and the resulting gas log will be returned in the `gas_profile` field.
+        /// Only supported for JSON output.
+        profile_gas: Query<Option<bool>>,
+        data: SimulateTransactionPost,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         data.verify()
             .context("Simulated transaction invalid")
@@ -428,7 +681,15 @@ impl TransactionsApi {
         }
         self.context
             .check_api_output_enabled("Simulate transaction", &accept_type)?;
+        let profile_gas = profile_gas.0.unwrap_or_default();
+        if profile_gas && accept_type == AcceptType::Bcs {
+            return Err(SubmitTransactionError::bad_request_with_code_no_info(
+                "Gas profiling is not supported for BCS output",
+                AptosErrorCode::BcsNotSupported,
+            ));
+        }
         let ledger_info = self.context.get_latest_ledger_info()?;
+        let (data, state_overrides) = data.into_parts();
         let mut signed_transaction = self.get_signed_transaction(&ledger_info, data)?;
 
         let estimated_gas_unit_price = match (
@@ -450,64 +711,9 @@ impl TransactionsApi {
 
         // If estimate max gas amount is provided, we will just make it the maximum value
         let estimated_max_gas_amount = if estimate_max_gas_amount.0.unwrap_or_default() {
-            // Retrieve max possible gas units
-            let (_, gas_params) = self.context.get_gas_schedule(&ledger_info)?;
-            let min_number_of_gas_units = u64::from(gas_params.vm.txn.min_transaction_gas_units)
-                / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
-            let max_number_of_gas_units = u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
-
-            // Retrieve account balance to determine max gas available
-            let account_state = self
-                .context
-                .get_account_state(
-                    signed_transaction.sender(),
-                    ledger_info.version(),
-                    &ledger_info,
-                )?
-                .ok_or_else(|| {
-                    SubmitTransactionError::bad_request_with_code(
-                        "Account not found",
-                        AptosErrorCode::InvalidInput,
-                        &ledger_info,
-                    )
-                })?;
-            let coin_store: CoinStoreResource = account_state
-                .get_coin_store_resource()
-                .and_then(|inner| {
-                    inner.ok_or_else(|| {
-                        anyhow!(
-                            "No coin store found for account {}",
-                            signed_transaction.sender()
-                        )
-                    })
-                })
-                .map_err(|err| {
-                    SubmitTransactionError::internal_with_code(
-                        format!("Failed to get coin store resource {}", err),
-                        AptosErrorCode::InternalError,
-                        &ledger_info,
-                    )
-                })?;
-
             let gas_unit_price =
                 estimated_gas_unit_price.unwrap_or_else(|| signed_transaction.gas_unit_price());
-
-            // With 0 gas price, we set it to max gas units, since we can't divide by 0
-            let max_account_gas_units = if gas_unit_price == 0 {
-                coin_store.coin()
-            } else {
-                coin_store.coin() / gas_unit_price
-            };
-
-            // To give better error messaging, we should not go below the minimum number of gas units
-            let max_account_gas_units =
-                std::cmp::max(min_number_of_gas_units, max_account_gas_units);
-
-            // Minimum of the max account and the max total needs to be used for estimation
-            Some(std::cmp::min(
-                max_account_gas_units,
-                max_number_of_gas_units,
-            ))
+            Some(self.max_gas_amount(&ledger_info, signed_transaction.sender(), gas_unit_price)?)
         } else {
             None
         };
@@ -521,8 +727,14 @@ impl TransactionsApi {
             );
         }
 
-        self.simulate(&accept_type, ledger_info, signed_transaction)
-            .await
+        self.simulate(
+            &accept_type,
+            ledger_info,
+            signed_transaction,
+            state_overrides,
+            profile_gas,
+        )
+        .await
     }
 
     /// Encode submission
@@ -587,18 +799,56 @@ impl TransactionsApi {
     /// (aggressive). Using a more aggressive value increases the likelihood that the transaction
     /// will make it into the next block; more aggressive values are computed with a larger history
     /// and higher percentile statistics. More details are in AIP-34.
+    ///
+    /// The gas unit prices at other percentiles of the recent blocks can be requested with
+    /// `percentiles`, and are returned in the `percentiles` field of the JSON output.
     #[oai(
         path = "/estimate_gas_price",
         method = "get",
         operation_id = "estimate_gas_price",
         tag = "ApiTags::Transactions"
     )]
-    async fn estimate_gas_price(&self, accept_type: AcceptType) -> BasicResult<GasEstimation> {
+    async fn estimate_gas_price(
+        &self,
+        accept_type: AcceptType,
+        /// Comma separated percentiles, from 0 to 100, to give the gas unit prices at, e.g. `50,90,99`
+        percentiles: Query<Option<String>>,
+        /// Number of the latest blocks the percentiles are computed over
+        ///
+        /// If not provided, or larger than the block history of the node, it will be
+        /// the block history of the node used for the prioritized estimate
+        block_history: Query<Option<u16>>,
+    ) -> BasicResult<GasEstimation> {
         fail_point_poem("endpoint_encode_submission")?;
         self.context
             .check_api_output_enabled("Estimate gas price", &accept_type)?;
         let latest_ledger_info = self.context.get_latest_ledger_info()?;
-        let gas_estimation = self.context.estimate_gas_price(&latest_ledger_info)?;
+        let gas_estimation = match percentiles.0 {
+            Some(percentiles) => {
+                let percentiles = parse_percentiles(&percentiles).map_err(|err| {
+                    BasicError::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        &latest_ledger_info,
+                    )
+                })?;
+                let max_block_history = self
+                    .context
+                    .node_config
+                    .api
+                    .gas_estimation
+                    .aggressive_block_history;
+                let block_history = block_history.0.map_or(max_block_history, |block_history| {
+                    std::cmp::min(block_history as usize, max_block_history)
+                });
+                self.context.estimate_gas_price_percentiles(
+                    &latest_ledger_info,
+                    block_history,
+                    &percentiles,
+                )?
+            },
+            None => self.context.estimate_gas_price(&latest_ledger_info)?,
+        };
 
         match accept_type {
             AcceptType::Json => BasicResponse::try_from_json((
@@ -618,29 +868,79 @@ impl TransactionsApi {
             },
         }
     }
+
+    /// Estimate gas of a transaction
+    ///
+    /// Simulates the transaction with the current estimate for the gas unit price, and gives
+    /// the gas it used, with a suggested max gas amount that adds a safety margin to it. The
+    /// transaction is submitted the same way as to the simulate endpoint, with an invalid
+    /// signature, and its max gas amount and gas unit price are ignored.
+    ///
+    /// If the simulated transaction fails, a 400 is returned with the reason.
+    #[oai(
+        path = "/estimate_gas_price",
+        method = "post",
+        operation_id = "estimate_gas_price_for_transaction",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn estimate_gas_price_for_transaction(
+        &self,
+        accept_type: AcceptType,
+        data: SubmitTransactionPost,
+    ) -> SimulateTransactionResult<TransactionGasEstimation> {
+        data.verify()
+            .context("Transaction to estimate the gas of invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_estimate_gas_price_for_transaction")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Estimate gas price for transaction"));
+        }
+        self.context
+            .check_api_output_enabled("Estimate gas price for transaction", &accept_type)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let signed_transaction = self.get_signed_transaction(&ledger_info, data)?;
+
+        let gas_unit_price = self.context.estimate_gas_price(&ledger_info)?.gas_estimate;
+        let max_gas_amount =
+            self.max_gas_amount(&ledger_info, signed_transaction.sender(), gas_unit_price)?;
+        let signed_transaction = override_gas_parameters(
+            &signed_transaction,
+            Some(max_gas_amount),
+            Some(gas_unit_price),
+        );
+        self.estimate_gas(&accept_type, ledger_info, signed_transaction)
+    }
 }
 
 impl TransactionsApi {
     /// List all transactions paging by ledger version
-    fn list(&self, accept_type: &AcceptType, page: Page) -> BasicResultWith404<Vec<Transaction>> {
+    /// List the transactions of a page that match the filter. The cursor of the next page is after
+    /// all the transactions of the page, including the ones filtered out.
+    fn list(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+        filter: &TransactionFilter,
+    ) -> BasicResultWith404<Vec<Transaction>> {
         let latest_ledger_info = self.context.get_latest_ledger_info()?;
-        let ledger_version = latest_ledger_info.version();
-
-        let limit = page.limit(&latest_ledger_info)?;
-        let start_version = page.compute_start(limit, ledger_version, &latest_ledger_info)?;
-        let data = self
+        let (start_version, data) = self
             .context
-            .get_transactions(start_version, limit, ledger_version)
-            .context("Failed to read raw transactions from storage")
-            .map_err(|err| {
-                BasicErrorWith404::internal_with_code(
-                    err,
-                    AptosErrorCode::InternalError,
-                    &latest_ledger_info,
-                )
-            })?;
+            .get_transactions_page(&page, &latest_ledger_info)?;
+        let next_cursor = PageCursor::Version(start_version + data.len() as u64);
 
         match accept_type {
+            // The filtered transactions skip the block metadata they take their timestamp from
+            AcceptType::Json if !filter.is_empty() => BasicResponse::try_from_json((
+                self.context
+                    .render_transactions_non_sequential(&latest_ledger_info, filter.apply(data))?,
+                &latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
             AcceptType::Json => {
                 let timestamp = self
                     .context
@@ -655,10 +955,13 @@ impl TransactionsApi {
                     BasicResponseStatus::Ok,
                 ))
             },
-            AcceptType::Bcs => {
-                BasicResponse::try_from_bcs((data, &latest_ledger_info, BasicResponseStatus::Ok))
-            },
+            AcceptType::Bcs => BasicResponse::try_from_bcs((
+                filter.apply(data),
+                &latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
         }
+        .map(|response| response.with_page_cursor(Some(next_cursor)))
     }
 
     async fn get_transaction_by_hash_inner(
@@ -667,6 +970,11 @@ impl TransactionsApi {
         hash: HashValue,
     ) -> BasicResultWith404<Transaction> {
         let ledger_info = self.context.get_latest_ledger_info()?;
+        if let Some(response) =
+            self.cached_transaction(accept_type, TransactionKey::Hash(hash.into()), &ledger_info)
+        {
+            return response;
+        }
         let txn_data = self
             .get_by_hash(hash.into(), &ledger_info)
             .await
@@ -685,12 +993,80 @@ impl TransactionsApi {
             .await
     }
 
+    async fn get_transactions_by_hashes_inner(
+        &self,
+        accept_type: &AcceptType,
+        hashes: Vec<HashValue>,
+    ) -> BasicResult<Vec<TransactionByHash>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let max_batch_size = self.context.max_transactions_by_hashes_batch_size();
+        if hashes.len() > max_batch_size {
+            return Err(BasicError::bad_request_with_code(
+                format!(
+                    "Requested too many transactions: {}, while limit is {}",
+                    hashes.len(),
+                    max_batch_size,
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let mut transactions = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            let txn_data = self
+                .get_by_hash(hash.into(), &ledger_info)
+                .await
+                .context(format!("Failed to get transaction by hash {}", hash))
+                .map_err(|err| {
+                    BasicError::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+                })?;
+            transactions.push((hash, txn_data));
+        }
+
+        match accept_type {
+            AcceptType::Json => {
+                let transactions = transactions
+                    .into_iter()
+                    .map(|(hash, txn_data)| {
+                        Ok(TransactionByHash {
+                            hash,
+                            transaction: txn_data
+                                .map(|txn_data| {
+                                    self.convert_transaction_data(txn_data, &ledger_info)
+                                })
+                                .transpose()?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, BasicError>>()?;
+                BasicResponse::try_from_json((transactions, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                let transactions: Vec<_> = transactions
+                    .into_iter()
+                    .map(|(hash, transaction)| BcsTransactionByHash {
+                        hash: hash.into(),
+                        transaction,
+                    })
+                    .collect();
+                BasicResponse::try_from_bcs((transactions, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     async fn get_transaction_by_version_inner(
         &self,
         accept_type: &AcceptType,
         version: U64,
     ) -> BasicResultWith404<Transaction> {
         let ledger_info = self.context.get_latest_ledger_info()?;
+        if let Some(response) = self.cached_transaction(
+            accept_type,
+            TransactionKey::Version(version.0),
+            &ledger_info,
+        ) {
+            return response;
+        }
         let txn_data = self
             .get_by_version(version.0, &ledger_info)
             .context(format!("Failed to get transaction by version {}", version))
@@ -710,57 +1086,113 @@ impl TransactionsApi {
             GetByVersionResponse::VersionTooNew => {
                 Err(transaction_not_found_by_version(version.0, &ledger_info))
             },
-            GetByVersionResponse::VersionTooOld => Err(version_pruned(version.0, &ledger_info)),
+            GetByVersionResponse::VersionTooOld => {
+                Err(self.context.version_pruned(version.0, &ledger_info))
+            },
         }
     }
 
-    /// Converts a transaction into the outgoing type
+    /// Looks up a committed transaction in the response cache. The transactions pruned since
+    /// they were cached are left to the DB lookup, which returns the right error.
+    fn cached_transaction(
+        &self,
+        accept_type: &AcceptType,
+        key: TransactionKey,
+        ledger_info: &LedgerInfo,
+    ) -> Option<BasicResultWith404<Transaction>> {
+        let (version, cached) = self
+            .context
+            .response_caches()
+            .transactions
+            .get(&(key, *accept_type))?;
+        if version < ledger_info.oldest_version() || version > ledger_info.version() {
+            return None;
+        }
+        Some(Self::cached_transaction_response(cached, ledger_info))
+    }
+
+    fn cached_transaction_response(
+        cached: CachedResponse<Transaction>,
+        ledger_info: &LedgerInfo,
+    ) -> BasicResultWith404<Transaction> {
+        match cached {
+            CachedResponse::Json(txn) => {
+                BasicResponse::try_from_json((txn, ledger_info, BasicResponseStatus::Ok))
+            },
+            CachedResponse::Bcs(bytes) => {
+                BasicResponse::try_from_encoded((bytes, ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
+    /// Converts a transaction into the outgoing type. The committed transactions are cached by
+    /// version and by hash, as they can't change anymore.
     async fn get_transaction_inner(
         &self,
         accept_type: &AcceptType,
         transaction_data: TransactionData,
         ledger_info: &LedgerInfo,
     ) -> BasicResultWith404<Transaction> {
-        match accept_type {
+        let committed = match &transaction_data {
+            TransactionData::OnChain(txn) => Some((
+                txn.version,
+                txn.info.transaction_hash(),
+                bcs::serialized_size(txn).unwrap_or(usize::MAX),
+            )),
+            TransactionData::Pending(_) => None,
+        };
+        let rendered = match accept_type {
             AcceptType::Json => {
-                let state_view = self.context.latest_state_view_poem(ledger_info)?;
-                let resolver = state_view.as_move_resolver();
-                let transaction = match transaction_data {
-                    TransactionData::OnChain(txn) => {
-                        let timestamp =
-                            self.context.get_block_timestamp(ledger_info, txn.version)?;
-                        resolver
-                            .as_converter(self.context.db.clone())
-                            .try_into_onchain_transaction(timestamp, txn)
-                            .context("Failed to convert on chain transaction to Transaction")
-                            .map_err(|err| {
-                                BasicErrorWith404::internal_with_code(
-                                    err,
-                                    AptosErrorCode::InternalError,
-                                    ledger_info,
-                                )
-                            })?
-                    },
-                    TransactionData::Pending(txn) => resolver
-                        .as_converter(self.context.db.clone())
-                        .try_into_pending_transaction(*txn)
-                        .context("Failed to convert on pending transaction to Transaction")
-                        .map_err(|err| {
-                            BasicErrorWith404::internal_with_code(
-                                err,
-                                AptosErrorCode::InternalError,
-                                ledger_info,
-                            )
-                        })?,
-                };
+                CachedResponse::Json(self.convert_transaction_data(transaction_data, ledger_info)?)
+            },
+            AcceptType::Bcs => CachedResponse::Bcs(
+                bcs::to_bytes(&transaction_data)
+                    .context("Failed to serialize transaction")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            ledger_info,
+                        )
+                    })?,
+            ),
+        };
+        if let Some((version, hash, size)) = committed {
+            let cache = &self.context.response_caches().transactions;
+            for key in [TransactionKey::Version(version), TransactionKey::Hash(hash)] {
+                cache.insert((key, *accept_type), (version, rendered.clone()), size);
+            }
+        }
 
-                BasicResponse::try_from_json((transaction, ledger_info, BasicResponseStatus::Ok))
+        Self::cached_transaction_response(rendered, ledger_info)
+    }
+
+    /// Converts a committed or pending transaction into its JSON representation
+    fn convert_transaction_data<E: InternalError>(
+        &self,
+        transaction_data: TransactionData,
+        ledger_info: &LedgerInfo,
+    ) -> Result<Transaction, E> {
+        let state_view = self.context.latest_state_view_poem(ledger_info)?;
+        let resolver = state_view.as_move_resolver();
+        match transaction_data {
+            TransactionData::OnChain(txn) => {
+                let timestamp = self.context.get_block_timestamp(ledger_info, txn.version)?;
+                resolver
+                    .as_converter(self.context.db.clone())
+                    .try_into_onchain_transaction(timestamp, txn)
+                    .context("Failed to convert on chain transaction to Transaction")
+                    .map_err(|err| {
+                        E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+                    })
             },
-            AcceptType::Bcs => BasicResponse::try_from_bcs((
-                transaction_data,
-                ledger_info,
-                BasicResponseStatus::Ok,
-            )),
+            TransactionData::Pending(txn) => resolver
+                .as_converter(self.context.db.clone())
+                .try_into_pending_transaction(*txn)
+                .context("Failed to convert on pending transaction to Transaction")
+                .map_err(|err| {
+                    E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+                }),
         }
     }
 
@@ -811,6 +1243,7 @@ impl TransactionsApi {
         accept_type: &AcceptType,
         page: Page,
         address: Address,
+        filter: &TransactionFilter,
     ) -> BasicResultWith404<Vec<Transaction>> {
         // Verify the account exists
         let account = Account::new(self.context.clone(), address, None, None, None)?;
@@ -825,6 +1258,15 @@ impl TransactionsApi {
             latest_ledger_info.version(),
             &latest_ledger_info,
         )?;
+        // The transactions of an account are the ones it sent, so they all have a sequence number
+        let next_cursor = PageCursor::SequenceNumber(
+            data.last()
+                .and_then(|txn| txn.transaction.try_as_signed_user_txn())
+                .map(|txn| txn.sequence_number() + 1)
+                .or_else(|| page.start_option())
+                .unwrap_or(0),
+        );
+        let data = filter.apply(data);
         match accept_type {
             AcceptType::Json => BasicResponse::try_from_json((
                 self.context
@@ -836,6 +1278,108 @@ impl TransactionsApi {
                 BasicResponse::try_from_bcs((data, &latest_ledger_info, BasicResponseStatus::Ok))
             },
         }
+        .map(|response| response.with_page_cursor(Some(next_cursor)))
+    }
+
+    /// List the transactions of an account from the account transaction index
+    fn list_history_by_account(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+        address: Address,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let start_version = match page.start_option() {
+            Some(start_version) => {
+                if start_version < latest_ledger_info.oldest_version() {
+                    return Err(self
+                        .context
+                        .version_pruned(start_version, &latest_ledger_info));
+                }
+                start_version
+            },
+            None => latest_ledger_info.oldest_version(),
+        };
+
+        let limit = page.limit(&latest_ledger_info)?;
+        let data = self.context.get_account_transaction_history(
+            address.into(),
+            start_version,
+            limit,
+            &latest_ledger_info,
+        )?;
+        // A partial page has all the transactions up to the ledger version
+        let next_cursor = PageCursor::Version(match data.last() {
+            Some(txn) if data.len() == limit as usize => txn.version + 1,
+            _ => latest_ledger_info.version() + 1,
+        });
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                self.context
+                    .render_transactions_non_sequential(&latest_ledger_info, data)?,
+                &latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((data, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+        .map(|response| response.with_page_cursor(Some(next_cursor)))
+    }
+
+    /// Computes the maximum gas amount the sender can pay for at the gas unit price, within the
+    /// limits of a transaction
+    fn max_gas_amount(
+        &self,
+        ledger_info: &LedgerInfo,
+        sender: AccountAddress,
+        gas_unit_price: u64,
+    ) -> Result<u64, SubmitTransactionError> {
+        // Retrieve max possible gas units
+        let (_, gas_params) = self.context.get_gas_schedule(ledger_info)?;
+        let min_number_of_gas_units = u64::from(gas_params.vm.txn.min_transaction_gas_units)
+            / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
+        let max_number_of_gas_units = u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
+
+        // Retrieve account balance to determine max gas available
+        let account_state = self
+            .context
+            .get_account_state(sender, ledger_info.version(), ledger_info)?
+            .ok_or_else(|| {
+                SubmitTransactionError::bad_request_with_code(
+                    "Account not found",
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                )
+            })?;
+        let coin_store: CoinStoreResource = account_state
+            .get_coin_store_resource()
+            .and_then(|inner| {
+                inner.ok_or_else(|| anyhow!("No coin store found for account {}", sender))
+            })
+            .map_err(|err| {
+                SubmitTransactionError::internal_with_code(
+                    format!("Failed to get coin store resource {}", err),
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+
+        // With 0 gas price, we set it to max gas units, since we can't divide by 0
+        let max_account_gas_units = if gas_unit_price == 0 {
+            coin_store.coin()
+        } else {
+            coin_store.coin() / gas_unit_price
+        };
+
+        // To give better error messaging, we should not go below the minimum number of gas units
+        let max_account_gas_units = std::cmp::max(min_number_of_gas_units, max_account_gas_units);
+
+        // Minimum of the max account and the max total needs to be used for estimation
+        Ok(std::cmp::min(
+            max_account_gas_units,
+            max_number_of_gas_units,
+        ))
     }
 
     /// Parses a single signed transaction
@@ -1006,6 +1550,7 @@ impl TransactionsApi {
 
     /// Submits a single transaction, and converts mempool codes to errors
     async fn create_internal(&self, txn: SignedTransaction) -> Result<(), AptosError> {
+        let sender = txn.sender();
         let (mempool_status, vm_status_opt) = self
             .context
             .submit_transaction(txn)
@@ -1041,10 +1586,20 @@ impl TransactionsApi {
                     ))
                 }
             },
-            MempoolStatusCode::InvalidSeqNumber => Err(AptosError::new_with_error_code(
-                mempool_status.message,
-                AptosErrorCode::SequenceNumberTooOld,
-            )),
+            MempoolStatusCode::InvalidSeqNumber => {
+                let error = AptosError::new_with_error_code(
+                    mempool_status.message,
+                    AptosErrorCode::SequenceNumberTooOld,
+                );
+                Err(match self.current_sequence_number(sender) {
+                    Some(sequence_number) => error.with_details(
+                        AptosErrorDetails::SequenceNumberTooOld(SequenceNumberTooOldDetails {
+                            current_sequence_number: sequence_number.into(),
+                        }),
+                    ),
+                    None => error,
+                })
+            },
             MempoolStatusCode::InvalidUpdate => Err(AptosError::new_with_error_code(
                 mempool_status.message,
                 AptosErrorCode::InvalidTransactionUpdate,
@@ -1056,6 +1611,18 @@ impl TransactionsApi {
         }
     }
 
+    /// The sequence number of an account at the latest ledger version, if it can be read
+    fn current_sequence_number(&self, address: AccountAddress) -> Option<u64> {
+        let ledger_info = self.context.get_latest_ledger_info_wrapped().ok()?;
+        let account_resource = self
+            .context
+            .get_account_state::<BasicError>(address, ledger_info.version(), &ledger_info)
+            .ok()??
+            .get_account_resource()
+            .ok()??;
+        Some(account_resource.sequence_number())
+    }
+
     /// Submits a single transaction
     async fn create(
         &self,
@@ -1104,26 +1671,58 @@ impl TransactionsApi {
                     SubmitTransactionResponseStatus::Accepted,
                 )),
             },
-            Err(error) => match error.error_code {
-                AptosErrorCode::InternalError => Err(
-                    SubmitTransactionError::internal_from_aptos_error(error, ledger_info),
-                ),
-                AptosErrorCode::VmError
-                | AptosErrorCode::SequenceNumberTooOld
-                | AptosErrorCode::InvalidTransactionUpdate => Err(
-                    SubmitTransactionError::bad_request_from_aptos_error(error, ledger_info),
-                ),
-                AptosErrorCode::MempoolIsFull => Err(
-                    SubmitTransactionError::insufficient_storage_from_aptos_error(
-                        error,
-                        ledger_info,
-                    ),
+            Err(error) => Err(submission_error(error, ledger_info)),
+        }
+    }
+
+    /// Submits a single transaction, and waits for it to be committed until the timeout
+    async fn create_and_wait(
+        &self,
+        accept_type: &AcceptType,
+        ledger_info: &LedgerInfo,
+        txn: SignedTransaction,
+        timeout: Duration,
+    ) -> SubmitAndWaitResult<Transaction> {
+        let deadline = Instant::now() + timeout;
+        let hash = txn.clone().committed_hash();
+        self.create_internal(txn.clone())
+            .await
+            .map_err(|error| submission_error(error, ledger_info))?;
+
+        loop {
+            let ledger_info = self.context.get_latest_ledger_info()?;
+            let committed = self
+                .context
+                .get_transaction_by_hash(hash, ledger_info.version())
+                .context(format!("Failed to get transaction by hash {}", hash))
+                .map_err(|err| {
+                    SubmitTransactionError::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?;
+            let (transaction_data, status) = match committed {
+                Some(txn) => (txn.into(), SubmitAndWaitResponseStatus::Ok),
+                None if Instant::now() >= deadline => (
+                    TransactionData::Pending(Box::new(txn)),
+                    SubmitAndWaitResponseStatus::Accepted,
                 ),
-                _ => Err(SubmitTransactionError::internal_from_aptos_error(
-                    error,
-                    ledger_info,
+                None => {
+                    tokio::time::sleep(SUBMIT_AND_WAIT_POLL_INTERVAL).await;
+                    continue;
+                },
+            };
+            return match accept_type {
+                AcceptType::Json => SubmitAndWaitResponse::try_from_json((
+                    self.convert_transaction_data(transaction_data, &ledger_info)?,
+                    &ledger_info,
+                    status,
                 )),
-            },
+                AcceptType::Bcs => {
+                    SubmitAndWaitResponse::try_from_bcs((transaction_data, &ledger_info, status))
+                },
+            };
         }
     }
 
@@ -1170,11 +1769,19 @@ impl TransactionsApi {
     ///
     /// Note: this returns a `Vec<UserTransaction>`, but for backwards compatibility, this can't
     /// be removed even though, there is only one possible transaction
+    ///
+    /// If `state_overrides` are given, the transaction is simulated on the latest state with
+    /// the overrides applied.
+    ///
+    /// If `profile_gas` is set, the transaction is simulated under the gas profiler and the
+    /// resulting gas log is attached to the returned transaction.
     pub async fn simulate(
         &self,
         accept_type: &AcceptType,
         ledger_info: LedgerInfo,
         txn: SignedTransaction,
+        state_overrides: Option<StateOverrides>,
+        profile_gas: bool,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         // Transactions shouldn't have a valid signature or this could be used to attack
         if txn.signature_is_valid() {
@@ -1186,8 +1793,51 @@ impl TransactionsApi {
         }
 
         // Simulate transaction
-        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
-        let (_, output) = AptosVM::simulate_signed_transaction(&txn, &state_view);
+        let base_view = self.context.latest_state_view_poem(&ledger_info)?;
+        let overrides = match state_overrides {
+            Some(state_overrides) => state_override_values(
+                &base_view,
+                &base_view
+                    .as_move_resolver()
+                    .as_converter(self.context.db.clone()),
+                state_overrides,
+            )
+            .context("Invalid state overrides")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?,
+            None => HashMap::new(),
+        };
+        let state_view = OverriddenStateView::new(&base_view, overrides);
+        let (output, gas_log) = if profile_gas {
+            let (_, output, gas_profiler) =
+                AptosVM::simulate_signed_transaction_with_custom_gas_meter(
+                    &txn,
+                    &state_view,
+                    |gas_feature_version, gas_params, storage_gas_params, balance| {
+                        let gas_meter = MemoryTrackedGasMeter::new(StandardGasMeter::new(
+                            StandardGasAlgebra::new(
+                                gas_feature_version,
+                                gas_params,
+                                storage_gas_params,
+                                balance,
+                            ),
+                        ));
+                        Ok(new_gas_profiler(gas_meter, txn.payload()))
+                    },
+                );
+            (
+                output,
+                gas_profiler.map(|gas_profiler| gas_profiler.finish(None)),
+            )
+        } else {
+            let (_, output) = AptosVM::simulate_signed_transaction(&txn, &state_view);
+            (output, None)
+        };
         let version = ledger_info.version();
 
         // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
@@ -1224,12 +1874,27 @@ impl TransactionsApi {
                     .context
                     .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
 
+                let gas_profile = gas_log
+                    .map(|gas_log| serde_json::to_value(&gas_log))
+                    .transpose()
+                    .context("Failed to serialize gas log")
+                    .map_err(|err| {
+                        SubmitTransactionError::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+
                 // Users can only make requests to simulate UserTransactions, so unpack
                 // the Vec<Transaction> into Vec<UserTransaction>.
                 let mut user_transactions = Vec::new();
                 for transaction in transactions.into_iter() {
                     match transaction {
-                        Transaction::UserTransaction(user_txn) => user_transactions.push(*user_txn),
+                        Transaction::UserTransaction(mut user_txn) => {
+                            user_txn.gas_profile = gas_profile.clone();
+                            user_transactions.push(*user_txn)
+                        },
                         _ => {
                             return Err(SubmitTransactionError::internal_with_code(
                                 "Simulation transaction resulted in a non-UserTransaction",
@@ -1251,6 +1916,68 @@ impl TransactionsApi {
         }
     }
 
+    /// Simulates a transaction to estimate its gas, and suggests its max gas amount by adding the
+    /// safety margin of the config to the gas it used
+    fn estimate_gas(
+        &self,
+        accept_type: &AcceptType,
+        ledger_info: LedgerInfo,
+        txn: SignedTransaction,
+    ) -> SimulateTransactionResult<TransactionGasEstimation> {
+        // Transactions shouldn't have a valid signature or this could be used to attack
+        if txn.signature_is_valid() {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "Transactions to estimate the gas of must have a non-valid signature",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
+        let (_, output) = AptosVM::simulate_signed_transaction(&txn, &state_view);
+        match output.status() {
+            TransactionStatus::Keep(ExecutionStatus::Success) => {},
+            status => {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    format!("Simulated transaction failed with {:?}", status),
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ))
+            },
+        }
+
+        let (_, gas_params) = self.context.get_gas_schedule(&ledger_info)?;
+        let max_number_of_gas_units = u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
+        let safety_margin_percent = self
+            .context
+            .node_config
+            .api
+            .gas_estimation
+            .max_gas_safety_margin_percent;
+        let gas_used = output.gas_used();
+        // Rounded up, so that the margin isn't lost on small amounts of gas
+        let max_gas_amount = gas_used
+            .saturating_mul(100 + safety_margin_percent)
+            .saturating_add(99)
+            / 100;
+        let gas_estimation = TransactionGasEstimation {
+            gas_used,
+            max_gas_amount: std::cmp::min(max_gas_amount, max_number_of_gas_units),
+            gas_unit_price: txn.gas_unit_price(),
+        };
+
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                gas_estimation,
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((gas_estimation, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     /// Encode message as BCS
     pub fn get_signing_message(
         &self,
@@ -1331,6 +2058,67 @@ fn override_gas_parameters(
     SignedTransaction::new_with_authenticator(raw_txn, signed_txn.authenticator())
 }
 
+/// Creates a gas profiler for the transaction payload, with the entry function (if any) as
+/// the root frame.
+fn new_gas_profiler<G: AptosGasMeter>(
+    gas_meter: G,
+    payload: &TransactionPayload,
+) -> GasProfiler<G> {
+    let entry_func = match payload {
+        TransactionPayload::EntryFunction(entry_func) => Some(entry_func),
+        TransactionPayload::Multisig(multisig) => multisig
+            .transaction_payload
+            .as_ref()
+            .map(|MultisigTransactionPayload::EntryFunction(entry_func)| entry_func),
+        TransactionPayload::Script(_) | TransactionPayload::ModuleBundle(_) => None,
+    };
+
+    match entry_func {
+        Some(entry_func) => GasProfiler::new_function(
+            gas_meter,
+            entry_func.module().clone(),
+            entry_func.function().to_owned(),
+            entry_func.ty_args().to_vec(),
+        ),
+        None => GasProfiler::new_script(gas_meter),
+    }
+}
+
+/// Parses comma separated percentiles, e.g. `50,90,99`
+fn parse_percentiles(percentiles: &str) -> anyhow::Result<Vec<u8>> {
+    percentiles
+        .split(',')
+        .map(|percentile| {
+            let percentile: u8 = percentile
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid percentile {:?}", percentile))?;
+            if percentile > 100 {
+                return Err(anyhow!("Percentile {} is larger than 100", percentile));
+            }
+            Ok(percentile)
+        })
+        .collect()
+}
+
+/// Converts the error of a transaction submission into a response
+fn submission_error(error: AptosError, ledger_info: &LedgerInfo) -> SubmitTransactionError {
+    match error.error_code {
+        AptosErrorCode::InternalError => {
+            SubmitTransactionError::internal_from_aptos_error(error, ledger_info)
+        },
+        AptosErrorCode::VmError
+        | AptosErrorCode::SequenceNumberTooOld
+        | AptosErrorCode::InvalidTransactionUpdate => {
+            SubmitTransactionError::bad_request_from_aptos_error(error, ledger_info)
+        },
+        AptosErrorCode::MempoolIsFull => {
+            SubmitTransactionError::insufficient_storage_from_aptos_error(error, ledger_info)
+        },
+        _ => SubmitTransactionError::internal_from_aptos_error(error, ledger_info),
+    }
+}
+
 enum GetByVersionResponse {
     VersionTooNew,
     VersionTooOld,
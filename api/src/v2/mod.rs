@@ -0,0 +1,29 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The v2 API is served next to the v1 API, under `/v2`, so that it can change in ways the v1
+//! API can't: its pages return the cursor of the next page in their body, and it returns BCS
+//! unless JSON is asked for. The v1 API stays as it is. Both versions share the context, the
+//! responses and the typed errors, and each version has its own OpenAPI spec.
+
+mod transactions;
+
+use crate::{basic::BasicApi, context::Context, runtime::new_api_service};
+use poem_openapi::OpenApiService;
+use std::sync::Arc;
+pub use transactions::TransactionsApiV2;
+
+/// Generate the top level API service of the v2 API
+pub fn get_api_service_v2(
+    context: Arc<Context>,
+) -> OpenApiService<(BasicApi, TransactionsApiV2), ()> {
+    // These APIs get merged.
+    let apis = (
+        BasicApi {
+            context: context.clone(),
+        },
+        TransactionsApiV2 { context },
+    );
+
+    new_api_service(apis, "/v2")
+}
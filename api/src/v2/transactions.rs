@@ -0,0 +1,94 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    accept_type::{AcceptType, BcsFirstAcceptType},
+    context::Context,
+    failpoint::fail_point_poem,
+    page::Page,
+    response::{BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404},
+    ApiTags,
+};
+use aptos_api_types::{BcsTransactionsPage, PageCursor, TransactionsPage};
+use poem_openapi::{param::Query, OpenApi};
+use std::sync::Arc;
+
+/// API for interacting with transactions in the v2 API
+pub struct TransactionsApiV2 {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl TransactionsApiV2 {
+    /// Get transactions
+    ///
+    /// Retrieve a page of on-chain committed transactions, along with the cursor
+    /// of the next page. Without a cursor, the latest transactions are returned.
+    ///
+    /// The transactions are returned in BCS, unless only JSON is accepted.
+    ///
+    /// If the cursor has been pruned, then a 410 will be returned.
+    #[oai(
+        path = "/transactions",
+        method = "get",
+        operation_id = "get_transactions",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_transactions(
+        &self,
+        accept_type: BcsFirstAcceptType,
+        /// Cursor returned with the previous page, to get the next page
+        cursor: Query<Option<PageCursor>>,
+        /// Max number of transactions to retrieve.
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<TransactionsPage> {
+        fail_point_poem("endpoint_get_transactions_v2")?;
+        let accept_type = accept_type.0;
+        self.context
+            .check_api_output_enabled("Get transactions", &accept_type)?;
+        let page = Page::new_with_cursor::<BasicErrorWith404>(
+            None,
+            cursor.0,
+            PageCursor::version,
+            limit.0,
+            self.context.max_transactions_page_size(),
+        )?;
+
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let (start_version, data) = self
+            .context
+            .get_transactions_page(&page, &latest_ledger_info)?;
+        let cursor = PageCursor::Version(start_version + data.len() as u64);
+
+        match accept_type {
+            AcceptType::Json => {
+                let timestamp = self
+                    .context
+                    .get_block_timestamp(&latest_ledger_info, start_version)?;
+                let transactions = self.context.render_transactions_sequential(
+                    &latest_ledger_info,
+                    data,
+                    timestamp,
+                )?;
+                BasicResponse::try_from_json((
+                    TransactionsPage {
+                        transactions,
+                        cursor,
+                    },
+                    &latest_ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => BasicResponse::try_from_bcs((
+                BcsTransactionsPage {
+                    transactions: data,
+                    cursor,
+                },
+                &latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        }
+    }
+}
@@ -9,9 +9,13 @@ use crate::{
     },
     ApiTags, Context,
 };
-use aptos_api_types::{AptosErrorCode, AsConverter, MoveValue, ViewRequest, U64};
+use aptos_api_types::{
+    AptosErrorCode, AsConverter, LedgerInfo, MoveValue, ViewRequest, ViewResponseBcs, U64,
+};
+use aptos_storage_interface::state_view::DbStateView;
+use aptos_types::transaction::EntryFunction;
 use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
-use move_core_types::language_storage::TypeTag;
+use move_core_types::{language_storage::TypeTag, resolver::MoveResolver};
 use poem_openapi::{param::Query, payload::Json, OpenApi};
 use std::sync::Arc;
 
@@ -26,6 +30,9 @@ impl ViewFunctionApi {
     ///
     /// Execute the Move function with the given parameters and return its execution result.
     ///
+    /// With BCS, the values are returned encoded in BCS along with their types, which avoids
+    /// converting the large integers through JSON.
+    ///
     /// The Aptos nodes prune account state history, via a configurable time window.
     /// If the requested ledger version has been pruned, the server responds with a 410.
     #[oai(
@@ -56,30 +63,173 @@ impl ViewFunctionApi {
 
         let state_view = self.context.latest_state_view_poem(&ledger_info)?;
         let resolver = state_view.as_move_resolver();
+        let requested_state_view = self.requested_state_view(&ledger_info, requested_version)?;
+        let (entry_func, return_vals) = self.execute(
+            &ledger_info,
+            &resolver,
+            &requested_state_view,
+            request.0,
+            None,
+        )?;
+        match accept_type {
+            AcceptType::Bcs => {
+                let output =
+                    self.bcs_output(&ledger_info, &resolver, &entry_func, return_vals, None)?;
+                BasicResponse::try_from_bcs((output, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Json => {
+                let move_vals =
+                    self.move_values(&ledger_info, &resolver, &entry_func, return_vals, None)?;
+                BasicResponse::try_from_json((move_vals, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
 
-        let entry_func = resolver
-            .as_converter(self.context.db.clone())
-            .convert_view_function(request.0)
-            .map_err(|err| {
-                BasicErrorWith404::bad_request_with_code(
-                    err,
-                    AptosErrorCode::InvalidInput,
+    /// Execute a batch of view functions
+    ///
+    /// Execute the Move functions with the given parameters against the same ledger
+    /// version, and return their execution results in the order of the requests. This
+    /// saves the overhead of a request per view function.
+    ///
+    /// If any of the functions fails, the whole batch fails with a 400, and the error
+    /// tells the index of the failed request.
+    ///
+    /// With BCS, the values of each function are returned encoded in BCS along with
+    /// their types.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view/batch",
+        method = "post",
+        operation_id = "view_batch",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function_batch(
+        &self,
+        accept_type: AcceptType,
+        /// View function requests with type and position arguments
+        requests: Json<Vec<ViewRequest>>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<Vec<MoveValue>>> {
+        fail_point_poem("endpoint_view_function_batch")?;
+        self.context
+            .check_api_output_enabled("View function batch", &accept_type)?;
+
+        let (ledger_info, requested_version) = self
+            .context
+            .get_latest_ledger_info_and_verify_lookup_version(
+                ledger_version.map(|inner| inner.0),
+            )?;
+        if requests.0.len() > self.context.max_view_function_batch_size() {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Requested too many view functions: {}, while limit is {}",
+                    requests.0.len(),
+                    self.context.max_view_function_batch_size(),
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
+        let resolver = state_view.as_move_resolver();
+        let requested_state_view = self.requested_state_view(&ledger_info, requested_version)?;
+        let outputs = requests
+            .0
+            .into_iter()
+            .enumerate()
+            .map(|(index, request)| {
+                self.execute(
                     &ledger_info,
+                    &resolver,
+                    &requested_state_view,
+                    request,
+                    Some(index),
                 )
-            })?;
-        let state_view = self
-            .context
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        match accept_type {
+            AcceptType::Bcs => {
+                let outputs = outputs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, (entry_func, return_vals))| {
+                        self.bcs_output(
+                            &ledger_info,
+                            &resolver,
+                            &entry_func,
+                            return_vals,
+                            Some(index),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                BasicResponse::try_from_bcs((outputs, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Json => {
+                let move_vals = outputs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, (entry_func, return_vals))| {
+                        self.move_values(
+                            &ledger_info,
+                            &resolver,
+                            &entry_func,
+                            return_vals,
+                            Some(index),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                BasicResponse::try_from_json((move_vals, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+}
+
+impl ViewFunctionApi {
+    fn requested_state_view(
+        &self,
+        ledger_info: &LedgerInfo,
+        requested_version: u64,
+    ) -> Result<DbStateView, BasicErrorWith404> {
+        self.context
             .state_view_at_version(requested_version)
             .map_err(|err| {
                 BasicErrorWith404::bad_request_with_code(
                     err,
                     AptosErrorCode::InternalError,
-                    &ledger_info,
+                    ledger_info,
+                )
+            })
+    }
+
+    /// Executes a view function request, and returns the function with the BCS encoded values
+    /// it returns. The index of a request of a batch is added to the errors.
+    fn execute<R: MoveResolver>(
+        &self,
+        ledger_info: &LedgerInfo,
+        resolver: &R,
+        state_view: &DbStateView,
+        request: ViewRequest,
+        index: Option<usize>,
+    ) -> Result<(EntryFunction, Vec<Vec<u8>>), BasicErrorWith404> {
+        let entry_func = resolver
+            .as_converter(self.context.db.clone())
+            .convert_view_function(request)
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    with_request_index(err, index),
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
                 )
             })?;
 
         let return_vals = AptosVM::execute_view_function(
-            &state_view,
+            state_view,
             entry_func.module().clone(),
             entry_func.function().to_owned(),
             entry_func.ty_args().to_owned(),
@@ -87,48 +237,83 @@ impl ViewFunctionApi {
             self.context.node_config.api.max_gas_view_function,
         )
         .map_err(|err| {
-            BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            BasicErrorWith404::bad_request_with_code_no_info(
+                with_request_index(err, index),
+                AptosErrorCode::InvalidInput,
+            )
         })?;
-        match accept_type {
-            AcceptType::Bcs => {
-                BasicResponse::try_from_bcs((return_vals, &ledger_info, BasicResponseStatus::Ok))
-            },
-            AcceptType::Json => {
-                let return_types = resolver
-                    .as_converter(self.context.db.clone())
-                    .function_return_types(&entry_func)
-                    .and_then(|tys| {
-                        tys.into_iter()
-                            .map(TypeTag::try_from)
-                            .collect::<anyhow::Result<Vec<_>>>()
-                    })
-                    .map_err(|err| {
-                        BasicErrorWith404::bad_request_with_code(
-                            err,
-                            AptosErrorCode::InternalError,
-                            &ledger_info,
-                        )
-                    })?;
+        Ok((entry_func, return_vals))
+    }
 
-                let move_vals = return_vals
-                    .into_iter()
-                    .zip(return_types.into_iter())
-                    .map(|(v, ty)| {
-                        resolver
-                            .as_converter(self.context.db.clone())
-                            .try_into_move_value(&ty, &v)
-                    })
+    /// Resolves the types of the values returned by a view function
+    fn return_types<R: MoveResolver>(
+        &self,
+        ledger_info: &LedgerInfo,
+        resolver: &R,
+        entry_func: &EntryFunction,
+        index: Option<usize>,
+    ) -> Result<Vec<TypeTag>, BasicErrorWith404> {
+        resolver
+            .as_converter(self.context.db.clone())
+            .function_return_types(entry_func)
+            .and_then(|tys| {
+                tys.into_iter()
+                    .map(TypeTag::try_from)
                     .collect::<anyhow::Result<Vec<_>>>()
-                    .map_err(|err| {
-                        BasicErrorWith404::bad_request_with_code(
-                            err,
-                            AptosErrorCode::InternalError,
-                            &ledger_info,
-                        )
-                    })?;
+            })
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    with_request_index(err, index),
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })
+    }
 
-                BasicResponse::try_from_json((move_vals, &ledger_info, BasicResponseStatus::Ok))
-            },
-        }
+    /// Pairs the BCS encoded values returned by a view function with their types
+    fn bcs_output<R: MoveResolver>(
+        &self,
+        ledger_info: &LedgerInfo,
+        resolver: &R,
+        entry_func: &EntryFunction,
+        return_vals: Vec<Vec<u8>>,
+        index: Option<usize>,
+    ) -> Result<ViewResponseBcs, BasicErrorWith404> {
+        Ok(ViewResponseBcs {
+            return_types: self.return_types(ledger_info, resolver, entry_func, index)?,
+            values: return_vals,
+        })
+    }
+
+    /// Converts the values returned by a view function to JSON values
+    fn move_values<R: MoveResolver>(
+        &self,
+        ledger_info: &LedgerInfo,
+        resolver: &R,
+        entry_func: &EntryFunction,
+        return_vals: Vec<Vec<u8>>,
+        index: Option<usize>,
+    ) -> Result<Vec<MoveValue>, BasicErrorWith404> {
+        let return_types = self.return_types(ledger_info, resolver, entry_func, index)?;
+        let converter = resolver.as_converter(self.context.db.clone());
+        return_vals
+            .into_iter()
+            .zip(return_types)
+            .map(|(v, ty)| converter.try_into_move_value(&ty, &v))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    with_request_index(err, index),
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })
+    }
+}
+
+fn with_request_index(err: anyhow::Error, index: Option<usize>) -> anyhow::Error {
+    match index {
+        Some(index) => anyhow::anyhow!("View function request {} failed: {:#}", index, err),
+        None => err,
     }
 }
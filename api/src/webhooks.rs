@@ -0,0 +1,391 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Calls the webhooks configured by the operator of the node with the newly committed
+//! transactions touching their addresses or emitting their event types.
+//!
+//! Every transaction is sent in a POST request of its own, in the order of the transactions.
+//! The body is signed with the secret of the webhook: the `X-Aptos-Webhook-Signature` header
+//! is `sha256=<hex encoded HMAC-SHA256 of the body>`. The failed calls are retried with an
+//! exponential backoff, and the transactions are dropped once the retries are exhausted. The
+//! webhooks follow the ledger from the start of the node, the transactions committed while
+//! the node is down aren't sent.
+
+use crate::{
+    context::Context,
+    metrics::{WEBHOOK_CALL_LATENCY, WEBHOOK_DELIVERIES},
+    response::BasicError,
+};
+use anyhow::{Context as AnyhowContext, Result};
+use aptos_api_types::{Transaction, TransactionOnChainData};
+use aptos_config::config::{WebhookConfig, WebhooksConfig};
+use aptos_logger::{info, warn};
+use aptos_types::state_store::state_key::StateKeyInner;
+use move_core_types::{
+    account_address::AccountAddress, language_storage::TypeTag, parser::parse_type_tag,
+};
+use reqwest::header::CONTENT_TYPE;
+use ring::hmac;
+use serde::Serialize;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tokio::sync::mpsc;
+
+/// How often the webhooks check for newly committed transactions
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+pub const SIGNATURE_HEADER: &str = "X-Aptos-Webhook-Signature";
+
+/// The body of the calls of the webhooks
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    webhook: &'a str,
+    chain_id: u8,
+    transaction: &'a Transaction,
+}
+
+#[derive(Debug, PartialEq)]
+struct WebhookFilter {
+    addresses: HashSet<AccountAddress>,
+    event_types: Vec<TypeTag>,
+}
+
+impl WebhookFilter {
+    fn new(config: &WebhookConfig) -> Result<Self> {
+        Ok(Self {
+            addresses: config.addresses.iter().copied().collect(),
+            event_types: config
+                .event_types
+                .iter()
+                .map(|event_type| parse_type_tag(event_type))
+                .collect::<Result<_>>()
+                .with_context(|| format!("Invalid event type of webhook {}", config.name))?,
+        })
+    }
+
+    /// A transaction touches an address if it's sent by it, changes its state or emits one of
+    /// its events
+    fn matches(&self, txn: &TransactionOnChainData) -> bool {
+        if !self.addresses.is_empty() {
+            let sender = txn
+                .transaction
+                .try_as_signed_user_txn()
+                .map(|txn| txn.sender());
+            if sender.map_or(false, |sender| self.addresses.contains(&sender)) {
+                return true;
+            }
+            if txn.changes.iter().any(|(key, _)| match key.inner() {
+                StateKeyInner::AccessPath(access_path) => {
+                    self.addresses.contains(&access_path.address)
+                },
+                _ => false,
+            }) {
+                return true;
+            }
+            if txn.events.iter().any(|event| {
+                event.event_key().map_or(false, |key| {
+                    self.addresses.contains(&key.get_creator_address())
+                })
+            }) {
+                return true;
+            }
+        }
+        txn.events
+            .iter()
+            .any(|event| self.event_types.contains(event.type_tag()))
+    }
+}
+
+/// Signs the body with the secret of a webhook
+fn sign(secret: &str, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    format!("sha256={}", hex::encode(hmac::sign(&key, body).as_ref()))
+}
+
+struct Webhook {
+    config: WebhookConfig,
+    filter: WebhookFilter,
+    sender: mpsc::Sender<Arc<Transaction>>,
+}
+
+/// Delivers the transactions queued for a webhook one at a time, retrying the failed calls
+struct Deliverer {
+    config: WebhookConfig,
+    client: reqwest::Client,
+    chain_id: u8,
+    max_retries: u32,
+    initial_retry_delay: Duration,
+}
+
+impl Deliverer {
+    async fn call(&self, body: &[u8], signature: &str) -> Result<()> {
+        let timer = WEBHOOK_CALL_LATENCY
+            .with_label_values(&[&self.config.name])
+            .start_timer();
+        let response = self
+            .client
+            .post(self.config.url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .body(body.to_vec())
+            .send()
+            .await;
+        timer.observe_duration();
+        response?.error_for_status()?;
+        Ok(())
+    }
+
+    async fn deliver(&self, transaction: &Transaction) {
+        let body = match serde_json::to_vec(&WebhookPayload {
+            webhook: &self.config.name,
+            chain_id: self.chain_id,
+            transaction,
+        }) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!(
+                    "Failed to serialize the transaction for webhook {}: {:?}",
+                    self.config.name, e
+                );
+                WEBHOOK_DELIVERIES
+                    .with_label_values(&[&self.config.name, "failed"])
+                    .inc();
+                return;
+            },
+        };
+        let signature = sign(&self.config.secret, &body);
+
+        let mut retry_delay = self.initial_retry_delay;
+        for attempt in 0..=self.max_retries {
+            match self.call(&body, &signature).await {
+                Ok(()) => {
+                    WEBHOOK_DELIVERIES
+                        .with_label_values(&[&self.config.name, "delivered"])
+                        .inc();
+                    return;
+                },
+                Err(e) if attempt < self.max_retries => {
+                    warn!(
+                        "Failed to call webhook {}, retrying in {:?}: {:?}",
+                        self.config.name, retry_delay, e
+                    );
+                    WEBHOOK_DELIVERIES
+                        .with_label_values(&[&self.config.name, "retried"])
+                        .inc();
+                    tokio::time::sleep(retry_delay).await;
+                    retry_delay *= 2;
+                },
+                Err(e) => warn!(
+                    "Failed to call webhook {}, dropping the transaction: {:?}",
+                    self.config.name, e
+                ),
+            }
+        }
+        WEBHOOK_DELIVERIES
+            .with_label_values(&[&self.config.name, "failed"])
+            .inc();
+    }
+
+    async fn run(self, mut receiver: mpsc::Receiver<Arc<Transaction>>) {
+        while let Some(transaction) = receiver.recv().await {
+            self.deliver(&transaction).await;
+        }
+    }
+}
+
+/// The webhooks and the tasks delivering their transactions
+pub struct Webhooks {
+    webhooks: Vec<Webhook>,
+    deliverers: Vec<(Deliverer, mpsc::Receiver<Arc<Transaction>>)>,
+}
+
+impl Webhooks {
+    pub fn new(config: &WebhooksConfig, chain_id: u8) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()
+            .context("Failed to build the HTTP client of the webhooks")?;
+        let (mut webhooks, mut deliverers) = (vec![], vec![]);
+        for webhook in &config.webhooks {
+            let (sender, receiver) = mpsc::channel(config.max_pending_deliveries);
+            webhooks.push(Webhook {
+                config: webhook.clone(),
+                filter: WebhookFilter::new(webhook)?,
+                sender,
+            });
+            deliverers.push((
+                Deliverer {
+                    config: webhook.clone(),
+                    client: client.clone(),
+                    chain_id,
+                    max_retries: config.max_retries,
+                    initial_retry_delay: Duration::from_millis(config.initial_retry_delay_ms),
+                },
+                receiver,
+            ));
+        }
+        Ok(Self {
+            webhooks,
+            deliverers,
+        })
+    }
+
+    /// Follows the ledger from the first version committed after the start of the node
+    pub async fn run(self, context: Arc<Context>) {
+        let Self {
+            webhooks,
+            deliverers,
+        } = self;
+        for (deliverer, receiver) in deliverers {
+            tokio::spawn(deliverer.run(receiver));
+        }
+        info!("Calling {} webhooks", webhooks.len());
+
+        let webhooks = Arc::new(webhooks);
+        let mut next_version = None;
+        loop {
+            let (context, webhooks) = (context.clone(), webhooks.clone());
+            let result = tokio::task::spawn_blocking(move || {
+                let start_version = match next_version {
+                    Some(version) => version,
+                    None => context.get_latest_ledger_info_wrapped()?.version() + 1,
+                };
+                Ok::<_, anyhow::Error>((
+                    start_version,
+                    dispatch(&webhooks, &context, start_version)?,
+                ))
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result);
+            match result {
+                Ok((start_version, version)) => {
+                    if version == start_version {
+                        tokio::time::sleep(POLL_INTERVAL).await;
+                    }
+                    next_version = Some(version);
+                },
+                Err(e) => {
+                    warn!(
+                        "Failed to dispatch the transactions to the webhooks: {:?}",
+                        e
+                    );
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                },
+            }
+        }
+    }
+}
+
+/// Reads the transactions from `start_version`, up to a page, and queues the ones matching the
+/// webhooks. Returns the next version to read.
+fn dispatch(webhooks: &[Webhook], context: &Context, start_version: u64) -> Result<u64> {
+    let ledger_info = context.get_latest_ledger_info_wrapped()?;
+    let ledger_version = ledger_info.version();
+    if start_version > ledger_version {
+        return Ok(start_version);
+    }
+    let limit = std::cmp::min(
+        ledger_version - start_version + 1,
+        context.max_transactions_page_size() as u64,
+    ) as u16;
+    let transactions = context.get_transactions(start_version, limit, ledger_version)?;
+    let next_version = start_version + transactions.len() as u64;
+
+    let (matches, matched): (Vec<Vec<usize>>, Vec<TransactionOnChainData>) = transactions
+        .into_iter()
+        .filter_map(|txn| {
+            let matches: Vec<_> = webhooks
+                .iter()
+                .enumerate()
+                .filter(|(_, webhook)| webhook.filter.matches(&txn))
+                .map(|(index, _)| index)
+                .collect();
+            (!matches.is_empty()).then_some((matches, txn))
+        })
+        .unzip();
+    let rendered = context
+        .render_transactions_non_sequential::<BasicError>(&ledger_info, matched)
+        .map_err(anyhow::Error::from)?;
+    for (indices, transaction) in matches.into_iter().zip(rendered) {
+        let transaction = Arc::new(transaction);
+        for index in indices {
+            let webhook = &webhooks[index];
+            if webhook.sender.try_send(transaction.clone()).is_err() {
+                warn!(
+                    "Too many transactions are pending for webhook {}, dropping one",
+                    webhook.config.name
+                );
+                WEBHOOK_DELIVERIES
+                    .with_label_values(&[&webhook.config.name, "dropped"])
+                    .inc();
+            }
+        }
+    }
+    Ok(next_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::{
+        contract_event::ContractEvent,
+        event::EventKey,
+        transaction::{ExecutionStatus, TransactionInfo},
+        write_set::WriteSet,
+    };
+
+    fn webhook_config(addresses: Vec<AccountAddress>, event_types: Vec<&str>) -> WebhookConfig {
+        WebhookConfig {
+            name: "test".to_string(),
+            url: "http://localhost:8000/webhook".parse().unwrap(),
+            secret: "secret".to_string(),
+            addresses,
+            event_types: event_types.into_iter().map(str::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_webhook_filter() {
+        let address = AccountAddress::from_hex_literal("0xa").unwrap();
+        let txn = TransactionOnChainData {
+            version: 1,
+            transaction: aptos_types::transaction::Transaction::StateCheckpoint(
+                aptos_crypto::HashValue::zero(),
+            ),
+            info: TransactionInfo::new(
+                aptos_crypto::HashValue::zero(),
+                aptos_crypto::HashValue::zero(),
+                aptos_crypto::HashValue::zero(),
+                None,
+                0,
+                ExecutionStatus::Success,
+            ),
+            events: vec![ContractEvent::new_v1(
+                EventKey::new(2, address),
+                0,
+                parse_type_tag("0x1::coin::DepositEvent").unwrap(),
+                vec![],
+            )],
+            accumulator_root_hash: aptos_crypto::HashValue::zero(),
+            changes: WriteSet::default(),
+        };
+        let filter = |addresses, event_types| {
+            WebhookFilter::new(&webhook_config(addresses, event_types)).unwrap()
+        };
+
+        assert!(filter(vec![address], vec![]).matches(&txn));
+        assert!(!filter(vec![AccountAddress::ONE], vec![]).matches(&txn));
+        assert!(filter(vec![], vec!["0x1::coin::DepositEvent"]).matches(&txn));
+        assert!(!filter(vec![], vec!["0x1::coin::WithdrawEvent"]).matches(&txn));
+        assert!(filter(vec![AccountAddress::ONE], vec!["0x1::coin::DepositEvent"]).matches(&txn));
+        assert!(WebhookFilter::new(&webhook_config(vec![], vec!["0x1::coin"])).is_err());
+    }
+
+    #[test]
+    fn test_sign() {
+        // From RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
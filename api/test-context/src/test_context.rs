@@ -839,6 +839,17 @@ impl TestContext {
         sender: &LocalAccount,
         payload: Value,
         status_code: u16,
+    ) -> Value {
+        self.simulate_transaction_with_state_overrides(sender, payload, Value::Null, status_code)
+            .await
+    }
+
+    pub async fn simulate_transaction_with_state_overrides(
+        &mut self,
+        sender: &LocalAccount,
+        payload: Value,
+        state_overrides: Value,
+        status_code: u16,
     ) -> Value {
         let mut request = json!({
             "sender": sender.address(),
@@ -870,6 +881,9 @@ impl TestContext {
             "public_key": HexEncodedBytes::from(sender.public_key().to_bytes().to_vec()),
             "signature": HexEncodedBytes::from(sig.to_bytes().to_vec()),
         });
+        if !state_overrides.is_null() {
+            request["state_overrides"] = state_overrides;
+        }
 
         self.expect_status_code(status_code)
             .post("/transactions/simulate", request)
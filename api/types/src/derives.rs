@@ -21,7 +21,7 @@
 use crate::{
     move_types::{MoveAbility, MoveStructValue},
     Address, EntryFunctionId, HashValue, HexEncodedBytes, IdentifierWrapper, MoveModuleId,
-    MoveStructTag, MoveType, StateKeyWrapper, U128, U256, U64,
+    MoveStructTag, MoveType, PageCursor, StateKeyWrapper, U128, U256, U64,
 };
 use aptos_openapi::{impl_poem_parameter, impl_poem_type};
 use indoc::indoc;
@@ -242,6 +242,17 @@ impl_poem_type!(
     )
 );
 
+impl_poem_type!(
+    PageCursor,
+    "string",
+    (
+        example = Some(serde_json::Value::String("00e803000000000000".to_string())),
+        description = Some(indoc! {"
+          An opaque cursor to the next page of a paginated list, returned in the X-Aptos-Cursor header.
+        "})
+    )
+);
+
 impl_poem_type!(
     U64,
     "string",
@@ -297,6 +308,7 @@ impl_poem_parameter!(
     IdentifierWrapper,
     HexEncodedBytes,
     MoveStructTag,
+    PageCursor,
     StateKeyWrapper,
     U64,
     U128
@@ -2,8 +2,9 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::U64;
 use aptos_types::vm_status::StatusCode;
-use poem_openapi::{Enum, Object};
+use poem_openapi::{Enum, Object, Union};
 use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
 
@@ -16,6 +17,10 @@ pub struct AptosError {
     pub error_code: AptosErrorCode,
     /// A code providing VM error details when submitting transactions to the VM
     pub vm_error_code: Option<u64>,
+    /// Details specific to the error code, so that clients don't have to parse the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub details: Option<AptosErrorDetails>,
 }
 
 impl std::fmt::Display for AptosError {
@@ -35,6 +40,7 @@ impl AptosError {
             message: format!("{:#}", error),
             error_code,
             vm_error_code: None,
+            details: None,
         }
     }
 
@@ -47,12 +53,62 @@ impl AptosError {
             message: format!("{:#}", error),
             error_code,
             vm_error_code: Some(vm_error_code as u64),
+            details: None,
         }
     }
+
+    pub fn with_details(mut self, details: AptosErrorDetails) -> AptosError {
+        self.details = Some(details);
+        self
+    }
+}
+
+/// Details of an error that depend on its error code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Union)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[oai(one_of, discriminator_name = "type", rename_all = "snake_case")]
+pub enum AptosErrorDetails {
+    VersionPruned(VersionPrunedDetails),
+    BlockPruned(BlockPrunedDetails),
+    SequenceNumberTooOld(SequenceNumberTooOldDetails),
+    RateLimitExceeded(RateLimitExceededDetails),
+}
+
+/// Details of a `version_pruned` error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct VersionPrunedDetails {
+    /// The oldest version whose data is available, which is later for the state than for the
+    /// transactions, as they are pruned separately
+    pub oldest_available_version: U64,
+    /// The number of versions kept before they are pruned, if known
+    pub prune_window: Option<U64>,
+}
+
+/// Details of a `block_pruned` error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct BlockPrunedDetails {
+    /// The oldest block height whose transactions are available
+    pub oldest_available_block_height: U64,
+}
+
+/// Details of a `sequence_number_too_old` error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SequenceNumberTooOldDetails {
+    /// The sequence number of the account at the latest ledger version, which is the lowest
+    /// sequence number a new transaction can use
+    pub current_sequence_number: U64,
+}
+
+/// Details of a `rate_limit_exceeded` error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct RateLimitExceededDetails {
+    /// Seconds to wait before the next request is accepted
+    pub retry_after_secs: U64,
 }
 
 /// These codes provide more granular error information beyond just the HTTP
-/// status code of the response.
+/// status code of the response. The codes are stable, so clients can branch on
+/// them instead of parsing the message of the error.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, Enum)]
 #[oai(rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
@@ -89,6 +145,10 @@ pub enum AptosErrorCode {
 
     /// The API's inputs were invalid
     InvalidInput = 300,
+    /// The API key of the request is missing or unknown
+    InvalidApiKey = 301,
+    /// The requests of the API key exceeded its rate limit
+    RateLimitExceeded = 302,
 
     /// The transaction was an invalid update to an already submitted transaction.
     InvalidTransactionUpdate = 401,
@@ -133,4 +193,31 @@ fn test_serialize_deserialize() {
     let _: AptosError = bcs::from_bytes(&bcs::to_bytes(&without_code).unwrap()).unwrap();
     let _: AptosError =
         serde_json::from_str(&serde_json::to_string(&without_code).unwrap()).unwrap();
+
+    let with_details =
+        AptosError::new_with_error_code("Version pruned", AptosErrorCode::VersionPruned)
+            .with_details(AptosErrorDetails::VersionPruned(VersionPrunedDetails {
+                oldest_available_version: 100.into(),
+                prune_window: None,
+            }));
+    let json = serde_json::to_value(&with_details).unwrap();
+    assert_eq!(
+        json["details"],
+        serde_json::json!({
+            "type": "version_pruned",
+            "oldest_available_version": "100",
+            "prune_window": null,
+        })
+    );
+    let deserialized: AptosError = serde_json::from_value(json).unwrap();
+    assert_eq!(deserialized.details, with_details.details);
+
+    // Errors of the servers without details can still be parsed
+    let without_details: AptosError = serde_json::from_value(serde_json::json!({
+        "message": "some message",
+        "error_code": "mempool_is_full",
+        "vm_error_code": null,
+    }))
+    .unwrap();
+    assert_eq!(without_details.details, None);
 }
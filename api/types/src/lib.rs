@@ -28,7 +28,10 @@ pub use address::Address;
 pub use block::{BcsBlock, Block};
 pub use bytecode::Bytecode;
 pub use convert::{new_vm_utf8_string, AsConverter, ExplainVMStatus, MoveConverter};
-pub use error::{AptosError, AptosErrorCode};
+pub use error::{
+    AptosError, AptosErrorCode, AptosErrorDetails, BlockPrunedDetails, RateLimitExceededDetails,
+    SequenceNumberTooOldDetails, VersionPrunedDetails,
+};
 pub use hash::HashValue;
 pub use headers::*;
 pub use index::{IndexResponse, IndexResponseBcs};
@@ -36,29 +39,31 @@ pub use ledger_info::LedgerInfo;
 pub use move_types::{
     verify_field_identifier, verify_function_identifier, verify_module_identifier, EntryFunctionId,
     HexEncodedBytes, MoveAbility, MoveFunction, MoveFunctionGenericTypeParam,
-    MoveFunctionVisibility, MoveModule, MoveModuleBytecode, MoveModuleId, MoveResource,
-    MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType, MoveValue,
-    ResourceGroup, MAX_RECURSIVE_TYPES_ALLOWED, U128, U256, U64,
+    MoveFunctionVisibility, MoveModule, MoveModuleBytecode, MoveModuleFunctions, MoveModuleId,
+    MoveResource, MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType,
+    MoveValue, ResourceGroup, MAX_RECURSIVE_TYPES_ALLOWED, U128, U256, U64,
 };
 use serde::{Deserialize, Deserializer};
 pub use state::RawStateValueRequest;
 use std::str::FromStr;
-pub use table::{RawTableItemRequest, TableItemRequest};
+pub use table::{RawTableItemRequest, TableItem, TableItemRequest, TableItemsRequest};
 pub use transaction::{
-    AccountSignature, BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem,
-    DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest, EntryFunctionPayload, Event,
-    FeePayerSignature, GasEstimation, GasEstimationBcs, GenesisPayload, GenesisTransaction,
-    ModuleBundlePayload, MultiAgentSignature, MultiEd25519Signature, MultisigPayload,
-    MultisigTransactionPayload, PendingTransaction, ScriptPayload, ScriptWriteSet,
-    Secp256k1EcdsaSignature, SubmitTransactionRequest, Transaction, TransactionData, TransactionId,
-    TransactionInfo, TransactionOnChainData, TransactionPayload, TransactionSignature,
-    TransactionSigningMessage, TransactionsBatchSingleSubmissionFailure,
-    TransactionsBatchSubmissionResult, UserCreateSigningMessageRequest, UserTransaction,
-    UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet, WriteSetChange,
-    WriteSetPayload, WriteTableItem,
+    AccountSignature, BalanceOverride, BcsTransactionByHash, BcsTransactionsPage,
+    BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem, DirectWriteSet,
+    Ed25519Signature, EncodeSubmissionRequest, EntryFunctionPayload, Event, EventOrder,
+    FeePayerSignature, GasEstimation, GasEstimationBcs, GasPricePercentile, GenesisPayload,
+    GenesisTransaction, ModuleBundlePayload, MultiAgentSignature, MultiEd25519Signature,
+    MultisigPayload, MultisigTransactionPayload, PendingTransaction, ResourceOverride,
+    ScriptPayload, ScriptWriteSet, Secp256k1EcdsaSignature, SimulateTransactionRequest,
+    StateOverrides, SubmitTransactionRequest, Transaction, TransactionByHash, TransactionData,
+    TransactionGasEstimation, TransactionId, TransactionInfo, TransactionOnChainData,
+    TransactionPayload, TransactionSignature, TransactionSigningMessage,
+    TransactionsBatchSingleSubmissionFailure, TransactionsBatchSubmissionResult, TransactionsPage,
+    UserCreateSigningMessageRequest, UserTransaction, UserTransactionRequest, VersionedEvent,
+    WriteModule, WriteResource, WriteSet, WriteSetChange, WriteSetPayload, WriteTableItem,
 };
-pub use view::ViewRequest;
-pub use wrappers::{EventGuid, IdentifierWrapper, StateKeyWrapper};
+pub use view::{ViewRequest, ViewResponseBcs};
+pub use wrappers::{EventGuid, IdentifierWrapper, PageCursor, StateKeyWrapper};
 
 pub fn deserialize_from_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
 where
@@ -742,6 +742,14 @@ pub struct MoveModule {
     pub structs: Vec<MoveStruct>,
 }
 
+/// The entry and view functions of a Move module
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct MoveModuleFunctions {
+    pub module: MoveModuleId,
+    /// Entry and view functions of the module
+    pub functions: Vec<MoveFunction>,
+}
+
 impl From<CompiledModule> for MoveModule {
     fn from(m: CompiledModule) -> Self {
         let (address, name) = <(AccountAddress, Identifier)>::from(m.self_id());
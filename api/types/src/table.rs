@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{HexEncodedBytes, MoveType, VerifyInput, VerifyInputWithRecursion};
+use crate::{HexEncodedBytes, MoveType, MoveValue, VerifyInput, VerifyInputWithRecursion};
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -22,6 +22,35 @@ impl VerifyInput for TableItemRequest {
     }
 }
 
+/// Table Items request for the GetTableItems API
+///
+/// The types are required to return the items as JSON. The items whose key isn't
+/// a `key_type` are skipped.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TableItemsRequest {
+    pub key_type: Option<MoveType>,
+    pub value_type: Option<MoveType>,
+}
+
+impl VerifyInput for TableItemsRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        if let Some(key_type) = &self.key_type {
+            key_type.verify(0)?;
+        }
+        if let Some(value_type) = &self.value_type {
+            value_type.verify(0)?;
+        }
+        Ok(())
+    }
+}
+
+/// A table item with its key, as returned by the GetTableItems API
+#[derive(Clone, Debug, PartialEq, Serialize, Object)]
+pub struct TableItem {
+    pub key: MoveValue,
+    pub value: MoveValue,
+}
+
 /// Table Item request for the GetTableItemRaw API
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct RawTableItemRequest {
@@ -3,9 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    Address, AptosError, EntryFunctionId, EventGuid, HashValue, HexEncodedBytes,
-    MoveModuleBytecode, MoveModuleId, MoveResource, MoveScriptBytecode, MoveStructTag, MoveType,
-    MoveValue, VerifyInput, VerifyInputWithRecursion, U64,
+    move_types::MoveStructValue, Address, AptosError, EntryFunctionId, EventGuid, HashValue,
+    HexEncodedBytes, MoveModuleBytecode, MoveModuleId, MoveResource, MoveScriptBytecode,
+    MoveStructTag, MoveType, MoveValue, PageCursor, VerifyInput, VerifyInputWithRecursion, U64,
 };
 use anyhow::{bail, Context as AnyhowContext};
 use aptos_crypto::{
@@ -23,7 +23,7 @@ use aptos_types::{
     },
 };
 use once_cell::sync::Lazy;
-use poem_openapi::{Object, Union};
+use poem_openapi::{Enum, Object, Union};
 use serde::{Deserialize, Serialize};
 use std::{
     boxed::Box,
@@ -272,6 +272,7 @@ impl
             request: (txn, payload).into(),
             events,
             timestamp: timestamp.into(),
+            gas_profile: None,
         }))
     }
 }
@@ -372,6 +373,11 @@ pub struct UserTransaction {
     /// Events generated by the transaction
     pub events: Vec<Event>,
     pub timestamp: U64,
+    /// Gas profile of the transaction, broken down by function calls, storage accesses and
+    /// writes. Only present for simulated transactions if gas profiling was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub gas_profile: Option<serde_json::Value>,
 }
 
 /// A state checkpoint transaction
@@ -401,6 +407,87 @@ impl VerifyInput for SubmitTransactionRequest {
     }
 }
 
+/// A request to simulate a transaction
+///
+/// This is a SubmitTransactionRequest, with optional changes to the state the
+/// transaction is simulated on
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulateTransactionRequest {
+    #[serde(flatten)]
+    #[oai(flatten)]
+    pub submit_transaction_request: SubmitTransactionRequest,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub state_overrides: Option<StateOverrides>,
+}
+
+impl VerifyInput for SimulateTransactionRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        self.submit_transaction_request.verify()?;
+        if let Some(state_overrides) = &self.state_overrides {
+            state_overrides.verify()?;
+        }
+        Ok(())
+    }
+}
+
+/// Changes to the state a transaction is simulated on
+///
+/// The changes only apply to the simulation, and are never persisted
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct StateOverrides {
+    /// Resources to replace, or to create if they don't exist
+    ///
+    /// Resources that are members of a resource group can't be overridden
+    #[serde(default)]
+    #[oai(default)]
+    pub resources: Vec<ResourceOverride>,
+    /// Coin balances to replace
+    #[serde(default)]
+    #[oai(default)]
+    pub balances: Vec<BalanceOverride>,
+    /// Current time of the chain, in microseconds
+    pub timestamp_usecs: Option<U64>,
+}
+
+impl VerifyInput for StateOverrides {
+    fn verify(&self) -> anyhow::Result<()> {
+        for resource in &self.resources {
+            resource.typ.verify(0)?;
+        }
+        for balance in &self.balances {
+            if let Some(coin_type) = &balance.coin_type {
+                coin_type.verify(0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A resource to replace in a simulation
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceOverride {
+    /// Account holding the resource
+    pub address: Address,
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: MoveStructTag,
+    pub data: MoveStructValue,
+}
+
+/// A coin balance to replace in a simulation
+///
+/// The account must already have a coin store for the coin. Only the balance of
+/// the store is replaced.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct BalanceOverride {
+    /// Account holding the coin store
+    pub address: Address,
+    /// Type of the coin, defaults to `0x1::aptos_coin::AptosCoin`
+    pub coin_type: Option<MoveStructTag>,
+    pub amount: U64,
+}
+
 /// Batch transaction submission result
 ///
 /// Tells which transactions failed
@@ -553,6 +640,17 @@ impl From<(&ContractEvent, serde_json::Value)> for Event {
     }
 }
 
+/// Order of the events of an event stream
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum EventOrder {
+    /// From the oldest event to the newest
+    Ascending,
+    /// From the newest event to the oldest
+    Descending,
+}
+
 /// An event from a transaction with a version
 #[derive(Clone, Debug, Deserialize, Eq, Object, PartialEq, Serialize)]
 pub struct VersionedEvent {
@@ -1515,6 +1613,45 @@ impl TransactionSigningMessage {
     }
 }
 
+/// A page of committed transactions
+///
+/// The cursor of the next page is returned with the transactions, so that
+/// it doesn't have to be read from a header.
+#[derive(Clone, Debug, Serialize, Deserialize, Object)]
+pub struct TransactionsPage {
+    /// The transactions of the page in sequential order
+    pub transactions: Vec<Transaction>,
+    /// The cursor of the next page
+    pub cursor: PageCursor,
+}
+
+/// A page of committed transactions for encoding in BCS
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BcsTransactionsPage {
+    /// The transactions of the page in sequential order
+    pub transactions: Vec<TransactionOnChainData>,
+    /// The cursor of the next page
+    pub cursor: PageCursor,
+}
+
+/// A transaction looked up by hash in a batch
+#[derive(Clone, Debug, Serialize, Deserialize, Object)]
+pub struct TransactionByHash {
+    /// The hash the transaction was looked up with
+    pub hash: HashValue,
+    /// The committed or pending transaction, or null if it isn't found
+    pub transaction: Option<Transaction>,
+}
+
+/// A transaction looked up by hash in a batch for encoding in BCS
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BcsTransactionByHash {
+    /// The hash the transaction was looked up with
+    pub hash: aptos_crypto::HashValue,
+    /// The committed or pending transaction, if it's found
+    pub transaction: Option<TransactionData>,
+}
+
 /// Struct holding the outputs of the estimate gas API
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct GasEstimationBcs {
@@ -1523,7 +1660,7 @@ pub struct GasEstimationBcs {
 }
 
 /// Struct holding the outputs of the estimate gas API
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct GasEstimation {
     /// The deprioritized estimate for the gas unit price
     pub deprioritized_gas_estimate: Option<u64>,
@@ -1531,4 +1668,28 @@ pub struct GasEstimation {
     pub gas_estimate: u64,
     /// The prioritized estimate for the gas unit price
     pub prioritized_gas_estimate: Option<u64>,
+    /// The gas unit prices at the requested percentiles of the recent blocks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub percentiles: Option<Vec<GasPricePercentile>>,
+}
+
+/// A gas unit price at a percentile of the recent blocks
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct GasPricePercentile {
+    /// The percentile, from 0 to 100
+    pub percentile: u8,
+    /// The minimum gas unit price to get into the blocks, at the percentile
+    pub gas_unit_price: u64,
+}
+
+/// Struct holding the outputs of the estimate gas API for a transaction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TransactionGasEstimation {
+    /// The gas units the transaction used when simulated
+    pub gas_used: u64,
+    /// The suggested max gas amount of the transaction: the gas used, plus a safety margin
+    pub max_gas_amount: u64,
+    /// The current estimate for the gas unit price, which the transaction was simulated with
+    pub gas_unit_price: u64,
 }
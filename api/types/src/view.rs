@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{EntryFunctionId, MoveType};
+use move_core_types::language_storage::TypeTag;
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 
@@ -14,3 +15,13 @@ pub struct ViewRequest {
     /// Arguments of the function
     pub arguments: Vec<serde_json::Value>,
 }
+
+/// The values returned by a view function, with their types resolved with the type arguments of
+/// the function, so that they can be decoded without a lossy round trip through JSON. This is
+/// just for the BCS response.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewResponseBcs {
+    pub return_types: Vec<TypeTag>,
+    /// BCS encoded values, one per return type
+    pub values: Vec<Vec<u8>>,
+}
@@ -151,3 +151,46 @@ impl From<StateKeyWrapper> for StateKey {
         value.0
     }
 }
+
+/// The position to continue a paginated list from, serialized as hex encoded BCS so that it is
+/// opaque to the clients. Unlike a start given by the client, it carries the kind of position it
+/// is, so that the cursor of an endpoint can't be passed to another one by mistake.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum PageCursor {
+    /// The next ledger version
+    Version(u64),
+    /// The next sequence number of the transactions of an account or of an event stream
+    SequenceNumber(u64),
+}
+
+impl PageCursor {
+    pub fn version(self) -> Option<u64> {
+        match self {
+            PageCursor::Version(version) => Some(version),
+            PageCursor::SequenceNumber(_) => None,
+        }
+    }
+
+    pub fn sequence_number(self) -> Option<u64> {
+        match self {
+            PageCursor::SequenceNumber(sequence_number) => Some(sequence_number),
+            PageCursor::Version(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for PageCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hex_string = hex::encode(bcs::to_bytes(self).map_err(|_| fmt::Error)?);
+        write!(f, "{}", hex_string)
+    }
+}
+
+impl FromStr for PageCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self, anyhow::Error> {
+        bcs::from_bytes(&hex::decode(s).context("Failed to decode cursor as hex string")?)
+            .context("Failed to decode cursor from hex string")
+    }
+}
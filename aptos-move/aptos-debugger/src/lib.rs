@@ -105,7 +105,7 @@ impl AptosDebugger {
             },
         )?;
 
-        Ok((status, output, gas_profiler.finish()))
+        Ok((status, output, gas_profiler.finish(None)))
     }
 
     pub async fn execute_past_transactions(
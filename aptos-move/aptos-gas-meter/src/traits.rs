@@ -11,7 +11,10 @@ use aptos_vm_types::{
     storage::StorageGasParameters,
 };
 use move_binary_format::errors::{Location, PartialVMResult, VMResult};
-use move_core_types::gas_algebra::{InternalGas, InternalGasUnit, NumBytes};
+use move_core_types::{
+    gas_algebra::{InternalGas, InternalGasUnit, NumBytes},
+    language_storage::ModuleId,
+};
 use move_vm_types::gas::GasMeter as MoveGasMeter;
 use std::fmt::Debug;
 
@@ -118,6 +121,30 @@ pub trait AptosGasMeter: MoveGasMeter {
         group_write: &GroupWrite,
     ) -> VMResult<()>;
 
+    /// Returns whether the gas meter wants to be notified of every module loaded as a dependency
+    /// of the transaction via [`AptosGasMeter::charge_dependency`].
+    ///
+    /// Walking the dependency closure of the transaction is not free, so the VM only does it for
+    /// gas meters that ask for it, such as profilers.
+    fn tracks_dependencies(&self) -> bool {
+        false
+    }
+
+    /// Charges for a module that is loaded as a dependency of the transaction, including
+    /// modules that are being published.
+    ///
+    /// Dependencies are not priced separately under the current gas schedule, so the default
+    /// implementation charges nothing. Gas meters that want to observe module loading, such
+    /// as profilers, can override this.
+    fn charge_dependency(
+        &mut self,
+        _is_new: bool,
+        _module_id: &ModuleId,
+        _size: NumBytes,
+    ) -> VMResult<()> {
+        Ok(())
+    }
+
     /// Calculates the storage fee for a state slot allocation.
     fn storage_fee_for_state_slot(&self, op: &WriteOp) -> Fee;
 
@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    log::{ExecutionAndIOCosts, ExecutionGasEvent},
+    log::{ExecutionAndIOCosts, ExecutionGasEvent, TransactionGasLog},
     render::{Render, TableKey},
 };
-use aptos_gas_algebra::{GasQuantity, GasScalingFactor, InternalGas};
-use aptos_types::state_store::state_key::StateKeyInner;
+use aptos_gas_algebra::{Fee, GasQuantity, GasScalingFactor, InternalGas, NumBytes};
+use aptos_types::state_store::state_key::{StateKey, StateKeyInner};
+use move_core_types::{account_address::AccountAddress, language_storage::TypeTag};
+use serde::Serialize;
 use std::{
     collections::{btree_map, BTreeMap},
     ops::Deref,
@@ -27,11 +29,80 @@ pub struct AggregatedExecutionGasEvents {
     pub ops: Vec<(String, usize, InternalGas)>,
     pub storage_reads: Vec<(String, usize, InternalGas)>,
     pub storage_writes: Vec<(String, usize, InternalGas)>,
+
+    pub resource_loads: AggregatedResourceLoads,
+}
+
+/// The number of loads, total cost and total bytes loaded for a group of resources.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ResourceLoadStats {
+    pub count: usize,
+    pub cost: InternalGas,
+    pub bytes: NumBytes,
+}
+
+/// Aggregated costs of all resource loads in a transaction, grouped by type and by address.
+///
+/// The groups are sorted by the amount of gas used, from high to low.
+#[derive(Debug, Serialize)]
+pub struct AggregatedResourceLoads {
+    pub by_type: Vec<(TypeTag, ResourceLoadStats)>,
+    pub by_address: Vec<(AccountAddress, ResourceLoadStats)>,
+}
+
+/// The I/O costs and storage fees of all writes to a single state item.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct StateKeyIoStats {
+    /// Number of transient (IO) charges for the item.
+    pub transient_count: usize,
+    pub transient: InternalGas,
+    /// Number of storage fee charges for the item.
+    pub storage_count: usize,
+    pub slot_fee: Fee,
+    pub bytes_fee: Fee,
+}
+
+impl StateKeyIoStats {
+    fn new() -> Self {
+        Self {
+            transient_count: 0,
+            transient: 0.into(),
+            storage_count: 0,
+            slot_fee: 0.into(),
+            bytes_fee: 0.into(),
+        }
+    }
+
+    /// Total storage fee paid for the item.
+    pub fn storage_fee(&self) -> Fee {
+        self.slot_fee + self.bytes_fee
+    }
+}
+
+/// The write costs of a transaction, grouped by state key.
+///
+/// The keys are sorted by their storage fees and then by their transient costs, from high to low.
+#[derive(Debug, Serialize)]
+pub struct AggregatedStateKeyCosts {
+    pub gas_scaling_factor: GasScalingFactor,
+    pub keys: Vec<(StateKey, StateKeyIoStats)>,
+}
+
+pub(crate) fn insert_or_add<K, U>(
+    map: &mut BTreeMap<K, (usize, GasQuantity<U>)>,
+    key: K,
+    amount: GasQuantity<U>,
+) where
+    K: Ord,
+{
+    insert_or_add_many(map, key, 1, amount)
 }
 
-fn insert_or_add<K, U>(
+/// Same as `insert_or_add`, but for an item that has been hit `count` times in total.
+pub(crate) fn insert_or_add_many<K, U>(
     map: &mut BTreeMap<K, (usize, GasQuantity<U>)>,
     key: K,
+    count: usize,
     amount: GasQuantity<U>,
 ) where
     K: Ord,
@@ -42,16 +113,16 @@ fn insert_or_add<K, U>(
     match map.entry(key) {
         btree_map::Entry::Occupied(entry) => {
             let r = entry.into_mut();
-            r.0 += 1;
+            r.0 += count;
             r.1 += amount;
         },
         btree_map::Entry::Vacant(entry) => {
-            entry.insert((1, amount));
+            entry.insert((count, amount));
         },
     }
 }
 
-fn into_sorted_vec<I, K, N>(collection: I) -> Vec<(K, usize, N)>
+pub(crate) fn into_sorted_vec<I, K, N>(collection: I) -> Vec<(K, usize, N)>
 where
     N: Ord,
     I: IntoIterator<Item = (K, (usize, N))>,
@@ -65,6 +136,22 @@ where
     v
 }
 
+/// Returns the key under which writes to the given state item are aggregated.
+///
+/// The address is dropped so that writes to the same resource type under different
+/// accounts end up in the same bucket.
+pub(crate) fn aggregation_key(key: &StateKey) -> String {
+    use StateKeyInner::*;
+
+    match key.deref() {
+        AccessPath(ap) => format!("{}", Render(&ap.get_path())),
+        TableItem { handle, key } => {
+            format!("table_item<{},{}>", Render(handle), TableKey { bytes: key },)
+        },
+        Raw(..) => panic!("not supported"),
+    }
+}
+
 impl ExecutionAndIOCosts {
     /// Counts the number of hits and aggregates the gas costs for each type of event.
     pub fn aggregate_gas_events(&self) -> AggregatedExecutionGasEvents {
@@ -77,9 +164,12 @@ impl ExecutionAndIOCosts {
         for event in self.gas_events() {
             match event {
                 Loc(..) | Call(..) => (),
-                Bytecode { op, cost } => insert_or_add(
+                Bytecode {
+                    op, cost, count, ..
+                } => insert_or_add_many(
                     &mut ops,
                     format!("{:?}", op).to_ascii_lowercase().to_string(),
+                    *count,
                     *cost,
                 ),
                 CallNative {
@@ -87,6 +177,7 @@ impl ExecutionAndIOCosts {
                     fn_name,
                     ty_args,
                     cost,
+                    ..
                 } => insert_or_add(
                     &mut ops,
                     format!(
@@ -95,26 +186,14 @@ impl ExecutionAndIOCosts {
                     ),
                     *cost,
                 ),
-                LoadResource {
-                    addr: _addr,
-                    ty,
-                    cost,
-                } => insert_or_add(&mut storage_reads, format!("{}", ty), *cost),
+                LoadResource { ty, cost, .. } => {
+                    insert_or_add(&mut storage_reads, format!("{}", ty), *cost)
+                },
             }
         }
 
         for write in &self.write_set_transient {
-            use StateKeyInner::*;
-
-            let key = match write.key.deref() {
-                AccessPath(ap) => format!("{}", Render(&ap.get_path())),
-                TableItem { handle, key } => {
-                    format!("table_item<{},{}>", Render(handle), TableKey { bytes: key },)
-                },
-                Raw(..) => panic!("not supported"),
-            };
-
-            insert_or_add(&mut storage_writes, key, write.cost);
+            insert_or_add(&mut storage_writes, aggregation_key(&write.key), write.cost);
         }
 
         AggregatedExecutionGasEvents {
@@ -124,6 +203,89 @@ impl ExecutionAndIOCosts {
             ops: into_sorted_vec(ops),
             storage_reads: into_sorted_vec(storage_reads),
             storage_writes: into_sorted_vec(storage_writes),
+            resource_loads: self.aggregate_resource_loads(),
+        }
+    }
+
+    /// Aggregates the costs and sizes of all resource loads, grouped by type and by address.
+    pub fn aggregate_resource_loads(&self) -> AggregatedResourceLoads {
+        let mut by_type = BTreeMap::new();
+        let mut by_address = BTreeMap::new();
+
+        for event in self.gas_events() {
+            if let ExecutionGasEvent::LoadResource {
+                addr,
+                ty,
+                cost,
+                bytes_loaded,
+            } = event
+            {
+                add_resource_load(&mut by_type, ty.clone(), *cost, *bytes_loaded);
+                add_resource_load(&mut by_address, *addr, *cost, *bytes_loaded);
+            }
+        }
+
+        AggregatedResourceLoads {
+            by_type: into_sorted_load_stats(by_type),
+            by_address: into_sorted_load_stats(by_address),
+        }
+    }
+}
+
+fn add_resource_load<K>(
+    map: &mut BTreeMap<K, ResourceLoadStats>,
+    key: K,
+    cost: InternalGas,
+    bytes: NumBytes,
+) where
+    K: Ord,
+{
+    let stats = map.entry(key).or_insert(ResourceLoadStats {
+        count: 0,
+        cost: 0.into(),
+        bytes: 0.into(),
+    });
+    stats.count += 1;
+    stats.cost += cost;
+    stats.bytes += bytes;
+}
+
+fn into_sorted_load_stats<K>(map: BTreeMap<K, ResourceLoadStats>) -> Vec<(K, ResourceLoadStats)> {
+    let mut v = map.into_iter().collect::<Vec<_>>();
+    // Sort in descending order.
+    v.sort_by(|(_key1, stats1), (_key2, stats2)| stats2.cost.cmp(&stats1.cost));
+    v
+}
+
+impl TransactionGasLog {
+    /// Groups the transient (IO) costs and the storage fees of all writes by the state key
+    /// they were charged for.
+    pub fn aggregate_state_key_costs(&self) -> AggregatedStateKeyCosts {
+        let mut keys: BTreeMap<&StateKey, StateKeyIoStats> = BTreeMap::new();
+        for write in &self.exec_io.write_set_transient {
+            let stats = keys.entry(&write.key).or_insert_with(StateKeyIoStats::new);
+            stats.transient_count += 1;
+            stats.transient += write.cost;
+        }
+        for write in &self.storage.write_set_storage {
+            let stats = keys.entry(&write.key).or_insert_with(StateKeyIoStats::new);
+            stats.storage_count += 1;
+            stats.slot_fee += write.slot_fee;
+            stats.bytes_fee += write.bytes_fee;
+        }
+
+        let mut keys = keys
+            .into_iter()
+            .map(|(key, stats)| (key.clone(), stats))
+            .collect::<Vec<_>>();
+        // Sort in descending order.
+        keys.sort_by(|(_key1, stats1), (_key2, stats2)| {
+            (stats2.storage_fee(), stats2.transient).cmp(&(stats1.storage_fee(), stats1.transient))
+        });
+
+        AggregatedStateKeyCosts {
+            gas_scaling_factor: self.exec_io.gas_scaling_factor,
+            keys,
         }
     }
 }
@@ -0,0 +1,321 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::{CallFrame, ExecutionAndIOCosts, ExecutionGasEvent};
+use aptos_gas_algebra::{GasScalingFactor, InternalGas};
+use move_binary_format::{file_format::CodeOffset, file_format_common::Opcodes};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Gas statistics of a single basic block, aggregated over all of its executions.
+#[derive(Debug, Clone, Serialize)]
+pub struct BasicBlockStats {
+    /// Name of the function (or script) the block belongs to.
+    pub function: String,
+    /// Code offset of the first instruction in the block.
+    pub start: CodeOffset,
+    /// Code offset of the last instruction in the block.
+    pub end: CodeOffset,
+    pub executions: usize,
+    /// Total cost of the instructions, native calls and resource loads in the block.
+    /// Costs of non-native callees are attributed to the blocks of the callees instead.
+    pub cost: InternalGas,
+}
+
+/// Gas costs of a transaction, attributed to the basic blocks of the functions executed.
+///
+/// The blocks are sorted by the amount of gas used, from high to low.
+#[derive(Debug, Serialize)]
+pub struct AggregatedBasicBlocks {
+    pub gas_scaling_factor: GasScalingFactor,
+    pub total: InternalGas,
+    pub blocks: Vec<BasicBlockStats>,
+}
+
+fn is_terminator(op: Opcodes) -> bool {
+    use Opcodes::*;
+
+    matches!(op, BR_TRUE | BR_FALSE | BRANCH | RET | ABORT)
+}
+
+/// Collects the offsets at which basic blocks start, for every function in the call graph.
+///
+/// Since the profiler does not have access to the bytecode, the leaders are derived from the
+/// trace itself: the entry of the function, every branch target that has been jumped to and
+/// every instruction following a terminator.
+fn collect_leaders(frame: &CallFrame, leaders: &mut BTreeMap<String, BTreeSet<CodeOffset>>) {
+    let offsets = leaders.entry(format!("{}", frame.name)).or_default();
+    offsets.insert(0);
+
+    let mut callees = vec![];
+    let mut pc: CodeOffset = 0;
+    for event in &frame.events {
+        use ExecutionGasEvent::*;
+
+        match event {
+            Loc(offset) => {
+                offsets.insert(*offset);
+                pc = *offset;
+            },
+            Bytecode { op, .. } => {
+                pc = pc.wrapping_add(1);
+                if is_terminator(*op) {
+                    offsets.insert(pc);
+                }
+            },
+            Call(callee) => callees.push(callee),
+            CallNative { .. } | LoadResource { .. } => (),
+        }
+    }
+
+    for callee in callees {
+        collect_leaders(callee, leaders);
+    }
+}
+
+struct BlockCollector<'a> {
+    leaders: &'a BTreeMap<String, BTreeSet<CodeOffset>>,
+    blocks: BTreeMap<(String, CodeOffset), BasicBlockStats>,
+}
+
+impl<'a> BlockCollector<'a> {
+    fn record_block(
+        &mut self,
+        function: &str,
+        start: CodeOffset,
+        end: CodeOffset,
+        cost: InternalGas,
+    ) {
+        let stats = self
+            .blocks
+            .entry((function.to_string(), start))
+            .or_insert_with(|| BasicBlockStats {
+                function: function.to_string(),
+                start,
+                end,
+                executions: 0,
+                cost: 0.into(),
+            });
+        stats.end = stats.end.max(end);
+        stats.executions += 1;
+        stats.cost += cost;
+    }
+
+    fn visit_frame(&mut self, frame: &CallFrame) {
+        let function = format!("{}", frame.name);
+        let leaders: &'a BTreeMap<_, _> = self.leaders;
+        let leaders = &leaders[&function];
+
+        // The block currently being executed, if any, with its start offset and cost so far.
+        let mut current: Option<(CodeOffset, InternalGas)> = None;
+        let mut pc: CodeOffset = 0;
+        for event in &frame.events {
+            use ExecutionGasEvent::*;
+
+            match event {
+                Loc(offset) => {
+                    if let Some((start, cost)) = current.take() {
+                        self.record_block(&function, start, pc.saturating_sub(1), cost);
+                    }
+                    pc = *offset;
+                },
+                Bytecode { op, cost, .. } => {
+                    // Control may fall through into an instruction that is also the target of
+                    // a jump, in which case a new block starts there.
+                    if let Some((start, block_cost)) = current {
+                        if start != pc && leaders.contains(&pc) {
+                            self.record_block(&function, start, pc.saturating_sub(1), block_cost);
+                            current = None;
+                        }
+                    }
+
+                    let (_, block_cost) = current.get_or_insert((pc, 0.into()));
+                    *block_cost += *cost;
+
+                    if is_terminator(*op) {
+                        let (start, block_cost) = current.take().unwrap();
+                        self.record_block(&function, start, pc, block_cost);
+                    }
+                    pc = pc.wrapping_add(1);
+                },
+                CallNative { cost, .. } | LoadResource { cost, .. } => {
+                    let (_, block_cost) = current.get_or_insert((pc, 0.into()));
+                    *block_cost += *cost;
+                },
+                Call(callee) => self.visit_frame(callee),
+            }
+        }
+
+        // The execution may have been aborted in the middle of a block, e.g. by running out
+        // of gas.
+        if let Some((start, cost)) = current {
+            self.record_block(&function, start, pc.saturating_sub(1).max(start), cost);
+        }
+    }
+}
+
+impl ExecutionAndIOCosts {
+    /// Attributes the execution gas to the basic blocks of each function and returns the `limit`
+    /// most expensive ones, which helps to identify hot loops.
+    ///
+    /// Blocks are keyed by their function and start offset, so blocks of the same function are
+    /// aggregated across all calls. The log must contain one event per instruction, i.e. this is
+    /// not meaningful if the profiler was configured to aggregate bytecodes.
+    pub fn aggregate_basic_blocks(&self, limit: usize) -> AggregatedBasicBlocks {
+        let mut leaders = BTreeMap::new();
+        collect_leaders(&self.call_graph, &mut leaders);
+
+        let mut collector = BlockCollector {
+            leaders: &leaders,
+            blocks: BTreeMap::new(),
+        };
+        collector.visit_frame(&self.call_graph);
+
+        let mut blocks = collector.blocks.into_values().collect::<Vec<_>>();
+        // Sort in descending order.
+        blocks.sort_by(|block1, block2| block2.cost.cmp(&block1.cost));
+        blocks.truncate(limit);
+
+        AggregatedBasicBlocks {
+            gas_scaling_factor: self.gas_scaling_factor,
+            total: self.total,
+            blocks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::FrameName;
+    use move_core_types::{
+        account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    };
+
+    fn bytecode(op: Opcodes, cost: u64) -> ExecutionGasEvent {
+        ExecutionGasEvent::Bytecode {
+            op,
+            cost: InternalGas::new(cost),
+            count: 1,
+            loc: None,
+        }
+    }
+
+    fn native(cost: u64) -> ExecutionGasEvent {
+        ExecutionGasEvent::CallNative {
+            module_id: ModuleId::new(AccountAddress::ONE, Identifier::new("native").unwrap()),
+            fn_name: Identifier::new("hash").unwrap(),
+            ty_args: vec![],
+            cost: InternalGas::new(cost),
+            args_size: 0.into(),
+            ret_size: 0.into(),
+        }
+    }
+
+    fn callee() -> ExecutionGasEvent {
+        ExecutionGasEvent::Call(CallFrame {
+            name: FrameName::Function {
+                module_id: ModuleId::new(AccountAddress::ONE, Identifier::new("test").unwrap()),
+                name: Identifier::new("g").unwrap(),
+                ty_args: vec![],
+            },
+            events: vec![bytecode(Opcodes::RET, 7)],
+        })
+    }
+
+    fn costs(events: Vec<ExecutionGasEvent>) -> ExecutionAndIOCosts {
+        ExecutionAndIOCosts {
+            gas_scaling_factor: 1.into(),
+            total: InternalGas::new(1000),
+            intrinsic_cost: InternalGas::new(0),
+            dependencies: vec![],
+            call_graph: CallFrame {
+                name: FrameName::Script,
+                events,
+            },
+            write_set_transient: vec![],
+        }
+    }
+
+    fn summary(blocks: &AggregatedBasicBlocks) -> Vec<(&str, CodeOffset, CodeOffset, usize, u64)> {
+        blocks
+            .blocks
+            .iter()
+            .map(|block| {
+                (
+                    block.function.as_str(),
+                    block.start,
+                    block.end,
+                    block.executions,
+                    u64::from(block.cost),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_loop_blocks() {
+        use ExecutionGasEvent::Loc;
+        use Opcodes::*;
+
+        // 0: LD_U64
+        // 1: LT
+        // 2: BR_FALSE 5
+        // 3: ADD, calling `g` and a native function
+        // 4: BRANCH 1
+        // 5: RET
+        let mut events = vec![bytecode(LD_U64, 10)];
+        for _ in 0..2 {
+            events.extend([
+                bytecode(LT, 2),
+                bytecode(BR_FALSE, 3),
+                bytecode(ADD, 4),
+                callee(),
+                native(20),
+                bytecode(BRANCH, 5),
+                Loc(1),
+            ]);
+        }
+        events.extend([
+            bytecode(LT, 2),
+            bytecode(BR_FALSE, 3),
+            Loc(5),
+            bytecode(RET, 6),
+        ]);
+
+        let blocks = costs(events).aggregate_basic_blocks(10);
+        // Natives are attributed to the block calling them, callees to their own blocks
+        assert_eq!(summary(&blocks), vec![
+            ("<script>", 3, 4, 2, 58),
+            ("<script>", 1, 2, 3, 15),
+            ("0x1::test::g", 0, 0, 2, 14),
+            ("<script>", 0, 0, 1, 10),
+            ("<script>", 5, 5, 1, 6),
+        ]);
+
+        let blocks = costs(vec![]).aggregate_basic_blocks(10);
+        assert!(blocks.blocks.is_empty());
+    }
+
+    #[test]
+    fn test_truncated_blocks() {
+        use Opcodes::*;
+
+        // Running out of gas in the middle of a block still records it
+        let events = vec![
+            bytecode(LD_U64, 1),
+            bytecode(LD_U64, 1),
+            bytecode(BR_TRUE, 1),
+            bytecode(ADD, 5),
+        ];
+        let blocks = costs(events).aggregate_basic_blocks(10);
+        assert_eq!(summary(&blocks), vec![
+            ("<script>", 3, 3, 1, 5),
+            ("<script>", 0, 2, 1, 3),
+        ]);
+
+        // Only the most expensive blocks are kept
+        let blocks = costs(vec![bytecode(BR_TRUE, 1), bytecode(ADD, 5)]).aggregate_basic_blocks(1);
+        assert_eq!(summary(&blocks), vec![("<script>", 1, 1, 1, 5)]);
+    }
+}
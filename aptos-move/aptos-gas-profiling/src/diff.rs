@@ -0,0 +1,370 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    log::{CallFrame, ExecutionGasEvent, TransactionGasLog},
+    render::Render,
+};
+use aptos_gas_algebra::{Fee, GasQuantity, GasScalingFactor, InternalGas, InternalGasUnit, Octa};
+use aptos_types::state_store::state_key::StateKey;
+use std::{
+    collections::{btree_map, BTreeMap},
+    fmt::{self, Debug},
+};
+
+/// The costs of the same item in two gas logs.
+/// A cost of zero means the item is absent from the log.
+pub struct GasDelta<U> {
+    pub before: GasQuantity<U>,
+    pub after: GasQuantity<U>,
+}
+
+impl<U> Clone for GasDelta<U> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<U> Copy for GasDelta<U> {}
+
+impl<U> Debug for GasDelta<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} -> {:?}", self.before, self.after)
+    }
+}
+
+impl<U> GasDelta<U> {
+    fn new(before: GasQuantity<U>, after: GasQuantity<U>) -> Self {
+        Self { before, after }
+    }
+
+    /// Returns the signed change in cost, from `before` to `after`.
+    pub fn change(&self) -> i128 {
+        u64::from(self.after) as i128 - u64::from(self.before) as i128
+    }
+
+    pub fn is_unchanged(&self) -> bool {
+        self.before == self.after
+    }
+}
+
+/// The differences between two transaction gas logs.
+///
+/// Frames in the call graphs are aligned by their call paths, e.g. `0x1::coin::transfer;0x1::coin::withdraw`,
+/// with the costs of repeated calls along the same path added up. Write set items are aligned by
+/// their state keys.
+///
+/// Only items whose costs differ are included. They are sorted by the magnitude of the change,
+/// from high to low.
+#[derive(Debug)]
+pub struct GasLogDelta {
+    pub gas_scaling_factor: GasScalingFactor,
+
+    pub exec_io_total: GasDelta<InternalGasUnit>,
+    pub intrinsic: GasDelta<InternalGasUnit>,
+    pub frames: Vec<(String, GasDelta<InternalGasUnit>)>,
+    pub write_set_transient: Vec<(StateKey, GasDelta<InternalGasUnit>)>,
+
+    pub storage_total: GasDelta<Octa>,
+    pub write_set_storage: Vec<(StateKey, GasDelta<Octa>)>,
+    pub events: GasDelta<Octa>,
+    pub txn_storage: GasDelta<Octa>,
+}
+
+/// Computes the per-path costs of a call graph, in the same way the flamegraphs do:
+/// the cost of a frame only includes the instructions executed by the frame itself, while
+/// native calls and resource loads are listed as separate children.
+fn collect_frame_costs(frame: &CallFrame) -> BTreeMap<String, InternalGas> {
+    struct Rec {
+        path: Vec<String>,
+        costs: BTreeMap<String, InternalGas>,
+    }
+
+    impl Rec {
+        fn add(&mut self, path: String, cost: InternalGas) {
+            *self.costs.entry(path).or_insert_with(|| 0.into()) += cost;
+        }
+
+        fn visit(&mut self, frame: &CallFrame) {
+            use ExecutionGasEvent::*;
+
+            self.path.push(format!("{}", frame.name));
+            let path = self.path.join(";");
+
+            let mut frame_cost = InternalGas::new(0);
+            for event in &frame.events {
+                match event {
+                    Loc(..) => (),
+                    Bytecode { cost, .. } => frame_cost += *cost,
+                    Call(inner_frame) => self.visit(inner_frame),
+                    CallNative {
+                        module_id,
+                        fn_name,
+                        ty_args,
+                        cost,
+                        ..
+                    } => self.add(
+                        format!(
+                            "{};{}",
+                            path,
+                            Render(&(module_id, fn_name.as_ident_str(), ty_args.as_slice()))
+                        ),
+                        *cost,
+                    ),
+                    LoadResource { addr, ty, cost, .. } => {
+                        self.add(format!("{};load<{}::{}>", path, Render(addr), ty), *cost)
+                    },
+                }
+            }
+
+            self.add(path, frame_cost);
+            self.path.pop();
+        }
+    }
+
+    let mut rec = Rec {
+        path: vec![],
+        costs: BTreeMap::new(),
+    };
+    rec.visit(frame);
+    rec.costs
+}
+
+/// Aligns two collections of costs by key, dropping the items whose costs are unchanged.
+fn align<K, U>(
+    before: impl IntoIterator<Item = (K, GasQuantity<U>)>,
+    after: impl IntoIterator<Item = (K, GasQuantity<U>)>,
+) -> Vec<(K, GasDelta<U>)>
+where
+    K: Ord,
+{
+    let mut map: BTreeMap<K, GasDelta<U>> = BTreeMap::new();
+
+    for (key, cost) in before {
+        match map.entry(key) {
+            btree_map::Entry::Occupied(entry) => entry.into_mut().before += cost,
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(GasDelta::new(cost, 0.into()));
+            },
+        }
+    }
+    for (key, cost) in after {
+        match map.entry(key) {
+            btree_map::Entry::Occupied(entry) => entry.into_mut().after += cost,
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(GasDelta::new(0.into(), cost));
+            },
+        }
+    }
+
+    let mut v = map
+        .into_iter()
+        .filter(|(_key, delta)| !delta.is_unchanged())
+        .collect::<Vec<_>>();
+    // Sort in descending order of the magnitude of change.
+    v.sort_by_key(|(_key, delta)| std::cmp::Reverse(delta.change().unsigned_abs()));
+    v
+}
+
+/// Compares two transaction gas logs, typically of the same transaction executed before and
+/// after a gas schedule or contract change.
+pub fn diff(before: &TransactionGasLog, after: &TransactionGasLog) -> GasLogDelta {
+    let sum_events = |log: &TransactionGasLog| {
+        log.storage
+            .events
+            .iter()
+            .fold(Fee::new(0), |total, event| total + event.cost)
+    };
+
+    GasLogDelta {
+        gas_scaling_factor: after.exec_io.gas_scaling_factor,
+
+        exec_io_total: GasDelta::new(before.exec_io.total, after.exec_io.total),
+        intrinsic: GasDelta::new(before.exec_io.intrinsic_cost, after.exec_io.intrinsic_cost),
+        frames: align(
+            collect_frame_costs(&before.exec_io.call_graph),
+            collect_frame_costs(&after.exec_io.call_graph),
+        ),
+        write_set_transient: align(
+            before
+                .exec_io
+                .write_set_transient
+                .iter()
+                .map(|write| (write.key.clone(), write.cost)),
+            after
+                .exec_io
+                .write_set_transient
+                .iter()
+                .map(|write| (write.key.clone(), write.cost)),
+        ),
+
+        storage_total: GasDelta::new(before.storage.total, after.storage.total),
+        write_set_storage: align(
+            before
+                .storage
+                .write_set_storage
+                .iter()
+                .map(|write| (write.key.clone(), write.cost)),
+            after
+                .storage
+                .write_set_storage
+                .iter()
+                .map(|write| (write.key.clone(), write.cost)),
+        ),
+        events: GasDelta::new(sum_events(before), sum_events(after)),
+        txn_storage: GasDelta::new(before.storage.txn_storage, after.storage.txn_storage),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::{
+        EventStorage, ExecutionAndIOCosts, FrameName, StorageFees, WriteOpType, WriteStorage,
+        WriteTransient,
+    };
+    use move_binary_format::file_format_common::Opcodes;
+    use move_core_types::{
+        account_address::AccountAddress,
+        identifier::Identifier,
+        language_storage::{ModuleId, TypeTag},
+    };
+
+    fn function(name: &str, events: Vec<ExecutionGasEvent>) -> ExecutionGasEvent {
+        ExecutionGasEvent::Call(CallFrame {
+            name: FrameName::Function {
+                module_id: ModuleId::new(AccountAddress::ONE, Identifier::new("test").unwrap()),
+                name: Identifier::new(name).unwrap(),
+                ty_args: vec![],
+            },
+            events,
+        })
+    }
+
+    fn bytecode(cost: u64) -> ExecutionGasEvent {
+        ExecutionGasEvent::Bytecode {
+            op: Opcodes::ADD,
+            cost: InternalGas::new(cost),
+            count: 1,
+            loc: None,
+        }
+    }
+
+    fn native(name: &str, cost: u64) -> ExecutionGasEvent {
+        ExecutionGasEvent::CallNative {
+            module_id: ModuleId::new(AccountAddress::ONE, Identifier::new("native").unwrap()),
+            fn_name: Identifier::new(name).unwrap(),
+            ty_args: vec![],
+            cost: InternalGas::new(cost),
+            args_size: 0.into(),
+            ret_size: 0.into(),
+        }
+    }
+
+    fn gas_log(
+        events: Vec<ExecutionGasEvent>,
+        writes: &[(u8, u64)],
+        event_fees: &[u64],
+    ) -> TransactionGasLog {
+        let key = |byte: u8| StateKey::raw(vec![byte]);
+        TransactionGasLog {
+            exec_io: ExecutionAndIOCosts {
+                gas_scaling_factor: 1.into(),
+                total: InternalGas::new(1000),
+                intrinsic_cost: InternalGas::new(100),
+                dependencies: vec![],
+                call_graph: CallFrame {
+                    name: FrameName::Script,
+                    events,
+                },
+                write_set_transient: writes
+                    .iter()
+                    .map(|(byte, cost)| WriteTransient {
+                        key: key(*byte),
+                        op_type: WriteOpType::Modification,
+                        cost: InternalGas::new(*cost),
+                        group_members: vec![],
+                    })
+                    .collect(),
+            },
+            storage: StorageFees {
+                total: Fee::new(500),
+                write_set_storage: writes
+                    .iter()
+                    .map(|(byte, cost)| WriteStorage {
+                        key: key(*byte),
+                        op_type: WriteOpType::Modification,
+                        cost: Fee::new(*cost),
+                        slot_fee: Fee::new(0),
+                        bytes_fee: Fee::new(*cost),
+                        group_members: vec![],
+                    })
+                    .collect(),
+                events: event_fees
+                    .iter()
+                    .map(|cost| EventStorage {
+                        ty: TypeTag::U64,
+                        cost: Fee::new(*cost),
+                    })
+                    .collect(),
+                event_discount: Fee::new(0),
+                txn_storage: Fee::new(50),
+            },
+        }
+    }
+
+    fn changes<K: Clone, U>(items: &[(K, GasDelta<U>)]) -> Vec<(K, u64, u64)> {
+        items
+            .iter()
+            .map(|(key, delta)| (key.clone(), u64::from(delta.before), u64::from(delta.after)))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = gas_log(
+            vec![
+                bytecode(100),
+                function("f", vec![bytecode(50), native("hash", 8)]),
+                function("f", vec![bytecode(20)]),
+                function("g", vec![bytecode(10)]),
+            ],
+            &[(1, 5), (2, 10)],
+            &[3, 4],
+        );
+        let after = gas_log(
+            vec![
+                bytecode(100),
+                function("f", vec![bytecode(40), native("hash", 8)]),
+                function("h", vec![bytecode(200)]),
+            ],
+            &[(1, 5), (3, 3)],
+            &[3, 4],
+        );
+
+        let delta = diff(&before, &after);
+        assert!(delta.exec_io_total.is_unchanged());
+        assert!(delta.intrinsic.is_unchanged());
+        assert!(delta.storage_total.is_unchanged());
+        assert!(delta.events.is_unchanged());
+        assert!(delta.txn_storage.is_unchanged());
+
+        // Repeated calls along the same path are added up, native calls are listed on their
+        // own, and unchanged paths are left out. The largest changes come first.
+        assert_eq!(changes(&delta.frames), vec![
+            ("<script>;0x1::test::h".to_string(), 0, 200),
+            ("<script>;0x1::test::f".to_string(), 70, 40),
+            ("<script>;0x1::test::g".to_string(), 10, 0),
+        ]);
+        assert_eq!(delta.frames[0].1.change(), 200);
+        assert_eq!(delta.frames[1].1.change(), -30);
+
+        // Writes are aligned by their state keys
+        let expected_writes = vec![
+            (StateKey::raw(vec![2]), 10, 0),
+            (StateKey::raw(vec![3]), 0, 3),
+        ];
+        assert_eq!(changes(&delta.write_set_transient), expected_writes);
+        assert_eq!(changes(&delta.write_set_storage), expected_writes);
+    }
+}
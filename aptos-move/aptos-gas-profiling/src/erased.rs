@@ -3,13 +3,14 @@
 
 use crate::{
     log::{
-        CallFrame, EventStorage, ExecutionAndIOCosts, ExecutionGasEvent, StorageFees, WriteStorage,
-        WriteTransient,
+        CallFrame, Dependency, EventStorage, ExecutionAndIOCosts, ExecutionGasEvent, StorageFees,
+        WriteOpType, WriteStorage, WriteTransient,
     },
     render::Render,
     FrameName, TransactionGasLog,
 };
 use aptos_gas_algebra::{Fee, GasQuantity, GasScalingFactor, InternalGas, InternalGasUnit, Octa};
+use move_core_types::language_storage::StructTag;
 
 /// Represents a node in a general tree structure with some text & cost attached to each node.
 pub struct Node<U> {
@@ -91,13 +92,28 @@ impl ExecutionGasEvent {
 
         match self {
             Loc(offset) => Node::new(format!("@{}", offset), 0),
-            Bytecode { op, cost } => Node::new(format!("{:?}", op).to_ascii_lowercase(), *cost),
+            Bytecode {
+                op,
+                cost,
+                count,
+                loc,
+            } => {
+                let mut op = format!("{:?}", op).to_ascii_lowercase();
+                if *count > 1 {
+                    op = format!("{} x {}", op, count);
+                }
+                match loc {
+                    Some(loc) => Node::new(format!("{} @ {}", op, loc), *cost),
+                    None => Node::new(op, *cost),
+                }
+            },
             Call(frame) => frame.to_erased(),
             CallNative {
                 module_id,
                 fn_name,
                 ty_args,
                 cost,
+                ..
             } => Node::new(
                 format!(
                     "{}",
@@ -105,7 +121,7 @@ impl ExecutionGasEvent {
                 ),
                 *cost,
             ),
-            LoadResource { addr, ty, cost } => {
+            LoadResource { addr, ty, cost, .. } => {
                 Node::new(format!("load<{}::{}>", Render(addr), ty), *cost)
             },
         }
@@ -138,11 +154,30 @@ impl CallFrame {
     }
 }
 
-impl WriteTransient {
+/// Lists the members touched within a resource group as cost-less child nodes, since the
+/// costs are only charged for the group as a whole.
+fn group_member_nodes<U>(members: &[(StructTag, WriteOpType)]) -> Vec<Node<U>> {
+    members
+        .iter()
+        .map(|(tag, op_type)| Node::new(format!("{}<{}>", Render(op_type), tag), 0))
+        .collect()
+}
+
+impl Dependency {
     fn to_erased(&self) -> Node<InternalGasUnit> {
         Node::new(
+            format!("{} ({} bytes)", Render(&self.id), self.size),
+            self.cost,
+        )
+    }
+}
+
+impl WriteTransient {
+    fn to_erased(&self) -> Node<InternalGasUnit> {
+        Node::new_with_children(
             format!("{}<{}>", Render(&self.op_type), Render(&self.key)),
             self.cost,
+            group_member_nodes(&self.group_members),
         )
     }
 }
@@ -153,6 +188,11 @@ impl ExecutionAndIOCosts {
         let mut nodes = vec![];
 
         nodes.push(Node::new("intrinsic", self.intrinsic_cost));
+        nodes.push(Node::new_with_children(
+            "dependencies",
+            0,
+            self.dependencies.iter().map(|dep| dep.to_erased()),
+        ));
         nodes.push(self.call_graph.to_erased());
 
         let writes = Node::new_with_children(
@@ -174,9 +214,10 @@ impl ExecutionAndIOCosts {
 
 impl WriteStorage {
     fn to_erased(&self) -> Node<Octa> {
-        Node::new(
+        Node::new_with_children(
             format!("{}<{}>", Render(&self.op_type), Render(&self.key)),
             self.cost,
+            group_member_nodes(&self.group_members),
         )
     }
 }
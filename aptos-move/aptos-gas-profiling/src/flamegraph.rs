@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    log::{CallFrame, ExecutionAndIOCosts, ExecutionGasEvent, StorageFees},
+    log::{CallFrame, ExecutionAndIOCosts, ExecutionGasEvent, StorageFees, TransactionGasLog},
     render::Render,
 };
 use inferno::flamegraph::TextTruncateDirection;
@@ -53,54 +53,34 @@ impl StorageFees {
         lines.into_inner()
     }
 
-    /// Tries to generate a flamegraph from the execution log.
+    /// Renders the storage fee log in the folded stack format, one stack per line.
+    ///
+    /// The output can be fed to external tools such as `flamegraph.pl` or `inferno-flamegraph`.
+    pub fn to_folded_stacks(&self) -> String {
+        self.to_folded_stack_lines().join("\n")
+    }
+
+    /// Tries to generate a flamegraph from the storage fee log.
     /// None will be returned if the log is empty.
     pub fn to_flamegraph(&self, title: String) -> anyhow::Result<Option<Vec<u8>>> {
-        let lines = self.to_folded_stack_lines();
-
-        if lines.is_empty() {
-            return Ok(None);
-        }
-
-        let mut options = inferno::flamegraph::Options::default();
-        options.flame_chart = true;
-        options.text_truncate_direction = TextTruncateDirection::Right;
-        options.color_diffusion = true;
-        options.title = title;
-
-        let mut graph_content = vec![];
-        inferno::flamegraph::from_lines(
-            &mut options,
-            lines.iter().rev().map(|s| s.as_str()),
-            &mut graph_content,
-        )?;
-        let graph_content = String::from_utf8_lossy(&graph_content);
-
-        // Inferno does not allow us to customize some of the text in the resulting graph,
-        // so we have to do it through regex replacement.
-        let re = regex::Regex::new("([1-9][0-9]*(,[0-9]+)*) samples")
-            .expect("should be able to build regex successfully");
-        let graph_content = re.replace_all(&graph_content, |caps: &Captures| {
-            let count: u64 = caps[1]
-                .replace(',', "")
-                .parse()
-                .expect("should be able parse count as u64");
-
+        render_flamegraph(self.to_folded_stack_lines(), title, |count| {
             format!("{} Octa", count)
-        });
-
-        Ok(Some(graph_content.as_bytes().to_vec()))
+        })
     }
 }
 
 impl ExecutionAndIOCosts {
     /// Convert the execution gas log into folded stack lines, which can
     /// then be used to generate a flamegraph.
-    fn to_folded_stack_lines(&self) -> Vec<String> {
+    pub(crate) fn to_folded_stack_lines(&self) -> Vec<String> {
         let mut lines = LineBuffer::new();
 
         lines.push("intrinsic", self.intrinsic_cost);
 
+        for dep in &self.dependencies {
+            lines.push(format!("dependencies;{}", Render(&dep.id)), dep.cost);
+        }
+
         let mut path = vec![];
 
         struct Rec<'a> {
@@ -126,6 +106,7 @@ impl ExecutionAndIOCosts {
                             fn_name,
                             ty_args,
                             cost,
+                            ..
                         } => self.lines.push(
                             format!(
                                 "{};{}",
@@ -134,7 +115,7 @@ impl ExecutionAndIOCosts {
                             ),
                             *cost,
                         ),
-                        LoadResource { addr, ty, cost } => self.lines.push(
+                        LoadResource { addr, ty, cost, .. } => self.lines.push(
                             format!("{};load<{}::{}>", self.path(), Render(addr), ty),
                             *cost,
                         ),
@@ -166,50 +147,93 @@ impl ExecutionAndIOCosts {
         lines.into_inner()
     }
 
+    /// Renders the execution & IO log in the folded stack format, one stack per line.
+    ///
+    /// Costs are expressed in internal gas units.
+    pub fn to_folded_stacks(&self) -> String {
+        self.to_folded_stack_lines().join("\n")
+    }
+
     /// Tries to generate a flamegraph from the execution log.
     /// None will be returned if the log is empty.
     pub fn to_flamegraph(&self, title: String) -> anyhow::Result<Option<Vec<u8>>> {
-        let lines = self.to_folded_stack_lines();
-
-        if lines.is_empty() {
-            return Ok(None);
-        }
-
-        let mut options = inferno::flamegraph::Options::default();
-        options.flame_chart = true;
-        options.text_truncate_direction = TextTruncateDirection::Right;
-        options.color_diffusion = true;
-        options.title = title;
-
-        let mut graph_content = vec![];
-        inferno::flamegraph::from_lines(
-            &mut options,
-            lines.iter().rev().map(|s| s.as_str()),
-            &mut graph_content,
-        )?;
-        let graph_content = String::from_utf8_lossy(&graph_content);
-
-        // Inferno does not allow us to customize some of the text in the resulting graph,
-        // so we have to do it through regex replacement.
-        let re = regex::Regex::new("([1-9][0-9]*(,[0-9]+)*) samples")
-            .expect("should be able to build regex successfully");
-        let graph_content = re.replace_all(&graph_content, |caps: &Captures| {
-            let count: u64 = caps[1]
-                .replace(',', "")
-                .parse()
-                .expect("should be able parse count as u64");
-
-            let count_scaled = count as f64 / u64::from(self.gas_scaling_factor) as f64;
+        let scaling_factor = u64::from(self.gas_scaling_factor) as f64;
 
+        render_flamegraph(self.to_folded_stack_lines(), title, |count| {
             format!(
                 "{} gas units",
                 crate::misc::strip_trailing_zeros_and_decimal_point(&format!(
                     "{:.8}",
-                    count_scaled
+                    count as f64 / scaling_factor
                 ))
             )
-        });
+        })
+    }
+}
 
-        Ok(Some(graph_content.as_bytes().to_vec()))
+/// Flamegraphs generated from a complete transaction gas log.
+///
+/// Execution & IO costs and storage fees are measured in different units, so they
+/// are rendered as two separate graphs.
+pub struct TransactionGasFlamegraphs {
+    pub exec_io: Option<Vec<u8>>,
+    pub storage: Option<Vec<u8>>,
+}
+
+impl TransactionGasLog {
+    /// Tries to generate the execution & IO and the storage fee flamegraphs for the transaction.
+    /// A graph will be omitted if the corresponding log is empty.
+    pub fn to_flamegraph(&self, title: &str) -> anyhow::Result<TransactionGasFlamegraphs> {
+        Ok(TransactionGasFlamegraphs {
+            exec_io: self
+                .exec_io
+                .to_flamegraph(format!("{} -- Execution & IO", title))?,
+            storage: self
+                .storage
+                .to_flamegraph(format!("{} -- Storage Fee", title))?,
+        })
+    }
+}
+
+/// Renders the given folded stack lines into an SVG flamegraph.
+///
+/// `fmt_count` is used to replace the sample counts generated by inferno with human
+/// readable costs.
+fn render_flamegraph(
+    lines: Vec<String>,
+    title: String,
+    fmt_count: impl Fn(u64) -> String,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    if lines.is_empty() {
+        return Ok(None);
     }
+
+    let mut options = inferno::flamegraph::Options::default();
+    options.flame_chart = true;
+    options.text_truncate_direction = TextTruncateDirection::Right;
+    options.color_diffusion = true;
+    options.title = title;
+
+    let mut graph_content = vec![];
+    inferno::flamegraph::from_lines(
+        &mut options,
+        lines.iter().rev().map(|s| s.as_str()),
+        &mut graph_content,
+    )?;
+    let graph_content = String::from_utf8_lossy(&graph_content);
+
+    // Inferno does not allow us to customize some of the text in the resulting graph,
+    // so we have to do it through regex replacement.
+    let re = regex::Regex::new("([1-9][0-9]*(,[0-9]+)*) samples")
+        .expect("should be able to build regex successfully");
+    let graph_content = re.replace_all(&graph_content, |caps: &Captures| {
+        let count: u64 = caps[1]
+            .replace(',', "")
+            .parse()
+            .expect("should be able parse count as u64");
+
+        fmt_count(count)
+    });
+
+    Ok(Some(graph_content.as_bytes().to_vec()))
 }
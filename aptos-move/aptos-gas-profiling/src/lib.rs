@@ -2,13 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod aggregate;
+mod blocks;
+mod diff;
 mod erased;
 mod flamegraph;
 mod log;
 mod misc;
+mod pprof;
 mod profiler;
 mod render;
+mod reprice;
+mod session;
+mod source;
 mod textualize;
+mod trace;
 
+pub use diff::{diff, GasDelta, GasLogDelta};
+pub use flamegraph::TransactionGasFlamegraphs;
 pub use log::{FrameName, TransactionGasLog};
 pub use profiler::GasProfiler;
+pub use session::{AggregatedSessionGasStats, FunctionGasStats, GasProfilingSession};
+pub use source::{SourceLocation, SourceMaps};
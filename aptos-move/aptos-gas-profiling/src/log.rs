@@ -1,27 +1,37 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_gas_algebra::{Fee, GasScalingFactor, InternalGas};
+use crate::source::SourceLocation;
+use aptos_gas_algebra::{AbstractValueSize, Fee, GasScalingFactor, InternalGas, NumBytes};
 use aptos_types::state_store::state_key::StateKey;
 use move_binary_format::{file_format::CodeOffset, file_format_common::Opcodes};
 use move_core_types::{
     account_address::AccountAddress,
     identifier::Identifier,
-    language_storage::{ModuleId, TypeTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
 };
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 use smallvec::{smallvec, SmallVec};
 
 /// An event occurred during the execution of a function, along with the
 /// gas cost associated with it, if any.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum ExecutionGasEvent {
     /// A special event indicating that the program counter has moved to
     /// a specific offset. This is emitted by the branch instructions
     /// and is crucial for reconstructing the control flow.
     Loc(CodeOffset),
     Bytecode {
+        #[serde(serialize_with = "serialize_opcode")]
         op: Opcodes,
         cost: InternalGas,
+        /// Number of executions of the instruction covered by this event.
+        /// Always 1, unless the profiler folds bytecode events into per-frame aggregates.
+        count: usize,
+        /// Location of the instruction in the source code.
+        /// Only available if source maps are provided when finishing the profiling.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        loc: Option<SourceLocation>,
     },
     Call(CallFrame),
     CallNative {
@@ -29,17 +39,24 @@ pub enum ExecutionGasEvent {
         fn_name: Identifier,
         ty_args: Vec<TypeTag>,
         cost: InternalGas,
+        /// Total abstract size of the arguments, with references resolved to the values
+        /// they point to.
+        args_size: AbstractValueSize,
+        /// Total abstract size of the return values.
+        /// Zero if the native function did not return successfully.
+        ret_size: AbstractValueSize,
     },
     LoadResource {
         addr: AccountAddress,
         ty: TypeTag,
         cost: InternalGas,
+        bytes_loaded: NumBytes,
     },
 }
 
 /// An enum representing the name of a call frame.
 /// Could be either a script or a function.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum FrameName {
     Script,
     Function {
@@ -51,7 +68,7 @@ pub enum FrameName {
 
 /// A struct containing information about a function call, including the name of the
 /// function and all gas events that happened during the call.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct CallFrame {
     pub name: FrameName,
     pub events: Vec<ExecutionGasEvent>,
@@ -59,48 +76,82 @@ pub struct CallFrame {
 
 /// The type of an operation performed on a storage item.
 ///
-/// Possible values: Creation, Modification & Deletion.
-#[derive(Debug)]
+/// Possible values: Creation, Modification & Deletion, with separate variants for
+/// operations performed on resource groups.
+#[derive(Debug, Clone, Copy, Serialize)]
 pub enum WriteOpType {
     Creation,
     Modification,
     Deletion,
+    GroupCreation,
+    GroupModification,
+    GroupDeletion,
 }
 
 /// Struct representing the transient (IO) cost of a write operation.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct WriteTransient {
     pub key: StateKey,
     pub op_type: WriteOpType,
     pub cost: InternalGas,
+    /// The members touched within a resource group, sorted by their tags.
+    /// Always empty for writes to regular storage items.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub group_members: Vec<(StructTag, WriteOpType)>,
 }
 
 /// Struct representing the storage cost of a write operation.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct WriteStorage {
     pub key: StateKey,
     pub op_type: WriteOpType,
+    /// Total storage fee of the write, i.e. the sum of the slot and the bytes fees.
     pub cost: Fee,
+    /// Fee charged for allocating a new state slot. Zero unless the item is created.
+    pub slot_fee: Fee,
+    /// Fee charged for the bytes exceeding the free quota.
+    pub bytes_fee: Fee,
+    /// The members touched within a resource group, sorted by their tags.
+    /// Always empty for writes to regular storage items.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub group_members: Vec<(StructTag, WriteOpType)>,
 }
 
-#[derive(Debug)]
+/// Struct representing a module loaded as a dependency of the transaction.
+///
+/// The current gas schedule does not meter dependencies, so only the sizes are meaningful and
+/// the costs are always zero.
+#[derive(Debug, Serialize)]
+pub struct Dependency {
+    /// Whether the module is newly published by the transaction.
+    pub is_new: bool,
+    pub id: ModuleId,
+    pub size: NumBytes,
+    /// Always zero, as dependencies are not charged for under the current gas schedule.
+    pub cost: InternalGas,
+}
+
+#[derive(Debug, Serialize)]
 /// Struct representing the storage cost of an event.
 pub struct EventStorage {
     pub ty: TypeTag,
     pub cost: Fee,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ExecutionAndIOCosts {
     pub gas_scaling_factor: GasScalingFactor,
     pub total: InternalGas,
 
     pub intrinsic_cost: InternalGas,
+    /// The modules loaded as dependencies of the transaction, with their sizes. They are not
+    /// charged for, so they add nothing to the total.
+    pub dependencies: Vec<Dependency>,
     pub call_graph: CallFrame,
     pub write_set_transient: Vec<WriteTransient>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 // Struct containing all types of storage fees.
 pub struct StorageFees {
     pub total: Fee,
@@ -113,6 +164,9 @@ pub struct StorageFees {
 
 /// A complete log that contains all gas-related information about a transaction, including
 /// the intrinsic cost, a detailed execution log and the write set costs.
+///
+/// The serialized form carries a `schema_version` field, which is bumped whenever the
+/// layout of the log changes in an incompatible way.
 #[derive(Debug)]
 pub struct TransactionGasLog {
     pub exec_io: ExecutionAndIOCosts,
@@ -178,7 +232,38 @@ impl ExecutionAndIOCosts {
 }
 
 impl TransactionGasLog {
+    /// Version of the serialized format of the gas log.
+    pub const SCHEMA_VERSION: u32 = 1;
+
     pub fn entry_point(&self) -> &FrameName {
         &self.exec_io.call_graph.name
     }
+
+    /// Exports the gas log as a JSON document.
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl Serialize for TransactionGasLog {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("TransactionGasLog", 4)?;
+        state.serialize_field("schema_version", &Self::SCHEMA_VERSION)?;
+        state.serialize_field("exec_io", &self.exec_io)?;
+        state.serialize_field("storage", &self.storage)?;
+        state.serialize_field("resource_loads", &self.exec_io.aggregate_resource_loads())?;
+        state.end()
+    }
+}
+
+/// Opcodes are serialized using their names, e.g. `"LD_U64"`, so the output stays readable
+/// and independent of the numeric encoding.
+fn serialize_opcode<S>(op: &Opcodes, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format!("{:?}", op))
 }
@@ -0,0 +1,156 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::ExecutionAndIOCosts;
+use prost::Message;
+use std::collections::BTreeMap;
+
+// The subset of the pprof profile format needed to represent the gas log.
+//
+// See https://github.com/google/pprof/blob/main/proto/profile.proto for the full definition.
+mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Profile {
+        #[prost(message, repeated, tag = "1")]
+        pub sample_type: Vec<ValueType>,
+        #[prost(message, repeated, tag = "2")]
+        pub sample: Vec<Sample>,
+        #[prost(message, repeated, tag = "4")]
+        pub location: Vec<Location>,
+        #[prost(message, repeated, tag = "5")]
+        pub function: Vec<Function>,
+        #[prost(string, repeated, tag = "6")]
+        pub string_table: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ValueType {
+        #[prost(int64, tag = "1")]
+        pub r#type: i64,
+        #[prost(int64, tag = "2")]
+        pub unit: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Sample {
+        #[prost(uint64, repeated, tag = "1")]
+        pub location_id: Vec<u64>,
+        #[prost(int64, repeated, tag = "2")]
+        pub value: Vec<i64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Location {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(message, repeated, tag = "4")]
+        pub line: Vec<Line>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Line {
+        #[prost(uint64, tag = "1")]
+        pub function_id: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Function {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(int64, tag = "2")]
+        pub name: i64,
+    }
+}
+
+struct ProfileBuilder {
+    profile: proto::Profile,
+    strings: BTreeMap<String, i64>,
+    /// Location ids of the frames seen so far, indexed by frame name.
+    /// Every frame is mapped to a function and a location with the same id.
+    locations: BTreeMap<String, u64>,
+}
+
+impl ProfileBuilder {
+    fn new() -> Self {
+        let mut builder = Self {
+            profile: proto::Profile::default(),
+            strings: BTreeMap::new(),
+            locations: BTreeMap::new(),
+        };
+        // The first entry of the string table must always be the empty string.
+        builder.intern("");
+        builder
+    }
+
+    fn intern(&mut self, s: &str) -> i64 {
+        if let Some(idx) = self.strings.get(s) {
+            return *idx;
+        }
+
+        let idx = self.profile.string_table.len() as i64;
+        self.profile.string_table.push(s.to_string());
+        self.strings.insert(s.to_string(), idx);
+        idx
+    }
+
+    fn location(&mut self, frame: &str) -> u64 {
+        if let Some(id) = self.locations.get(frame) {
+            return *id;
+        }
+
+        // Ids must be non-zero.
+        let id = self.locations.len() as u64 + 1;
+        let name = self.intern(frame);
+        self.profile.function.push(proto::Function { id, name });
+        self.profile.location.push(proto::Location {
+            id,
+            line: vec![proto::Line { function_id: id }],
+        });
+        self.locations.insert(frame.to_string(), id);
+        id
+    }
+
+    fn add_sample(&mut self, stack: &str, value: u64) {
+        // The first location in a sample is the leaf.
+        let location_id = stack
+            .split(';')
+            .rev()
+            .map(|frame| self.location(frame))
+            .collect();
+
+        self.profile.sample.push(proto::Sample {
+            location_id,
+            value: vec![value as i64],
+        });
+    }
+}
+
+impl ExecutionAndIOCosts {
+    /// Exports the execution gas log as an (uncompressed) profile in the pprof protobuf format,
+    /// which can be explored with standard tools such as `go tool pprof` or Speedscope.
+    ///
+    /// Each sample corresponds to a stack in the flamegraph and is weighted by its cost in
+    /// internal gas units.
+    pub fn to_pprof(&self) -> Vec<u8> {
+        let mut builder = ProfileBuilder::new();
+
+        let ty = builder.intern("gas");
+        let unit = builder.intern("internal_gas_units");
+        builder
+            .profile
+            .sample_type
+            .push(proto::ValueType { r#type: ty, unit });
+
+        for line in self.to_folded_stack_lines() {
+            let (stack, count) = line
+                .rsplit_once(' ')
+                .expect("folded stack lines must end with a count");
+            let count = count
+                .parse()
+                .expect("folded stack lines must end with a valid count");
+            builder.add_sample(stack, count);
+        }
+
+        builder.profile.encode_to_vec()
+    }
+}
@@ -1,11 +1,14 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::log::{
-    CallFrame, EventStorage, ExecutionAndIOCosts, ExecutionGasEvent, FrameName, StorageFees,
-    TransactionGasLog, WriteOpType, WriteStorage, WriteTransient,
+use crate::{
+    log::{
+        CallFrame, Dependency, EventStorage, ExecutionAndIOCosts, ExecutionGasEvent, FrameName,
+        StorageFees, TransactionGasLog, WriteOpType, WriteStorage, WriteTransient,
+    },
+    source::SourceMaps,
 };
-use aptos_gas_algebra::{Fee, FeePerGasUnit, InternalGas, NumArgs, NumBytes};
+use aptos_gas_algebra::{AbstractValueSize, Fee, FeePerGasUnit, InternalGas, NumArgs, NumBytes};
 use aptos_gas_meter::AptosGasMeter;
 use aptos_types::{
     contract_event::ContractEvent, state_store::state_key::StateKey, write_set::WriteOp,
@@ -19,12 +22,13 @@ use move_binary_format::{
 use move_core_types::{
     account_address::AccountAddress,
     identifier::Identifier,
-    language_storage::{ModuleId, TypeTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
 };
 use move_vm_types::{
     gas::{GasMeter, SimpleInstruction},
     views::{TypeView, ValueView},
 };
+use std::collections::{btree_map, BTreeMap};
 
 /// A special gas meter adapter that records all gas-related events, along with the associated costs
 /// assessed by the underlying gas meter.
@@ -33,7 +37,16 @@ pub struct GasProfiler<G> {
 
     intrinsic_cost: Option<InternalGas>,
     total_exec_io: InternalGas,
+    dependencies: Vec<Dependency>,
     frames: Vec<CallFrame>,
+    /// If set, bytecode events are folded into one event per opcode and call frame,
+    /// bounding the memory used by the profiler.
+    aggregate_bytecodes: bool,
+    /// For each frame on the stack, the positions of the aggregated bytecode events in its
+    /// event stream, indexed by opcode. Only used if `aggregate_bytecodes` is set.
+    bytecode_indices: Vec<BTreeMap<u8, usize>>,
+    /// Abstract size of the arguments of the native function currently being executed.
+    native_args_size: AbstractValueSize,
     write_set_transient: Vec<WriteTransient>,
     storage_fees: Option<StorageFees>,
 }
@@ -87,7 +100,11 @@ impl<G> GasProfiler<G> {
 
             intrinsic_cost: None,
             total_exec_io: 0.into(),
+            dependencies: vec![],
             frames: vec![CallFrame::new_script()],
+            aggregate_bytecodes: false,
+            bytecode_indices: vec![BTreeMap::new()],
+            native_args_size: 0.into(),
             write_set_transient: vec![],
             storage_fees: None,
         }
@@ -104,11 +121,28 @@ impl<G> GasProfiler<G> {
 
             intrinsic_cost: None,
             total_exec_io: 0.into(),
+            dependencies: vec![],
             frames: vec![CallFrame::new_function(module_id, func_name, ty_args)],
+            aggregate_bytecodes: false,
+            bytecode_indices: vec![BTreeMap::new()],
+            native_args_size: 0.into(),
             write_set_transient: vec![],
             storage_fees: None,
         }
     }
+
+    /// Enables or disables the streaming mode, in which bytecode events are folded into
+    /// per-opcode aggregates for each call frame as they are recorded, instead of being kept
+    /// individually.
+    ///
+    /// The call graph, native function calls and resource loads are still recorded in full,
+    /// so memory usage only grows with the number of calls rather than the number of
+    /// instructions executed. This makes it possible to profile huge scripts, at the cost of
+    /// losing the order of the instructions and their source locations.
+    pub fn with_bytecode_aggregation(mut self, aggregate_bytecodes: bool) -> Self {
+        self.aggregate_bytecodes = aggregate_bytecodes;
+        self
+    }
 }
 
 impl<G> GasProfiler<G>
@@ -134,11 +168,76 @@ where
     }
 
     fn record_bytecode(&mut self, op: Opcodes, cost: InternalGas) {
-        self.record_gas_event(ExecutionGasEvent::Bytecode { op, cost })
+        if !self.aggregate_bytecodes {
+            self.record_gas_event(ExecutionGasEvent::Bytecode {
+                op,
+                cost,
+                count: 1,
+                loc: None,
+            });
+            return;
+        }
+
+        self.total_exec_io += cost;
+
+        let indices = self.bytecode_indices.last_mut().expect("frame must exist");
+        let events = &mut self.frames.last_mut().expect("frame must exist").events;
+        match indices.entry(op as u8) {
+            btree_map::Entry::Occupied(entry) => match &mut events[*entry.get()] {
+                ExecutionGasEvent::Bytecode {
+                    cost: total_cost,
+                    count,
+                    ..
+                } => {
+                    *total_cost += cost;
+                    *count += 1;
+                },
+                _ => unreachable!("index must point to a bytecode event"),
+            },
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(events.len());
+                events.push(ExecutionGasEvent::Bytecode {
+                    op,
+                    cost,
+                    count: 1,
+                    loc: None,
+                });
+            },
+        }
     }
 
     fn record_offset(&mut self, offset: CodeOffset) {
-        self.record_gas_event(ExecutionGasEvent::Loc(offset))
+        // The offsets are meaningless once the instructions are aggregated.
+        if !self.aggregate_bytecodes {
+            self.record_gas_event(ExecutionGasEvent::Loc(offset))
+        }
+    }
+
+    fn push_frame(&mut self, frame: CallFrame) {
+        self.frames.push(frame);
+        self.bytecode_indices.push(BTreeMap::new());
+    }
+
+    fn pop_frame(&mut self) -> CallFrame {
+        self.bytecode_indices.pop();
+        self.frames.pop().expect("frame must exist")
+    }
+
+    /// Pops the current frame and attaches it to the event stream of its caller.
+    fn return_to_caller(&mut self) {
+        let cur_frame = self.pop_frame();
+        let last_frame = self.frames.last_mut().expect("frame must exist");
+        last_frame.events.push(ExecutionGasEvent::Call(cur_frame));
+    }
+
+    /// Calculates the total abstract size of the given values, dereferencing any references.
+    fn abstract_value_size(&self, vals: impl Iterator<Item = impl ValueView>) -> AbstractValueSize {
+        let abs_val_params = &self.base.vm_gas_params().misc.abs_val;
+        let feature_version = self.base.feature_version();
+
+        vals.fold(0.into(), |total, val| {
+            total + abs_val_params.abstract_value_size_dereferenced(val, feature_version)
+        })
     }
 
     /// Delegate the charging call to the base gas meter and measure variation in balance.
@@ -164,13 +263,6 @@ where
         fn charge_ld_const_after_deserialization(&mut self, val: impl ValueView)
             -> PartialVMResult<()>;
 
-        // Note: we don't use this to charge gas so no need to record anything.
-        fn charge_native_function_before_execution(
-            &mut self,
-            ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
-            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
-        ) -> PartialVMResult<()>;
-
         // Note: we don't use this to charge gas so no need to record anything.
         fn charge_drop_frame(
             &mut self,
@@ -313,15 +405,34 @@ where
         self.base.balance_internal()
     }
 
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()> {
+        // Note: this is not used to charge gas, but it is the only place where the
+        // arguments of a native function are visible.
+        self.native_args_size = self.abstract_value_size(args.clone());
+
+        self.base
+            .charge_native_function_before_execution(ty_args, args)
+    }
+
     fn charge_native_function(
         &mut self,
         amount: InternalGas,
         ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView> + Clone>,
     ) -> PartialVMResult<()> {
+        let ret_size = match &ret_vals {
+            Some(ret_vals) => self.abstract_value_size(ret_vals.clone()),
+            None => 0.into(),
+        };
+        let args_size = std::mem::replace(&mut self.native_args_size, 0.into());
+
         let (cost, res) =
             self.delegate_charge(|base| base.charge_native_function(amount, ret_vals));
 
-        let cur = self.frames.pop().expect("frame must exist");
+        let cur = self.pop_frame();
         let (module_id, name, ty_args) = match cur.name {
             FrameName::Function {
                 module_id,
@@ -336,6 +447,8 @@ where
             fn_name: name,
             ty_args,
             cost,
+            args_size,
+            ret_size,
         });
 
         res
@@ -380,12 +493,10 @@ where
         // TODO: Right now we keep the last frame on the stack even after hitting the ret instruction,
         //       so that it can be picked up by finishing procedure.
         //       This is a bit hacky and can lead to weird behaviors if the profiler is used
-        //       over multiple transactions, but again, guarding against that case is a broader
-        //       problem we can deal with in the future.
+        //       over multiple transactions. Use `GasProfilingSession` with a fresh profiler
+        //       per transaction to profile multiple transactions instead.
         if matches!(instr, SimpleInstruction::Ret) && self.frames.len() > 1 {
-            let cur_frame = self.frames.pop().expect("frame must exist");
-            let last_frame = self.frames.last_mut().expect("frame must exist");
-            last_frame.events.push(ExecutionGasEvent::Call(cur_frame));
+            self.return_to_caller();
         }
 
         res
@@ -402,7 +513,7 @@ where
             self.delegate_charge(|base| base.charge_call(module_id, func_name, args, num_locals));
 
         self.record_bytecode(Opcodes::CALL, cost);
-        self.frames.push(CallFrame::new_function(
+        self.push_frame(CallFrame::new_function(
             module_id.clone(),
             Identifier::new(func_name).unwrap(),
             vec![],
@@ -429,7 +540,7 @@ where
         });
 
         self.record_bytecode(Opcodes::CALL_GENERIC, cost);
-        self.frames.push(CallFrame::new_function(
+        self.push_frame(CallFrame::new_function(
             module_id.clone(),
             Identifier::new(func_name).unwrap(),
             ty_tags,
@@ -454,6 +565,7 @@ where
             addr,
             ty: ty_tag,
             cost,
+            bytes_loaded,
         });
 
         res
@@ -471,6 +583,30 @@ fn write_op_type(op: &WriteOp) -> WriteOpType {
     }
 }
 
+fn group_write_op_type(group_write: &GroupWrite) -> WriteOpType {
+    use WriteOpType as T;
+
+    match write_op_type(group_write.metadata_op()) {
+        T::Creation => T::GroupCreation,
+        T::Modification => T::GroupModification,
+        T::Deletion => T::GroupDeletion,
+        T::GroupCreation | T::GroupModification | T::GroupDeletion => {
+            unreachable!("metadata op cannot be a group op")
+        },
+    }
+}
+
+/// Returns the members touched by a group write, sorted by their tags.
+fn group_members(group_write: &GroupWrite) -> Vec<(StructTag, WriteOpType)> {
+    let mut members = group_write
+        .inner_ops()
+        .iter()
+        .map(|(tag, op)| (tag.clone(), write_op_type(op)))
+        .collect::<Vec<_>>();
+    members.sort_by(|(tag1, _), (tag2, _)| tag1.cmp(tag2));
+    members
+}
+
 impl<G> AptosGasMeter for GasProfiler<G>
 where
     G: AptosGasMeter,
@@ -511,6 +647,7 @@ where
             key: key.clone(),
             cost,
             op_type: write_op_type(op),
+            group_members: vec![],
         });
 
         res
@@ -528,7 +665,8 @@ where
         self.write_set_transient.push(WriteTransient {
             key: key.clone(),
             cost,
-            op_type: write_op_type(group_write.metadata_op()),
+            op_type: group_write_op_type(group_write),
+            group_members: group_members(group_write),
         });
 
         res
@@ -570,6 +708,9 @@ where
                 key: key.clone(),
                 op_type: write_op_type(op),
                 cost: fee,
+                slot_fee,
+                bytes_fee,
+                group_members: vec![],
             });
             // TODO(gas): track storage refund in the profiler
             write_fee += fee;
@@ -587,11 +728,13 @@ where
             let bytes_fee = self.storage_fee_for_state_bytes(key, group_write.encoded_group_size());
 
             let fee = slot_fee + bytes_fee;
-            // TODO: should we distringuish group writes.
             write_set_storage.push(WriteStorage {
                 key: key.clone(),
-                op_type: write_op_type(group_write.metadata_op()),
+                op_type: group_write_op_type(group_write),
                 cost: fee,
+                slot_fee,
+                bytes_fee,
+                group_members: group_members(group_write),
             });
 
             write_fee += fee;
@@ -633,6 +776,30 @@ where
         Ok(total_refund)
     }
 
+    fn tracks_dependencies(&self) -> bool {
+        true
+    }
+
+    fn charge_dependency(
+        &mut self,
+        is_new: bool,
+        module_id: &ModuleId,
+        size: NumBytes,
+    ) -> VMResult<()> {
+        let (cost, res) =
+            self.delegate_charge(|base| base.charge_dependency(is_new, module_id, size));
+
+        self.total_exec_io += cost;
+        self.dependencies.push(Dependency {
+            is_new,
+            id: module_id.clone(),
+            size,
+            cost,
+        });
+
+        res
+    }
+
     fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()> {
         let (cost, res) =
             self.delegate_charge(|base| base.charge_intrinsic_gas_for_transaction(txn_size));
@@ -648,11 +815,21 @@ impl<G> GasProfiler<G>
 where
     G: AptosGasMeter,
 {
-    pub fn finish(mut self) -> TransactionGasLog {
+    /// Finishes profiling and produces the gas log of the transaction.
+    ///
+    /// If source maps are provided, the instructions in the log will be annotated with their
+    /// locations in the Move source code, unless bytecode aggregation is enabled.
+    pub fn finish(mut self, source_maps: Option<&SourceMaps>) -> TransactionGasLog {
         while self.frames.len() > 1 {
-            let cur = self.frames.pop().expect("frame must exist");
-            let last = self.frames.last_mut().expect("frame must exist");
-            last.events.push(ExecutionGasEvent::Call(cur));
+            self.return_to_caller();
+        }
+
+        let mut call_graph = self.pop_frame();
+        if let Some(source_maps) = source_maps {
+            // Source locations cannot be reconstructed once the instructions are aggregated.
+            if !self.aggregate_bytecodes {
+                call_graph.resolve_source_locations(source_maps);
+            }
         }
 
         TransactionGasLog {
@@ -660,7 +837,8 @@ where
                 gas_scaling_factor: self.base.gas_unit_scaling_factor(),
                 total: self.total_exec_io,
                 intrinsic_cost: self.intrinsic_cost.unwrap_or_else(|| 0.into()),
-                call_graph: self.frames.pop().expect("frame must exist"),
+                dependencies: self.dependencies,
+                call_graph,
                 write_set_transient: self.write_set_transient,
             },
             storage: self.storage_fees.unwrap_or_else(|| StorageFees {
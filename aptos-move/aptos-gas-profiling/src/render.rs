@@ -130,6 +130,9 @@ impl<'a> Display for Render<'a, WriteOpType> {
             Creation => "create",
             Modification => "modify",
             Deletion => "delete",
+            GroupCreation => "create_group",
+            GroupModification => "modify_group",
+            GroupDeletion => "delete_group",
         })
     }
 }
@@ -0,0 +1,488 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::{
+    CallFrame, Dependency, EventStorage, ExecutionAndIOCosts, ExecutionGasEvent, FrameName,
+    StorageFees, TransactionGasLog, WriteStorage, WriteTransient,
+};
+use aptos_gas_algebra::{GasQuantity, InternalGas};
+use aptos_gas_schedule::{AptosGasParameters, ToOnChainGasSchedule};
+use move_binary_format::file_format_common::Opcodes;
+use std::collections::BTreeMap;
+
+/// The gas parameters that the costs of the different parts of a transaction are scaled by,
+/// from the most to the least recent names under which they are listed in the gas schedule.
+const INTRINSIC_KEYS: &[&str] = &["txn.min_transaction_gas_units"];
+const LOAD_RESOURCE_KEYS: &[&str] = &["txn.storage_io_per_state_slot_read", "txn.load_data.base"];
+const WRITE_TRANSIENT_KEYS: &[&str] = &[
+    "txn.storage_io_per_state_slot_write",
+    "txn.write_data.per_op",
+];
+const SLOT_FEE_KEYS: &[&str] = &["txn.storage_fee_per_state_slot_create"];
+const BYTES_FEE_KEYS: &[&str] = &["txn.storage_fee_per_excess_state_byte"];
+const EVENT_FEE_KEYS: &[&str] = &["txn.storage_fee_per_event_byte"];
+const TXN_STORAGE_FEE_KEYS: &[&str] = &["txn.storage_fee_per_transaction_byte"];
+
+/// Two gas schedules flattened into maps from parameter names to values, used to compute
+/// the ratios by which individual costs change.
+struct Repricer {
+    old: BTreeMap<String, u64>,
+    new: BTreeMap<String, u64>,
+}
+
+impl Repricer {
+    /// Scales a cost by the ratio between the new and the old values of the first parameter
+    /// in `keys` that is present in both schedules.
+    ///
+    /// The cost is kept as is if none of the parameters is available or if the old value is zero.
+    fn scale<U>(&self, cost: GasQuantity<U>, keys: &[&str]) -> GasQuantity<U> {
+        for key in keys {
+            if let (Some(old), Some(new)) = (self.old.get(*key), self.new.get(*key)) {
+                if *old == 0 {
+                    return cost;
+                }
+                let scaled = u64::from(cost) as u128 * *new as u128 / *old as u128;
+                return GasQuantity::new(scaled.min(u64::MAX as u128) as u64);
+            }
+        }
+        cost
+    }
+
+    fn reprice_frame(&self, frame: &CallFrame) -> CallFrame {
+        use ExecutionGasEvent::*;
+
+        let events = frame
+            .events
+            .iter()
+            .map(|event| match event {
+                Loc(offset) => Loc(*offset),
+                Bytecode {
+                    op,
+                    cost,
+                    count,
+                    loc,
+                } => {
+                    let keys = opcode_param_keys(*op);
+                    let keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+                    Bytecode {
+                        op: *op,
+                        cost: self.scale(*cost, &keys),
+                        count: *count,
+                        loc: loc.clone(),
+                    }
+                },
+                Call(inner_frame) => Call(self.reprice_frame(inner_frame)),
+                CallNative {
+                    module_id,
+                    fn_name,
+                    ty_args,
+                    cost,
+                    args_size,
+                    ret_size,
+                } => CallNative {
+                    module_id: module_id.clone(),
+                    fn_name: fn_name.clone(),
+                    ty_args: ty_args.clone(),
+                    cost: *cost,
+                    args_size: *args_size,
+                    ret_size: *ret_size,
+                },
+                LoadResource {
+                    addr,
+                    ty,
+                    cost,
+                    bytes_loaded,
+                } => LoadResource {
+                    addr: *addr,
+                    ty: ty.clone(),
+                    cost: self.scale(*cost, LOAD_RESOURCE_KEYS),
+                    bytes_loaded: *bytes_loaded,
+                },
+            })
+            .collect();
+
+        let name = match &frame.name {
+            FrameName::Script => FrameName::Script,
+            FrameName::Function {
+                module_id,
+                name,
+                ty_args,
+            } => FrameName::Function {
+                module_id: module_id.clone(),
+                name: name.clone(),
+                ty_args: ty_args.clone(),
+            },
+        };
+
+        CallFrame { name, events }
+    }
+}
+
+/// Returns the total cost of all events in a frame, including the costs of its callees.
+fn frame_total(frame: &CallFrame) -> InternalGas {
+    use ExecutionGasEvent::*;
+
+    frame
+        .events
+        .iter()
+        .fold(InternalGas::new(0), |total, event| match event {
+            Loc(..) => total,
+            Call(inner_frame) => total + frame_total(inner_frame),
+            Bytecode { cost, .. } | CallNative { cost, .. } | LoadResource { cost, .. } => {
+                total + *cost
+            },
+        })
+}
+
+/// Returns the name under which the cost of an instruction is listed in the gas schedule,
+/// e.g. `ld_u64` or `imm_borrow_global`.
+fn opcode_param_name(op: Opcodes) -> String {
+    use Opcodes::*;
+
+    match op {
+        XOR => "bit_xor".to_string(),
+        SHL => "bit_shl".to_string(),
+        SHR => "bit_shr".to_string(),
+        _ => format!("{:?}", op).to_ascii_lowercase(),
+    }
+}
+
+/// Returns the names of the gas parameters an instruction may be priced by, i.e. its flat cost
+/// or the base of its cost if it also depends on the sizes of its operands.
+fn opcode_param_keys(op: Opcodes) -> [String; 2] {
+    let name = opcode_param_name(op);
+    [format!("instr.{}", name), format!("instr.{}.base", name)]
+}
+
+impl TransactionGasLog {
+    /// Estimates the costs of the transaction under a different gas schedule, without
+    /// re-executing it.
+    ///
+    /// Every cost is scaled by how much its primary gas parameter changes between the two
+    /// schedules. This is exact for instructions with fixed costs, but only an estimate for
+    /// costs that also depend on the sizes of the values involved. Native function calls and
+    /// dependencies are kept as is.
+    ///
+    /// The result can be compared against the original log using [`crate::diff`].
+    pub fn reprice(
+        &self,
+        old_params: &AptosGasParameters,
+        new_params: &AptosGasParameters,
+        feature_version: u64,
+    ) -> TransactionGasLog {
+        let repricer = Repricer {
+            old: old_params
+                .to_on_chain_gas_schedule(feature_version)
+                .into_iter()
+                .collect(),
+            new: new_params
+                .to_on_chain_gas_schedule(feature_version)
+                .into_iter()
+                .collect(),
+        };
+
+        let exec_io = &self.exec_io;
+        let intrinsic_cost = repricer.scale(exec_io.intrinsic_cost, INTRINSIC_KEYS);
+        let dependencies = exec_io
+            .dependencies
+            .iter()
+            .map(|dep| Dependency {
+                is_new: dep.is_new,
+                id: dep.id.clone(),
+                size: dep.size,
+                cost: dep.cost,
+            })
+            .collect::<Vec<_>>();
+        let call_graph = repricer.reprice_frame(&exec_io.call_graph);
+        let write_set_transient = exec_io
+            .write_set_transient
+            .iter()
+            .map(|write| WriteTransient {
+                key: write.key.clone(),
+                op_type: write.op_type,
+                cost: repricer.scale(write.cost, WRITE_TRANSIENT_KEYS),
+                group_members: write.group_members.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut total_exec_io = intrinsic_cost;
+        for dep in &dependencies {
+            total_exec_io += dep.cost;
+        }
+        total_exec_io += frame_total(&call_graph);
+        for write in &write_set_transient {
+            total_exec_io += write.cost;
+        }
+
+        let storage = &self.storage;
+        let write_set_storage = storage
+            .write_set_storage
+            .iter()
+            .map(|write| {
+                let slot_fee = repricer.scale(write.slot_fee, SLOT_FEE_KEYS);
+                let bytes_fee = repricer.scale(write.bytes_fee, BYTES_FEE_KEYS);
+                WriteStorage {
+                    key: write.key.clone(),
+                    op_type: write.op_type,
+                    cost: slot_fee + bytes_fee,
+                    slot_fee,
+                    bytes_fee,
+                    group_members: write.group_members.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+        let events = storage
+            .events
+            .iter()
+            .map(|event| EventStorage {
+                ty: event.ty.clone(),
+                cost: repricer.scale(event.cost, EVENT_FEE_KEYS),
+            })
+            .collect::<Vec<_>>();
+        let event_discount = repricer.scale(storage.event_discount, EVENT_FEE_KEYS);
+        let txn_storage = repricer.scale(storage.txn_storage, TXN_STORAGE_FEE_KEYS);
+
+        let mut total_storage = txn_storage;
+        for write in &write_set_storage {
+            total_storage += write.cost;
+        }
+        for event in &events {
+            total_storage += event.cost;
+        }
+
+        TransactionGasLog {
+            exec_io: ExecutionAndIOCosts {
+                gas_scaling_factor: new_params.vm.txn.scaling_factor(),
+                total: total_exec_io,
+                intrinsic_cost,
+                dependencies,
+                call_graph,
+                write_set_transient,
+            },
+            storage: StorageFees {
+                total: total_storage,
+                write_set_storage,
+                events,
+                event_discount,
+                txn_storage,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_gas_schedule::{InitialGasSchedule, LATEST_GAS_FEATURE_VERSION};
+    use move_core_types::language_storage::TypeTag;
+
+    fn schedule(params: &AptosGasParameters) -> BTreeMap<String, u64> {
+        params
+            .to_on_chain_gas_schedule(LATEST_GAS_FEATURE_VERSION)
+            .into_iter()
+            .collect()
+    }
+
+    fn bytecode(op: Opcodes, cost: u64) -> ExecutionGasEvent {
+        ExecutionGasEvent::Bytecode {
+            op,
+            cost: InternalGas::new(cost),
+            count: 1,
+            loc: None,
+        }
+    }
+
+    fn bytecode_costs(frame: &CallFrame) -> Vec<u64> {
+        frame
+            .events
+            .iter()
+            .flat_map(|event| match event {
+                ExecutionGasEvent::Bytecode { cost, .. } => vec![u64::from(*cost)],
+                ExecutionGasEvent::Call(inner_frame) => bytecode_costs(inner_frame),
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    fn gas_log(params: &AptosGasParameters) -> TransactionGasLog {
+        let call_graph = CallFrame {
+            name: FrameName::Script,
+            events: vec![
+                bytecode(Opcodes::LD_U64, 100),
+                bytecode(Opcodes::ADD, 30),
+                ExecutionGasEvent::Call(CallFrame {
+                    name: FrameName::Script,
+                    events: vec![bytecode(Opcodes::LD_U64, 100)],
+                }),
+            ],
+        };
+        TransactionGasLog {
+            exec_io: ExecutionAndIOCosts {
+                gas_scaling_factor: params.vm.txn.scaling_factor(),
+                total: InternalGas::new(1230),
+                intrinsic_cost: InternalGas::new(1000),
+                dependencies: vec![],
+                call_graph,
+                write_set_transient: vec![],
+            },
+            storage: StorageFees {
+                total: GasQuantity::new(500),
+                write_set_storage: vec![],
+                events: vec![EventStorage {
+                    ty: TypeTag::U64,
+                    cost: GasQuantity::new(200),
+                }],
+                event_discount: GasQuantity::new(0),
+                txn_storage: GasQuantity::new(300),
+            },
+        }
+    }
+
+    #[test]
+    fn test_param_keys_are_in_schedule() {
+        use Opcodes::*;
+
+        let schedule = schedule(&AptosGasParameters::initial());
+        let ops = [
+            POP,
+            RET,
+            BR_TRUE,
+            BR_FALSE,
+            BRANCH,
+            LD_U64,
+            LD_CONST,
+            LD_TRUE,
+            LD_FALSE,
+            COPY_LOC,
+            MOVE_LOC,
+            ST_LOC,
+            MUT_BORROW_LOC,
+            IMM_BORROW_LOC,
+            MUT_BORROW_FIELD,
+            IMM_BORROW_FIELD,
+            CALL,
+            PACK,
+            UNPACK,
+            READ_REF,
+            WRITE_REF,
+            ADD,
+            SUB,
+            MUL,
+            MOD,
+            DIV,
+            BIT_OR,
+            BIT_AND,
+            XOR,
+            OR,
+            AND,
+            NOT,
+            EQ,
+            NEQ,
+            LT,
+            GT,
+            LE,
+            GE,
+            ABORT,
+            NOP,
+            EXISTS,
+            MUT_BORROW_GLOBAL,
+            IMM_BORROW_GLOBAL,
+            MOVE_FROM,
+            MOVE_TO,
+            FREEZE_REF,
+            SHL,
+            SHR,
+            LD_U8,
+            LD_U128,
+            CAST_U8,
+            CAST_U64,
+            CAST_U128,
+            MUT_BORROW_FIELD_GENERIC,
+            IMM_BORROW_FIELD_GENERIC,
+            CALL_GENERIC,
+            PACK_GENERIC,
+            UNPACK_GENERIC,
+            EXISTS_GENERIC,
+            MUT_BORROW_GLOBAL_GENERIC,
+            IMM_BORROW_GLOBAL_GENERIC,
+            MOVE_FROM_GENERIC,
+            MOVE_TO_GENERIC,
+            VEC_PACK,
+            VEC_LEN,
+            VEC_IMM_BORROW,
+            VEC_MUT_BORROW,
+            VEC_PUSH_BACK,
+            VEC_POP_BACK,
+            VEC_UNPACK,
+            VEC_SWAP,
+            LD_U16,
+            LD_U32,
+            LD_U256,
+            CAST_U16,
+            CAST_U32,
+            CAST_U256,
+        ];
+        // Every opcode is listed, as they are numbered consecutively from 1
+        for (i, op) in ops.iter().enumerate() {
+            assert_eq!(*op as usize, i + 1);
+        }
+        assert_eq!(ops.len(), CAST_U256 as usize);
+
+        for op in ops {
+            let keys = opcode_param_keys(op);
+            assert!(
+                keys.iter().any(|key| schedule.contains_key(key)),
+                "no gas parameter for {:?} among {:?}",
+                op,
+                keys
+            );
+        }
+        for keys in [
+            INTRINSIC_KEYS,
+            LOAD_RESOURCE_KEYS,
+            WRITE_TRANSIENT_KEYS,
+            SLOT_FEE_KEYS,
+            BYTES_FEE_KEYS,
+            EVENT_FEE_KEYS,
+            TXN_STORAGE_FEE_KEYS,
+        ] {
+            assert!(
+                keys.iter().any(|key| schedule.contains_key(*key)),
+                "no gas parameter among {:?}",
+                keys
+            );
+        }
+    }
+
+    #[test]
+    fn test_reprice_doubled_param() {
+        let old_params = AptosGasParameters::initial();
+        let log = gas_log(&old_params);
+
+        // Doubling the price of an instruction doubles its costs only
+        let mut new_params = AptosGasParameters::initial();
+        new_params.vm.instr.ld_u64 = InternalGas::new(u64::from(old_params.vm.instr.ld_u64) * 2);
+        let repriced = log.reprice(&old_params, &new_params, LATEST_GAS_FEATURE_VERSION);
+        assert_eq!(
+            bytecode_costs(&repriced.exec_io.call_graph),
+            vec![200, 30, 200]
+        );
+        assert_eq!(repriced.exec_io.intrinsic_cost, InternalGas::new(1000));
+        assert_eq!(repriced.exec_io.total, InternalGas::new(1430));
+        assert_eq!(repriced.storage.total, GasQuantity::new(500));
+
+        // Doubling the price of event bytes doubles the event fees only
+        let mut new_params = AptosGasParameters::initial();
+        new_params.vm.txn.storage_fee_per_event_byte =
+            GasQuantity::new(u64::from(old_params.vm.txn.storage_fee_per_event_byte) * 2);
+        let repriced = log.reprice(&old_params, &new_params, LATEST_GAS_FEATURE_VERSION);
+        assert_eq!(
+            bytecode_costs(&repriced.exec_io.call_graph),
+            vec![100, 30, 100]
+        );
+        assert_eq!(repriced.exec_io.total, InternalGas::new(1230));
+        assert_eq!(repriced.storage.events[0].cost, GasQuantity::new(400));
+        assert_eq!(repriced.storage.txn_storage, GasQuantity::new(300));
+        assert_eq!(repriced.storage.total, GasQuantity::new(700));
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    aggregate::{aggregation_key, insert_or_add, into_sorted_vec},
+    log::{CallFrame, ExecutionGasEvent, TransactionGasLog},
+    profiler::GasProfiler,
+    render::Render,
+    source::SourceMaps,
+};
+use aptos_gas_algebra::{Fee, GasScalingFactor, InternalGas};
+use aptos_gas_meter::AptosGasMeter;
+use std::collections::BTreeMap;
+
+/// Gas statistics of a single function, accumulated over all of its invocations.
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionGasStats {
+    /// Number of times the function has been called.
+    pub calls: usize,
+    /// Cost of the instructions executed by the function itself, excluding callees.
+    pub self_cost: InternalGas,
+    /// Cost of the function including all callees and resource loads.
+    ///
+    /// Recursive invocations are only counted once, at the outermost call.
+    pub inclusive_cost: InternalGas,
+}
+
+/// Gas statistics aggregated over all transactions in a profiling session.
+///
+/// All lists are sorted by the amount of gas used, from high to low.
+pub struct AggregatedSessionGasStats {
+    pub num_transactions: usize,
+
+    /// The gas scaling factor.
+    /// This is included so to make this struct self-contained, suitable for displaying in (external) gas units.
+    pub gas_scaling_factor: GasScalingFactor,
+
+    pub total_exec_io: InternalGas,
+    pub total_storage: Fee,
+
+    pub functions: Vec<(String, FunctionGasStats)>,
+    pub resource_reads: Vec<(String, usize, InternalGas)>,
+    pub resource_writes: Vec<(String, usize, InternalGas)>,
+    pub resource_storage: Vec<(String, usize, Fee)>,
+}
+
+/// A profiling session spanning multiple transactions, e.g. a whole block or a Move test suite.
+///
+/// A `GasProfiler` is only meant to be used for a single transaction, so every transaction
+/// should be executed with a fresh profiler, which is then handed back to the session via
+/// `finish_transaction`. The session unwinds any frames left open by the profiler and folds
+/// the resulting log into its statistics, so only the aggregated data is kept in memory.
+pub struct GasProfilingSession {
+    num_transactions: usize,
+    gas_scaling_factor: Option<GasScalingFactor>,
+
+    total_exec_io: InternalGas,
+    total_storage: Fee,
+
+    functions: BTreeMap<String, FunctionGasStats>,
+    resource_reads: BTreeMap<String, (usize, InternalGas)>,
+    resource_writes: BTreeMap<String, (usize, InternalGas)>,
+    resource_storage: BTreeMap<String, (usize, Fee)>,
+}
+
+impl Default for GasProfilingSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GasProfilingSession {
+    pub fn new() -> Self {
+        Self {
+            num_transactions: 0,
+            gas_scaling_factor: None,
+
+            total_exec_io: 0.into(),
+            total_storage: 0.into(),
+
+            functions: BTreeMap::new(),
+            resource_reads: BTreeMap::new(),
+            resource_writes: BTreeMap::new(),
+            resource_storage: BTreeMap::new(),
+        }
+    }
+
+    pub fn num_transactions(&self) -> usize {
+        self.num_transactions
+    }
+
+    /// Finishes profiling the current transaction and adds its log to the session.
+    ///
+    /// The log is returned so it can still be inspected or rendered individually.
+    pub fn finish_transaction<G>(
+        &mut self,
+        profiler: GasProfiler<G>,
+        source_maps: Option<&SourceMaps>,
+    ) -> TransactionGasLog
+    where
+        G: AptosGasMeter,
+    {
+        let log = profiler.finish(source_maps);
+        self.add_transaction(&log);
+        log
+    }
+
+    /// Adds the log of a transaction that has already been profiled to the session.
+    pub fn add_transaction(&mut self, log: &TransactionGasLog) {
+        self.num_transactions += 1;
+        self.gas_scaling_factor
+            .get_or_insert(log.exec_io.gas_scaling_factor);
+
+        self.total_exec_io += log.exec_io.total;
+        self.total_storage += log.storage.total;
+
+        self.visit_frame(&log.exec_io.call_graph, &mut vec![]);
+
+        for write in &log.exec_io.write_set_transient {
+            insert_or_add(
+                &mut self.resource_writes,
+                aggregation_key(&write.key),
+                write.cost,
+            );
+        }
+        for write in &log.storage.write_set_storage {
+            insert_or_add(
+                &mut self.resource_storage,
+                aggregation_key(&write.key),
+                write.cost,
+            );
+        }
+    }
+
+    /// Records the stats of a call frame and all its callees, returning the inclusive cost
+    /// of the frame.
+    ///
+    /// `active` contains the names of the frames currently on the call stack, which is used
+    /// to avoid counting the costs of recursive calls multiple times.
+    fn visit_frame(&mut self, frame: &CallFrame, active: &mut Vec<String>) -> InternalGas {
+        use ExecutionGasEvent::*;
+
+        let name = format!("{}", frame.name);
+        let is_recursive = active.contains(&name);
+        active.push(name);
+
+        let mut self_cost = InternalGas::new(0);
+        let mut inclusive_cost = InternalGas::new(0);
+
+        for event in &frame.events {
+            match event {
+                Loc(..) => (),
+                Bytecode { cost, .. } => {
+                    self_cost += *cost;
+                    inclusive_cost += *cost;
+                },
+                Call(inner_frame) => inclusive_cost += self.visit_frame(inner_frame, active),
+                CallNative {
+                    module_id,
+                    fn_name,
+                    ty_args,
+                    cost,
+                    ..
+                } => {
+                    let native_name = format!(
+                        "{}",
+                        Render(&(module_id, fn_name.as_ident_str(), ty_args.as_slice()))
+                    );
+                    let is_native_recursive = active.contains(&native_name);
+                    self.record_call(native_name, *cost, *cost, is_native_recursive);
+                    inclusive_cost += *cost;
+                },
+                LoadResource { ty, cost, .. } => {
+                    insert_or_add(&mut self.resource_reads, format!("{}", ty), *cost);
+                    inclusive_cost += *cost;
+                },
+            }
+        }
+
+        let name = active.pop().expect("frame must exist");
+        self.record_call(name, self_cost, inclusive_cost, is_recursive);
+
+        inclusive_cost
+    }
+
+    fn record_call(
+        &mut self,
+        name: String,
+        self_cost: InternalGas,
+        inclusive_cost: InternalGas,
+        is_recursive: bool,
+    ) {
+        let stats = self.functions.entry(name).or_insert(FunctionGasStats {
+            calls: 0,
+            self_cost: 0.into(),
+            inclusive_cost: 0.into(),
+        });
+        stats.calls += 1;
+        stats.self_cost += self_cost;
+        if !is_recursive {
+            stats.inclusive_cost += inclusive_cost;
+        }
+    }
+
+    /// Produces the aggregated statistics of all transactions recorded so far.
+    pub fn aggregate(&self) -> AggregatedSessionGasStats {
+        let mut functions = self
+            .functions
+            .iter()
+            .map(|(name, stats)| (name.clone(), *stats))
+            .collect::<Vec<_>>();
+        // Sort in descending order.
+        functions.sort_by(|(_name1, stats1), (_name2, stats2)| {
+            stats2.inclusive_cost.cmp(&stats1.inclusive_cost)
+        });
+
+        AggregatedSessionGasStats {
+            num_transactions: self.num_transactions,
+            // An empty session has nothing to scale, so any factor will do.
+            gas_scaling_factor: self.gas_scaling_factor.unwrap_or_else(|| 1.into()),
+
+            total_exec_io: self.total_exec_io,
+            total_storage: self.total_storage,
+
+            functions,
+            resource_reads: into_sorted_vec(self.resource_reads.clone()),
+            resource_writes: into_sorted_vec(self.resource_writes.clone()),
+            resource_storage: into_sorted_vec(self.resource_storage.clone()),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::{CallFrame, ExecutionGasEvent, FrameName};
+use aptos_framework::BuiltPackage;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_core_types::{
+    identifier::{IdentStr, Identifier},
+    language_storage::ModuleId,
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    path::Path,
+};
+
+/// A location in the Move source code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Source information of a single module.
+struct ModuleSourceInfo {
+    file: String,
+    /// Byte offsets at which each line of the source file starts.
+    line_starts: Vec<usize>,
+    functions: BTreeMap<Identifier, FunctionDefinitionIndex>,
+    source_map: SourceMap,
+}
+
+/// A collection of source maps that can be used to resolve code offsets of Move functions
+/// to locations in the original source files.
+pub struct SourceMaps {
+    modules: BTreeMap<ModuleId, ModuleSourceInfo>,
+}
+
+impl SourceMaps {
+    pub fn new() -> Self {
+        Self {
+            modules: BTreeMap::new(),
+        }
+    }
+
+    /// Collects the source maps of all modules in a built package, including its dependencies.
+    pub fn from_built_package(package: &BuiltPackage) -> anyhow::Result<Self> {
+        let mut source_maps = Self::new();
+        for (named_module, source_path) in package.all_named_modules_with_source() {
+            let source = std::fs::read_to_string(source_path)?;
+            source_maps.add_module(
+                &named_module.module,
+                named_module.source_map.clone(),
+                source_path,
+                &source,
+            );
+        }
+        Ok(source_maps)
+    }
+
+    /// Adds the source map of a module, along with the content of the source file it was
+    /// compiled from.
+    pub fn add_module(
+        &mut self,
+        module: &CompiledModule,
+        source_map: SourceMap,
+        source_path: &Path,
+        source: &str,
+    ) {
+        // Only the file name is kept so the reports stay readable.
+        let file = source_path
+            .file_name()
+            .unwrap_or(source_path.as_os_str())
+            .to_string_lossy()
+            .to_string();
+
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+
+        let functions = module
+            .function_defs()
+            .iter()
+            .enumerate()
+            .map(|(idx, def)| {
+                let handle = module.function_handle_at(def.function);
+                (
+                    module.identifier_at(handle.name).to_owned(),
+                    FunctionDefinitionIndex(idx as u16),
+                )
+            })
+            .collect();
+
+        self.modules.insert(module.self_id(), ModuleSourceInfo {
+            file,
+            line_starts,
+            functions,
+            source_map,
+        });
+    }
+
+    /// Resolves a code offset within a function to a location in the source code, if the
+    /// source map of the module is available.
+    pub fn resolve(
+        &self,
+        module_id: &ModuleId,
+        fn_name: &IdentStr,
+        offset: CodeOffset,
+    ) -> Option<SourceLocation> {
+        let info = self.modules.get(module_id)?;
+        let fdef_idx = info.functions.get(fn_name)?;
+        let loc = info.source_map.get_code_location(*fdef_idx, offset).ok()?;
+
+        let line = match info.line_starts.binary_search(&(loc.start() as usize)) {
+            Ok(idx) => idx,
+            Err(idx) => idx - 1,
+        };
+
+        Some(SourceLocation {
+            file: info.file.clone(),
+            line: line as u32 + 1,
+        })
+    }
+}
+
+impl Default for SourceMaps {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CallFrame {
+    /// Annotates the bytecode events in this frame and all its callees with locations in the
+    /// source code.
+    ///
+    /// The profiler only records the code offsets of branch targets, so the offsets of the other
+    /// instructions are reconstructed by assuming control flows sequentially in between.
+    pub(crate) fn resolve_source_locations(&mut self, source_maps: &SourceMaps) {
+        let (module_id, fn_name) = match &self.name {
+            FrameName::Function {
+                module_id, name, ..
+            } => (module_id, name),
+            FrameName::Script => {
+                // Scripts do not have source maps, but their callees may.
+                for event in &mut self.events {
+                    if let ExecutionGasEvent::Call(frame) = event {
+                        frame.resolve_source_locations(source_maps);
+                    }
+                }
+                return;
+            },
+        };
+
+        let mut pc: CodeOffset = 0;
+        for event in &mut self.events {
+            use ExecutionGasEvent::*;
+
+            match event {
+                Loc(offset) => pc = *offset,
+                Bytecode { loc, .. } => {
+                    *loc = source_maps.resolve(module_id, fn_name, pc);
+                    pc = pc.wrapping_add(1);
+                },
+                Call(frame) => frame.resolve_source_locations(source_maps),
+                CallNative { .. } | LoadResource { .. } => (),
+            }
+        }
+    }
+}
@@ -2,11 +2,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    aggregate::AggregatedExecutionGasEvents,
+    aggregate::{
+        AggregatedExecutionGasEvents, AggregatedResourceLoads, AggregatedStateKeyCosts,
+        ResourceLoadStats,
+    },
+    blocks::AggregatedBasicBlocks,
+    diff::{GasDelta, GasLogDelta},
     erased::{Node, TypeErasedExecutionAndIoCosts, TypeErasedGasLog, TypeErasedStorageFees},
+    render::Render,
+    session::AggregatedSessionGasStats,
+};
+use aptos_gas_algebra::{Fee, GasQuantity, GasScalingFactor, InternalGas};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Write},
 };
-use aptos_gas_algebra::{GasQuantity, InternalGas};
-use std::fmt::{self, Write};
 
 fn indent(output: &mut impl Write, count: usize) -> fmt::Result {
     if count == 0 {
@@ -191,6 +201,300 @@ impl AggregatedExecutionGasEvents {
             table.push(fmt_item(name, *count, *cost));
         }
 
+        render_table(output, &table, 4)?;
+        writeln!(output)?;
+        self.resource_loads
+            .textualize(output, self.gas_scaling_factor)
+    }
+}
+
+impl AggregatedResourceLoads {
+    pub fn textualize(
+        &self,
+        output: &mut impl Write,
+        gas_scaling_factor: GasScalingFactor,
+    ) -> fmt::Result {
+        let scaling_factor = u64::from(gas_scaling_factor) as f64;
+
+        let fmt_item = |name: String, stats: &ResourceLoadStats| {
+            let cost_scaled = format!("{:.8}", (u64::from(stats.cost) as f64 / scaling_factor));
+            let cost_scaled =
+                crate::misc::strip_trailing_zeros_and_decimal_point(&cost_scaled).to_string();
+
+            [
+                format!("        {}", name),
+                format!("x{}", stats.count),
+                format!("{} bytes", stats.bytes),
+                cost_scaled,
+            ]
+        };
+
+        let mut table = vec![];
+
+        table.push([
+            "resource loads (gas unit, aggregated)".to_string(),
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+        ]);
+
+        table.push([
+            "    by type".to_string(),
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+        ]);
+        for (ty, stats) in &self.by_type {
+            table.push(fmt_item(format!("{}", ty), stats));
+        }
+
+        table.push([
+            "    by address".to_string(),
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+        ]);
+        for (addr, stats) in &self.by_address {
+            table.push(fmt_item(format!("{}", Render(addr)), stats));
+        }
+
+        render_table(output, &table, 4)
+    }
+}
+
+impl AggregatedBasicBlocks {
+    pub fn textualize(&self, output: &mut impl Write) -> fmt::Result {
+        let total_cost = u64::from(self.total) as f64;
+        let scaling_factor = u64::from(self.gas_scaling_factor) as f64;
+
+        let mut table = vec![];
+
+        table.push([
+            "hottest basic blocks (gas unit)".to_string(),
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+        ]);
+        for block in &self.blocks {
+            let cost_scaled = format!("{:.8}", (u64::from(block.cost) as f64 / scaling_factor));
+            let cost_scaled =
+                crate::misc::strip_trailing_zeros_and_decimal_point(&cost_scaled).to_string();
+
+            let percentage = format!("{:.2}%", u64::from(block.cost) as f64 / total_cost * 100.0);
+
+            table.push([
+                format!("    {} [{}..={}]", block.function, block.start, block.end),
+                format!("x{}", block.executions),
+                cost_scaled,
+                percentage,
+            ]);
+        }
+
+        render_table(output, &table, 4)
+    }
+}
+
+impl AggregatedStateKeyCosts {
+    pub fn textualize(&self, output: &mut impl Write) -> fmt::Result {
+        let scaling_factor = u64::from(self.gas_scaling_factor) as f64;
+
+        let fmt_gas = |cost: InternalGas| {
+            let cost_scaled = format!("{:.8}", (u64::from(cost) as f64 / scaling_factor));
+            crate::misc::strip_trailing_zeros_and_decimal_point(&cost_scaled).to_string()
+        };
+        let fmt_fee = |cost: Fee| {
+            let cost_scaled = format!("{:.8}", (u64::from(cost) as f64 / 1_0000_0000f64));
+            crate::misc::strip_trailing_zeros_and_decimal_point(&cost_scaled).to_string()
+        };
+
+        let mut table = vec![[
+            "writes by state key".to_string(),
+            "IO (gas unit)".to_string(),
+            "slot fees (APT)".to_string(),
+            "bytes fees (APT)".to_string(),
+        ]];
+        for (key, stats) in &self.keys {
+            table.push([
+                format!("    {}", Render(key)),
+                format!("{} (x{})", fmt_gas(stats.transient), stats.transient_count),
+                fmt_fee(stats.slot_fee),
+                fmt_fee(stats.bytes_fee),
+            ]);
+        }
+
+        render_table(output, &table, 4)
+    }
+}
+
+impl AggregatedSessionGasStats {
+    pub fn textualize(&self, output: &mut impl Write) -> fmt::Result {
+        let scaling_factor = u64::from(self.gas_scaling_factor) as f64;
+
+        let fmt_gas = |cost: InternalGas| {
+            let cost_scaled = format!("{:.8}", (u64::from(cost) as f64 / scaling_factor));
+            crate::misc::strip_trailing_zeros_and_decimal_point(&cost_scaled).to_string()
+        };
+        let fmt_fee = |cost: Fee| {
+            let cost_scaled = format!("{:.8}", (u64::from(cost) as f64 / 1_0000_0000f64));
+            crate::misc::strip_trailing_zeros_and_decimal_point(&cost_scaled).to_string()
+        };
+        let header = |text: &str| {
+            [
+                text.to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ]
+        };
+
+        let mut table = vec![];
+
+        table.push(header(&format!("{} transaction(s)", self.num_transactions)));
+
+        table.push([
+            "execution & IO (gas unit)".to_string(),
+            "".to_string(),
+            fmt_gas(self.total_exec_io),
+            "".to_string(),
+        ]);
+
+        table.push([
+            "    functions".to_string(),
+            "calls".to_string(),
+            "inclusive".to_string(),
+            "self".to_string(),
+        ]);
+        for (name, stats) in &self.functions {
+            table.push([
+                format!("        {}", name),
+                format!("x{}", stats.calls),
+                fmt_gas(stats.inclusive_cost),
+                fmt_gas(stats.self_cost),
+            ]);
+        }
+
+        table.push(header("    resource reads"));
+        for (name, count, cost) in &self.resource_reads {
+            table.push([
+                format!("        {}", name),
+                format!("x{}", count),
+                fmt_gas(*cost),
+                "".to_string(),
+            ]);
+        }
+
+        table.push(header("    resource writes"));
+        for (name, count, cost) in &self.resource_writes {
+            table.push([
+                format!("        {}", name),
+                format!("x{}", count),
+                fmt_gas(*cost),
+                "".to_string(),
+            ]);
+        }
+
+        table.push([
+            "storage fees (APT)".to_string(),
+            "".to_string(),
+            fmt_fee(self.total_storage),
+            "".to_string(),
+        ]);
+        for (name, count, cost) in &self.resource_storage {
+            table.push([
+                format!("        {}", name),
+                format!("x{}", count),
+                fmt_fee(*cost),
+                "".to_string(),
+            ]);
+        }
+
+        render_table(output, &table, 4)
+    }
+}
+
+impl GasLogDelta {
+    pub fn textualize(&self, output: &mut impl Write) -> fmt::Result {
+        let scaling_factor = u64::from(self.gas_scaling_factor) as f64;
+
+        let fmt_change = |change: i128, scale: f64| {
+            let sign = match change.cmp(&0) {
+                Ordering::Greater => "+",
+                Ordering::Less => "-",
+                Ordering::Equal => "",
+            };
+            let cost_scaled = format!("{:.8}", change.unsigned_abs() as f64 / scale);
+            format!(
+                "{}{}",
+                sign,
+                crate::misc::strip_trailing_zeros_and_decimal_point(&cost_scaled)
+            )
+        };
+        let fmt_cost = |cost: u64, scale: f64| {
+            let cost_scaled = format!("{:.8}", cost as f64 / scale);
+            crate::misc::strip_trailing_zeros_and_decimal_point(&cost_scaled).to_string()
+        };
+        let fmt_item = |name: String, delta_before: u64, delta_after: u64, change: i128, scale| {
+            [
+                name,
+                fmt_cost(delta_before, scale),
+                fmt_cost(delta_after, scale),
+                fmt_change(change, scale),
+            ]
+        };
+        let fmt_gas = |name: String, delta: &GasDelta<_>| {
+            fmt_item(
+                name,
+                u64::from(delta.before),
+                u64::from(delta.after),
+                delta.change(),
+                scaling_factor,
+            )
+        };
+        let fmt_fee = |name: String, delta: &GasDelta<_>| {
+            fmt_item(
+                name,
+                u64::from(delta.before),
+                u64::from(delta.after),
+                delta.change(),
+                1_0000_0000f64,
+            )
+        };
+
+        let mut table = vec![[
+            "".to_string(),
+            "before".to_string(),
+            "after".to_string(),
+            "change".to_string(),
+        ]];
+
+        table.push(fmt_gas(
+            "execution & IO (gas unit)".to_string(),
+            &self.exec_io_total,
+        ));
+        if !self.intrinsic.is_unchanged() {
+            table.push(fmt_gas("    intrinsic".to_string(), &self.intrinsic));
+        }
+        for (path, delta) in &self.frames {
+            table.push(fmt_gas(format!("    {}", path), delta));
+        }
+        for (key, delta) in &self.write_set_transient {
+            table.push(fmt_gas(format!("    write<{}>", Render(key)), delta));
+        }
+
+        table.push(fmt_fee(
+            "storage fees (APT)".to_string(),
+            &self.storage_total,
+        ));
+        if !self.txn_storage.is_unchanged() {
+            table.push(fmt_fee("    transaction".to_string(), &self.txn_storage));
+        }
+        for (key, delta) in &self.write_set_storage {
+            table.push(fmt_fee(format!("    write<{}>", Render(key)), delta));
+        }
+        if !self.events.is_unchanged() {
+            table.push(fmt_fee("    events".to_string(), &self.events));
+        }
+
         render_table(output, &table, 4)
     }
 }
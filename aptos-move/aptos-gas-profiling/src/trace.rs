@@ -0,0 +1,136 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    log::{CallFrame, ExecutionAndIOCosts, ExecutionGasEvent},
+    render::Render,
+};
+use aptos_gas_algebra::InternalGas;
+use serde::Serialize;
+
+/// A complete ("X") event in the Chrome trace event format.
+///
+/// See https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+#[derive(Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    ts: u64,
+    dur: u64,
+    pid: u64,
+    tid: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace {
+    trace_events: Vec<TraceEvent>,
+    display_time_unit: &'static str,
+}
+
+struct TraceBuilder {
+    events: Vec<TraceEvent>,
+    /// Current position on the time axis, measured in internal gas units.
+    now: u64,
+}
+
+impl TraceBuilder {
+    fn push(&mut self, name: String, cat: &'static str, cost: InternalGas) {
+        let dur = u64::from(cost);
+        if dur == 0 {
+            return;
+        }
+
+        self.events.push(TraceEvent {
+            name,
+            cat,
+            ph: "X",
+            ts: self.now,
+            dur,
+            pid: 0,
+            tid: 0,
+        });
+        self.now += dur;
+    }
+
+    /// Emits the events of a frame and all its callees, followed by an event spanning
+    /// the entire frame.
+    fn visit(&mut self, frame: &CallFrame) {
+        use ExecutionGasEvent::*;
+
+        let start = self.now;
+
+        for event in &frame.events {
+            match event {
+                Loc(_) => (),
+                Bytecode { op, cost, .. } => {
+                    self.push(format!("{:?}", op).to_ascii_lowercase(), "bytecode", *cost)
+                },
+                Call(inner_frame) => self.visit(inner_frame),
+                CallNative {
+                    module_id,
+                    fn_name,
+                    ty_args,
+                    cost,
+                    ..
+                } => self.push(
+                    format!(
+                        "{}",
+                        Render(&(module_id, fn_name.as_ident_str(), ty_args.as_slice()))
+                    ),
+                    "native",
+                    *cost,
+                ),
+                LoadResource { addr, ty, cost, .. } => self.push(
+                    format!("load<{}::{}>", Render(addr), ty),
+                    "storage_read",
+                    *cost,
+                ),
+            }
+        }
+
+        if self.now > start {
+            self.events.push(TraceEvent {
+                name: format!("{}", frame.name),
+                cat: "call",
+                ph: "X",
+                ts: start,
+                dur: self.now - start,
+                pid: 0,
+                tid: 0,
+            });
+        }
+    }
+}
+
+impl ExecutionAndIOCosts {
+    /// Exports the execution gas log in the Chrome trace event format, which can be loaded into
+    /// Perfetto or chrome://tracing.
+    ///
+    /// The time axis represents internal gas units, with one gas unit mapped to one microsecond.
+    pub fn to_chrome_trace(&self) -> anyhow::Result<String> {
+        let mut builder = TraceBuilder {
+            events: vec![],
+            now: 0,
+        };
+
+        builder.push("intrinsic".to_string(), "intrinsic", self.intrinsic_cost);
+        for dep in &self.dependencies {
+            builder.push(format!("{}", Render(&dep.id)), "dependency", dep.cost);
+        }
+        builder.visit(&self.call_graph);
+        for write in &self.write_set_transient {
+            builder.push(
+                format!("{}<{}>", Render(&write.op_type), Render(&write.key)),
+                "storage_write",
+                write.cost,
+            );
+        }
+
+        Ok(serde_json::to_string(&Trace {
+            trace_events: builder.events,
+            display_time_unit: "ms",
+        })?)
+    }
+}
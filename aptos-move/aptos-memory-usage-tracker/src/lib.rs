@@ -475,6 +475,8 @@ where
         fn storage_discount_for_events(&self, total_cost: Fee) -> Fee;
 
         fn storage_fee_for_transaction_storage(&self, txn_size: NumBytes) -> Fee;
+
+        fn tracks_dependencies(&self) -> bool;
     }
 
     delegate_mut! {
@@ -491,5 +493,7 @@ where
         ) -> PartialVMResult<()>;
 
         fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()>;
+
+        fn charge_dependency(&mut self, is_new: bool, module_id: &ModuleId, size: NumBytes) -> VMResult<()>;
     }
 }
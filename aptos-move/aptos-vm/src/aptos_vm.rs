@@ -19,7 +19,7 @@ use anyhow::{anyhow, Result};
 use aptos_block_executor::txn_commit_hook::NoOpTransactionCommitHook;
 use aptos_crypto::HashValue;
 use aptos_framework::natives::code::PublishRequest;
-use aptos_gas_algebra::Gas;
+use aptos_gas_algebra::{Gas, NumBytes};
 use aptos_gas_meter::{AptosGasMeter, StandardGasAlgebra, StandardGasMeter};
 use aptos_gas_schedule::VMGasParameters;
 use aptos_logger::{enabled, prelude::*, Level};
@@ -55,9 +55,10 @@ use aptos_vm_types::{
 };
 use fail::fail_point;
 use move_binary_format::{
-    access::ModuleAccess,
+    access::{ModuleAccess, ScriptAccess},
     compatibility::Compatibility,
     errors::{verification_error, Location, PartialVMError, VMError, VMResult},
+    file_format::CompiledScript,
     CompiledModule, IndexKind,
 };
 use move_core_types::{
@@ -86,6 +87,7 @@ use std::{
 static EXECUTION_CONCURRENCY_LEVEL: OnceCell<usize> = OnceCell::new();
 static NUM_EXECUTION_SHARD: OnceCell<usize> = OnceCell::new();
 static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
+static NUM_STATE_CHECKPOINT_THREADS: OnceCell<usize> = OnceCell::new();
 static PARANOID_TYPE_CHECKS: OnceCell<bool> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
 static TIMED_FEATURE_OVERRIDE: OnceCell<TimedFeatureOverride> = OnceCell::new();
@@ -207,6 +209,21 @@ impl AptosVM {
         }
     }
 
+    /// Sets the # of threads to calculate the state checkpoints of chunks.
+    pub fn set_num_state_checkpoint_threads_once(num_threads: usize) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        NUM_STATE_CHECKPOINT_THREADS.set(max(num_threads, 1)).ok();
+    }
+
+    /// Returns the # of threads to calculate the state checkpoints of chunks if already set,
+    /// otherwise return default value (16).
+    pub fn get_num_state_checkpoint_threads() -> usize {
+        match NUM_STATE_CHECKPOINT_THREADS.get() {
+            Some(num_threads) => *num_threads,
+            None => 16,
+        }
+    }
+
     /// Sets addigional details in counters when invoked the first time.
     pub fn set_processed_transactions_detailed_counters() {
         // Only the first call succeeds, due to OnceCell semantics.
@@ -412,6 +429,7 @@ impl AptosVM {
             script_fn.function(),
             script_fn.ty_args(),
         )?;
+        self.charge_dependencies(session, gas_meter, [script_fn.module().clone()])?;
         let struct_constructors = self
             .0
             .get_features()
@@ -459,6 +477,15 @@ impl AptosVM {
                 TransactionPayload::Script(script) => {
                     let loaded_func =
                         session.load_script(script.code(), script.ty_args().to_vec())?;
+                    if gas_meter.tracks_dependencies() {
+                        if let Ok(compiled_script) = CompiledScript::deserialize(script.code()) {
+                            self.charge_dependencies(
+                                &session,
+                                gas_meter,
+                                compiled_script.immediate_dependencies(),
+                            )?;
+                        }
+                    }
                     // Gerardo: consolidate the extended validation to verifier.
                     verifier::event_validation::verify_no_event_emission_in_script(
                         script.code(),
@@ -942,6 +969,47 @@ impl AptosVM {
         )
     }
 
+    /// Reports the modules in the transitive dependency closure of `roots`, as read from storage,
+    /// to the gas meter. Does nothing unless the gas meter tracks dependencies.
+    ///
+    /// Modules that fail to load or deserialize are skipped rather than failing the transaction,
+    /// so that it fails in the VM the same way it does with a gas meter that doesn't track them.
+    fn charge_dependencies(
+        &self,
+        session: &SessionExt,
+        gas_meter: &mut impl AptosGasMeter,
+        roots: impl IntoIterator<Item = ModuleId>,
+    ) -> VMResult<()> {
+        if !gas_meter.tracks_dependencies() {
+            return Ok(());
+        }
+
+        let mut visited = BTreeSet::new();
+        let mut stack: Vec<ModuleId> = roots.into_iter().collect();
+        while let Some(module_id) = stack.pop() {
+            if !visited.insert(module_id.clone()) {
+                continue;
+            }
+            let bytes = match session.load_module(&module_id) {
+                Ok(bytes) => bytes,
+                Err(_) => continue,
+            };
+            gas_meter.charge_dependency(false, &module_id, NumBytes::new(bytes.len() as u64))?;
+
+            let module = match CompiledModule::deserialize(&bytes) {
+                Ok(module) => module,
+                Err(_) => continue,
+            };
+            stack.extend(
+                module
+                    .immediate_dependencies()
+                    .into_iter()
+                    .filter(|dep| !visited.contains(dep)),
+            );
+        }
+        Ok(())
+    }
+
     /// Resolve a pending code publish request registered via the NativeCodeContext.
     fn resolve_pending_code_publish(
         &self,
@@ -975,6 +1043,15 @@ impl AptosVM {
                 }
             }
 
+            // Report the modules being published as new dependencies of the transaction.
+            for (m, code) in modules.iter().zip(bundle.iter()) {
+                gas_meter.charge_dependency(
+                    true,
+                    &m.self_id(),
+                    NumBytes::new(code.code().len() as u64),
+                )?;
+            }
+
             // Publish the bundle and execute initializers
             // publish_module_bundle doesn't actually load the published module into
             // the loader cache. It only puts the module data in the data cache.
@@ -1442,18 +1519,51 @@ impl AptosVM {
         txn: &SignedTransaction,
         executor_view: &impl ExecutorView,
     ) -> (VMStatus, TransactionOutput) {
+        let (vm_status, output, _gas_meter) =
+            Self::simulate_signed_transaction_with_custom_gas_meter(
+                txn,
+                executor_view,
+                |gas_feature_version, gas_params, storage_gas_params, balance| {
+                    Ok(MemoryTrackedGasMeter::new(StandardGasMeter::new(
+                        StandardGasAlgebra::new(
+                            gas_feature_version,
+                            gas_params,
+                            storage_gas_params,
+                            balance,
+                        ),
+                    )))
+                },
+            );
+        (vm_status, output)
+    }
+
+    /// Executes a SignedTransaction without performing signature verification, using a custom
+    /// gas meter created by `make_gas_meter`.
+    ///
+    /// The gas meter is returned along with the output, so it can be inspected afterwards, unless
+    /// the transaction was discarded before the gas meter could be created.
+    pub fn simulate_signed_transaction_with_custom_gas_meter<G, F>(
+        txn: &SignedTransaction,
+        executor_view: &impl ExecutorView,
+        make_gas_meter: F,
+    ) -> (VMStatus, TransactionOutput, Option<G>)
+    where
+        G: AptosGasMeter,
+        F: FnOnce(u64, VMGasParameters, StorageGasParameters, Gas) -> Result<G, VMStatus>,
+    {
         let vm = AptosVM::new(&StorageAdapter::from_borrowed(executor_view));
         let simulation_vm = AptosSimulationVM(vm);
         let log_context = AdapterLogSchema::new(executor_view.id(), 0);
 
         let resolver = simulation_vm.0.as_move_resolver(executor_view);
-        let (vm_status, vm_output) =
-            simulation_vm.simulate_signed_transaction(&resolver, txn, &log_context);
+        let ((vm_status, vm_output), gas_meter) =
+            simulation_vm.simulate_signed_transaction(&resolver, txn, &log_context, make_gas_meter);
         (
             vm_status,
             vm_output
                 .try_into_transaction_output(&resolver)
                 .expect("Simulation cannot fail"),
+            gas_meter,
         )
     }
 
@@ -1888,16 +1998,28 @@ impl AptosSimulationVM {
         )
     }
 
-    fn simulate_signed_transaction(
+    /// Simulates the transaction using the gas meter created by `make_gas_meter`.
+    ///
+    /// The gas meter is returned along with the output, unless the transaction was discarded
+    /// before it could be created.
+    fn simulate_signed_transaction<G, F>(
         &self,
         resolver: &impl AptosMoveResolver,
         txn: &SignedTransaction,
         log_context: &AdapterLogSchema,
-    ) -> (VMStatus, VMOutput) {
+        make_gas_meter: F,
+    ) -> ((VMStatus, VMOutput), Option<G>)
+    where
+        G: AptosGasMeter,
+        F: FnOnce(u64, VMGasParameters, StorageGasParameters, Gas) -> Result<G, VMStatus>,
+    {
         // simulation transactions should not carry valid signatures, otherwise malicious fullnodes
         // may execute them without user's explicit permission.
         if txn.signature_is_valid() {
-            return discard_error_vm_status(VMStatus::error(StatusCode::INVALID_SIGNATURE, None));
+            return (
+                discard_error_vm_status(VMStatus::error(StatusCode::INVALID_SIGNATURE, None)),
+                None,
+            );
         }
 
         // Revalidate the transaction.
@@ -1906,25 +2028,27 @@ impl AptosSimulationVM {
         if let Err(err) =
             self.validate_simulated_transaction(&mut session, resolver, txn, &txn_data, log_context)
         {
-            return discard_error_vm_status(err);
+            return (discard_error_vm_status(err), None);
         };
 
         let gas_params = match self.0 .0.get_gas_parameters(log_context) {
-            Err(err) => return discard_error_vm_status(err),
+            Err(err) => return (discard_error_vm_status(err), None),
             Ok(s) => s,
         };
         let storage_gas_params = match self.0 .0.get_storage_gas_parameters(log_context) {
-            Err(err) => return discard_error_vm_status(err),
+            Err(err) => return (discard_error_vm_status(err), None),
             Ok(s) => s,
         };
 
-        let mut gas_meter =
-            MemoryTrackedGasMeter::new(StandardGasMeter::new(StandardGasAlgebra::new(
-                self.0 .0.get_gas_feature_version(),
-                gas_params.vm.clone(),
-                storage_gas_params.clone(),
-                txn_data.max_gas_amount(),
-            )));
+        let mut gas_meter = match make_gas_meter(
+            self.0 .0.get_gas_feature_version(),
+            gas_params.vm.clone(),
+            storage_gas_params.clone(),
+            txn_data.max_gas_amount(),
+        ) {
+            Err(err) => return (discard_error_vm_status(err), None),
+            Ok(gas_meter) => gas_meter,
+        };
 
         let mut new_published_modules_loaded = false;
         let result = match txn.payload() {
@@ -1994,7 +2118,7 @@ impl AptosSimulationVM {
             ),
         };
 
-        match result {
+        let status_and_output = match result {
             Ok(output) => output,
             Err(err) => {
                 // Invalidate the loader cache in case there was a new module loaded from a module
@@ -2026,6 +2150,8 @@ impl AptosSimulationVM {
                     (vm_status, output)
                 }
             },
-        }
+        };
+
+        (status_and_output, Some(gas_meter))
     }
 }
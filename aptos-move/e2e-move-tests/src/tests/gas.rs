@@ -38,8 +38,19 @@ fn save_profiling_results(name: &str, log: &TransactionGasLog) {
         .unwrap();
     writeln!(text).unwrap();
     writeln!(text).unwrap();
+    log.exec_io
+        .aggregate_basic_blocks(20)
+        .textualize(&mut text)
+        .unwrap();
+    writeln!(text).unwrap();
+    writeln!(text).unwrap();
 
     erased.storage.textualize(&mut text, true).unwrap();
+    writeln!(text).unwrap();
+    writeln!(text).unwrap();
+    log.aggregate_state_key_costs()
+        .textualize(&mut text)
+        .unwrap();
 
     fs::write(path.join("log.txt"), text).unwrap();
 }
@@ -576,7 +576,7 @@ impl FakeExecutor {
 
         Ok((
             output.try_into_transaction_output(&resolver)?,
-            gas_profiler.finish(),
+            gas_profiler.finish(None),
         ))
     }
 
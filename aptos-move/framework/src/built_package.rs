@@ -297,6 +297,19 @@ impl BuiltPackage {
             })
     }
 
+    /// Returns an iterator for all compiled proper (non-script) modules, including dependencies,
+    /// along with the paths of the source files they were compiled from.
+    pub fn all_named_modules_with_source(
+        &self,
+    ) -> impl Iterator<Item = (&NamedCompiledModule, &Path)> {
+        self.package
+            .all_modules()
+            .filter_map(|unit| match &unit.unit {
+                CompiledUnit::Module(module) => Some((module, unit.source_path.as_path())),
+                CompiledUnit::Script(_) => None,
+            })
+    }
+
     /// Returns the number of scripts in the package.
     pub fn script_count(&self) -> usize {
         self.package.scripts().count()
@@ -90,6 +90,19 @@ pub struct AptosNodeArgs {
     /// Display information about the build of this node
     #[clap(long)]
     info: bool,
+
+    /// URL or local directory of a state snapshot to bootstrap an empty DB from.
+    ///
+    /// The snapshot is verified against the waypoint in the node configuration. Overrides
+    /// `storage#bootstrap_from_snapshot` in the node configuration.
+    #[clap(long, value_parser, conflicts_with("test"))]
+    bootstrap_from_snapshot: Option<String>,
+
+    /// Process again the transactions that the indexer recorded as dead letters, on startup.
+    ///
+    /// Overrides `indexer#reprocess_dead_letters` in the node configuration.
+    #[clap(long, conflicts_with("test"))]
+    reprocess_indexer_dead_letters: bool,
 }
 
 impl AptosNodeArgs {
@@ -142,13 +155,20 @@ impl AptosNodeArgs {
             }
 
             // A config file exists, attempt to parse the config
-            let config = NodeConfig::load_from_path(config_path.clone()).unwrap_or_else(|error| {
-                panic!(
-                    "Failed to load the node config file! Given file path: {:?}. Error: {:?}",
-                    config_path.display(),
-                    error
-                )
-            });
+            let mut config =
+                NodeConfig::load_from_path(config_path.clone()).unwrap_or_else(|error| {
+                    panic!(
+                        "Failed to load the node config file! Given file path: {:?}. Error: {:?}",
+                        config_path.display(),
+                        error
+                    )
+                });
+            if let Some(snapshot) = self.bootstrap_from_snapshot {
+                config.storage.bootstrap_from_snapshot = Some(snapshot);
+            }
+            if self.reprocess_indexer_dead_letters {
+                config.indexer.reprocess_dead_letters = Some(true);
+            }
 
             // Start the node
             start(config, None, true).expect("Node should start correctly");
@@ -63,7 +63,7 @@ pub fn bootstrap_api_and_indexer(
         chain_id,
         aptos_db.clone(),
         mempool_client_sender.clone(),
-    );
+    )?;
 
     // Create the indexer runtime
     let indexer_runtime =
@@ -12,7 +12,7 @@ use aptos_data_streaming_service::{
 use aptos_event_notifications::{
     DbBackedOnChainConfig, EventSubscriptionService, ReconfigNotificationListener,
 };
-use aptos_executor::chunk_executor::ChunkExecutor;
+use aptos_executor::chunk_executor::{ChunkExecutor, ChunkExecutorConfig};
 use aptos_infallible::RwLock;
 use aptos_mempool_notifications::MempoolNotificationListener;
 use aptos_network::application::{
@@ -100,7 +100,16 @@ pub fn start_state_sync_and_get_notification_handles(
         setup_data_streaming_service(node_config.state_sync, aptos_data_client.clone())?;
 
     // Create the chunk executor and persistent storage
-    let chunk_executor = Arc::new(ChunkExecutor::<AptosVM>::new(db_rw.clone()));
+    let chunk_executor = Arc::new(ChunkExecutor::<AptosVM>::new_with_config(
+        db_rw.clone(),
+        ChunkExecutorConfig {
+            trust_proof_verified_signatures: node_config
+                .state_sync
+                .state_sync_driver
+                .trust_proof_verified_signatures,
+            ..ChunkExecutorConfig::default()
+        },
+    ));
     let metadata_storage = PersistentMetadataStorage::new(&node_config.storage.dir());
 
     // Create notification senders and listeners for mempool, consensus and the storage service
@@ -7,7 +7,9 @@ use aptos_config::{config::NodeConfig, utils::get_genesis_txn};
 use aptos_db::{fast_sync_storage_wrapper::FastSyncStorageWrapper, AptosDB};
 use aptos_executor::db_bootstrapper::maybe_bootstrap;
 use aptos_logger::{debug, info};
-use aptos_storage_interface::{DbReader, DbReaderWriter};
+use aptos_storage_interface::{
+    state_snapshot_import::import_state_snapshot, DbReader, DbReaderWriter,
+};
 use aptos_types::waypoint::Waypoint;
 use aptos_vm::AptosVM;
 use either::Either;
@@ -25,6 +27,43 @@ pub(crate) fn maybe_apply_genesis(db_rw: &DbReaderWriter, node_config: &NodeConf
     Ok(())
 }
 
+/// If configured and the DB is empty, initializes the DB from a state snapshot
+fn maybe_bootstrap_from_snapshot(db_rw: &DbReaderWriter, node_config: &NodeConfig) -> Result<()> {
+    let snapshot = match &node_config.storage.bootstrap_from_snapshot {
+        Some(snapshot) => snapshot,
+        None => return Ok(()),
+    };
+    if db_rw.reader.get_latest_ledger_info_option()?.is_some() {
+        info!(
+            "DB is not empty, skip bootstrapping from state snapshot {}.",
+            snapshot
+        );
+        return Ok(());
+    }
+
+    info!("Bootstrapping DB from state snapshot {}.", snapshot);
+    let read_file = |file_name: &str| -> Result<Vec<u8>> {
+        if snapshot.starts_with("http://") || snapshot.starts_with("https://") {
+            let url = format!("{}/{}", snapshot.trim_end_matches('/'), file_name);
+            Ok(reqwest::blocking::get(url)?
+                .error_for_status()?
+                .bytes()?
+                .to_vec())
+        } else {
+            Ok(fs::read(Path::new(snapshot).join(file_name))?)
+        }
+    };
+    let waypoint = node_config.base.waypoint.waypoint();
+    import_state_snapshot(db_rw, waypoint, read_file).map_err(|err| {
+        anyhow!(
+            "Failed to bootstrap DB from state snapshot {}: {}",
+            snapshot,
+            err
+        )
+    })?;
+    Ok(())
+}
+
 #[cfg(not(feature = "consensus-only-perf-test"))]
 pub(crate) fn bootstrap_db(
     node_config: &NodeConfig,
@@ -37,6 +76,7 @@ pub(crate) fn bootstrap_db(
                     node_config.storage.backup_service_address,
                     db_arc.clone(),
                 );
+                maybe_bootstrap_from_snapshot(&db_rw, node_config)?;
                 maybe_apply_genesis(&db_rw, node_config)?;
                 (db_arc as Arc<dyn DbReader>, db_rw, Some(db_backup_service))
             },
@@ -68,7 +108,7 @@ pub(crate) fn bootstrap_db(
         &node_config.storage.dir(),
         false, /* readonly */
         node_config.storage.storage_pruner_config,
-        node_config.storage.rocksdb_configs,
+        node_config.storage.rocksdb_configs_with_overrides(),
         node_config.storage.enable_indexer,
         node_config.storage.buffered_state_target_items,
         node_config.storage.max_num_nodes_per_lru_cache_shard,
@@ -53,6 +53,9 @@ pub fn set_aptos_vm_configurations(node_config: &NodeConfig) {
     AptosVM::set_num_proof_reading_threads_once(
         node_config.execution.num_proof_reading_threads as usize,
     );
+    AptosVM::set_num_state_checkpoint_threads_once(
+        node_config.execution.num_state_checkpoint_threads as usize,
+    );
 
     if node_config
         .execution
@@ -4,8 +4,9 @@
 
 use crate::{
     config::{
-        config_sanitizer::ConfigSanitizer, gas_estimation_config::GasEstimationConfig,
-        node_config_loader::NodeType, Error, NodeConfig,
+        api_key_config::ApiKeysConfig, config_sanitizer::ConfigSanitizer,
+        gas_estimation_config::GasEstimationConfig, node_config_loader::NodeType,
+        webhook_config::WebhooksConfig, Error, NodeConfig,
     },
     utils,
 };
@@ -33,12 +34,20 @@ pub struct ApiConfig {
     /// Enables failpoints for error testing
     #[serde(default = "default_disabled")]
     pub failpoints_enabled: bool,
+    /// Enables the stream of new events at `/v1/events/stream`
+    #[serde(default = "default_disabled")]
+    pub event_stream_enabled: bool,
     /// Enables JSON output of APIs that support it
     #[serde(default = "default_enabled")]
     pub json_output_enabled: bool,
     /// Enables BCS output of APIs that support it
     #[serde(default = "default_enabled")]
     pub bcs_output_enabled: bool,
+    /// Enables compressing the responses with zstd or gzip, as negotiated with `Accept-Encoding`
+    #[serde(default = "default_enabled")]
+    pub compression_enabled: bool,
+    /// Minimum size of a response body in bytes for it to be compressed
+    pub compression_min_size: usize,
     /// Enables encode submission API
     #[serde(default = "default_enabled")]
     pub encode_submission_enabled: bool,
@@ -50,6 +59,15 @@ pub struct ApiConfig {
     pub transaction_simulation_enabled: bool,
     /// Maximum number of transactions that can be sent with the Batch submit API
     pub max_submit_transaction_batch_size: usize,
+    /// Maximum number of transactions that can be looked up with `/transactions/by_hashes`
+    pub max_transactions_by_hashes_batch_size: usize,
+    /// Maximum time `/transactions/submit_and_wait` waits for the transaction to be committed
+    pub max_submit_and_wait_timeout_secs: u64,
+    /// Maximum number of entries of each cache of the responses that can't change once
+    /// committed, like the transactions and the blocks
+    pub response_cache_max_entries: usize,
+    /// Maximum size in bytes of the data of each response cache. Set to 0 to disable the caches.
+    pub response_cache_max_bytes: usize,
     /// Maximum page size for transaction paginated APIs
     pub max_transactions_page_size: u16,
     /// Maximum page size for event paginated APIs
@@ -58,10 +76,20 @@ pub struct ApiConfig {
     pub max_account_resources_page_size: u16,
     /// Maximum page size for module paginated APIs
     pub max_account_modules_page_size: u16,
+    /// Maximum page size for table item paginated APIs
+    pub max_table_items_page_size: u16,
     /// Maximum gas unit limit for view functions
     ///
     /// This limits the execution length of a view function to the given gas used.
     pub max_gas_view_function: u64,
+    /// Maximum number of view functions that can be executed with the batch view API
+    pub max_view_function_batch_size: usize,
+    /// Optional: Seconds the latest ledger info can be behind the current time for `/-/health`
+    /// to report the node as healthy. The `max_sync_lag_secs` param overrides it.
+    pub health_check_max_sync_lag_secs: Option<u64>,
+    /// Optional: Minimum number of connected peers for `/-/health` to report the node as healthy.
+    /// The `min_connected_peers` param overrides it.
+    pub health_check_min_connected_peers: Option<u64>,
     /// Optional: Maximum number of worker threads for the API.
     ///
     /// If not set, `runtime_worker_multiplier` will multiply times the number of CPU cores on the machine
@@ -72,6 +100,10 @@ pub struct ApiConfig {
     pub runtime_worker_multiplier: usize,
     /// Configs for computing unit gas price estimation
     pub gas_estimation: GasEstimationConfig,
+    /// Configs for the API keys and their rate limits
+    pub api_keys: ApiKeysConfig,
+    /// Configs for the webhooks called with the committed transactions
+    pub webhooks: WebhooksConfig,
 }
 
 const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -79,9 +111,15 @@ const DEFAULT_PORT: u16 = 8080;
 const DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT: u64 = 8 * 1024 * 1024; // 8 MB
 pub const DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE: usize = 10;
 pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
+const DEFAULT_MAX_TRANSACTIONS_BY_HASHES_BATCH_SIZE: usize = 100;
 const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
+const DEFAULT_MAX_VIEW_FUNCTION_BATCH_SIZE: usize = 50;
+const DEFAULT_COMPRESSION_MIN_SIZE: usize = 1024; // 1 KB
+const DEFAULT_MAX_SUBMIT_AND_WAIT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_RESPONSE_CACHE_MAX_ENTRIES: usize = 10_000;
+const DEFAULT_RESPONSE_CACHE_MAX_BYTES: usize = 64 * 1024 * 1024; // 64 MB
 
 fn default_enabled() -> bool {
     true
@@ -102,20 +140,33 @@ impl Default for ApiConfig {
             tls_key_path: None,
             content_length_limit: None,
             failpoints_enabled: default_disabled(),
+            event_stream_enabled: default_disabled(),
             bcs_output_enabled: default_enabled(),
             json_output_enabled: default_enabled(),
+            compression_enabled: default_enabled(),
+            compression_min_size: DEFAULT_COMPRESSION_MIN_SIZE,
             encode_submission_enabled: default_enabled(),
             transaction_submission_enabled: default_enabled(),
             transaction_simulation_enabled: default_enabled(),
             max_submit_transaction_batch_size: DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE,
+            max_transactions_by_hashes_batch_size: DEFAULT_MAX_TRANSACTIONS_BY_HASHES_BATCH_SIZE,
+            max_submit_and_wait_timeout_secs: DEFAULT_MAX_SUBMIT_AND_WAIT_TIMEOUT_SECS,
+            response_cache_max_entries: DEFAULT_RESPONSE_CACHE_MAX_ENTRIES,
+            response_cache_max_bytes: DEFAULT_RESPONSE_CACHE_MAX_BYTES,
             max_transactions_page_size: DEFAULT_MAX_PAGE_SIZE,
             max_events_page_size: DEFAULT_MAX_PAGE_SIZE,
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
+            max_table_items_page_size: DEFAULT_MAX_PAGE_SIZE,
             max_gas_view_function: DEFAULT_MAX_VIEW_GAS,
+            max_view_function_batch_size: DEFAULT_MAX_VIEW_FUNCTION_BATCH_SIZE,
+            health_check_max_sync_lag_secs: None,
+            health_check_min_connected_peers: None,
             max_runtime_workers: None,
             runtime_worker_multiplier: 2,
             gas_estimation: GasEstimationConfig::default(),
+            api_keys: ApiKeysConfig::default(),
+            webhooks: WebhooksConfig::default(),
         }
     }
 }
@@ -164,6 +215,8 @@ impl ConfigSanitizer for ApiConfig {
         }
 
         GasEstimationConfig::sanitize(node_config, node_type, chain_id)?;
+        ApiKeysConfig::sanitize(node_config, node_type, chain_id)?;
+        WebhooksConfig::sanitize(node_config, node_type, chain_id)?;
 
         Ok(())
     }
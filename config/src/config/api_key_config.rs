@@ -0,0 +1,140 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+};
+use aptos_types::chain_id::ChainId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+pub const DEFAULT_API_KEY_HEADER: &str = "x-aptos-api-key";
+
+/// A rate limit, as a bucket of `burst` requests refilled at `requests_per_second`
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiRateLimitConfig {
+    pub requests_per_second: u32,
+    pub burst: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    /// Name of the key in the metrics, so that the key itself isn't exposed
+    pub name: String,
+    pub key: String,
+    pub rate_limit: ApiRateLimitConfig,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiKeysConfig {
+    /// Enables the API keys and their rate limits
+    pub enabled: bool,
+    /// Header of the requests carrying the API key
+    pub header: String,
+    pub keys: Vec<ApiKeyConfig>,
+    /// Rate limit shared by all the requests without an API key. If not set, the requests
+    /// without an API key are rejected.
+    pub anonymous_rate_limit: Option<ApiRateLimitConfig>,
+}
+
+impl Default for ApiKeysConfig {
+    fn default() -> ApiKeysConfig {
+        ApiKeysConfig {
+            enabled: false,
+            header: DEFAULT_API_KEY_HEADER.to_string(),
+            keys: vec![],
+            anonymous_rate_limit: None,
+        }
+    }
+}
+
+impl ConfigSanitizer for ApiKeysConfig {
+    fn sanitize(
+        node_config: &NodeConfig,
+        _node_type: NodeType,
+        _chain_id: ChainId,
+    ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let api_keys_config = &node_config.api.api_keys;
+        if !api_keys_config.enabled {
+            return Ok(());
+        }
+
+        let (mut names, mut keys) = (HashSet::new(), HashSet::new());
+        for api_key in &api_keys_config.keys {
+            if !names.insert(&api_key.name) || !keys.insert(&api_key.key) {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!("API key {} is configured more than once", api_key.name),
+                ));
+            }
+        }
+
+        let rate_limits = api_keys_config
+            .keys
+            .iter()
+            .map(|api_key| (api_key.name.as_str(), &api_key.rate_limit))
+            .chain(
+                api_keys_config
+                    .anonymous_rate_limit
+                    .iter()
+                    .map(|rate_limit| ("anonymous", rate_limit)),
+            );
+        for (name, rate_limit) in rate_limits {
+            if rate_limit.requests_per_second == 0 || rate_limit.burst == 0 {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!(
+                        "The requests per second and burst of the {} rate limit must be > 0",
+                        name
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiConfig;
+
+    fn api_key(name: &str, key: &str, requests_per_second: u32) -> ApiKeyConfig {
+        ApiKeyConfig {
+            name: name.to_string(),
+            key: key.to_string(),
+            rate_limit: ApiRateLimitConfig {
+                requests_per_second,
+                burst: 10,
+            },
+        }
+    }
+
+    fn sanitize(keys: Vec<ApiKeyConfig>) -> Result<(), Error> {
+        let node_config = NodeConfig {
+            api: ApiConfig {
+                api_keys: ApiKeysConfig {
+                    enabled: true,
+                    keys,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        ApiKeysConfig::sanitize(&node_config, NodeType::PublicFullnode, ChainId::mainnet())
+    }
+
+    #[test]
+    fn test_sanitize_api_keys() {
+        sanitize(vec![api_key("a", "key_a", 10), api_key("b", "key_b", 5)]).unwrap();
+        assert!(sanitize(vec![api_key("a", "key_a", 10), api_key("a", "key_b", 5)]).is_err());
+        assert!(sanitize(vec![api_key("a", "key_a", 10), api_key("b", "key_a", 5)]).is_err());
+        assert!(sanitize(vec![api_key("a", "key_a", 0)]).is_err());
+    }
+}
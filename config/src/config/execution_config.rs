@@ -28,6 +28,8 @@ pub struct ExecutionConfig {
     pub concurrency_level: u16,
     /// Number of threads to read proofs
     pub num_proof_reading_threads: u16,
+    /// Number of threads to calculate the state checkpoints of synced chunks
+    pub num_state_checkpoint_threads: u16,
     /// Enables paranoid mode for types, which adds extra runtime VM checks
     pub paranoid_type_verification: bool,
     /// Enables paranoid mode for hot potatoes, which adds extra runtime VM checks
@@ -60,6 +62,7 @@ impl Default for ExecutionConfig {
             // Parallel execution by default.
             concurrency_level: 8,
             num_proof_reading_threads: 32,
+            num_state_checkpoint_threads: 16,
             paranoid_type_verification: true,
             paranoid_hot_potato_verification: true,
             processed_transactions_detailed_counters: false,
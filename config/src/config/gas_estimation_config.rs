@@ -32,6 +32,8 @@ pub struct GasEstimationConfig {
     pub aggressive_block_history: usize,
     /// Time after write when previous value is returned without recomputing
     pub cache_expiration_ms: u64,
+    /// Margin added to the gas used by a simulated transaction to suggest its max gas amount
+    pub max_gas_safety_margin_percent: u64,
 }
 
 impl Default for GasEstimationConfig {
@@ -44,6 +46,7 @@ impl Default for GasEstimationConfig {
             market_block_history: 30,
             aggressive_block_history: 120,
             cache_expiration_ms: 500,
+            max_gas_safety_margin_percent: 20,
         }
     }
 }
@@ -5,22 +5,28 @@ use crate::config::{
     config_optimizer::ConfigOptimizer, node_config_loader::NodeType, Error, NodeConfig,
 };
 use aptos_logger::warn;
-use aptos_types::chain_id::ChainId;
+use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
-use std::fmt::{Debug, Formatter};
+use std::{
+    fmt::{Debug, Formatter},
+    str::FromStr,
+};
 
 // Useful indexer environment variables
 const GAP_LOOKBACK_VERSIONS: &str = "GAP_LOOKBACK_VERSIONS";
 const INDEXER_DATABASE_URL: &str = "INDEXER_DATABASE_URL";
 const PROCESSOR_NAME: &str = "PROCESSOR_NAME";
 const STARTING_VERSION: &str = "STARTING_VERSION";
+const BACKFILL_START_VERSION: &str = "BACKFILL_START_VERSION";
+const BACKFILL_END_VERSION: &str = "BACKFILL_END_VERSION";
 
 // Useful indexer defaults
 pub const DEFAULT_BATCH_SIZE: u16 = 500;
 pub const DEFAULT_FETCH_TASKS: u8 = 5;
 pub const DEFAULT_PROCESSOR_TASKS: u8 = 5;
 pub const DEFAULT_EMIT_EVERY: u64 = 1000;
+pub const DEFAULT_BALANCE_SNAPSHOT_INTERVAL_SECS: u64 = 3600;
 
 #[derive(Clone, Default, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -46,6 +52,20 @@ pub struct IndexerConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub starting_version: Option<u64>,
 
+    /// If set, will also process the versions from `backfill_start_version` to
+    /// `backfill_end_version` (inclusive), alongside following the head of the chain. The
+    /// processor's tables are written to idempotently, and its last success version is not
+    /// updated by the backfill.
+    /// Alternatively can set the `BACKFILL_START_VERSION` env var
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backfill_start_version: Option<u64>,
+
+    /// The last version to backfill. Defaults to the version before the starting version of the
+    /// head-following processing.
+    /// Alternatively can set the `BACKFILL_END_VERSION` env var
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backfill_end_version: Option<u64>,
+
     ///////////////////
     ///////////////////
     ///////////////////
@@ -57,6 +77,13 @@ pub struct IndexerConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub check_chain_id: Option<bool>,
 
+    /// If set, truncates all the indexer tables when the chain changed, i.e. its chain id or
+    /// genesis is different (e.g. after a network wipe), instead of failing to start. A ledger
+    /// behind the indexed data on the same chain always fails. Only applies if `check_chain_id`
+    /// is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset_on_chain_change: Option<bool>,
+
     /// How many versions to fetch and process from a node in parallel
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub batch_size: Option<u16>,
@@ -65,7 +92,8 @@ pub struct IndexerConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub fetch_tasks: Option<u8>,
 
-    /// How many tasks to run for processing the transactions
+    /// How many batches of transactions to process concurrently. The checkpoint of the processor
+    /// is still committed in version order, once all the batches before are processed.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub processor_tasks: Option<u8>,
 
@@ -87,6 +115,92 @@ pub struct IndexerConfig {
     /// Custom NFT points contract
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub nft_points_contract: Option<String>,
+
+    /// If set, the processors also publish their records to Kafka or NATS, or write them to
+    /// Parquet files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sink: Option<IndexerSinkConfig>,
+
+    /// If set, serves the progress of the processor (last processed version, lag, errors and
+    /// throughput) as JSON at `/health` on this port. The status is 503 if it stalled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_port: Option<u16>,
+
+    /// If set, only the transactions touching one of these addresses are indexed, i.e. calling
+    /// an entry function or emitting an event of a module at one of them, or writing to one of
+    /// them. ex: ["0x1", "0x3"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_module_addresses: Option<Vec<String>>,
+
+    /// If set, a batch that fails is processed again one transaction at a time, and the
+    /// transactions that still fail are recorded in the `dead_letters` table and skipped instead
+    /// of stopping the processor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_letter_failed_transactions: Option<bool>,
+
+    /// If set, the transactions in the `dead_letters` table of the processor are processed again
+    /// on startup, e.g. after deploying a fix, and removed from it if they succeed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reprocess_dead_letters: Option<bool>,
+
+    /// Length in seconds of the intervals that the balance_history_processor snapshots the
+    /// balances at the end of (default hourly). Changing it doesn't rewrite the existing snapshots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_snapshot_interval_secs: Option<u64>,
+
+    /// Tables to partition by transaction version, with the retention of their partitions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partitioned_tables: Option<Vec<IndexerTablePartitionConfig>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexerSinkConfig {
+    /// Where the records are published
+    pub sink_type: IndexerSinkType,
+
+    /// The records of a table are published as JSON to the `<topic_prefix>.<table>` topic
+    /// (Kafka) or subject (NATS), written to the `<topic_prefix>.<table>` directory (Parquet), or
+    /// inserted into the `<table>` table of the `<topic_prefix>` database (ClickHouse)
+    pub topic_prefix: String,
+
+    /// If set, the records are only published to the sink and not written to Postgres. With
+    /// ClickHouse, this only applies to the tables it stores.
+    /// The processor status is still kept in Postgres.
+    #[serde(default)]
+    pub skip_postgres_writes: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexerTablePartitionConfig {
+    /// The table to partition, whose primary key must include `transaction_version`. Its existing
+    /// rows become its first partition.
+    pub table: String,
+
+    /// How many versions each partition holds
+    pub partition_versions: u64,
+
+    /// If set, the partitions whose versions are all more than this many versions behind the last
+    /// processed version are dropped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_versions: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerSinkType {
+    /// Kafka bootstrap servers, ex: "localhost:9092". Requires the `kafka` feature
+    Kafka { brokers: String },
+    /// NATS server url, ex: "nats://localhost:4222". Requires the `nats` feature
+    Nats { url: String },
+    /// Local directory or `gs://<bucket>/<prefix>` to write Parquet files to, ex: "/data/indexer".
+    /// Requires the `parquet` feature
+    Parquet { path: String },
+    /// ClickHouse HTTP interface url, ex: "http://localhost:8123". Only the high volume tables
+    /// (events and coin_activities) are stored in ClickHouse, and their schema is created on startup
+    #[serde(rename = "clickhouse")]
+    ClickHouse { url: String },
 }
 
 impl Debug for IndexerConfig {
@@ -103,8 +217,11 @@ impl Debug for IndexerConfig {
             .field("postgres_uri", &postgres_uri)
             .field("processor", &self.processor)
             .field("starting_version", &self.starting_version)
+            .field("backfill_start_version", &self.backfill_start_version)
+            .field("backfill_end_version", &self.backfill_end_version)
             .field("skip_migrations", &self.skip_migrations)
             .field("check_chain_id", &self.check_chain_id)
+            .field("reset_on_chain_change", &self.reset_on_chain_change)
             .field("batch_size", &self.batch_size)
             .field("fetch_tasks", &self.fetch_tasks)
             .field("processor_tasks", &self.processor_tasks)
@@ -112,6 +229,19 @@ impl Debug for IndexerConfig {
             .field("gap_lookback_versions", &self.gap_lookback_versions)
             .field("ans_contract_address", &self.ans_contract_address)
             .field("nft_points_contract", &self.nft_points_contract)
+            .field("sink", &self.sink)
+            .field("health_port", &self.health_port)
+            .field("allowed_module_addresses", &self.allowed_module_addresses)
+            .field(
+                "dead_letter_failed_transactions",
+                &self.dead_letter_failed_transactions,
+            )
+            .field("reprocess_dead_letters", &self.reprocess_dead_letters)
+            .field(
+                "balance_snapshot_interval_secs",
+                &self.balance_snapshot_interval_secs,
+            )
+            .field("partitioned_tables", &self.partitioned_tables)
             .finish()
     }
 }
@@ -155,24 +285,75 @@ impl ConfigOptimizer for IndexerConfig {
         );
 
         // Verify and set the starting version
-        indexer_config.starting_version = match std::env::var(STARTING_VERSION).ok() {
-            None => indexer_config.starting_version,
-            Some(starting_version) => match starting_version.parse::<u64>() {
-                Ok(version) => Some(version),
-                Err(error) => {
-                    // This will allow a processor to have STARTING_VERSION undefined when deploying
-                    warn!(
-                        "Invalid STARTING_VERSION: {}. Error: {:?}. Using {:?} instead.",
-                        starting_version, error, indexer_config.starting_version
-                    );
-                    indexer_config.starting_version
-                },
-            },
-        };
+        // This will allow a processor to have STARTING_VERSION undefined when deploying
+        indexer_config.starting_version =
+            version_env_var_or_default(STARTING_VERSION, indexer_config.starting_version);
+
+        // Verify and set the backfill range
+        indexer_config.backfill_start_version = version_env_var_or_default(
+            BACKFILL_START_VERSION,
+            indexer_config.backfill_start_version,
+        );
+        indexer_config.backfill_end_version =
+            version_env_var_or_default(BACKFILL_END_VERSION, indexer_config.backfill_end_version);
+        if let (Some(start_version), Some(end_version)) = (
+            indexer_config.backfill_start_version,
+            indexer_config.backfill_end_version,
+        ) {
+            if start_version > end_version {
+                return Err(Error::ConfigSanitizerFailed(
+                    "IndexerConfigOptimizer".to_string(),
+                    format!(
+                        "The backfill start version {} is after the backfill end version {}!",
+                        start_version, end_version
+                    ),
+                ));
+            }
+        }
+
+        // Verify the module address allowlist
+        for address in indexer_config.allowed_module_addresses.iter().flatten() {
+            if let Err(error) = AccountAddress::from_str(address) {
+                return Err(Error::ConfigSanitizerFailed(
+                    "IndexerConfigOptimizer".to_string(),
+                    format!("Invalid allowed module address {}: {}", address, error),
+                ));
+            }
+        }
+
+        // Verify the partitioned tables, whose names are used in the partitioning statements
+        for partition_config in indexer_config.partitioned_tables.iter().flatten() {
+            if partition_config.table.is_empty()
+                || !partition_config
+                    .table
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    "IndexerConfigOptimizer".to_string(),
+                    format!("Invalid partitioned table name {}!", partition_config.table),
+                ));
+            }
+            if partition_config.partition_versions == 0 {
+                return Err(Error::ConfigSanitizerFailed(
+                    "IndexerConfigOptimizer".to_string(),
+                    format!(
+                        "The partitions of table {} must hold at least one version!",
+                        partition_config.table
+                    ),
+                ));
+            }
+        }
 
         // Set appropriate defaults
         indexer_config.skip_migrations = indexer_config.skip_migrations.or(Some(false));
         indexer_config.check_chain_id = indexer_config.check_chain_id.or(Some(true));
+        indexer_config.reset_on_chain_change = indexer_config.reset_on_chain_change.or(Some(false));
+        indexer_config.dead_letter_failed_transactions = indexer_config
+            .dead_letter_failed_transactions
+            .or(Some(false));
+        indexer_config.reprocess_dead_letters =
+            indexer_config.reprocess_dead_letters.or(Some(false));
         indexer_config.batch_size = default_if_zero(
             indexer_config.batch_size.map(|v| v as u64),
             DEFAULT_BATCH_SIZE as u64,
@@ -189,6 +370,10 @@ impl ConfigOptimizer for IndexerConfig {
         )
         .map(|value| value as u8);
         indexer_config.emit_every = indexer_config.emit_every.or(Some(0));
+        indexer_config.balance_snapshot_interval_secs = default_if_zero(
+            indexer_config.balance_snapshot_interval_secs,
+            DEFAULT_BALANCE_SNAPSHOT_INTERVAL_SECS,
+        );
         indexer_config.gap_lookback_versions = env_var_or_default(
             GAP_LOOKBACK_VERSIONS,
             indexer_config.gap_lookback_versions.or(Some(1_500_000)),
@@ -208,6 +393,24 @@ fn default_if_zero(value: Option<u64>, default: u64) -> Option<u64> {
     }
 }
 
+/// Returns the version in the environment variable `env_var` if it is set and valid,
+/// otherwise returns `default`.
+fn version_env_var_or_default(env_var: &'static str, default: Option<u64>) -> Option<u64> {
+    match std::env::var(env_var).ok() {
+        None => default,
+        Some(version) => match version.parse::<u64>() {
+            Ok(version) => Some(version),
+            Err(error) => {
+                warn!(
+                    "Invalid {}: {}. Error: {:?}. Using {:?} instead.",
+                    env_var, version, error, default
+                );
+                default
+            },
+        },
+    }
+}
+
 /// Returns the value of the environment variable `env_var`
 /// if it is set, otherwise returns `default`.
 fn env_var_or_default<T: std::str::FromStr>(
@@ -36,6 +36,12 @@ pub struct IndexerGrpcConfig {
 
     /// Number of transactions returned in a single stream response
     pub output_batch_size: u16,
+
+    /// If set along with `tls_key_path`, the grpc server uses TLS with this PEM certificate
+    pub tls_cert_path: Option<String>,
+
+    /// If set along with `tls_cert_path`, the grpc server uses TLS with this PEM private key
+    pub tls_key_path: Option<String>,
 }
 
 // Reminder, #[serde(default)] on IndexerGrpcConfig means that the default values for
@@ -53,6 +59,8 @@ impl Default for IndexerGrpcConfig {
             processor_task_count: DEFAULT_PROCESSOR_TASK_COUNT,
             processor_batch_size: DEFAULT_PROCESSOR_BATCH_SIZE,
             output_batch_size: DEFAULT_OUTPUT_BATCH_SIZE,
+            tls_cert_path: None,
+            tls_key_path: None,
         }
     }
 }
@@ -75,6 +83,16 @@ impl ConfigSanitizer for IndexerGrpcConfig {
                 "storage.enable_indexer must be true if indexer_grpc.enabled is true".to_string(),
             ));
         }
+
+        if node_config.indexer_grpc.tls_cert_path.is_some()
+            != node_config.indexer_grpc.tls_key_path.is_some()
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "indexer_grpc.tls_cert_path and indexer_grpc.tls_key_path must be set together"
+                    .to_string(),
+            ));
+        }
         Ok(())
     }
 }
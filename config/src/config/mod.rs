@@ -4,6 +4,7 @@
 
 // All modules should be declared below
 mod api_config;
+mod api_key_config;
 mod base_config;
 mod config_optimizer;
 mod config_sanitizer;
@@ -30,9 +31,11 @@ mod secure_backend_config;
 mod state_sync_config;
 mod storage_config;
 mod utils;
+mod webhook_config;
 
 // All public usage statements should be declared below
 pub use api_config::*;
+pub use api_key_config::*;
 pub use base_config::*;
 pub use consensus_config::*;
 pub use error::*;
@@ -56,3 +59,4 @@ pub use safety_rules_config::*;
 pub use secure_backend_config::*;
 pub use state_sync_config::*;
 pub use storage_config::*;
+pub use webhook_config::*;
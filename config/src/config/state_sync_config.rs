@@ -104,10 +104,15 @@ pub struct StateSyncDriverConfig {
     pub enable_auto_bootstrapping: bool,
     /// The interval (ms) to refresh the storage summary
     pub fallback_to_output_syncing_secs: u64,
+    /// The time (ms) to wait before retrying to fetch the data of a corrupted ledger range
+    pub ledger_repair_retry_interval_ms: u64,
     /// The interval (ms) at which to check state sync progress
     pub progress_check_interval_ms: u64,
     /// The maximum time (secs) to wait for connections from peers before auto-bootstrapping
     pub max_connection_deadline_secs: u64,
+    /// The maximum number of failed attempts to repair a corrupted ledger range, see
+    /// `LedgerChecksumConfig`, before giving up
+    pub max_ledger_repair_attempts: u64,
     /// The maximum number of notifications to process per driver loop
     pub max_consecutive_stream_notifications: u64,
     /// The maximum number of stream timeouts allowed before termination
@@ -120,6 +125,9 @@ pub struct StateSyncDriverConfig {
     pub mempool_commit_ack_timeout_ms: u64,
     /// The version lag we'll tolerate before snapshot syncing
     pub num_versions_to_skip_snapshot_sync: u64,
+    /// Skip verifying the signatures of the transactions executed by state sync, since they are
+    /// covered by proofs verified against the ledger info, see `ChunkExecutorConfig`
+    pub trust_proof_verified_signatures: bool,
 }
 
 /// The default state sync driver config will be the one that gets (and keeps)
@@ -132,14 +140,17 @@ impl Default for StateSyncDriverConfig {
             continuous_syncing_mode: ContinuousSyncingMode::ExecuteTransactionsOrApplyOutputs,
             enable_auto_bootstrapping: false,
             fallback_to_output_syncing_secs: 180, // 3 minutes
+            ledger_repair_retry_interval_ms: 1000,
             progress_check_interval_ms: 50,
             max_connection_deadline_secs: 10,
+            max_ledger_repair_attempts: 30,
             max_consecutive_stream_notifications: 10,
             max_num_stream_timeouts: 12,
             max_pending_data_chunks: 100,
             max_stream_wait_time_ms: 5000,
             mempool_commit_ack_timeout_ms: 5000, // 5 seconds
             num_versions_to_skip_snapshot_sync: 100_000_000, // At 5k TPS, this allows a node to fail for about 6 hours.
+            trust_proof_verified_signatures: false,
         }
     }
 }
@@ -10,8 +10,10 @@ use aptos_logger::warn;
 use aptos_types::chain_id::ChainId;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, HashSet},
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::PathBuf,
+    str::FromStr,
 };
 
 // Lru cache will consume about 2G RAM based on this default value.
@@ -22,7 +24,7 @@ pub const BUFFERED_STATE_TARGET_ITEMS: usize = 100_000;
 /// Port selected RocksDB options for tuning underlying rocksdb instance of AptosDB.
 /// see <https://github.com/facebook/rocksdb/blob/master/include/rocksdb/options.h>
 /// for detailed explanations.
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct RocksdbConfig {
     /// Maximum number of files open by RocksDB at one time
@@ -37,6 +39,32 @@ pub struct RocksdbConfig {
     pub block_size: u64,
     /// Whether cache index and filter blocks into block cache.
     pub cache_index_and_filter_blocks: bool,
+    /// Compaction style of all column families
+    pub compaction_style: RocksdbCompactionStyle,
+    /// Compression of the column families not in `compression_per_cf`
+    pub compression: RocksdbCompression,
+    /// Compression of individual column families, by column family name
+    pub compression_per_cf: BTreeMap<String, RocksdbCompression>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RocksdbCompactionStyle {
+    Level,
+    Universal,
+    Fifo,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RocksdbCompression {
+    None,
+    Snappy,
+    Zlib,
+    Bz2,
+    Lz4,
+    Lz4hc,
+    Zstd,
 }
 
 impl Default for RocksdbConfig {
@@ -56,11 +84,14 @@ impl Default for RocksdbConfig {
             block_size: 4 * (1u64 << 10),
             // Whether cache index and filter blocks into block cache.
             cache_index_and_filter_blocks: false,
+            compaction_style: RocksdbCompactionStyle::Level,
+            compression: RocksdbCompression::Lz4,
+            compression_per_cf: BTreeMap::new(),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct RocksdbConfigs {
     // TODO(grao): Add RocksdbConfig for individual ledger DBs when necessary.
@@ -70,6 +101,12 @@ pub struct RocksdbConfigs {
     pub index_db_config: RocksdbConfig,
     // Note: Not ready for production use yet.
     pub enable_storage_sharding: bool,
+    /// Interval of reporting the properties of every column family (e.g. size, number of keys,
+    /// pending compaction bytes and amplification) as metrics. 0 disables reporting.
+    pub property_report_interval_ms: u64,
+    /// Places state kv DB shards on other paths than the DB root, e.g. to spread them across
+    /// disks. Shards not listed stay under the DB root. Requires `enable_storage_sharding`.
+    pub state_kv_shard_paths: Vec<StateKvShardPathConfig>,
 }
 
 impl Default for RocksdbConfigs {
@@ -83,8 +120,139 @@ impl Default for RocksdbConfigs {
                 ..Default::default()
             },
             enable_storage_sharding: false,
+            property_report_interval_ms: 10000,
+            state_kv_shard_paths: Vec::new(),
+        }
+    }
+}
+
+/// Number of shards of the state kv DB, i.e. shard ids are in `0..NUM_STATE_KV_SHARDS`.
+pub const NUM_STATE_KV_SHARDS: u8 = 16;
+
+/// A set of state kv DB shards placed on a path other than the DB root. The shards live in
+/// `<path>/state_kv_db/shard_<id>`, same as under the DB root.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StateKvShardPathConfig {
+    pub shards: Vec<u8>,
+    pub path: PathBuf,
+}
+
+impl RocksdbConfigs {
+    fn validate_state_kv_shard_paths(&self) -> Result<(), String> {
+        if self.state_kv_shard_paths.is_empty() {
+            return Ok(());
+        }
+        if !self.enable_storage_sharding {
+            return Err("state_kv_shard_paths requires enable_storage_sharding.".to_string());
+        }
+        let mut seen = HashSet::new();
+        for shard_path in &self.state_kv_shard_paths {
+            if !shard_path.path.is_absolute() {
+                return Err(format!(
+                    "state kv shard path {:?} must be absolute.",
+                    shard_path.path
+                ));
+            }
+            for shard_id in &shard_path.shards {
+                if *shard_id >= NUM_STATE_KV_SHARDS {
+                    return Err(format!("Invalid state kv shard id {}.", shard_id));
+                }
+                if !seen.insert(*shard_id) {
+                    return Err(format!(
+                        "State kv shard {} is assigned to multiple paths.",
+                        shard_id
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses `<shards>=<path>`, where `<shards>` is a comma separated list of shard ids or ranges of
+/// them, e.g. `0-7=/mnt/disk1` or `8,10-11=/mnt/disk2`.
+impl FromStr for StateKvShardPathConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (shards_str, path) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow::format_err!("Expecting <shards>=<path>, got {}.", s))?;
+        let mut shards = Vec::new();
+        for part in shards_str.split(',') {
+            match part.split_once('-') {
+                Some((first, last)) => {
+                    shards.extend(first.trim().parse::<u8>()?..=last.trim().parse::<u8>()?)
+                },
+                None => shards.push(part.trim().parse::<u8>()?),
+            }
+        }
+        Ok(Self {
+            shards,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+/// Overrides of selected RocksDB options of an AptosDB instance, on top of its config in
+/// `rocksdb_configs`. Options that are not set keep the value from `rocksdb_configs`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RocksdbOverride {
+    pub block_cache_size: Option<u64>,
+    pub compaction_style: Option<RocksdbCompactionStyle>,
+    /// Compression of individual column families, by column family name
+    pub compression: BTreeMap<String, RocksdbCompression>,
+    pub max_background_jobs: Option<i32>,
+}
+
+impl RocksdbOverride {
+    fn apply(&self, config: &mut RocksdbConfig) {
+        if let Some(block_cache_size) = self.block_cache_size {
+            config.block_cache_size = block_cache_size;
+        }
+        if let Some(compaction_style) = self.compaction_style {
+            config.compaction_style = compaction_style;
+        }
+        config.compression_per_cf.extend(
+            self.compression
+                .iter()
+                .map(|(cf_name, compression)| (cf_name.clone(), *compression)),
+        );
+        if let Some(max_background_jobs) = self.max_background_jobs {
+            config.max_background_jobs = max_background_jobs;
         }
     }
+
+    fn validate(&self, db_name: &str) -> Result<(), String> {
+        if self.block_cache_size == Some(0) {
+            return Err(format!("{}: block_cache_size must be positive.", db_name));
+        }
+        if matches!(self.max_background_jobs, Some(jobs) if jobs <= 0) {
+            return Err(format!(
+                "{}: max_background_jobs must be positive.",
+                db_name
+            ));
+        }
+        if let Some(cf_name) = self.compression.keys().find(|cf_name| cf_name.is_empty()) {
+            return Err(format!(
+                "{}: invalid column family name {:?} in compression.",
+                db_name, cf_name
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Per AptosDB instance overrides of the RocksDB options. The column families in `compression`
+/// are checked against the ones of the instance when the DB is opened.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RocksdbOverrides {
+    pub ledger_db: RocksdbOverride,
+    pub state_merkle_db: RocksdbOverride,
+    pub state_kv_db: RocksdbOverride,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -109,10 +277,32 @@ pub struct StorageConfig {
     pub max_num_nodes_per_lru_cache_shard: usize,
     /// Rocksdb-specific configurations
     pub rocksdb_configs: RocksdbConfigs,
+    /// Overrides of selected Rocksdb options, see `rocksdb_configs_with_overrides()`
+    pub rocksdb_overrides: RocksdbOverrides,
     /// Try to enable the internal indexer. The indexer expects to have seen all transactions
     /// since genesis. To recover operation after data loss, or to bootstrap a node in fast sync
     /// mode, the indexer db needs to be copied in from another node.
     pub enable_indexer: bool,
+    /// Event fields indexed by the internal indexer, to look up the events of a type by the value
+    /// of one of their fields (see `DbReader::get_events_by_field`). Requires `enable_indexer`.
+    pub event_field_indices: Vec<EventFieldIndexConfig>,
+    /// Indexes the transactions signed by or touching each account in the internal indexer (see
+    /// `DbReader::get_account_transaction_versions`). Requires `enable_indexer`.
+    pub enable_account_transaction_index: bool,
+    /// Moves old ledger data to a cold storage tier
+    pub cold_tier_config: ColdTierConfig,
+    /// Coalesces small commits, e.g. of state sync catching up, into fewer disk syncs
+    pub group_commit_config: GroupCommitConfig,
+    /// Detects and repairs corrupted ledger data
+    pub ledger_checksum_config: LedgerChecksumConfig,
+    /// Maintains the number of state items and bytes attributed to each account, module and table
+    /// (see `DbReader::get_storage_footprint`). Enabling it on an existing DB scans the latest
+    /// state snapshot once.
+    pub enable_storage_footprint: bool,
+    /// URL or local directory of a state snapshot exported for bootstrapping (see
+    /// `aptos-db-tool export-state --for-bootstrap`). If set and the DB is empty, the DB is
+    /// initialized from the snapshot, after verifying it against the waypoint.
+    pub bootstrap_from_snapshot: Option<String>,
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -188,6 +378,92 @@ impl From<EpochSnapshotPrunerConfig> for StateMerklePrunerConfig {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventFieldIndexConfig {
+    /// The event struct type, e.g. `0x1::coin::WithdrawEvent`.
+    pub event_type: String,
+    /// The name of the indexed field. Only fields of primitive types (addresses, integers,
+    /// booleans and byte vectors) can be indexed.
+    pub field: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColdTierConfig {
+    /// Boolean to enable/disable the cold tier. When enabled, the transactions, events and write
+    /// sets of old versions are moved to the cold storage and served from there, while everything
+    /// else (transaction infos, accumulators, indices) stays on local disk.
+    pub enable: bool,
+    /// Number of latest versions whose transactions, events and write sets are kept on local disk.
+    pub hot_window: u64,
+    /// Number of versions stored together in a single object of the cold storage. Must not be
+    /// changed once data has been moved to the cold tier.
+    pub segment_size: u64,
+    /// Where the cold tier is stored.
+    pub storage: ColdStorageConfig,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GroupCommitConfig {
+    /// Boolean to enable/disable group commit. When enabled, commits without a ledger info are
+    /// written to disk without syncing, and synced together once the pending ones reach the limits
+    /// below. Until then, they are lost on a machine crash and the node syncs them again.
+    pub enable: bool,
+    /// Maximum time a commit stays pending before being synced.
+    pub max_latency_ms: u64,
+    /// Pending commits are synced once they contain this many transactions.
+    pub max_pending_transactions: usize,
+}
+
+impl Default for GroupCommitConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            max_latency_ms: 200,
+            max_pending_transactions: 10_000,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LedgerChecksumConfig {
+    /// Boolean to enable/disable the ledger checksums. When enabled, a checksum of the
+    /// transactions, events, write sets and transaction infos of each commit is stored, and the
+    /// latest commits are verified against them when the node starts. Corrupted versions are then
+    /// fetched again from the state sync peers, instead of requiring a full resync.
+    pub enable: bool,
+    /// Number of latest versions verified when the node starts. 0 verifies all the versions that
+    /// have checksums.
+    pub verify_window_on_startup: u64,
+}
+
+impl Default for LedgerChecksumConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            verify_window_on_startup: 1_000_000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColdStorageConfig {
+    /// A local directory, e.g. a mount point of a network file system.
+    LocalDir(PathBuf),
+    /// Shell commands talking to an object store (e.g. S3 or GCS). The object name is passed in
+    /// the `$NAME` environment variable. `put_command` reads the object from stdin, e.g.
+    /// `aws s3 cp - s3://bucket/$NAME`, and `get_command` writes it to stdout, e.g.
+    /// `aws s3 cp s3://bucket/$NAME -`.
+    Command {
+        put_command: String,
+        get_command: String,
+    },
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, Default)]
 #[serde(default, deny_unknown_fields)]
 pub struct PrunerConfig {
@@ -209,6 +485,19 @@ impl Default for LedgerPrunerConfig {
     }
 }
 
+impl Default for ColdTierConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            // Keeps roughly the same amount of data on local disk as the default ledger prune
+            // window.
+            hot_window: 150_000_000,
+            segment_size: 100_000,
+            storage: ColdStorageConfig::LocalDir(PathBuf::from("/opt/aptos/data/cold_tier")),
+        }
+    }
+}
+
 impl Default for StateMerklePrunerConfig {
     fn default() -> Self {
         StateMerklePrunerConfig {
@@ -258,7 +547,15 @@ impl Default for StorageConfig {
             storage_pruner_config: PrunerConfig::default(),
             data_dir: PathBuf::from("/opt/aptos/data"),
             rocksdb_configs: RocksdbConfigs::default(),
+            rocksdb_overrides: RocksdbOverrides::default(),
             enable_indexer: false,
+            event_field_indices: Vec::new(),
+            enable_account_transaction_index: false,
+            cold_tier_config: ColdTierConfig::default(),
+            group_commit_config: GroupCommitConfig::default(),
+            ledger_checksum_config: LedgerChecksumConfig::default(),
+            enable_storage_footprint: false,
+            bootstrap_from_snapshot: None,
             buffered_state_target_items: BUFFERED_STATE_TARGET_ITEMS,
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         }
@@ -278,6 +575,22 @@ impl StorageConfig {
         self.data_dir = data_dir;
     }
 
+    /// The Rocksdb configs to open AptosDB with, i.e. `rocksdb_configs` with `rocksdb_overrides`
+    /// applied.
+    pub fn rocksdb_configs_with_overrides(&self) -> RocksdbConfigs {
+        let mut rocksdb_configs = self.rocksdb_configs.clone();
+        self.rocksdb_overrides
+            .ledger_db
+            .apply(&mut rocksdb_configs.ledger_db_config);
+        self.rocksdb_overrides
+            .state_merkle_db
+            .apply(&mut rocksdb_configs.state_merkle_db_config);
+        self.rocksdb_overrides
+            .state_kv_db
+            .apply(&mut rocksdb_configs.state_kv_db_config);
+        rocksdb_configs
+    }
+
     pub fn randomize_ports(&mut self) {
         self.backup_service_address
             .set_port(utils::get_available_port());
@@ -331,6 +644,58 @@ impl ConfigSanitizer for StorageConfig {
                 "user_pruning_window_offset is larger than the ledger prune window, the API will refuse to return any data.".to_string(),
             ));
         }
+        if !config.event_field_indices.is_empty() && !config.enable_indexer {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "event_field_indices requires enable_indexer.".to_string(),
+            ));
+        }
+        if config.enable_account_transaction_index && !config.enable_indexer {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "enable_account_transaction_index requires enable_indexer.".to_string(),
+            ));
+        }
+        if config.cold_tier_config.enable {
+            if config.storage_pruner_config.ledger_pruner_config.enable {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "The cold tier requires the ledger pruner to be disabled.".to_string(),
+                ));
+            }
+            if config.cold_tier_config.segment_size == 0 {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "The cold tier segment_size must be positive.".to_string(),
+                ));
+            }
+        }
+        if config.group_commit_config.enable
+            && (config.group_commit_config.max_latency_ms == 0
+                || config.group_commit_config.max_pending_transactions == 0)
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The group commit max_latency_ms and max_pending_transactions must be positive."
+                    .to_string(),
+            ));
+        }
+        config
+            .rocksdb_configs
+            .validate_state_kv_shard_paths()
+            .map_err(|error| Error::ConfigSanitizerFailed(sanitizer_name.clone(), error))?;
+        for (db_name, rocksdb_override) in [
+            ("ledger_db", &config.rocksdb_overrides.ledger_db),
+            ("state_merkle_db", &config.rocksdb_overrides.state_merkle_db),
+            ("state_kv_db", &config.rocksdb_overrides.state_kv_db),
+        ] {
+            rocksdb_override.validate(db_name).map_err(|error| {
+                Error::ConfigSanitizerFailed(
+                    sanitizer_name.clone(),
+                    format!("Invalid rocksdb_overrides, {}", error),
+                )
+            })?;
+        }
 
         Ok(())
     }
@@ -338,7 +703,12 @@ impl ConfigSanitizer for StorageConfig {
 
 #[cfg(test)]
 mod test {
-    use crate::config::PrunerConfig;
+    use crate::config::{
+        config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+        PrunerConfig, RocksdbCompactionStyle, RocksdbCompression, RocksdbConfigs, RocksdbOverride,
+        RocksdbOverrides, StateKvShardPathConfig, StorageConfig,
+    };
+    use aptos_types::chain_id::ChainId;
 
     #[test]
     pub fn test_default_prune_window() {
@@ -349,4 +719,96 @@ mod test {
         assert!(config.state_merkle_pruner_config.prune_window >= 100_000);
         assert!(config.epoch_snapshot_pruner_config.prune_window > 50_000_000);
     }
+
+    #[test]
+    fn test_rocksdb_overrides() {
+        let storage_config = StorageConfig {
+            rocksdb_overrides: RocksdbOverrides {
+                state_merkle_db: RocksdbOverride {
+                    block_cache_size: Some(1 << 30),
+                    compaction_style: Some(RocksdbCompactionStyle::Universal),
+                    compression: [(
+                        "jellyfish_merkle_node".to_string(),
+                        RocksdbCompression::Zstd,
+                    )]
+                    .into_iter()
+                    .collect(),
+                    max_background_jobs: Some(4),
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let rocksdb_configs = storage_config.rocksdb_configs_with_overrides();
+        assert_eq!(
+            rocksdb_configs.ledger_db_config,
+            storage_config.rocksdb_configs.ledger_db_config
+        );
+        let state_merkle_db_config = rocksdb_configs.state_merkle_db_config;
+        assert_eq!(state_merkle_db_config.block_cache_size, 1 << 30);
+        assert_eq!(
+            state_merkle_db_config.compaction_style,
+            RocksdbCompactionStyle::Universal
+        );
+        assert_eq!(
+            state_merkle_db_config
+                .compression_per_cf
+                .get("jellyfish_merkle_node"),
+            Some(&RocksdbCompression::Zstd)
+        );
+        assert_eq!(state_merkle_db_config.max_background_jobs, 4);
+    }
+
+    #[test]
+    fn test_sanitize_invalid_rocksdb_overrides() {
+        let node_config = NodeConfig {
+            storage: StorageConfig {
+                rocksdb_overrides: RocksdbOverrides {
+                    ledger_db: RocksdbOverride {
+                        max_background_jobs: Some(0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let error = StorageConfig::sanitize(&node_config, NodeType::Validator, ChainId::testnet())
+            .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_state_kv_shard_paths() {
+        let sanitize = |enable_storage_sharding, shard_paths: &[&str]| {
+            let node_config = NodeConfig {
+                storage: StorageConfig {
+                    rocksdb_configs: RocksdbConfigs {
+                        enable_storage_sharding,
+                        state_kv_shard_paths: shard_paths
+                            .iter()
+                            .map(|s| s.parse::<StateKvShardPathConfig>().unwrap())
+                            .collect(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            StorageConfig::sanitize(&node_config, NodeType::Validator, ChainId::testnet())
+        };
+
+        assert!(sanitize(true, &["0-7=/mnt/disk1", "8,10-11=/mnt/disk2"]).is_ok());
+        // Sharding is not enabled.
+        assert!(sanitize(false, &["0-7=/mnt/disk1"]).is_err());
+        // Relative path.
+        assert!(sanitize(true, &["0-7=disk1"]).is_err());
+        // Invalid shard id.
+        assert!(sanitize(true, &["15-16=/mnt/disk1"]).is_err());
+        // Shard 7 on two paths.
+        assert!(sanitize(true, &["0-7=/mnt/disk1", "7=/mnt/disk2"]).is_err());
+    }
 }
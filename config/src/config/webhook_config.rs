@@ -0,0 +1,157 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+};
+use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const DEFAULT_MAX_RETRIES: u32 = 5;
+const DEFAULT_INITIAL_RETRY_DELAY_MS: u64 = 500;
+const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 10_000;
+const DEFAULT_MAX_PENDING_DELIVERIES: usize = 1_000;
+
+/// A URL called with the committed transactions touching its addresses or emitting its events
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// Name of the webhook in the metrics and the logs
+    pub name: String,
+    pub url: url::Url,
+    /// Secret the body of every call is signed with, so that the receiver can check it comes
+    /// from the node
+    pub secret: String,
+    /// The transactions sent by these addresses, or changing their state or emitting their
+    /// events
+    #[serde(default)]
+    pub addresses: Vec<AccountAddress>,
+    /// The transactions emitting events of these types, e.g. `0x1::coin::DepositEvent`
+    #[serde(default)]
+    pub event_types: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhooksConfig {
+    /// Enables calling the webhooks with the newly committed transactions
+    pub enabled: bool,
+    pub webhooks: Vec<WebhookConfig>,
+    /// Maximum number of times a failed call is retried before the transaction is dropped
+    pub max_retries: u32,
+    /// Delay before the first retry of a call, doubled at every retry
+    pub initial_retry_delay_ms: u64,
+    pub request_timeout_ms: u64,
+    /// Maximum number of transactions waiting to be delivered to a webhook. The transactions
+    /// over it are dropped, so that a slow webhook doesn't hold up the node.
+    pub max_pending_deliveries: usize,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> WebhooksConfig {
+        WebhooksConfig {
+            enabled: false,
+            webhooks: vec![],
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_retry_delay_ms: DEFAULT_INITIAL_RETRY_DELAY_MS,
+            request_timeout_ms: DEFAULT_REQUEST_TIMEOUT_MS,
+            max_pending_deliveries: DEFAULT_MAX_PENDING_DELIVERIES,
+        }
+    }
+}
+
+impl ConfigSanitizer for WebhooksConfig {
+    fn sanitize(
+        node_config: &NodeConfig,
+        _node_type: NodeType,
+        _chain_id: ChainId,
+    ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let webhooks_config = &node_config.api.webhooks;
+        if !webhooks_config.enabled {
+            return Ok(());
+        }
+
+        let mut names = HashSet::new();
+        for webhook in &webhooks_config.webhooks {
+            if !names.insert(&webhook.name) {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!("Webhook {} is configured more than once", webhook.name),
+                ));
+            }
+            if webhook.addresses.is_empty() && webhook.event_types.is_empty() {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!(
+                        "Webhook {} needs at least one address or event type",
+                        webhook.name
+                    ),
+                ));
+            }
+            if webhook.secret.is_empty() {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!("Webhook {} needs a secret", webhook.name),
+                ));
+            }
+        }
+        if webhooks_config.max_pending_deliveries == 0 {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "max_pending_deliveries must be greater than 0!".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiConfig;
+
+    fn webhook(name: &str, addresses: Vec<AccountAddress>, secret: &str) -> WebhookConfig {
+        WebhookConfig {
+            name: name.to_string(),
+            url: "http://localhost:8000/webhook".parse().unwrap(),
+            secret: secret.to_string(),
+            addresses,
+            event_types: vec![],
+        }
+    }
+
+    fn sanitize(webhooks: Vec<WebhookConfig>) -> Result<(), Error> {
+        let node_config = NodeConfig {
+            api: ApiConfig {
+                webhooks: WebhooksConfig {
+                    enabled: true,
+                    webhooks,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        WebhooksConfig::sanitize(&node_config, NodeType::PublicFullnode, ChainId::mainnet())
+    }
+
+    #[test]
+    fn test_sanitize_webhooks() {
+        let addresses = vec![AccountAddress::ONE];
+        sanitize(vec![
+            webhook("a", addresses.clone(), "secret"),
+            webhook("b", addresses.clone(), "secret"),
+        ])
+        .unwrap();
+        assert!(sanitize(vec![
+            webhook("a", addresses.clone(), "secret"),
+            webhook("a", addresses.clone(), "secret"),
+        ])
+        .is_err());
+        assert!(sanitize(vec![webhook("a", vec![], "secret")]).is_err());
+        assert!(sanitize(vec![webhook("a", addresses, "")]).is_err());
+    }
+}
@@ -402,6 +402,10 @@ async fn test_commit_sync_race() {
             Ok(())
         }
 
+        fn prune_speculative_state(&self, _before_block_id: HashValue) -> ExecutorResult<()> {
+            Ok(())
+        }
+
         fn finish(&self) {}
     }
 
@@ -28,7 +28,7 @@ use aptos_api_types::{
     mime_types::{BCS, BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, JSON},
     AptosError, BcsBlock, Block, GasEstimation, HexEncodedBytes, IndexResponse, MoveModuleId,
     TransactionData, TransactionOnChainData, TransactionsBatchSubmissionResult, UserTransaction,
-    VersionedEvent, ViewRequest,
+    VersionedEvent, ViewRequest, ViewResponseBcs,
 };
 use aptos_crypto::HashValue;
 use aptos_logger::{debug, info, sample, sample::SampleRate};
@@ -307,6 +307,21 @@ impl Client {
         self.json(response).await
     }
 
+    /// Returns the BCS encoded values of a view function with their types
+    pub async fn view_bcs(
+        &self,
+        request: &ViewRequest,
+        version: Option<u64>,
+    ) -> AptosResult<Response<ViewResponseBcs>> {
+        let mut url = self.build_path("view")?;
+        if let Some(version) = version {
+            url.set_query(Some(format!("ledger_version={}", version).as_str()));
+        }
+
+        let response = self.post_bcs(url, serde_json::to_value(request)?).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn simulate(
         &self,
         txn: &SignedTransaction,
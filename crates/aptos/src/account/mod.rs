@@ -12,6 +12,7 @@ pub mod key_rotation;
 pub mod list;
 pub mod multisig_account;
 pub mod transfer;
+pub mod transfer_batch;
 
 /// Tool for interacting with accounts
 ///
@@ -27,6 +28,7 @@ pub enum AccountTool {
     LookupAddress(key_rotation::LookupAddress),
     RotateKey(key_rotation::RotateKey),
     Transfer(transfer::TransferCoins),
+    TransferBatch(transfer_batch::TransferBatch),
 }
 
 impl AccountTool {
@@ -40,6 +42,7 @@ impl AccountTool {
             AccountTool::LookupAddress(tool) => tool.execute_serialized().await,
             AccountTool::RotateKey(tool) => tool.execute_serialized().await,
             AccountTool::Transfer(tool) => tool.execute_serialized().await,
+            AccountTool::TransferBatch(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -50,9 +53,12 @@ pub enum MultisigAccountTool {
     Approve(multisig_account::Approve),
     Create(multisig_account::Create),
     CreateTransaction(multisig_account::CreateTransaction),
+    DecodePayload(multisig_account::DecodePayload),
     Execute(multisig_account::Execute),
     ExecuteReject(multisig_account::ExecuteReject),
     ExecuteWithPayload(multisig_account::ExecuteWithPayload),
+    ExportPayload(multisig_account::ExportPayload),
+    ListPending(multisig_account::ListPending),
     Reject(multisig_account::Reject),
     VerifyProposal(multisig_account::VerifyProposal),
 }
@@ -63,9 +69,12 @@ impl MultisigAccountTool {
             MultisigAccountTool::Approve(tool) => tool.execute_serialized().await,
             MultisigAccountTool::Create(tool) => tool.execute_serialized().await,
             MultisigAccountTool::CreateTransaction(tool) => tool.execute_serialized().await,
+            MultisigAccountTool::DecodePayload(tool) => tool.execute_serialized().await,
             MultisigAccountTool::Execute(tool) => tool.execute_serialized().await,
             MultisigAccountTool::ExecuteReject(tool) => tool.execute_serialized().await,
             MultisigAccountTool::ExecuteWithPayload(tool) => tool.execute_serialized().await,
+            MultisigAccountTool::ExportPayload(tool) => tool.execute_serialized().await,
+            MultisigAccountTool::ListPending(tool) => tool.execute_serialized().await,
             MultisigAccountTool::Reject(tool) => tool.execute_serialized().await,
             MultisigAccountTool::VerifyProposal(tool) => tool.execute_serialized().await,
         }
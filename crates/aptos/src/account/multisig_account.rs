@@ -4,9 +4,9 @@
 use crate::common::{
     types::{
         CliCommand, CliError, CliTypedResult, EntryFunctionArguments, MultisigAccount,
-        MultisigAccountWithSequenceNumber, TransactionOptions, TransactionSummary,
+        MultisigAccountWithSequenceNumber, SaveFile, TransactionOptions, TransactionSummary,
     },
-    utils::view_json_option_str,
+    utils::{read_from_file, view_json_option_str},
 };
 use aptos_cached_packages::aptos_stdlib;
 use aptos_crypto::HashValue;
@@ -26,9 +26,20 @@ use clap::Parser;
 use once_cell::sync::Lazy;
 use serde::Serialize;
 use serde_json::json;
+use std::{path::PathBuf, str::FromStr};
 
 static GET_TRANSACTION_ENTRY_FUNCTION: Lazy<EntryFunctionId> =
     Lazy::new(|| "0x1::multisig_account::get_transaction".parse().unwrap());
+static GET_PENDING_TRANSACTIONS_ENTRY_FUNCTION: Lazy<EntryFunctionId> = Lazy::new(|| {
+    "0x1::multisig_account::get_pending_transactions"
+        .parse()
+        .unwrap()
+});
+static LAST_RESOLVED_SEQUENCE_NUMBER_ENTRY_FUNCTION: Lazy<EntryFunctionId> = Lazy::new(|| {
+    "0x1::multisig_account::last_resolved_sequence_number"
+        .parse()
+        .unwrap()
+});
 
 /// Create a new multisig account (v2) on-chain.
 ///
@@ -369,3 +380,222 @@ impl CliCommand<TransactionSummary> for ExecuteReject {
             .map(|inner| inner.into())
     }
 }
+
+/// A multisig transaction payload decoded for review
+#[derive(Clone, Debug, Serialize)]
+pub struct DecodedPayload {
+    /// Hash of the payload, as stored on-chain when only the hash is proposed
+    pub payload_hash: String,
+    pub function: String,
+    pub type_args: Vec<String>,
+    /// The BCS encoded arguments of the function
+    pub args: Vec<HexEncodedBytes>,
+}
+
+impl TryFrom<&[u8]> for DecodedPayload {
+    type Error = CliError;
+
+    fn try_from(payload_bytes: &[u8]) -> Result<Self, Self::Error> {
+        let MultisigTransactionPayload::EntryFunction(entry_function) =
+            bcs::from_bytes::<MultisigTransactionPayload>(payload_bytes)
+                .map_err(|err| CliError::BCS("MultisigTransactionPayload", err))?;
+        Ok(DecodedPayload {
+            payload_hash: HashValue::sha3_256_of(payload_bytes).to_hex_literal(),
+            function: format!(
+                "{}::{}",
+                entry_function.module().short_str_lossless(),
+                entry_function.function()
+            ),
+            type_args: entry_function
+                .ty_args()
+                .iter()
+                .map(|type_arg| type_arg.to_string())
+                .collect(),
+            args: entry_function
+                .args()
+                .iter()
+                .map(|arg| HexEncodedBytes::from(arg.clone()))
+                .collect(),
+        })
+    }
+}
+
+/// Save the payload of a multisig transaction to a file.
+///
+/// The file holds the BCS encoded payload as hex, the same bytes that are stored on-chain with the
+/// proposal. It can be handed to the co-signers on air-gapped machines, who can review it with
+/// DecodePayload and compare its hash with the one of the proposal before approving it.
+#[derive(Debug, Parser)]
+pub struct ExportPayload {
+    #[clap(flatten)]
+    pub(crate) entry_function_args: EntryFunctionArguments,
+    #[clap(flatten)]
+    pub(crate) save_file: SaveFile,
+}
+
+#[async_trait]
+impl CliCommand<DecodedPayload> for ExportPayload {
+    fn command_name(&self) -> &'static str {
+        "ExportPayloadMultisig"
+    }
+
+    async fn execute(self) -> CliTypedResult<DecodedPayload> {
+        self.save_file.check_file()?;
+        let payload_bytes =
+            to_bytes::<MultisigTransactionPayload>(&self.entry_function_args.try_into()?)?;
+        self.save_file.save_to_file(
+            "Multisig transaction payload",
+            HexEncodedBytes::from(payload_bytes.clone())
+                .to_string()
+                .as_bytes(),
+        )?;
+        DecodedPayload::try_from(payload_bytes.as_slice())
+    }
+}
+
+/// Decode the payload of a multisig transaction saved to a file.
+///
+/// This doesn't connect to the network, so that the co-signers can review the payload of a
+/// proposal on an air-gapped machine.
+#[derive(Debug, Parser)]
+pub struct DecodePayload {
+    /// File with the hex encoded payload, as saved by ExportPayload
+    #[clap(long, value_parser)]
+    pub(crate) payload_file: PathBuf,
+}
+
+#[async_trait]
+impl CliCommand<DecodedPayload> for DecodePayload {
+    fn command_name(&self) -> &'static str {
+        "DecodePayloadMultisig"
+    }
+
+    async fn execute(self) -> CliTypedResult<DecodedPayload> {
+        let payload_hex = String::from_utf8(read_from_file(&self.payload_file)?)?;
+        let payload_bytes = payload_hex.trim().parse::<HexEncodedBytes>()?;
+        DecodedPayload::try_from(payload_bytes.inner())
+    }
+}
+
+/// A multisig transaction waiting for approvals or rejections
+#[derive(Clone, Debug, Serialize)]
+pub struct PendingTransaction {
+    pub sequence_number: u64,
+    pub creator: AccountAddress,
+    pub creation_time_secs: u64,
+    /// The decoded payload, if the full payload is stored on-chain
+    pub payload: Option<DecodedPayload>,
+    pub payload_hash: Option<String>,
+    pub approvals: Vec<AccountAddress>,
+    pub rejections: Vec<AccountAddress>,
+}
+
+fn parse_view_field<T: FromStr>(value: &serde_json::Value, field: &str) -> CliTypedResult<T> {
+    value[field]
+        .as_str()
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| {
+            CliError::UnexpectedError(format!(
+                "Invalid {} of the multisig transaction: {}",
+                field, value[field]
+            ))
+        })
+}
+
+impl PendingTransaction {
+    /// Parses the `MultisigTransaction` returned by the view functions
+    fn from_view(sequence_number: u64, transaction: &serde_json::Value) -> CliTypedResult<Self> {
+        let payload = view_json_option_str(&transaction["payload"])?
+            .map(|payload| DecodedPayload::try_from(payload.parse::<HexEncodedBytes>()?.inner()))
+            .transpose()?;
+        let (mut approvals, mut rejections) = (vec![], vec![]);
+        let votes = transaction["votes"]["data"].as_array().ok_or_else(|| {
+            CliError::UnexpectedError(format!(
+                "Invalid votes of the multisig transaction: {}",
+                transaction["votes"]
+            ))
+        })?;
+        for vote in votes {
+            let owner = parse_view_field(vote, "key")?;
+            match vote["value"].as_bool() {
+                Some(true) => approvals.push(owner),
+                Some(false) => rejections.push(owner),
+                None => {
+                    return Err(CliError::UnexpectedError(format!(
+                        "Invalid vote of the multisig transaction: {}",
+                        vote
+                    )))
+                },
+            }
+        }
+        Ok(PendingTransaction {
+            sequence_number,
+            creator: parse_view_field(transaction, "creator")?,
+            creation_time_secs: parse_view_field(transaction, "creation_time_secs")?,
+            payload_hash: match &payload {
+                Some(payload) => Some(payload.payload_hash.clone()),
+                None => view_json_option_str(&transaction["payload_hash"])?,
+            },
+            payload,
+            approvals,
+            rejections,
+        })
+    }
+}
+
+/// List the pending transactions of a multisig account.
+///
+/// The full payloads stored on-chain are decoded, along with the approvals and rejections of every
+/// transaction, ordered by sequence number. The next transaction to execute is the first one.
+#[derive(Debug, Parser)]
+pub struct ListPending {
+    #[clap(flatten)]
+    pub(crate) multisig_account: MultisigAccount,
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<PendingTransaction>> for ListPending {
+    fn command_name(&self) -> &'static str {
+        "ListPendingMultisig"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<PendingTransaction>> {
+        let multisig_address =
+            serde_json::Value::String(String::from(&self.multisig_account.multisig_address));
+        let last_resolved_sequence_number: u64 = self
+            .txn_options
+            .view(ViewRequest {
+                function: LAST_RESOLVED_SEQUENCE_NUMBER_ENTRY_FUNCTION.clone(),
+                type_arguments: vec![],
+                arguments: vec![multisig_address.clone()],
+            })
+            .await?[0]
+            .as_str()
+            .and_then(|sequence_number| sequence_number.parse().ok())
+            .ok_or_else(|| {
+                CliError::UnexpectedError(
+                    "Invalid last resolved sequence number of the multisig account".to_string(),
+                )
+            })?;
+        let pending_transactions = self
+            .txn_options
+            .view(ViewRequest {
+                function: GET_PENDING_TRANSACTIONS_ENTRY_FUNCTION.clone(),
+                type_arguments: vec![],
+                arguments: vec![multisig_address],
+            })
+            .await?;
+        let pending_transactions = pending_transactions[0]
+            .as_array()
+            .ok_or_else(|| CliError::UnexpectedError("Invalid pending transactions".to_string()))?;
+        pending_transactions
+            .iter()
+            .zip(last_resolved_sequence_number + 1..)
+            .map(|(transaction, sequence_number)| {
+                PendingTransaction::from_view(sequence_number, transaction)
+            })
+            .collect()
+    }
+}
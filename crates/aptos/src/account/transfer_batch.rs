@@ -0,0 +1,604 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{load_account_arg, CliCommand, CliError, CliTypedResult, TransactionOptions},
+    utils::{get_account_with_state, prompt_yes_with_override},
+};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::{ed25519::Ed25519Signature, HashValue, PrivateKey};
+use aptos_global_constants::adjust_gas_headroom;
+use aptos_rest_client::{error::RestError, Client};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{EntryFunction, RawTransaction, SignedTransaction, TransactionPayload},
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_core_types::{
+    ident_str,
+    language_storage::{ModuleId, StructTag, TypeTag},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const APTOS_COIN: &str = "0x1::aptos_coin::AptosCoin";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    Success,
+    Failed,
+    /// The transaction was submitted, but it isn't known yet whether it was committed
+    Pending,
+    /// The transfer succeeded in a previous run
+    AlreadyTransferred,
+    /// The transfer was only simulated
+    Simulated,
+}
+
+/// The result of the transfer of a row of the CSV file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransferResult {
+    /// The row of the transfer in the CSV file, starting at 1 after the header
+    pub row: usize,
+    pub recipient: AccountAddress,
+    pub amount: u64,
+    pub status: TransferStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<HashValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_timestamp_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TransferResult {
+    fn new(payout: &Payout, status: TransferStatus) -> Self {
+        Self {
+            row: payout.row,
+            recipient: payout.recipient,
+            amount: payout.amount,
+            status,
+            transaction_hash: None,
+            expiration_timestamp_secs: None,
+            version: None,
+            gas_used: None,
+            error: None,
+        }
+    }
+
+    fn with_error(mut self, error: impl ToString) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+
+    fn with_transaction(mut self, txn: &SignedTransaction) -> Self {
+        self.transaction_hash = Some(txn.clone().committed_hash());
+        self.expiration_timestamp_secs = Some(txn.expiration_timestamp_secs());
+        self
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchTransferSummary {
+    pub sender: AccountAddress,
+    pub results_file: PathBuf,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub pending: usize,
+    pub results: Vec<TransferResult>,
+}
+
+/// A row of the CSV file
+struct Payout {
+    row: usize,
+    recipient: AccountAddress,
+    amount: u64,
+}
+
+#[derive(Deserialize)]
+struct PayoutRecord {
+    recipient: String,
+    amount: u64,
+}
+
+fn read_payouts(csv_file: &Path) -> CliTypedResult<Vec<Payout>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(csv_file)
+        .map_err(|err| {
+            CliError::UnableToReadFile(csv_file.display().to_string(), err.to_string())
+        })?;
+    let mut payouts = vec![];
+    for (index, record) in reader.deserialize::<PayoutRecord>().enumerate() {
+        let row = index + 1;
+        let record = record
+            .map_err(|err| CliError::UnableToParse("CSV file", format!("Row {}: {}", row, err)))?;
+        let recipient = load_account_arg(&record.recipient)
+            .map_err(|err| CliError::UnableToParse("CSV file", format!("Row {}: {}", row, err)))?;
+        payouts.push(Payout {
+            row,
+            recipient,
+            amount: record.amount,
+        });
+    }
+    Ok(payouts)
+}
+
+/// The results of the previous runs, the last result of every row
+fn read_results(results_file: &Path) -> CliTypedResult<BTreeMap<usize, TransferResult>> {
+    let mut results = BTreeMap::new();
+    if !results_file.exists() {
+        return Ok(results);
+    }
+    let file = File::open(results_file)
+        .map_err(|err| CliError::IO(results_file.display().to_string(), err))?;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|err| CliError::IO(results_file.display().to_string(), err))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let result: TransferResult = serde_json::from_str(&line)
+            .map_err(|err| CliError::UnableToParse("results file", err.to_string()))?;
+        results.insert(result.row, result);
+    }
+    Ok(results)
+}
+
+fn now_secs() -> CliTypedResult<u64> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+        .as_secs())
+}
+
+/// Transfer coins or a fungible asset to the recipients of a CSV file
+///
+/// The CSV file has a `recipient,amount` header, and a row per transfer with the address or the
+/// profile of the recipient and the amount in the smallest unit of the asset, e.g. Octas for APT.
+///
+/// The transfers are submitted in batches of consecutive sequence numbers, and the result of
+/// every row is appended to the results file as soon as it's known. Running the command again
+/// with the same results file skips the rows that were already transferred, and retries the
+/// failed ones, so that a partially failed batch can be resumed.
+#[derive(Debug, Parser)]
+pub struct TransferBatch {
+    /// CSV file with the recipients and the amounts
+    #[clap(long, value_parser)]
+    pub(crate) csv: PathBuf,
+
+    /// Coin type to transfer, defaults to APT
+    #[clap(long, conflicts_with = "fungible_asset")]
+    pub(crate) coin_type: Option<String>,
+
+    /// Address of the metadata object of a fungible asset to transfer instead of a coin
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) fungible_asset: Option<AccountAddress>,
+
+    /// File the result of every row is appended to, as JSON lines
+    ///
+    /// Defaults to the CSV file with a `.results.jsonl` extension
+    #[clap(long, value_parser)]
+    pub(crate) results_file: Option<PathBuf>,
+
+    /// Simulate the transfers without submitting them
+    #[clap(long)]
+    pub(crate) dry_run: bool,
+
+    /// Maximum number of transactions submitted before waiting for them to be committed
+    #[clap(long, default_value_t = 20)]
+    pub(crate) batch_size: usize,
+
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+}
+
+impl TransferBatch {
+    fn payload(
+        &self,
+        recipient: AccountAddress,
+        amount: u64,
+    ) -> CliTypedResult<TransactionPayload> {
+        if let Some(metadata) = self.fungible_asset {
+            return Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+                ModuleId::new(
+                    AccountAddress::ONE,
+                    ident_str!("primary_fungible_store").to_owned(),
+                ),
+                ident_str!("transfer").to_owned(),
+                vec![TypeTag::Struct(Box::new(StructTag {
+                    address: AccountAddress::ONE,
+                    module: ident_str!("fungible_asset").to_owned(),
+                    name: ident_str!("Metadata").to_owned(),
+                    type_params: vec![],
+                }))],
+                vec![
+                    bcs::to_bytes(&metadata)?,
+                    bcs::to_bytes(&recipient)?,
+                    bcs::to_bytes(&amount)?,
+                ],
+            )));
+        }
+        let coin_type = self.coin_type.as_deref().unwrap_or(APTOS_COIN);
+        let coin_type = TypeTag::from_str(coin_type).map_err(|err| {
+            CliError::CommandArgumentError(format!("Invalid coin type {}: {}", coin_type, err))
+        })?;
+        Ok(aptos_stdlib::aptos_account_transfer_coins(
+            coin_type, recipient, amount,
+        ))
+    }
+
+    /// Checks the pending transactions of the previous runs, which may have been committed since
+    fn check_previous_result<'a>(
+        payout: &Payout,
+        previous: Option<&'a TransferResult>,
+    ) -> CliTypedResult<Option<&'a TransferResult>> {
+        let previous = match previous {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        if previous.recipient != payout.recipient || previous.amount != payout.amount {
+            return Err(CliError::CommandArgumentError(format!(
+                "Row {} of the CSV file doesn't match the results file, it was a transfer of {} to {}",
+                payout.row, previous.amount, previous.recipient
+            )));
+        }
+        Ok(Some(previous))
+    }
+
+    async fn resolve_pending(
+        client: &Client,
+        result: &TransferResult,
+    ) -> CliTypedResult<Option<TransferResult>> {
+        let hash = match result.transaction_hash {
+            Some(hash) => hash,
+            None => return Ok(None),
+        };
+        match client.get_transaction_by_hash(hash).await {
+            Ok(response) => {
+                let txn = response.into_inner();
+                if txn.is_pending() {
+                    return Err(CliError::UnexpectedError(format!(
+                        "The transfer of row {} is still pending in transaction {}, try again once it's committed",
+                        result.row, hash
+                    )));
+                }
+                let mut resolved = result.clone();
+                resolved.version = txn.version();
+                resolved.gas_used = txn.transaction_info().ok().map(|info| info.gas_used.0);
+                if txn.success() {
+                    resolved.status = TransferStatus::Success;
+                    resolved.error = None;
+                } else {
+                    resolved.status = TransferStatus::Failed;
+                    resolved.error = Some(txn.vm_status());
+                }
+                Ok(Some(resolved))
+            },
+            Err(RestError::Api(error)) => {
+                // The transaction is only known to be dropped once it expired
+                let ledger_timestamp_secs = error
+                    .state
+                    .map(|state| state.timestamp_usecs / 1_000_000)
+                    .unwrap_or_default();
+                if result.expiration_timestamp_secs.unwrap_or_default() < ledger_timestamp_secs {
+                    Ok(None)
+                } else {
+                    Err(CliError::UnexpectedError(format!(
+                        "The transaction {} of row {} may still be committed, try again once it expired",
+                        hash, result.row
+                    )))
+                }
+            },
+            Err(err) => Err(CliError::ApiError(err.to_string())),
+        }
+    }
+
+    /// Waits for a submitted transaction, looking it up if waiting failed
+    async fn wait_for_result(
+        client: &Client,
+        payout: &Payout,
+        txn: &SignedTransaction,
+    ) -> TransferResult {
+        let pending = TransferResult::new(payout, TransferStatus::Pending).with_transaction(txn);
+        let wait_error = match client.wait_for_signed_transaction(txn).await {
+            Ok(response) => {
+                let committed = response.into_inner();
+                let mut result = pending;
+                result.status = TransferStatus::Success;
+                result.version = committed.version();
+                result.gas_used = committed
+                    .transaction_info()
+                    .ok()
+                    .map(|info| info.gas_used.0);
+                return result;
+            },
+            Err(err) => err,
+        };
+        match client
+            .get_transaction_by_hash(txn.clone().committed_hash())
+            .await
+        {
+            Ok(response) if !response.inner().is_pending() => {
+                let committed = response.into_inner();
+                let mut result = pending.with_error(committed.vm_status());
+                result.status = TransferStatus::Failed;
+                result.version = committed.version();
+                result.gas_used = committed
+                    .transaction_info()
+                    .ok()
+                    .map(|info| info.gas_used.0);
+                result
+            },
+            _ => pending.with_error(wait_error),
+        }
+    }
+}
+
+struct ResultsWriter {
+    path: PathBuf,
+    file: File,
+}
+
+impl ResultsWriter {
+    fn append(&mut self, result: &TransferResult) -> CliTypedResult<()> {
+        let line = serde_json::to_string(result)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        writeln!(self.file, "{}", line)
+            .and_then(|_| self.file.flush())
+            .map_err(|err| CliError::IO(self.path.display().to_string(), err))
+    }
+}
+
+#[async_trait]
+impl CliCommand<BatchTransferSummary> for TransferBatch {
+    fn command_name(&self) -> &'static str {
+        "TransferBatch"
+    }
+
+    async fn execute(self) -> CliTypedResult<BatchTransferSummary> {
+        if self.batch_size == 0 {
+            return Err(CliError::CommandArgumentError(
+                "--batch-size must be greater than 0".to_string(),
+            ));
+        }
+        let payouts = read_payouts(&self.csv)?;
+        let results_file = self
+            .results_file
+            .clone()
+            .unwrap_or_else(|| self.csv.with_extension("results.jsonl"));
+        let previous_results = read_results(&results_file)?;
+
+        let client = self
+            .txn_options
+            .rest_options
+            .client(&self.txn_options.profile_options)?;
+        let (private_key, sender) = self.txn_options.get_key_and_address()?;
+        let public_key = private_key.public_key();
+
+        // Rows transferred in a previous run are skipped, and the pending ones are looked up
+        let mut results = BTreeMap::new();
+        let mut todo = vec![];
+        for payout in &payouts {
+            let previous = Self::check_previous_result(payout, previous_results.get(&payout.row))?;
+            let previous = match previous {
+                Some(previous) if previous.status == TransferStatus::Pending => {
+                    Self::resolve_pending(&client, previous).await?
+                },
+                previous => previous.cloned(),
+            };
+            match previous {
+                Some(previous) if previous.status == TransferStatus::Success => {
+                    let mut result = previous;
+                    result.status = TransferStatus::AlreadyTransferred;
+                    results.insert(payout.row, result);
+                },
+                _ => todo.push(payout),
+            }
+        }
+
+        let (account, state) = get_account_with_state(&client, sender).await?;
+        let chain_id = ChainId::new(state.chain_id);
+        let gas_unit_price = match self.txn_options.gas_options.gas_unit_price {
+            Some(gas_unit_price) => gas_unit_price,
+            None => self.txn_options.estimate_gas_price().await?,
+        };
+
+        // The transfers are simulated independently, with the current sequence number, to check
+        // them and to estimate their gas
+        let mut max_gas_amounts = BTreeMap::new();
+        if self.dry_run || self.txn_options.gas_options.max_gas.is_none() {
+            let expiration_timestamp_secs =
+                now_secs()? + self.txn_options.gas_options.expiration_secs;
+            let mut checked = vec![];
+            for payout in todo {
+                let signed_transaction = SignedTransaction::new(
+                    RawTransaction::new(
+                        sender,
+                        account.sequence_number,
+                        self.payload(payout.recipient, payout.amount)?,
+                        self.txn_options.gas_options.max_gas.unwrap_or(0),
+                        gas_unit_price,
+                        expiration_timestamp_secs,
+                        chain_id,
+                    ),
+                    public_key.clone(),
+                    Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+                );
+                let txns = client
+                    .simulate_with_gas_estimation(
+                        &signed_transaction,
+                        self.txn_options.gas_options.max_gas.is_none(),
+                        false,
+                    )
+                    .await?
+                    .into_inner();
+                let simulated_txn = txns.first().unwrap();
+                let mut result = TransferResult::new(payout, TransferStatus::Simulated);
+                result.gas_used = Some(simulated_txn.info.gas_used.0);
+                if !simulated_txn.info.success {
+                    result.status = TransferStatus::Failed;
+                    result.error = Some(simulated_txn.info.vm_status.clone());
+                    results.insert(payout.row, result);
+                    continue;
+                }
+                max_gas_amounts.insert(
+                    payout.row,
+                    self.txn_options.gas_options.max_gas.unwrap_or_else(|| {
+                        adjust_gas_headroom(
+                            simulated_txn.info.gas_used.0,
+                            simulated_txn.request.max_gas_amount.0,
+                        )
+                    }),
+                );
+                if self.dry_run {
+                    results.insert(payout.row, result);
+                } else {
+                    checked.push(payout);
+                }
+            }
+            todo = checked;
+        }
+
+        if !self.dry_run && !todo.is_empty() {
+            let max_gas = |payout: &Payout| {
+                max_gas_amounts
+                    .get(&payout.row)
+                    .copied()
+                    .or(self.txn_options.gas_options.max_gas)
+                    .unwrap_or_default()
+            };
+            let total_amount: u128 = todo.iter().map(|payout| payout.amount as u128).sum();
+            let total_max_gas: u128 = todo
+                .iter()
+                .map(|payout| max_gas(payout) as u128 * gas_unit_price as u128)
+                .sum();
+            prompt_yes_with_override(
+                &format!(
+                    "Do you want to submit {} transfers for a total of {}, and a maximum of {} Octas of gas at a gas unit price of {} Octas?",
+                    todo.len(),
+                    total_amount,
+                    total_max_gas,
+                    gas_unit_price
+                ),
+                self.txn_options.prompt_options,
+            )?;
+
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&results_file)
+                .map_err(|err| CliError::IO(results_file.display().to_string(), err))?;
+            let mut writer = ResultsWriter {
+                path: results_file.clone(),
+                file,
+            };
+
+            let mut queue: VecDeque<&Payout> = todo.into_iter().collect();
+            while !queue.is_empty() {
+                // The sequence number is fetched again for every batch, in case a transaction
+                // of the previous one was dropped
+                let mut sequence_number = get_account_with_state(&client, sender)
+                    .await?
+                    .0
+                    .sequence_number;
+                let expiration_timestamp_secs =
+                    now_secs()? + self.txn_options.gas_options.expiration_secs;
+                let batch: Vec<_> = queue.drain(..queue.len().min(self.batch_size)).collect();
+
+                let mut submitted = vec![];
+                let mut aborted = None;
+                for (index, payout) in batch.iter().enumerate() {
+                    let txn = RawTransaction::new(
+                        sender,
+                        sequence_number,
+                        self.payload(payout.recipient, payout.amount)?,
+                        max_gas(payout),
+                        gas_unit_price,
+                        expiration_timestamp_secs,
+                        chain_id,
+                    )
+                    .sign(&private_key, public_key.clone())
+                    .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+                    .into_inner();
+                    match client.submit(&txn).await {
+                        Ok(_) => {
+                            sequence_number += 1;
+                            writer.append(
+                                &TransferResult::new(payout, TransferStatus::Pending)
+                                    .with_transaction(&txn),
+                            )?;
+                            submitted.push((payout, txn));
+                        },
+                        Err(RestError::Api(error)) => {
+                            // A rejected transaction leaves a gap in the sequence numbers, so
+                            // the rest of the batch is submitted again in the next one
+                            let result = TransferResult::new(payout, TransferStatus::Failed)
+                                .with_error(error.error.message);
+                            writer.append(&result)?;
+                            results.insert(payout.row, result);
+                            for payout in batch[index + 1..].iter().rev() {
+                                queue.push_front(*payout);
+                            }
+                            break;
+                        },
+                        Err(err) => {
+                            // The transaction may have been accepted, so it's left pending
+                            // to be looked up when the transfers are resumed
+                            let result = TransferResult::new(payout, TransferStatus::Pending)
+                                .with_transaction(&txn)
+                                .with_error(&err);
+                            writer.append(&result)?;
+                            results.insert(payout.row, result);
+                            aborted = Some(err);
+                            break;
+                        },
+                    }
+                }
+
+                for (payout, txn) in &submitted {
+                    let result = Self::wait_for_result(&client, payout, txn).await;
+                    writer.append(&result)?;
+                    results.insert(payout.row, result);
+                }
+
+                if let Some(err) = aborted {
+                    return Err(CliError::ApiError(format!(
+                        "Failed to submit the transfers, run the command again to resume them, the results are in {}: {}",
+                        results_file.display(),
+                        err
+                    )));
+                }
+            }
+        }
+
+        let results: Vec<_> = results.into_values().collect();
+        let count = |status: TransferStatus| {
+            results
+                .iter()
+                .filter(|result| result.status == status)
+                .count()
+        };
+        Ok(BatchTransferSummary {
+            sender,
+            results_file,
+            succeeded: count(TransferStatus::Success) + count(TransferStatus::AlreadyTransferred),
+            failed: count(TransferStatus::Failed),
+            pending: count(TransferStatus::Pending),
+            results,
+        })
+    }
+}
@@ -384,6 +384,24 @@ impl CliConfig {
     }
 }
 
+/// Format the result of a command is printed in
+///
+/// The result is wrapped in `Result` or `Error` in every format, and keeps the same fields.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+/// Options shared by every command
+#[derive(Debug, Default, Parser)]
+pub struct GlobalOptions {
+    /// Format of the output of the command
+    #[clap(long, global = true, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+}
+
 /// Types of Keys used by the blockchain
 #[derive(ValueEnum, Clone, Copy, Debug)]
 pub enum KeyType {
@@ -1790,12 +1808,11 @@ impl TransactionOptions {
             };
         }
 
+        let graphs = gas_log.to_flamegraph(&format!("Transaction {}", hash))?;
+
         // Generate the execution & IO flamegraph.
         println!();
-        match gas_log
-            .exec_io
-            .to_flamegraph(format!("Transaction {} -- Execution & IO", hash))?
-        {
+        match graphs.exec_io {
             Some(graph_bytes) => {
                 create_dir!();
                 let graph_file_path = Path::join(dir, format!("{}.exec_io.svg", raw_file_name));
@@ -1817,10 +1834,7 @@ impl TransactionOptions {
         }
 
         // Generate the storage fee flamegraph.
-        match gas_log
-            .storage
-            .to_flamegraph(format!("Transaction {} -- Storage Fee", hash))?
-        {
+        match graphs.storage {
             Some(graph_bytes) => {
                 create_dir!();
                 let graph_file_path = Path::join(dir, format!("{}.storage.svg", raw_file_name));
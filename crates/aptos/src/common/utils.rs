@@ -3,7 +3,7 @@
 
 use crate::{
     common::types::{
-        account_address_from_public_key, CliError, CliTypedResult, PromptOptions,
+        account_address_from_public_key, CliError, CliTypedResult, OutputFormat, PromptOptions,
         TransactionOptions, TransactionSummary,
     },
     config::GlobalConfig,
@@ -31,6 +31,7 @@ use std::{
     collections::BTreeMap,
     env,
     fs::OpenOptions,
+    future::Future,
     io::Write,
     path::{Path, PathBuf},
     str::FromStr,
@@ -38,6 +39,21 @@ use std::{
 };
 use tokio::time::timeout;
 
+tokio::task_local! {
+    /// The format of the result of the command run by the current task, see `with_output_format()`
+    static OUTPUT_FORMAT: OutputFormat;
+}
+
+/// Runs a command with its result printed in `format`. Commands run concurrently, e.g. by the
+/// tests, can use different formats.
+pub async fn with_output_format<F: Future>(format: OutputFormat, command: F) -> F::Output {
+    OUTPUT_FORMAT.scope(format, command).await
+}
+
+pub fn output_format() -> OutputFormat {
+    OUTPUT_FORMAT.try_with(|format| *format).unwrap_or_default()
+}
+
 /// Prompts for confirmation until a yes or no is given explicitly
 pub fn prompt_yes(prompt: &str) -> bool {
     let mut result: Result<bool, ()> = Err(());
@@ -75,8 +91,8 @@ pub async fn to_common_success_result<T>(
     .await
 }
 
-/// For pretty printing outputs in JSON. You can opt out of printing the error as
-/// JSON by setting `jsonify_error` to false.
+/// For pretty printing outputs in JSON, or in YAML with `--format yaml`. You can opt out of
+/// printing the error as JSON by setting `jsonify_error` to false.
 pub async fn to_common_result<T: Serialize>(
     command: &str,
     start_time: Instant,
@@ -112,7 +128,10 @@ pub async fn to_common_result<T: Serialize>(
 
     let is_err = result.is_err();
     let result = ResultWrapper::<T>::from(result);
-    let string = serde_json::to_string_pretty(&result).unwrap();
+    let string = match output_format() {
+        OutputFormat::Json => serde_json::to_string_pretty(&result).unwrap(),
+        OutputFormat::Yaml => serde_yaml::to_string(&result).unwrap(),
+    };
     if is_err {
         Err(string)
     } else {
@@ -3,7 +3,7 @@
 
 #![allow(unsafe_code)]
 
-use crate::Tool;
+use crate::Cli;
 use clap::Parser;
 use std::{
     ffi::{c_char, CStr, CString},
@@ -29,7 +29,7 @@ pub unsafe extern "C" fn run_aptos_sync(s: *const c_char) -> *const c_char {
 
     // Create a new Tokio runtime and block on the execution of `cli.execute()`
     let result_string = Runtime::new().unwrap().block_on(async move {
-        let cli = Tool::parse_from(input_string);
+        let cli = Cli::parse_from(input_string);
         cli.execute().await
     });
 
@@ -58,7 +58,7 @@ pub unsafe extern "C" fn run_aptos_async(s: *mut c_char) -> *mut c_char {
     thread::spawn(move || {
         let rt = Runtime::new().unwrap();
         let input_string = c_str.to_str().unwrap().split_whitespace();
-        let cli = Tool::parse_from(input_string);
+        let cli = Cli::parse_from(input_string);
 
         // Run the CLI once
         rt.block_on(async { cli.execute().await })
@@ -15,16 +15,33 @@ pub mod op;
 pub mod stake;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod test;
+pub mod transaction;
 pub mod update;
 
 use crate::common::{
-    types::{CliCommand, CliResult, CliTypedResult},
-    utils::cli_build_information,
+    types::{CliCommand, CliResult, CliTypedResult, GlobalOptions},
+    utils::{cli_build_information, with_output_format},
 };
 use async_trait::async_trait;
 use clap::Parser;
 use std::collections::BTreeMap;
 
+/// Command Line Interface (CLI) for developing and interacting with the Aptos blockchain
+#[derive(Parser)]
+#[clap(name = "aptos", author, version, propagate_version = true, styles = aptos_cli_common::aptos_cli_style())]
+pub struct Cli {
+    #[clap(flatten)]
+    pub global_options: GlobalOptions,
+    #[clap(subcommand)]
+    pub tool: Tool,
+}
+
+impl Cli {
+    pub async fn execute(self) -> CliResult {
+        with_output_format(self.global_options.format, self.tool.execute()).await
+    }
+}
+
 /// Command Line Interface (CLI) for developing and interacting with the Aptos blockchain
 #[derive(Parser)]
 #[clap(name = "aptos", author, version, propagate_version = true, styles = aptos_cli_common::aptos_cli_style())]
@@ -49,6 +66,8 @@ pub enum Tool {
     Node(node::NodeTool),
     #[clap(subcommand)]
     Stake(stake::StakeTool),
+    #[clap(subcommand)]
+    Transaction(transaction::TransactionTool),
     Update(update::UpdateTool),
 }
 
@@ -68,6 +87,7 @@ impl Tool {
             Multisig(tool) => tool.execute().await,
             Node(tool) => tool.execute().await,
             Stake(tool) => tool.execute().await,
+            Transaction(tool) => tool.execute().await,
             Update(tool) => tool.execute_serialized().await,
         }
     }
@@ -93,5 +113,5 @@ impl CliCommand<BTreeMap<String, String>> for InfoTool {
 #[test]
 fn verify_tool() {
     use clap::CommandFactory;
-    Tool::command().debug_assert()
+    Cli::command().debug_assert()
 }
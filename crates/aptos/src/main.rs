@@ -9,7 +9,7 @@
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
-use aptos::{move_tool, Tool};
+use aptos::{move_tool, Cli};
 use clap::Parser;
 use std::{process::exit, time::Duration};
 
@@ -24,7 +24,7 @@ fn main() {
         .unwrap();
 
     // Run the corresponding tool.
-    let result = runtime.block_on(Tool::parse().execute());
+    let result = runtime.block_on(Cli::parse().execute());
 
     // Shutdown the runtime with a timeout. We do this to make sure that we don't sit
     // here waiting forever waiting for tasks that sometimes don't want to exit on
@@ -0,0 +1,413 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{
+        CliCommand, CliError, CliTypedResult, MovePackageDir, ProfileOptions, RestOptions,
+    },
+    move_tool::{stored_package::CachedPackageRegistry, IncludedArtifacts},
+};
+use aptos_framework::{natives::code::UpgradePolicy, BuiltPackage};
+use async_trait::async_trait;
+use clap::Parser;
+use move_binary_format::{
+    file_format::{Ability, AbilitySet, Visibility},
+    normalized::{Field, Function, Module, Type},
+    CompiledModule,
+};
+use move_core_types::account_address::AccountAddress;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    PackageImmutable,
+    UpgradePolicyWeakened,
+    ModuleRemoved,
+    StructRemoved,
+    StructAbilitiesRemoved,
+    StructTypeParametersChanged,
+    StructLayoutChanged,
+    FunctionRemoved,
+    FunctionVisibilityReduced,
+    EntryFunctionRemoved,
+    FunctionSignatureChanged,
+    FriendRemoved,
+}
+
+/// A change of the package that the network may reject
+#[derive(Clone, Debug, Serialize)]
+pub struct CompatibilityIssue {
+    pub kind: IssueKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    /// The struct or function that changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    /// Whether the upgrade is rejected because of this change. The changes of friend functions
+    /// and declarations are only rejected if the network doesn't treat friend functions as
+    /// private.
+    pub breaking: bool,
+    pub message: String,
+}
+
+/// The changes of the local package that are incompatible with its published version
+#[derive(Clone, Debug, Serialize)]
+pub struct UpgradeCompatibility {
+    pub package: String,
+    pub address: AccountAddress,
+    /// Whether a version of the package is published at the address
+    pub published: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upgrade_policy: Option<String>,
+    /// Whether the upgrade is expected to be accepted
+    pub compatible: bool,
+    pub issues: Vec<CompatibilityIssue>,
+}
+
+struct Issues {
+    module: String,
+    issues: Vec<CompatibilityIssue>,
+}
+
+impl Issues {
+    fn push(&mut self, kind: IssueKind, member: &impl ToString, breaking: bool, message: String) {
+        self.issues.push(CompatibilityIssue {
+            kind,
+            module: Some(self.module.clone()),
+            member: Some(member.to_string()),
+            breaking,
+            message,
+        });
+    }
+}
+
+fn visibility_name(visibility: Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public => "public",
+        Visibility::Friend => "friend",
+        Visibility::Private => "private",
+    }
+}
+
+/// The abilities as written in Move, e.g. `copy, drop`
+fn ability_names(abilities: AbilitySet) -> String {
+    abilities
+        .into_iter()
+        .map(|ability| match ability {
+            Ability::Copy => "copy",
+            Ability::Drop => "drop",
+            Ability::Store => "store",
+            Ability::Key => "key",
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The new constraints of the type parameters must be a subset of the old ones
+fn type_parameters_compatible(old: &[AbilitySet], new: &[AbilitySet]) -> bool {
+    old.len() == new.len()
+        && old
+            .iter()
+            .zip(new)
+            .all(|(old_constraints, new_constraints)| new_constraints.is_subset(*old_constraints))
+}
+
+fn check_function(issues: &mut Issues, name: &impl ToString, old: &Function, new: &Function) {
+    let breaking = old.visibility != Visibility::Friend;
+    let visibility_compatible = match (old.visibility, new.visibility) {
+        (Visibility::Public, new_visibility) => new_visibility == Visibility::Public,
+        (Visibility::Friend, new_visibility) => new_visibility != Visibility::Private,
+        (Visibility::Private, _) => true,
+    };
+    if !visibility_compatible {
+        issues.push(
+            IssueKind::FunctionVisibilityReduced,
+            name,
+            breaking,
+            format!(
+                "Visibility is reduced from {} to {}",
+                visibility_name(old.visibility),
+                visibility_name(new.visibility)
+            ),
+        );
+    }
+    if old.is_entry && !new.is_entry {
+        issues.push(
+            IssueKind::EntryFunctionRemoved,
+            name,
+            breaking,
+            "Is no longer an entry function".to_string(),
+        );
+    }
+    if old.parameters != new.parameters || old.return_ != new.return_ {
+        let signature = |function: &Function| {
+            let types = |types: &[Type]| {
+                types
+                    .iter()
+                    .map(|type_| format!("{}", type_))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            format!(
+                "({}): ({})",
+                types(&function.parameters),
+                types(&function.return_)
+            )
+        };
+        issues.push(
+            IssueKind::FunctionSignatureChanged,
+            name,
+            breaking,
+            format!(
+                "Signature changed from {} to {}",
+                signature(old),
+                signature(new)
+            ),
+        );
+    }
+    if !type_parameters_compatible(&old.type_parameters, &new.type_parameters) {
+        issues.push(
+            IssueKind::FunctionSignatureChanged,
+            name,
+            breaking,
+            "Type parameters or their constraints changed".to_string(),
+        );
+    }
+}
+
+/// Compares the modules with the same rules as the compatibility check of the VM, but reports
+/// every change instead of failing at the first one
+fn check_module(old: &Module, new: &Module) -> Vec<CompatibilityIssue> {
+    let mut issues = Issues {
+        module: old.name.to_string(),
+        issues: vec![],
+    };
+
+    for (name, old_struct) in &old.structs {
+        let new_struct = match new.structs.get(name) {
+            Some(new_struct) => new_struct,
+            None => {
+                issues.push(
+                    IssueKind::StructRemoved,
+                    name,
+                    true,
+                    "Struct is removed".to_string(),
+                );
+                continue;
+            },
+        };
+        if !old_struct.abilities.is_subset(new_struct.abilities) {
+            issues.push(
+                IssueKind::StructAbilitiesRemoved,
+                name,
+                true,
+                format!(
+                    "Abilities changed from {{{}}} to {{{}}}",
+                    ability_names(old_struct.abilities),
+                    ability_names(new_struct.abilities)
+                ),
+            );
+        }
+        let type_parameters_compatible = old_struct.type_parameters.len()
+            == new_struct.type_parameters.len()
+            && old_struct
+                .type_parameters
+                .iter()
+                .zip(&new_struct.type_parameters)
+                .all(|(old_param, new_param)| {
+                    (!old_param.is_phantom || new_param.is_phantom)
+                        && new_param.constraints.is_subset(old_param.constraints)
+                });
+        if !type_parameters_compatible {
+            issues.push(
+                IssueKind::StructTypeParametersChanged,
+                name,
+                true,
+                "Type parameters, their constraints or phantom declarations changed".to_string(),
+            );
+        }
+        if old_struct.fields != new_struct.fields {
+            let fields = |fields: &[Field]| {
+                fields
+                    .iter()
+                    .map(|field| format!("{}: {}", field.name, field.type_))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            issues.push(
+                IssueKind::StructLayoutChanged,
+                name,
+                true,
+                format!(
+                    "Fields changed from {{ {} }} to {{ {} }}",
+                    fields(&old_struct.fields),
+                    fields(&new_struct.fields)
+                ),
+            );
+        }
+    }
+
+    for (name, old_function) in &old.exposed_functions {
+        match new.exposed_functions.get(name) {
+            Some(new_function) => check_function(&mut issues, name, old_function, new_function),
+            None => issues.push(
+                IssueKind::FunctionRemoved,
+                name,
+                old_function.visibility != Visibility::Friend,
+                "Function is removed".to_string(),
+            ),
+        }
+    }
+
+    let new_friends: BTreeSet<_> = new.friends.iter().collect();
+    for friend in &old.friends {
+        if !new_friends.contains(friend) {
+            issues.push(
+                IssueKind::FriendRemoved,
+                &friend.short_str_lossless(),
+                false,
+                "Friend declaration is removed".to_string(),
+            );
+        }
+    }
+    issues.issues
+}
+
+/// Check that the local package can upgrade its published version
+///
+/// The local package is compiled and compared with the version published at its address, with
+/// the checks the network runs on publishing: the upgrade policy can't be weakened, modules
+/// can't be removed, and with the `compatible` policy, structs can't change their layout and
+/// public functions can't be removed or change their signature.
+#[derive(Parser)]
+pub struct CheckUpgrade {
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<UpgradeCompatibility> for CheckUpgrade {
+    fn command_name(&self) -> &'static str {
+        "CheckUpgrade"
+    }
+
+    async fn execute(self) -> CliTypedResult<UpgradeCompatibility> {
+        let build_options = IncludedArtifacts::None.build_options(
+            self.move_options.dev,
+            self.move_options.skip_fetch_latest_git_deps,
+            self.move_options.named_addresses(),
+            self.move_options.bytecode_version,
+            self.move_options.compiler_version,
+            self.move_options.skip_attribute_checks,
+            self.move_options.check_test_code,
+        );
+        let package = BuiltPackage::build(self.move_options.get_package_path()?, build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let new_modules: BTreeMap<String, &CompiledModule> = package
+            .modules()
+            .map(|module| (module.self_id().name().to_string(), module))
+            .collect();
+        let address = *package
+            .modules()
+            .next()
+            .ok_or_else(|| {
+                CliError::CommandArgumentError("The package has no modules".to_string())
+            })?
+            .self_id()
+            .address();
+        let new_policy = package.extract_metadata()?.upgrade_policy;
+
+        let url = self.rest_options.url(&self.profile_options)?;
+        let client = self.rest_options.client(&self.profile_options)?;
+        let registry = CachedPackageRegistry::create(url, address).await.ok();
+        let old_package = match &registry {
+            Some(registry) => registry.get_package(package.name()).await.ok(),
+            None => None,
+        };
+        let old_package = match old_package {
+            Some(old_package) => old_package,
+            None => {
+                return Ok(UpgradeCompatibility {
+                    package: package.name().to_string(),
+                    address,
+                    published: false,
+                    upgrade_policy: None,
+                    compatible: true,
+                    issues: vec![],
+                })
+            },
+        };
+
+        let old_policy = old_package.upgrade_policy();
+        let mut issues = vec![];
+        if old_policy == UpgradePolicy::immutable() {
+            issues.push(CompatibilityIssue {
+                kind: IssueKind::PackageImmutable,
+                module: None,
+                member: None,
+                breaking: true,
+                message: "The published package is immutable".to_string(),
+            });
+        }
+        if new_policy.policy < old_policy.policy {
+            issues.push(CompatibilityIssue {
+                kind: IssueKind::UpgradePolicyWeakened,
+                module: None,
+                member: None,
+                breaking: true,
+                message: format!(
+                    "The upgrade policy is weakened from {} to {}",
+                    old_policy, new_policy
+                ),
+            });
+        }
+        for module_name in old_package.module_names() {
+            let new_module = match new_modules.get(module_name) {
+                Some(new_module) => new_module,
+                None => {
+                    issues.push(CompatibilityIssue {
+                        kind: IssueKind::ModuleRemoved,
+                        module: Some(module_name.to_string()),
+                        member: None,
+                        breaking: true,
+                        message: "Module is removed".to_string(),
+                    });
+                    continue;
+                },
+            };
+            // Only the compatible policy is checked by the VM
+            if old_policy != UpgradePolicy::compat() {
+                continue;
+            }
+            let old_module_bytes = client
+                .get_account_module_bcs(address, module_name)
+                .await?
+                .into_inner();
+            let old_module = CompiledModule::deserialize(&old_module_bytes).map_err(|err| {
+                CliError::UnexpectedError(format!(
+                    "Failed to deserialize the published module {}: {}",
+                    module_name, err
+                ))
+            })?;
+            issues.extend(check_module(
+                &Module::new(&old_module),
+                &Module::new(new_module),
+            ));
+        }
+
+        Ok(UpgradeCompatibility {
+            package: package.name().to_string(),
+            address,
+            published: true,
+            upgrade_policy: Some(old_policy.to_string()),
+            compatible: issues.iter().all(|issue| !issue.breaking),
+            issues,
+        })
+    }
+}
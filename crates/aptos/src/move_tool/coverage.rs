@@ -1,17 +1,23 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::{CliCommand, CliError, CliResult, CliTypedResult, MovePackageDir};
+use crate::common::{
+    types::{CliCommand, CliError, CliResult, CliTypedResult, MovePackageDir},
+    utils::{create_dir_if_not_exist, write_to_file},
+};
 use aptos_framework::extended_checks;
 use async_trait::async_trait;
 use clap::{Parser, Subcommand};
 use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_coverage::{
-    coverage_map::CoverageMap, format_csv_summary, format_human_summary,
-    source_coverage::SourceCoverageBuilder, summary::summarize_inst_cov,
+    coverage_map::CoverageMap,
+    format_csv_summary, format_human_summary,
+    source_coverage::{SourceCoverageBuilder, HTML_STYLE},
+    summary::summarize_inst_cov,
 };
 use move_disassembler::disassembler::Disassembler;
 use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig, CompilerConfig};
+use std::path::Path;
 
 /// Display a coverage summary for all modules in a package
 ///
@@ -169,6 +175,86 @@ fn compile_coverage(
     Ok((coverage_map, package))
 }
 
+/// Saves the coverage of the modules of a package as an LCOV file, and as an HTML report with
+/// an index of the modules and one page per module highlighting its uncovered source
+pub fn write_coverage_reports(
+    move_options: MovePackageDir,
+    lcov_file: Option<&Path>,
+    html_dir: Option<&Path>,
+) -> CliTypedResult<()> {
+    let package_path = move_options.get_package_path()?;
+    let (coverage_map, package) = compile_coverage(move_options)?;
+    if let Some(html_dir) = html_dir {
+        create_dir_if_not_exist(html_dir)?;
+    }
+
+    let mut lcov = Vec::new();
+    let mut index_rows = String::new();
+    for unit in package.root_modules() {
+        let (module, source_map) = match &unit.unit {
+            CompiledUnit::Module(NamedCompiledModule {
+                module, source_map, ..
+            }) => (module, source_map),
+            _ => continue,
+        };
+        let source_coverage = SourceCoverageBuilder::new(module, &coverage_map, source_map);
+        let line_coverage = source_coverage.compute_line_coverage(&unit.source_path);
+        // The dashboards match the files of the report with the files of the repository
+        let source_path = unit
+            .source_path
+            .strip_prefix(&package_path)
+            .unwrap_or(&unit.source_path);
+        line_coverage
+            .output_lcov(source_path, &mut lcov)
+            .map_err(|err| CliError::IO("LCOV coverage".to_string(), err))?;
+
+        if let Some(html_dir) = html_dir {
+            let module_name = unit.unit.name().to_string();
+            let mut page = Vec::new();
+            source_coverage
+                .compute_source_coverage(&unit.source_path)
+                .output_html(&module_name, &mut page)
+                .map_err(|err| CliError::IO("HTML coverage".to_string(), err))?;
+            write_to_file(
+                &html_dir.join(format!("{}.html", module_name)),
+                "HTML coverage",
+                &page,
+            )?;
+            let (lines_hit, lines_found) = (line_coverage.lines_hit(), line_coverage.lines_found());
+            index_rows.push_str(&format!(
+                "<tr><td><a href=\"{0}.html\">{0}</a></td><td>{1}</td><td>{2}</td><td>{3:.2}%</td></tr>\n",
+                module_name,
+                lines_hit,
+                lines_found,
+                if lines_found == 0 {
+                    100.0
+                } else {
+                    lines_hit as f64 * 100.0 / lines_found as f64
+                }
+            ));
+        }
+    }
+
+    if let Some(lcov_file) = lcov_file {
+        write_to_file(lcov_file, "LCOV coverage", &lcov)?;
+    }
+    if let Some(html_dir) = html_dir {
+        let index = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Coverage</title>\n\
+            <style>\n{}</style>\n</head>\n<body>\n<h1>Coverage</h1>\n<table>\n\
+            <tr><th>Module</th><th>Lines hit</th><th>Lines</th><th>Coverage</th></tr>\n{}</table>\n\
+            </body>\n</html>\n",
+            HTML_STYLE, index_rows
+        );
+        write_to_file(
+            &html_dir.join("index.html"),
+            "HTML coverage",
+            index.as_bytes(),
+        )?;
+    }
+    Ok(())
+}
+
 /// Computes coverage for a package
 ///
 /// Computes coverage on a previous unit test run for a package.  Coverage input must
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod aptos_debug_natives;
+pub mod check_upgrade;
 pub mod coverage;
 mod disassembler;
 mod manifest;
@@ -20,7 +21,7 @@ use crate::{
         },
         utils::{
             check_if_file_exists, create_dir_if_not_exist, dir_default_to_current,
-            profile_or_submit, prompt_yes_with_override, write_to_file,
+            profile_or_submit, prompt_yes_with_override, read_from_file, write_to_file,
         },
     },
     governance::CompileScriptFunction,
@@ -31,12 +32,18 @@ use crate::{
     },
     CliCommand, CliResult,
 };
+use anyhow::Context;
 use aptos_crypto::HashValue;
 use aptos_framework::{
     docgen::DocgenOptions, extended_checks, natives::code::UpgradePolicy, prover::ProverOptions,
     BuildOptions, BuiltPackage,
 };
-use aptos_gas_schedule::{MiscGasParameters, NativeGasParameters};
+use aptos_gas_meter::{StandardGasAlgebra, StandardGasMeter};
+use aptos_gas_profiling::{GasProfiler, SourceMaps};
+use aptos_gas_schedule::{
+    AptosGasParameters, InitialGasSchedule, MiscGasParameters, NativeGasParameters,
+    LATEST_GAS_FEATURE_VERSION,
+};
 use aptos_rest_client::aptos_api_types::{
     EntryFunctionId, HexEncodedBytes, IdentifierWrapper, MoveModuleId,
 };
@@ -44,16 +51,24 @@ use aptos_types::{
     account_address::{create_resource_address, AccountAddress},
     transaction::{TransactionArgument, TransactionPayload},
 };
+use aptos_vm_types::storage::StorageGasParameters;
 use async_trait::async_trait;
 use clap::{Parser, Subcommand, ValueEnum};
 use itertools::Itertools;
 use move_cli::{self, base::test::UnitTestResult};
 use move_command_line_common::env::MOVE_HOME;
-use move_core_types::{identifier::Identifier, language_storage::ModuleId, u256::U256};
+use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_core_types::{
+    identifier::{IdentStr, Identifier},
+    language_storage::ModuleId,
+    resolver::MoveResolver,
+    u256::U256,
+};
 use move_package::{
     source_package::layout::SourcePackageLayout, BuildConfig, CompilerConfig, CompilerVersion,
 };
 use move_unit_test::UnitTestingConfig;
+use move_vm_runtime::{move_vm::MoveVM, native_extensions::NativeContextExtensions};
 pub use package_hooks::*;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -74,6 +89,7 @@ use tokio::task;
 #[derive(Subcommand)]
 pub enum MoveTool {
     BuildPublishPayload(BuildPublishPayload),
+    CheckUpgrade(check_upgrade::CheckUpgrade),
     Clean(CleanPackage),
     Compile(CompilePackage),
     CompileScript(CompileScript),
@@ -100,6 +116,7 @@ impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
             MoveTool::BuildPublishPayload(tool) => tool.execute_serialized().await,
+            MoveTool::CheckUpgrade(tool) => tool.execute_serialized().await,
             MoveTool::Clean(tool) => tool.execute_serialized().await,
             MoveTool::Compile(tool) => tool.execute_serialized().await,
             MoveTool::CompileScript(tool) => tool.execute_serialized().await,
@@ -436,9 +453,25 @@ pub struct TestPackage {
     #[clap(long = "coverage")]
     pub compute_coverage: bool,
 
+    /// Save the coverage as an LCOV file, for coverage dashboards like Codecov
+    #[clap(long, value_parser, requires = "compute_coverage")]
+    pub coverage_lcov: Option<PathBuf>,
+
+    /// Save the coverage as an HTML report in this directory, with the uncovered source
+    /// highlighted
+    #[clap(long, value_parser, requires = "compute_coverage")]
+    pub coverage_html: Option<PathBuf>,
+
     /// Dump storage state on failure.
     #[clap(long = "dump")]
     pub dump_state: bool,
+
+    /// Run the tests under the gas profiler and save one gas log per test into this directory
+    ///
+    /// Combine with `--filter` to only profile selected tests. Note that native functions
+    /// are not charged in unit tests, so their costs will show up as zero.
+    #[clap(long, value_parser)]
+    pub profile_gas: Option<PathBuf>,
 }
 
 #[async_trait]
@@ -465,6 +498,31 @@ impl CliCommand<&'static str> for TestPackage {
         };
 
         let path = self.move_options.get_package_path()?;
+        if let Some(output_dir) = &self.profile_gas {
+            create_dir_if_not_exist(output_dir)?;
+            let output_dir = output_dir.clone();
+            let source_maps = test_source_maps(&path, &config)?;
+            move_unit_test::profiling::set_profiling_hook(Box::new(
+                move |vm, storage, extensions, module_id, fn_name, args| {
+                    if let Err(err) = profile_unit_test(
+                        &output_dir,
+                        &source_maps,
+                        vm,
+                        storage,
+                        extensions,
+                        module_id,
+                        fn_name,
+                        args,
+                    ) {
+                        eprintln!(
+                            "Failed to profile test {}::{}: {:#}",
+                            module_id, fn_name, err
+                        );
+                    }
+                },
+            ));
+        }
+
         let result = move_cli::base::test::run_move_unit_tests(
             path.as_path(),
             config.clone(),
@@ -490,6 +548,13 @@ impl CliCommand<&'static str> for TestPackage {
         if self.compute_coverage {
             // TODO: config seems to be dead here.
             config.test_mode = false;
+            if self.coverage_lcov.is_some() || self.coverage_html.is_some() {
+                coverage::write_coverage_reports(
+                    self.move_options.clone(),
+                    self.coverage_lcov.as_deref(),
+                    self.coverage_html.as_deref(),
+                )?;
+            }
             let summary = SummaryCoverage {
                 summarize_functions: false,
                 output_csv: false,
@@ -508,6 +573,82 @@ impl CliCommand<&'static str> for TestPackage {
     }
 }
 
+/// Collects the source maps of a package compiled in test mode, which is how the unit tests are
+/// run, so the gas logs of the tests can refer to the Move source.
+fn test_source_maps(package_path: &Path, config: &BuildConfig) -> CliTypedResult<SourceMaps> {
+    let package = config
+        .clone()
+        .compile_package(package_path, &mut Vec::new())
+        .map_err(|err| CliError::MoveCompilationError(err.to_string()))?;
+    let mut source_maps = SourceMaps::new();
+    for unit in package.all_modules() {
+        if let CompiledUnit::Module(NamedCompiledModule {
+            module, source_map, ..
+        }) = &unit.unit
+        {
+            let source = read_from_file(&unit.source_path)?;
+            source_maps.add_module(
+                module,
+                source_map.clone(),
+                &unit.source_path,
+                &String::from_utf8_lossy(&source),
+            );
+        }
+    }
+    Ok(source_maps)
+}
+
+/// Re-executes a unit test under the gas profiler and saves the resulting gas log as JSON.
+///
+/// The test is executed with the latest gas schedule and the maximum gas budget of a transaction.
+fn profile_unit_test<'a>(
+    output_dir: &Path,
+    source_maps: &SourceMaps,
+    vm: &MoveVM,
+    storage: &'a dyn MoveResolver,
+    extensions: NativeContextExtensions<'a>,
+    module_id: &ModuleId,
+    fn_name: &IdentStr,
+    args: Vec<Vec<u8>>,
+) -> anyhow::Result<()> {
+    let gas_params = AptosGasParameters::initial();
+    let storage_gas_params =
+        StorageGasParameters::unlimited(gas_params.vm.txn.free_write_bytes_quota);
+    let balance = gas_params.vm.txn.maximum_number_of_gas_units;
+    let mut gas_profiler = GasProfiler::new_function(
+        StandardGasMeter::new(StandardGasAlgebra::new(
+            LATEST_GAS_FEATURE_VERSION,
+            gas_params.vm,
+            storage_gas_params,
+            balance,
+        )),
+        module_id.clone(),
+        fn_name.to_owned(),
+        vec![],
+    );
+
+    let mut session = vm.new_session_with_extensions(storage, extensions);
+    // The outcome of the test has already been reported by the test runner, so only the
+    // gas usage is of interest here.
+    let _ = session.execute_function_bypass_visibility(
+        module_id,
+        fn_name,
+        vec![],
+        args,
+        &mut gas_profiler,
+    );
+    let gas_log = gas_profiler.finish(Some(source_maps));
+
+    let path = output_dir.join(format!(
+        "{}-{}-{}.json",
+        module_id.address().short_str_lossless(),
+        module_id.name(),
+        fn_name
+    ));
+    std::fs::write(&path, gas_log.to_json()?)
+        .with_context(|| format!("Failed to write gas log to {}", path.display()))
+}
+
 /// Proves a Move package
 ///
 /// This is a tool for formal verification of a Move package using
@@ -45,7 +45,11 @@ pub struct IndexerApiArgs {
     /// --processors), and configure them to write to this DB, and run an API that lets
     /// you access the data they write to storage. This is opt in because it requires
     /// Docker to be installed on the host system.
-    #[clap(long, conflicts_with = "no_txn_stream")]
+    ///
+    /// The readiness endpoint (see --ready-server-listen-port) only reports ready once
+    /// the node, the transaction stream, postgres, the processors and the API are all
+    /// up.
+    #[clap(long, visible_alias = "with-indexer", conflicts_with = "no_txn_stream")]
     pub with_indexer_api: bool,
 
     /// The port at which to run the indexer API.
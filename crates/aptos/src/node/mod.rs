@@ -1454,7 +1454,7 @@ impl Time {
 
 #[cfg(test)]
 mod tests {
-    use crate::{CliResult, Tool};
+    use crate::{Cli, CliResult};
     use clap::Parser;
 
     // TODO: there have to be cleaner ways to test things. Maybe a CLI test framework?
@@ -1488,8 +1488,8 @@ mod tests {
     }
 
     async fn run_tool_with_args(args: &[&str]) -> CliResult {
-        let tool: Tool = Tool::try_parse_from(args).map_err(|msg| msg.to_string())?;
-        tool.execute().await
+        let cli: Cli = Cli::try_parse_from(args).map_err(|msg| msg.to_string())?;
+        cli.execute().await
     }
 
     fn assert_contains(message: String, expected_string: &str) {
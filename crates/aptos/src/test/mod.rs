@@ -852,7 +852,10 @@ impl CliTestFramework {
             filter: filter.map(|str| str.to_string()),
             ignore_compile_warnings: false,
             compute_coverage: false,
+            coverage_lcov: None,
+            coverage_html: None,
             dump_state: false,
+            profile_gas: None,
         }
         .execute()
         .await
@@ -3,7 +3,7 @@
 
 use crate::{
     move_tool::{ArgWithType, FunctionArgType},
-    CliResult, Tool,
+    Cli, CliResult,
 };
 use clap::Parser;
 use std::str::FromStr;
@@ -108,6 +108,33 @@ async fn ensure_every_command_args_work() {
     assert_cmd_not_panic(&["aptos", "stake", "withdraw-stake", "--help"]).await;
 }
 
+/// The results and errors printed with `--format yaml` have the same schema as the JSON ones
+#[tokio::test]
+async fn ensure_results_can_be_printed_as_yaml() {
+    let json = run_cmd(&["aptos", "info"]).await.unwrap();
+    let yaml = run_cmd(&["aptos", "--format", "yaml", "info"])
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let yaml: serde_json::Value = serde_yaml::from_str(&yaml).unwrap();
+    assert!(yaml["Result"].is_object());
+    assert_eq!(yaml, json);
+
+    // The flag is global, so it can also follow the subcommand
+    let args = ["aptos", "transaction", "decode", "--hex", "0x00"];
+    let json = run_cmd(&args).await.unwrap_err();
+    let yaml = run_cmd(&[&args[..], &["--format", "yaml"]].concat())
+        .await
+        .unwrap_err();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let yaml: serde_json::Value = serde_yaml::from_str(&yaml).unwrap();
+    assert!(yaml["Error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Unable to parse 'transaction'"));
+    assert_eq!(yaml, json);
+}
+
 /// Ensure we can parse URLs for args
 #[tokio::test]
 async fn ensure_can_parse_args_with_urls() {
@@ -141,6 +168,6 @@ async fn assert_cmd_not_panic(args: &[&str]) {
 }
 
 async fn run_cmd(args: &[&str]) -> CliResult {
-    let tool: Tool = Tool::try_parse_from(args).map_err(|msg| msg.to_string())?;
-    tool.execute().await
+    let cli: Cli = Cli::try_parse_from(args).map_err(|msg| msg.to_string())?;
+    cli.execute().await
 }
@@ -0,0 +1,411 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions},
+    utils::read_from_file,
+};
+use aptos_crypto::HashValue;
+use aptos_rest_client::{
+    aptos_api_types::{HexEncodedBytes, MoveType},
+    Client,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{
+        EntryFunction, MultisigTransactionPayload, RawTransaction, SignedTransaction,
+        TransactionPayload,
+    },
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_core_types::{
+    language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS},
+    value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
+};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A transaction or multisig payload decoded for review
+#[derive(Clone, Debug, Serialize)]
+pub struct DecodedTransaction {
+    /// One of `signed_transaction`, `raw_transaction` or `multisig_payload`
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<HashValue>,
+    #[serde(flatten)]
+    pub details: Option<TransactionDetails>,
+    pub payload: DecodedPayload,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TransactionDetails {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub chain_id: u8,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    /// The most the transaction can cost, in Octas
+    pub max_fee: u64,
+    pub expiration_timestamp_secs: u64,
+    pub expired: bool,
+}
+
+impl From<&RawTransaction> for TransactionDetails {
+    fn from(txn: &RawTransaction) -> Self {
+        let now_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or_default();
+        TransactionDetails {
+            sender: txn.sender(),
+            sequence_number: txn.sequence_number(),
+            chain_id: txn.chain_id().id(),
+            max_gas_amount: txn.max_gas_amount(),
+            gas_unit_price: txn.gas_unit_price(),
+            max_fee: txn.max_gas_amount().saturating_mul(txn.gas_unit_price()),
+            expiration_timestamp_secs: txn.expiration_timestamp_secs(),
+            expired: txn.expiration_timestamp_secs() <= now_secs,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DecodedPayload {
+    EntryFunction {
+        function: String,
+        type_args: Vec<String>,
+        args: Vec<DecodedArgument>,
+    },
+    Multisig {
+        multisig_address: AccountAddress,
+        /// The payload to execute, if it isn't stored on-chain with the proposal
+        transaction_payload: Option<Box<DecodedPayload>>,
+    },
+    Script {
+        code_hash: HashValue,
+        type_args: Vec<String>,
+    },
+    ModuleBundle,
+}
+
+/// An argument of an entry function, decoded with the type of its parameter if the ABI of the
+/// function was found, or left BCS encoded otherwise
+#[derive(Clone, Debug, Serialize)]
+pub struct DecodedArgument {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub arg_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bcs: Option<HexEncodedBytes>,
+}
+
+fn is_struct(struct_tag: &StructTag, module: &str, name: &str) -> bool {
+    struct_tag.address == CORE_CODE_ADDRESS
+        && struct_tag.module.as_str() == module
+        && struct_tag.name.as_str() == name
+}
+
+/// The type of a parameter of a function, with its generic type parameters replaced by the type
+/// arguments of the call
+fn resolve_type(move_type: &MoveType, ty_args: &[TypeTag]) -> Option<TypeTag> {
+    match move_type {
+        MoveType::GenericTypeParam { index } => ty_args.get(*index as usize).cloned(),
+        MoveType::Vector { items } => {
+            Some(TypeTag::Vector(Box::new(resolve_type(items, ty_args)?)))
+        },
+        MoveType::Struct(struct_tag) => Some(TypeTag::Struct(Box::new(StructTag {
+            address: *struct_tag.address.inner(),
+            module: struct_tag.module.0.clone(),
+            name: struct_tag.name.0.clone(),
+            type_params: struct_tag
+                .generic_type_params
+                .iter()
+                .map(|type_param| resolve_type(type_param, ty_args))
+                .collect::<Option<_>>()?,
+        }))),
+        move_type => TypeTag::try_from(move_type.clone()).ok(),
+    }
+}
+
+/// The layout of the types that can be passed to entry functions
+fn type_layout(type_tag: &TypeTag) -> Option<MoveTypeLayout> {
+    Some(match type_tag {
+        TypeTag::Bool => MoveTypeLayout::Bool,
+        TypeTag::U8 => MoveTypeLayout::U8,
+        TypeTag::U16 => MoveTypeLayout::U16,
+        TypeTag::U32 => MoveTypeLayout::U32,
+        TypeTag::U64 => MoveTypeLayout::U64,
+        TypeTag::U128 => MoveTypeLayout::U128,
+        TypeTag::U256 => MoveTypeLayout::U256,
+        TypeTag::Address => MoveTypeLayout::Address,
+        TypeTag::Vector(item) => MoveTypeLayout::Vector(Box::new(type_layout(item)?)),
+        TypeTag::Struct(struct_tag) if is_struct(struct_tag, "string", "String") => {
+            MoveTypeLayout::Struct(MoveStructLayout::Runtime(vec![MoveTypeLayout::Vector(
+                Box::new(MoveTypeLayout::U8),
+            )]))
+        },
+        TypeTag::Struct(struct_tag) if is_struct(struct_tag, "object", "Object") => {
+            MoveTypeLayout::Struct(MoveStructLayout::Runtime(vec![MoveTypeLayout::Address]))
+        },
+        TypeTag::Struct(struct_tag) if is_struct(struct_tag, "option", "Option") => {
+            MoveTypeLayout::Struct(MoveStructLayout::Runtime(vec![MoveTypeLayout::Vector(
+                Box::new(type_layout(struct_tag.type_params.first()?)?),
+            )]))
+        },
+        _ => return None,
+    })
+}
+
+fn vector_u8(values: &[MoveValue]) -> Option<Vec<u8>> {
+    values
+        .iter()
+        .map(|value| match value {
+            MoveValue::U8(byte) => Some(*byte),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Converts a value to JSON as the API does, with the large integers as strings and the bytes as
+/// hex
+fn value_to_json(value: &MoveValue, type_tag: &TypeTag) -> Option<serde_json::Value> {
+    Some(match (value, type_tag) {
+        (MoveValue::Bool(value), _) => json!(value),
+        (MoveValue::U8(value), _) => json!(value),
+        (MoveValue::U16(value), _) => json!(value),
+        (MoveValue::U32(value), _) => json!(value),
+        (MoveValue::U64(value), _) => json!(value.to_string()),
+        (MoveValue::U128(value), _) => json!(value.to_string()),
+        (MoveValue::U256(value), _) => json!(value.to_string()),
+        (MoveValue::Address(address), _) => json!(address.to_hex_literal()),
+        (MoveValue::Vector(values), TypeTag::Vector(item)) if **item == TypeTag::U8 => {
+            json!(HexEncodedBytes::from(vector_u8(values)?).to_string())
+        },
+        (MoveValue::Vector(values), TypeTag::Vector(item)) => serde_json::Value::Array(
+            values
+                .iter()
+                .map(|value| value_to_json(value, item))
+                .collect::<Option<_>>()?,
+        ),
+        (MoveValue::Struct(MoveStruct::Runtime(fields)), TypeTag::Struct(struct_tag)) => {
+            match fields.as_slice() {
+                [MoveValue::Vector(values)] if is_struct(struct_tag, "string", "String") => {
+                    json!(String::from_utf8(vector_u8(values)?).ok()?)
+                },
+                [address] if is_struct(struct_tag, "object", "Object") => {
+                    value_to_json(address, &TypeTag::Address)?
+                },
+                [MoveValue::Vector(values)] if is_struct(struct_tag, "option", "Option") => {
+                    match values.first() {
+                        Some(value) => value_to_json(value, struct_tag.type_params.first()?)?,
+                        None => serde_json::Value::Null,
+                    }
+                },
+                _ => return None,
+            }
+        },
+        _ => return None,
+    })
+}
+
+fn decode_argument(arg: &[u8], type_tag: Option<TypeTag>) -> DecodedArgument {
+    let value = type_tag.as_ref().and_then(|type_tag| {
+        let value = MoveValue::simple_deserialize(arg, &type_layout(type_tag)?).ok()?;
+        value_to_json(&value, type_tag)
+    });
+    DecodedArgument {
+        arg_type: type_tag.map(|type_tag| type_tag.to_string()),
+        bcs: value.is_none().then(|| HexEncodedBytes::from(arg.to_vec())),
+        value,
+    }
+}
+
+/// The types of the non-signer parameters of the function, from the ABI of its module
+async fn fetch_param_types(
+    client: &Client,
+    entry_function: &EntryFunction,
+) -> Option<Vec<Option<TypeTag>>> {
+    let module_id = entry_function.module();
+    let module = client
+        .get_account_module(*module_id.address(), module_id.name().as_str())
+        .await
+        .ok()?
+        .into_inner()
+        .try_parse_abi()
+        .ok()?;
+    let function = module
+        .abi?
+        .exposed_functions
+        .into_iter()
+        .find(|function| function.name.0.as_ident_str() == entry_function.function())?;
+    Some(
+        function
+            .params
+            .iter()
+            .filter(|param| match param {
+                MoveType::Signer => false,
+                MoveType::Reference { to, .. } => !matches!(**to, MoveType::Signer),
+                _ => true,
+            })
+            .map(|param| resolve_type(param, entry_function.ty_args()))
+            .collect(),
+    )
+}
+
+async fn decode_entry_function(
+    entry_function: &EntryFunction,
+    client: Option<&Client>,
+) -> DecodedPayload {
+    let param_types = match client {
+        Some(client) => fetch_param_types(client, entry_function).await,
+        None => None,
+    };
+    DecodedPayload::EntryFunction {
+        function: format!(
+            "{}::{}",
+            entry_function.module().short_str_lossless(),
+            entry_function.function()
+        ),
+        type_args: entry_function
+            .ty_args()
+            .iter()
+            .map(|type_arg| type_arg.to_string())
+            .collect(),
+        args: entry_function
+            .args()
+            .iter()
+            .enumerate()
+            .map(|(index, arg)| {
+                let type_tag = param_types
+                    .as_ref()
+                    .and_then(|param_types| param_types.get(index).cloned().flatten());
+                decode_argument(arg, type_tag)
+            })
+            .collect(),
+    }
+}
+
+/// Decodes a payload, with the arguments of its entry function left BCS encoded if there is no
+/// client to fetch the ABI of the function with
+pub(crate) async fn decode_payload(
+    payload: &TransactionPayload,
+    client: Option<&Client>,
+) -> DecodedPayload {
+    match payload {
+        TransactionPayload::EntryFunction(entry_function) => {
+            decode_entry_function(entry_function, client).await
+        },
+        TransactionPayload::Multisig(multisig) => DecodedPayload::Multisig {
+            multisig_address: multisig.multisig_address,
+            transaction_payload: match &multisig.transaction_payload {
+                Some(MultisigTransactionPayload::EntryFunction(entry_function)) => Some(Box::new(
+                    decode_entry_function(entry_function, client).await,
+                )),
+                None => None,
+            },
+        },
+        TransactionPayload::Script(script) => DecodedPayload::Script {
+            code_hash: HashValue::sha3_256_of(script.code()),
+            type_args: script
+                .ty_args()
+                .iter()
+                .map(|type_arg| type_arg.to_string())
+                .collect(),
+        },
+        TransactionPayload::ModuleBundle(_) => DecodedPayload::ModuleBundle,
+    }
+}
+
+/// Decode a BCS encoded transaction to review it before signing or submitting it.
+///
+/// The input is either a raw transaction, as saved by `aptos transaction build`, a signed
+/// transaction, as saved by `aptos transaction sign`, or a multisig transaction payload, as
+/// stored on-chain with a proposal. The arguments of the entry functions are decoded with the ABI
+/// of their module fetched from the network, and are left BCS encoded if it can't be fetched.
+#[derive(Debug, Parser)]
+pub struct Decode {
+    /// The BCS encoded transaction or payload, as hex
+    #[clap(long, required_unless_present = "input", conflicts_with = "input")]
+    pub(crate) hex: Option<String>,
+    /// File with the BCS encoded transaction or payload, as bytes or as hex
+    #[clap(long, value_parser)]
+    pub(crate) input: Option<PathBuf>,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+impl Decode {
+    fn read_input(&self) -> CliTypedResult<Vec<u8>> {
+        match (&self.hex, &self.input) {
+            (Some(hex), _) => Ok(hex.trim().parse::<HexEncodedBytes>()?.into()),
+            (None, Some(input)) => {
+                let bytes = read_from_file(input)?;
+                // The payloads saved by `aptos multisig export-payload` are hex encoded
+                match std::str::from_utf8(&bytes)
+                    .ok()
+                    .and_then(|hex| hex.trim().parse::<HexEncodedBytes>().ok())
+                {
+                    Some(hex) => Ok(hex.into()),
+                    None => Ok(bytes),
+                }
+            },
+            (None, None) => Err(CliError::CommandArgumentError(
+                "One of ['--hex', '--input'] must be used".to_string(),
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl CliCommand<DecodedTransaction> for Decode {
+    fn command_name(&self) -> &'static str {
+        "DecodeTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<DecodedTransaction> {
+        let bytes = self.read_input()?;
+        let client = self.rest_options.client(&self.profile_options).ok();
+        let client = client.as_ref();
+
+        if let Ok(signed_transaction) = bcs::from_bytes::<SignedTransaction>(&bytes) {
+            let raw_transaction = signed_transaction.raw_transaction_ref();
+            Ok(DecodedTransaction {
+                kind: "signed_transaction",
+                transaction_hash: Some(signed_transaction.clone().committed_hash()),
+                details: Some(TransactionDetails::from(raw_transaction)),
+                payload: decode_payload(raw_transaction.payload(), client).await,
+            })
+        } else if let Ok(raw_transaction) = bcs::from_bytes::<RawTransaction>(&bytes) {
+            Ok(DecodedTransaction {
+                kind: "raw_transaction",
+                transaction_hash: None,
+                details: Some(TransactionDetails::from(&raw_transaction)),
+                payload: decode_payload(raw_transaction.payload(), client).await,
+            })
+        } else if let Ok(MultisigTransactionPayload::EntryFunction(entry_function)) =
+            bcs::from_bytes::<MultisigTransactionPayload>(&bytes)
+        {
+            Ok(DecodedTransaction {
+                kind: "multisig_payload",
+                transaction_hash: None,
+                details: None,
+                payload: decode_entry_function(&entry_function, client).await,
+            })
+        } else {
+            Err(CliError::UnableToParse(
+                "transaction",
+                "expected a BCS encoded signed transaction, raw transaction or multisig payload"
+                    .to_string(),
+            ))
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliResult};
+use clap::Subcommand;
+
+pub mod decode;
+pub mod offline;
+
+/// Tool for building, signing and submitting transactions in separate steps
+///
+/// This allows a transaction to be built on a machine connected to the network, signed on an
+/// air-gapped machine holding the private key, and submitted later.
+#[derive(Debug, Subcommand)]
+pub enum TransactionTool {
+    Build(offline::Build),
+    Decode(decode::Decode),
+    Sign(offline::Sign),
+    Submit(offline::Submit),
+}
+
+impl TransactionTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            TransactionTool::Build(tool) => tool.execute_serialized().await,
+            TransactionTool::Decode(tool) => tool.execute_serialized().await,
+            TransactionTool::Sign(tool) => tool.execute_serialized().await,
+            TransactionTool::Submit(tool) => tool.execute_serialized().await,
+        }
+    }
+}
@@ -0,0 +1,314 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            CliCommand, CliError, CliTypedResult, EncodingOptions, EntryFunctionArguments,
+            GasOptions, PrivateKeyInputOptions, ProfileOptions, PromptOptions, RestOptions,
+            SaveFile, TransactionSummary,
+        },
+        utils::{get_account_with_state, prompt_yes_with_override, read_from_file},
+    },
+    transaction::decode::decode_payload,
+};
+use aptos_crypto::{ed25519::Ed25519Signature, HashValue, PrivateKey};
+use aptos_global_constants::adjust_gas_headroom;
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{RawTransaction, SignedTransaction, TransactionPayload},
+};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A transaction built or signed offline
+#[derive(Clone, Debug, Serialize)]
+pub struct OfflineTransactionSummary {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub chain_id: u8,
+    /// The function called by the transaction
+    pub payload: String,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    pub expiration_timestamp_secs: u64,
+    /// The hash of the signed transaction, to look it up once submitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<HashValue>,
+}
+
+fn describe_payload(payload: &TransactionPayload) -> String {
+    match payload {
+        TransactionPayload::EntryFunction(entry_function) => format!(
+            "{}::{}",
+            entry_function.module().short_str_lossless(),
+            entry_function.function()
+        ),
+        TransactionPayload::Script(_) => "script".to_string(),
+        TransactionPayload::ModuleBundle(_) => "module bundle".to_string(),
+        TransactionPayload::Multisig(multisig) => format!(
+            "multisig transaction of {}",
+            multisig.multisig_address.to_hex_literal()
+        ),
+    }
+}
+
+impl From<&SignedTransaction> for OfflineTransactionSummary {
+    fn from(txn: &SignedTransaction) -> Self {
+        OfflineTransactionSummary {
+            sender: txn.sender(),
+            sequence_number: txn.sequence_number(),
+            chain_id: txn.chain_id().id(),
+            payload: describe_payload(txn.payload()),
+            max_gas_amount: txn.max_gas_amount(),
+            gas_unit_price: txn.gas_unit_price(),
+            expiration_timestamp_secs: txn.expiration_timestamp_secs(),
+            transaction_hash: Some(txn.clone().committed_hash()),
+        }
+    }
+}
+
+fn load_bcs_file<T: serde::de::DeserializeOwned>(
+    path: &Path,
+    name: &'static str,
+) -> CliTypedResult<T> {
+    bcs::from_bytes(&read_from_file(path)?).map_err(|err| CliError::BCS(name, err))
+}
+
+/// Build a transaction and save it to a file without signing it.
+///
+/// The sequence number of the sender, the chain ID and the gas unit price are read from the
+/// network. The transaction expires `--expiration-secs` after it's built, which has to leave
+/// enough time to sign and submit it.
+#[derive(Debug, Parser)]
+pub struct Build {
+    /// Sender account address
+    ///
+    /// Defaults to the account of the profile
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) sender_account: Option<AccountAddress>,
+    /// Sequence number of the transaction
+    ///
+    /// Defaults to the current sequence number of the sender. Set it to build several
+    /// transactions to sign at once.
+    #[clap(long)]
+    pub(crate) sequence_number: Option<u64>,
+    #[clap(flatten)]
+    pub(crate) entry_function_args: EntryFunctionArguments,
+    #[clap(flatten)]
+    pub(crate) gas_options: GasOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    /// File to save the BCS encoded raw transaction to
+    #[clap(long, value_parser)]
+    pub(crate) output: PathBuf,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<OfflineTransactionSummary> for Build {
+    fn command_name(&self) -> &'static str {
+        "BuildTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<OfflineTransactionSummary> {
+        let save_file = SaveFile {
+            output_file: self.output,
+            prompt_options: self.prompt_options,
+        };
+        save_file.check_file()?;
+
+        let client = self.rest_options.client(&self.profile_options)?;
+        let sender = match self.sender_account {
+            Some(sender) => sender,
+            None => self.profile_options.account_address()?,
+        };
+        let (account, state) = get_account_with_state(&client, sender).await?;
+        let chain_id = ChainId::new(state.chain_id);
+        let sequence_number = self.sequence_number.unwrap_or(account.sequence_number);
+        let gas_unit_price = match self.gas_options.gas_unit_price {
+            Some(gas_unit_price) => gas_unit_price,
+            None => client.estimate_gas_price().await?.into_inner().gas_estimate,
+        };
+        let expiration_timestamp_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+            .as_secs()
+            + self.gas_options.expiration_secs;
+        let payload = TransactionPayload::EntryFunction(self.entry_function_args.try_into()?);
+
+        let max_gas_amount = if let Some(max_gas) = self.gas_options.max_gas {
+            max_gas
+        } else {
+            // Without the private key, the transaction is simulated with the public key of the
+            // profile to estimate its gas
+            let public_key = self.profile_options.public_key().map_err(|_| {
+                CliError::CommandArgumentError(
+                    "--max-gas is required when the profile has no public key to simulate the \
+                    transaction with"
+                        .to_string(),
+                )
+            })?;
+            let signed_transaction = SignedTransaction::new(
+                RawTransaction::new(
+                    sender,
+                    sequence_number,
+                    payload.clone(),
+                    0,
+                    gas_unit_price,
+                    expiration_timestamp_secs,
+                    chain_id,
+                ),
+                public_key,
+                Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+            );
+            let txns = client
+                .simulate_with_gas_estimation(&signed_transaction, true, false)
+                .await?
+                .into_inner();
+            let simulated_txn = txns.first().unwrap();
+            if !simulated_txn.info.success {
+                return Err(CliError::SimulationError(
+                    simulated_txn.info.vm_status.clone(),
+                ));
+            }
+            adjust_gas_headroom(
+                simulated_txn.info.gas_used.0,
+                simulated_txn.request.max_gas_amount.0,
+            )
+        };
+
+        let raw_transaction = RawTransaction::new(
+            sender,
+            sequence_number,
+            payload.clone(),
+            max_gas_amount,
+            gas_unit_price,
+            expiration_timestamp_secs,
+            chain_id,
+        );
+        save_file.save_to_file("Raw transaction", &bcs::to_bytes(&raw_transaction)?)?;
+        Ok(OfflineTransactionSummary {
+            sender,
+            sequence_number,
+            chain_id: chain_id.id(),
+            payload: describe_payload(&payload),
+            max_gas_amount,
+            gas_unit_price,
+            expiration_timestamp_secs,
+            transaction_hash: None,
+        })
+    }
+}
+
+/// Sign a transaction saved by `aptos transaction build`.
+///
+/// This doesn't need the network, so that the transaction can be signed on an air-gapped
+/// machine. The transaction is shown and has to be confirmed before it's signed. The arguments
+/// of the function are decoded if the ABI of its module can be fetched from the network, and are
+/// shown BCS encoded otherwise. Hardware wallets aren't supported yet.
+#[derive(Debug, Parser)]
+pub struct Sign {
+    /// File with the BCS encoded raw transaction
+    #[clap(long, value_parser)]
+    pub(crate) input: PathBuf,
+    /// File to save the BCS encoded signed transaction to
+    #[clap(long, value_parser)]
+    pub(crate) output: PathBuf,
+    #[clap(flatten)]
+    pub(crate) private_key_options: PrivateKeyInputOptions,
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<OfflineTransactionSummary> for Sign {
+    fn command_name(&self) -> &'static str {
+        "SignTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<OfflineTransactionSummary> {
+        let save_file = SaveFile {
+            output_file: self.output,
+            prompt_options: self.prompt_options,
+        };
+        save_file.check_file()?;
+
+        let raw_transaction: RawTransaction = load_bcs_file(&self.input, "RawTransaction")?;
+        let private_key = self
+            .private_key_options
+            .extract_private_key(self.encoding_options.encoding, &self.profile_options)?;
+        let signed_transaction = raw_transaction
+            .sign(&private_key, private_key.public_key())?
+            .into_inner();
+        let summary = OfflineTransactionSummary::from(&signed_transaction);
+        let client = self.rest_options.client(&self.profile_options).ok();
+        let payload = serde_json::to_string_pretty(
+            &decode_payload(signed_transaction.payload(), client.as_ref()).await,
+        )
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        // The transaction file isn't trusted, so the fee is computed without overflowing
+        let max_fee = u128::from(summary.max_gas_amount) * u128::from(summary.gas_unit_price);
+        prompt_yes_with_override(
+            &format!(
+                "{}\nDo you want to sign {} from {} with sequence number {} on chain {} for a \
+                maximum of {} Octas at a gas unit price of {} Octas?",
+                payload,
+                summary.payload,
+                summary.sender.to_hex_literal(),
+                summary.sequence_number,
+                summary.chain_id,
+                max_fee,
+                summary.gas_unit_price
+            ),
+            self.prompt_options,
+        )?;
+        save_file.save_to_file("Signed transaction", &bcs::to_bytes(&signed_transaction)?)?;
+        Ok(summary)
+    }
+}
+
+/// Submit a transaction saved by `aptos transaction sign`, and wait for it to be committed.
+#[derive(Debug, Parser)]
+pub struct Submit {
+    /// File with the BCS encoded signed transaction
+    #[clap(long, value_parser)]
+    pub(crate) signed: PathBuf,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for Submit {
+    fn command_name(&self) -> &'static str {
+        "SubmitTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let signed_transaction: SignedTransaction =
+            load_bcs_file(&self.signed, "SignedTransaction")?;
+        let client = self.rest_options.client(&self.profile_options)?;
+        let response = client
+            .submit_and_wait(&signed_transaction)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        Ok(TransactionSummary::from(response.into_inner()))
+    }
+}
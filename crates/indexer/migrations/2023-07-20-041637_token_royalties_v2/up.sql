@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- royalties of token v2 collections and tokens, stored in 0x4::royalty::Royalty
+CREATE TABLE IF NOT EXISTS current_token_royalties_v2 (
+  object_address VARCHAR(66) UNIQUE PRIMARY KEY NOT NULL,
+  payee_address VARCHAR(66) NOT NULL,
+  royalty_points_numerator NUMERIC NOT NULL,
+  royalty_points_denominator NUMERIC NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS ctr2_payee_index ON current_token_royalties_v2 (payee_address);
+CREATE INDEX IF NOT EXISTS ctr2_insat_index ON current_token_royalties_v2 (inserted_at);
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE ledger_infos DROP COLUMN IF EXISTS genesis_hash;
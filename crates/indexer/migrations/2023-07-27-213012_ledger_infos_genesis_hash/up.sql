@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- identifies the chain together with the chain id, as a chain can be wiped and restarted with the same id
+ALTER TABLE ledger_infos
+ADD COLUMN IF NOT EXISTS genesis_hash VARCHAR(66);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS fungible_asset_metadata;
+DROP TABLE IF EXISTS fungible_asset_balances;
+DROP TABLE IF EXISTS current_fungible_asset_balances;
+DROP TABLE IF EXISTS fungible_asset_activities;
+DROP TABLE IF EXISTS fungible_asset_supply;
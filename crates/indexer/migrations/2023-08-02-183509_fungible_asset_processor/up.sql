@@ -0,0 +1,75 @@
+-- Your SQL goes here
+-- metadata of fungible assets, stored in 0x1::fungible_asset::Metadata
+CREATE TABLE IF NOT EXISTS fungible_asset_metadata (
+  asset_type VARCHAR(66) UNIQUE PRIMARY KEY NOT NULL,
+  creator_address VARCHAR(66) NOT NULL,
+  name VARCHAR(32) NOT NULL,
+  symbol VARCHAR(10) NOT NULL,
+  decimals INT NOT NULL,
+  icon_uri VARCHAR(512) NOT NULL,
+  project_uri VARCHAR(512) NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS fam_creator_index ON fungible_asset_metadata (creator_address);
+CREATE INDEX IF NOT EXISTS fam_insat_index ON fungible_asset_metadata (inserted_at);
+-- balances of fungible stores, stored in 0x1::fungible_asset::FungibleStore
+CREATE TABLE IF NOT EXISTS fungible_asset_balances (
+  transaction_version BIGINT NOT NULL,
+  write_set_change_index BIGINT NOT NULL,
+  storage_id VARCHAR(66) NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  asset_type VARCHAR(66) NOT NULL,
+  is_frozen BOOLEAN NOT NULL,
+  amount NUMERIC NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  -- Constraints
+  PRIMARY KEY (transaction_version, write_set_change_index)
+);
+CREATE INDEX IF NOT EXISTS fab_owner_at_index ON fungible_asset_balances (owner_address, asset_type);
+CREATE INDEX IF NOT EXISTS fab_insat_index ON fungible_asset_balances (inserted_at);
+CREATE TABLE IF NOT EXISTS current_fungible_asset_balances (
+  storage_id VARCHAR(66) UNIQUE PRIMARY KEY NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  asset_type VARCHAR(66) NOT NULL,
+  is_frozen BOOLEAN NOT NULL,
+  amount NUMERIC NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS cfab_owner_at_index ON current_fungible_asset_balances (owner_address, asset_type);
+CREATE INDEX IF NOT EXISTS cfab_insat_index ON current_fungible_asset_balances (inserted_at);
+-- deposits to and withdrawals from fungible stores
+CREATE TABLE IF NOT EXISTS fungible_asset_activities (
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  storage_id VARCHAR(66) NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  asset_type VARCHAR(66) NOT NULL,
+  amount NUMERIC NOT NULL,
+  type VARCHAR NOT NULL,
+  entry_function_id_str VARCHAR(100),
+  block_height BIGINT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  -- Constraints
+  PRIMARY KEY (transaction_version, event_index)
+);
+CREATE INDEX IF NOT EXISTS faa_owner_at_index ON fungible_asset_activities (owner_address, asset_type);
+CREATE INDEX IF NOT EXISTS faa_at_index ON fungible_asset_activities (asset_type);
+CREATE INDEX IF NOT EXISTS faa_insat_index ON fungible_asset_activities (inserted_at);
+-- supply of fungible assets, stored in 0x1::fungible_asset::Supply
+CREATE TABLE IF NOT EXISTS fungible_asset_supply (
+  transaction_version BIGINT NOT NULL,
+  asset_type VARCHAR(66) NOT NULL,
+  current_supply NUMERIC NOT NULL,
+  maximum_supply NUMERIC,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  -- Constraints
+  PRIMARY KEY (transaction_version, asset_type)
+);
+CREATE INDEX IF NOT EXISTS fas_insat_index ON fungible_asset_supply (inserted_at);
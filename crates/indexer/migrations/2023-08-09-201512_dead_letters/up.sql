@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- transactions that a processor failed on, and skipped so that it doesn't stall
+CREATE TABLE IF NOT EXISTS dead_letters (
+  processor VARCHAR(50) NOT NULL,
+  transaction_version BIGINT NOT NULL,
+  error TEXT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  -- Constraints
+  PRIMARY KEY (processor, transaction_version)
+);
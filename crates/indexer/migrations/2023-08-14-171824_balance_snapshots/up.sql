@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- balance of a coin store or fungible store at the end of each interval it changed in, e.g. hourly
+CREATE TABLE IF NOT EXISTS balance_snapshots (
+  -- the owner address for coins, as the coin stores live at the owner
+  storage_id VARCHAR(66) NOT NULL,
+  asset_type_hash VARCHAR(64) NOT NULL,
+  -- start of the interval
+  snapshot_at TIMESTAMP NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  asset_type VARCHAR(5000) NOT NULL,
+  amount NUMERIC NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  -- Constraints
+  PRIMARY KEY (storage_id, asset_type_hash, snapshot_at)
+);
+CREATE INDEX IF NOT EXISTS bs_owner_type_at_index ON balance_snapshots (owner_address, asset_type_hash, snapshot_at);
+CREATE INDEX IF NOT EXISTS bs_insat_index ON balance_snapshots (inserted_at);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS ev_ooa_index;
+ALTER TABLE events DROP COLUMN IF EXISTS object_owner_address;
+DROP TABLE IF EXISTS object_metadata;
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- owner of the object at the time of the event, for the events emitted by objects
+ALTER TABLE events
+ADD COLUMN IF NOT EXISTS object_owner_address VARCHAR(66);
+CREATE INDEX IF NOT EXISTS ev_ooa_index ON events (object_owner_address);
+-- who created each object, i.e. the sender of the first transaction writing it
+CREATE TABLE IF NOT EXISTS object_metadata (
+  object_address VARCHAR(66) UNIQUE PRIMARY KEY NOT NULL,
+  creator_address VARCHAR(66) NOT NULL,
+  first_transaction_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS om_creator_index ON object_metadata (creator_address);
+CREATE INDEX IF NOT EXISTS om_insat_index ON object_metadata (inserted_at);
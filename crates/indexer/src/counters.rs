@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    register_int_counter, register_int_counter_vec, register_int_gauge_vec, IntCounter,
-    IntCounterVec, IntGaugeVec,
+    register_gauge, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, Gauge, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -37,6 +37,17 @@ pub static PROCESSOR_SUCCESSES: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of transactions any given processor failed on and skipped, after recording them as
+/// dead letters
+pub static PROCESSOR_DEAD_LETTERS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_dead_letter_count",
+        "Number of transactions a given processor failed on and skipped",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
 /// Number of times the connection pool has timed out when trying to get a connection
 pub static UNABLE_TO_GET_CONNECTION: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -73,6 +84,33 @@ pub static FETCHED_TRANSACTION: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of times the indexer retried fetching transactions from the node
+pub static FETCH_RETRIES: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "indexer_fetch_retry_count",
+        "Number of times the indexer retried fetching transactions"
+    )
+    .unwrap()
+});
+
+/// How many retries the fetcher can still do before it has to fetch successfully again
+pub static FETCH_RETRY_BUDGET: Lazy<Gauge> = Lazy::new(|| {
+    register_gauge!(
+        "indexer_fetch_retry_budget",
+        "How many retries the fetcher can still do"
+    )
+    .unwrap()
+});
+
+/// How many batches of transactions the fetcher currently fetches concurrently
+pub static FETCH_CONCURRENCY: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "indexer_fetch_concurrency",
+        "How many batches of transactions the fetcher fetches concurrently"
+    )
+    .unwrap()
+});
+
 /// Max version processed
 pub static LATEST_PROCESSED_VERSION: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -82,3 +120,22 @@ pub static LATEST_PROCESSED_VERSION: Lazy<IntGaugeVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// How many versions a processor is behind the node
+pub static PROCESSOR_LAG_VERSIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_processor_lag_versions",
+        "How many versions a processor is behind the node",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of rows written to Postgres
+pub static ROWS_WRITTEN: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "indexer_rows_written_count",
+        "Number of rows written to Postgres"
+    )
+    .unwrap()
+});
@@ -3,7 +3,7 @@
 
 //! Database-related functions
 #![allow(clippy::extra_unused_lifetimes)]
-use crate::util::remove_null_bytes;
+use crate::{counters::ROWS_WRITTEN, util::remove_null_bytes};
 use diesel::{
     pg::{Pg, PgConnection},
     query_builder::{AstPass, Query, QueryFragment},
@@ -82,8 +82,9 @@ where
     let debug = diesel::debug_query::<diesel::pg::Pg, _>(&final_query).to_string();
     aptos_logger::debug!("Executing query: {:?}", debug);
     let res = final_query.execute(conn);
-    if let Err(ref e) = res {
-        aptos_logger::warn!("Error running query: {:?}\n{}", e, debug);
+    match res {
+        Ok(rows) => ROWS_WRITTEN.inc_by(rows as u64),
+        Err(ref e) => aptos_logger::warn!("Error running query: {:?}\n{}", e, debug),
     }
     res
 }
@@ -0,0 +1,186 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports the health and progress of the processors, both as Prometheus metrics and through an
+//! optional HTTP endpoint, so that operators can tell whether a processor has stalled.
+
+use crate::counters::{PROCESSOR_ERRORS, PROCESSOR_LAG_VERSIONS};
+use aptos_logger::info;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+pub const HEALTH_PATH: &str = "/health";
+/// A processor behind the node that didn't make progress for this long is reported as stalled
+pub const STALL_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// What the health endpoint reports for a processor
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ProcessorHealth {
+    pub processor_name: String,
+    pub last_processed_version: Option<u64>,
+    pub node_latest_version: u64,
+    pub lag_versions: u64,
+    pub error_count: u64,
+    pub versions_per_second: f64,
+    pub rows_written_per_second: f64,
+    pub seconds_since_last_progress: u64,
+    pub stalled: bool,
+}
+
+struct ProcessorProgress {
+    last_processed_version: Option<u64>,
+    node_latest_version: u64,
+    versions_per_second: f64,
+    rows_written_per_second: f64,
+    last_progress: Instant,
+}
+
+impl ProcessorProgress {
+    fn lag_versions(&self) -> u64 {
+        match self.last_processed_version {
+            Some(version) => self.node_latest_version.saturating_sub(version),
+            // Nothing is processed yet, versions 0 to node_latest_version are left
+            None => self.node_latest_version + 1,
+        }
+    }
+}
+
+/// Tracks the progress of the processors running in this indexer
+#[derive(Clone, Default)]
+pub struct HealthReporter {
+    processors: Arc<RwLock<BTreeMap<String, ProcessorProgress>>>,
+}
+
+impl HealthReporter {
+    pub fn update(
+        &self,
+        processor_name: &str,
+        last_processed_version: Option<u64>,
+        node_latest_version: u64,
+        versions_per_second: f64,
+        rows_written_per_second: f64,
+    ) {
+        let mut processors = self.processors.write().unwrap();
+        let progress = processors
+            .entry(processor_name.to_string())
+            .or_insert_with(|| ProcessorProgress {
+                last_processed_version,
+                node_latest_version,
+                versions_per_second,
+                rows_written_per_second,
+                last_progress: Instant::now(),
+            });
+        if progress.last_processed_version != last_processed_version {
+            progress.last_processed_version = last_processed_version;
+            progress.last_progress = Instant::now();
+        }
+        progress.node_latest_version = node_latest_version;
+        progress.versions_per_second = versions_per_second;
+        progress.rows_written_per_second = rows_written_per_second;
+
+        PROCESSOR_LAG_VERSIONS
+            .with_label_values(&[processor_name])
+            .set(progress.lag_versions() as i64);
+    }
+
+    pub fn report(&self) -> Vec<ProcessorHealth> {
+        self.processors
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(processor_name, progress)| {
+                let lag_versions = progress.lag_versions();
+                let since_last_progress = progress.last_progress.elapsed();
+                ProcessorHealth {
+                    processor_name: processor_name.clone(),
+                    last_processed_version: progress.last_processed_version,
+                    node_latest_version: progress.node_latest_version,
+                    lag_versions,
+                    error_count: PROCESSOR_ERRORS.with_label_values(&[processor_name]).get(),
+                    versions_per_second: progress.versions_per_second,
+                    rows_written_per_second: progress.rows_written_per_second,
+                    seconds_since_last_progress: since_last_progress.as_secs(),
+                    stalled: lag_versions > 0 && since_last_progress > STALL_THRESHOLD,
+                }
+            })
+            .collect()
+    }
+
+    /// Serves the health of the processors at `HEALTH_PATH`, as JSON. The status is 503 if any
+    /// processor is stalled.
+    pub async fn serve(self, address: SocketAddr) {
+        info!("Starting indexer health endpoint at {}...", address);
+        let make_service = make_service_fn(move |_conn| {
+            let health_reporter = self.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let health_reporter = health_reporter.clone();
+                    async move { Ok::<_, Infallible>(health_reporter.handle_request(request)) }
+                }))
+            }
+        });
+        Server::bind(&address)
+            .serve(make_service)
+            .await
+            .expect("Indexer health endpoint failed");
+    }
+
+    fn handle_request(&self, request: Request<Body>) -> Response<Body> {
+        let (status_code, body) = if request.uri().path() == HEALTH_PATH {
+            let report = self.report();
+            let status_code = if report.iter().any(|health| health.stalled) {
+                StatusCode::SERVICE_UNAVAILABLE
+            } else {
+                StatusCode::OK
+            };
+            (
+                status_code,
+                serde_json::to_string_pretty(&report).expect("Health must serialize to JSON"),
+            )
+        } else {
+            (StatusCode::NOT_FOUND, "Not found".to_string())
+        };
+        Response::builder()
+            .status(status_code)
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let health_reporter = HealthReporter::default();
+        health_reporter.update("test_health_processor", None, 9, 0.0, 0.0);
+        health_reporter.update("test_health_processor", Some(4), 10, 5.0, 20.0);
+
+        let report = health_reporter.report();
+        assert_eq!(report.len(), 1);
+        let health = &report[0];
+        assert_eq!(health.last_processed_version, Some(4));
+        assert_eq!(health.node_latest_version, 10);
+        assert_eq!(health.lag_versions, 6);
+        assert_eq!(health.rows_written_per_second, 20.0);
+        assert!(!health.stalled);
+        assert_eq!(
+            health_reporter
+                .handle_request(Request::get(HEALTH_PATH).body(Body::empty()).unwrap())
+                .status(),
+            StatusCode::OK
+        );
+    }
+}
@@ -13,6 +13,8 @@ pub enum TransactionProcessingError {
     ConnectionPoolError(ErrorWithVersionAndName),
     /// Could not commit the transaction
     TransactionCommitError(ErrorWithVersionAndName),
+    /// Could not publish the records to the sink
+    SinkPublishError(ErrorWithVersionAndName),
 }
 
 impl TransactionProcessingError {
@@ -20,6 +22,7 @@ impl TransactionProcessingError {
         match self {
             TransactionProcessingError::ConnectionPoolError(ewv) => ewv,
             TransactionProcessingError::TransactionCommitError(ewv) => ewv,
+            TransactionProcessingError::SinkPublishError(ewv) => ewv,
         }
     }
 }
@@ -1,7 +1,13 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::counters::{FETCHED_TRANSACTION, UNABLE_TO_FETCH_TRANSACTION};
+use crate::{
+    counters::{
+        FETCHED_TRANSACTION, FETCH_CONCURRENCY, FETCH_RETRIES, UNABLE_TO_FETCH_TRANSACTION,
+    },
+    indexer::retry_budget::{backoff, RetryBudget},
+};
+use anyhow::{ensure, Context as AnyhowContext, Result};
 use aptos_api::Context;
 use aptos_api_types::{AsConverter, LedgerInfo, Transaction, TransactionOnChainData};
 use aptos_logger::prelude::*;
@@ -15,6 +21,9 @@ const RETRY_TIME_MILLIS: u64 = 300;
 const MAX_RETRY_TIME_MILLIS: u64 = 120000;
 const TRANSACTION_FETCH_BATCH_SIZE: u16 = 500;
 const TRANSACTION_CHANNEL_SIZE: usize = 35;
+// Fraction of a retry earned by each successful fetch, and the most retries that can be saved up
+const RETRY_BUDGET_DEPOSIT_RATIO: f64 = 0.1;
+const RETRY_BUDGET_MAX_RETRIES: f64 = 20.0;
 
 #[derive(Debug)]
 pub struct Fetcher {
@@ -24,6 +33,10 @@ pub struct Fetcher {
     current_version: u64,
     highest_known_version: u64,
     transactions_sender: mpsc::Sender<Vec<Transaction>>,
+    retry_budget: Arc<RetryBudget>,
+    /// How many batches are fetched concurrently, up to `options.max_tasks`. Halved when a fetch
+    /// fails and increased by one when they all succeed.
+    concurrency: usize,
 }
 
 impl Fetcher {
@@ -32,7 +45,10 @@ impl Fetcher {
         starting_version: u64,
         options: TransactionFetcherOptions,
         transactions_sender: mpsc::Sender<Vec<Transaction>>,
+        retry_budget: Arc<RetryBudget>,
     ) -> Self {
+        let concurrency = options.max_tasks;
+        FETCH_CONCURRENCY.set(concurrency as i64);
         Self {
             context,
             options,
@@ -40,12 +56,17 @@ impl Fetcher {
             current_version: starting_version,
             highest_known_version: 0,
             transactions_sender,
+            retry_budget,
+            concurrency,
         }
     }
 
     pub fn set_highest_known_version(&mut self) -> anyhow::Result<()> {
         let info = self.context.get_latest_ledger_info_wrapped()?;
-        self.highest_known_version = info.ledger_version.0;
+        self.highest_known_version = match self.options.ending_version {
+            Some(ending_version) => std::cmp::min(info.ledger_version.0, ending_version),
+            None => info.ledger_version.0,
+        };
         self.chain_id = info.chain_id;
         Ok(())
     }
@@ -82,11 +103,24 @@ impl Fetcher {
     /// If the processor channel is full, it will wait for the processor to catch up.
     /// 1. Get the latest ledger info, and set the highest known version (if we've caught up)
     /// 2. Determine how many batches of size `options.transaction_fetch_batch_size` we need to catch up
-    /// 3. Spawn tasks which fetch 'raw' `OnChainTransactions` from storage, and convert them to `Transaction`s. We spawn at most `options.max_tasks` tasks.
+    /// 3. Spawn tasks which fetch 'raw' `OnChainTransactions` from storage, and convert them to `Transaction`s. We spawn at most `concurrency` tasks.
     /// 4. We wait for all the tasks to complete, then send the `Transaction`s to the processor, via the `transactions_sender` channel.
+    /// If a task fails once its retries are exhausted, only the batches before it are sent, and
+    /// the next round starts from it after a backoff, with fewer tasks.
+    /// If `options.ending_version` is set, stops once it has been sent, which closes the channel.
     pub async fn run(&mut self) {
         let transaction_fetch_batch_size = self.options.transaction_fetch_batch_size;
+        let mut failed_rounds = 0;
         loop {
+            if let Some(ending_version) = self.options.ending_version {
+                if self.current_version > ending_version {
+                    info!(
+                        ending_version = ending_version,
+                        "Fetched all transactions up to the ending version"
+                    );
+                    return;
+                }
+            }
             self.ensure_highest_known_version().await;
 
             info!(
@@ -101,9 +135,7 @@ impl Fetcher {
             let mut starting_version = self.current_version;
             let mut num_fetches = 0;
 
-            while num_fetches < self.options.max_tasks
-                && starting_version <= self.highest_known_version
-            {
+            while num_fetches < self.concurrency && starting_version <= self.highest_known_version {
                 let num_transactions_to_fetch = std::cmp::min(
                     transaction_fetch_batch_size as u64,
                     self.highest_known_version - starting_version + 1,
@@ -111,12 +143,16 @@ impl Fetcher {
 
                 let context = self.context.clone();
                 let highest_known_version = self.highest_known_version;
+                let options = self.options.clone();
+                let retry_budget = self.retry_budget.clone();
                 let task = tokio::spawn(async move {
                     fetch_nexts(
                         context,
                         starting_version,
                         highest_known_version,
                         num_transactions_to_fetch,
+                        &options,
+                        &retry_budget,
                     )
                     .await
                 });
@@ -125,10 +161,39 @@ impl Fetcher {
                 num_fetches += 1;
             }
 
-            let batches = match futures::future::try_join_all(tasks).await {
+            let results = match futures::future::try_join_all(tasks).await {
                 Ok(res) => res,
                 Err(err) => panic!("Error fetching transaction batches: {:?}", err),
             };
+            // The batches have to be sent in order, so the ones after a failed one are dropped
+            let mut batches = vec![];
+            let mut fetch_error = None;
+            for result in results {
+                match result {
+                    Ok(batch) => batches.push(batch),
+                    Err(err) => {
+                        fetch_error = Some(err);
+                        break;
+                    },
+                }
+            }
+            match fetch_error {
+                Some(err) => {
+                    self.concurrency = std::cmp::max(self.concurrency / 2, 1);
+                    failed_rounds += 1;
+                    error!(
+                        current_version = self.current_version,
+                        concurrency = self.concurrency,
+                        error = format!("{:?}", err),
+                        "Failed to fetch transactions, fetching fewer batches at a time"
+                    );
+                },
+                None => {
+                    self.concurrency = std::cmp::min(self.concurrency + 1, self.options.max_tasks);
+                    failed_rounds = 0;
+                },
+            }
+            FETCH_CONCURRENCY.set(self.concurrency as i64);
 
             let versions_fetched = batches.iter().fold(0, |acc, v| acc + v.len());
             let fetch_millis = (chrono::Utc::now().naive_utc() - fetch_start).num_milliseconds();
@@ -139,6 +204,14 @@ impl Fetcher {
                 "Finished fetching transaction batches"
             );
             self.send_transaction_batches(batches).await;
+            if failed_rounds > 0 {
+                tokio::time::sleep(backoff(
+                    self.options.starting_retry_time,
+                    self.options.max_retry_time,
+                    failed_rounds - 1,
+                ))
+                .await;
+            }
         }
     }
 
@@ -170,41 +243,53 @@ impl Fetcher {
     }
 }
 
+/// Retries with an exponential backoff as long as the retry budget allows it
 async fn fetch_raw_txns_with_retries(
     context: Arc<Context>,
     starting_version: u64,
     ledger_version: u64,
     num_transactions_to_fetch: u16,
-    max_retries: u8,
-) -> Vec<TransactionOnChainData> {
+    options: &TransactionFetcherOptions,
+    retry_budget: &RetryBudget,
+) -> Result<Vec<TransactionOnChainData>> {
     let mut retries = 0;
     loop {
         match context.get_transactions(starting_version, num_transactions_to_fetch, ledger_version)
         {
-            Ok(raw_txns) => return raw_txns,
+            Ok(raw_txns) => {
+                retry_budget.deposit();
+                return Ok(raw_txns);
+            },
             Err(err) => {
                 UNABLE_TO_FETCH_TRANSACTION.inc();
-                retries += 1;
-                if retries >= max_retries {
-                    error!(
-                        starting_version = starting_version,
-                        num_transactions = num_transactions_to_fetch,
-                        error = format!("{:?}", err),
-                        "Could not fetch transactions: retries exhausted",
-                    );
-                    panic!(
-                        "Could not fetch {} transactions after {} retries, starting at {}: {:?}",
-                        num_transactions_to_fetch, retries, starting_version, err
-                    );
-                } else {
+                if !retry_budget.try_withdraw() {
                     error!(
                         starting_version = starting_version,
                         num_transactions = num_transactions_to_fetch,
+                        retries = retries,
                         error = format!("{:?}", err),
-                        "Could not fetch transactions: will retry",
+                        "Could not fetch transactions: retry budget exhausted",
                     );
+                    return Err(err.context(format!(
+                        "Could not fetch {} transactions after {} retries, starting at {}",
+                        num_transactions_to_fetch, retries, starting_version
+                    )));
                 }
-                tokio::time::sleep(Duration::from_millis(300)).await;
+                error!(
+                    starting_version = starting_version,
+                    num_transactions = num_transactions_to_fetch,
+                    retries = retries,
+                    error = format!("{:?}", err),
+                    "Could not fetch transactions: will retry",
+                );
+                FETCH_RETRIES.inc();
+                tokio::time::sleep(backoff(
+                    options.starting_retry_time,
+                    options.max_retry_time,
+                    retries,
+                ))
+                .await;
+                retries += 1;
             },
         }
     }
@@ -215,7 +300,9 @@ async fn fetch_nexts(
     starting_version: u64,
     ledger_version: u64,
     num_transactions_to_fetch: u16,
-) -> Vec<Transaction> {
+    options: &TransactionFetcherOptions,
+    retry_budget: &RetryBudget,
+) -> Result<Vec<Transaction>> {
     let start_millis = chrono::Utc::now().naive_utc();
 
     let raw_txns = fetch_raw_txns_with_retries(
@@ -223,26 +310,27 @@ async fn fetch_nexts(
         starting_version,
         ledger_version,
         num_transactions_to_fetch,
-        3,
+        options,
+        retry_budget,
     )
-    .await;
+    .await?;
 
     let (_, _, block_event) = context
         .db
         .get_block_info_by_version(starting_version)
-        .unwrap_or_else(|_| {
-            panic!(
+        .with_context(|| {
+            format!(
                 "Could not get block_info for start version {}",
-                starting_version,
+                starting_version
             )
-        });
+        })?;
     let mut timestamp = block_event.proposed_time();
     let mut epoch = block_event.epoch();
     let mut epoch_bcs = aptos_api_types::U64::from(epoch);
     let mut block_height = block_event.height();
     let mut block_height_bcs = aptos_api_types::U64::from(block_height);
 
-    let state_view = context.latest_state_view().unwrap();
+    let state_view = context.latest_state_view()?;
     let resolver = state_view.as_move_resolver();
     let converter = resolver.as_converter(context.db.clone());
 
@@ -304,9 +392,11 @@ async fn fetch_nexts(
         }
     }
 
-    if transactions.is_empty() {
-        panic!("No transactions!");
-    }
+    ensure!(
+        !transactions.is_empty(),
+        "No transactions starting at {}!",
+        starting_version
+    );
 
     let fetch_millis = (chrono::Utc::now().naive_utc() - start_millis).num_milliseconds();
 
@@ -323,7 +413,7 @@ async fn fetch_nexts(
 
     FETCHED_TRANSACTION.inc();
 
-    transactions
+    Ok(transactions)
 }
 
 #[derive(Clone, Debug)]
@@ -335,6 +425,8 @@ pub struct TransactionFetcherOptions {
     pub transaction_fetch_batch_size: u16,
     pub max_pending_batches: usize,
     pub max_tasks: usize,
+    /// The last version to fetch (inclusive), if the fetcher should not follow the head of the chain
+    pub ending_version: Option<u64>,
 }
 
 fn default_if_zero<T>(value: Option<T>, default: T) -> T
@@ -379,8 +471,14 @@ impl TransactionFetcherOptions {
             transaction_fetch_batch_size,
             max_pending_batches,
             max_tasks: std::cmp::max(max_tasks, 1),
+            ending_version: None,
         }
     }
+
+    pub fn with_ending_version(mut self, ending_version: u64) -> Self {
+        self.ending_version = Some(ending_version);
+        self
+    }
 }
 
 impl Default for TransactionFetcherOptions {
@@ -396,6 +494,8 @@ pub struct TransactionFetcher {
     fetcher_handle: Option<JoinHandle<()>>,
     transactions_sender: Option<mpsc::Sender<Vec<Transaction>>>,
     transaction_receiver: mpsc::Receiver<Vec<Transaction>>,
+    /// Shared by all the fetches to the node
+    retry_budget: Arc<RetryBudget>,
 }
 
 impl TransactionFetcher {
@@ -414,6 +514,10 @@ impl TransactionFetcher {
             fetcher_handle: None,
             transactions_sender: Some(transactions_sender),
             transaction_receiver,
+            retry_budget: Arc::new(RetryBudget::new(
+                RETRY_BUDGET_DEPOSIT_RATIO,
+                RETRY_BUDGET_MAX_RETRIES,
+            )),
         }
     }
 }
@@ -426,8 +530,11 @@ impl TransactionFetcherTrait for TransactionFetcher {
         match self.transaction_receiver.try_next() {
             Ok(Some(transactions)) => transactions,
             Ok(None) => {
-                // We never close the channel, so this should never happen
-                panic!("Transaction fetcher channel closed");
+                // The channel is only closed once the fetcher is done with the ending version
+                if self.options.ending_version.is_none() {
+                    panic!("Transaction fetcher channel closed");
+                }
+                vec![]
             },
             // The error here is when the channel is empty which we definitely expect.
             Err(_) => vec![],
@@ -440,6 +547,38 @@ impl TransactionFetcherTrait for TransactionFetcher {
             .unwrap_or_else(|err| panic!("Failed to get ledger info: {}", err))
     }
 
+    fn fetch_genesis_hash(&mut self) -> String {
+        // The ledger info ending epoch 0 is the one of the genesis, and isn't pruned unlike the
+        // genesis transaction
+        let epoch_change_proof = self
+            .context
+            .db
+            .get_epoch_ending_ledger_infos(0, 1)
+            .unwrap_or_else(|err| panic!("Failed to get genesis ledger info: {}", err));
+        epoch_change_proof
+            .ledger_info_with_sigs
+            .first()
+            .expect("Genesis ledger info must exist")
+            .ledger_info()
+            .transaction_accumulator_hash()
+            .to_hex_literal()
+    }
+
+    async fn fetch_transaction(&mut self, version: u64) -> Transaction {
+        let ledger_version = self.fetch_ledger_info().ledger_version.0;
+        fetch_nexts(
+            self.context.clone(),
+            version,
+            ledger_version,
+            1,
+            &self.options,
+            &self.retry_budget,
+        )
+        .await
+        .unwrap_or_else(|err| panic!("Failed to fetch transaction {}: {:?}", version, err))
+        .remove(0)
+    }
+
     async fn set_version(&mut self, version: u64) {
         if self.fetcher_handle.is_some() {
             panic!("TransactionFetcher already started!");
@@ -456,9 +595,15 @@ impl TransactionFetcherTrait for TransactionFetcher {
         let starting_version = self.starting_version;
 
         let options2 = self.options.clone();
+        let retry_budget = self.retry_budget.clone();
         let fetcher_handle = tokio::spawn(async move {
-            let mut fetcher =
-                Fetcher::new(context, starting_version, options2, transactions_sender);
+            let mut fetcher = Fetcher::new(
+                context,
+                starting_version,
+                options2,
+                transactions_sender,
+                retry_budget,
+            );
             fetcher.run().await;
         });
         self.fetcher_handle = Some(fetcher_handle);
@@ -472,6 +617,12 @@ pub trait TransactionFetcherTrait: Send + Sync {
 
     fn fetch_ledger_info(&mut self) -> LedgerInfo;
 
+    /// Identifies the chain along with the chain id, which can be reused after a wipe
+    fn fetch_genesis_hash(&mut self) -> String;
+
+    /// Fetches a single transaction, regardless of the version of the fetcher
+    async fn fetch_transaction(&mut self, version: u64) -> Transaction;
+
     async fn set_version(&mut self, version: u64);
 
     async fn start(&mut self);
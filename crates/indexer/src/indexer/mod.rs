@@ -3,6 +3,9 @@
 
 pub mod errors;
 pub mod fetcher;
+pub mod module_filter;
+pub mod partitioning;
 pub mod processing_result;
+pub mod retry_budget;
 pub mod tailer;
 pub mod transaction_processor;
@@ -0,0 +1,191 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::{PgDbPool, PgPoolConnection},
+    indexer::{
+        errors::TransactionProcessingError, processing_result::ProcessingResult,
+        transaction_processor::TransactionProcessor,
+    },
+};
+use aptos_api_types::{Address, Event, MoveType, Transaction, TransactionPayload, WriteSetChange};
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use std::{collections::HashSet, sync::Arc};
+
+/// Matches the transactions touching one of the given module addresses, i.e. calling an entry
+/// function or emitting an event of a module at one of them, or writing to one of them
+#[derive(Clone, Debug)]
+pub struct ModuleFilter {
+    module_addresses: HashSet<AccountAddress>,
+}
+
+impl ModuleFilter {
+    pub fn new(module_addresses: HashSet<AccountAddress>) -> Self {
+        Self { module_addresses }
+    }
+
+    fn is_allowed(&self, address: &Address) -> bool {
+        self.module_addresses.contains(address.inner())
+    }
+
+    fn touches_events(&self, events: &[Event]) -> bool {
+        events.iter().any(|event| match &event.typ {
+            MoveType::Struct(inner) => self.is_allowed(&inner.address),
+            _ => false,
+        })
+    }
+
+    fn touches_changes(&self, changes: &[WriteSetChange]) -> bool {
+        changes.iter().any(|change| match change {
+            WriteSetChange::DeleteModule(module) => self.is_allowed(&module.address),
+            WriteSetChange::DeleteResource(resource) => self.is_allowed(&resource.address),
+            WriteSetChange::WriteModule(module) => self.is_allowed(&module.address),
+            WriteSetChange::WriteResource(resource) => self.is_allowed(&resource.address),
+            // Table items aren't stored under an address
+            WriteSetChange::DeleteTableItem(_) | WriteSetChange::WriteTableItem(_) => false,
+        })
+    }
+
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        match transaction {
+            Transaction::UserTransaction(txn) => {
+                let calls_entry_function = match &txn.request.payload {
+                    TransactionPayload::EntryFunctionPayload(payload) => {
+                        self.is_allowed(&payload.function.module.address)
+                    },
+                    _ => false,
+                };
+                calls_entry_function
+                    || self.touches_events(&txn.events)
+                    || self.touches_changes(&txn.info.changes)
+            },
+            Transaction::GenesisTransaction(txn) => {
+                self.touches_events(&txn.events) || self.touches_changes(&txn.info.changes)
+            },
+            Transaction::BlockMetadataTransaction(txn) => {
+                self.touches_events(&txn.events) || self.touches_changes(&txn.info.changes)
+            },
+            Transaction::StateCheckpointTransaction(txn) => self.touches_changes(&txn.info.changes),
+            Transaction::PendingTransaction(_) => false,
+        }
+    }
+}
+
+/// Wraps a processor so that it only processes the transactions matching a `ModuleFilter`.
+/// The other transactions are skipped but still marked as processed.
+#[derive(Debug)]
+pub struct ModuleFilteredProcessor {
+    processor: Arc<dyn TransactionProcessor>,
+    filter: ModuleFilter,
+}
+
+impl ModuleFilteredProcessor {
+    pub fn new(processor: Arc<dyn TransactionProcessor>, filter: ModuleFilter) -> Self {
+        Self { processor, filter }
+    }
+}
+
+#[async_trait]
+impl TransactionProcessor for ModuleFilteredProcessor {
+    fn name(&self) -> &'static str {
+        self.processor.name()
+    }
+
+    async fn process_transactions(
+        &self,
+        transactions: Vec<Transaction>,
+        start_version: u64,
+        end_version: u64,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        self.processor
+            .process_transactions(transactions, start_version, end_version)
+            .await
+    }
+
+    fn connection_pool(&self) -> &PgDbPool {
+        self.processor.connection_pool()
+    }
+
+    fn run_migrations(&self, conn: &mut PgPoolConnection) -> anyhow::Result<()> {
+        self.processor.run_migrations(conn)
+    }
+
+    fn filter_transaction(&self, transaction: &Transaction) -> bool {
+        self.filter.matches(transaction) && self.processor.filter_transaction(transaction)
+    }
+
+    fn on_checkpoint(&self, version: u64) -> anyhow::Result<()> {
+        self.processor.on_checkpoint(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_module_filter() {
+        let block_metadata_transaction: Transaction = serde_json::from_value(serde_json::json!(
+            {
+              "type": "block_metadata_transaction",
+              "version": "69158",
+              "hash": "0x2b7c58ed8524d228f9d0543a82e2793d04e8871df322f976b0e7bb8c5ced4ff5",
+              "state_change_hash": "0x3ead9eb40582fbc7df5e02f72280931dc3e6f1aae45dc832966b4cd972dac4b8",
+              "event_root_hash": "0x2e481956dea9c59b6fc9f823fe5f4c45efce173e42c551c1fe073b5d76a65504",
+              "gas_used": "0",
+              "success": true,
+              "vm_status": "Executed successfully",
+              "accumulator_root_hash": "0xb0ad602f805eb20c398f0f29a3504a9ef38bcc52c9c451deb9ec4a2d18807b49",
+              "id": "0xeef99391a3fc681f16963a6c03415bc0b1b12b56c00429308fa8bf46ac9eddf0",
+              "round": "57600",
+              "failed_proposer_indices": [],
+              "epoch": "1",
+              "previous_block_votes_bitvec": [],
+              "proposer": "0x68f04222bd9f8846cda028ea5ba3846a806b04a47e1f1a4f0939f350d713b2eb",
+              "timestamp": "1649395495746947",
+              "events": [
+                {
+                  "guid": {
+                    "account_address": "0xa550c18",
+                    "creation_number": "6"
+                  },
+                  "sequence_number": "0",
+                  "type": "0x1::block::NewBlockEvent",
+                  "data": {}
+                }
+              ],
+              "changes": [
+                {
+                  "type": "write_resource",
+                  "address": "0xa550c18",
+                  "state_key_hash": "0xf113db06626eb7724773e4e9dacecc8a6cb3a710b8b70365768168b24fe06ce3",
+                  "data": {
+                    "type": "0x1::timestamp::CurrentTimeMicroseconds",
+                    "data": {
+                      "microseconds": "1650419261396337"
+                    }
+                  }
+                }
+              ]
+            }
+        ))
+        .unwrap();
+
+        let filter = |addresses: &[&str]| {
+            ModuleFilter::new(
+                addresses
+                    .iter()
+                    .map(|address| AccountAddress::from_str(address).unwrap())
+                    .collect(),
+            )
+        };
+
+        // The event module
+        assert!(filter(&["0x1"]).matches(&block_metadata_transaction));
+        // The write set address
+        assert!(filter(&["0x2", "0xa550c18"]).matches(&block_metadata_transaction));
+        assert!(!filter(&["0x2"]).matches(&block_metadata_transaction));
+    }
+}
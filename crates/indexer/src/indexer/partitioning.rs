@@ -0,0 +1,348 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Partitions tables by transaction version, so that their old rows can be aged out by dropping
+//! whole partitions instead of deleting rows.
+
+use crate::{
+    database::{PgDbPool, PgPoolConnection},
+    models::processor_status::ProcessorStatusV2Query,
+};
+use anyhow::{anyhow, Result};
+use aptos_config::config::IndexerTablePartitionConfig;
+use aptos_logger::{error, info};
+use diesel::{
+    result::Error,
+    sql_query,
+    sql_types::{BigInt, Nullable, Text},
+    RunQueryDsl,
+};
+use std::time::Duration;
+
+/// How many partitions to keep ahead of the last processed version, so that there is always one
+/// for the versions being processed
+const PARTITIONS_AHEAD: u64 = 2;
+/// How often the partitions are created and dropped
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, QueryableByName)]
+struct RelationKind {
+    #[diesel(sql_type = Text)]
+    relkind: String,
+}
+
+#[derive(Debug, QueryableByName)]
+struct RelationName {
+    #[diesel(sql_type = Text)]
+    relname: String,
+}
+
+#[derive(Debug, QueryableByName)]
+struct MaxVersion {
+    #[diesel(sql_type = Nullable<BigInt>)]
+    max_version: Option<i64>,
+}
+
+/// Partitions a table by ranges of `partition_versions` versions. The partition for the versions
+/// from `start` (inclusive) to `end` (exclusive) is named `<table>_p<start>_<end>`.
+#[derive(Clone, Debug)]
+pub struct TablePartitioner {
+    table: String,
+    partition_versions: u64,
+    retention_versions: Option<u64>,
+}
+
+impl TablePartitioner {
+    pub fn new(config: &IndexerTablePartitionConfig) -> Self {
+        Self {
+            table: config.table.clone(),
+            partition_versions: config.partition_versions,
+            retention_versions: config.retention_versions,
+        }
+    }
+
+    fn partition_start(&self, version: u64) -> u64 {
+        version / self.partition_versions * self.partition_versions
+    }
+
+    fn partition_name(&self, start_version: u64, end_version: u64) -> String {
+        format!("{}_p{}_{}", self.table, start_version, end_version)
+    }
+
+    /// Gets the range of versions of a partition of the table from its name
+    fn partition_range(&self, partition_name: &str) -> Option<(u64, u64)> {
+        let (start_version, end_version) = partition_name
+            .strip_prefix(&format!("{}_p", self.table))?
+            .split_once('_')?;
+        Some((start_version.parse().ok()?, end_version.parse().ok()?))
+    }
+
+    /// Turns the table into a table partitioned by transaction version if it isn't one yet. The
+    /// existing rows become the first partition, which holds all the versions up to the end of
+    /// the partition of the last version in the table.
+    pub fn partition_table(&self, conn: &mut PgPoolConnection) -> Result<()> {
+        let relation_kinds: Vec<RelationKind> = sql_query(
+            "SELECT relkind::text AS relkind FROM pg_class WHERE relname = $1 AND relnamespace = 'public'::regnamespace",
+        )
+        .bind::<Text, _>(&self.table)
+        .load(conn)?;
+        let relation_kind = relation_kinds
+            .first()
+            .ok_or_else(|| anyhow!("Table {} doesn't exist", self.table))?;
+        // 'p' is a partitioned table
+        if relation_kind.relkind == "p" {
+            return Ok(());
+        }
+
+        let max_versions: Vec<MaxVersion> = sql_query(format!(
+            "SELECT MAX(transaction_version) AS max_version FROM {}",
+            self.table
+        ))
+        .load(conn)?;
+        let max_version = max_versions
+            .first()
+            .and_then(|max_version| max_version.max_version)
+            .unwrap_or(0) as u64;
+        let end_version = self.partition_start(max_version) + self.partition_versions;
+        let first_partition = self.partition_name(0, end_version);
+        info!(
+            table = self.table,
+            first_partition = first_partition,
+            "Partitioning table by transaction version"
+        );
+
+        conn.build_transaction()
+            .read_write()
+            .run::<_, Error, _>(|pg_conn| {
+                sql_query(format!(
+                    "ALTER TABLE {} RENAME TO {}",
+                    self.table, first_partition
+                ))
+                .execute(pg_conn)?;
+                sql_query(format!(
+                    "CREATE TABLE {} (LIKE {} INCLUDING ALL) PARTITION BY RANGE (transaction_version)",
+                    self.table, first_partition
+                ))
+                .execute(pg_conn)?;
+                sql_query(format!(
+                    "ALTER TABLE {} ATTACH PARTITION {} FOR VALUES FROM (MINVALUE) TO ({})",
+                    self.table, first_partition, end_version
+                ))
+                .execute(pg_conn)?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+
+    /// Plans the partitions needed for the version, given the end version of the last partition.
+    /// Returns the version to extend the last partition to, if there is a gap between it and the
+    /// partition of the version, and the partitions to create after it. Covering the gap with the
+    /// last partition avoids creating every partition in it one by one, e.g. when the processor
+    /// starts at a high version on an empty table.
+    fn plan_partitions(
+        &self,
+        last_end_version: Option<u64>,
+        version: u64,
+    ) -> (Option<u64>, Vec<(u64, u64)>) {
+        let version_start = self.partition_start(version);
+        let extended_end_version = last_end_version
+            .filter(|end_version| *end_version < version_start)
+            .map(|_| version_start);
+        let mut start_version = extended_end_version
+            .or(last_end_version)
+            .unwrap_or(version_start);
+        let target_end_version = version_start + (PARTITIONS_AHEAD + 1) * self.partition_versions;
+        let mut new_partitions = vec![];
+        while start_version < target_end_version {
+            let end_version = start_version + self.partition_versions;
+            new_partitions.push((start_version, end_version));
+            start_version = end_version;
+        }
+        (extended_end_version, new_partitions)
+    }
+
+    /// Moves the end of a partition later, by detaching it and attaching it back with the new
+    /// range under its new name. The first partition keeps holding all the versions before it.
+    fn extend_partition(
+        &self,
+        conn: &mut PgPoolConnection,
+        partition: &str,
+        start_version: u64,
+        end_version: u64,
+    ) -> Result<String> {
+        let extended_partition = self.partition_name(start_version, end_version);
+        let lower_bound = if start_version == 0 {
+            "MINVALUE".to_string()
+        } else {
+            start_version.to_string()
+        };
+        info!(
+            table = self.table,
+            partition = partition,
+            extended_partition = extended_partition,
+            "Extending partition"
+        );
+        conn.build_transaction()
+            .read_write()
+            .run::<_, Error, _>(|pg_conn| {
+                sql_query(format!(
+                    "ALTER TABLE {} DETACH PARTITION {}",
+                    self.table, partition
+                ))
+                .execute(pg_conn)?;
+                sql_query(format!(
+                    "ALTER TABLE {} RENAME TO {}",
+                    partition, extended_partition
+                ))
+                .execute(pg_conn)?;
+                sql_query(format!(
+                    "ALTER TABLE {} ATTACH PARTITION {} FOR VALUES FROM ({}) TO ({})",
+                    self.table, extended_partition, lower_bound, end_version
+                ))
+                .execute(pg_conn)?;
+                Ok(())
+            })?;
+        Ok(extended_partition)
+    }
+
+    /// Creates the partitions up to a few partitions after the version, and drops the ones whose
+    /// versions are all older than the retention
+    pub fn maintain_partitions(&self, conn: &mut PgPoolConnection, version: u64) -> Result<()> {
+        let partitions: Vec<RelationName> = sql_query(
+            "SELECT c.relname::text AS relname FROM pg_inherits i JOIN pg_class c ON c.oid = i.inhrelid WHERE i.inhparent = $1::regclass",
+        )
+        .bind::<Text, _>(&self.table)
+        .load(conn)?;
+        let mut partitions = partitions
+            .into_iter()
+            .filter_map(|partition| {
+                self.partition_range(&partition.relname)
+                    .map(|(start_version, end_version)| {
+                        (partition.relname, start_version, end_version)
+                    })
+            })
+            .collect::<Vec<_>>();
+        partitions.sort_by_key(|(_, _, end_version)| *end_version);
+
+        let (extended_end_version, new_partitions) = self.plan_partitions(
+            partitions.last().map(|(_, _, end_version)| *end_version),
+            version,
+        );
+        if let (Some(extended_end_version), Some((partition, start_version, end_version))) =
+            (extended_end_version, partitions.last_mut())
+        {
+            *partition =
+                self.extend_partition(conn, partition, *start_version, extended_end_version)?;
+            *end_version = extended_end_version;
+        }
+        for (start_version, end_version) in new_partitions {
+            let partition = self.partition_name(start_version, end_version);
+            info!(
+                table = self.table,
+                partition = partition,
+                "Creating partition"
+            );
+            sql_query(format!(
+                "CREATE TABLE IF NOT EXISTS {} PARTITION OF {} FOR VALUES FROM ({}) TO ({})",
+                partition, self.table, start_version, end_version
+            ))
+            .execute(conn)?;
+        }
+
+        if let Some(retention_versions) = self.retention_versions {
+            let oldest_kept_version = version.saturating_sub(retention_versions);
+            for (partition, _, end_version) in partitions {
+                if end_version <= oldest_kept_version {
+                    info!(
+                        table = self.table,
+                        partition = partition,
+                        "Dropping partition past its retention"
+                    );
+                    sql_query(format!("DROP TABLE IF EXISTS {}", partition)).execute(conn)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Keeps maintaining the partitions of the tables as the processor moves forward
+pub async fn run_partition_maintenance(
+    connection_pool: PgDbPool,
+    processor_name: String,
+    partitioners: Vec<TablePartitioner>,
+) {
+    loop {
+        tokio::time::sleep(MAINTENANCE_INTERVAL).await;
+        let result = connection_pool
+            .get()
+            .map_err(anyhow::Error::from)
+            .and_then(|mut conn| {
+                let version = ProcessorStatusV2Query::get_by_processor(&processor_name, &mut conn)?
+                    .map_or(0, |status| status.last_success_version as u64);
+                for partitioner in &partitioners {
+                    partitioner.maintain_partitions(&mut conn, version)?;
+                }
+                Ok(())
+            });
+        if let Err(e) = result {
+            error!(
+                processor_name = processor_name,
+                error = format!("{:?}", e),
+                "Failed to maintain the partitions"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_range() {
+        let partitioner = TablePartitioner::new(&IndexerTablePartitionConfig {
+            table: "coin_activities".to_string(),
+            partition_versions: 1000,
+            retention_versions: None,
+        });
+        assert_eq!(partitioner.partition_start(2500), 2000);
+        let partition = partitioner.partition_name(2000, 3000);
+        assert_eq!(partition, "coin_activities_p2000_3000");
+        assert_eq!(partitioner.partition_range(&partition), Some((2000, 3000)));
+        assert_eq!(partitioner.partition_range("coin_activities_pkey"), None);
+        assert_eq!(partitioner.partition_range("events_p0_1000"), None);
+    }
+
+    #[test]
+    fn test_plan_partitions() {
+        let partitioner = TablePartitioner::new(&IndexerTablePartitionConfig {
+            table: "coin_activities".to_string(),
+            partition_versions: 1000,
+            retention_versions: None,
+        });
+        // Only the partitions missing ahead of the version are created
+        assert_eq!(
+            partitioner.plan_partitions(Some(3000), 1500),
+            (None, vec![(3000, 4000)])
+        );
+        assert_eq!(
+            partitioner.plan_partitions(Some(4000), 1500),
+            (None, vec![])
+        );
+        assert_eq!(
+            partitioner.plan_partitions(None, 1500),
+            (None, vec![(1000, 2000), (2000, 3000), (3000, 4000)])
+        );
+        // Starting far past the first partition of an empty table extends it to the partition
+        // of the version instead of creating all the partitions in between
+        assert_eq!(
+            partitioner.plan_partitions(Some(1000), 5_000_000_500),
+            (Some(5_000_000_000), vec![
+                (5_000_000_000, 5_000_001_000),
+                (5_000_001_000, 5_000_002_000),
+                (5_000_002_000, 5_000_003_000),
+            ])
+        );
+    }
+}
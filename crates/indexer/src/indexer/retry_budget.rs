@@ -0,0 +1,101 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::counters::FETCH_RETRY_BUDGET;
+use std::{sync::Mutex, time::Duration};
+
+/// Limits the retries of all the requests to a fraction of the successful ones, so that the
+/// requests to a failing node don't multiply. Each success deposits `deposit_ratio` of a retry,
+/// up to `max_balance` retries, and each retry withdraws one. It starts full.
+#[derive(Debug)]
+pub struct RetryBudget {
+    balance: Mutex<f64>,
+    deposit_ratio: f64,
+    max_balance: f64,
+}
+
+impl RetryBudget {
+    pub fn new(deposit_ratio: f64, max_balance: f64) -> Self {
+        FETCH_RETRY_BUDGET.set(max_balance);
+        Self {
+            balance: Mutex::new(max_balance),
+            deposit_ratio,
+            max_balance,
+        }
+    }
+
+    /// Records a successful request
+    pub fn deposit(&self) {
+        let mut balance = self.balance.lock().unwrap();
+        *balance = f64::min(*balance + self.deposit_ratio, self.max_balance);
+        FETCH_RETRY_BUDGET.set(*balance);
+    }
+
+    /// Returns whether a request can be retried, and if so takes the retry from the budget
+    pub fn try_withdraw(&self) -> bool {
+        let mut balance = self.balance.lock().unwrap();
+        if *balance < 1.0 {
+            return false;
+        }
+        *balance -= 1.0;
+        FETCH_RETRY_BUDGET.set(*balance);
+        true
+    }
+}
+
+/// How long to wait before the `attempt`th retry (starting at 0): doubles at every attempt from
+/// `starting_retry_time`, up to `max_retry_time`
+pub fn backoff(starting_retry_time: Duration, max_retry_time: Duration, attempt: u32) -> Duration {
+    starting_retry_time
+        .checked_mul(2u32.saturating_pow(attempt))
+        .map_or(max_retry_time, |retry_time| {
+            std::cmp::min(retry_time, max_retry_time)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_budget() {
+        let budget = RetryBudget::new(0.5, 2.0);
+        assert!(budget.try_withdraw());
+        assert!(budget.try_withdraw());
+        assert!(!budget.try_withdraw());
+
+        budget.deposit();
+        assert!(!budget.try_withdraw());
+        budget.deposit();
+        assert!(budget.try_withdraw());
+
+        for _ in 0..10 {
+            budget.deposit();
+        }
+        assert!(budget.try_withdraw());
+        assert!(budget.try_withdraw());
+        assert!(!budget.try_withdraw());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let starting_retry_time = Duration::from_millis(300);
+        let max_retry_time = Duration::from_secs(2);
+        assert_eq!(
+            backoff(starting_retry_time, max_retry_time, 0),
+            Duration::from_millis(300)
+        );
+        assert_eq!(
+            backoff(starting_retry_time, max_retry_time, 2),
+            Duration::from_millis(1200)
+        );
+        assert_eq!(
+            backoff(starting_retry_time, max_retry_time, 3),
+            max_retry_time
+        );
+        assert_eq!(
+            backoff(starting_retry_time, max_retry_time, 40),
+            max_retry_time
+        );
+    }
+}
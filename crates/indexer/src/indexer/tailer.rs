@@ -1,7 +1,8 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    database::{execute_with_better_error, PgDbPool},
+    counters::PROCESSOR_DEAD_LETTERS,
+    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
     indexer::{
         errors::TransactionProcessingError,
         fetcher::{TransactionFetcher, TransactionFetcherOptions, TransactionFetcherTrait},
@@ -9,14 +10,16 @@ use crate::{
         transaction_processor::TransactionProcessor,
     },
     models::{
+        dead_letters::{DeadLetter, DeadLetterQuery},
         ledger_info::LedgerInfo,
         processor_status::{ProcessorStatusV2, ProcessorStatusV2Query},
     },
-    schema::{ledger_infos, processor_status},
+    schema::{dead_letters, ledger_infos, processor_status},
 };
 use anyhow::{ensure, Context, Result};
 use aptos_api::context::Context as ApiContext;
-use aptos_logger::{debug, info};
+use aptos_api_types::Transaction;
+use aptos_logger::{debug, error, info, warn};
 use chrono::ParseError;
 use diesel::{
     pg::upsert::excluded,
@@ -35,6 +38,7 @@ pub struct Tailer {
     pub transaction_fetcher: Arc<Mutex<dyn TransactionFetcherTrait>>,
     processor: Arc<dyn TransactionProcessor>,
     connection_pool: PgDbPool,
+    dead_letter_failed_transactions: bool,
 }
 
 impl Tailer {
@@ -50,62 +54,149 @@ impl Tailer {
             transaction_fetcher: Arc::new(Mutex::new(transaction_fetcher)),
             connection_pool,
             processor,
+            dead_letter_failed_transactions: false,
         })
     }
 
+    /// If enabled, the transactions that a batch fails on are recorded as dead letters and
+    /// skipped instead of failing the batch
+    pub fn with_dead_letters(mut self, dead_letter_failed_transactions: bool) -> Self {
+        self.dead_letter_failed_transactions = dead_letter_failed_transactions;
+        self
+    }
+
     pub fn run_migrations(&self) {
-        let _ = &self
+        let mut conn = self
             .connection_pool
             .get()
-            .expect("Could not get connection for migrations")
-            .run_pending_migrations(MIGRATIONS)
+            .expect("Could not get connection for migrations");
+        conn.run_pending_migrations(MIGRATIONS)
             .expect("migrations failed!");
+        self.processor
+            .run_migrations(&mut conn)
+            .unwrap_or_else(|e| {
+                panic!(
+                    "[{}] processor migrations failed: {:?}",
+                    self.processor.name(),
+                    e
+                )
+            });
     }
 
-    /// If chain id doesn't exist, save it. Otherwise, make sure that we're indexing the same chain
-    pub async fn check_or_update_chain_id(&self) -> Result<u64> {
+    /// If chain id doesn't exist, save it. Otherwise, make sure that we're indexing the same chain,
+    /// i.e. that neither the chain id nor the genesis changed, and that the ledger wasn't truncated
+    /// below what the processor already indexed. If the chain changed and `reset_on_chain_change`
+    /// is set, all the indexer tables are truncated so that the new chain is indexed from scratch.
+    pub async fn check_or_update_chain_id(&self, reset_on_chain_change: bool) -> Result<u64> {
         info!(
             processor_name = self.processor.name(),
             "Checking if chain id is correct"
         );
         let mut conn = self.connection_pool.get()?;
 
-        let maybe_existing_chain_id = LedgerInfo::get(&mut conn)?.map(|li| li.chain_id);
+        let maybe_existing_ledger_info = LedgerInfo::get(&mut conn)?;
 
-        let new_chain_id = self
-            .transaction_fetcher
-            .lock()
-            .await
-            .fetch_ledger_info()
-            .chain_id as i64;
-
-        match maybe_existing_chain_id {
-            Some(chain_id) => {
-                ensure!(chain_id == new_chain_id, "Wrong chain detected! Trying to index chain {} now but existing data is for chain {}", new_chain_id, chain_id);
-                info!(
-                    processor_name = self.processor.name(),
-                    chain_id = chain_id,
-                    "Chain id matches! Continue to index...",
-                );
-                Ok(chain_id as u64)
-            },
-            None => {
-                info!(
-                    processor_name = self.processor.name(),
-                    chain_id = new_chain_id,
-                    "Adding chain id to db, continue to index.."
+        let (new_chain_id, ledger_version, genesis_hash) = {
+            let mut transaction_fetcher = self.transaction_fetcher.lock().await;
+            let ledger_info = transaction_fetcher.fetch_ledger_info();
+            (
+                ledger_info.chain_id as i64,
+                ledger_info.ledger_version.0 as i64,
+                transaction_fetcher.fetch_genesis_hash(),
+            )
+        };
+
+        if let Some(existing_ledger_info) = maybe_existing_ledger_info {
+            let last_success_version = ProcessorStatusV2Query::get_by_processor(
+                &self.processor.name().to_string(),
+                &mut conn,
+            )?
+            .map(|status| status.last_success_version);
+            let chain_change = if existing_ledger_info.chain_id != new_chain_id {
+                Some(format!(
+                    "Trying to index chain {} now but existing data is for chain {}",
+                    new_chain_id, existing_ledger_info.chain_id
+                ))
+            } else if existing_ledger_info
+                .genesis_hash
+                .as_ref()
+                .map_or(false, |existing_genesis_hash| {
+                    existing_genesis_hash != &genesis_hash
+                })
+            {
+                Some(format!(
+                    "Trying to index the chain with genesis {} now but existing data is for genesis {:?}",
+                    genesis_hash, existing_ledger_info.genesis_hash
+                ))
+            } else {
+                None
+            };
+            if chain_change.is_none() {
+                // Same chain but behind the indexed data, e.g. the node DB was restored from an
+                // older backup. Keep the data and wait for the node to catch up instead.
+                ensure!(
+                    last_success_version.map_or(true, |version| version <= ledger_version),
+                    "The ledger is at version {} but existing data is up to version {:?}. Wait for the node to catch up before indexing.",
+                    ledger_version,
+                    last_success_version
                 );
-                execute_with_better_error(
-                    &mut conn,
-                    diesel::insert_into(ledger_infos::table).values(LedgerInfo {
-                        chain_id: new_chain_id,
-                    }),
-                    None,
-                )
-                .context(r#"Error updating chain_id!"#)
-                .map(|_| new_chain_id as u64)
-            },
+            }
+
+            match chain_change {
+                None => {
+                    if existing_ledger_info.genesis_hash.is_none() {
+                        execute_with_better_error(
+                            &mut conn,
+                            diesel::update(ledger_infos::table)
+                                .set(ledger_infos::genesis_hash.eq(&genesis_hash)),
+                            None,
+                        )
+                        .context(r#"Error updating genesis_hash!"#)?;
+                    }
+                    info!(
+                        processor_name = self.processor.name(),
+                        chain_id = new_chain_id,
+                        "Chain id matches! Continue to index...",
+                    );
+                    return Ok(new_chain_id as u64);
+                },
+                Some(chain_change) => {
+                    ensure!(
+                        reset_on_chain_change,
+                        "Wrong chain detected! {}. Set `reset_on_chain_change` to wipe the existing data.",
+                        chain_change
+                    );
+                    warn!(
+                        processor_name = self.processor.name(),
+                        chain_change = chain_change,
+                        "Chain changed, truncating all the indexer tables!"
+                    );
+                    truncate_tables(&mut conn)?;
+                },
+            }
         }
+
+        info!(
+            processor_name = self.processor.name(),
+            chain_id = new_chain_id,
+            genesis_hash = genesis_hash,
+            "Adding chain id to db, continue to index.."
+        );
+        execute_with_better_error(
+            &mut conn,
+            diesel::insert_into(ledger_infos::table).values(LedgerInfo {
+                chain_id: new_chain_id,
+                genesis_hash: Some(genesis_hash),
+            }),
+            None,
+        )
+        .context(r#"Error updating chain_id!"#)
+        .map(|_| new_chain_id as u64)
+    }
+
+    /// Calls the checkpoint hook of the processor, once the last processed version is stored
+    pub fn on_checkpoint(&self, version: u64) -> Result<()> {
+        self.processor.on_checkpoint(version)
     }
 
     pub async fn set_fetcher_version(&self, version: u64) {
@@ -123,13 +214,26 @@ impl Tailer {
         u64,
         Option<Result<ProcessingResult, TransactionProcessingError>>,
     ) {
-        let transactions = self
-            .transaction_fetcher
+        let transactions = self.fetch_next_batch().await;
+        self.process_batch(transactions).await
+    }
+
+    /// Gets the next batch of transactions from the fetcher, which is empty when caught up
+    pub async fn fetch_next_batch(&self) -> Vec<Transaction> {
+        self.transaction_fetcher
             .lock()
             .await
             .fetch_next_batch()
-            .await;
+            .await
+    }
 
+    pub async fn process_batch(
+        &self,
+        transactions: Vec<Transaction>,
+    ) -> (
+        u64,
+        Option<Result<ProcessingResult, TransactionProcessingError>>,
+    ) {
         let num_txns = transactions.len() as u64;
         // When the batch is empty b/c we're caught up
         if num_txns == 0 {
@@ -147,10 +251,13 @@ impl Tailer {
 
         let batch_start = chrono::Utc::now().naive_utc();
 
-        let results = self
-            .processor
-            .process_transactions_with_status(transactions)
-            .await;
+        let results = if self.dead_letter_failed_transactions {
+            self.process_batch_with_dead_letters(transactions).await
+        } else {
+            self.processor
+                .process_transactions_with_status(transactions)
+                .await
+        };
 
         let batch_millis = (chrono::Utc::now().naive_utc() - batch_start).num_milliseconds();
 
@@ -165,6 +272,137 @@ impl Tailer {
         (num_txns, Some(results))
     }
 
+    /// Processes the batch, and if it fails, processes its transactions one at a time so that only
+    /// the ones still failing are skipped, after recording them in the dead letter table. Failing
+    /// to get a connection isn't specific to a transaction, so it still fails the batch.
+    async fn process_batch_with_dead_letters(
+        &self,
+        transactions: Vec<Transaction>,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        let processor_name = self.processor.name();
+        let start_version = transactions.first().unwrap().version().unwrap();
+        let end_version = transactions.last().unwrap().version().unwrap();
+
+        let batch_error = match self
+            .processor
+            .process_transactions_with_status(transactions.clone())
+            .await
+        {
+            Err(TransactionProcessingError::ConnectionPoolError(ewv)) => {
+                return Err(TransactionProcessingError::ConnectionPoolError(ewv))
+            },
+            Err(tpe) => tpe,
+            result => return result,
+        };
+        warn!(
+            processor_name = processor_name,
+            start_version = start_version,
+            end_version = end_version,
+            error = ?batch_error.inner().0,
+            "Batch failed, processing its transactions one at a time"
+        );
+
+        for transaction in transactions {
+            let version = transaction.version().unwrap();
+            if let Err(tpe) = self
+                .processor
+                .process_transactions_with_status(vec![transaction])
+                .await
+            {
+                let (err, _, _, _) = tpe.inner();
+                error!(
+                    processor_name = processor_name,
+                    version = version,
+                    error = ?err,
+                    "Skipping the transaction after recording it as a dead letter"
+                );
+                self.add_dead_letter(version, err).map_err(|e| {
+                    TransactionProcessingError::TransactionCommitError((
+                        e,
+                        start_version,
+                        end_version,
+                        processor_name,
+                    ))
+                })?;
+                PROCESSOR_DEAD_LETTERS
+                    .with_label_values(&[processor_name])
+                    .inc();
+            }
+        }
+        Ok(ProcessingResult::new(
+            processor_name,
+            start_version,
+            end_version,
+        ))
+    }
+
+    /// Records that the processor failed on a transaction, or updates the error if it already did
+    fn add_dead_letter(&self, version: u64, err: &anyhow::Error) -> Result<()> {
+        let mut conn = self.connection_pool.get()?;
+        execute_with_better_error(
+            &mut conn,
+            diesel::insert_into(dead_letters::table)
+                .values(DeadLetter {
+                    processor: self.processor.name().to_string(),
+                    transaction_version: version as i64,
+                    error: format!("{:?}", err),
+                })
+                .on_conflict((dead_letters::processor, dead_letters::transaction_version))
+                .do_update()
+                .set(dead_letters::error.eq(excluded(dead_letters::error))),
+            None,
+        )?;
+        Ok(())
+    }
+
+    /// Processes again the dead letters of the processor, e.g. after deploying a fix, and removes
+    /// the ones that succeed this time. Returns how many succeeded and how many failed again.
+    pub async fn reprocess_dead_letters(&self) -> Result<(usize, usize)> {
+        let processor_name = self.processor.name();
+        let dead_letters =
+            DeadLetterQuery::get_by_processor(processor_name, &mut self.connection_pool.get()?)?;
+
+        let (mut num_succeeded, mut num_failed) = (0, 0);
+        for dead_letter in dead_letters {
+            let version = dead_letter.transaction_version as u64;
+            let transaction = self
+                .transaction_fetcher
+                .lock()
+                .await
+                .fetch_transaction(version)
+                .await;
+            match self
+                .processor
+                .process_transactions_with_status(vec![transaction])
+                .await
+            {
+                Ok(_) => {
+                    let mut conn = self.connection_pool.get()?;
+                    execute_with_better_error(
+                        &mut conn,
+                        diesel::delete(dead_letters::table)
+                            .filter(dead_letters::processor.eq(processor_name))
+                            .filter(dead_letters::transaction_version.eq(version as i64)),
+                        None,
+                    )?;
+                    num_succeeded += 1;
+                },
+                Err(tpe) => {
+                    let (err, _, _, _) = tpe.inner();
+                    warn!(
+                        processor_name = processor_name,
+                        version = version,
+                        error = ?err,
+                        "Dead letter failed again"
+                    );
+                    self.add_dead_letter(version, err)?;
+                    num_failed += 1;
+                },
+            }
+        }
+        Ok((num_succeeded, num_failed))
+    }
+
     /// Store last processed version from database. We can assume that all previously processed
     /// versions are successful because any gap would cause the processor to panic
     pub fn update_last_processed_version(&self, processor_name: &str, version: u64) -> Result<()> {
@@ -302,6 +540,79 @@ pub async fn await_tasks<T: Debug>(tasks: Vec<JoinHandle<T>>) -> Vec<T> {
     results
 }
 
+/// The tables created by the indexer migrations, as listed in the diesel schema
+const INDEXER_TABLES: &[&str] = &[
+    "account_transactions",
+    "balance_snapshots",
+    "block_metadata_transactions",
+    "coin_activities",
+    "coin_balances",
+    "coin_infos",
+    "coin_supply",
+    "collection_datas",
+    "collections_v2",
+    "current_ans_lookup",
+    "current_coin_balances",
+    "current_collection_datas",
+    "current_collections_v2",
+    "current_delegated_staking_pool_balances",
+    "current_delegator_balances",
+    "current_fungible_asset_balances",
+    "current_objects",
+    "current_staking_pool_voter",
+    "current_table_items",
+    "current_token_datas",
+    "current_token_datas_v2",
+    "current_token_ownerships",
+    "current_token_ownerships_v2",
+    "current_token_pending_claims",
+    "current_token_royalties_v2",
+    "current_token_v2_metadata",
+    "dead_letters",
+    "delegated_staking_activities",
+    "delegated_staking_pool_balances",
+    "delegated_staking_pools",
+    "events",
+    "fungible_asset_activities",
+    "fungible_asset_balances",
+    "fungible_asset_metadata",
+    "fungible_asset_supply",
+    "indexer_status",
+    "ledger_infos",
+    "move_modules",
+    "move_resources",
+    "nft_points",
+    "object_metadata",
+    "objects",
+    "processor_status",
+    "processor_statuses",
+    "proposal_votes",
+    "signatures",
+    "table_items",
+    "table_metadatas",
+    "token_activities",
+    "token_activities_v2",
+    "token_datas",
+    "token_datas_v2",
+    "token_ownerships",
+    "token_ownerships_v2",
+    "tokens",
+    "transactions",
+    "user_transactions",
+    "write_set_changes",
+];
+
+/// Truncates the indexer tables, leaving any other table in the database untouched
+fn truncate_tables(conn: &mut PgPoolConnection) -> Result<()> {
+    let table_names = INDEXER_TABLES
+        .iter()
+        .map(|table| format!("\"{}\"", table))
+        .collect::<Vec<_>>()
+        .join(", ");
+    sql_query(format!("TRUNCATE TABLE {}", table_names)).execute(conn)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -336,6 +647,14 @@ mod test {
             unimplemented!();
         }
 
+        fn fetch_genesis_hash(&mut self) -> String {
+            "0x1".to_string()
+        }
+
+        async fn fetch_transaction(&mut self, _version: u64) -> Transaction {
+            unimplemented!();
+        }
+
         fn fetch_ledger_info(&mut self) -> APILedgerInfo {
             APILedgerInfo {
                 chain_id: self.chain_id,
@@ -908,15 +1227,49 @@ mod test {
             .await
             .unwrap();
 
-        let (_conn_pool, tailer) = setup_indexer().unwrap();
+        let (conn_pool, tailer) = setup_indexer().unwrap();
+        tailer.set_fetcher_version(4).await;
+        assert!(tailer.check_or_update_chain_id(false).await.is_ok());
+        assert!(tailer.check_or_update_chain_id(false).await.is_ok());
+
+        tailer.set_fetcher_version(10).await;
+        assert!(tailer.check_or_update_chain_id(false).await.is_err());
+
         tailer.set_fetcher_version(4).await;
-        assert!(tailer.check_or_update_chain_id().await.is_ok());
-        assert!(tailer.check_or_update_chain_id().await.is_ok());
+        assert!(tailer.check_or_update_chain_id(false).await.is_ok());
 
+        // Wipes the existing data when indexing another chain
         tailer.set_fetcher_version(10).await;
-        assert!(tailer.check_or_update_chain_id().await.is_err());
+        assert_eq!(tailer.check_or_update_chain_id(true).await.unwrap(), 10);
+        assert!(tailer.check_or_update_chain_id(false).await.is_ok());
+        let ledger_info = LedgerInfo::get(&mut conn_pool.get().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(ledger_info.chain_id, 10);
+    }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_version_regression_keeps_data() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, tailer) = setup_indexer().unwrap();
+        let processor_name = tailer.processor.name().to_string();
         tailer.set_fetcher_version(4).await;
-        assert!(tailer.check_or_update_chain_id().await.is_ok());
+        assert!(tailer.check_or_update_chain_id(false).await.is_ok());
+        tailer
+            .update_last_processed_version(&processor_name, 5)
+            .unwrap();
+
+        // The fake ledger stays at version 0 on the same chain, behind the indexed data
+        assert!(tailer.check_or_update_chain_id(true).await.is_err());
+
+        let mut conn = conn_pool.get().unwrap();
+        let ledger_info = LedgerInfo::get(&mut conn).unwrap().unwrap();
+        assert_eq!(ledger_info.chain_id, 4);
+        let status = ProcessorStatusV2Query::get_by_processor(&processor_name, &mut conn)
+            .unwrap()
+            .unwrap();
+        assert_eq!(status.last_success_version, 5);
     }
 }
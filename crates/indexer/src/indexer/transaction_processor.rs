@@ -19,6 +19,9 @@ use schema::processor_statuses::{self, dsl};
 use std::fmt::Debug;
 
 /// The `TransactionProcessor` is used by an instance of a `Tailer` to process transactions
+///
+/// Processors outside of this crate can implement it and be registered with
+/// `processors::register_processor` to be selected by name in the indexer config.
 #[async_trait]
 pub trait TransactionProcessor: Send + Sync + Debug {
     /// name of the processor, for status logging
@@ -38,6 +41,27 @@ pub trait TransactionProcessor: Send + Sync + Debug {
     /// This is used by the `get_conn()` helper below
     fn connection_pool(&self) -> &PgDbPool;
 
+    //* Below are lifecycle hooks with a default implementation *//
+
+    /// Runs the migrations of the tables owned by the processor. This is called after the
+    /// migrations of the indexer tables, unless migrations are skipped in the config.
+    /// Processors outside of this crate can embed their own with `embed_migrations!`.
+    fn run_migrations(&self, _conn: &mut PgPoolConnection) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Whether a transaction should be passed to `process_transactions`. The versions of the
+    /// transactions filtered out are still marked as processed.
+    fn filter_transaction(&self, _transaction: &Transaction) -> bool {
+        true
+    }
+
+    /// Called once all versions up to `version` (inclusive) are processed and the checkpoint of
+    /// the processor, i.e. its last success version, is persisted. An error stops the indexer.
+    fn on_checkpoint(&self, _version: u64) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     //* Below are helper methods that don't need to be implemented *//
 
     /// Gets the connection.
@@ -79,6 +103,10 @@ pub trait TransactionProcessor: Send + Sync + Debug {
         let end_version = txns.last().unwrap().version().unwrap();
 
         self.mark_versions_started(start_version, end_version);
+        let txns = txns
+            .into_iter()
+            .filter(|txn| self.filter_transaction(txn))
+            .collect();
         let res = self
             .process_transactions(txns, start_version, end_version)
             .await;
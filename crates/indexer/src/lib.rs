@@ -13,11 +13,13 @@ extern crate diesel;
 
 pub mod counters;
 pub mod database;
+pub mod health;
 pub mod indexer;
 pub mod models;
 pub mod processors;
 pub mod runtime;
 pub mod schema;
+pub mod sinks;
 mod util;
 
 /// By default, skips test unless `INDEXER_DATABASE_URL` is set.
@@ -0,0 +1,118 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    models::{
+        coin_models::coin_balances::CoinBalance,
+        fungible_asset_models::v2_fungible_asset_balances::FungibleAssetBalance,
+    },
+    schema::balance_snapshots,
+    util::hash_str,
+};
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+// storage_id, asset_type_hash, snapshot_at
+pub type BalanceSnapshotPK = (String, String, chrono::NaiveDateTime);
+
+/// The balance of a coin store or a fungible store at the end of an interval, as of the last
+/// transaction changing it in that interval
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(storage_id, asset_type_hash, snapshot_at))]
+#[diesel(table_name = balance_snapshots)]
+pub struct BalanceSnapshot {
+    pub storage_id: String,
+    pub asset_type_hash: String,
+    pub snapshot_at: chrono::NaiveDateTime,
+    pub owner_address: String,
+    pub asset_type: String,
+    pub amount: BigDecimal,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl BalanceSnapshot {
+    /// Coin stores live at the address of their owner, so it identifies the store for a coin type
+    pub fn from_coin_balance(coin_balance: &CoinBalance, interval_secs: u64) -> Self {
+        Self {
+            storage_id: coin_balance.owner_address.clone(),
+            asset_type_hash: coin_balance.coin_type_hash.clone(),
+            snapshot_at: get_snapshot_time(coin_balance.transaction_timestamp, interval_secs),
+            owner_address: coin_balance.owner_address.clone(),
+            asset_type: coin_balance.coin_type.clone(),
+            amount: coin_balance.amount.clone(),
+            last_transaction_version: coin_balance.transaction_version,
+            last_transaction_timestamp: coin_balance.transaction_timestamp,
+        }
+    }
+
+    pub fn from_fungible_asset_balance(
+        fungible_asset_balance: &FungibleAssetBalance,
+        interval_secs: u64,
+    ) -> Self {
+        Self {
+            storage_id: fungible_asset_balance.storage_id.clone(),
+            asset_type_hash: hash_str(&fungible_asset_balance.asset_type),
+            snapshot_at: get_snapshot_time(
+                fungible_asset_balance.transaction_timestamp,
+                interval_secs,
+            ),
+            owner_address: fungible_asset_balance.owner_address.clone(),
+            asset_type: fungible_asset_balance.asset_type.clone(),
+            amount: fungible_asset_balance.amount.clone(),
+            last_transaction_version: fungible_asset_balance.transaction_version,
+            last_transaction_timestamp: fungible_asset_balance.transaction_timestamp,
+        }
+    }
+
+    pub fn pk(&self) -> BalanceSnapshotPK {
+        (
+            self.storage_id.clone(),
+            self.asset_type_hash.clone(),
+            self.snapshot_at,
+        )
+    }
+}
+
+/// Start of the interval of `interval_secs` seconds that the timestamp falls in, with the
+/// intervals counted from the unix epoch so that e.g. hourly ones start on the hour
+pub fn get_snapshot_time(
+    timestamp: chrono::NaiveDateTime,
+    interval_secs: u64,
+) -> chrono::NaiveDateTime {
+    let interval_secs = interval_secs as i64;
+    let secs = timestamp.timestamp();
+    chrono::NaiveDateTime::from_timestamp_opt(secs - secs.rem_euclid(interval_secs), 0)
+        .expect("Snapshot time is before the timestamp so it is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_snapshot_time() {
+        let timestamp = chrono::NaiveDateTime::parse_from_str(
+            "2023-08-14 17:18:24.123456",
+            "%Y-%m-%d %H:%M:%S%.f",
+        )
+        .unwrap();
+        assert_eq!(
+            get_snapshot_time(timestamp, 3600).to_string(),
+            "2023-08-14 17:00:00"
+        );
+        assert_eq!(
+            get_snapshot_time(timestamp, 86400).to_string(),
+            "2023-08-14 00:00:00"
+        );
+        assert_eq!(
+            get_snapshot_time(timestamp, 1).to_string(),
+            "2023-08-14 17:18:24"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+#![allow(clippy::extra_unused_lifetimes)]
+use crate::{database::PgPoolConnection, schema::dead_letters};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+#[derive(AsChangeset, Debug, Insertable)]
+#[diesel(table_name = dead_letters)]
+/// A transaction that a processor failed on and skipped
+pub struct DeadLetter {
+    pub processor: String,
+    pub transaction_version: i64,
+    pub error: String,
+}
+
+#[derive(Debug, Queryable)]
+#[diesel(table_name = dead_letters)]
+pub struct DeadLetterQuery {
+    pub processor: String,
+    pub transaction_version: i64,
+    pub error: String,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+impl DeadLetterQuery {
+    /// Gets the dead letters of a processor, oldest version first
+    pub fn get_by_processor(
+        processor_name: &str,
+        conn: &mut PgPoolConnection,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        dead_letters::table
+            .filter(dead_letters::processor.eq(processor_name))
+            .order(dead_letters::transaction_version.asc())
+            .load::<Self>(conn)
+    }
+}
@@ -20,6 +20,8 @@ pub struct Event {
     pub type_: String,
     pub data: serde_json::Value,
     pub event_index: Option<i64>,
+    /// Owner of the object at the time of the event, if the event was emitted by an object
+    pub object_owner_address: Option<String>,
 }
 
 /// Need a separate struct for queryable because we don't want to define the inserted_at column (letting DB fill)
@@ -37,6 +39,7 @@ pub struct EventQuery {
     pub data: serde_json::Value,
     pub inserted_at: chrono::NaiveDateTime,
     pub event_index: Option<i64>,
+    pub object_owner_address: Option<String>,
 }
 
 impl Event {
@@ -55,6 +58,7 @@ impl Event {
             type_: event.typ.to_string(),
             data: event.data.clone(),
             event_index: Some(event_index),
+            object_owner_address: None,
         }
     }
 
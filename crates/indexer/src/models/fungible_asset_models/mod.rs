@@ -0,0 +1,7 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod v2_fungible_asset_activities;
+pub mod v2_fungible_asset_balances;
+pub mod v2_fungible_asset_supply;
+pub mod v2_fungible_metadata;
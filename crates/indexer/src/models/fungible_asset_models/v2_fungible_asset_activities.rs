@@ -0,0 +1,77 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    models::{
+        coin_models::v2_fungible_asset_utils::FungibleAssetEvent,
+        token_models::v2_token_utils::TokenV2AggregatedDataMapping,
+    },
+    schema::fungible_asset_activities,
+    util::standardize_address,
+};
+use aptos_api_types::Event as APIEvent;
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, event_index))]
+#[diesel(table_name = fungible_asset_activities)]
+pub struct FungibleAssetActivity {
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub storage_id: String,
+    pub owner_address: String,
+    pub asset_type: String,
+    pub amount: BigDecimal,
+    pub type_: String,
+    pub entry_function_id_str: Option<String>,
+    pub block_height: i64,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl FungibleAssetActivity {
+    /// Parsing 0x1::fungible_asset::DepositEvent and WithdrawEvent. They are emitted by the
+    /// fungible store, which tells us the owner and the fungible asset.
+    pub fn from_event(
+        event: &APIEvent,
+        txn_version: i64,
+        block_height: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+        event_index: i64,
+        entry_function_id_str: &Option<String>,
+        fungible_asset_metadata: &TokenV2AggregatedDataMapping,
+    ) -> anyhow::Result<Option<Self>> {
+        let event_type = event.typ.to_string();
+        if let Some(fa_event) =
+            FungibleAssetEvent::from_event(event_type.as_str(), &event.data, txn_version)?
+        {
+            let storage_id = standardize_address(&event.guid.account_address.to_string());
+            if let Some(metadata) = fungible_asset_metadata.get(&storage_id) {
+                if let Some(fungible_asset_store) = &metadata.fungible_asset_store {
+                    let amount = match fa_event {
+                        FungibleAssetEvent::DepositEvent(inner) => inner.amount,
+                        FungibleAssetEvent::WithdrawEvent(inner) => inner.amount,
+                    };
+                    return Ok(Some(Self {
+                        transaction_version: txn_version,
+                        event_index,
+                        storage_id,
+                        owner_address: metadata.object.object_core.get_owner_address(),
+                        asset_type: fungible_asset_store.metadata.get_reference_address(),
+                        amount,
+                        type_: event_type,
+                        entry_function_id_str: entry_function_id_str.clone(),
+                        block_height,
+                        transaction_timestamp: txn_timestamp,
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    models::{
+        coin_models::v2_fungible_asset_utils::FungibleAssetStore,
+        token_models::v2_token_utils::TokenV2AggregatedDataMapping,
+    },
+    schema::{current_fungible_asset_balances, fungible_asset_balances},
+    util::standardize_address,
+};
+use aptos_api_types::WriteResource;
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+// PK of current_fungible_asset_balances, i.e. storage_id
+pub type CurrentFungibleAssetBalancePK = String;
+
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, write_set_change_index))]
+#[diesel(table_name = fungible_asset_balances)]
+pub struct FungibleAssetBalance {
+    pub transaction_version: i64,
+    pub write_set_change_index: i64,
+    pub storage_id: String,
+    pub owner_address: String,
+    pub asset_type: String,
+    pub is_frozen: bool,
+    pub amount: BigDecimal,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(storage_id))]
+#[diesel(table_name = current_fungible_asset_balances)]
+pub struct CurrentFungibleAssetBalance {
+    pub storage_id: String,
+    pub owner_address: String,
+    pub asset_type: String,
+    pub is_frozen: bool,
+    pub amount: BigDecimal,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl FungibleAssetBalance {
+    /// Parsing 0x1::fungible_asset::FungibleStore. The store is an object, and its owner is the
+    /// owner of the balance.
+    pub fn from_write_resource(
+        write_resource: &WriteResource,
+        write_set_change_index: i64,
+        txn_version: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+        fungible_asset_metadata: &TokenV2AggregatedDataMapping,
+    ) -> anyhow::Result<Option<(Self, CurrentFungibleAssetBalance)>> {
+        if let Some(inner) = FungibleAssetStore::from_write_resource(write_resource, txn_version)? {
+            let storage_id = standardize_address(&write_resource.address.to_string());
+            if let Some(metadata) = fungible_asset_metadata.get(&storage_id) {
+                let owner_address = metadata.object.object_core.get_owner_address();
+                let asset_type = inner.metadata.get_reference_address();
+                let balance = Self {
+                    transaction_version: txn_version,
+                    write_set_change_index,
+                    storage_id: storage_id.clone(),
+                    owner_address: owner_address.clone(),
+                    asset_type: asset_type.clone(),
+                    is_frozen: inner.frozen,
+                    amount: inner.balance.clone(),
+                    transaction_timestamp: txn_timestamp,
+                };
+                let current_balance = CurrentFungibleAssetBalance {
+                    storage_id,
+                    owner_address,
+                    asset_type,
+                    is_frozen: inner.frozen,
+                    amount: inner.balance,
+                    last_transaction_version: txn_version,
+                    last_transaction_timestamp: txn_timestamp,
+                };
+                return Ok(Some((balance, current_balance)));
+            }
+        }
+        Ok(None)
+    }
+}
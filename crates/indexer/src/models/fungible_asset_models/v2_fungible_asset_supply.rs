@@ -0,0 +1,48 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    models::coin_models::v2_fungible_asset_utils::FungibleAssetSupply,
+    schema::fungible_asset_supply, util::standardize_address,
+};
+use aptos_api_types::WriteResource;
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, asset_type))]
+#[diesel(table_name = fungible_asset_supply)]
+pub struct FungibleAssetSupplyModel {
+    pub transaction_version: i64,
+    pub asset_type: String,
+    pub current_supply: BigDecimal,
+    pub maximum_supply: Option<BigDecimal>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl FungibleAssetSupplyModel {
+    /// Parsing 0x1::fungible_asset::Supply, which is stored next to the metadata of the fungible
+    /// asset and changes on every mint and burn
+    pub fn from_write_resource(
+        write_resource: &WriteResource,
+        txn_version: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+    ) -> anyhow::Result<Option<Self>> {
+        if let Some(inner) = FungibleAssetSupply::from_write_resource(write_resource, txn_version)?
+        {
+            return Ok(Some(Self {
+                transaction_version: txn_version,
+                asset_type: standardize_address(&write_resource.address.to_string()),
+                current_supply: inner.current.clone(),
+                maximum_supply: inner.get_maximum(),
+                transaction_timestamp: txn_timestamp,
+            }));
+        }
+        Ok(None)
+    }
+}
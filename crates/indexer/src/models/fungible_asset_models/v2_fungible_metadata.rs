@@ -0,0 +1,67 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    models::{
+        coin_models::v2_fungible_asset_utils::FungibleAssetMetadata,
+        token_models::v2_token_utils::TokenV2AggregatedDataMapping,
+    },
+    schema::fungible_asset_metadata,
+    util::standardize_address,
+};
+use aptos_api_types::WriteResource;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+// PK of fungible_asset_metadata, i.e. asset_type
+pub type FungibleAssetMetadataPK = String;
+
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(asset_type))]
+#[diesel(table_name = fungible_asset_metadata)]
+pub struct FungibleAssetMetadataModel {
+    pub asset_type: String,
+    pub creator_address: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: i32,
+    pub icon_uri: String,
+    pub project_uri: String,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl FungibleAssetMetadataModel {
+    /// Parsing 0x1::fungible_asset::Metadata, which is stored in the object identifying the
+    /// fungible asset. The creator is the owner of that object.
+    pub fn from_write_resource(
+        write_resource: &WriteResource,
+        txn_version: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+        fungible_asset_metadata: &TokenV2AggregatedDataMapping,
+    ) -> anyhow::Result<Option<Self>> {
+        if let Some(inner) =
+            FungibleAssetMetadata::from_write_resource(write_resource, txn_version)?
+        {
+            let asset_type = standardize_address(&write_resource.address.to_string());
+            if let Some(metadata) = fungible_asset_metadata.get(&asset_type) {
+                return Ok(Some(Self {
+                    asset_type,
+                    creator_address: metadata.object.object_core.get_owner_address(),
+                    name: inner.get_name(),
+                    symbol: inner.get_symbol(),
+                    decimals: inner.decimals,
+                    icon_uri: inner.get_icon_uri(),
+                    project_uri: inner.get_project_uri(),
+                    last_transaction_version: txn_version,
+                    last_transaction_timestamp: txn_timestamp,
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -9,6 +9,8 @@ use diesel::{OptionalExtension, QueryDsl, RunQueryDsl};
 #[diesel(primary_key(chain_id))]
 pub struct LedgerInfo {
     pub chain_id: i64,
+    /// Accumulator root hash of the genesis, missing if the chain id was stored by an older indexer
+    pub genesis_hash: Option<String>,
 }
 
 impl LedgerInfo {
@@ -1,9 +1,12 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod balance_snapshots;
 pub mod block_metadata_transactions;
 pub mod coin_models;
+pub mod dead_letters;
 pub mod events;
+pub mod fungible_asset_models;
 pub mod ledger_info;
 pub mod move_modules;
 pub mod move_resources;
@@ -15,4 +15,5 @@ pub mod v2_token_activities;
 pub mod v2_token_datas;
 pub mod v2_token_metadata;
 pub mod v2_token_ownerships;
+pub mod v2_token_royalties;
 pub mod v2_token_utils;
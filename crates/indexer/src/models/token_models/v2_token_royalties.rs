@@ -0,0 +1,55 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use super::v2_token_utils::{Royalty, TokenV2AggregatedDataMapping};
+use crate::{schema::current_token_royalties_v2, util::standardize_address};
+use aptos_api_types::WriteResource;
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+// PK of current_token_royalties_v2, i.e. object_address
+pub type CurrentTokenRoyaltyV2PK = String;
+
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(object_address))]
+#[diesel(table_name = current_token_royalties_v2)]
+pub struct CurrentTokenRoyaltyV2 {
+    pub object_address: String,
+    pub payee_address: String,
+    pub royalty_points_numerator: BigDecimal,
+    pub royalty_points_denominator: BigDecimal,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl CurrentTokenRoyaltyV2 {
+    /// Parsing 0x4::royalty::Royalty, which can be stored in either a collection or a token object
+    pub fn from_write_resource(
+        write_resource: &WriteResource,
+        txn_version: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+        token_v2_metadata: &TokenV2AggregatedDataMapping,
+    ) -> anyhow::Result<Option<Self>> {
+        if let Some(royalty) = Royalty::from_write_resource(write_resource, txn_version)? {
+            let object_address = standardize_address(&write_resource.address.to_string());
+            // Royalties only apply to objects
+            if !token_v2_metadata.contains_key(&object_address) {
+                return Ok(None);
+            }
+            return Ok(Some(Self {
+                object_address,
+                payee_address: royalty.get_payee_address(),
+                royalty_points_numerator: royalty.numerator,
+                royalty_points_denominator: royalty.denominator,
+                last_transaction_version: txn_version,
+                last_transaction_timestamp: txn_timestamp,
+            }));
+        }
+        Ok(None)
+    }
+}
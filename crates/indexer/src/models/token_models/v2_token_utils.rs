@@ -43,6 +43,7 @@ pub struct TokenV2AggregatedData {
     pub fungible_asset_store: Option<FungibleAssetStore>,
     pub object: ObjectWithMetadata,
     pub property_map: Option<PropertyMap>,
+    pub royalty: Option<Royalty>,
     pub token: Option<TokenV2>,
     pub transfer_event: Option<(EventIndex, TransferEvent)>,
     pub unlimited_supply: Option<UnlimitedSupply>,
@@ -323,6 +324,51 @@ impl UnlimitedSupply {
     }
 }
 
+/* Section on Royalties */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Royalty {
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub numerator: BigDecimal,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub denominator: BigDecimal,
+    pub payee_address: String,
+}
+
+impl Royalty {
+    pub fn from_write_resource(
+        write_resource: &WriteResource,
+        txn_version: i64,
+    ) -> anyhow::Result<Option<Self>> {
+        let type_str = format!(
+            "{}::{}::{}",
+            write_resource.data.typ.address,
+            write_resource.data.typ.module,
+            write_resource.data.typ.name
+        );
+        if !V2TokenResource::is_resource_supported(type_str.as_str()) {
+            return Ok(None);
+        }
+        let resource = MoveResource::from_write_resource(
+            write_resource,
+            0, // Placeholder, this isn't used anyway
+            txn_version,
+            0, // Placeholder, this isn't used anyway
+        );
+
+        if let V2TokenResource::Royalty(inner) =
+            V2TokenResource::from_resource(&type_str, resource.data.as_ref().unwrap(), txn_version)?
+        {
+            Ok(Some(inner))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn get_payee_address(&self) -> String {
+        standardize_address(&self.payee_address)
+    }
+}
+
 /* Section on Events */
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MintEvent {
@@ -447,6 +493,7 @@ pub enum V2TokenResource {
     UnlimitedSupply(UnlimitedSupply),
     TokenV2(TokenV2),
     PropertyMap(PropertyMap),
+    Royalty(Royalty),
 }
 
 impl V2TokenResource {
@@ -460,6 +507,7 @@ impl V2TokenResource {
                 | "0x4::aptos_token::AptosCollection"
                 | "0x4::token::Token"
                 | "0x4::property_map::PropertyMap"
+                | "0x4::royalty::Royalty"
         )
     }
 
@@ -490,6 +538,9 @@ impl V2TokenResource {
             "0x4::property_map::PropertyMap" => {
                 serde_json::from_value(data.clone()).map(|inner| Some(Self::PropertyMap(inner)))
             },
+            "0x4::royalty::Royalty" => {
+                serde_json::from_value(data.clone()).map(|inner| Some(Self::Royalty(inner)))
+            },
             _ => Ok(None),
         }
         .context(format!(
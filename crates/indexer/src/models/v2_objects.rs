@@ -12,7 +12,7 @@ use super::token_models::{
 use crate::{
     database::PgPoolConnection,
     models::move_resources::MoveResource,
-    schema::{current_objects, objects},
+    schema::{current_objects, object_metadata, objects},
 };
 use aptos_api_types::{DeleteResource, WriteResource};
 use bigdecimal::BigDecimal;
@@ -51,6 +51,17 @@ pub struct CurrentObject {
     pub is_deleted: bool,
 }
 
+/// Who created an object, which is the sender of the first transaction writing the object, or its
+/// owner if that isn't a user transaction
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(object_address))]
+#[diesel(table_name = object_metadata)]
+pub struct ObjectMetadata {
+    pub object_address: String,
+    pub creator_address: String,
+    pub first_transaction_version: i64,
+}
+
 #[derive(Debug, Deserialize, Identifiable, Queryable, Serialize)]
 #[diesel(primary_key(object_address))]
 #[diesel(table_name = current_objects)]
@@ -192,6 +203,16 @@ impl Object {
     }
 }
 
+impl ObjectMetadata {
+    pub fn from_object(object: &Object, txn_sender: Option<&String>) -> Self {
+        Self {
+            object_address: object.object_address.clone(),
+            creator_address: txn_sender.unwrap_or(&object.owner_address).clone(),
+            first_transaction_version: object.transaction_version,
+        }
+    }
+}
+
 impl CurrentObjectQuery {
     /// TODO: Change this to a KV store
     pub fn get_by_address(
@@ -0,0 +1,195 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::fungible_asset_processor::parse_v2_fungible_assets;
+use crate::{
+    database::{
+        clean_data_for_db, execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection,
+    },
+    indexer::{
+        errors::TransactionProcessingError, processing_result::ProcessingResult,
+        transaction_processor::TransactionProcessor,
+    },
+    models::{
+        balance_snapshots::{BalanceSnapshot, BalanceSnapshotPK},
+        coin_models::coin_activities::CoinActivity,
+    },
+    schema,
+    sinks::{ProcessorSink, SinkRecords},
+};
+use aptos_api_types::Transaction;
+use async_trait::async_trait;
+use diesel::{pg::upsert::excluded, result::Error, ExpressionMethods, PgConnection};
+use field_count::FieldCount;
+use std::{collections::HashMap, fmt::Debug};
+
+pub const NAME: &str = "balance_history_processor";
+
+pub struct BalanceHistoryTransactionProcessor {
+    connection_pool: PgDbPool,
+    snapshot_interval_secs: u64,
+    sink: Option<ProcessorSink>,
+}
+
+impl BalanceHistoryTransactionProcessor {
+    pub fn new(connection_pool: PgDbPool, snapshot_interval_secs: u64) -> Self {
+        Self {
+            connection_pool,
+            snapshot_interval_secs,
+            sink: None,
+        }
+    }
+
+    pub fn with_sink(mut self, sink: Option<ProcessorSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+}
+
+impl Debug for BalanceHistoryTransactionProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "BalanceHistoryTransactionProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+fn insert_to_db(
+    conn: &mut PgPoolConnection,
+    name: &'static str,
+    start_version: u64,
+    end_version: u64,
+    balance_snapshots: Vec<BalanceSnapshot>,
+) -> Result<(), diesel::result::Error> {
+    aptos_logger::trace!(
+        name = name,
+        start_version = start_version,
+        end_version = end_version,
+        "Inserting to db",
+    );
+    match conn
+        .build_transaction()
+        .read_write()
+        .run::<_, Error, _>(|pg_conn| insert_balance_snapshots(pg_conn, &balance_snapshots))
+    {
+        Ok(_) => Ok(()),
+        Err(_) => conn
+            .build_transaction()
+            .read_write()
+            .run::<_, Error, _>(|pg_conn| {
+                let balance_snapshots = clean_data_for_db(balance_snapshots, true);
+                insert_balance_snapshots(pg_conn, &balance_snapshots)
+            }),
+    }
+}
+
+fn insert_balance_snapshots(
+    conn: &mut PgConnection,
+    item_to_insert: &[BalanceSnapshot],
+) -> Result<(), diesel::result::Error> {
+    use schema::balance_snapshots::dsl::*;
+
+    let chunks = get_chunks(item_to_insert.len(), BalanceSnapshot::field_count());
+    for (start_ind, end_ind) in chunks {
+        execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::balance_snapshots::table)
+                .values(&item_to_insert[start_ind..end_ind])
+                .on_conflict((storage_id, asset_type_hash, snapshot_at))
+                .do_update()
+                .set((
+                    owner_address.eq(excluded(owner_address)),
+                    amount.eq(excluded(amount)),
+                    last_transaction_version.eq(excluded(last_transaction_version)),
+                    last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+                    inserted_at.eq(excluded(inserted_at)),
+                )),
+            Some(" WHERE balance_snapshots.last_transaction_version <= excluded.last_transaction_version "),
+        )?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl TransactionProcessor for BalanceHistoryTransactionProcessor {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    async fn process_transactions(
+        &self,
+        transactions: Vec<Transaction>,
+        start_version: u64,
+        end_version: u64,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        let mut conn = self.get_conn();
+
+        // The transactions are in version order, so the last balance of each store in an
+        // interval overrides the earlier ones
+        let mut balance_snapshots: HashMap<BalanceSnapshotPK, BalanceSnapshot> = HashMap::new();
+        for txn in &transactions {
+            // The supply isn't needed, so neither is the coin info of the APT coin
+            let (_, coin_balances, _, _, _) = CoinActivity::from_transaction(txn, &None);
+            for coin_balance in &coin_balances {
+                let snapshot =
+                    BalanceSnapshot::from_coin_balance(coin_balance, self.snapshot_interval_secs);
+                balance_snapshots.insert(snapshot.pk(), snapshot);
+            }
+        }
+        let (_, _, fungible_asset_balances, _, _) = parse_v2_fungible_assets(&transactions);
+        for fungible_asset_balance in &fungible_asset_balances {
+            let snapshot = BalanceSnapshot::from_fungible_asset_balance(
+                fungible_asset_balance,
+                self.snapshot_interval_secs,
+            );
+            balance_snapshots.insert(snapshot.pk(), snapshot);
+        }
+
+        let mut balance_snapshots = balance_snapshots
+            .into_values()
+            .collect::<Vec<BalanceSnapshot>>();
+        // Sort by PK
+        balance_snapshots.sort_by_key(|snapshot| snapshot.pk());
+
+        let sink_records = self.sink.as_ref().map(|_| {
+            let mut records = SinkRecords::default();
+            records.add("balance_snapshots", &balance_snapshots);
+            records
+        });
+
+        let tx_result = match &self.sink {
+            Some(sink) if sink.skip_postgres_writes() => Ok(()),
+            _ => insert_to_db(
+                &mut conn,
+                self.name(),
+                start_version,
+                end_version,
+                balance_snapshots,
+            ),
+        };
+        if let Err(err) = tx_result {
+            return Err(TransactionProcessingError::TransactionCommitError((
+                anyhow::Error::from(err),
+                start_version,
+                end_version,
+                self.name(),
+            )));
+        }
+        if let (Some(sink), Some(sink_records)) = (&self.sink, sink_records) {
+            sink.publish(sink_records, start_version, end_version, self.name())
+                .await?;
+        }
+        Ok(ProcessingResult::new(
+            self.name(),
+            start_version,
+            end_version,
+        ))
+    }
+
+    fn connection_pool(&self) -> &PgDbPool {
+        &self.connection_pool
+    }
+}
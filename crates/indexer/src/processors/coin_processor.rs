@@ -17,6 +17,7 @@ use crate::{
         coin_supply::CoinSupply,
     },
     schema,
+    sinks::{ProcessorSink, SinkRecords},
 };
 use aptos_api_types::Transaction as APITransaction;
 use aptos_types::APTOS_COIN_TYPE;
@@ -28,11 +29,20 @@ use std::{collections::HashMap, fmt::Debug};
 pub const NAME: &str = "coin_processor";
 pub struct CoinTransactionProcessor {
     connection_pool: PgDbPool,
+    sink: Option<ProcessorSink>,
 }
 
 impl CoinTransactionProcessor {
     pub fn new(connection_pool: PgDbPool) -> Self {
-        Self { connection_pool }
+        Self {
+            connection_pool,
+            sink: None,
+        }
+    }
+
+    pub fn with_sink(mut self, sink: Option<ProcessorSink>) -> Self {
+        self.sink = sink;
+        self
     }
 }
 
@@ -328,31 +338,54 @@ impl TransactionProcessor for CoinTransactionProcessor {
                 .cmp(&(&b.transaction_version, &b.account_address))
         });
 
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            all_coin_activities,
-            all_coin_infos,
-            all_coin_balances,
-            all_current_coin_balances,
-            all_coin_supply,
-            account_transactions,
-        );
-        match tx_result {
-            Ok(_) => Ok(ProcessingResult::new(
+        let sink_records = self.sink.as_ref().map(|_| {
+            let mut records = SinkRecords::default();
+            records.add("coin_activities", &all_coin_activities);
+            records.add("coin_infos", &all_coin_infos);
+            records.add("coin_balances", &all_coin_balances);
+            records.add("current_coin_balances", &all_current_coin_balances);
+            records.add("coin_supply", &all_coin_supply);
+            records.add("account_transactions", &account_transactions);
+            records
+        });
+
+        // The coin activities may only be stored in the sink, e.g. ClickHouse
+        let all_coin_activities = match &self.sink {
+            Some(sink) if sink.skip_postgres_writes_for("coin_activities") => vec![],
+            _ => all_coin_activities,
+        };
+        let tx_result = match &self.sink {
+            Some(sink) if sink.skip_postgres_writes() => Ok(()),
+            _ => insert_to_db(
+                &mut conn,
                 self.name(),
                 start_version,
                 end_version,
-            )),
-            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+                all_coin_activities,
+                all_coin_infos,
+                all_coin_balances,
+                all_current_coin_balances,
+                all_coin_supply,
+                account_transactions,
+            ),
+        };
+        if let Err(err) = tx_result {
+            return Err(TransactionProcessingError::TransactionCommitError((
                 anyhow::Error::from(err),
                 start_version,
                 end_version,
                 self.name(),
-            ))),
+            )));
+        }
+        if let (Some(sink), Some(sink_records)) = (&self.sink, sink_records) {
+            sink.publish(sink_records, start_version, end_version, self.name())
+                .await?;
         }
+        Ok(ProcessingResult::new(
+            self.name(),
+            start_version,
+            end_version,
+        ))
     }
 
     fn connection_pool(&self) -> &PgDbPool {
@@ -18,10 +18,11 @@ use crate::{
         signatures::Signature,
         transactions::{TransactionDetail, TransactionModel},
         user_transactions::UserTransactionModel,
-        v2_objects::{CurrentObject, Object},
+        v2_objects::{CurrentObject, Object, ObjectMetadata},
         write_set_changes::{WriteSetChangeDetail, WriteSetChangeModel},
     },
     schema,
+    sinks::{ProcessorSink, SinkRecords},
 };
 use aptos_api_types::{Transaction, WriteSetChange};
 use async_trait::async_trait;
@@ -32,11 +33,20 @@ use std::{collections::HashMap, fmt::Debug};
 pub const NAME: &str = "default_processor";
 pub struct DefaultTransactionProcessor {
     connection_pool: PgDbPool,
+    sink: Option<ProcessorSink>,
 }
 
 impl DefaultTransactionProcessor {
     pub fn new(connection_pool: PgDbPool) -> Self {
-        Self { connection_pool }
+        Self {
+            connection_pool,
+            sink: None,
+        }
+    }
+
+    pub fn with_sink(mut self, sink: Option<ProcessorSink>) -> Self {
+        self.sink = sink;
+        self
     }
 }
 
@@ -68,12 +78,12 @@ fn insert_to_db_impl(
         &[CurrentTableItem],
         &[TableMetadata],
     ),
-    object_core: (&[Object], &[CurrentObject]),
+    object_core: (&[Object], &[CurrentObject], &[ObjectMetadata]),
 ) -> Result<(), diesel::result::Error> {
     let (user_transactions, signatures, block_metadata_transactions) = txn_details;
     let (move_modules, move_resources, table_items, current_table_items, table_metadata) =
         wsc_details;
-    let (objects, current_objects) = object_core;
+    let (objects, current_objects, object_metadata) = object_core;
     insert_transactions(conn, txns)?;
     insert_user_transactions(conn, user_transactions)?;
     insert_signatures(conn, signatures)?;
@@ -87,6 +97,7 @@ fn insert_to_db_impl(
     insert_table_metadata(conn, table_metadata)?;
     insert_objects(conn, objects)?;
     insert_current_objects(conn, current_objects)?;
+    insert_object_metadata(conn, object_metadata)?;
     Ok(())
 }
 
@@ -110,7 +121,7 @@ fn insert_to_db(
         Vec<CurrentTableItem>,
         Vec<TableMetadata>,
     ),
-    object_core: (Vec<Object>, Vec<CurrentObject>),
+    object_core: (Vec<Object>, Vec<CurrentObject>, Vec<ObjectMetadata>),
 ) -> Result<(), diesel::result::Error> {
     aptos_logger::trace!(
         name = name,
@@ -121,7 +132,7 @@ fn insert_to_db(
     let (user_transactions, signatures, block_metadata_transactions) = txn_details;
     let (move_modules, move_resources, table_items, current_table_items, table_metadata) =
         wsc_details;
-    let (objects, current_objects) = object_core;
+    let (objects, current_objects, object_metadata) = object_core;
     match conn
         .build_transaction()
         .read_write()
@@ -143,7 +154,7 @@ fn insert_to_db(
                     &current_table_items,
                     &table_metadata,
                 ),
-                (&objects, &current_objects),
+                (&objects, &current_objects, &object_metadata),
             )
         }) {
         Ok(_) => Ok(()),
@@ -161,6 +172,7 @@ fn insert_to_db(
             let table_metadata = clean_data_for_db(table_metadata, true);
             let objects = clean_data_for_db(objects, true);
             let current_objects = clean_data_for_db(current_objects, true);
+            let object_metadata = clean_data_for_db(object_metadata, true);
 
             conn.build_transaction()
                 .read_write()
@@ -182,7 +194,7 @@ fn insert_to_db(
                             &current_table_items,
                             &table_metadata,
                         ),
-                        (&objects, &current_objects),
+                        (&objects, &current_objects, &object_metadata),
                     )
                 })
         },
@@ -289,6 +301,7 @@ fn insert_events(
                 .set((
                     inserted_at.eq(excluded(inserted_at)),
                     event_index.eq(excluded(event_index)),
+                    object_owner_address.eq(excluded(object_owner_address)),
                 )),
             None,
         )?;
@@ -469,6 +482,30 @@ fn insert_current_objects(
     Ok(())
 }
 
+fn insert_object_metadata(
+    conn: &mut PgConnection,
+    items_to_insert: &[ObjectMetadata],
+) -> Result<(), diesel::result::Error> {
+    use schema::object_metadata::dsl::*;
+    let chunks = get_chunks(items_to_insert.len(), ObjectMetadata::field_count());
+    for (start_ind, end_ind) in chunks {
+        execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::object_metadata::table)
+                .values(&items_to_insert[start_ind..end_ind])
+                .on_conflict(object_address)
+                .do_update()
+                .set((
+                    creator_address.eq(excluded(creator_address)),
+                    first_transaction_version.eq(excluded(first_transaction_version)),
+                    inserted_at.eq(excluded(inserted_at)),
+                )),
+            Some(" WHERE object_metadata.first_transaction_version > excluded.first_transaction_version "),
+        )?;
+    }
+    Ok(())
+}
+
 #[async_trait]
 impl TransactionProcessor for DefaultTransactionProcessor {
     fn name(&self) -> &'static str {
@@ -483,7 +520,7 @@ impl TransactionProcessor for DefaultTransactionProcessor {
     ) -> Result<ProcessingResult, TransactionProcessingError> {
         let mut conn = self.get_conn();
 
-        let (txns, txn_details, events, write_set_changes, wsc_details) =
+        let (txns, txn_details, mut events, write_set_changes, wsc_details) =
             TransactionModel::from_transactions(&transactions);
 
         let mut signatures = vec![];
@@ -575,6 +612,39 @@ impl TransactionProcessor for DefaultTransactionProcessor {
                 }
             }
         }
+        // Objects emit events from the resources of their resource group, and their ObjectCore is
+        // written along, so the owner of the object at the time of the event is known
+        let object_owners: HashMap<(i64, &String), &String> = all_objects
+            .iter()
+            .map(|object| {
+                (
+                    (object.transaction_version, &object.object_address),
+                    &object.owner_address,
+                )
+            })
+            .collect();
+        for event in events.iter_mut() {
+            event.object_owner_address = object_owners
+                .get(&(event.transaction_version, &event.account_address))
+                .map(|owner_address| owner_address.to_string());
+        }
+        let txn_senders: HashMap<i64, &String> = user_transactions
+            .iter()
+            .map(|user_txn| (user_txn.version, &user_txn.sender))
+            .collect();
+        // The objects are in version order, so this keeps the first transaction writing each one
+        let mut object_metadata: HashMap<String, ObjectMetadata> = HashMap::new();
+        for object in &all_objects {
+            object_metadata
+                .entry(object.object_address.clone())
+                .or_insert_with(|| {
+                    ObjectMetadata::from_object(
+                        object,
+                        txn_senders.get(&object.transaction_version).copied(),
+                    )
+                });
+        }
+
         // Getting list of values and sorting by pk in order to avoid postgres deadlock since we're doing multi threaded db writes
         let mut current_table_items = current_table_items
             .into_values()
@@ -589,38 +659,73 @@ impl TransactionProcessor for DefaultTransactionProcessor {
             .sort_by(|a, b| (&a.table_handle, &a.key_hash).cmp(&(&b.table_handle, &b.key_hash)));
         table_metadata.sort_by(|a, b| a.handle.cmp(&b.handle));
         all_current_objects.sort_by(|a, b| a.object_address.cmp(&b.object_address));
+        let mut object_metadata = object_metadata
+            .into_values()
+            .collect::<Vec<ObjectMetadata>>();
+        object_metadata.sort_by(|a, b| a.object_address.cmp(&b.object_address));
 
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            txns,
-            (user_transactions, signatures, block_metadata_transactions),
-            events,
-            write_set_changes,
-            (
-                move_modules,
-                move_resources,
-                table_items,
-                current_table_items,
-                table_metadata,
-            ),
-            (all_objects, all_current_objects),
-        );
-        match tx_result {
-            Ok(_) => Ok(ProcessingResult::new(
+        let sink_records = self.sink.as_ref().map(|_| {
+            let mut records = SinkRecords::default();
+            records.add("transactions", &txns);
+            records.add("user_transactions", &user_transactions);
+            records.add("signatures", &signatures);
+            records.add("block_metadata_transactions", &block_metadata_transactions);
+            records.add("events", &events);
+            records.add("write_set_changes", &write_set_changes);
+            records.add("move_modules", &move_modules);
+            records.add("move_resources", &move_resources);
+            records.add("table_items", &table_items);
+            records.add("current_table_items", &current_table_items);
+            records.add("table_metadatas", &table_metadata);
+            records.add("objects", &all_objects);
+            records.add("current_objects", &all_current_objects);
+            records.add("object_metadata", &object_metadata);
+            records
+        });
+
+        // The events may only be stored in the sink, e.g. ClickHouse
+        let events = match &self.sink {
+            Some(sink) if sink.skip_postgres_writes_for("events") => vec![],
+            _ => events,
+        };
+        let tx_result = match &self.sink {
+            Some(sink) if sink.skip_postgres_writes() => Ok(()),
+            _ => insert_to_db(
+                &mut conn,
                 self.name(),
                 start_version,
                 end_version,
-            )),
-            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+                txns,
+                (user_transactions, signatures, block_metadata_transactions),
+                events,
+                write_set_changes,
+                (
+                    move_modules,
+                    move_resources,
+                    table_items,
+                    current_table_items,
+                    table_metadata,
+                ),
+                (all_objects, all_current_objects, object_metadata),
+            ),
+        };
+        if let Err(err) = tx_result {
+            return Err(TransactionProcessingError::TransactionCommitError((
                 anyhow::Error::from(err),
                 start_version,
                 end_version,
                 self.name(),
-            ))),
+            )));
         }
+        if let (Some(sink), Some(sink_records)) = (&self.sink, sink_records) {
+            sink.publish(sink_records, start_version, end_version, self.name())
+                .await?;
+        }
+        Ok(ProcessingResult::new(
+            self.name(),
+            start_version,
+            end_version,
+        ))
     }
 
     fn connection_pool(&self) -> &PgDbPool {
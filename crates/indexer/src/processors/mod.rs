@@ -1,21 +1,73 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod balance_history_processor;
 pub mod coin_processor;
 pub mod default_processor;
+pub mod fungible_asset_processor;
 pub mod stake_processor;
 pub mod token_processor;
 
 use self::{
+    balance_history_processor::NAME as BALANCE_HISTORY_PROCESSOR_NAME,
     coin_processor::NAME as COIN_PROCESSOR_NAME, default_processor::NAME as DEFAULT_PROCESSOR_NAME,
+    fungible_asset_processor::NAME as FUNGIBLE_ASSET_PROCESSOR_NAME,
     stake_processor::NAME as STAKE_PROCESSOR_NAME, token_processor::NAME as TOKEN_PROCESSOR_NAME,
 };
+use crate::{database::PgDbPool, indexer::transaction_processor::TransactionProcessor};
+use anyhow::{ensure, Result};
+use aptos_config::config::IndexerConfig;
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// Builds a processor registered with `register_processor` from the indexer config
+pub type ProcessorBuilder =
+    Arc<dyn Fn(&IndexerConfig, PgDbPool) -> Arc<dyn TransactionProcessor> + Send + Sync>;
+
+/// Processors defined outside of this crate, keyed by the name used in the indexer config
+static CUSTOM_PROCESSORS: Lazy<RwLock<HashMap<String, ProcessorBuilder>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+const BUILT_IN_PROCESSOR_NAMES: [&str; 6] = [
+    DEFAULT_PROCESSOR_NAME,
+    TOKEN_PROCESSOR_NAME,
+    COIN_PROCESSOR_NAME,
+    STAKE_PROCESSOR_NAME,
+    FUNGIBLE_ASSET_PROCESSOR_NAME,
+    BALANCE_HISTORY_PROCESSOR_NAME,
+];
+
+/// Registers a processor so that it can be selected by `name` in the indexer config. This needs
+/// to be called before the indexer is bootstrapped. The name is also the key of the processor's
+/// checkpoint in the `processor_status` table, so it should be what `TransactionProcessor::name`
+/// returns.
+pub fn register_processor(name: &str, builder: ProcessorBuilder) -> Result<()> {
+    ensure!(
+        !BUILT_IN_PROCESSOR_NAMES.contains(&name),
+        "Processor {} is built in",
+        name
+    );
+    let mut custom_processors = CUSTOM_PROCESSORS.write().unwrap();
+    ensure!(
+        !custom_processors.contains_key(name),
+        "Processor {} is already registered",
+        name
+    );
+    custom_processors.insert(name.to_string(), builder);
+    Ok(())
+}
 
 pub enum Processor {
     CoinProcessor,
     DefaultProcessor,
     TokenProcessor,
     StakeProcessor,
+    FungibleAssetProcessor,
+    BalanceHistoryProcessor,
+    CustomProcessor(ProcessorBuilder),
 }
 
 impl Processor {
@@ -25,7 +77,30 @@ impl Processor {
             TOKEN_PROCESSOR_NAME => Self::TokenProcessor,
             COIN_PROCESSOR_NAME => Self::CoinProcessor,
             STAKE_PROCESSOR_NAME => Self::StakeProcessor,
-            _ => panic!("Processor unsupported {}", input_str),
+            FUNGIBLE_ASSET_PROCESSOR_NAME => Self::FungibleAssetProcessor,
+            BALANCE_HISTORY_PROCESSOR_NAME => Self::BalanceHistoryProcessor,
+            _ => match CUSTOM_PROCESSORS.read().unwrap().get(input_str) {
+                Some(builder) => Self::CustomProcessor(builder.clone()),
+                None => panic!("Processor unsupported {}", input_str),
+            },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_processor() {
+        let builder: ProcessorBuilder = Arc::new(|_, _| unimplemented!());
+        assert!(register_processor(DEFAULT_PROCESSOR_NAME, builder.clone()).is_err());
+
+        register_processor("test_custom_processor", builder.clone()).unwrap();
+        assert!(register_processor("test_custom_processor", builder).is_err());
+        assert!(matches!(
+            Processor::from_string(&"test_custom_processor".to_string()),
+            Processor::CustomProcessor(_)
+        ));
+    }
+}
@@ -19,6 +19,7 @@ use crate::{
         staking_pool_voter::{CurrentStakingPoolVoter, StakingPoolVoterMap},
     },
     schema,
+    sinks::{ProcessorSink, SinkRecords},
 };
 use aptos_api_types::Transaction as APITransaction;
 use async_trait::async_trait;
@@ -29,11 +30,20 @@ use std::{collections::HashMap, fmt::Debug};
 pub const NAME: &str = "stake_processor";
 pub struct StakeTransactionProcessor {
     connection_pool: PgDbPool,
+    sink: Option<ProcessorSink>,
 }
 
 impl StakeTransactionProcessor {
     pub fn new(connection_pool: PgDbPool) -> Self {
-        Self { connection_pool }
+        Self {
+            connection_pool,
+            sink: None,
+        }
+    }
+
+    pub fn with_sink(mut self, sink: Option<ProcessorSink>) -> Self {
+        self.sink = sink;
+        self
     }
 }
 
@@ -384,32 +394,57 @@ impl TransactionProcessor for StakeTransactionProcessor {
         all_current_delegator_pool_balances
             .sort_by(|a, b| a.staking_pool_address.cmp(&b.staking_pool_address));
 
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            all_current_stake_pool_voters,
-            all_proposal_votes,
-            all_delegator_activities,
-            all_delegator_balances,
-            all_delegator_pools,
-            all_delegator_pool_balances,
-            all_current_delegator_pool_balances,
-        );
-        match tx_result {
-            Ok(_) => Ok(ProcessingResult::new(
+        let sink_records = self.sink.as_ref().map(|_| {
+            let mut records = SinkRecords::default();
+            records.add("current_staking_pool_voter", &all_current_stake_pool_voters);
+            records.add("proposal_votes", &all_proposal_votes);
+            records.add("delegated_staking_activities", &all_delegator_activities);
+            records.add("current_delegator_balances", &all_delegator_balances);
+            records.add("delegated_staking_pools", &all_delegator_pools);
+            records.add(
+                "delegated_staking_pool_balances",
+                &all_delegator_pool_balances,
+            );
+            records.add(
+                "current_delegated_staking_pool_balances",
+                &all_current_delegator_pool_balances,
+            );
+            records
+        });
+
+        let tx_result = match &self.sink {
+            Some(sink) if sink.skip_postgres_writes() => Ok(()),
+            _ => insert_to_db(
+                &mut conn,
                 self.name(),
                 start_version,
                 end_version,
-            )),
-            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+                all_current_stake_pool_voters,
+                all_proposal_votes,
+                all_delegator_activities,
+                all_delegator_balances,
+                all_delegator_pools,
+                all_delegator_pool_balances,
+                all_current_delegator_pool_balances,
+            ),
+        };
+        if let Err(err) = tx_result {
+            return Err(TransactionProcessingError::TransactionCommitError((
                 anyhow::Error::from(err),
                 start_version,
                 end_version,
                 self.name(),
-            ))),
+            )));
+        }
+        if let (Some(sink), Some(sink_records)) = (&self.sink, sink_records) {
+            sink.publish(sink_records, start_version, end_version, self.name())
+                .await?;
         }
+        Ok(ProcessingResult::new(
+            self.name(),
+            start_version,
+            end_version,
+        ))
     }
 
     fn connection_pool(&self) -> &PgDbPool {
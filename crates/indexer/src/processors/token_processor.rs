@@ -36,14 +36,16 @@ use crate::{
                 CurrentTokenOwnershipV2, CurrentTokenOwnershipV2PK, NFTOwnershipV2,
                 TokenOwnershipV2,
             },
+            v2_token_royalties::{CurrentTokenRoyaltyV2, CurrentTokenRoyaltyV2PK},
             v2_token_utils::{
-                AptosCollection, BurnEvent, FixedSupply, ObjectWithMetadata, PropertyMap, TokenV2,
-                TokenV2AggregatedData, TokenV2AggregatedDataMapping, TokenV2Burned, TransferEvent,
-                UnlimitedSupply,
+                AptosCollection, BurnEvent, FixedSupply, ObjectWithMetadata, PropertyMap, Royalty,
+                TokenV2, TokenV2AggregatedData, TokenV2AggregatedDataMapping, TokenV2Burned,
+                TransferEvent, UnlimitedSupply,
             },
         },
     },
     schema,
+    sinks::{ProcessorSink, SinkRecords},
     util::{parse_timestamp, standardize_address, truncate_str},
 };
 use aptos_api_types::{Transaction, TransactionPayload, WriteSetChange};
@@ -60,6 +62,7 @@ pub struct TokenTransactionProcessor {
     connection_pool: PgDbPool,
     ans_contract_address: Option<String>,
     nft_points_contract: Option<String>,
+    sink: Option<ProcessorSink>,
 }
 
 impl TokenTransactionProcessor {
@@ -76,8 +79,14 @@ impl TokenTransactionProcessor {
             connection_pool,
             ans_contract_address,
             nft_points_contract,
+            sink: None,
         }
     }
+
+    pub fn with_sink(mut self, sink: Option<ProcessorSink>) -> Self {
+        self.sink = sink;
+        self
+    }
 }
 
 impl Debug for TokenTransactionProcessor {
@@ -112,6 +121,7 @@ fn insert_to_db_impl(
         current_token_ownerships_v2,
         token_activities_v2,
         current_token_v2_metadata,
+        current_token_royalties_v2,
     ): (
         &[CollectionV2],
         &[TokenDataV2],
@@ -121,6 +131,7 @@ fn insert_to_db_impl(
         &[CurrentTokenOwnershipV2],
         &[TokenActivityV2],
         &[CurrentTokenV2Metadata],
+        &[CurrentTokenRoyaltyV2],
     ),
 ) -> Result<(), diesel::result::Error> {
     let (tokens, token_ownerships, token_datas, collection_datas) = basic_token_transaction_lists;
@@ -145,6 +156,7 @@ fn insert_to_db_impl(
     insert_current_token_ownerships_v2(conn, current_token_ownerships_v2)?;
     insert_token_activities_v2(conn, token_activities_v2)?;
     insert_current_token_v2_metadatas(conn, current_token_v2_metadata)?;
+    insert_current_token_royalties_v2(conn, current_token_royalties_v2)?;
     Ok(())
 }
 
@@ -177,6 +189,7 @@ fn insert_to_db(
         current_token_ownerships_v2,
         token_activities_v2,
         current_token_v2_metadata,
+        current_token_royalties_v2,
     ): (
         Vec<CollectionV2>,
         Vec<TokenDataV2>,
@@ -186,6 +199,7 @@ fn insert_to_db(
         Vec<CurrentTokenOwnershipV2>,
         Vec<TokenActivityV2>,
         Vec<CurrentTokenV2Metadata>,
+        Vec<CurrentTokenRoyaltyV2>,
     ),
 ) -> Result<(), diesel::result::Error> {
     aptos_logger::trace!(
@@ -222,6 +236,7 @@ fn insert_to_db(
                     &current_token_ownerships_v2,
                     &token_activities_v2,
                     &current_token_v2_metadata,
+                    &current_token_royalties_v2,
                 ),
             )
         }) {
@@ -250,6 +265,8 @@ fn insert_to_db(
                     clean_data_for_db(current_token_ownerships_v2, true);
                 let token_activities_v2 = clean_data_for_db(token_activities_v2, true);
                 let current_token_v2_metadata = clean_data_for_db(current_token_v2_metadata, true);
+                let current_token_royalties_v2 =
+                    clean_data_for_db(current_token_royalties_v2, true);
 
                 insert_to_db_impl(
                     pg_conn,
@@ -272,6 +289,7 @@ fn insert_to_db(
                         &current_token_ownerships_v2,
                         &token_activities_v2,
                         &current_token_v2_metadata,
+                        &current_token_royalties_v2,
                     ),
                 )
             }),
@@ -843,6 +861,35 @@ fn insert_current_token_v2_metadatas(
     Ok(())
 }
 
+fn insert_current_token_royalties_v2(
+    conn: &mut PgConnection,
+    items_to_insert: &[CurrentTokenRoyaltyV2],
+) -> Result<(), diesel::result::Error> {
+    use schema::current_token_royalties_v2::dsl::*;
+
+    let chunks = get_chunks(items_to_insert.len(), CurrentTokenRoyaltyV2::field_count());
+
+    for (start_ind, end_ind) in chunks {
+        execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::current_token_royalties_v2::table)
+                .values(&items_to_insert[start_ind..end_ind])
+                .on_conflict(object_address)
+                .do_update()
+                .set((
+                    payee_address.eq(excluded(payee_address)),
+                    royalty_points_numerator.eq(excluded(royalty_points_numerator)),
+                    royalty_points_denominator.eq(excluded(royalty_points_denominator)),
+                    last_transaction_version.eq(excluded(last_transaction_version)),
+                    last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+                    inserted_at.eq(excluded(inserted_at)),
+                )),
+            Some(" WHERE current_token_royalties_v2.last_transaction_version <= excluded.last_transaction_version "),
+        )?;
+    }
+    Ok(())
+}
+
 #[async_trait]
 impl TransactionProcessor for TokenTransactionProcessor {
     fn name(&self) -> &'static str {
@@ -979,57 +1026,91 @@ impl TransactionProcessor for TokenTransactionProcessor {
             token_datas_v2,
             token_ownerships_v2,
             current_collections_v2,
-            current_token_ownerships_v2,
             current_token_datas_v2,
+            current_token_ownerships_v2,
             token_activities_v2,
             current_token_v2_metadata,
+            current_token_royalties_v2,
         ) = parse_v2_token(&transactions, &table_handle_to_owner, &mut conn);
 
-        let tx_result = insert_to_db(
-            &mut conn,
-            self.name(),
-            start_version,
-            end_version,
-            (
-                all_tokens,
-                all_token_ownerships,
-                all_token_datas,
-                all_collection_datas,
-            ),
-            (
-                all_current_token_ownerships,
-                all_current_token_datas,
-                all_current_collection_datas,
-            ),
-            all_token_activities,
-            all_current_token_claims,
-            all_current_ans_lookups,
-            all_nft_points,
-            // Token V2 stuff which will token v1 tables above
-            (
-                collections_v2,
-                token_datas_v2,
-                token_ownerships_v2,
-                current_collections_v2,
-                current_token_ownerships_v2,
-                current_token_datas_v2,
-                token_activities_v2,
-                current_token_v2_metadata,
-            ),
-        );
-        match tx_result {
-            Ok(_) => Ok(ProcessingResult::new(
+        let sink_records = self.sink.as_ref().map(|_| {
+            let mut records = SinkRecords::default();
+            records.add("tokens", &all_tokens);
+            records.add("token_ownerships", &all_token_ownerships);
+            records.add("token_datas", &all_token_datas);
+            records.add("collection_datas", &all_collection_datas);
+            records.add("current_token_ownerships", &all_current_token_ownerships);
+            records.add("current_token_datas", &all_current_token_datas);
+            records.add("current_collection_datas", &all_current_collection_datas);
+            records.add("token_activities", &all_token_activities);
+            records.add("current_token_pending_claims", &all_current_token_claims);
+            records.add("current_ans_lookup", &all_current_ans_lookups);
+            records.add("nft_points", &all_nft_points);
+            records.add("collections_v2", &collections_v2);
+            records.add("token_datas_v2", &token_datas_v2);
+            records.add("token_ownerships_v2", &token_ownerships_v2);
+            records.add("current_collections_v2", &current_collections_v2);
+            records.add("current_token_datas_v2", &current_token_datas_v2);
+            records.add("current_token_ownerships_v2", &current_token_ownerships_v2);
+            records.add("token_activities_v2", &token_activities_v2);
+            records.add("current_token_v2_metadata", &current_token_v2_metadata);
+            records.add("current_token_royalties_v2", &current_token_royalties_v2);
+            records
+        });
+
+        let tx_result = match &self.sink {
+            Some(sink) if sink.skip_postgres_writes() => Ok(()),
+            _ => insert_to_db(
+                &mut conn,
                 self.name(),
                 start_version,
                 end_version,
-            )),
-            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+                (
+                    all_tokens,
+                    all_token_ownerships,
+                    all_token_datas,
+                    all_collection_datas,
+                ),
+                (
+                    all_current_token_ownerships,
+                    all_current_token_datas,
+                    all_current_collection_datas,
+                ),
+                all_token_activities,
+                all_current_token_claims,
+                all_current_ans_lookups,
+                all_nft_points,
+                // Token V2 stuff which will token v1 tables above
+                (
+                    collections_v2,
+                    token_datas_v2,
+                    token_ownerships_v2,
+                    current_collections_v2,
+                    current_token_datas_v2,
+                    current_token_ownerships_v2,
+                    token_activities_v2,
+                    current_token_v2_metadata,
+                    current_token_royalties_v2,
+                ),
+            ),
+        };
+        if let Err(err) = tx_result {
+            return Err(TransactionProcessingError::TransactionCommitError((
                 anyhow::Error::from(err),
                 start_version,
                 end_version,
                 self.name(),
-            ))),
+            )));
+        }
+        if let (Some(sink), Some(sink_records)) = (&self.sink, sink_records) {
+            sink.publish(sink_records, start_version, end_version, self.name())
+                .await?;
         }
+        Ok(ProcessingResult::new(
+            self.name(),
+            start_version,
+            end_version,
+        ))
     }
 
     fn connection_pool(&self) -> &PgDbPool {
@@ -1050,6 +1131,7 @@ fn parse_v2_token(
     Vec<CurrentTokenOwnershipV2>,
     Vec<TokenActivityV2>,
     Vec<CurrentTokenV2Metadata>,
+    Vec<CurrentTokenRoyaltyV2>,
 ) {
     // Token V2 and V1 combined
     let mut collections_v2 = vec![];
@@ -1073,6 +1155,9 @@ fn parse_v2_token(
     // Basically token properties
     let mut current_token_v2_metadata: HashMap<CurrentTokenV2MetadataPK, CurrentTokenV2Metadata> =
         HashMap::new();
+    // Royalties of collections and tokens
+    let mut current_token_royalties_v2: HashMap<CurrentTokenRoyaltyV2PK, CurrentTokenRoyaltyV2> =
+        HashMap::new();
 
     // Code above is inefficient (multiple passthroughs) so I'm approaching TokenV2 with a cleaner code structure
     for txn in transactions {
@@ -1103,6 +1188,7 @@ fn parse_v2_token(
                                 object,
                                 unlimited_supply: None,
                                 property_map: None,
+                                royalty: None,
                                 transfer_event: None,
                                 token: None,
                                 fungible_asset_metadata: None,
@@ -1139,6 +1225,11 @@ fn parse_v2_token(
                         {
                             aggregated_data.property_map = Some(property_map);
                         }
+                        if let Some(royalty) =
+                            Royalty::from_write_resource(wr, txn_version).unwrap()
+                        {
+                            aggregated_data.royalty = Some(royalty);
+                        }
                         if let Some(token) = TokenV2::from_write_resource(wr, txn_version).unwrap()
                         {
                             aggregated_data.token = Some(token);
@@ -1477,6 +1568,19 @@ fn parse_v2_token(
                                 token_metadata,
                             );
                         }
+
+                        // Track royalties
+                        if let Some(royalty) = CurrentTokenRoyaltyV2::from_write_resource(
+                            resource,
+                            txn_version,
+                            txn_timestamp,
+                            &token_v2_metadata_helper,
+                        )
+                        .unwrap()
+                        {
+                            current_token_royalties_v2
+                                .insert(royalty.object_address.clone(), royalty);
+                        }
                     },
                     WriteSetChange::DeleteResource(resource) => {
                         // Add burned NFT handling
@@ -1531,6 +1635,9 @@ fn parse_v2_token(
     let mut current_token_v2_metadata = current_token_v2_metadata
         .into_values()
         .collect::<Vec<CurrentTokenV2Metadata>>();
+    let mut current_token_royalties_v2 = current_token_royalties_v2
+        .into_values()
+        .collect::<Vec<CurrentTokenRoyaltyV2>>();
 
     // Sort by PK
     current_collections_v2.sort_by(|a, b| a.collection_id.cmp(&b.collection_id));
@@ -1552,6 +1659,7 @@ fn parse_v2_token(
     current_token_v2_metadata.sort_by(|a, b| {
         (&a.object_address, &a.resource_type).cmp(&(&b.object_address, &b.resource_type))
     });
+    current_token_royalties_v2.sort_by(|a, b| a.object_address.cmp(&b.object_address));
 
     (
         collections_v2,
@@ -1562,5 +1670,6 @@ fn parse_v2_token(
         current_token_ownerships_v2,
         token_activities_v2,
         current_token_v2_metadata,
+        current_token_royalties_v2,
     )
 }
@@ -2,25 +2,55 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    counters::ROWS_WRITTEN,
     database::new_db_pool,
+    health::HealthReporter,
     indexer::{
-        fetcher::TransactionFetcherOptions, processing_result::ProcessingResult, tailer::Tailer,
+        errors::TransactionProcessingError,
+        fetcher::TransactionFetcherOptions,
+        module_filter::{ModuleFilter, ModuleFilteredProcessor},
+        partitioning::{run_partition_maintenance, TablePartitioner},
+        processing_result::ProcessingResult,
+        tailer::Tailer,
         transaction_processor::TransactionProcessor,
     },
     processors::{
+        balance_history_processor::BalanceHistoryTransactionProcessor,
         coin_processor::CoinTransactionProcessor, default_processor::DefaultTransactionProcessor,
+        fungible_asset_processor::FungibleAssetTransactionProcessor,
         stake_processor::StakeTransactionProcessor, token_processor::TokenTransactionProcessor,
         Processor,
     },
+    sinks::ProcessorSink,
 };
 use aptos_api::context::Context;
 use aptos_config::config::{IndexerConfig, NodeConfig};
 use aptos_logger::{error, info};
 use aptos_mempool::MempoolClientSender;
 use aptos_storage_interface::DbReader;
-use aptos_types::chain_id::ChainId;
-use std::{collections::VecDeque, sync::Arc};
-use tokio::runtime::Runtime;
+use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
+use futures::stream::{FuturesOrdered, StreamExt};
+use std::{
+    collections::VecDeque,
+    net::{Ipv4Addr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{runtime::Runtime, task::JoinHandle};
+
+/// How often the health of the processor is refreshed
+const HEALTH_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+/// How long to wait for new transactions when caught up with the node
+const CAUGHT_UP_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Number of transactions in a batch and the result of processing it, if it's not empty
+type BatchResult = (
+    u64,
+    Option<Result<ProcessingResult, TransactionProcessingError>>,
+);
+/// The batches being processed concurrently, in version order
+type BatchesInFlight = FuturesOrdered<JoinHandle<BatchResult>>;
 
 pub struct MovingAverage {
     window_millis: u64,
@@ -97,20 +127,130 @@ pub fn bootstrap(
     Some(Ok(runtime))
 }
 
+/// Fetches the next batches and spawns their processing, until `parallelism` batches are in
+/// flight or the fetcher is caught up. The batches are fetched in version order, so they stay in
+/// that order in `in_flight` whichever finishes processing first.
+async fn fill_batches_in_flight(
+    tailer: &Tailer,
+    in_flight: &mut BatchesInFlight,
+    parallelism: usize,
+) {
+    while in_flight.len() < parallelism {
+        let transactions = tailer.fetch_next_batch().await;
+        if transactions.is_empty() {
+            break;
+        }
+        let other_tailer = tailer.clone();
+        in_flight.push_back(tokio::spawn(async move {
+            other_tailer.process_batch(transactions).await
+        }));
+    }
+}
+
+/// Waits for the oldest batch in flight, and returns its range of versions and how many there
+/// are, or None if there is no batch in flight. Panics if the batch failed.
+async fn next_processed_batch(
+    processor_name: &str,
+    in_flight: &mut BatchesInFlight,
+) -> Option<(u64, u64, u64)> {
+    let (num_txns, res) = match in_flight.next().await? {
+        Ok(batch) => batch,
+        Err(err) => panic!("Error processing transaction batch: {:?}", err),
+    };
+    match res {
+        Some(Ok(processed_result)) => Some((
+            processed_result.start_version,
+            processed_result.end_version,
+            num_txns,
+        )),
+        Some(Err(tpe)) => {
+            let (err, start_version, end_version, _) = tpe.inner();
+            error!(
+                processor_name = processor_name,
+                start_version = start_version,
+                end_version = end_version,
+                error =? err,
+                "Error processing batch!"
+            );
+            panic!(
+                "Error in '{}' while processing batch: {:?}",
+                processor_name, err
+            );
+        },
+        None => unreachable!("Empty batches are never processed"),
+    }
+}
+
+/// Processes the versions from `start_version` to `end_version` (inclusive) with a tailer whose
+/// fetcher stops at `end_version`. Unlike the head-following loop, this doesn't update the last
+/// success version of the processor.
+async fn run_backfill(
+    tailer: Tailer,
+    processor_name: String,
+    processor_tasks: u8,
+    start_version: u64,
+    end_version: u64,
+) {
+    info!(
+        processor_name = processor_name,
+        start_version = start_version,
+        end_version = end_version,
+        "Starting backfill..."
+    );
+    tailer.set_fetcher_version(start_version).await;
+    tailer.transaction_fetcher.lock().await.start().await;
+
+    let mut versions_processed: u64 = 0;
+    let mut in_flight = BatchesInFlight::new();
+    loop {
+        fill_batches_in_flight(&tailer, &mut in_flight, processor_tasks as usize).await;
+        match next_processed_batch(&processor_name, &mut in_flight).await {
+            Some((_, batch_end_version, num_res)) => {
+                versions_processed += num_res;
+                // Batches complete in order, so all the versions before are processed as well
+                if batch_end_version >= end_version {
+                    break;
+                }
+            },
+            None => tokio::time::sleep(CAUGHT_UP_BACKOFF).await,
+        }
+    }
+    info!(
+        processor_name = processor_name,
+        start_version = start_version,
+        end_version = end_version,
+        versions_processed = versions_processed,
+        "Backfill finished!"
+    );
+}
+
 pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
     // All of these options should be filled already with defaults
     let processor_name = config.processor.clone().unwrap();
     let check_chain_id = config.check_chain_id.unwrap();
+    let reset_on_chain_change = config.reset_on_chain_change.unwrap();
     let skip_migrations = config.skip_migrations.unwrap();
     let fetch_tasks = config.fetch_tasks.unwrap();
     let processor_tasks = config.processor_tasks.unwrap();
     let emit_every = config.emit_every.unwrap();
     let batch_size = config.batch_size.unwrap();
     let lookback_versions = config.gap_lookback_versions.unwrap() as i64;
+    let dead_letter_failed_transactions = config.dead_letter_failed_transactions.unwrap();
+    let reprocess_dead_letters = config.reprocess_dead_letters.unwrap();
 
     info!(processor_name = processor_name, "Starting indexer...");
 
-    let db_uri = &config.postgres_uri.unwrap();
+    let health_reporter = HealthReporter::default();
+    if let Some(health_port) = config.health_port {
+        tokio::spawn(
+            health_reporter
+                .clone()
+                .serve(SocketAddr::from((Ipv4Addr::UNSPECIFIED, health_port))),
+        );
+    }
+    let health_context = context.clone();
+
+    let db_uri = config.postgres_uri.as_ref().unwrap();
     info!(
         processor_name = processor_name,
         "Creating connection pool..."
@@ -123,31 +263,117 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
 
     info!(processor_name = processor_name, "Instantiating tailer... ");
 
+    let sink = match &config.sink {
+        Some(sink_config) => {
+            info!(
+                processor_name = processor_name,
+                sink = ?sink_config,
+                "Creating sink..."
+            );
+            Some(
+                ProcessorSink::new(sink_config)
+                    .await
+                    .expect("Failed to create sink"),
+            )
+        },
+        None => None,
+    };
+
     let processor_enum = Processor::from_string(&processor_name);
     let processor: Arc<dyn TransactionProcessor> = match processor_enum {
+        Processor::CustomProcessor(builder) => builder(&config, conn_pool.clone()),
         Processor::DefaultProcessor => {
-            Arc::new(DefaultTransactionProcessor::new(conn_pool.clone()))
+            Arc::new(DefaultTransactionProcessor::new(conn_pool.clone()).with_sink(sink))
         },
-        Processor::TokenProcessor => Arc::new(TokenTransactionProcessor::new(
-            conn_pool.clone(),
-            config.ans_contract_address,
-            config.nft_points_contract,
-        )),
-        Processor::CoinProcessor => Arc::new(CoinTransactionProcessor::new(conn_pool.clone())),
-        Processor::StakeProcessor => Arc::new(StakeTransactionProcessor::new(conn_pool.clone())),
+        Processor::TokenProcessor => Arc::new(
+            TokenTransactionProcessor::new(
+                conn_pool.clone(),
+                config.ans_contract_address,
+                config.nft_points_contract,
+            )
+            .with_sink(sink),
+        ),
+        Processor::CoinProcessor => {
+            Arc::new(CoinTransactionProcessor::new(conn_pool.clone()).with_sink(sink))
+        },
+        Processor::StakeProcessor => {
+            Arc::new(StakeTransactionProcessor::new(conn_pool.clone()).with_sink(sink))
+        },
+        Processor::FungibleAssetProcessor => {
+            Arc::new(FungibleAssetTransactionProcessor::new(conn_pool.clone()).with_sink(sink))
+        },
+        Processor::BalanceHistoryProcessor => Arc::new(
+            BalanceHistoryTransactionProcessor::new(
+                conn_pool.clone(),
+                config.balance_snapshot_interval_secs.unwrap(),
+            )
+            .with_sink(sink),
+        ),
+    };
+
+    let processor: Arc<dyn TransactionProcessor> = match &config.allowed_module_addresses {
+        Some(addresses) => {
+            info!(
+                processor_name = processor_name,
+                allowed_module_addresses = ?addresses,
+                "Only indexing the transactions touching the allowed module addresses"
+            );
+            let filter = ModuleFilter::new(
+                addresses
+                    .iter()
+                    .map(|address| {
+                        AccountAddress::from_str(address).expect("Invalid allowed module address")
+                    })
+                    .collect(),
+            );
+            Arc::new(ModuleFilteredProcessor::new(processor, filter))
+        },
+        None => processor,
     };
 
     let options =
         TransactionFetcherOptions::new(None, None, Some(batch_size), None, fetch_tasks as usize);
 
-    let tailer = Tailer::new(context, conn_pool.clone(), processor, options)
-        .expect("Failed to instantiate tailer");
+    let tailer = Tailer::new(
+        context.clone(),
+        conn_pool.clone(),
+        processor.clone(),
+        options.clone(),
+    )
+    .expect("Failed to instantiate tailer")
+    .with_dead_letters(dead_letter_failed_transactions);
 
     if !skip_migrations {
         info!(processor_name = processor_name, "Running migrations...");
         tailer.run_migrations();
     }
 
+    // This needs to happen before getting the starting version, since the existing data is wiped
+    // if the chain changed and `reset_on_chain_change` is set
+    if check_chain_id {
+        tailer
+            .check_or_update_chain_id(reset_on_chain_change)
+            .await
+            .expect("Failed to get chain ID");
+    }
+
+    if reprocess_dead_letters {
+        info!(
+            processor_name = processor_name,
+            "Reprocessing dead letters..."
+        );
+        let (num_succeeded, num_failed) = tailer
+            .reprocess_dead_letters()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to reprocess dead letters: {:?}", e));
+        info!(
+            processor_name = processor_name,
+            num_succeeded = num_succeeded,
+            num_failed = num_failed,
+            "Reprocessed dead letters"
+        );
+    }
+
     info!(
         processor_name = processor_name,
         lookback_versions = lookback_versions,
@@ -178,6 +404,65 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
     );
     tailer.set_fetcher_version(start_version).await;
 
+    let partitioners = config
+        .partitioned_tables
+        .iter()
+        .flatten()
+        .map(TablePartitioner::new)
+        .collect::<Vec<_>>();
+    if !partitioners.is_empty() {
+        info!(
+            processor_name = processor_name,
+            partitioned_tables = ?config.partitioned_tables,
+            "Partitioning tables..."
+        );
+        let mut conn = conn_pool
+            .get()
+            .expect("DB connection should be available to partition the tables");
+        for partitioner in &partitioners {
+            partitioner
+                .partition_table(&mut conn)
+                .and_then(|_| partitioner.maintain_partitions(&mut conn, start_version))
+                .unwrap_or_else(|e| panic!("Failed to partition {:?}: {:?}", partitioner, e));
+        }
+        tokio::spawn(run_partition_maintenance(
+            conn_pool.clone(),
+            processor_name.clone(),
+            partitioners,
+        ));
+    }
+
+    if let Some(backfill_start_version) = config.backfill_start_version {
+        match config
+            .backfill_end_version
+            .or_else(|| start_version.checked_sub(1))
+        {
+            Some(backfill_end_version) if backfill_start_version <= backfill_end_version => {
+                let backfill_tailer = Tailer::new(
+                    context,
+                    conn_pool.clone(),
+                    processor,
+                    options.with_ending_version(backfill_end_version),
+                )
+                .expect("Failed to instantiate backfill tailer")
+                .with_dead_letters(dead_letter_failed_transactions);
+                tokio::spawn(run_backfill(
+                    backfill_tailer,
+                    processor_name.clone(),
+                    processor_tasks,
+                    backfill_start_version,
+                    backfill_end_version,
+                ));
+            },
+            backfill_end_version => info!(
+                processor_name = processor_name,
+                backfill_start_version = backfill_start_version,
+                backfill_end_version = backfill_end_version,
+                "Nothing to backfill"
+            ),
+        }
+    }
+
     info!(processor_name = processor_name, "Starting fetcher...");
     tailer.transaction_fetcher.lock().await.start().await;
 
@@ -190,86 +475,86 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
     let mut versions_processed: u64 = 0;
     let mut base: u64 = 0;
 
-    // Check once here to avoid a boolean check every iteration
-    if check_chain_id {
-        tailer
-            .check_or_update_chain_id()
-            .await
-            .expect("Failed to get chain ID");
-    }
-
     let mut ma = MovingAverage::new(10_000);
+    let mut rows_ma = MovingAverage::new(10_000);
+    let mut rows_written = ROWS_WRITTEN.get();
+    let mut last_processed_version = start_version.checked_sub(1);
+    let mut last_health_update: Option<Instant> = None;
+
+    let mut in_flight = BatchesInFlight::new();
 
     loop {
-        let mut tasks = vec![];
-        for _ in 0..processor_tasks {
-            let other_tailer = tailer.clone();
-            let task = tokio::spawn(async move { other_tailer.process_next_batch().await });
-            tasks.push(task);
-        }
-        let batches = match futures::future::try_join_all(tasks).await {
-            Ok(res) => res,
-            Err(err) => panic!("Error processing transaction batches: {:?}", err),
-        };
-
-        let mut batch_start_version = u64::MAX;
-        let mut batch_end_version = 0;
-        let mut num_res = 0;
-
-        for (num_txn, res) in batches {
-            let processed_result: ProcessingResult = match res {
-                // When the batch is empty b/c we're caught up, continue to next batch
-                None => continue,
-                Some(Ok(res)) => res,
-                Some(Err(tpe)) => {
-                    let (err, start_version, end_version, _) = tpe.inner();
+        fill_batches_in_flight(&tailer, &mut in_flight, processor_tasks as usize).await;
+        let processed_batch = next_processed_batch(&processor_name, &mut in_flight).await;
+
+        let num_res = processed_batch.map_or(0, |(_, _, num_res)| num_res);
+        ma.tick_now(num_res);
+        let new_rows_written = ROWS_WRITTEN.get();
+        rows_ma.tick_now(new_rows_written - rows_written);
+        rows_written = new_rows_written;
+
+        match processed_batch {
+            Some((batch_start_version, batch_end_version, num_res)) => {
+                // The batches are committed in order, so all the versions up to this one are
+                // processed even if later batches already finished
+                tailer
+                    .update_last_processed_version(&processor_name, batch_end_version)
+                    .unwrap_or_else(|e| {
+                        error!(
+                            processor_name = processor_name,
+                            end_version = batch_end_version,
+                            error = format!("{:?}", e),
+                            "Failed to update last processed version!"
+                        );
+                        panic!("Failed to update last processed version: {:?}", e);
+                    });
+                tailer.on_checkpoint(batch_end_version).unwrap_or_else(|e| {
                     error!(
                         processor_name = processor_name,
-                        start_version = start_version,
-                        end_version = end_version,
-                        error =? err,
-                        "Error processing batch!"
+                        end_version = batch_end_version,
+                        error = format!("{:?}", e),
+                        "Processor checkpoint hook failed!"
                     );
-                    panic!(
-                        "Error in '{}' while processing batch: {:?}",
-                        processor_name, err
-                    );
-                },
-            };
-            batch_start_version =
-                std::cmp::min(batch_start_version, processed_result.start_version);
-            batch_end_version = std::cmp::max(batch_end_version, processed_result.end_version);
-            num_res += num_txn;
+                    panic!("Processor checkpoint hook failed: {:?}", e);
+                });
+                last_processed_version = Some(batch_end_version);
+
+                versions_processed += num_res;
+                if emit_every != 0 {
+                    let new_base: u64 = versions_processed / emit_every;
+                    if base != new_base {
+                        base = new_base;
+                        info!(
+                            processor_name = processor_name,
+                            batch_start_version = batch_start_version,
+                            batch_end_version = batch_end_version,
+                            versions_processed = versions_processed,
+                            tps = (ma.avg() * 1000.0) as u64,
+                            "Processed batch version"
+                        );
+                    }
+                }
+            },
+            // Caught up with the node
+            None => tokio::time::sleep(CAUGHT_UP_BACKOFF).await,
         }
 
-        tailer
-            .update_last_processed_version(&processor_name, batch_end_version)
-            .unwrap_or_else(|e| {
-                error!(
+        if last_health_update.map_or(true, |updated| updated.elapsed() >= HEALTH_UPDATE_INTERVAL) {
+            match health_context.get_latest_ledger_info_wrapped() {
+                Ok(ledger_info) => health_reporter.update(
+                    &processor_name,
+                    last_processed_version,
+                    ledger_info.ledger_version.0,
+                    ma.avg() * 1000.0,
+                    rows_ma.avg() * 1000.0,
+                ),
+                Err(e) => error!(
                     processor_name = processor_name,
-                    end_version = batch_end_version,
                     error = format!("{:?}", e),
-                    "Failed to update last processed version!"
-                );
-                panic!("Failed to update last processed version: {:?}", e);
-            });
-
-        ma.tick_now(num_res);
-
-        versions_processed += num_res;
-        if emit_every != 0 {
-            let new_base: u64 = versions_processed / emit_every;
-            if base != new_base {
-                base = new_base;
-                info!(
-                    processor_name = processor_name,
-                    batch_start_version = batch_start_version,
-                    batch_end_version = batch_end_version,
-                    versions_processed = versions_processed,
-                    tps = (ma.avg() * 1000.0) as u64,
-                    "Processed batch version"
-                );
+                    "Failed to get the ledger info for the health report"
+                ),
             }
+            last_health_update = Some(Instant::now());
         }
     }
 }
@@ -11,6 +11,24 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    balance_snapshots (storage_id, asset_type_hash, snapshot_at) {
+        #[max_length = 66]
+        storage_id -> Varchar,
+        #[max_length = 64]
+        asset_type_hash -> Varchar,
+        snapshot_at -> Timestamp,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        #[max_length = 5000]
+        asset_type -> Varchar,
+        amount -> Numeric,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     block_metadata_transactions (version) {
         version -> Int8,
@@ -270,6 +288,22 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    current_fungible_asset_balances (storage_id) {
+        #[max_length = 66]
+        storage_id -> Varchar,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        #[max_length = 66]
+        asset_type -> Varchar,
+        is_frozen -> Bool,
+        amount -> Numeric,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     current_objects (object_address) {
         #[max_length = 66]
@@ -451,6 +485,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    current_token_royalties_v2 (object_address) {
+        #[max_length = 66]
+        object_address -> Varchar,
+        #[max_length = 66]
+        payee_address -> Varchar,
+        royalty_points_numerator -> Numeric,
+        royalty_points_denominator -> Numeric,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     current_token_v2_metadata (object_address, resource_type) {
         #[max_length = 66]
@@ -465,6 +513,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    dead_letters (processor, transaction_version) {
+        #[max_length = 50]
+        processor -> Varchar,
+        transaction_version -> Int8,
+        error -> Text,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     delegated_staking_activities (transaction_version, event_index) {
         transaction_version -> Int8,
@@ -517,6 +575,79 @@ diesel::table! {
         data -> Jsonb,
         inserted_at -> Timestamp,
         event_index -> Nullable<Int8>,
+        #[max_length = 66]
+        object_owner_address -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    fungible_asset_activities (transaction_version, event_index) {
+        transaction_version -> Int8,
+        event_index -> Int8,
+        #[max_length = 66]
+        storage_id -> Varchar,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        #[max_length = 66]
+        asset_type -> Varchar,
+        amount -> Numeric,
+        #[sql_name = "type"]
+        type_ -> Varchar,
+        #[max_length = 100]
+        entry_function_id_str -> Nullable<Varchar>,
+        block_height -> Int8,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    fungible_asset_balances (transaction_version, write_set_change_index) {
+        transaction_version -> Int8,
+        write_set_change_index -> Int8,
+        #[max_length = 66]
+        storage_id -> Varchar,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        #[max_length = 66]
+        asset_type -> Varchar,
+        is_frozen -> Bool,
+        amount -> Numeric,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    fungible_asset_metadata (asset_type) {
+        #[max_length = 66]
+        asset_type -> Varchar,
+        #[max_length = 66]
+        creator_address -> Varchar,
+        #[max_length = 32]
+        name -> Varchar,
+        #[max_length = 10]
+        symbol -> Varchar,
+        decimals -> Int4,
+        #[max_length = 512]
+        icon_uri -> Varchar,
+        #[max_length = 512]
+        project_uri -> Varchar,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    fungible_asset_supply (transaction_version, asset_type) {
+        transaction_version -> Int8,
+        #[max_length = 66]
+        asset_type -> Varchar,
+        current_supply -> Numeric,
+        maximum_supply -> Nullable<Numeric>,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
     }
 }
 
@@ -532,6 +663,8 @@ diesel::table! {
 diesel::table! {
     ledger_infos (chain_id) {
         chain_id -> Int8,
+        #[max_length = 66]
+        genesis_hash -> Nullable<Varchar>,
     }
 }
 
@@ -585,6 +718,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    object_metadata (object_address) {
+        #[max_length = 66]
+        object_address -> Varchar,
+        #[max_length = 66]
+        creator_address -> Varchar,
+        first_transaction_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     objects (transaction_version, write_set_change_index) {
         transaction_version -> Int8,
@@ -937,6 +1081,7 @@ diesel::table! {
 
 diesel::allow_tables_to_appear_in_same_query!(
     account_transactions,
+    balance_snapshots,
     block_metadata_transactions,
     coin_activities,
     coin_balances,
@@ -950,6 +1095,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     current_collections_v2,
     current_delegated_staking_pool_balances,
     current_delegator_balances,
+    current_fungible_asset_balances,
     current_objects,
     current_staking_pool_voter,
     current_table_items,
@@ -958,16 +1104,23 @@ diesel::allow_tables_to_appear_in_same_query!(
     current_token_ownerships,
     current_token_ownerships_v2,
     current_token_pending_claims,
+    current_token_royalties_v2,
     current_token_v2_metadata,
+    dead_letters,
     delegated_staking_activities,
     delegated_staking_pool_balances,
     delegated_staking_pools,
     events,
+    fungible_asset_activities,
+    fungible_asset_balances,
+    fungible_asset_metadata,
+    fungible_asset_supply,
     indexer_status,
     ledger_infos,
     move_modules,
     move_resources,
     nft_points,
+    object_metadata,
     objects,
     processor_status,
     processor_statuses,
@@ -0,0 +1,132 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::RecordSink;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+
+/// The high volume tables stored in ClickHouse, with their schema. `{database}` is replaced with
+/// the database of the sink. The tables dedupe the rows with the same ordering key, i.e. the
+/// Postgres primary key, so that a batch can be inserted again after a failure.
+const TABLE_SCHEMAS: [(&str, &str); 2] = [
+    (
+        "events",
+        "CREATE TABLE IF NOT EXISTS {database}.events (
+            sequence_number Int64,
+            creation_number Int64,
+            account_address String,
+            transaction_version Int64,
+            transaction_block_height Int64,
+            type_ String,
+            data String,
+            event_index Nullable(Int64),
+            object_owner_address Nullable(String),
+            inserted_at DateTime DEFAULT now()
+        )
+        ENGINE = ReplacingMergeTree
+        PARTITION BY intDiv(transaction_version, 100000000)
+        ORDER BY (account_address, creation_number, sequence_number)",
+    ),
+    (
+        "coin_activities",
+        "CREATE TABLE IF NOT EXISTS {database}.coin_activities (
+            transaction_version Int64,
+            event_account_address String,
+            event_creation_number Int64,
+            event_sequence_number Int64,
+            owner_address String,
+            coin_type String,
+            amount Decimal(38, 0),
+            activity_type String,
+            is_gas_fee Bool,
+            is_transaction_success Bool,
+            entry_function_id_str Nullable(String),
+            block_height Int64,
+            transaction_timestamp DateTime64(6),
+            event_index Nullable(Int64),
+            inserted_at DateTime DEFAULT now()
+        )
+        ENGINE = ReplacingMergeTree
+        PARTITION BY intDiv(transaction_version, 100000000)
+        ORDER BY (transaction_version, event_account_address, event_creation_number, event_sequence_number)",
+    ),
+];
+
+const TABLES: [&str; 2] = [TABLE_SCHEMAS[0].0, TABLE_SCHEMAS[1].0];
+
+/// Lets ClickHouse parse the JSON of the records: the timestamps aren't in its default format,
+/// the event data is stored as a string, and the records can have more fields than the tables
+const SETTINGS: [(&str, &str); 3] = [
+    ("date_time_input_format", "best_effort"),
+    ("input_format_json_read_objects_as_strings", "1"),
+    ("input_format_skip_unknown_fields", "1"),
+];
+
+/// Inserts the records of the high volume tables into ClickHouse, through its HTTP interface.
+/// The records of the other tables aren't published.
+pub struct ClickHouseSink {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl ClickHouseSink {
+    /// Creates the database and the tables if they don't exist yet
+    pub async fn new(url: &str, database: &str) -> Result<Self> {
+        let sink = Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+        };
+        sink.execute(
+            &format!("CREATE DATABASE IF NOT EXISTS {}", database),
+            vec![],
+        )
+        .await?;
+        for (_, schema) in TABLE_SCHEMAS {
+            sink.execute(&schema.replace("{database}", database), vec![])
+                .await?;
+        }
+        Ok(sink)
+    }
+
+    async fn execute(&self, query: &str, body: Vec<u8>) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.url)
+            .query(&[("query", query)])
+            .query(&SETTINGS)
+            .body(body)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!(
+                "ClickHouse query failed with {}: {}",
+                status,
+                response.text().await?
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecordSink for ClickHouseSink {
+    fn tables(&self) -> Option<&[&'static str]> {
+        Some(&TABLES)
+    }
+
+    /// The topic is `<database>.<table>`, so all the records of the batch are inserted at once
+    async fn publish(
+        &self,
+        topic: &str,
+        records: Vec<Vec<u8>>,
+        _start_version: u64,
+        _end_version: u64,
+    ) -> Result<()> {
+        self.execute(
+            &format!("INSERT INTO {} FORMAT JSONEachRow", topic),
+            records.join(&b'\n'),
+        )
+        .await
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::RecordSink;
+use anyhow::Result;
+use async_trait::async_trait;
+use rdkafka::{
+    producer::{FutureProducer, FutureRecord},
+    ClientConfig,
+};
+use std::time::Duration;
+
+const MESSAGE_TIMEOUT_MS: &str = "30000";
+
+/// Publishes the records to Kafka topics
+pub struct KafkaSink {
+    producer: FutureProducer,
+}
+
+impl KafkaSink {
+    pub fn new(brokers: &str) -> Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", MESSAGE_TIMEOUT_MS)
+            // Keep the records of a topic in order when retrying
+            .set("enable.idempotence", "true")
+            .create()?;
+        Ok(Self { producer })
+    }
+}
+
+#[async_trait]
+impl RecordSink for KafkaSink {
+    async fn publish(
+        &self,
+        topic: &str,
+        records: Vec<Vec<u8>>,
+        _start_version: u64,
+        _end_version: u64,
+    ) -> Result<()> {
+        let deliveries = records.iter().map(|record| {
+            self.producer.send(
+                FutureRecord::<(), _>::to(topic).payload(record),
+                Duration::from_secs(0),
+            )
+        });
+        for delivery in futures::future::join_all(deliveries).await {
+            delivery.map_err(|(err, _message)| err)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sinks publish the records of the processors to a streaming system, to Parquet files or to
+//! ClickHouse, in addition to or instead of writing them to Postgres.
+
+pub mod clickhouse;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+#[cfg(feature = "nats")]
+pub mod nats;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+
+use crate::indexer::errors::TransactionProcessingError;
+use anyhow::Result;
+use aptos_config::config::{IndexerSinkConfig, IndexerSinkType};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// A system the records can be published to
+#[async_trait]
+pub trait RecordSink: Send + Sync {
+    /// The tables whose records are published, or None for all of them
+    fn tables(&self) -> Option<&[&'static str]> {
+        None
+    }
+
+    /// Publishes the records produced from the versions `start_version` to `end_version`, in
+    /// order, to the topic. Returns once they are all acknowledged.
+    async fn publish(
+        &self,
+        topic: &str,
+        records: Vec<Vec<u8>>,
+        start_version: u64,
+        end_version: u64,
+    ) -> Result<()>;
+
+    /// Called once the records of all the topics are published for the versions `start_version`
+    /// to `end_version`
+    async fn finish_batch(&self, _start_version: u64, _end_version: u64) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The records produced by a processor for a batch of transactions, serialized to JSON and
+/// grouped by table
+#[derive(Default)]
+pub struct SinkRecords {
+    tables: Vec<(&'static str, Vec<Vec<u8>>)>,
+}
+
+impl SinkRecords {
+    pub fn add<T: Serialize>(&mut self, table: &'static str, records: &[T]) {
+        if records.is_empty() {
+            return;
+        }
+        let records = records
+            .iter()
+            .map(|record| serde_json::to_vec(record).expect("Records must serialize to JSON"))
+            .collect();
+        self.tables.push((table, records));
+    }
+}
+
+/// The sink used by a processor, with the config of how the processor uses it
+#[derive(Clone)]
+pub struct ProcessorSink {
+    sink: Arc<dyn RecordSink>,
+    topic_prefix: String,
+    skip_postgres_writes: bool,
+}
+
+impl ProcessorSink {
+    pub async fn new(config: &IndexerSinkConfig) -> Result<Self> {
+        let sink: Arc<dyn RecordSink> = match &config.sink_type {
+            #[cfg(feature = "kafka")]
+            IndexerSinkType::Kafka { brokers } => Arc::new(kafka::KafkaSink::new(brokers)?),
+            #[cfg(feature = "nats")]
+            IndexerSinkType::Nats { url } => Arc::new(nats::NatsSink::new(url).await?),
+            #[cfg(feature = "parquet")]
+            IndexerSinkType::Parquet { path } => Arc::new(parquet::ParquetSink::new(path)),
+            IndexerSinkType::ClickHouse { url } => {
+                Arc::new(clickhouse::ClickHouseSink::new(url, &config.topic_prefix).await?)
+            },
+            #[allow(unreachable_patterns)]
+            sink_type => anyhow::bail!(
+                "Sink {:?} is not supported, the indexer needs to be built with its feature",
+                sink_type
+            ),
+        };
+        Ok(Self::from_sink(
+            sink,
+            config.topic_prefix.clone(),
+            config.skip_postgres_writes,
+        ))
+    }
+
+    pub fn from_sink(
+        sink: Arc<dyn RecordSink>,
+        topic_prefix: String,
+        skip_postgres_writes: bool,
+    ) -> Self {
+        Self {
+            sink,
+            topic_prefix,
+            skip_postgres_writes,
+        }
+    }
+
+    /// Whether none of the records are written to Postgres
+    pub fn skip_postgres_writes(&self) -> bool {
+        self.skip_postgres_writes && self.sink.tables().is_none()
+    }
+
+    /// Whether the records of the table aren't written to Postgres, as they are only published
+    pub fn skip_postgres_writes_for(&self, table: &str) -> bool {
+        self.skip_postgres_writes && self.publishes(table)
+    }
+
+    fn publishes(&self, table: &str) -> bool {
+        self.sink
+            .tables()
+            .map_or(true, |tables| tables.contains(&table))
+    }
+
+    /// Publishes the records of the tables, each to its own topic, then finishes the batch
+    pub async fn publish(
+        &self,
+        records: SinkRecords,
+        start_version: u64,
+        end_version: u64,
+        processor_name: &'static str,
+    ) -> Result<(), TransactionProcessingError> {
+        let to_processing_error = |err: anyhow::Error| {
+            TransactionProcessingError::SinkPublishError((
+                err,
+                start_version,
+                end_version,
+                processor_name,
+            ))
+        };
+        for (table, table_records) in records.tables {
+            if !self.publishes(table) {
+                continue;
+            }
+            let topic = format!("{}.{}", self.topic_prefix, table);
+            self.sink
+                .publish(&topic, table_records, start_version, end_version)
+                .await
+                .map_err(|err| {
+                    to_processing_error(err.context(format!("Failed to publish to {}", topic)))
+                })?;
+        }
+        self.sink
+            .finish_batch(start_version, end_version)
+            .await
+            .map_err(|err| to_processing_error(err.context("Failed to finish the batch")))
+    }
+}
+
+impl std::fmt::Debug for ProcessorSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProcessorSink")
+            .field("topic_prefix", &self.topic_prefix)
+            .field("skip_postgres_writes", &self.skip_postgres_writes)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct FakeSink {
+        tables: Option<&'static [&'static str]>,
+        published: Mutex<Vec<(String, Vec<Vec<u8>>)>>,
+        finished_batches: Mutex<Vec<(u64, u64)>>,
+    }
+
+    #[async_trait]
+    impl RecordSink for FakeSink {
+        fn tables(&self) -> Option<&[&'static str]> {
+            self.tables
+        }
+
+        async fn publish(
+            &self,
+            topic: &str,
+            records: Vec<Vec<u8>>,
+            _start_version: u64,
+            _end_version: u64,
+        ) -> Result<()> {
+            self.published
+                .lock()
+                .unwrap()
+                .push((topic.to_string(), records));
+            Ok(())
+        }
+
+        async fn finish_batch(&self, start_version: u64, end_version: u64) -> Result<()> {
+            self.finished_batches
+                .lock()
+                .unwrap()
+                .push((start_version, end_version));
+            Ok(())
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Record {
+        version: i64,
+    }
+
+    #[tokio::test]
+    async fn test_publish() {
+        let sink = Arc::new(FakeSink::default());
+        let processor_sink = ProcessorSink::from_sink(sink.clone(), "aptos".to_string(), false);
+        let mut records = SinkRecords::default();
+        records.add("events", &[Record { version: 1 }, Record { version: 2 }]);
+        records.add::<Record>("transactions", &[]);
+        processor_sink
+            .publish(records, 1, 2, "test_processor")
+            .await
+            .unwrap();
+
+        assert_eq!(*sink.published.lock().unwrap(), vec![(
+            "aptos.events".to_string(),
+            vec![br#"{"version":1}"#.to_vec(), br#"{"version":2}"#.to_vec()],
+        )]);
+        assert_eq!(*sink.finished_batches.lock().unwrap(), vec![(1, 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_publish_some_tables() {
+        let sink = Arc::new(FakeSink {
+            tables: Some(&["events"]),
+            ..Default::default()
+        });
+        let processor_sink = ProcessorSink::from_sink(sink.clone(), "aptos".to_string(), true);
+        assert!(!processor_sink.skip_postgres_writes());
+        assert!(processor_sink.skip_postgres_writes_for("events"));
+        assert!(!processor_sink.skip_postgres_writes_for("transactions"));
+
+        let mut records = SinkRecords::default();
+        records.add("events", &[Record { version: 1 }]);
+        records.add("transactions", &[Record { version: 1 }]);
+        processor_sink
+            .publish(records, 1, 1, "test_processor")
+            .await
+            .unwrap();
+
+        assert_eq!(*sink.published.lock().unwrap(), vec![(
+            "aptos.events".to_string(),
+            vec![br#"{"version":1}"#.to_vec()],
+        )]);
+    }
+}
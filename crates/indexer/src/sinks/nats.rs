@@ -0,0 +1,37 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::RecordSink;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+/// Publishes the records to NATS subjects
+pub struct NatsSink {
+    client: async_nats::Client,
+}
+
+impl NatsSink {
+    pub async fn new(url: &str) -> Result<Self> {
+        let client = async_nats::connect(url).await?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl RecordSink for NatsSink {
+    async fn publish(
+        &self,
+        topic: &str,
+        records: Vec<Vec<u8>>,
+        _start_version: u64,
+        _end_version: u64,
+    ) -> Result<()> {
+        for record in records {
+            self.client
+                .publish(topic.to_string(), record.into())
+                .await?;
+        }
+        self.client.flush().await.map_err(|e| anyhow!(e))?;
+        Ok(())
+    }
+}
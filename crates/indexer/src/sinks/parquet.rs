@@ -0,0 +1,287 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::RecordSink;
+use anyhow::{format_err, Result};
+use async_trait::async_trait;
+use parquet::{
+    basic::Compression,
+    data_type::{BoolType, ByteArray, ByteArrayType, Int64Type},
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::parser::parse_message_type,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+const GCS_PREFIX: &str = "gs://";
+
+/// Writes the records of each topic and batch of versions to a Parquet file at
+/// `<topic>/<start_version>_<end_version>.parquet`, and a manifest listing the files of the batch
+/// at `manifests/<start_version>_<end_version>.json` once they are all written
+pub struct ParquetSink {
+    location: Location,
+    /// The files written for the batches that aren't finished yet, by version range
+    pending_files: Mutex<HashMap<(u64, u64), Vec<ManifestFile>>>,
+}
+
+enum Location {
+    Local(PathBuf),
+    Gcs { bucket: String, prefix: String },
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Manifest {
+    start_version: u64,
+    end_version: u64,
+    files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct ManifestFile {
+    topic: String,
+    path: String,
+    num_rows: usize,
+}
+
+impl ParquetSink {
+    /// `path` is either a local directory or `gs://<bucket>/<prefix>`
+    pub fn new(path: &str) -> Self {
+        let location = match path.strip_prefix(GCS_PREFIX) {
+            Some(bucket_and_prefix) => {
+                let (bucket, prefix) = bucket_and_prefix
+                    .split_once('/')
+                    .unwrap_or((bucket_and_prefix, ""));
+                Location::Gcs {
+                    bucket: bucket.to_string(),
+                    prefix: prefix.trim_end_matches('/').to_string(),
+                }
+            },
+            None => Location::Local(PathBuf::from(path)),
+        };
+        Self {
+            location,
+            pending_files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn write(&self, path: &str, data: Vec<u8>, content_type: &str) -> Result<()> {
+        match &self.location {
+            Location::Local(dir) => {
+                let path = dir.join(path);
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(path, data).await?;
+            },
+            Location::Gcs { bucket, prefix } => {
+                let name = if prefix.is_empty() {
+                    path.to_string()
+                } else {
+                    format!("{}/{}", prefix, path)
+                };
+                cloud_storage::Object::create(bucket, data, &name, content_type).await?;
+            },
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecordSink for ParquetSink {
+    async fn publish(
+        &self,
+        topic: &str,
+        records: Vec<Vec<u8>>,
+        start_version: u64,
+        end_version: u64,
+    ) -> Result<()> {
+        let path = format!("{}/{}_{}.parquet", topic, start_version, end_version);
+        self.write(&path, to_parquet(&records)?, "application/octet-stream")
+            .await?;
+        self.pending_files
+            .lock()
+            .unwrap()
+            .entry((start_version, end_version))
+            .or_default()
+            .push(ManifestFile {
+                topic: topic.to_string(),
+                path,
+                num_rows: records.len(),
+            });
+        Ok(())
+    }
+
+    async fn finish_batch(&self, start_version: u64, end_version: u64) -> Result<()> {
+        let files = self
+            .pending_files
+            .lock()
+            .unwrap()
+            .remove(&(start_version, end_version))
+            .unwrap_or_default();
+        let manifest = Manifest {
+            start_version,
+            end_version,
+            files,
+        };
+        self.write(
+            &format!("manifests/{}_{}.json", start_version, end_version),
+            serde_json::to_vec_pretty(&manifest)?,
+            "application/json",
+        )
+        .await
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ColumnType {
+    Int64,
+    Bool,
+    /// Strings, and the other JSON values serialized to JSON
+    String,
+}
+
+impl ColumnType {
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => None,
+            Value::Bool(_) => Some(Self::Bool),
+            Value::Number(number) if number.is_i64() => Some(Self::Int64),
+            _ => Some(Self::String),
+        }
+    }
+}
+
+/// Converts JSON objects to a Parquet file with a single row group. The columns are the keys of
+/// the objects, in order of appearance, and are all optional. A column is typed as an integer or
+/// a boolean if all its values are, otherwise as a string.
+fn to_parquet(records: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let rows = records
+        .iter()
+        .map(|record| serde_json::from_slice::<Map<String, Value>>(record))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut columns: Vec<(String, Option<ColumnType>)> = vec![];
+    for row in &rows {
+        for (name, value) in row {
+            let value_type = ColumnType::of(value);
+            match columns.iter_mut().find(|(column, _)| column == name) {
+                Some((_, column_type)) => {
+                    *column_type = match (*column_type, value_type) {
+                        (None, value_type) => value_type,
+                        (column_type, None) => column_type,
+                        (Some(column_type), Some(value_type)) if column_type == value_type => {
+                            Some(column_type)
+                        },
+                        _ => Some(ColumnType::String),
+                    }
+                },
+                None => columns.push((name.clone(), value_type)),
+            }
+        }
+    }
+
+    let fields: String = columns
+        .iter()
+        .map(|(name, column_type)| {
+            let physical_type = match column_type.unwrap_or(ColumnType::String) {
+                ColumnType::Int64 => "INT64",
+                ColumnType::Bool => "BOOLEAN",
+                ColumnType::String => "BYTE_ARRAY",
+            };
+            let converted_type = match column_type.unwrap_or(ColumnType::String) {
+                ColumnType::String => " (UTF8)",
+                _ => "",
+            };
+            format!("OPTIONAL {} {}{};\n", physical_type, name, converted_type)
+        })
+        .collect();
+    let schema = parse_message_type(&format!("message schema {{\n{}}}", fields))?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let mut buffer = vec![];
+    let mut writer = SerializedFileWriter::new(&mut buffer, Arc::new(schema), Arc::new(props))?;
+    let mut row_group_writer = writer.next_row_group()?;
+    for (name, column_type) in &columns {
+        let values = rows
+            .iter()
+            .map(|row| row.get(name).filter(|v| !v.is_null()));
+        let def_levels: Vec<i16> = values.clone().map(|v| v.is_some() as i16).collect();
+        let mut column_writer = row_group_writer
+            .next_column()?
+            .ok_or_else(|| format_err!("Fewer columns in the Parquet schema than in the rows"))?;
+        match column_type.unwrap_or(ColumnType::String) {
+            ColumnType::Int64 => {
+                let values: Vec<i64> = values.flatten().filter_map(Value::as_i64).collect();
+                column_writer
+                    .typed::<Int64Type>()
+                    .write_batch(&values, Some(&def_levels), None)?;
+            },
+            ColumnType::Bool => {
+                let values: Vec<bool> = values.flatten().filter_map(Value::as_bool).collect();
+                column_writer
+                    .typed::<BoolType>()
+                    .write_batch(&values, Some(&def_levels), None)?;
+            },
+            ColumnType::String => {
+                let values: Vec<ByteArray> = values
+                    .flatten()
+                    .map(|value| match value {
+                        Value::String(value) => ByteArray::from(value.as_bytes().to_vec()),
+                        value => ByteArray::from(value.to_string().into_bytes()),
+                    })
+                    .collect();
+                column_writer.typed::<ByteArrayType>().write_batch(
+                    &values,
+                    Some(&def_levels),
+                    None,
+                )?;
+            },
+        }
+        column_writer.close()?;
+    }
+    row_group_writer.close()?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::{
+        basic::Type as PhysicalType,
+        file::reader::{FileReader, SerializedFileReader},
+    };
+
+    #[test]
+    fn test_to_parquet() {
+        let records = vec![
+            br#"{"version":1,"success":true,"data":{"amount":"10"}}"#.to_vec(),
+            br#"{"version":2,"success":null,"data":null,"type":"0x1::coin::CoinStore"}"#.to_vec(),
+        ];
+        let reader =
+            SerializedFileReader::new(bytes::Bytes::from(to_parquet(&records).unwrap())).unwrap();
+        let file_metadata = reader.metadata().file_metadata();
+        assert_eq!(file_metadata.num_rows(), 2);
+        assert_eq!(
+            file_metadata
+                .schema_descr()
+                .columns()
+                .iter()
+                .map(|column| (column.name(), column.physical_type()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("version", PhysicalType::INT64),
+                ("success", PhysicalType::BOOLEAN),
+                ("data", PhysicalType::BYTE_ARRAY),
+                ("type", PhysicalType::BYTE_ARRAY),
+            ]
+        );
+    }
+}
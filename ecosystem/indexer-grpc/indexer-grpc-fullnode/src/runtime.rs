@@ -5,8 +5,9 @@ use crate::{
     fullnode_data_service::FullnodeDataService, localnet_data_service::LocalnetDataService,
     ServiceContext,
 };
+use anyhow::Context as AnyhowContext;
 use aptos_api::context::Context;
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{IndexerGrpcConfig, NodeConfig};
 use aptos_logger::info;
 use aptos_mempool::MempoolClientSender;
 use aptos_protos::{
@@ -21,7 +22,10 @@ use aptos_storage_interface::DbReader;
 use aptos_types::chain_id::ChainId;
 use std::{net::ToSocketAddrs, sync::Arc};
 use tokio::runtime::Runtime;
-use tonic::{codec::CompressionEncoding, transport::Server};
+use tonic::{
+    codec::CompressionEncoding,
+    transport::{Identity, Server, ServerTlsConfig},
+};
 
 // Default Values
 pub const DEFAULT_NUM_RETRIES: usize = 3;
@@ -34,9 +38,19 @@ pub fn bootstrap(
     chain_id: ChainId,
     db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
-) -> Option<Runtime> {
+) -> anyhow::Result<Option<Runtime>> {
     if !config.indexer_grpc.enabled {
-        return None;
+        return Ok(None);
+    }
+
+    // Set up TLS before spawning the server, so that unusable certificates fail the node startup.
+    let mut server_builder = Server::builder()
+        .http2_keepalive_interval(Some(std::time::Duration::from_secs(60)))
+        .http2_keepalive_timeout(Some(std::time::Duration::from_secs(5)));
+    if let Some(tls_config) = load_tls_config(&config.indexer_grpc)? {
+        server_builder = server_builder
+            .tls_config(tls_config)
+            .context("Failed to set up TLS for the GRPC server")?;
     }
 
     let runtime = aptos_runtimes::spawn_named_runtime("indexer-grpc".to_string(), None);
@@ -77,10 +91,7 @@ pub fn bootstrap(
 
         let reflection_service_clone = reflection_service.clone();
 
-        let tonic_server = Server::builder()
-            .http2_keepalive_interval(Some(std::time::Duration::from_secs(60)))
-            .http2_keepalive_timeout(Some(std::time::Duration::from_secs(5)))
-            .add_service(reflection_service_clone);
+        let tonic_server = server_builder.add_service(reflection_service_clone);
 
         let router = match use_data_service_interface {
             false => tonic_server.add_service(FullnodeDataServer::new(server)),
@@ -98,5 +109,20 @@ pub fn bootstrap(
             .unwrap();
         info!(address = address, "[indexer-grpc] Started GRPC server");
     });
-    Some(runtime)
+    Ok(Some(runtime))
+}
+
+/// Loads the TLS identity of the grpc server from the configured PEM files, if any.
+fn load_tls_config(config: &IndexerGrpcConfig) -> anyhow::Result<Option<ServerTlsConfig>> {
+    let (cert_path, key_path) = match (&config.tls_cert_path, &config.tls_key_path) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        _ => return Ok(None),
+    };
+    let cert = std::fs::read(cert_path)
+        .with_context(|| format!("Failed to read TLS cert {}", cert_path))?;
+    let key =
+        std::fs::read(key_path).with_context(|| format!("Failed to read TLS key {}", key_path))?;
+    Ok(Some(
+        ServerTlsConfig::new().identity(Identity::from_pem(cert, key)),
+    ))
 }
@@ -62,7 +62,7 @@ where
             &config.storage.dir(),
             false, /* readonly */
             config.storage.storage_pruner_config,
-            config.storage.rocksdb_configs,
+            config.storage.rocksdb_configs_with_overrides(),
             false,
             config.storage.buffered_state_target_items,
             config.storage.max_num_nodes_per_lru_cache_shard,
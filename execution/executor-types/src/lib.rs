@@ -15,6 +15,7 @@ use aptos_types::{
     contract_event::ContractEvent,
     epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
+    on_chain_config::{FeatureFlag, Features},
     proof::{AccumulatorExtensionProof, SparseMerkleProofExt},
     state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{
@@ -30,13 +31,17 @@ pub use parsed_transaction_output::ParsedTransactionOutput;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::max,
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     fmt::Debug,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, Mutex,
     },
 };
+pub use transaction_mismatch::{TransactionMismatch, TransactionMismatchField};
 
 mod error;
 mod executed_chunk;
@@ -45,6 +50,7 @@ pub mod in_memory_state_calculator;
 mod ledger_update_output;
 pub mod parsed_transaction_output;
 pub mod state_checkpoint_output;
+mod transaction_mismatch;
 
 pub trait ChunkExecutorTrait: Send + Sync {
     /// Verifies the transactions based on the provided proofs and ledger info. If the transactions
@@ -113,6 +119,10 @@ pub trait ChunkExecutorTrait: Send + Sync {
     /// Commit a previously executed chunk. Returns a chunk commit notification.
     fn commit_chunk(&self) -> Result<ChunkCommitNotification>;
 
+    /// Reports the chunks that have been enqueued but not committed yet, so that callers can
+    /// apply backpressure before enqueuing more chunks.
+    fn queue_status(&self) -> ChunkQueueStatus;
+
     /// Resets the chunk executor by synchronizing state with storage.
     fn reset(&self) -> Result<()>;
 
@@ -190,10 +200,34 @@ pub trait BlockExecutorTrait: Send + Sync {
         )
     }
 
+    /// Releases the speculative state of the blocks that are neither ancestors nor descendants of
+    /// `before_block_id`, i.e. the forks abandoned once consensus decided to commit it.
+    fn prune_speculative_state(&self, before_block_id: HashValue) -> ExecutorResult<()>;
+
     /// Finishes the block executor by releasing memory held by inner data structures(SMT).
     fn finish(&self);
 }
 
+/// Feature flags to enable or disable on top of the on-chain `Features` when re-executing
+/// transactions.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct FeatureOverrides {
+    pub enable: Vec<FeatureFlag>,
+    pub disable: Vec<FeatureFlag>,
+}
+
+impl FeatureOverrides {
+    pub fn apply(&self, features: &mut Features) {
+        for flag in &self.enable {
+            features.enable(*flag);
+        }
+        for flag in &self.disable {
+            features.disable(*flag);
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum VerifyExecutionMode {
     NoVerify,
@@ -201,6 +235,11 @@ pub enum VerifyExecutionMode {
         txns_to_skip: Arc<BTreeSet<Version>>,
         lazy_quit: bool,
         seen_error: Arc<AtomicBool>,
+        mismatches: Arc<Mutex<Vec<TransactionMismatch>>>,
+        audit_report: Option<Arc<Mutex<File>>>,
+        /// Feature overrides by the first version they apply to. Each one applies until the
+        /// version of the next one.
+        feature_overrides: Arc<BTreeMap<Version, FeatureOverrides>>,
     },
 }
 
@@ -210,6 +249,9 @@ impl VerifyExecutionMode {
             txns_to_skip: Arc::new(BTreeSet::new()),
             lazy_quit: false,
             seen_error: Arc::new(AtomicBool::new(false)),
+            mismatches: Arc::new(Mutex::new(Vec::new())),
+            audit_report: None,
+            feature_overrides: Arc::new(BTreeMap::new()),
         }
     }
 
@@ -218,6 +260,9 @@ impl VerifyExecutionMode {
             txns_to_skip: Arc::new(txns_to_skip.into_iter().collect()),
             lazy_quit: false,
             seen_error: Arc::new(AtomicBool::new(false)),
+            mismatches: Arc::new(Mutex::new(Vec::new())),
+            audit_report: None,
+            feature_overrides: Arc::new(BTreeMap::new()),
         }
     }
 
@@ -228,6 +273,29 @@ impl VerifyExecutionMode {
         }
     }
 
+    pub fn feature_overrides(&self) -> Arc<BTreeMap<Version, FeatureOverrides>> {
+        match self {
+            VerifyExecutionMode::NoVerify => Arc::new(BTreeMap::new()),
+            VerifyExecutionMode::Verify {
+                feature_overrides, ..
+            } => feature_overrides.clone(),
+        }
+    }
+
+    /// Sets the feature overrides to re-execute transactions with, keyed by the first version
+    /// each of them applies to, e.g. to match the historical on-chain configuration when
+    /// replaying under a newer binary.
+    pub fn set_feature_overrides(mut self, overrides: BTreeMap<Version, FeatureOverrides>) -> Self {
+        if let Self::Verify {
+            ref mut feature_overrides,
+            ..
+        } = self
+        {
+            *feature_overrides = Arc::new(overrides);
+        }
+        self
+    }
+
     pub fn set_lazy_quit(mut self, is_lazy_quit: bool) -> Self {
         if let Self::Verify {
             ref mut lazy_quit, ..
@@ -238,6 +306,26 @@ impl VerifyExecutionMode {
         self
     }
 
+    /// Writes every mismatch found to the file at `path` as JSON lines, replacing its existing
+    /// content. Implies lazy quit, so that the report covers all the transactions replayed rather
+    /// than only the first mismatch.
+    pub fn set_audit_report(self, path: &Path) -> Result<Self> {
+        let mut mode = self.set_lazy_quit(true);
+        if let Self::Verify {
+            ref mut audit_report,
+            ..
+        } = mode
+        {
+            let file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(path)?;
+            *audit_report = Some(Arc::new(Mutex::new(file)));
+        }
+        Ok(mode)
+    }
+
     pub fn is_lazy_quit(&self) -> bool {
         match self {
             VerifyExecutionMode::NoVerify => false,
@@ -264,6 +352,37 @@ impl VerifyExecutionMode {
             VerifyExecutionMode::Verify { seen_error, .. } => seen_error.load(Ordering::Relaxed),
         }
     }
+
+    pub fn record_mismatch(&self, mismatch: TransactionMismatch) -> Result<()> {
+        match self {
+            VerifyExecutionMode::NoVerify => unreachable!("Should not call in no-verify mode."),
+            VerifyExecutionMode::Verify {
+                mismatches,
+                audit_report,
+                ..
+            } => {
+                if let Some(audit_report) = audit_report {
+                    let mut file = audit_report.lock().expect("Lock poisoned.");
+                    serde_json::to_writer(&mut *file, &mismatch)?;
+                    writeln!(file)?;
+                    file.flush()?;
+                }
+                mismatches.lock().expect("Lock poisoned.").push(mismatch);
+                Ok(())
+            },
+        }
+    }
+
+    /// All the mismatches recorded so far, in the order they are found. Useful to inspect the
+    /// result of a lazy-quit run, which keeps going after a mismatch.
+    pub fn mismatches(&self) -> Vec<TransactionMismatch> {
+        match self {
+            VerifyExecutionMode::NoVerify => vec![],
+            VerifyExecutionMode::Verify { mismatches, .. } => {
+                mismatches.lock().expect("Lock poisoned.").clone()
+            },
+        }
+    }
 }
 
 pub trait TransactionReplayer: Send {
@@ -277,9 +396,29 @@ pub trait TransactionReplayer: Send {
     ) -> Result<()>;
 
     fn commit(&self) -> Result<ExecutedChunk>;
+
+    /// Resets the replayer to the latest state committed to the DB, and returns the version to
+    /// resume the replay from, i.e. the one following the latest version in the DB. The
+    /// transactions committed by an interrupted replay don't need to be replayed again.
+    fn resume_replay(&self) -> Result<Version>;
+}
+
+/// The chunks held by a chunk executor that are not committed yet.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ChunkQueueStatus {
+    /// Number of chunks executed (or applied) and waiting for the ledger update.
+    pub chunks_to_update_ledger: usize,
+    /// Number of chunks waiting to be committed.
+    pub chunks_to_commit: usize,
+    /// Total number of transactions in the chunks not committed yet.
+    pub txns_in_flight: usize,
+    /// Approximate size of the chunks not committed yet, in bytes, based on the sizes of the
+    /// write sets and events.
+    pub bytes_in_flight: usize,
 }
 
 /// A structure that holds relevant information about a chunk that was committed.
+#[derive(Clone)]
 pub struct ChunkCommitNotification {
     pub committed_events: Vec<ContractEvent>,
     pub committed_transactions: Vec<Transaction>,
@@ -0,0 +1,126 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::{
+    contract_event::ContractEvent,
+    proof::accumulator::InMemoryEventAccumulator,
+    state_store::state_key::StateKey,
+    transaction::{TransactionInfo, TransactionOutput, TransactionStatus, Version},
+    write_set::{WriteOp, WriteSet},
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A part of a `TransactionOutput` that doesn't match the `TransactionInfo` it's verified against.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum TransactionMismatchField {
+    Status,
+    GasUsed,
+    WriteSet,
+    Events,
+}
+
+/// Describes how the output of a replayed transaction differs from the one committed on chain.
+#[derive(Clone, Debug, Serialize)]
+pub struct TransactionMismatch {
+    pub version: Version,
+    pub mismatching_fields: Vec<TransactionMismatchField>,
+    pub expected_gas_used: u64,
+    pub actual_gas_used: u64,
+    /// Write ops that differ, as `(key, expected, actual)`. Only available if the write set
+    /// mismatches and the expected write set is known.
+    pub write_ops_diff: Vec<(StateKey, Option<WriteOp>, Option<WriteOp>)>,
+    /// Events that differ, as `(index, expected, actual)`. Only available if the events
+    /// mismatch and the expected events are known.
+    pub events_diff: Vec<(usize, Option<ContractEvent>, Option<ContractEvent>)>,
+}
+
+impl TransactionMismatch {
+    /// Compares the output against the transaction info, in the same way as
+    /// `TransactionOutput::ensure_match_transaction_info()`. Returns `None` if they match.
+    pub fn new(
+        version: Version,
+        output: &TransactionOutput,
+        txn_info: &TransactionInfo,
+        expected_write_set: Option<&WriteSet>,
+        expected_events: Option<&[ContractEvent]>,
+    ) -> Option<Self> {
+        let mut mismatching_fields = vec![];
+        let mut write_ops_diff = vec![];
+        let mut events_diff = vec![];
+
+        let expected_txn_status: TransactionStatus = txn_info.status().clone().into();
+        if output.status() != &expected_txn_status {
+            mismatching_fields.push(TransactionMismatchField::Status);
+        }
+
+        if output.gas_used() != txn_info.gas_used() {
+            mismatching_fields.push(TransactionMismatchField::GasUsed);
+        }
+
+        if CryptoHash::hash(output.write_set()) != txn_info.state_change_hash() {
+            mismatching_fields.push(TransactionMismatchField::WriteSet);
+            if let Some(expected_write_set) = expected_write_set {
+                write_ops_diff = diff_write_sets(expected_write_set, output.write_set());
+            }
+        }
+
+        let event_hashes = output
+            .events()
+            .iter()
+            .map(CryptoHash::hash)
+            .collect::<Vec<_>>();
+        let event_root_hash = InMemoryEventAccumulator::from_leaves(&event_hashes).root_hash;
+        if event_root_hash != txn_info.event_root_hash() {
+            mismatching_fields.push(TransactionMismatchField::Events);
+            if let Some(expected_events) = expected_events {
+                events_diff = diff_events(expected_events, output.events());
+            }
+        }
+
+        if mismatching_fields.is_empty() {
+            return None;
+        }
+        Some(Self {
+            version,
+            mismatching_fields,
+            expected_gas_used: txn_info.gas_used(),
+            actual_gas_used: output.gas_used(),
+            write_ops_diff,
+            events_diff,
+        })
+    }
+}
+
+fn diff_write_sets(
+    expected: &WriteSet,
+    actual: &WriteSet,
+) -> Vec<(StateKey, Option<WriteOp>, Option<WriteOp>)> {
+    let mut ops: BTreeMap<&StateKey, (Option<&WriteOp>, Option<&WriteOp>)> = BTreeMap::new();
+    for (key, op) in expected {
+        ops.entry(key).or_default().0 = Some(op);
+    }
+    for (key, op) in actual {
+        ops.entry(key).or_default().1 = Some(op);
+    }
+
+    ops.into_iter()
+        .filter(|(_key, (expected, actual))| expected != actual)
+        .map(|(key, (expected, actual))| (key.clone(), expected.cloned(), actual.cloned()))
+        .collect()
+}
+
+fn diff_events(
+    expected: &[ContractEvent],
+    actual: &[ContractEvent],
+) -> Vec<(usize, Option<ContractEvent>, Option<ContractEvent>)> {
+    (0..expected.len().max(actual.len()))
+        .filter_map(|idx| {
+            let (expected, actual) = (expected.get(idx), actual.get(idx));
+            (expected != actual).then(|| (idx, expected.cloned(), actual.cloned()))
+        })
+        .collect()
+}
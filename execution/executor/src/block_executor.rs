@@ -61,9 +61,37 @@ impl TransactionBlockExecutor for AptosVM {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlockGasLimitOverride {
+    /// Executes every block with this gas limit.
+    Limit(u64),
+    /// Executes every block without a gas limit.
+    NoLimit,
+}
+
+/// Overrides of the configuration the block executor is given by its caller, so that consensus
+/// tests and local simulations can tweak the execution without patching the executor.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BlockExecutorConfigOverride {
+    /// Replaces the block gas limit passed to `execute_and_state_checkpoint()`, which is
+    /// otherwise the on-chain one.
+    pub block_gas_limit: Option<BlockGasLimitOverride>,
+}
+
+impl BlockExecutorConfigOverride {
+    pub fn block_gas_limit(&self, maybe_block_gas_limit: Option<u64>) -> Option<u64> {
+        match self.block_gas_limit {
+            Some(BlockGasLimitOverride::Limit(limit)) => Some(limit),
+            Some(BlockGasLimitOverride::NoLimit) => None,
+            None => maybe_block_gas_limit,
+        }
+    }
+}
+
 pub struct BlockExecutor<V> {
     pub db: DbReaderWriter,
     inner: RwLock<Option<BlockExecutorInner<V>>>,
+    config_override: RwLock<BlockExecutorConfigOverride>,
 }
 
 impl<V> BlockExecutor<V>
@@ -71,12 +99,25 @@ where
     V: TransactionBlockExecutor,
 {
     pub fn new(db: DbReaderWriter) -> Self {
+        Self::new_with_config_override(db, BlockExecutorConfigOverride::default())
+    }
+
+    pub fn new_with_config_override(
+        db: DbReaderWriter,
+        config_override: BlockExecutorConfigOverride,
+    ) -> Self {
         Self {
             db,
             inner: RwLock::new(None),
+            config_override: RwLock::new(config_override),
         }
     }
 
+    /// Replaces the config override, taking effect from the next block executed.
+    pub fn set_config_override(&self, config_override: BlockExecutorConfigOverride) {
+        *self.config_override.write() = config_override;
+    }
+
     pub fn root_smt(&self) -> SparseMerkleTree<StateValue> {
         self.inner
             .read()
@@ -118,6 +159,10 @@ where
         maybe_block_gas_limit: Option<u64>,
     ) -> ExecutorResult<StateCheckpointOutput> {
         self.maybe_initialize()?;
+        let maybe_block_gas_limit = self
+            .config_override
+            .read()
+            .block_gas_limit(maybe_block_gas_limit);
         self.inner
             .read()
             .as_ref()
@@ -152,6 +197,15 @@ where
             .commit_blocks_ext(block_ids, ledger_info_with_sigs, save_state_snapshots)
     }
 
+    fn prune_speculative_state(&self, before_block_id: HashValue) -> ExecutorResult<()> {
+        self.maybe_initialize()?;
+        self.inner
+            .read()
+            .as_ref()
+            .expect("BlockExecutor is not reset")
+            .prune_speculative_state(before_block_id)
+    }
+
     fn finish(&self) {
         *self.inner.write() = None;
     }
@@ -189,6 +243,12 @@ where
         self.block_tree.root_block().id
     }
 
+    fn prune_speculative_state(&self, before_block_id: HashValue) -> ExecutorResult<()> {
+        // The blocks are dropped asynchronously, no need to wait for it.
+        let _ = self.block_tree.prune_forks(before_block_id)?;
+        Ok(())
+    }
+
     fn execute_and_state_checkpoint(
         &self,
         block: ExecutableBlock,
@@ -7,26 +7,26 @@
 use crate::{
     components::{
         apply_chunk_output::{ensure_no_discard, ensure_no_retry, ApplyChunkOutput},
-        chunk_commit_queue::{ChunkCommitQueue, ChunkToUpdateLedger},
+        chunk_commit_queue::{ChunkCommitQueue, ChunkStageTimes, ChunkToUpdateLedger},
         chunk_output::ChunkOutput,
     },
     logging::{LogEntry, LogSchema},
     metrics::{
         APTOS_EXECUTOR_APPLY_CHUNK_SECONDS, APTOS_EXECUTOR_COMMIT_CHUNK_SECONDS,
         APTOS_EXECUTOR_EXECUTE_CHUNK_SECONDS, APTOS_EXECUTOR_LEDGER_UPDATE_OTHER_SECONDS,
-        APTOS_EXECUTOR_VM_EXECUTE_CHUNK_SECONDS,
+        APTOS_EXECUTOR_SIG_VERIFICATION_SKIPPED_TXNS, APTOS_EXECUTOR_VM_EXECUTE_CHUNK_SECONDS,
     },
 };
 use anyhow::{anyhow, ensure, Result};
 use aptos_executor_types::{
-    ChunkCommitNotification, ChunkExecutorTrait, ExecutedChunk, ParsedTransactionOutput,
-    TransactionReplayer, VerifyExecutionMode,
+    ChunkCommitNotification, ChunkExecutorTrait, ChunkQueueStatus, ExecutedChunk, FeatureOverrides,
+    ParsedTransactionOutput, TransactionMismatch, TransactionReplayer, VerifyExecutionMode,
 };
 use aptos_experimental_runtimes::thread_manager::optimal_min_len;
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use aptos_metrics_core::TimerHelper;
-use aptos_state_view::StateViewId;
+use aptos_state_view::{StateViewId, TStateView};
 use aptos_storage_interface::{
     async_proof_fetcher::AsyncProofFetcher, cached_state_view::CachedStateView,
     state_delta::StateDelta, DbReaderWriter, ExecutedTrees,
@@ -34,6 +34,8 @@ use aptos_storage_interface::{
 use aptos_types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
+    on_chain_config::{Features, OnChainConfig},
+    state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{
         signature_verified_transaction::SignatureVerifiedTransaction, Transaction, TransactionInfo,
         TransactionListWithProof, TransactionOutput, TransactionOutputListWithProof,
@@ -46,7 +48,15 @@ use fail::fail_point;
 use itertools::multizip;
 use once_cell::sync::Lazy;
 use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
-use std::{iter::once, marker::PhantomData, sync::Arc};
+use std::{
+    iter::once,
+    marker::PhantomData,
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
+    time::Instant,
+};
 
 pub static SIG_VERIFY_POOL: Lazy<Arc<rayon::ThreadPool>> = Lazy::new(|| {
     Arc::new(
@@ -58,19 +68,82 @@ pub static SIG_VERIFY_POOL: Lazy<Arc<rayon::ThreadPool>> = Lazy::new(|| {
     )
 });
 
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChunkExecutorConfig {
+    /// Skips verifying the signatures of the transactions in chunks executed by
+    /// `enqueue_chunk_by_execution()`. Those transactions are covered by a verified proof, i.e.
+    /// they have been committed by the validators, who only commit transactions with valid
+    /// signatures. Every skipped chunk is logged and counted in
+    /// `aptos_executor_sig_verification_skipped_txns`.
+    pub trust_proof_verified_signatures: bool,
+    /// Max number of the keys read by a chunk executed by `enqueue_chunk_by_execution()` whose
+    /// proofs are prefetched for the next one, since consecutive chunks tend to read the same hot
+    /// keys. 0 disables the prefetching.
+    pub max_proof_prefetch_hints: usize,
+}
+
 pub struct ChunkExecutor<V> {
     db: DbReaderWriter,
+    config: ChunkExecutorConfig,
     inner: RwLock<Option<ChunkExecutorInner<V>>>,
+    commit_subscribers: Mutex<Vec<SyncSender<ChunkCommitNotification>>>,
 }
 
 impl<V: VMExecutor> ChunkExecutor<V> {
     pub fn new(db: DbReaderWriter) -> Self {
+        Self::new_with_config(db, ChunkExecutorConfig::default())
+    }
+
+    pub fn new_with_config(db: DbReaderWriter, config: ChunkExecutorConfig) -> Self {
         Self {
             db,
+            config,
             inner: RwLock::new(None),
+            commit_subscribers: Mutex::new(Vec::new()),
         }
     }
 
+    /// Returns a receiver that gets a notification for every chunk committed from now on,
+    /// in commit order. Subscriptions survive `reset()`, and are dropped once the receiver is.
+    ///
+    /// At most `capacity` notifications are buffered. Commits never wait for a subscriber: one
+    /// that falls further behind is dropped, i.e. its receiver gets disconnected once the
+    /// buffered notifications are received, rather than silently missing some commits.
+    pub fn subscribe_commits(&self, capacity: usize) -> Receiver<ChunkCommitNotification> {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        self.commit_subscribers.lock().push(sender);
+        receiver
+    }
+
+    fn notify_commit_subscribers(&self, notification: &ChunkCommitNotification) {
+        self.commit_subscribers.lock().retain(|sender| {
+            match sender.try_send(notification.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!("Dropping a chunk commit subscriber that fell behind.");
+                    false
+                },
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+    }
+
+    /// Resets the executor to start from the state snapshot at `version` rather than from the
+    /// latest state in the DB, so that a window of transactions can be replayed without the
+    /// preceding history, e.g. on a node restored from a state snapshot.
+    ///
+    /// `state` must be the state right after `version`, and its base must be available in the
+    /// DB, as must the transaction accumulator up to `version`.
+    pub fn reset_from_state_snapshot(&self, version: Version, state: StateDelta) -> Result<()> {
+        *self.inner.write() = Some(ChunkExecutorInner::new_from_state_snapshot(
+            self.db.clone(),
+            self.config,
+            version,
+            state,
+        )?);
+        Ok(())
+    }
+
     fn maybe_initialize(&self) -> Result<()> {
         if self.inner.read().is_none() {
             self.reset()?;
@@ -120,35 +193,80 @@ impl<V: VMExecutor> ChunkExecutorTrait for ChunkExecutor<V> {
     }
 
     fn commit_chunk(&self) -> Result<ChunkCommitNotification> {
-        self.inner
+        let notification = self
+            .inner
             .read()
             .as_ref()
             .expect("not reset")
-            .commit_chunk()
+            .commit_chunk()?;
+        self.notify_commit_subscribers(&notification);
+        Ok(notification)
+    }
+
+    fn queue_status(&self) -> ChunkQueueStatus {
+        self.inner
+            .read()
+            .as_ref()
+            .map(|inner| inner.commit_queue.lock().status())
+            .unwrap_or_default()
     }
 
     fn reset(&self) -> Result<()> {
-        *self.inner.write() = Some(ChunkExecutorInner::new(self.db.clone())?);
+        *self.inner.write() = Some(ChunkExecutorInner::new(self.db.clone(), self.config)?);
         Ok(())
     }
 
     fn finish(&self) {
         *self.inner.write() = None;
+        // Syncs the chunks coalesced by group commit right away, instead of leaving them to the
+        // next commit.
+        if let Err(e) = self.db.writer.flush_pending_commits() {
+            warn!(error = ?e, "Failed to flush pending commits.");
+        }
     }
 }
 
 struct ChunkExecutorInner<V> {
     db: DbReaderWriter,
+    config: ChunkExecutorConfig,
     commit_queue: Mutex<ChunkCommitQueue>,
+    /// The keys whose proofs are prefetched for the next chunk executed.
+    proof_prefetch_hints: Mutex<Vec<StateKey>>,
     _phantom: PhantomData<V>,
 }
 
 impl<V: VMExecutor> ChunkExecutorInner<V> {
-    pub fn new(db: DbReaderWriter) -> Result<Self> {
+    pub fn new(db: DbReaderWriter, config: ChunkExecutorConfig) -> Result<Self> {
         let commit_queue = Mutex::new(ChunkCommitQueue::new_from_db(&db.reader)?);
         Ok(Self {
             db,
+            config,
+            commit_queue,
+            proof_prefetch_hints: Mutex::new(Vec::new()),
+            _phantom: PhantomData,
+        })
+    }
+
+    pub fn new_from_state_snapshot(
+        db: DbReaderWriter,
+        config: ChunkExecutorConfig,
+        version: Version,
+        state: StateDelta,
+    ) -> Result<Self> {
+        ensure!(
+            state.current_version == Some(version),
+            "State is not at the snapshot version. state version: {:?}, snapshot version: {}",
+            state.current_version,
+            version,
+        );
+        let transaction_accumulator = db.reader.get_accumulator_summary(version)?.0;
+        let executed_trees = ExecutedTrees::new(state, Arc::new(transaction_accumulator));
+        let commit_queue = Mutex::new(ChunkCommitQueue::new_from_executed_trees(executed_trees));
+        Ok(Self {
+            db,
+            config,
             commit_queue,
+            proof_prefetch_hints: Mutex::new(Vec::new()),
             _phantom: PhantomData,
         })
     }
@@ -165,6 +283,7 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
     }
 
     fn commit_chunk_impl(&self) -> Result<ExecutedChunk> {
+        let start = Instant::now();
         let (persisted_state, chunk) = self.commit_queue.lock().next_chunk_to_commit()?;
 
         if chunk.ledger_info.is_some() || !chunk.transactions_to_commit().is_empty() {
@@ -188,7 +307,7 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
         }
         self.commit_queue
             .lock()
-            .dequeue_committed(chunk.result_state.clone())?;
+            .dequeue_committed(chunk.result_state.clone(), start.elapsed())?;
 
         Ok(chunk)
     }
@@ -234,26 +353,61 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
             .map(|t| t.state_checkpoint_hash())
             .collect();
 
-        // TODO(skedia) In the chunk executor path, we ideally don't need to verify the signature
-        // as only transactions with verified signatures are committed to the storage.
         let num_txns = transactions.len();
-        let sig_verified_txns = SIG_VERIFY_POOL.install(|| {
-            transactions
-                .into_par_iter()
-                .with_min_len(optimal_min_len(num_txns, 32))
-                .map(|t| t.into())
-                .collect::<Vec<_>>()
-        });
+        let sig_verified_txns: Vec<SignatureVerifiedTransaction> =
+            if self.config.trust_proof_verified_signatures {
+                // Only transactions with verified signatures are committed to the storage, and
+                // the proof of the chunk has been verified above.
+                APTOS_EXECUTOR_SIG_VERIFICATION_SKIPPED_TXNS.inc_by(num_txns as u64);
+                info!(
+                    LogSchema::new(LogEntry::ChunkExecutor)
+                        .first_version_in_request(Some(first_version_in_request))
+                        .num_txns_in_request(num_txns),
+                    "Skipped signature verification of proof-verified transactions.",
+                );
+                transactions
+                    .into_iter()
+                    .map(SignatureVerifiedTransaction::Valid)
+                    .collect()
+            } else {
+                SIG_VERIFY_POOL.install(|| {
+                    transactions
+                        .into_par_iter()
+                        .with_min_len(optimal_min_len(num_txns, 32))
+                        .map(|t| t.into())
+                        .collect()
+                })
+            };
 
         // Execute transactions.
+        let mut stage_times = ChunkStageTimes::default();
         let state_view = self.latest_state_view(&parent_state)?;
+        state_view.prefetch_proofs(self.proof_prefetch_hints.lock().iter());
         let chunk_output = {
             let _timer = APTOS_EXECUTOR_VM_EXECUTE_CHUNK_SECONDS.start_timer();
+            let start = Instant::now();
             // State sync executor shouldn't have block gas limit.
-            ChunkOutput::by_transaction_execution::<V>(sig_verified_txns.into(), state_view, None)?
+            let chunk_output = ChunkOutput::by_transaction_execution::<V>(
+                sig_verified_txns.into(),
+                state_view,
+                None,
+            )?;
+            stage_times.vm_execution = start.elapsed();
+            chunk_output
         };
+        if self.config.max_proof_prefetch_hints > 0 {
+            *self.proof_prefetch_hints.lock() = chunk_output
+                .state_cache
+                .sharded_state_cache
+                .keys()
+                .take(self.config.max_proof_prefetch_hints)
+                .collect();
+        }
+
+        let num_bytes = chunk_output.num_bytes();
 
         // Calcualte state snapshot
+        let start = Instant::now();
         let (result_state, next_epoch_state, state_checkpoint_output) =
             ApplyChunkOutput::calculate_state_checkpoint(
                 chunk_output,
@@ -262,18 +416,21 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
                 Some(known_state_checkpoints),
                 false, // is_block
             )?;
+        stage_times.state_checkpoint = start.elapsed();
 
         // Enqueue for next stage.
-        self.commit_queue
-            .lock()
-            .enqueue_for_ledger_update(ChunkToUpdateLedger {
+        self.commit_queue.lock().enqueue_for_ledger_update(
+            ChunkToUpdateLedger {
                 result_state,
                 state_checkpoint_output,
                 next_epoch_state,
                 verified_target_li,
                 epoch_change_li,
                 txn_infos_with_proof,
-            })?;
+            },
+            num_bytes,
+            stage_times,
+        )?;
 
         info!(
             LogSchema::new(LogEntry::ChunkExecutor)
@@ -329,7 +486,11 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
         let chunk_output =
             ChunkOutput::by_transaction_output(transactions_and_outputs, state_view)?;
 
+        let num_bytes = chunk_output.num_bytes();
+
         // Calculate state snapshot
+        let mut stage_times = ChunkStageTimes::default();
+        let start = Instant::now();
         let (result_state, next_epoch_state, state_checkpoint_output) =
             ApplyChunkOutput::calculate_state_checkpoint(
                 chunk_output,
@@ -338,18 +499,21 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
                 Some(known_state_checkpoints),
                 false, // is_block
             )?;
+        stage_times.state_checkpoint = start.elapsed();
 
         // Enqueue for next stage.
-        self.commit_queue
-            .lock()
-            .enqueue_for_ledger_update(ChunkToUpdateLedger {
+        self.commit_queue.lock().enqueue_for_ledger_update(
+            ChunkToUpdateLedger {
                 result_state,
                 state_checkpoint_output,
                 next_epoch_state,
                 verified_target_li,
                 epoch_change_li,
                 txn_infos_with_proof,
-            })?;
+            },
+            num_bytes,
+            stage_times,
+        )?;
 
         info!(
             LogSchema::new(LogEntry::ChunkExecutor)
@@ -365,6 +529,7 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
         let _timer =
             APTOS_EXECUTOR_LEDGER_UPDATE_OTHER_SECONDS.timer_with(&["chunk_update_ledger_total"]);
 
+        let start = Instant::now();
         let (parent_accumulator, chunk) = self.commit_queue.lock().next_chunk_to_update_ledger()?;
         let ChunkToUpdateLedger {
             result_state,
@@ -405,7 +570,7 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
 
         self.commit_queue
             .lock()
-            .save_ledger_update_output(executed_chunk)?;
+            .save_ledger_update_output(executed_chunk, start.elapsed())?;
         info!(
             LogSchema::new(LogEntry::ChunkExecutor)
                 .first_version_in_request(Some(first_version))
@@ -423,6 +588,27 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
     }
 }
 
+/// Makes the state view return the on-chain `Features` with the overrides applied.
+fn override_features(state_view: &CachedStateView, overrides: &FeatureOverrides) -> Result<()> {
+    let state_key = StateKey::access_path(Features::access_path()?);
+    let (metadata, mut features) = match state_view.get_state_value(&state_key)? {
+        Some(state_value) => {
+            let (metadata, bytes) = state_value.into();
+            (metadata, bcs::from_bytes::<Features>(&bytes)?)
+        },
+        None => (None, Features::default()),
+    };
+    overrides.apply(&mut features);
+
+    let bytes = bcs::to_bytes(&features)?.into();
+    let state_value = match metadata {
+        Some(metadata) => StateValue::new_with_metadata(bytes, metadata),
+        None => StateValue::new_legacy(bytes),
+    };
+    state_view.override_state_value(state_key, Some(state_value));
+    Ok(())
+}
+
 /// Verifies the transaction list proof against the ledger info and returns transactions
 /// that are not already applied in the ledger.
 #[cfg(not(feature = "consensus-only-perf-test"))]
@@ -472,6 +658,15 @@ impl<V: VMExecutor> TransactionReplayer for ChunkExecutor<V> {
     fn commit(&self) -> Result<ExecutedChunk> {
         self.inner.read().as_ref().expect("not reset").commit()
     }
+
+    fn resume_replay(&self) -> Result<Version> {
+        self.reset()?;
+        self.inner
+            .read()
+            .as_ref()
+            .expect("not reset")
+            .resume_replay()
+    }
 }
 
 impl<V: VMExecutor> TransactionReplayer for ChunkExecutorInner<V> {
@@ -527,6 +722,10 @@ impl<V: VMExecutor> TransactionReplayer for ChunkExecutorInner<V> {
     fn commit(&self) -> Result<ExecutedChunk> {
         self.commit_chunk_impl()
     }
+
+    fn resume_replay(&self) -> Result<Version> {
+        Ok(self.commit_queue.lock().latest_state().next_version())
+    }
 }
 
 impl<V: VMExecutor> ChunkExecutorInner<V> {
@@ -617,8 +816,18 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
         end_version: Version,
         verify_execution_mode: &VerifyExecutionMode,
     ) -> Result<Version> {
+        // Feature overrides apply from their first version on, so don't execute past the next one.
+        let feature_overrides = verify_execution_mode.feature_overrides();
+        let end_version = feature_overrides
+            .range(begin_version + 1..end_version)
+            .next()
+            .map_or(end_version, |(version, _)| *version);
+
         // Execute transactions.
         let state_view = self.latest_state_view(latest_view.state())?;
+        if let Some((_, overrides)) = feature_overrides.range(..=begin_version).next_back() {
+            override_features(&state_view, overrides)?;
+        }
         let txns = transactions
             .iter()
             .take((end_version - begin_version) as usize)
@@ -643,6 +852,15 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
                 Some(write_set),
                 Some(events),
             ) {
+                if let Some(mismatch) = TransactionMismatch::new(
+                    version,
+                    txn_out,
+                    txn_info,
+                    Some(write_set),
+                    Some(events),
+                ) {
+                    verify_execution_mode.record_mismatch(mismatch)?;
+                }
                 if verify_execution_mode.is_lazy_quit() {
                     error!("(Not quitting right away.) {}", err);
                     verify_execution_mode.mark_seen_error();
@@ -0,0 +1,184 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::{anyhow, Result};
+use aptos_executor_types::{ChunkCommitNotification, ChunkExecutorTrait};
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{TransactionListWithProof, TransactionOutputListWithProof},
+};
+use std::{
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+/// Bounds of the queues between the stages of a `ChunkExecutorPipeline`.
+#[derive(Clone, Copy, Debug)]
+pub struct ChunkExecutorPipelineConfig {
+    /// Max number of chunks waiting to be executed or applied. `enqueue_chunk()` blocks once
+    /// the queue is full.
+    pub max_chunks_to_execute: usize,
+    /// Max number of executed chunks waiting for the ledger update.
+    pub max_chunks_to_update_ledger: usize,
+    /// Max number of chunks waiting to be committed.
+    pub max_chunks_to_commit: usize,
+}
+
+impl Default for ChunkExecutorPipelineConfig {
+    fn default() -> Self {
+        Self {
+            max_chunks_to_execute: 4,
+            max_chunks_to_update_ledger: 4,
+            max_chunks_to_commit: 4,
+        }
+    }
+}
+
+/// A chunk to be fed into the pipeline, either to be executed or to have its transaction
+/// outputs applied.
+pub enum ChunkToEnqueue {
+    Transactions {
+        txn_list_with_proof: TransactionListWithProof,
+        verified_target_li: LedgerInfoWithSignatures,
+        epoch_change_li: Option<LedgerInfoWithSignatures>,
+    },
+    TransactionOutputs {
+        txn_output_list_with_proof: TransactionOutputListWithProof,
+        verified_target_li: LedgerInfoWithSignatures,
+        epoch_change_li: Option<LedgerInfoWithSignatures>,
+    },
+}
+
+/// Drives the stages of a `ChunkExecutorTrait` (execution, ledger update and commit) on
+/// dedicated threads, so that consecutive chunks are processed by different stages concurrently.
+///
+/// Chunks are processed strictly in the order they are enqueued. Once any stage fails, the error
+/// is reported through `next_commit_notification()` and the pipeline stops accepting chunks. The
+/// executor needs to be reset before it can be used again.
+pub struct ChunkExecutorPipeline {
+    join_handles: Vec<JoinHandle<()>>,
+    chunk_sender: Option<SyncSender<ChunkToEnqueue>>,
+    commit_notification_receiver: Receiver<Result<ChunkCommitNotification>>,
+}
+
+impl ChunkExecutorPipeline {
+    pub fn new<C>(executor: Arc<C>, config: &ChunkExecutorPipelineConfig) -> Self
+    where
+        C: ChunkExecutorTrait + 'static,
+    {
+        let executor_1 = executor.clone();
+        let executor_2 = executor.clone();
+        let executor_3 = executor;
+
+        let (chunk_sender, chunk_receiver) =
+            mpsc::sync_channel::<ChunkToEnqueue>(config.max_chunks_to_execute);
+        let (ledger_update_sender, ledger_update_receiver) =
+            mpsc::sync_channel::<Result<()>>(config.max_chunks_to_update_ledger);
+        let (commit_sender, commit_receiver) =
+            mpsc::sync_channel::<Result<()>>(config.max_chunks_to_commit);
+        // Not bounded, the commit notifications are expected to be consumed promptly.
+        let (commit_notification_sender, commit_notification_receiver) = mpsc::channel();
+
+        let mut join_handles = vec![];
+
+        let execution_thread = std::thread::Builder::new()
+            .name("chunk_execution".to_string())
+            .spawn(move || {
+                while let Ok(chunk) = chunk_receiver.recv() {
+                    let res = match chunk {
+                        ChunkToEnqueue::Transactions {
+                            txn_list_with_proof,
+                            verified_target_li,
+                            epoch_change_li,
+                        } => executor_1.enqueue_chunk_by_execution(
+                            txn_list_with_proof,
+                            &verified_target_li,
+                            epoch_change_li.as_ref(),
+                        ),
+                        ChunkToEnqueue::TransactionOutputs {
+                            txn_output_list_with_proof,
+                            verified_target_li,
+                            epoch_change_li,
+                        } => executor_1.enqueue_chunk_by_transaction_outputs(
+                            txn_output_list_with_proof,
+                            &verified_target_li,
+                            epoch_change_li.as_ref(),
+                        ),
+                    };
+                    let failed = res.is_err();
+                    if ledger_update_sender.send(res).is_err() || failed {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed to spawn chunk execution thread.");
+        join_handles.push(execution_thread);
+
+        let ledger_update_thread = std::thread::Builder::new()
+            .name("chunk_ledger_update".to_string())
+            .spawn(move || {
+                while let Ok(res) = ledger_update_receiver.recv() {
+                    let res = res.and_then(|()| executor_2.update_ledger());
+                    let failed = res.is_err();
+                    if commit_sender.send(res).is_err() || failed {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed to spawn chunk ledger update thread.");
+        join_handles.push(ledger_update_thread);
+
+        let commit_thread = std::thread::Builder::new()
+            .name("chunk_committer".to_string())
+            .spawn(move || {
+                while let Ok(res) = commit_receiver.recv() {
+                    let res = res.and_then(|()| executor_3.commit_chunk());
+                    let failed = res.is_err();
+                    if commit_notification_sender.send(res).is_err() || failed {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed to spawn chunk committer thread.");
+        join_handles.push(commit_thread);
+
+        Self {
+            join_handles,
+            chunk_sender: Some(chunk_sender),
+            commit_notification_receiver,
+        }
+    }
+
+    /// Enqueues a chunk for execution, blocking while the pipeline is at capacity.
+    pub fn enqueue_chunk(&self, chunk: ChunkToEnqueue) -> Result<()> {
+        self.chunk_sender
+            .as_ref()
+            .expect("Pipeline already finished.")
+            .send(chunk)
+            .map_err(|_| anyhow!("Chunk executor pipeline stopped."))
+    }
+
+    /// Waits for the next chunk to be committed, in the order the chunks are enqueued.
+    pub fn next_commit_notification(&self) -> Result<ChunkCommitNotification> {
+        self.commit_notification_receiver
+            .recv()
+            .map_err(|_| anyhow!("Chunk executor pipeline stopped."))?
+    }
+
+    /// Stops accepting new chunks and waits for all the enqueued ones to be committed.
+    /// Returns the commit notifications that haven't been consumed yet.
+    pub fn finish(mut self) -> Result<Vec<ChunkCommitNotification>> {
+        self.chunk_sender = None;
+        for handle in self.join_handles.drain(..) {
+            handle
+                .join()
+                .expect("Chunk executor pipeline thread panicked.");
+        }
+        self.commit_notification_receiver.try_iter().collect()
+    }
+}
@@ -32,12 +32,26 @@ use aptos_types::{
     },
     write_set::WriteSet,
 };
+use aptos_vm::AptosVM;
+use once_cell::sync::Lazy;
 use rayon::prelude::*;
 use std::{
     iter::{once, repeat},
     sync::Arc,
 };
 
+/// Pool to calculate the state checkpoints of chunks on, sized by
+/// `AptosVM::get_num_state_checkpoint_threads()`. Blocks keep using the global pool.
+pub static STATE_CHECKPOINT_POOL: Lazy<Arc<rayon::ThreadPool>> = Lazy::new(|| {
+    Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(AptosVM::get_num_state_checkpoint_threads())
+            .thread_name(|index| format!("state-ckpt-{}", index))
+            .build()
+            .unwrap(),
+    )
+});
+
 pub struct ApplyChunkOutput;
 
 impl ApplyChunkOutput {
@@ -80,13 +94,22 @@ impl ApplyChunkOutput {
             let _timer = APTOS_EXECUTOR_OTHER_TIMERS_SECONDS
                 .with_label_values(&["calculate_for_transactions"])
                 .start_timer();
-            InMemoryStateCalculatorV2::calculate_for_transactions(
-                parent_state,
-                state_cache,
-                &to_keep,
-                new_epoch,
-                is_block,
-            )?
+            let calculate = || {
+                InMemoryStateCalculatorV2::calculate_for_transactions(
+                    parent_state,
+                    state_cache,
+                    &to_keep,
+                    new_epoch,
+                    is_block,
+                )
+            };
+            if is_block {
+                calculate()?
+            } else {
+                // The calculation of large chunks is sharded across the pool, see
+                // `InMemoryStateCalculatorV2`.
+                STATE_CHECKPOINT_POOL.install(calculate)?
+            }
         };
 
         let mut state_checkpoint_output = StateCheckpointOutput::new(
@@ -298,6 +298,62 @@ impl BlockTree {
         Ok(rx)
     }
 
+    // Drop the blocks that are neither ancestors nor descendants of `block_id`, i.e. the forks
+    // abandoned once `block_id` is decided to be committed. The root stays the same.
+    //
+    // Like in `prune()`, dropping happens asynchronously and a receiver is returned to wait for
+    // it to complete.
+    pub fn prune_forks(&self, block_id: HashValue) -> Result<Receiver<()>> {
+        let mut path = Vec::new();
+        ensure!(
+            Self::find_path(&self.root_block(), block_id, &mut path),
+            "Block {:x} is not a descendant of the root.",
+            block_id,
+        );
+
+        let mut pruned = Vec::new();
+        for (block, child) in path.iter().zip(path.iter().skip(1)) {
+            let mut children = block.children.lock();
+            let (kept, abandoned): (Vec<_>, Vec<_>) = std::mem::take(&mut *children)
+                .into_iter()
+                .partition(|c| c.id == child.id);
+            *children = kept;
+            pruned.extend(abandoned);
+        }
+        info!(
+            LogSchema::new(LogEntry::SpeculationCache)
+                .block_id(block_id)
+                .num(pruned.len() as u64),
+            "Pruned abandoned forks."
+        );
+
+        let (tx, rx) = channel::<()>();
+        THREAD_MANAGER.get_non_exe_cpu_pool().spawn(move || {
+            let _timer = APTOS_EXECUTOR_OTHER_TIMERS_SECONDS
+                .with_label_values(&["drop_abandoned_forks"])
+                .start_timer();
+            drop(pruned);
+            tx.send(()).ok();
+        });
+        Ok(rx)
+    }
+
+    // Collect the blocks from `block` down to the one with `id` into `path`, returns false if
+    // there's no such descendant.
+    fn find_path(block: &Arc<Block>, id: HashValue, path: &mut Vec<Arc<Block>>) -> bool {
+        path.push(block.clone());
+        if block.id == id {
+            return true;
+        }
+        for child in block.children.lock().iter() {
+            if Self::find_path(child, id, path) {
+                return true;
+            }
+        }
+        path.pop();
+        false
+    }
+
     pub fn add_block(
         &self,
         parent_block_id: HashValue,
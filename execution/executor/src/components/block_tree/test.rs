@@ -114,6 +114,18 @@ fn test_branch() {
     assert_eq!(block_tree.root_block().id, id(9));
 }
 
+#[test]
+fn test_prune_forks() {
+    let block_tree = create_tree();
+    block_tree.prune_forks(id(9)).unwrap().recv().unwrap();
+    let num_blocks = block_tree.size();
+    // The root, 6, 9 and the descendants of 9 are kept.
+    assert_eq!(num_blocks, 5);
+    assert_eq!(block_tree.root_block().id, *PRE_GENESIS_BLOCK_ID);
+    assert!(block_tree.add_block(id(9), id(12), empty_block()).is_ok());
+    assert!(block_tree.prune_forks(id(2)).is_err());
+}
+
 #[test]
 fn test_reconfig_id_update() {
     let block_tree = create_tree();
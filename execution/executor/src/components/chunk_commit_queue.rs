@@ -4,15 +4,32 @@
 
 #![forbid(unsafe_code)]
 
+use crate::{
+    logging::{LogEntry, LogSchema},
+    metrics::{
+        APTOS_EXECUTOR_CHUNK_QUEUE, APTOS_EXECUTOR_CHUNK_STAGE_SECONDS,
+        APTOS_EXECUTOR_CHUNK_TXNS_PER_SECOND,
+    },
+};
 use anyhow::{anyhow, ensure, Result};
-use aptos_executor_types::{state_checkpoint_output::StateCheckpointOutput, ExecutedChunk};
+use aptos_executor_types::{
+    state_checkpoint_output::StateCheckpointOutput, ChunkQueueStatus, ExecutedChunk,
+};
+use aptos_logger::info;
 use aptos_storage_interface::{state_delta::StateDelta, DbReader, ExecutedTrees};
 use aptos_types::{
+    contract_event::ContractEvent,
     epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
     proof::{accumulator::InMemoryTransactionAccumulator, TransactionInfoListWithProof},
+    transaction::Version,
+    write_set::{TransactionWrite, WriteSet},
+};
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
 };
-use std::{collections::VecDeque, sync::Arc};
 
 pub(crate) struct ChunkToUpdateLedger {
     pub result_state: StateDelta,
@@ -27,6 +44,68 @@ pub(crate) struct ChunkToUpdateLedger {
     pub txn_infos_with_proof: TransactionInfoListWithProof,
 }
 
+/// Time a chunk spent in each stage of the chunk executor.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ChunkStageTimes {
+    pub vm_execution: Duration,
+    pub state_checkpoint: Duration,
+    pub ledger_update: Duration,
+    pub commit: Duration,
+}
+
+/// A chunk enqueued and not committed yet.
+struct ChunkInFlight {
+    first_version: Version,
+    num_txns: usize,
+    num_bytes: usize,
+    enqueued_at: Instant,
+    stage_times: ChunkStageTimes,
+}
+
+impl ChunkInFlight {
+    /// Reports the metrics of the chunk, once it's committed.
+    fn report(&self) {
+        let total = self.enqueued_at.elapsed();
+        for (stage, time) in [
+            ("vm_execution", self.stage_times.vm_execution),
+            ("state_checkpoint", self.stage_times.state_checkpoint),
+            ("ledger_update", self.stage_times.ledger_update),
+            ("commit", self.stage_times.commit),
+            ("total", total),
+        ] {
+            APTOS_EXECUTOR_CHUNK_STAGE_SECONDS
+                .with_label_values(&[stage])
+                .observe(time.as_secs_f64());
+        }
+        let txns_per_second = self.num_txns as f64 / total.as_secs_f64().max(f64::EPSILON);
+        APTOS_EXECUTOR_CHUNK_TXNS_PER_SECOND.observe(txns_per_second);
+
+        info!(
+            LogSchema::new(LogEntry::ChunkExecutor)
+                .first_version_in_request(Some(self.first_version))
+                .num_txns_in_request(self.num_txns),
+            last_version = (self.first_version + self.num_txns as Version).saturating_sub(1),
+            txns_per_second = txns_per_second as u64,
+            vm_execution_ms = self.stage_times.vm_execution.as_millis() as u64,
+            state_checkpoint_ms = self.stage_times.state_checkpoint.as_millis() as u64,
+            ledger_update_ms = self.stage_times.ledger_update.as_millis() as u64,
+            commit_ms = self.stage_times.commit.as_millis() as u64,
+            total_ms = total.as_millis() as u64,
+            "Chunk committed.",
+        );
+    }
+}
+
+/// Approximate memory footprint of a transaction output, i.e. the total size of the state items
+/// written and the events emitted.
+pub(crate) fn output_size(write_set: &WriteSet, events: &[ContractEvent]) -> usize {
+    write_set
+        .iter()
+        .map(|(key, op)| key.size() + op.bytes_len())
+        .sum::<usize>()
+        + events.iter().map(ContractEvent::size).sum::<usize>()
+}
+
 /// It's a two stage pipeline:
 ///           (front)     (front)
 ///          /           /
@@ -43,21 +122,70 @@ pub struct ChunkCommitQueue {
     latest_txn_accumulator: Arc<InMemoryTransactionAccumulator>,
     to_commit: VecDeque<Option<ExecutedChunk>>,
     to_update_ledger: VecDeque<Option<ChunkToUpdateLedger>>,
+    /// Chunks not committed yet, in version order.
+    chunks_in_flight: VecDeque<ChunkInFlight>,
+    txns_in_flight: usize,
+    bytes_in_flight: usize,
 }
 
 impl ChunkCommitQueue {
     pub(crate) fn new_from_db(db: &Arc<dyn DbReader>) -> Result<Self> {
+        Ok(Self::new_from_executed_trees(
+            db.get_latest_executed_trees()?,
+        ))
+    }
+
+    /// Starts the queue from the given trees, which are treated as already persisted.
+    pub(crate) fn new_from_executed_trees(executed_trees: ExecutedTrees) -> Self {
         let ExecutedTrees {
             state,
             transaction_accumulator,
-        } = db.get_latest_executed_trees()?;
-        Ok(Self {
+        } = executed_trees;
+        Self {
             persisted_state: state.clone(),
             latest_state: state,
             latest_txn_accumulator: transaction_accumulator,
             to_commit: VecDeque::new(),
             to_update_ledger: VecDeque::new(),
-        })
+            chunks_in_flight: VecDeque::new(),
+            txns_in_flight: 0,
+            bytes_in_flight: 0,
+        }
+    }
+
+    pub(crate) fn status(&self) -> ChunkQueueStatus {
+        ChunkQueueStatus {
+            chunks_to_update_ledger: self.to_update_ledger.len(),
+            chunks_to_commit: self.to_commit.len(),
+            txns_in_flight: self.txns_in_flight,
+            bytes_in_flight: self.bytes_in_flight,
+        }
+    }
+
+    fn update_gauges(&self) {
+        let status = self.status();
+        for (name, value) in [
+            ("chunks_to_update_ledger", status.chunks_to_update_ledger),
+            ("chunks_to_commit", status.chunks_to_commit),
+            ("txns_in_flight", status.txns_in_flight),
+            ("bytes_in_flight", status.bytes_in_flight),
+        ] {
+            APTOS_EXECUTOR_CHUNK_QUEUE
+                .with_label_values(&[name])
+                .set(value as i64);
+        }
+    }
+
+    fn add_in_flight(&mut self, num_txns: usize, num_bytes: usize, stage_times: ChunkStageTimes) {
+        self.chunks_in_flight.push_back(ChunkInFlight {
+            first_version: self.latest_state.next_version(),
+            num_txns,
+            num_bytes,
+            enqueued_at: Instant::now(),
+            stage_times,
+        });
+        self.txns_in_flight += num_txns;
+        self.bytes_in_flight += num_bytes;
     }
 
     pub(crate) fn latest_state(&self) -> StateDelta {
@@ -75,13 +203,25 @@ impl ChunkCommitQueue {
         ))
     }
 
+    /// `num_bytes` is the total size of the outputs of the chunk, see `output_size()`.
     pub(crate) fn enqueue_for_ledger_update(
         &mut self,
         chunk_to_update_ledger: ChunkToUpdateLedger,
+        num_bytes: usize,
+        stage_times: ChunkStageTimes,
     ) -> Result<()> {
+        self.add_in_flight(
+            chunk_to_update_ledger
+                .txn_infos_with_proof
+                .transaction_infos
+                .len(),
+            num_bytes,
+            stage_times,
+        );
         self.latest_state = chunk_to_update_ledger.result_state.clone();
         self.to_update_ledger
             .push_back(Some(chunk_to_update_ledger));
+        self.update_gauges();
         Ok(())
     }
 
@@ -98,7 +238,11 @@ impl ChunkCommitQueue {
         Ok((self.latest_txn_accumulator.clone(), chunk))
     }
 
-    pub(crate) fn save_ledger_update_output(&mut self, chunk: ExecutedChunk) -> Result<()> {
+    pub(crate) fn save_ledger_update_output(
+        &mut self,
+        chunk: ExecutedChunk,
+        ledger_update_time: Duration,
+    ) -> Result<()> {
         ensure!(
             !self.to_update_ledger.is_empty(),
             "to_update_ledger is empty."
@@ -107,9 +251,14 @@ impl ChunkCommitQueue {
             self.to_update_ledger.front().unwrap().is_none(),
             "Head of to_update_ledger has not been processed."
         );
+        // Chunks to commit precede the ones to update ledger in `chunks_in_flight`.
+        if let Some(chunk_in_flight) = self.chunks_in_flight.get_mut(self.to_commit.len()) {
+            chunk_in_flight.stage_times.ledger_update = ledger_update_time;
+        }
         self.latest_txn_accumulator = chunk.ledger_update_output.transaction_accumulator.clone();
         self.to_update_ledger.pop_front();
         self.to_commit.push_back(Some(chunk));
+        self.update_gauges();
 
         Ok(())
     }
@@ -130,13 +279,27 @@ impl ChunkCommitQueue {
             self.to_update_ledger.is_empty(),
             "Mixed usage of different modes."
         );
+        let txns_to_commit = chunk.transactions_to_commit();
+        self.add_in_flight(
+            txns_to_commit.len(),
+            txns_to_commit
+                .iter()
+                .map(|txn| output_size(txn.write_set(), txn.events()))
+                .sum(),
+            ChunkStageTimes::default(),
+        );
         self.latest_state = chunk.result_state.clone();
         self.latest_txn_accumulator = chunk.ledger_update_output.transaction_accumulator.clone();
         self.to_commit.push_back(Some(chunk));
+        self.update_gauges();
         Ok(())
     }
 
-    pub(crate) fn dequeue_committed(&mut self, latest_state: StateDelta) -> Result<()> {
+    pub(crate) fn dequeue_committed(
+        &mut self,
+        latest_state: StateDelta,
+        commit_time: Duration,
+    ) -> Result<()> {
         ensure!(!self.to_commit.is_empty(), "to_commit is empty.");
         ensure!(
             self.to_commit.front().unwrap().is_none(),
@@ -144,6 +307,13 @@ impl ChunkCommitQueue {
         );
         self.to_commit.pop_front();
         self.persisted_state = latest_state;
+        if let Some(mut chunk_in_flight) = self.chunks_in_flight.pop_front() {
+            self.txns_in_flight -= chunk_in_flight.num_txns;
+            self.bytes_in_flight -= chunk_in_flight.num_bytes;
+            chunk_in_flight.stage_times.commit = commit_time;
+            chunk_in_flight.report();
+        }
+        self.update_gauges();
         Ok(())
     }
 }
@@ -4,7 +4,10 @@
 
 #![forbid(unsafe_code)]
 
-use crate::{components::apply_chunk_output::ApplyChunkOutput, metrics};
+use crate::{
+    components::{apply_chunk_output::ApplyChunkOutput, chunk_commit_queue::output_size},
+    metrics,
+};
 use anyhow::Result;
 use aptos_crypto::HashValue;
 use aptos_executor_types::{state_checkpoint_output::StateCheckpointOutput, ExecutedChunk};
@@ -57,6 +60,14 @@ pub struct ChunkOutput {
 }
 
 impl ChunkOutput {
+    /// Approximate size of the outputs in bytes, see `output_size()`.
+    pub fn num_bytes(&self) -> usize {
+        self.transaction_outputs
+            .iter()
+            .map(|output| output_size(output.write_set(), output.events()))
+            .sum()
+    }
+
     pub fn by_transaction_execution<V: VMExecutor>(
         transactions: ExecutableTransactions,
         state_view: CachedStateView,
@@ -106,9 +106,9 @@ impl InMemoryStateCalculatorV2 {
 
         let (updates_before_last_checkpoint, updates_after_last_checkpoint) =
             if let Some(index) = last_checkpoint_index {
-                (
-                    Self::calculate_updates(&state_updates_vec[..=index]),
-                    Self::calculate_updates(&state_updates_vec[index + 1..]),
+                rayon::join(
+                    || Self::calculate_updates(&state_updates_vec[..=index]),
+                    || Self::calculate_updates(&state_updates_vec[index + 1..]),
                 )
             } else {
                 (
@@ -352,13 +352,14 @@ impl InMemoryStateCalculatorV2 {
             .with_label_values(&["make_checkpoint"])
             .start_timer();
 
-        // Update SMT.
-        //
-        // TODO(grao): Consider use the sharded updates directly instead of flatten.
+        // Update SMT, hashing the keys of each shard in parallel.
         let smt_updates: Vec<_> = updates
-            .iter()
-            .flatten()
-            .map(|(key, value)| (key.hash(), value.as_ref()))
+            .par_iter()
+            .flat_map_iter(|shard| {
+                shard
+                    .iter()
+                    .map(|(key, value)| (key.hash(), value.as_ref()))
+            })
             .collect();
         let new_checkpoint =
             latest_checkpoint
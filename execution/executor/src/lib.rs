@@ -15,5 +15,6 @@ mod tests;
 
 pub mod block_executor;
 pub mod chunk_executor;
+pub mod chunk_executor_pipeline;
 pub mod components;
 pub mod db_bootstrapper;
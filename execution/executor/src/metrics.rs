@@ -4,7 +4,8 @@
 
 use aptos_metrics_core::{
     exponential_buckets, register_histogram, register_histogram_vec, register_int_counter,
-    register_int_counter_vec, Histogram, HistogramVec, IntCounter, IntCounterVec,
+    register_int_counter_vec, register_int_gauge_vec, Histogram, HistogramVec, IntCounter,
+    IntCounterVec, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -64,6 +65,51 @@ pub static APTOS_EXECUTOR_OTHER_TIMERS_SECONDS: Lazy<HistogramVec> = Lazy::new(|
     .unwrap()
 });
 
+/// Chunks (and their transactions and bytes) held by the chunk executor and not committed yet.
+pub static APTOS_EXECUTOR_CHUNK_QUEUE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_executor_chunk_queue",
+        "Chunks held by the chunk executor and not committed yet",
+        &["name"]
+    )
+    .unwrap()
+});
+
+/// Time each committed chunk spent in the stages of the chunk executor, and in total since it
+/// was enqueued.
+pub static APTOS_EXECUTOR_CHUNK_STAGE_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        // metric name
+        "aptos_executor_chunk_stage_seconds",
+        // metric description
+        "The time spent in seconds by each committed chunk in the stages of the chunk executor",
+        &["stage"],
+        exponential_buckets(/*start=*/ 1e-3, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
+    )
+    .unwrap()
+});
+
+pub static APTOS_EXECUTOR_CHUNK_TXNS_PER_SECOND: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        // metric name
+        "aptos_executor_chunk_txns_per_second",
+        // metric description
+        "Throughput of each committed chunk, from being enqueued to being committed",
+        exponential_buckets(/*start=*/ 1.0, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
+    )
+    .unwrap()
+});
+
+/// Transactions executed by the chunk executor without verifying their signatures, see
+/// `ChunkExecutorConfig::trust_proof_verified_signatures`.
+pub static APTOS_EXECUTOR_SIG_VERIFICATION_SKIPPED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_executor_sig_verification_skipped_txns",
+        "Number of proof-verified chunk transactions executed without verifying their signatures"
+    )
+    .unwrap()
+});
+
 pub static APTOS_EXECUTOR_ERRORS: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!("aptos_executor_error_total", "Cumulative number of errors").unwrap()
 });
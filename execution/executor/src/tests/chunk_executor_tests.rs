@@ -6,14 +6,15 @@
 
 use crate::{
     block_executor::BlockExecutor,
-    chunk_executor::ChunkExecutor,
+    chunk_executor::{ChunkExecutor, ChunkExecutorConfig},
+    chunk_executor_pipeline::{ChunkExecutorPipeline, ChunkExecutorPipelineConfig, ChunkToEnqueue},
     db_bootstrapper::{generate_waypoint, maybe_bootstrap},
     mock_vm::{encode_mint_transaction, MockVM},
     tests,
 };
 use aptos_crypto::HashValue;
 use aptos_db::AptosDB;
-use aptos_executor_types::{BlockExecutorTrait, ChunkExecutorTrait};
+use aptos_executor_types::{BlockExecutorTrait, ChunkExecutorTrait, ChunkQueueStatus};
 use aptos_storage_interface::DbReaderWriter;
 use aptos_types::{
     ledger_info::LedgerInfoWithSignatures,
@@ -21,6 +22,7 @@ use aptos_types::{
     transaction::TransactionListWithProof,
 };
 use rand::Rng;
+use std::sync::Arc;
 
 pub struct TestExecutor {
     _path: aptos_temppath::TempPath,
@@ -56,7 +58,12 @@ fn execute_and_commit_chunks(
     executor
         .execute_chunk(chunks[0].clone(), &ledger_info, None)
         .unwrap();
+    let status = executor.queue_status();
+    assert_eq!(status.chunks_to_update_ledger, 0);
+    assert_eq!(status.chunks_to_commit, 1);
+    assert_eq!(status.txns_in_flight, chunks[0].transactions.len());
     executor.commit_chunk().unwrap();
+    assert_eq!(executor.queue_status(), ChunkQueueStatus::default());
     let li = db.reader.get_latest_ledger_info().unwrap();
     assert_eq!(li.ledger_info().version(), 0);
     assert_eq!(li.ledger_info().consensus_block_id(), HashValue::zero());
@@ -156,6 +163,66 @@ fn test_executor_execute_or_apply_and_commit_chunk() {
     assert_eq!(li, ledger_info);
 }
 
+#[test]
+#[cfg_attr(feature = "consensus-only-perf-test", ignore)]
+fn test_executor_execute_and_commit_chunks_with_proof_prefetch() {
+    let (chunks, ledger_info) = tests::create_transaction_chunks(vec![1..31, 31..71, 71..91]);
+
+    let TestExecutor { _path, db, .. } = TestExecutor::new();
+    // The proofs of the keys read by each chunk are prefetched for the next one.
+    let executor = ChunkExecutor::<MockVM>::new_with_config(db.clone(), ChunkExecutorConfig {
+        max_proof_prefetch_hints: 1000,
+        ..ChunkExecutorConfig::default()
+    });
+    execute_and_commit_chunks(chunks, ledger_info, &db, &executor);
+}
+
+#[test]
+#[cfg_attr(feature = "consensus-only-perf-test", ignore)]
+fn test_executor_pipeline_execute_and_commit_chunks() {
+    let first_batch_size = 30;
+    let second_batch_size = 40;
+    let third_batch_size = 20;
+
+    let first_batch_start = 1;
+    let second_batch_start = first_batch_start + first_batch_size;
+    let third_batch_start = second_batch_start + second_batch_size;
+
+    let (chunks, ledger_info) = tests::create_transaction_chunks(vec![
+        first_batch_start..first_batch_start + first_batch_size,
+        second_batch_start..second_batch_start + second_batch_size,
+        third_batch_start..third_batch_start + third_batch_size,
+    ]);
+
+    let TestExecutor {
+        _path,
+        db,
+        executor,
+    } = TestExecutor::new();
+    let pipeline =
+        ChunkExecutorPipeline::new(Arc::new(executor), &ChunkExecutorPipelineConfig::default());
+    for chunk in chunks {
+        pipeline
+            .enqueue_chunk(ChunkToEnqueue::Transactions {
+                txn_list_with_proof: chunk,
+                verified_target_li: ledger_info.clone(),
+                epoch_change_li: None,
+            })
+            .unwrap();
+    }
+
+    // Chunks are committed in the order they are enqueued.
+    let notification = pipeline.next_commit_notification().unwrap();
+    assert_eq!(
+        notification.committed_transactions.len(),
+        first_batch_size as usize
+    );
+    let notifications = pipeline.finish().unwrap();
+    assert_eq!(notifications.len(), 2);
+    let li = db.reader.get_latest_ledger_info().unwrap();
+    assert_eq!(li, ledger_info);
+}
+
 #[test]
 fn test_executor_execute_and_commit_chunk_restart() {
     let first_batch_size = 30;
@@ -202,6 +269,85 @@ fn test_executor_execute_and_commit_chunk_restart() {
     }
 }
 
+#[test]
+fn test_executor_commit_notifications() {
+    let (chunks, ledger_info) = tests::create_transaction_chunks(vec![1..31, 31..71, 71..91]);
+
+    let TestExecutor {
+        _path, executor, ..
+    } = TestExecutor::new();
+    let commits = executor.subscribe_commits(3);
+    let lagging_commits = executor.subscribe_commits(1);
+    drop(executor.subscribe_commits(3));
+
+    // The notifications survive a restart from the DB.
+    executor.reset().unwrap();
+    for chunk in &chunks {
+        executor
+            .execute_chunk(chunk.clone(), &ledger_info, None)
+            .unwrap();
+        executor.commit_chunk().unwrap();
+    }
+
+    let committed_transactions: Vec<_> = commits
+        .try_iter()
+        .map(|notification| notification.committed_transactions.len())
+        .collect();
+    assert_eq!(committed_transactions, vec![30, 40, 20]);
+
+    // The lagging subscriber gets what was buffered, then gets disconnected.
+    assert_eq!(
+        lagging_commits.recv().unwrap().committed_transactions.len(),
+        30
+    );
+    assert!(matches!(
+        lagging_commits.try_recv(),
+        Err(std::sync::mpsc::TryRecvError::Disconnected)
+    ));
+}
+
+#[test]
+fn test_executor_execute_and_commit_chunk_from_state_snapshot() {
+    let first_batch_size = 30;
+    let second_batch_size = 40;
+
+    let (chunks, ledger_info) = {
+        let first_batch_start = 1;
+        let second_batch_start = first_batch_start + first_batch_size;
+        tests::create_transaction_chunks(vec![
+            first_batch_start..first_batch_start + first_batch_size,
+            second_batch_start..second_batch_start + second_batch_size,
+        ])
+    };
+
+    let TestExecutor {
+        _path,
+        db,
+        executor,
+    } = TestExecutor::new();
+    executor
+        .execute_chunk(chunks[0].clone(), &ledger_info, None)
+        .unwrap();
+    executor.commit_chunk().unwrap();
+
+    // Start a new executor from the state snapshot after the first chunk.
+    let executor = ChunkExecutor::<MockVM>::new(db.clone());
+    let state = db.reader.get_latest_executed_trees().unwrap().state;
+    assert!(executor
+        .reset_from_state_snapshot(first_batch_size - 1, state.clone())
+        .is_err());
+    executor
+        .reset_from_state_snapshot(first_batch_size, state)
+        .unwrap();
+
+    executor
+        .execute_chunk(chunks[1].clone(), &ledger_info, None)
+        .unwrap();
+    executor.commit_chunk().unwrap();
+    let li = db.reader.get_latest_ledger_info().unwrap();
+    assert_eq!(li, ledger_info);
+}
+
 #[test]
 #[cfg_attr(feature = "consensus-only-perf-test", ignore)]
 fn test_executor_execute_and_commit_chunk_local_result_mismatch() {
@@ -4,6 +4,7 @@
 
 use crate::{
     block_executor::BlockExecutor,
+    chunk_executor::ChunkExecutor,
     components::chunk_output::ChunkOutput,
     db_bootstrapper::{generate_waypoint, maybe_bootstrap},
     mock_vm::{
@@ -15,7 +16,8 @@ use anyhow::Result;
 use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
 use aptos_db::AptosDB;
 use aptos_executor_types::{
-    BlockExecutorTrait, ExecutedChunk, LedgerUpdateOutput, TransactionReplayer, VerifyExecutionMode,
+    BlockExecutorTrait, ExecutedChunk, FeatureOverrides, LedgerUpdateOutput, TransactionReplayer,
+    VerifyExecutionMode,
 };
 use aptos_state_view::StateViewId;
 use aptos_storage_interface::{
@@ -27,7 +29,9 @@ use aptos_types::{
     block_info::BlockInfo,
     bytes::NumToBytes,
     chain_id::ChainId,
+    contract_event::ContractEvent,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    on_chain_config::{FeatureFlag, Features, OnChainConfig},
     proof::definition::LeafCount,
     state_store::{state_key::StateKey, state_value::StateValue},
     test_helpers::transaction_test_helpers::{block, BLOCK_GAS_LIMIT},
@@ -656,6 +660,84 @@ fn test_reconfig_suffix_empty_blocks() {
         .unwrap();
 }
 
+#[test]
+#[cfg_attr(feature = "consensus-only-perf-test", ignore)]
+fn test_replay_with_feature_overrides() {
+    let executor = TestExecutor::new();
+    let block = TestBlock::new(10, 1, gen_block_id(1), BLOCK_GAS_LIMIT);
+    let output = executor
+        .execute_block(
+            (block.id, block.txns.clone()).into(),
+            executor.committed_block_id(),
+            BLOCK_GAS_LIMIT,
+        )
+        .unwrap();
+    let ledger_version = ledger_version_from_block_size(block.txns.len(), BLOCK_GAS_LIMIT) as u64;
+    let ledger_info = gen_ledger_info(ledger_version, output.root_hash(), block.id, 1);
+    executor.commit_blocks(vec![block.id], ledger_info).unwrap();
+
+    let db = executor.db.reader.clone();
+    let txn_list = db
+        .get_transactions(1, ledger_version, ledger_version, false)
+        .unwrap();
+    let write_sets = db
+        .get_write_set_iterator(1, ledger_version)
+        .unwrap()
+        .collect::<Result<_>>()
+        .unwrap();
+    let event_vecs = db
+        .get_events_iterator(1, ledger_version)
+        .unwrap()
+        .collect::<Result<_>>()
+        .unwrap();
+
+    // The overrides start in the middle of the chunk, which has to be split at their versions.
+    let verify_execution_mode = VerifyExecutionMode::verify_all().set_feature_overrides(
+        [
+            (3, FeatureOverrides {
+                enable: vec![FeatureFlag::RESOURCE_GROUPS],
+                disable: vec![],
+            }),
+            (7, FeatureOverrides {
+                enable: vec![],
+                disable: vec![FeatureFlag::RESOURCE_GROUPS],
+            }),
+        ]
+        .into_iter()
+        .collect(),
+    );
+    let replayer = chunk_executor_tests::TestExecutor::new();
+    replayer
+        .executor
+        .replay(
+            txn_list.transactions,
+            txn_list.proof.transaction_infos,
+            write_sets,
+            event_vecs,
+            &verify_execution_mode,
+        )
+        .unwrap();
+    replayer.executor.commit().unwrap();
+    assert!(verify_execution_mode.mismatches().is_empty());
+
+    // The overridden features are only seen by the execution, not committed.
+    let replayed_db = replayer.db.reader.clone();
+    assert_eq!(
+        replayed_db
+            .get_accumulator_root_hash(ledger_version)
+            .unwrap(),
+        db.get_accumulator_root_hash(ledger_version).unwrap()
+    );
+    let features_key = StateKey::access_path(Features::access_path().unwrap());
+    assert_eq!(
+        replayed_db
+            .get_state_value_by_version(&features_key, ledger_version)
+            .unwrap(),
+        db.get_state_value_by_version(&features_key, ledger_version)
+            .unwrap()
+    );
+}
+
 struct TestBlock {
     txns: Vec<SignatureVerifiedTransaction>,
     id: HashValue,
@@ -799,8 +881,38 @@ proptest! {
             let txn_list = db.get_transactions(1 /* start version */, num_txns, ledger_version /* ledger version */, false /* fetch events */).unwrap();
             prop_assert_eq!(&block.inner_txns(), &txn_list.transactions);
             let txn_infos = txn_list.proof.transaction_infos;
-            let write_sets = db.get_write_set_iterator(1, num_txns).unwrap().collect::<Result<_>>().unwrap();
-            let event_vecs = db.get_events_iterator(1, num_txns).unwrap().collect::<Result<_>>().unwrap();
+            let write_sets: Vec<WriteSet> = db.get_write_set_iterator(1, num_txns).unwrap().collect::<Result<_>>().unwrap();
+            let event_vecs: Vec<Vec<ContractEvent>> = db.get_events_iterator(1, num_txns).unwrap().collect::<Result<_>>().unwrap();
+
+            // An interrupted replay resumes from the version following the latest one in the DB.
+            let txns = block.inner_txns();
+            let split = reconfig_txn_index as usize + 1;
+            let interrupted = chunk_executor_tests::TestExecutor::new();
+            prop_assert_eq!(interrupted.executor.resume_replay().unwrap(), 1);
+            interrupted.executor.replay(
+                txns[..split].to_vec(),
+                txn_infos[..split].to_vec(),
+                write_sets[..split].to_vec(),
+                event_vecs[..split].to_vec(),
+                &VerifyExecutionMode::verify_all(),
+            ).unwrap();
+            interrupted.executor.commit().unwrap();
+            let resumed = ChunkExecutor::<MockVM>::new(interrupted.db.clone());
+            let next_version = resumed.resume_replay().unwrap();
+            prop_assert_eq!(next_version, split as Version + 1);
+            let skip = (next_version - 1) as usize;
+            resumed.replay(
+                txns[skip..].to_vec(),
+                txn_infos[skip..].to_vec(),
+                write_sets[skip..].to_vec(),
+                event_vecs[skip..].to_vec(),
+                &VerifyExecutionMode::verify_all(),
+            ).unwrap();
+            resumed.commit().unwrap();
+            prop_assert_eq!(
+                interrupted.db.reader.get_accumulator_root_hash(num_txns).unwrap(),
+                db.get_accumulator_root_hash(num_txns).unwrap()
+            );
 
             // replay txns in one batch across epoch boundary,
             // and the replayer should deal with `Retry`s automatically
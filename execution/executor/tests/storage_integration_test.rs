@@ -3,18 +3,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_cached_packages::aptos_stdlib;
+use aptos_config::config::EventFieldIndexConfig;
 use aptos_crypto::{hash::CryptoHash, PrivateKey};
+use aptos_db::AptosDB;
+use aptos_executor::block_executor::BlockExecutor;
 use aptos_executor_test_helpers::{
-    gen_block_id, gen_ledger_info_with_sigs, get_test_signed_transaction,
+    bootstrap_genesis, gen_block_id, gen_ledger_info_with_sigs, get_test_signed_transaction,
     integration_test_impl::{
         create_db_and_executor, test_execution_with_storage_impl, verify_committed_txn_status,
     },
 };
 use aptos_executor_types::BlockExecutorTrait;
 use aptos_state_view::account_with_state_view::AsAccountWithStateView;
-use aptos_storage_interface::state_view::DbStateViewAtVersion;
+use aptos_storage_interface::{state_view::DbStateViewAtVersion, DbReaderWriter};
 use aptos_types::{
     access_path::AccessPath,
+    account_address::AccountAddress,
     account_config::{aptos_test_root_address, AccountResource, CORE_CODE_ADDRESS},
     account_view::AccountView,
     block_metadata::BlockMetadata,
@@ -26,7 +30,10 @@ use aptos_types::{
     trusted_state::TrustedState,
     validator_signer::ValidatorSigner,
 };
-use move_core_types::move_resource::MoveStructType;
+use aptos_vm::AptosVM;
+use move_core_types::{
+    ident_str, language_storage::TypeTag, move_resource::MoveStructType, parser::parse_type_tag,
+};
 
 #[test]
 fn test_genesis() {
@@ -194,3 +201,187 @@ fn test_reconfiguration() {
 fn test_execution_with_storage() {
     test_execution_with_storage_impl();
 }
+
+#[test]
+#[cfg_attr(feature = "consensus-only-perf-test", ignore)]
+fn test_event_field_index() {
+    let path = aptos_temppath::TempPath::new();
+    path.create_as_dir().unwrap();
+    let (genesis, validators) = aptos_vm_genesis::test_genesis_change_set_and_validators(Some(1));
+    let genesis_key = &aptos_vm_genesis::GENESIS_KEYPAIR.0;
+    let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
+
+    let mut aptos_db = AptosDB::new_for_test_with_indexer(path.path());
+    aptos_db
+        .enable_event_field_index(&[EventFieldIndexConfig {
+            event_type: "0x1::coin::DepositEvent".to_string(),
+            field: "amount".to_string(),
+        }])
+        .unwrap();
+    let (_, db) = DbReaderWriter::wrap(aptos_db);
+    bootstrap_genesis::<AptosVM>(&db, &genesis_txn).unwrap();
+    let executor = BlockExecutor::<AptosVM>::new(db.clone());
+    let signer = ValidatorSigner::new(
+        validators[0].data.owner_address,
+        validators[0].consensus_key.clone(),
+    );
+    let validator_account = signer.author();
+
+    let amounts = [1_234_567u64, 7_654_321, 1_234_567];
+    let txns = amounts
+        .iter()
+        .enumerate()
+        .map(|(seq_num, amount)| {
+            get_test_signed_transaction(
+                aptos_test_root_address(),
+                seq_num as u64,
+                genesis_key.clone(),
+                genesis_key.public_key(),
+                Some(aptos_stdlib::aptos_coin_mint(validator_account, *amount)),
+            )
+        })
+        .collect();
+    let txn_block = into_signature_verified_block(txns);
+    let block_id = gen_block_id(1);
+    let vm_output = executor
+        .execute_block(
+            (block_id, txn_block).into(),
+            executor.committed_block_id(),
+            BLOCK_GAS_LIMIT,
+        )
+        .unwrap();
+    let ledger_info_with_sigs = gen_ledger_info_with_sigs(1, &vm_output, block_id, &[signer]);
+    executor
+        .commit_blocks(vec![block_id], ledger_info_with_sigs)
+        .unwrap();
+    let ledger_version = db.reader.get_latest_version().unwrap();
+
+    let deposit_event = parse_type_tag("0x1::coin::DepositEvent").unwrap();
+    let get_versions = |amount: u64, start_version, limit| {
+        db.reader
+            .get_events_by_field(
+                &deposit_event,
+                ident_str!("amount"),
+                &bcs::to_bytes(&amount).unwrap(),
+                start_version,
+                limit,
+                ledger_version,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|event| {
+                assert_eq!(event.event.type_tag(), &deposit_event);
+                event.transaction_version
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(get_versions(1_234_567, 0, 10), vec![1, 3]);
+    assert_eq!(get_versions(1_234_567, 2, 10), vec![3]);
+    assert_eq!(get_versions(1_234_567, 0, 1), vec![1]);
+    assert_eq!(get_versions(7_654_321, 0, 10), vec![2]);
+    assert!(get_versions(42, 0, 10).is_empty());
+
+    // Fields not configured are not indexed.
+    assert!(db
+        .reader
+        .get_events_by_field(
+            &TypeTag::Address,
+            ident_str!("amount"),
+            &[],
+            0,
+            10,
+            ledger_version,
+        )
+        .is_err());
+}
+
+#[test]
+#[cfg_attr(feature = "consensus-only-perf-test", ignore)]
+fn test_account_transaction_index() {
+    let path = aptos_temppath::TempPath::new();
+    path.create_as_dir().unwrap();
+    let (genesis, validators) = aptos_vm_genesis::test_genesis_change_set_and_validators(Some(1));
+    let genesis_key = &aptos_vm_genesis::GENESIS_KEYPAIR.0;
+    let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
+    let signer = ValidatorSigner::new(
+        validators[0].data.owner_address,
+        validators[0].consensus_key.clone(),
+    );
+    let root = aptos_test_root_address();
+    let account1 = AccountAddress::random();
+    let account2 = AccountAddress::random();
+
+    let execute_block =
+        |db: &DbReaderWriter, round: u64, first_seq_num: u64, to: &[AccountAddress]| {
+            let executor = BlockExecutor::<AptosVM>::new(db.clone());
+            let txns = to
+                .iter()
+                .enumerate()
+                .map(|(i, to)| {
+                    get_test_signed_transaction(
+                        root,
+                        first_seq_num + i as u64,
+                        genesis_key.clone(),
+                        genesis_key.public_key(),
+                        Some(aptos_stdlib::aptos_account_transfer(*to, 1_000)),
+                    )
+                })
+                .collect();
+            let block_id = gen_block_id(round);
+            let vm_output = executor
+                .execute_block(
+                    (block_id, into_signature_verified_block(txns)).into(),
+                    executor.committed_block_id(),
+                    BLOCK_GAS_LIMIT,
+                )
+                .unwrap();
+            let ledger_info_with_sigs =
+                gen_ledger_info_with_sigs(1, &vm_output, block_id, std::slice::from_ref(&signer));
+            executor
+                .commit_blocks(vec![block_id], ledger_info_with_sigs)
+                .unwrap();
+        };
+
+    // Commits versions 1 to 3 before the index is enabled.
+    {
+        let (_, db) = DbReaderWriter::wrap(AptosDB::new_for_test_with_indexer(path.path()));
+        bootstrap_genesis::<AptosVM>(&db, &genesis_txn).unwrap();
+        execute_block(&db, 1, 0, &[account1, account2, account1]);
+        assert!(!db.reader.account_transaction_index_enabled());
+    }
+
+    // The index catches up when enabled, and is maintained on commit from then on.
+    let mut aptos_db = AptosDB::new_for_test_with_indexer(path.path());
+    aptos_db.enable_account_transaction_index().unwrap();
+    let (_, db) = DbReaderWriter::wrap(aptos_db);
+    assert!(db.reader.account_transaction_index_enabled());
+    let ledger_version = db.reader.get_latest_version().unwrap();
+    let get_versions = |db: &DbReaderWriter, address, start_version, limit, ledger_version| {
+        db.reader
+            .get_account_transaction_versions(address, start_version, limit, ledger_version)
+            .unwrap()
+    };
+    assert_eq!(get_versions(&db, account1, 0, 10, ledger_version), vec![
+        1, 3
+    ]);
+    assert_eq!(get_versions(&db, account2, 0, 10, ledger_version), vec![2]);
+    assert_eq!(get_versions(&db, root, 1, 10, ledger_version), vec![
+        1, 2, 3
+    ]);
+    assert_eq!(get_versions(&db, root, 2, 1, ledger_version), vec![2]);
+    assert!(get_versions(&db, AccountAddress::random(), 0, 10, ledger_version).is_empty());
+
+    execute_block(&db, 2, 3, &[account2]);
+    let new_ledger_version = db.reader.get_latest_version().unwrap();
+    let new_version = ledger_version + 1;
+    assert_eq!(
+        get_versions(&db, account2, 0, 10, new_ledger_version),
+        vec![2, new_version]
+    );
+    // Versions after the ledger version are not returned.
+    assert_eq!(get_versions(&db, account2, 0, 10, ledger_version), vec![2]);
+    assert_eq!(
+        get_versions(&db, account1, 0, 10, new_ledger_version),
+        vec![1, 3]
+    );
+}
@@ -5,6 +5,8 @@
 use crate::{
     driver::{DriverConfiguration, StateSyncDriver},
     driver_client::{ClientNotificationListener, DriverClient, DriverNotification},
+    ledger_repairer::LedgerRepairer,
+    logging::{LogEntry, LogSchema},
     metadata_storage::MetadataStorageInterface,
     notification_handlers::{
         CommitNotification, CommitNotificationListener, ConsensusNotificationHandler,
@@ -19,6 +21,7 @@ use aptos_data_streaming_service::streaming_client::StreamingServiceClient;
 use aptos_event_notifications::{EventNotificationSender, EventSubscriptionService};
 use aptos_executor_types::ChunkExecutorTrait;
 use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
 use aptos_mempool_notifications::MempoolNotificationSender;
 use aptos_storage_interface::DbReaderWriter;
 use aptos_storage_service_notifications::StorageServiceNotificationSender;
@@ -164,6 +167,14 @@ impl DriverFactory {
             waypoint,
         );
 
+        // Create the ledger repairer
+        let ledger_repairer = LedgerRepairer::new(
+            aptos_data_client.clone(),
+            node_config.state_sync.state_sync_driver,
+            node_config.state_sync.aptos_data_client.response_timeout_ms,
+            storage.clone(),
+        );
+
         // Create the state sync driver
         let state_sync_driver = StateSyncDriver::new(
             client_notification_listener,
@@ -182,11 +193,19 @@ impl DriverFactory {
             time_service,
         );
 
-        // Spawn the driver
+        // Spawn the driver, once the corrupted ledger data (if any) is repaired
+        let driver = async move {
+            if let Err(error) = ledger_repairer.repair_corrupted_ledger_data().await {
+                error!(LogSchema::new(LogEntry::LedgerRepairer)
+                    .error(&error)
+                    .message("Failed to repair the corrupted ledger data!"));
+            }
+            state_sync_driver.start_driver().await
+        };
         if let Some(driver_runtime) = &driver_runtime {
-            driver_runtime.spawn(state_sync_driver.start_driver());
+            driver_runtime.spawn(driver);
         } else {
-            tokio::spawn(state_sync_driver.start_driver());
+            tokio::spawn(driver);
         }
 
         // Create the driver factory
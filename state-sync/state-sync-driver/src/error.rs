@@ -29,6 +29,8 @@ pub enum Error {
     IntegerOverflow(String),
     #[error("An invalid payload was received: {0}")]
     InvalidPayload(String),
+    #[error("Failed to repair the corrupted ledger data: {0}")]
+    LedgerRepairFailed(String),
     #[error("Failed to notify mempool of the new commit: {0}")]
     NotifyMempoolError(String),
     #[error("Failed to notify the storage service of the new commit: {0}")]
@@ -61,6 +63,7 @@ impl Error {
             Error::FullNodeConsensusNotification(_) => "full_node_consensus_notification",
             Error::IntegerOverflow(_) => "integer_overflow",
             Error::InvalidPayload(_) => "invalid_payload",
+            Error::LedgerRepairFailed(_) => "ledger_repair_failed",
             Error::NotifyMempoolError(_) => "notify_mempool_error",
             Error::NotifyStorageServiceError(_) => "notify_storage_service_error",
             Error::OldSyncRequest(_, _) => "old_sync_request",
@@ -0,0 +1,158 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    error::Error,
+    logging::{LogEntry, LogSchema},
+    utils,
+};
+use aptos_config::config::StateSyncDriverConfig;
+use aptos_data_client::interface::{AptosDataClientInterface, ResponseError};
+use aptos_logger::prelude::*;
+use aptos_storage_interface::DbReaderWriter;
+use aptos_types::transaction::Version;
+use std::{ops::Range, time::Duration};
+
+/// Repairs the ledger data that storage found corrupted on startup (see
+/// `DbReader::get_corrupted_ledger_ranges`), by fetching the affected
+/// versions from peers, instead of requiring the node to resync.
+pub struct LedgerRepairer<DataClient> {
+    aptos_data_client: DataClient,
+    driver_config: StateSyncDriverConfig,
+    response_timeout_ms: u64,
+    storage: DbReaderWriter,
+}
+
+impl<DataClient: AptosDataClientInterface + Send + Clone + 'static> LedgerRepairer<DataClient> {
+    pub fn new(
+        aptos_data_client: DataClient,
+        driver_config: StateSyncDriverConfig,
+        response_timeout_ms: u64,
+        storage: DbReaderWriter,
+    ) -> Self {
+        Self {
+            aptos_data_client,
+            driver_config,
+            response_timeout_ms,
+            storage,
+        }
+    }
+
+    /// Repairs all the corrupted ledger ranges reported by storage
+    pub async fn repair_corrupted_ledger_data(&self) -> Result<(), Error> {
+        let corrupted_ranges =
+            self.storage
+                .reader
+                .get_corrupted_ledger_ranges()
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to get the corrupted ledger ranges from storage: {:?}",
+                        error
+                    ))
+                })?;
+        if corrupted_ranges.is_empty() {
+            return Ok(());
+        }
+
+        // The data is verified against the latest ledger info, which
+        // covers all the corrupted versions.
+        let proof_version = utils::fetch_latest_synced_ledger_info(self.storage.reader.clone())?
+            .ledger_info()
+            .version();
+        info!(LogSchema::new(LogEntry::LedgerRepairer).message(&format!(
+            "Repairing the corrupted ledger ranges: {:?}, proof version: {:?}",
+            corrupted_ranges, proof_version
+        )));
+        for range in corrupted_ranges {
+            self.repair_range(range, proof_version).await?;
+        }
+
+        info!(LogSchema::new(LogEntry::LedgerRepairer)
+            .message("Finished repairing the corrupted ledger data!"));
+        Ok(())
+    }
+
+    /// Fetches and stores the outputs of the given range, which might
+    /// take several requests if peers return partial responses.
+    async fn repair_range(
+        &self,
+        range: Range<Version>,
+        proof_version: Version,
+    ) -> Result<(), Error> {
+        let mut next_version = range.start;
+        let mut num_failed_attempts = 0;
+        while next_version < range.end {
+            match self
+                .fetch_and_repair(next_version, range.end - 1, proof_version)
+                .await
+            {
+                Ok(num_repaired) => {
+                    next_version += num_repaired;
+                },
+                Err(error) => {
+                    num_failed_attempts += 1;
+                    warn!(LogSchema::new(LogEntry::LedgerRepairer)
+                        .error(&error)
+                        .message(&format!(
+                            "Failed to repair the ledger data at version: {:?}, attempt: {:?}",
+                            next_version, num_failed_attempts
+                        )));
+                    if num_failed_attempts >= self.driver_config.max_ledger_repair_attempts {
+                        return Err(Error::LedgerRepairFailed(format!(
+                            "Gave up repairing the range: {:?}, after {:?} failed attempts",
+                            range, num_failed_attempts
+                        )));
+                    }
+                    tokio::time::sleep(Duration::from_millis(
+                        self.driver_config.ledger_repair_retry_interval_ms,
+                    ))
+                    .await;
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches the outputs from `start_version` to `end_version` (inclusive),
+    /// stores the ones returned and returns how many there were
+    async fn fetch_and_repair(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        proof_version: Version,
+    ) -> Result<u64, Error> {
+        let response = self
+            .aptos_data_client
+            .get_transaction_outputs_with_proof(
+                proof_version,
+                start_version,
+                end_version,
+                self.response_timeout_ms,
+            )
+            .await
+            .map_err(|error| Error::AdvertisedDataError(error.to_string()))?;
+        let (context, outputs_with_proof) = response.into_parts();
+
+        let num_outputs = outputs_with_proof.transactions_and_outputs.len() as u64;
+        if num_outputs == 0 {
+            context
+                .response_callback
+                .notify_bad_response(ResponseError::InvalidData);
+            return Err(Error::InvalidPayload(
+                "Received an empty transaction output list!".into(),
+            ));
+        }
+
+        // Storage verifies the proof before overwriting anything
+        if let Err(error) = self.storage.writer.repair_ledger_data(outputs_with_proof) {
+            context
+                .response_callback
+                .notify_bad_response(ResponseError::ProofVerificationError);
+            return Err(Error::VerificationError(format!(
+                "Failed to repair the ledger data: {:?}",
+                error
+            )));
+        }
+        Ok(num_outputs)
+    }
+}
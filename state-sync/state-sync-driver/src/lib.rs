@@ -10,6 +10,7 @@ mod driver;
 mod driver_client;
 pub mod driver_factory;
 mod error;
+mod ledger_repairer;
 mod logging;
 pub mod metadata_storage;
 pub mod metrics;
@@ -14,7 +14,7 @@ use aptos_data_streaming_service::{
     data_stream::{DataStreamId, DataStreamListener},
     streaming_client::{DataStreamingClient, Epoch, NotificationAndFeedback},
 };
-use aptos_executor_types::{ChunkCommitNotification, ChunkExecutorTrait};
+use aptos_executor_types::{ChunkCommitNotification, ChunkExecutorTrait, ChunkQueueStatus};
 use aptos_storage_interface::{
     cached_state_view::ShardedStateCache, state_delta::StateDelta, DbReader, DbReaderWriter,
     DbWriter, ExecutedTrees, Order, StateSnapshotReceiver,
@@ -167,6 +167,8 @@ mock! {
 
         fn commit_chunk(&self) -> Result<ChunkCommitNotification>;
 
+        fn queue_status(&self) -> ChunkQueueStatus;
+
         fn reset(&self) -> Result<()>;
 
         fn finish(&self);
@@ -2,30 +2,45 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    get_first_seq_num_and_limit,
+    db_metadata::{DbMetadataKey, DbMetadataSchema},
+    get_first_seq_num_and_limit, parse_amplification,
     pruner::{LedgerPrunerManager, StateMerklePrunerManager},
+    schema::{storage_footprint::StorageFootprintSchema, transaction::TransactionSchema},
+    state_kv_db::StateKvDb,
     test_helper,
-    test_helper::{arb_blocks_to_commit, put_as_state_root, put_transaction_info},
+    test_helper::{
+        arb_blocks_to_commit, put_as_state_root, put_transaction_info, update_in_memory_state,
+    },
+    utils::get_progress,
     AptosDB, PrunerManager, StaleNodeIndexSchema,
 };
 use aptos_config::config::{
-    EpochSnapshotPrunerConfig, LedgerPrunerConfig, PrunerConfig, RocksdbConfigs,
+    EpochSnapshotPrunerConfig, GroupCommitConfig, LedgerChecksumConfig, LedgerPrunerConfig,
+    PrunerConfig, RocksdbCompression, RocksdbConfig, RocksdbConfigs, StateKvShardPathConfig,
     StateMerklePrunerConfig, BUFFERED_STATE_TARGET_ITEMS,
-    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
 };
 use aptos_crypto::{hash::CryptoHash, HashValue};
-use aptos_storage_interface::{DbReader, ExecutedTrees, Order};
+use aptos_schemadb::SchemaBatch;
+use aptos_storage_interface::{DbReader, DbWriter, ExecutedTrees, Order};
 use aptos_temppath::TempPath;
 use aptos_types::{
     ledger_info::LedgerInfoWithSignatures,
     proof::SparseMerkleLeafNode,
     state_store::{
-        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+        state_key::StateKey,
+        state_storage_usage::StateStorageUsage,
+        state_value::StateValue,
+        storage_footprint::{StorageFootprint, StorageFootprintOwner, StorageFootprintOwnerKind},
     },
     transaction::{ExecutionStatus, TransactionInfo, TransactionToCommit, Version},
 };
 use proptest::prelude::*;
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    sync::Arc,
+};
 use test_helper::{test_save_blocks_impl, test_sync_transactions_impl};
 
 proptest! {
@@ -40,6 +55,289 @@ proptest! {
     fn test_sync_transactions(input in arb_blocks_to_commit(), threshold in 10..20usize) {
         test_sync_transactions_impl(input, threshold);
     }
+
+    #[test]
+    fn test_group_commit(input in arb_blocks_to_commit()) {
+        test_group_commit_impl(input);
+    }
+
+    #[test]
+    fn test_ledger_checksum(input in arb_blocks_to_commit()) {
+        test_ledger_checksum_impl(input);
+    }
+
+    #[test]
+    fn test_ledger_checksum_partial_repair(input in arb_blocks_to_commit()) {
+        test_ledger_checksum_partial_repair_impl(input);
+    }
+
+    #[test]
+    fn test_storage_footprint(input in arb_blocks_to_commit()) {
+        test_storage_footprint_impl(input);
+    }
+}
+
+fn test_group_commit_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
+    let tmp_dir = TempPath::new();
+    let mut db = AptosDB::new_for_test(&tmp_dir);
+    db.enable_group_commit(&GroupCommitConfig {
+        enable: true,
+        max_latency_ms: 3_600_000,
+        max_pending_transactions: usize::MAX,
+    });
+    let persisted_version = || {
+        get_progress(
+            db.ledger_db.metadata_db(),
+            &DbMetadataKey::OverallCommitProgress,
+        )
+        .unwrap()
+    };
+
+    let mut in_memory_state = db
+        .state_store
+        .buffered_state()
+        .lock()
+        .current_state()
+        .clone();
+    let mut next_version: Version = 0;
+    let mut expected_persisted_version = None;
+    for (txns_to_commit, _ledger_info_with_sigs) in &input {
+        update_in_memory_state(&mut in_memory_state, txns_to_commit);
+        db.save_transactions_for_test(
+            txns_to_commit,
+            next_version,                /* first_version */
+            next_version.checked_sub(1), /* base_state_version */
+            None,
+            false, /* sync_commit */
+            in_memory_state.clone(),
+        )
+        .unwrap();
+        next_version += txns_to_commit.len() as u64;
+
+        // Reconfigurations are synced right away.
+        if txns_to_commit.last().unwrap().is_reconfig() {
+            expected_persisted_version = Some(next_version - 1);
+        }
+        assert_eq!(db.get_latest_version().unwrap(), next_version - 1);
+        assert_eq!(persisted_version(), expected_persisted_version);
+    }
+
+    db.flush_pending_commits().unwrap();
+    assert_eq!(persisted_version(), Some(next_version - 1));
+}
+
+/// Commits the blocks with the ledger checksums enabled, returning the versions of each commit.
+fn commit_with_ledger_checksum(
+    db: &mut AptosDB,
+    config: &LedgerChecksumConfig,
+    input: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)],
+) -> Vec<Range<Version>> {
+    db.enable_ledger_checksum(config).unwrap();
+
+    let mut in_memory_state = db
+        .state_store
+        .buffered_state()
+        .lock()
+        .current_state()
+        .clone();
+    let mut next_version: Version = 0;
+    let mut commits = vec![];
+    for (txns_to_commit, ledger_info_with_sigs) in input {
+        update_in_memory_state(&mut in_memory_state, txns_to_commit);
+        db.save_transactions_for_test(
+            txns_to_commit,
+            next_version,                /* first_version */
+            next_version.checked_sub(1), /* base_state_version */
+            Some(ledger_info_with_sigs),
+            false, /* sync_commit */
+            in_memory_state.clone(),
+        )
+        .unwrap();
+        commits.push(next_version..next_version + txns_to_commit.len() as u64);
+        next_version += txns_to_commit.len() as u64;
+    }
+    commits
+}
+
+fn test_ledger_checksum_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
+    let tmp_dir = TempPath::new();
+    let mut db = AptosDB::new_for_test(&tmp_dir);
+    let config = LedgerChecksumConfig {
+        enable: true,
+        verify_window_on_startup: 0,
+    };
+    let last_commit = commit_with_ledger_checksum(&mut db, &config, &input)
+        .pop()
+        .unwrap();
+    let next_version = last_commit.end;
+    assert!(db
+        .verify_ledger_checksums(0, next_version)
+        .unwrap()
+        .is_empty());
+
+    // Lose a transaction of the last commit, which is detected on the next start.
+    let outputs = db
+        .get_transaction_outputs(
+            last_commit.start,
+            last_commit.end - last_commit.start,
+            next_version - 1,
+        )
+        .unwrap();
+    let expected_transaction = db.read_transaction(last_commit.start).unwrap();
+    let batch = SchemaBatch::new();
+    batch
+        .delete::<TransactionSchema>(&last_commit.start)
+        .unwrap();
+    db.ledger_db.transaction_db().write_schemas(batch).unwrap();
+    db.enable_ledger_checksum(&config).unwrap();
+    assert_eq!(db.get_corrupted_ledger_ranges().unwrap(), vec![
+        last_commit.clone()
+    ]);
+
+    // Data not matching the ledger is rejected.
+    let mut bad_outputs = outputs.clone();
+    bad_outputs.first_transaction_output_version = Some(last_commit.start + 1);
+    assert!(db.repair_ledger_data(bad_outputs).is_err());
+    assert_eq!(db.get_corrupted_ledger_ranges().unwrap(), vec![
+        last_commit.clone()
+    ]);
+
+    db.repair_ledger_data(outputs).unwrap();
+    assert!(db.get_corrupted_ledger_ranges().unwrap().is_empty());
+    assert_eq!(
+        db.read_transaction(last_commit.start).unwrap(),
+        expected_transaction
+    );
+}
+
+fn test_ledger_checksum_partial_repair_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmp_dir = TempPath::new();
+    let mut db = AptosDB::new_for_test(&tmp_dir);
+    let config = LedgerChecksumConfig {
+        enable: true,
+        verify_window_on_startup: 0,
+    };
+    let commits = commit_with_ledger_checksum(&mut db, &config, &input);
+    let ledger_version = commits.last().unwrap().end - 1;
+    // Only the commits of several versions can be repaired partially.
+    let commit = match commits.into_iter().rev().find(|c| c.end - c.start >= 2) {
+        Some(commit) => commit,
+        None => return,
+    };
+
+    // A peer might only return part of the versions, so the commit is repaired in two parts.
+    let mid_version = commit.end - 1;
+    let first_part = db
+        .get_transaction_outputs(commit.start, mid_version - commit.start, ledger_version)
+        .unwrap();
+    let second_part = db
+        .get_transaction_outputs(mid_version, commit.end - mid_version, ledger_version)
+        .unwrap();
+
+    // Lose the last transaction of the commit.
+    let batch = SchemaBatch::new();
+    batch.delete::<TransactionSchema>(&mid_version).unwrap();
+    db.ledger_db.transaction_db().write_schemas(batch).unwrap();
+    db.enable_ledger_checksum(&config).unwrap();
+    assert_eq!(db.get_corrupted_ledger_ranges().unwrap(), vec![
+        commit.clone()
+    ]);
+
+    db.repair_ledger_data(first_part).unwrap();
+    assert_eq!(db.get_corrupted_ledger_ranges().unwrap(), vec![
+        commit.clone()
+    ]);
+    // The commit starts before the second part, and is verified again nonetheless.
+    db.repair_ledger_data(second_part).unwrap();
+    assert!(db.get_corrupted_ledger_ranges().unwrap().is_empty());
+}
+
+fn test_storage_footprint_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
+    let tmp_dir = TempPath::new();
+    let mut db = AptosDB::new_for_test(&tmp_dir);
+    db.enable_storage_footprint().unwrap();
+
+    let mut in_memory_state = db
+        .state_store
+        .buffered_state()
+        .lock()
+        .current_state()
+        .clone();
+    let mut next_version: Version = 0;
+    let mut state = HashMap::new();
+    for (txns_to_commit, ledger_info_with_sigs) in &input {
+        update_in_memory_state(&mut in_memory_state, txns_to_commit);
+        db.save_transactions_for_test(
+            txns_to_commit,
+            next_version,                /* first_version */
+            next_version.checked_sub(1), /* base_state_version */
+            Some(ledger_info_with_sigs),
+            true, /* sync_commit */
+            in_memory_state.clone(),
+        )
+        .unwrap();
+        next_version += txns_to_commit.len() as u64;
+        for txn_to_commit in txns_to_commit {
+            for (key, value) in txn_to_commit.state_updates().iter().flatten() {
+                match value {
+                    Some(value) => state.insert(key.clone(), value.clone()),
+                    None => state.remove(key),
+                };
+            }
+        }
+    }
+
+    let mut expected: HashMap<StorageFootprintOwner, StorageFootprint> = HashMap::new();
+    for (key, value) in &state {
+        for owner in StorageFootprintOwner::owners_of(key) {
+            let footprint = expected.entry(owner).or_default();
+            footprint.items += 1;
+            footprint.bytes += (key.size() + value.size()) as u64;
+        }
+    }
+    let mut expected_top: Vec<_> = expected
+        .iter()
+        .filter(|(owner, _)| owner.kind() == StorageFootprintOwnerKind::Account)
+        .map(|(owner, footprint)| (owner.clone(), *footprint))
+        .collect();
+    expected_top.sort_by(|(owner1, footprint1), (owner2, footprint2)| {
+        (footprint2.bytes, footprint2.items, owner2).cmp(&(
+            footprint1.bytes,
+            footprint1.items,
+            owner1,
+        ))
+    });
+    expected_top.truncate(3);
+    let check = |db: &AptosDB| {
+        for (owner, footprint) in &expected {
+            assert_eq!(db.get_storage_footprint(owner).unwrap(), *footprint);
+        }
+        assert_eq!(
+            db.get_top_storage_footprints(StorageFootprintOwnerKind::Account, 3)
+                .unwrap(),
+            expected_top
+        );
+    };
+    check(&db);
+
+    // Counters that are not up to date are rebuilt from the latest snapshot on the next start.
+    let batch = SchemaBatch::new();
+    batch
+        .delete::<DbMetadataSchema>(&DbMetadataKey::StorageFootprintProgress)
+        .unwrap();
+    if let Some(owner) = expected.keys().next() {
+        batch
+            .put::<StorageFootprintSchema>(owner, &StorageFootprint {
+                items: 1000,
+                bytes: 1000,
+            })
+            .unwrap();
+    }
+    db.ledger_db.metadata_db().write_schemas(batch).unwrap();
+    db.enable_storage_footprint().unwrap();
+    check(&db);
 }
 
 #[test]
@@ -75,6 +373,91 @@ fn test_get_first_seq_num_and_limit() {
     );
 }
 
+#[test]
+fn test_parse_amplification() {
+    let cf_stats = "
+** Compaction Stats [default] **
+Level    Files   Size     Score Read(GB)  Rn(GB) Rnp1(GB) Write(GB) Wnew(GB) Moved(GB) W-Amp Rd(MB/s) Wr(MB/s) Comp(sec) CompMergeCPU(sec) Comp(cnt) Avg(sec) KeyIn KeyDrop
+----------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+  L0      2/0    1.52 KB   0.5      0.0     0.0      0.0       0.0      0.0       0.0   1.0      0.0      0.3      0.01              0.00         2    0.003       0      0
+  L1      0/0    0.00 KB   0.0      0.0     0.0      0.0       0.0      0.0       0.0   0.0      0.0      0.0      0.00              0.00         0    0.000       0      0
+  L2      3/0   10.21 MB   0.1      0.0     0.0      0.0       0.0      0.0       0.0   2.5      0.0      0.0      0.00              0.00         0    0.000       0      0
+ Sum      5/0   10.22 MB   0.0      0.0     0.0      0.0       0.0      0.0       0.0   3.5      0.0      0.3      0.01              0.00         2    0.003       0      0
+";
+    assert_eq!(parse_amplification(cf_stats), Some((3.0, 3.5)));
+    assert_eq!(parse_amplification(""), None);
+}
+
+#[test]
+fn test_compression_config() {
+    let open_with_compression = |cf_name: &str| {
+        let tmp_dir = TempPath::new();
+        AptosDB::open(
+            &tmp_dir,
+            false, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfigs {
+                state_merkle_db_config: RocksdbConfig {
+                    compression_per_cf: [(cf_name.to_string(), RocksdbCompression::Zstd)]
+                        .into_iter()
+                        .collect(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            false, /* enable_indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )
+        .is_ok()
+    };
+
+    assert!(open_with_compression("jellyfish_merkle_node"));
+    // Not a column family of the state merkle DB.
+    assert!(!open_with_compression("transaction"));
+}
+
+#[test]
+fn test_state_kv_shard_paths() {
+    let tmp_dir = TempPath::new();
+    let shard_dir = TempPath::new();
+    let open_with_shard_paths = |shard_paths: Vec<StateKvShardPathConfig>| {
+        AptosDB::open(
+            &tmp_dir,
+            false, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfigs {
+                enable_storage_sharding: true,
+                state_kv_shard_paths: shard_paths,
+                ..Default::default()
+            },
+            false, /* enable_indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )
+    };
+    let shard_paths = vec![StateKvShardPathConfig {
+        shards: vec![0, 3],
+        path: shard_dir.path().to_path_buf(),
+    }];
+
+    drop(open_with_shard_paths(Vec::new()).unwrap());
+    // Shards 0 and 3 are still under the DB root.
+    assert!(open_with_shard_paths(shard_paths.clone()).is_err());
+
+    let relocated = StateKvDb::relocate_shards(tmp_dir.path(), &shard_paths, &[]).unwrap();
+    assert_eq!(relocated, vec![0, 3]);
+    drop(open_with_shard_paths(shard_paths.clone()).unwrap());
+    // The shards are not found under the DB root anymore.
+    assert!(open_with_shard_paths(Vec::new()).is_err());
+
+    // Moves them back.
+    let relocated =
+        StateKvDb::relocate_shards(tmp_dir.path(), &[], &[shard_dir.path().to_path_buf()]).unwrap();
+    assert_eq!(relocated, vec![0, 3]);
+    open_with_shard_paths(Vec::new()).unwrap();
+}
+
 #[test]
 fn test_too_many_requested() {
     let tmp_dir = TempPath::new();
@@ -136,6 +519,18 @@ fn test_error_if_version_pruned() {
         "Transaction at version 9 is pruned, min available version is 10."
     );
     assert!(db.error_if_ledger_pruned("Transaction", 10).is_ok());
+
+    db.state_store
+        .state_kv_pruner
+        .save_min_readable_version(7)
+        .unwrap();
+    assert_eq!(db.get_first_state_kv_version().unwrap(), 7);
+    assert_eq!(
+        db.error_if_state_kv_pruned("StateValue", 6)
+            .unwrap_err()
+            .to_string(),
+        "StateValue at version 6 is pruned, min available version is 7."
+    );
 }
 
 #[test]
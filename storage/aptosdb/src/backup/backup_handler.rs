@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    cold_tier::{chain_cold_tier_iter, ColdTier},
     event_store::EventStore,
     ledger_store::LedgerStore,
     metrics::{
@@ -32,6 +33,7 @@ pub struct BackupHandler {
     transaction_store: Arc<TransactionStore>,
     state_store: Arc<StateStore>,
     event_store: Arc<EventStore>,
+    cold_tier: Option<Arc<ColdTier>>,
 }
 
 impl BackupHandler {
@@ -40,12 +42,14 @@ impl BackupHandler {
         transaction_store: Arc<TransactionStore>,
         state_store: Arc<StateStore>,
         event_store: Arc<EventStore>,
+        cold_tier: Option<Arc<ColdTier>>,
     ) -> Self {
         Self {
             ledger_store,
             transaction_store,
             state_store,
             event_store,
+            cold_tier,
         }
     }
 
@@ -57,18 +61,51 @@ impl BackupHandler {
     ) -> Result<
         impl Iterator<Item = Result<(Transaction, TransactionInfo, Vec<ContractEvent>, WriteSet)>> + '_,
     > {
-        let txn_iter = self
-            .transaction_store
-            .get_transaction_iter(start_version, num_transactions)?;
+        // Transactions, events and write sets of old versions might have been moved to the cold
+        // tier, while transaction infos always stay on local disk.
+        let cold_tier = self.cold_tier.as_deref();
+        let txn_iter = chain_cold_tier_iter(
+            cold_tier,
+            start_version,
+            num_transactions as u64,
+            ColdTier::get_transaction,
+            |start_version, limit| {
+                let iter = self
+                    .transaction_store
+                    .get_transaction_iter(start_version, limit)?;
+                Ok(Box::new(iter) as Box<dyn Iterator<Item = Result<Transaction>> + '_>)
+            },
+        )?;
         let mut txn_info_iter = self
             .ledger_store
             .get_transaction_info_iter(start_version, num_transactions)?;
-        let mut event_vec_iter = self
-            .event_store
-            .get_events_by_version_iter(start_version, num_transactions)?;
-        let mut write_set_iter = self
-            .transaction_store
-            .get_write_set_iter(start_version, num_transactions)?;
+        let mut event_vec_iter = chain_cold_tier_iter(
+            cold_tier,
+            start_version,
+            num_transactions as u64,
+            ColdTier::get_events,
+            |start_version, limit| {
+                let iter = self
+                    .event_store
+                    .get_events_by_version_iter(start_version, limit)?;
+                Ok(Box::new(iter)
+                    as Box<
+                        dyn Iterator<Item = Result<Vec<ContractEvent>>> + '_,
+                    >)
+            },
+        )?;
+        let mut write_set_iter = chain_cold_tier_iter(
+            cold_tier,
+            start_version,
+            num_transactions as u64,
+            ColdTier::get_write_set,
+            |start_version, limit| {
+                let iter = self
+                    .transaction_store
+                    .get_write_set_iter(start_version, limit)?;
+                Ok(Box::new(iter) as Box<dyn Iterator<Item = Result<WriteSet>> + '_>)
+            },
+        )?;
 
         let zipped = txn_iter.enumerate().map(move |(idx, txn_res)| {
             let version = start_version + idx as u64; // overflow is impossible since it's check upon txn_iter construction.
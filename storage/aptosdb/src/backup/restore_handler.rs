@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup::restore_utils,
+    backup::{restore_utils, restore_utils::PreparedTransactions},
     db_metadata::{DbMetadataKey, DbMetadataSchema},
     event_store::EventStore,
     ledger_store::LedgerStore,
@@ -140,6 +140,42 @@ impl RestoreHandler {
         )
     }
 
+    /// Prepares the DB writes of the transactions without writing them, see
+    /// `restore_utils::prepare_transactions()`. `frozen_subtrees` are the left siblings in the
+    /// range proof of the transactions, needed if the ones right before them might not be in the
+    /// DB yet.
+    pub fn prepare_transactions(
+        &self,
+        first_version: Version,
+        txns: &[Transaction],
+        txn_infos: &[TransactionInfo],
+        events: &[Vec<ContractEvent>],
+        write_sets: Vec<WriteSet>,
+        frozen_subtrees: Option<&[HashValue]>,
+    ) -> Result<PreparedTransactions> {
+        restore_utils::prepare_transactions(
+            self.ledger_store.clone(),
+            self.transaction_store.clone(),
+            self.event_store.clone(),
+            self.state_store.clone(),
+            first_version,
+            txns,
+            txn_infos,
+            events,
+            write_sets,
+            frozen_subtrees,
+        )
+    }
+
+    /// Writes transactions prepared by `prepare_transactions()`, in the order of versions.
+    pub fn commit_prepared_transactions(&self, prepared: PreparedTransactions) -> Result<()> {
+        restore_utils::commit_prepared_transactions(
+            self.ledger_store.clone(),
+            self.state_store.clone(),
+            prepared,
+        )
+    }
+
     pub fn get_next_expected_transaction_version(&self) -> Result<Version> {
         Ok(self.aptosdb.get_latest_version().map_or(0, |ver| ver + 1))
     }
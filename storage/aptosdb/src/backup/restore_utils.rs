@@ -12,13 +12,18 @@ use crate::{
     schema::{
         db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
         transaction_accumulator::TransactionAccumulatorSchema,
+        transaction_info::TransactionInfoSchema,
     },
     state_store::StateStore,
     transaction_store::TransactionStore,
     ShardedStateKvSchemaBatch,
 };
-use anyhow::{ensure, Result};
-use aptos_crypto::HashValue;
+use anyhow::{ensure, format_err, Result};
+use aptos_accumulator::{HashReader, MerkleAccumulator};
+use aptos_crypto::{
+    hash::{CryptoHash, TransactionAccumulatorHasher},
+    HashValue,
+};
 use aptos_schemadb::{SchemaBatch, DB};
 use aptos_types::{
     contract_event::ContractEvent,
@@ -30,7 +35,13 @@ use aptos_types::{
     transaction::{Transaction, TransactionInfo, TransactionOutput, Version},
     write_set::WriteSet,
 };
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
+
+/// The DB writes of a chunk of transactions, see `prepare_transactions()`.
+pub struct PreparedTransactions {
+    last_version: Version,
+    ledger_db_batch: LedgerDbSchemaBatches,
+}
 
 /// Saves the given ledger infos to the ledger store. If a change set is provided,
 /// a batch of db alterations will be added to the change set without writing them to the db.
@@ -137,6 +148,7 @@ pub(crate) fn save_transactions(
             txn_infos,
             events,
             write_sets.as_ref(),
+            None,
             ledger_db_batch,
             state_kv_batches,
             state_kv_metadata_batch,
@@ -156,6 +168,7 @@ pub(crate) fn save_transactions(
             txn_infos,
             events,
             write_sets.as_ref(),
+            None,
             &mut ledger_db_batch,
             &mut sharded_kv_schema_batch,
             &state_kv_metadata_batch,
@@ -176,6 +189,79 @@ pub(crate) fn save_transactions(
     Ok(())
 }
 
+/// Prepares the DB writes of the given transactions without writing them, so that consecutive
+/// chunks of transactions can be prepared concurrently and then committed in order by
+/// `commit_prepared_transactions()`.
+///
+/// `frozen_subtrees` are the roots of the frozen subtrees of the transaction accumulator before
+/// `first_version`, in the order of the left siblings in a range proof. They are read from the DB
+/// if not provided, in which case the transactions before `first_version` must be in the DB
+/// already.
+pub(crate) fn prepare_transactions(
+    ledger_store: Arc<LedgerStore>,
+    transaction_store: Arc<TransactionStore>,
+    event_store: Arc<EventStore>,
+    state_store: Arc<StateStore>,
+    first_version: Version,
+    txns: &[Transaction],
+    txn_infos: &[TransactionInfo],
+    events: &[Vec<ContractEvent>],
+    write_sets: Vec<WriteSet>,
+    frozen_subtrees: Option<&[HashValue]>,
+) -> Result<PreparedTransactions> {
+    ensure!(!txns.is_empty(), "No transactions to prepare.");
+
+    let frozen_subtrees = frozen_subtrees
+        .map(|hashes| FrozenSubtrees::new(first_version, hashes))
+        .transpose()?;
+    let mut ledger_db_batch = LedgerDbSchemaBatches::new();
+    // Not touched, since KVs are not replayed.
+    let mut sharded_kv_schema_batch = new_sharded_kv_schema_batch();
+    let state_kv_metadata_batch = SchemaBatch::new();
+    save_transactions_impl(
+        ledger_store,
+        transaction_store,
+        event_store,
+        state_store,
+        first_version,
+        txns,
+        txn_infos,
+        events,
+        write_sets.as_ref(),
+        frozen_subtrees.as_ref(),
+        &mut ledger_db_batch,
+        &mut sharded_kv_schema_batch,
+        &state_kv_metadata_batch,
+        false, /* kv_replay */
+    )?;
+
+    Ok(PreparedTransactions {
+        last_version: first_version + txns.len() as u64 - 1,
+        ledger_db_batch,
+    })
+}
+
+/// Writes transactions prepared by `prepare_transactions()` to the db. Chunks must be committed in
+/// the order of versions, since each commit moves the commit progress to its last version.
+pub(crate) fn commit_prepared_transactions(
+    ledger_store: Arc<LedgerStore>,
+    state_store: Arc<StateStore>,
+    prepared: PreparedTransactions,
+) -> Result<()> {
+    let PreparedTransactions {
+        last_version,
+        ledger_db_batch,
+    } = prepared;
+    // commit the state kv before ledger in case of failure happens, same as in `save_transactions()`
+    state_store.state_db.state_kv_db.commit(
+        last_version,
+        SchemaBatch::new(),
+        new_sharded_kv_schema_batch(),
+    )?;
+
+    ledger_store.ledger_db.write_schemas(ledger_db_batch)
+}
+
 /// Saves the given transaction outputs to the db. If a change set is provided, a batch
 /// of db alterations will be added to the change set without writing them to the db.
 pub fn save_transaction_outputs(
@@ -231,6 +317,7 @@ pub(crate) fn save_transactions_impl(
     txn_infos: &[TransactionInfo],
     events: &[Vec<ContractEvent>],
     write_sets: &[WriteSet],
+    frozen_subtrees: Option<&FrozenSubtrees>,
     ledger_db_batch: &mut LedgerDbSchemaBatches,
     state_kv_batches: &mut ShardedStateKvSchemaBatch,
     state_kv_metadata_batch: &SchemaBatch,
@@ -245,12 +332,21 @@ pub(crate) fn save_transactions_impl(
         )?;
     }
 
-    ledger_store.put_transaction_infos(
-        first_version,
-        txn_infos,
-        &ledger_db_batch.transaction_info_db_batches,
-        &ledger_db_batch.transaction_accumulator_db_batches,
-    )?;
+    if let Some(frozen_subtrees) = frozen_subtrees {
+        put_transaction_infos_with_frozen_subtrees(
+            frozen_subtrees,
+            first_version,
+            txn_infos,
+            ledger_db_batch,
+        )?;
+    } else {
+        ledger_store.put_transaction_infos(
+            first_version,
+            txn_infos,
+            &ledger_db_batch.transaction_info_db_batches,
+            &ledger_db_batch.transaction_accumulator_db_batches,
+        )?;
+    }
 
     event_store.put_events_multiple_versions(
         first_version,
@@ -294,6 +390,68 @@ pub(crate) fn save_transactions_impl(
     Ok(())
 }
 
+/// Same as `LedgerStore::put_transaction_infos()`, except that the transaction accumulator is
+/// appended to without reading the DB.
+fn put_transaction_infos_with_frozen_subtrees(
+    frozen_subtrees: &FrozenSubtrees,
+    first_version: Version,
+    txn_infos: &[TransactionInfo],
+    ledger_db_batch: &LedgerDbSchemaBatches,
+) -> Result<()> {
+    for (idx, txn_info) in txn_infos.iter().enumerate() {
+        ledger_db_batch
+            .transaction_info_db_batches
+            .put::<TransactionInfoSchema>(&(first_version + idx as Version), txn_info)?;
+    }
+
+    let txn_hashes: Vec<HashValue> = txn_infos.iter().map(TransactionInfo::hash).collect();
+    let (_root_hash, writes) =
+        MerkleAccumulator::<FrozenSubtrees, TransactionAccumulatorHasher>::append(
+            frozen_subtrees,
+            first_version, /* num_existing_leaves */
+            &txn_hashes,
+        )?;
+    writes.iter().try_for_each(|(pos, hash)| {
+        ledger_db_batch
+            .transaction_accumulator_db_batches
+            .put::<TransactionAccumulatorSchema>(pos, hash)
+    })
+}
+
+/// The roots of the frozen subtrees of the transaction accumulator, which are all the existing
+/// nodes read when appending to the accumulator.
+pub(crate) struct FrozenSubtrees {
+    hashes: HashMap<Position, HashValue>,
+}
+
+impl FrozenSubtrees {
+    fn new(num_leaves: LeafCount, frozen_subtrees: &[HashValue]) -> Result<Self> {
+        let positions: Vec<_> = FrozenSubTreeIterator::new(num_leaves).collect();
+        ensure!(
+            positions.len() == frozen_subtrees.len(),
+            "Number of frozen subtree roots not expected. Expected: {}, actual: {}",
+            positions.len(),
+            frozen_subtrees.len(),
+        );
+
+        Ok(Self {
+            hashes: positions
+                .into_iter()
+                .zip(frozen_subtrees.iter().rev().cloned())
+                .collect(),
+        })
+    }
+}
+
+impl HashReader for FrozenSubtrees {
+    fn get(&self, position: Position) -> Result<HashValue> {
+        self.hashes
+            .get(&position)
+            .cloned()
+            .ok_or_else(|| format_err!("{} is not a frozen subtree root.", position))
+    }
+}
+
 /// A helper function that saves the transaction outputs to the given change set
 pub fn save_transaction_outputs_impl(
     transaction_store: Arc<TransactionStore>,
@@ -4,12 +4,14 @@
 
 use crate::{
     test_helper::{arb_blocks_to_commit, update_in_memory_state},
-    AptosDB,
+    AptosDB, GetRestoreHandler,
 };
 use anyhow::Result;
+use aptos_storage_interface::DbReader;
 use aptos_temppath::TempPath;
 use aptos_types::transaction::Version;
 use proptest::prelude::*;
+use std::sync::Arc;
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
@@ -72,4 +74,81 @@ proptest! {
             .unwrap();
         prop_assert_eq!(&non_existent, &[]);
     }
+
+    #[test]
+    fn test_prepare_transactions_out_of_order(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        let mut in_memory_state = db.state_store.buffered_state().lock().current_state().clone();
+        let mut cur_ver: Version = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+            update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+            db.save_transactions_for_test(
+                txns_to_commit,
+                cur_ver,
+                cur_ver.checked_sub(1),
+                Some(ledger_info_with_sigs),
+                true, // sync commit
+                in_memory_state.clone(),
+            )
+            .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+        let ledger_version = cur_ver - 1;
+
+        let tmp_dir2 = TempPath::new();
+        let restore_handler = Arc::new(AptosDB::new_for_test(&tmp_dir2)).get_restore_handler();
+        // Prepare the chunks backwards, so none of the previous chunks is in the DB when one is
+        // prepared.
+        let mut first_version = cur_ver;
+        let mut prepared = input
+            .iter()
+            .rev()
+            .map(|(txns_to_commit, _ledger_info_with_sigs)| {
+                first_version -= txns_to_commit.len() as u64;
+                let range_proof = db
+                    .get_transaction_accumulator_range_proof(
+                        first_version,
+                        txns_to_commit.len() as u64,
+                        ledger_version,
+                    )
+                    .unwrap();
+                restore_handler
+                    .prepare_transactions(
+                        first_version,
+                        &txns_to_commit
+                            .iter()
+                            .map(|t| t.transaction().clone())
+                            .collect::<Vec<_>>(),
+                        &txns_to_commit
+                            .iter()
+                            .map(|t| t.transaction_info().clone())
+                            .collect::<Vec<_>>(),
+                        &txns_to_commit
+                            .iter()
+                            .map(|t| t.events().to_vec())
+                            .collect::<Vec<_>>(),
+                        txns_to_commit
+                            .iter()
+                            .map(|t| t.write_set().clone())
+                            .collect(),
+                        Some(range_proof.left_siblings()),
+                    )
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        prepared.reverse();
+        for prepared in prepared {
+            restore_handler.commit_prepared_transactions(prepared).unwrap();
+        }
+
+        prop_assert_eq!(
+            restore_handler.aptosdb.get_accumulator_root_hash(ledger_version).unwrap(),
+            db.get_accumulator_root_hash(ledger_version).unwrap()
+        );
+        prop_assert_eq!(
+            restore_handler.get_next_expected_transaction_version().unwrap(),
+            cur_ver
+        );
+    }
 }
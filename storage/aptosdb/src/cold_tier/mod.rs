@@ -0,0 +1,415 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This file defines the cold tier of AptosDB, which holds the transactions, events and write
+//! sets of old versions in an object store (e.g. S3 or GCS) instead of on local disk.
+//!
+//! Versions are moved to the cold tier in segments of a fixed number of versions, each of which is
+//! stored as a single object. Transaction infos, accumulators and indices stay on local disk, so
+//! proofs and lookups by hash, account or event key keep working while the payloads are fetched
+//! from the cold tier.
+
+use crate::{
+    event_store::EventStore,
+    ledger_db::LedgerDb,
+    ledger_store::LedgerStore,
+    metrics::COLD_TIER_ARCHIVED_VERSION,
+    schema::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        event::EventSchema,
+    },
+    transaction_store::TransactionStore,
+};
+use anyhow::{ensure, format_err, Result};
+use aptos_config::config::ColdStorageConfig;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_schemadb::SchemaBatch;
+use aptos_types::{
+    contract_event::ContractEvent,
+    transaction::{Transaction, Version},
+    write_set::WriteSet,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+#[cfg(test)]
+mod test;
+
+/// An object store holding the segments of the cold tier.
+pub trait ColdStorage: Send + Sync {
+    /// Stores `bytes` under `name`, replacing the existing object if any.
+    fn put(&self, name: &str, bytes: &[u8]) -> Result<()>;
+
+    /// Returns the object stored under `name`.
+    fn get(&self, name: &str) -> Result<Vec<u8>>;
+}
+
+pub fn create_cold_storage(config: &ColdStorageConfig) -> Result<Arc<dyn ColdStorage>> {
+    Ok(match config {
+        ColdStorageConfig::LocalDir(dir) => Arc::new(LocalColdStorage::new(dir.clone())?),
+        ColdStorageConfig::Command {
+            put_command,
+            get_command,
+        } => Arc::new(CommandColdStorage {
+            put_command: put_command.clone(),
+            get_command: get_command.clone(),
+        }),
+    })
+}
+
+/// Stores the objects as files in a local directory.
+pub struct LocalColdStorage {
+    dir: PathBuf,
+}
+
+impl LocalColdStorage {
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+}
+
+impl ColdStorage for LocalColdStorage {
+    fn put(&self, name: &str, bytes: &[u8]) -> Result<()> {
+        // Write to a temporary file first, so a crash never leaves a partial object behind.
+        let tmp_path = self.dir.join(format!("{}.tmp", name));
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(tmp_path, self.dir.join(name))?;
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<Vec<u8>> {
+        fs::read(self.dir.join(name))
+            .map_err(|err| format_err!("Failed to read cold tier object {}: {}", name, err))
+    }
+}
+
+/// Talks to an object store by running shell commands, with the object name passed in `$NAME`.
+pub struct CommandColdStorage {
+    /// Reads the object from stdin.
+    put_command: String,
+    /// Writes the object to stdout.
+    get_command: String,
+}
+
+impl ColdStorage for CommandColdStorage {
+    fn put(&self, name: &str, bytes: &[u8]) -> Result<()> {
+        let mut child = Command::new("sh")
+            .args(["-c", &self.put_command])
+            .env("NAME", name)
+            .stdin(Stdio::piped())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped.")
+            .write_all(bytes)?;
+        let status = child.wait()?;
+        ensure!(
+            status.success(),
+            "Command storing cold tier object {} failed: {}",
+            name,
+            status,
+        );
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<Vec<u8>> {
+        let output = Command::new("sh")
+            .args(["-c", &self.get_command])
+            .env("NAME", name)
+            .stderr(Stdio::inherit())
+            .output()?;
+        ensure!(
+            output.status.success(),
+            "Command reading cold tier object {} failed: {}",
+            name,
+            output.status,
+        );
+        Ok(output.stdout)
+    }
+}
+
+/// The transactions, events and write sets of a range of versions, stored as one object.
+#[derive(Debug, Deserialize, Serialize)]
+struct ColdSegment {
+    first_version: Version,
+    transactions: Vec<Transaction>,
+    events: Vec<Vec<ContractEvent>>,
+    write_sets: Vec<WriteSet>,
+}
+
+impl ColdSegment {
+    fn index(&self, version: Version) -> usize {
+        (version - self.first_version) as usize
+    }
+}
+
+fn segment_name(first_version: Version) -> String {
+    format!("ledger_segment_{:020}", first_version)
+}
+
+pub struct ColdTier {
+    storage: Arc<dyn ColdStorage>,
+    segment_size: u64,
+    /// Data of versions before this one has been moved to the cold tier.
+    archived_version: AtomicU64,
+    /// The segment read last, since reads of old data tend to be sequential.
+    cached_segment: Mutex<Option<Arc<ColdSegment>>>,
+}
+
+impl ColdTier {
+    pub fn new(
+        ledger_db: &LedgerDb,
+        storage: Arc<dyn ColdStorage>,
+        segment_size: u64,
+    ) -> Result<Self> {
+        ensure!(segment_size > 0, "Cold tier segment size must be positive.");
+        let archived_version = ledger_db
+            .metadata_db()
+            .get::<DbMetadataSchema>(&DbMetadataKey::ColdTierProgress)?
+            .map_or(0, DbMetadataValue::expect_version);
+        COLD_TIER_ARCHIVED_VERSION.set(archived_version as i64);
+
+        Ok(Self {
+            storage,
+            segment_size,
+            archived_version: AtomicU64::new(archived_version),
+            cached_segment: Mutex::new(None),
+        })
+    }
+
+    pub fn archived_version(&self) -> Version {
+        self.archived_version.load(Ordering::Acquire)
+    }
+
+    pub fn contains(&self, version: Version) -> bool {
+        version < self.archived_version()
+    }
+
+    pub fn get_transaction(&self, version: Version) -> Result<Transaction> {
+        let segment = self.get_segment(version)?;
+        Ok(segment.transactions[segment.index(version)].clone())
+    }
+
+    pub fn get_events(&self, version: Version) -> Result<Vec<ContractEvent>> {
+        let segment = self.get_segment(version)?;
+        Ok(segment.events[segment.index(version)].clone())
+    }
+
+    pub fn get_event(&self, version: Version, index: u64) -> Result<ContractEvent> {
+        let segment = self.get_segment(version)?;
+        segment.events[segment.index(version)]
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| format_err!("Event {} of version {} not found.", index, version))
+    }
+
+    pub fn get_write_set(&self, version: Version) -> Result<WriteSet> {
+        let segment = self.get_segment(version)?;
+        Ok(segment.write_sets[segment.index(version)].clone())
+    }
+
+    fn get_segment(&self, version: Version) -> Result<Arc<ColdSegment>> {
+        ensure!(
+            self.contains(version),
+            "Version {} is not in the cold tier.",
+            version
+        );
+        let first_version = version - version % self.segment_size;
+
+        // Holding the lock while fetching avoids downloading the same segment concurrently.
+        let mut cached_segment = self.cached_segment.lock();
+        if let Some(segment) = cached_segment.as_ref() {
+            if segment.first_version == first_version {
+                return Ok(Arc::clone(segment));
+            }
+        }
+
+        let segment: ColdSegment =
+            bcs::from_bytes(&self.storage.get(&segment_name(first_version))?)?;
+        let num_versions = self.segment_size as usize;
+        ensure!(
+            segment.first_version == first_version
+                && segment.transactions.len() == num_versions
+                && segment.events.len() == num_versions
+                && segment.write_sets.len() == num_versions,
+            "Cold tier segment at version {} is corrupted, or segment_size has changed.",
+            first_version,
+        );
+        let segment = Arc::new(segment);
+        *cached_segment = Some(Arc::clone(&segment));
+        Ok(segment)
+    }
+
+    /// Moves the transactions, events and write sets of all complete segments before
+    /// `target_version` to the cold tier and deletes them from local disk. Returns the new
+    /// archived version.
+    pub fn archive(
+        &self,
+        ledger_db: &LedgerDb,
+        transaction_store: &TransactionStore,
+        event_store: &EventStore,
+        target_version: Version,
+    ) -> Result<Version> {
+        let mut archived_version = self.archived_version();
+        while archived_version + self.segment_size <= target_version {
+            let end_version = archived_version + self.segment_size;
+            let num_versions = self.segment_size as usize;
+
+            let segment = ColdSegment {
+                first_version: archived_version,
+                transactions: transaction_store
+                    .get_transaction_iter(archived_version, num_versions)?
+                    .collect::<Result<_>>()?,
+                events: event_store
+                    .get_events_by_version_iter(archived_version, num_versions)?
+                    .collect::<Result<_>>()?,
+                write_sets: transaction_store
+                    .get_write_set_iter(archived_version, num_versions)?
+                    .collect::<Result<_>>()?,
+            };
+            self.storage
+                .put(&segment_name(archived_version), &bcs::to_bytes(&segment)?)?;
+
+            // Record the progress before deleting anything, so reads of the segment are served by
+            // the cold tier from now on.
+            ledger_db.metadata_db().put::<DbMetadataSchema>(
+                &DbMetadataKey::ColdTierProgress,
+                &DbMetadataValue::Version(end_version),
+            )?;
+            self.archived_version.store(end_version, Ordering::Release);
+            COLD_TIER_ARCHIVED_VERSION.set(end_version as i64);
+
+            let transaction_batch = SchemaBatch::new();
+            transaction_store.prune_transaction_schema(
+                archived_version,
+                end_version,
+                &transaction_batch,
+            )?;
+            ledger_db
+                .transaction_db()
+                .write_schemas(transaction_batch)?;
+
+            let write_set_batch = SchemaBatch::new();
+            transaction_store.prune_write_set(archived_version, end_version, &write_set_batch)?;
+            ledger_db.write_set_db().write_schemas(write_set_batch)?;
+
+            // Only the events themselves are deleted, the event indices stay on local disk.
+            let event_batch = SchemaBatch::new();
+            for (version, events) in (archived_version..end_version).zip(&segment.events) {
+                for idx in 0..events.len() {
+                    event_batch.delete::<EventSchema>(&(version, idx as u64))?;
+                }
+            }
+            ledger_db.event_db().write_schemas(event_batch)?;
+
+            info!(
+                first_version = archived_version,
+                end_version = end_version,
+                "Moved ledger data to the cold tier."
+            );
+            archived_version = end_version;
+        }
+        Ok(archived_version)
+    }
+}
+
+/// Chains the versions of `[start_version, start_version + limit)` that are in the cold tier
+/// with an iterator over the rest, created by `get_hot_iter` from local disk.
+pub(crate) fn chain_cold_tier_iter<'a, T: 'a>(
+    cold_tier: Option<&'a ColdTier>,
+    start_version: Version,
+    limit: u64,
+    read_cold: fn(&ColdTier, Version) -> Result<T>,
+    get_hot_iter: impl FnOnce(Version, usize) -> Result<Box<dyn Iterator<Item = Result<T>> + 'a>>,
+) -> Result<Box<dyn Iterator<Item = Result<T>> + 'a>> {
+    let cold_tier = match cold_tier {
+        Some(cold_tier) if cold_tier.contains(start_version) => cold_tier,
+        _ => return get_hot_iter(start_version, limit as usize),
+    };
+    let end_version = start_version.saturating_add(limit);
+    let hot_start_version = std::cmp::min(cold_tier.archived_version(), end_version);
+    let cold_iter =
+        (start_version..hot_start_version).map(move |version| read_cold(cold_tier, version));
+    let hot_iter = get_hot_iter(
+        hot_start_version,
+        (end_version - hot_start_version) as usize,
+    )?;
+    Ok(Box::new(cold_iter.chain(hot_iter)))
+}
+
+/// Periodically moves the data falling out of the hot window to the cold tier.
+pub(crate) struct ColdTierArchiver {
+    sender: Mutex<mpsc::Sender<()>>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl ColdTierArchiver {
+    pub fn new(
+        cold_tier: Arc<ColdTier>,
+        ledger_db: Arc<LedgerDb>,
+        ledger_store: Arc<LedgerStore>,
+        transaction_store: Arc<TransactionStore>,
+        event_store: Arc<EventStore>,
+        hot_window: u64,
+    ) -> Self {
+        let (send, recv) = mpsc::channel();
+        let join_handle = Some(
+            thread::Builder::new()
+                .name("cold-tier".to_string())
+                .spawn(move || loop {
+                    if let Some(ledger_info) = ledger_store.get_latest_ledger_info_option() {
+                        let target_version =
+                            (ledger_info.ledger_info().version() + 1).saturating_sub(hot_window);
+                        if let Err(e) = cold_tier.archive(
+                            &ledger_db,
+                            &transaction_store,
+                            &event_store,
+                            target_version,
+                        ) {
+                            warn!(
+                                error = ?e,
+                                "Moving ledger data to the cold tier failed."
+                            );
+                        }
+                    }
+
+                    const TIMEOUT_MS: u64 = if cfg!(test) { 10 } else { 60000 };
+                    match recv.recv_timeout(Duration::from_millis(TIMEOUT_MS)) {
+                        Ok(_) => break,
+                        Err(mpsc::RecvTimeoutError::Timeout) => (),
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                })
+                .expect("Failed to spawn the cold tier archiver thread."),
+        );
+        Self {
+            sender: Mutex::new(send),
+            join_handle,
+        }
+    }
+}
+
+impl Drop for ColdTierArchiver {
+    fn drop(&mut self) {
+        self.sender.lock().send(()).unwrap();
+        self.join_handle
+            .take()
+            .expect("Cold tier archiver thread must exist.")
+            .join()
+            .expect("Cold tier archiver thread should join peacefully.");
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::AptosDB;
+use aptos_proptest_helpers::Index;
+use aptos_storage_interface::DbReader;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    proptest_types::{AccountInfoUniverse, SignatureCheckedTransactionGen},
+    transaction::TransactionInfo,
+};
+use proptest::{collection::vec, prelude::*};
+
+const SEGMENT_SIZE: u64 = 4;
+
+fn save(
+    db: &AptosDB,
+    transactions: &[Transaction],
+    txn_infos: &[TransactionInfo],
+    events: &[Vec<ContractEvent>],
+    write_sets: &[WriteSet],
+) {
+    let transaction_batch = SchemaBatch::new();
+    let transaction_info_batch = SchemaBatch::new();
+    let event_batch = SchemaBatch::new();
+    let write_set_batch = SchemaBatch::new();
+    for (version, (((txn, txn_info), events), write_set)) in transactions
+        .iter()
+        .zip(txn_infos)
+        .zip(events)
+        .zip(write_sets)
+        .enumerate()
+    {
+        let version = version as Version;
+        db.transaction_store
+            .put_transaction(version, txn, /*skip_index=*/ false, &transaction_batch)
+            .unwrap();
+        db.ledger_store
+            .put_transaction_info(version, txn_info, &transaction_info_batch)
+            .unwrap();
+        db.event_store
+            .put_events(version, events, /*skip_index=*/ true, &event_batch)
+            .unwrap();
+        db.transaction_store
+            .put_write_set(version, write_set, &write_set_batch)
+            .unwrap();
+    }
+    db.ledger_db
+        .transaction_db()
+        .write_schemas(transaction_batch)
+        .unwrap();
+    db.ledger_db
+        .transaction_info_db()
+        .write_schemas(transaction_info_batch)
+        .unwrap();
+    db.ledger_db.event_db().write_schemas(event_batch).unwrap();
+    db.ledger_db
+        .write_set_db()
+        .write_schemas(write_set_batch)
+        .unwrap();
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(5))]
+
+    #[test]
+    fn test_archive_and_read(
+        mut universe in any_with::<AccountInfoUniverse>(3),
+        gens in vec((any::<Index>(), any::<SignatureCheckedTransactionGen>()), 10),
+        txn_infos in vec(any::<TransactionInfo>(), 10),
+        events in vec(vec(any::<ContractEvent>(), 0..3), 10),
+        write_sets in vec(any::<WriteSet>(), 10),
+    ) {
+        let tmp_dir = TempPath::new();
+        let mut db = AptosDB::new_for_test(&tmp_dir);
+        let transactions = gens
+            .into_iter()
+            .map(|(index, gen)| {
+                Transaction::UserTransaction(gen.materialize(*index, &mut universe).into_inner())
+            })
+            .collect::<Vec<_>>();
+        save(&db, &transactions, &txn_infos, &events, &write_sets);
+
+        let cold_dir = TempPath::new();
+        let storage = Arc::new(LocalColdStorage::new(cold_dir.path().to_path_buf()).unwrap());
+        let cold_tier = Arc::new(ColdTier::new(&db.ledger_db, storage.clone(), SEGMENT_SIZE).unwrap());
+
+        // Only complete segments are moved.
+        let archived_version = cold_tier
+            .archive(&db.ledger_db, &db.transaction_store, &db.event_store, 10)
+            .unwrap();
+        prop_assert_eq!(archived_version, 8);
+        prop_assert!(db.transaction_store.get_transaction(0).is_err());
+        prop_assert!(db.transaction_store.get_write_set(7).is_err());
+        prop_assert!(db.transaction_store.get_transaction(8).is_ok());
+
+        // The progress is persisted.
+        prop_assert_eq!(
+            ColdTier::new(&db.ledger_db, storage, SEGMENT_SIZE).unwrap().archived_version(),
+            8
+        );
+
+        db.cold_tier = Some(cold_tier);
+        for version in 0..10 {
+            let idx = version as usize;
+            prop_assert_eq!(&db.read_transaction(version).unwrap(), &transactions[idx]);
+            prop_assert_eq!(&db.read_events(version).unwrap(), &events[idx]);
+            prop_assert_eq!(&db.read_write_set(version).unwrap(), &write_sets[idx]);
+        }
+
+        let iter_transactions = db
+            .get_transaction_iterator(2, 8)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        prop_assert_eq!(&iter_transactions[..], &transactions[2..10]);
+        let iter_write_sets = db
+            .get_write_set_iterator(6, 4)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        prop_assert_eq!(&iter_write_sets[..], &write_sets[6..10]);
+
+        // Backups read the moved data from the cold tier as well.
+        let backup_handler = db.get_backup_handler();
+        let backup = backup_handler
+            .get_transaction_iter(2, 8)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        prop_assert_eq!(backup.len(), 8);
+        for (idx, (txn, txn_info, events_of_txn, write_set)) in (2..10).zip(backup) {
+            prop_assert_eq!(&txn, &transactions[idx]);
+            prop_assert_eq!(&txn_info, &txn_infos[idx]);
+            prop_assert_eq!(&events_of_txn, &events[idx]);
+            prop_assert_eq!(&write_set, &write_sets[idx]);
+        }
+    }
+}
@@ -0,0 +1,532 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db_debugger::common::DbDir,
+    schema::{
+        event_by_key::EventByKeySchema, event_by_version::EventByVersionSchema,
+        transaction_accumulator::TransactionAccumulatorSchema,
+    },
+    AptosDB,
+};
+use anyhow::{bail, ensure, format_err, Result};
+use aptos_config::config::{
+    RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    contract_event::ContractEvent,
+    proof::{accumulator::InMemoryEventAccumulator, position::Position},
+    transaction::Version,
+};
+use clap::Parser;
+use serde::Serialize;
+use std::{fs, path::PathBuf};
+
+#[derive(Parser)]
+#[clap(
+    about = "Walk the ledger, state KV and state merkle stores and verify the invariants between \
+    them, producing a JSON report."
+)]
+pub struct Cmd {
+    #[clap(flatten)]
+    db_dir: DbDir,
+
+    #[clap(long, help = "Defaults to the oldest version not pruned.")]
+    start_version: Option<Version>,
+
+    #[clap(long, help = "Inclusive. Defaults to the latest version.")]
+    end_version: Option<Version>,
+
+    #[clap(
+        long,
+        default_value_t = 10,
+        help = "Number of state snapshots in the range to recompute the state root for."
+    )]
+    num_state_samples: u64,
+
+    #[clap(
+        long,
+        default_value_t = 100,
+        help = "Number of state values to verify against the root hash, per sampled snapshot."
+    )]
+    num_keys_per_sample: usize,
+
+    #[clap(
+        long,
+        default_value_t = 100,
+        help = "Stop checking once this many errors are found."
+    )]
+    max_errors: usize,
+
+    #[clap(long, help = "Write the report to this file instead of stdout.")]
+    report: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbCheckReport {
+    pub ledger_version: Version,
+    pub start_version: Version,
+    pub end_version: Version,
+    pub num_versions_checked: u64,
+    pub sampled_state_versions: Vec<Version>,
+    pub num_state_values_checked: usize,
+    pub errors: Vec<DbCheckError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbCheckError {
+    pub check: DbCheck,
+    pub version: Option<Version>,
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DbCheck {
+    /// The transaction accumulator matches the transaction infos and the ledger infos.
+    Accumulator,
+    /// The transaction, write set and events match the transaction info.
+    TransactionInfo,
+    /// Every event is reachable through the event indices.
+    EventIndex,
+    /// The state snapshot root matches the transaction info.
+    StateRoot,
+    /// The state values match the state merkle tree.
+    StateValue,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        let db = AptosDB::open(
+            self.db_dir.as_ref(),
+            true, /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfigs {
+                enable_storage_sharding: self.db_dir.sharding_config.enable_storage_sharding,
+                ..Default::default()
+            },
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+
+        let ledger_version = db.ledger_store.get_latest_version()?;
+        let start_version = match self.start_version {
+            Some(version) => version,
+            None => db.get_first_txn_version()?.unwrap_or(0),
+        };
+        let end_version = self.end_version.unwrap_or(ledger_version);
+        ensure!(
+            start_version <= end_version && end_version <= ledger_version,
+            "Invalid range [{}, {}], latest version is {}.",
+            start_version,
+            end_version,
+            ledger_version,
+        );
+
+        let mut checker = Checker {
+            db: &db,
+            report: DbCheckReport {
+                ledger_version,
+                start_version,
+                end_version,
+                num_versions_checked: 0,
+                sampled_state_versions: vec![],
+                num_state_values_checked: 0,
+                errors: vec![],
+            },
+            max_errors: self.max_errors,
+        };
+        checker.check_ledger_infos()?;
+        checker.check_versions()?;
+        checker.check_state_samples(self.num_state_samples, self.num_keys_per_sample)?;
+
+        let report = checker.report;
+        let json = serde_json::to_string_pretty(&report)?;
+        match &self.report {
+            Some(path) => fs::write(path, json)?,
+            None => println!("{}", json),
+        }
+        ensure!(
+            report.errors.is_empty(),
+            "Found {} inconsistencies.",
+            report.errors.len()
+        );
+        Ok(())
+    }
+}
+
+struct Checker<'a> {
+    db: &'a AptosDB,
+    report: DbCheckReport,
+    max_errors: usize,
+}
+
+impl<'a> Checker<'a> {
+    fn record(&mut self, check: DbCheck, version: Option<Version>, result: Result<()>) {
+        if let Err(e) = result {
+            self.report.errors.push(DbCheckError {
+                check,
+                version,
+                message: format!("{:#}", e),
+            });
+        }
+    }
+
+    fn too_many_errors(&self) -> bool {
+        self.report.errors.len() >= self.max_errors
+    }
+
+    /// Checks the accumulator root hash against the latest ledger info of every epoch covering
+    /// the range.
+    fn check_ledger_infos(&mut self) -> Result<()> {
+        let db = self.db;
+        let ledger_store = &db.ledger_store;
+        let start_epoch = ledger_store.get_epoch(self.report.start_version)?;
+        let latest_epoch = ledger_store.get_latest_ledger_info()?.ledger_info().epoch();
+        for epoch in start_epoch..=latest_epoch {
+            if self.too_many_errors() {
+                break;
+            }
+            let li = ledger_store.get_latest_ledger_info_in_epoch(epoch)?;
+            let version = li.ledger_info().version();
+            let result = ledger_store.get_root_hash(version).and_then(|root_hash| {
+                ensure!(
+                    root_hash == li.ledger_info().transaction_accumulator_hash(),
+                    "Accumulator root hash {} doesn't match the ledger info of epoch {}, {}.",
+                    root_hash,
+                    epoch,
+                    li.ledger_info().transaction_accumulator_hash(),
+                );
+                Ok(())
+            });
+            self.record(DbCheck::Accumulator, Some(version), result);
+        }
+        Ok(())
+    }
+
+    fn check_versions(&mut self) -> Result<()> {
+        let (start_version, end_version) = (self.report.start_version, self.report.end_version);
+        eprintln!("Checking versions [{}, {}]...", start_version, end_version);
+        for version in start_version..=end_version {
+            if self.too_many_errors() {
+                break;
+            }
+            self.check_version(version);
+            self.report.num_versions_checked += 1;
+            if (version - start_version + 1) % 100_000 == 0 {
+                eprintln!("    Checked up to version {}.", version);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_version(&mut self, version: Version) {
+        let txn_info = match self.db.ledger_store.get_transaction_info(version) {
+            Ok(txn_info) => txn_info,
+            Err(e) => {
+                self.record(DbCheck::TransactionInfo, Some(version), Err(e));
+                return;
+            },
+        };
+
+        let accumulator_result = self
+            .db
+            .ledger_db
+            .transaction_accumulator_db()
+            .get::<TransactionAccumulatorSchema>(&Position::from_leaf_index(version))
+            .and_then(|leaf_hash| {
+                ensure!(
+                    leaf_hash == Some(txn_info.hash()),
+                    "Accumulator leaf {:?} doesn't match the transaction info hash {}.",
+                    leaf_hash,
+                    txn_info.hash(),
+                );
+                Ok(())
+            });
+        self.record(DbCheck::Accumulator, Some(version), accumulator_result);
+
+        let txn_result = self
+            .db
+            .transaction_store
+            .get_transaction(version)
+            .and_then(|txn| {
+                ensure!(
+                    txn.hash() == txn_info.transaction_hash(),
+                    "Transaction hash {} doesn't match the transaction info, {}.",
+                    txn.hash(),
+                    txn_info.transaction_hash(),
+                );
+                Ok(())
+            });
+        self.record(DbCheck::TransactionInfo, Some(version), txn_result);
+
+        let write_set_result =
+            self.db
+                .transaction_store
+                .get_write_set(version)
+                .and_then(|write_set| {
+                    ensure!(
+                        CryptoHash::hash(&write_set) == txn_info.state_change_hash(),
+                        "Write set hash doesn't match the transaction info, {}.",
+                        txn_info.state_change_hash(),
+                    );
+                    Ok(())
+                });
+        self.record(DbCheck::TransactionInfo, Some(version), write_set_result);
+
+        let events = match self.db.event_store.get_events_by_version(version) {
+            Ok(events) => events,
+            Err(e) => {
+                self.record(DbCheck::TransactionInfo, Some(version), Err(e));
+                return;
+            },
+        };
+        let event_hashes: Vec<HashValue> = events.iter().map(CryptoHash::hash).collect();
+        let event_root_hash = InMemoryEventAccumulator::from_leaves(&event_hashes).root_hash();
+        let events_result = if event_root_hash == txn_info.event_root_hash() {
+            Ok(())
+        } else {
+            Err(format_err!(
+                "Event root hash {} doesn't match the transaction info, {}.",
+                event_root_hash,
+                txn_info.event_root_hash(),
+            ))
+        };
+        self.record(DbCheck::TransactionInfo, Some(version), events_result);
+
+        // The indices are not written when sharding is enabled.
+        if !self.db.skip_index_and_usage {
+            let index_result = self.check_event_indices(version, &events);
+            self.record(DbCheck::EventIndex, Some(version), index_result);
+        }
+    }
+
+    fn check_event_indices(&self, version: Version, events: &[ContractEvent]) -> Result<()> {
+        let event_db = self.db.ledger_db.event_db();
+        for (idx, event) in events.iter().enumerate() {
+            if let ContractEvent::V1(v1) = event {
+                let key = *v1.key();
+                let seq_num = v1.sequence_number();
+                let by_key = event_db.get::<EventByKeySchema>(&(key, seq_num))?;
+                ensure!(
+                    by_key == Some((version, idx as u64)),
+                    "EventByKeySchema entry of event {} ({}, {}) is {:?}.",
+                    idx,
+                    key,
+                    seq_num,
+                    by_key,
+                );
+                let by_version = event_db.get::<EventByVersionSchema>(&(key, version, seq_num))?;
+                ensure!(
+                    by_version == Some(idx as u64),
+                    "EventByVersionSchema entry of event {} ({}, {}) is {:?}.",
+                    idx,
+                    key,
+                    seq_num,
+                    by_version,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Picks state snapshots evenly spread over the range, and verifies the state values sampled
+    /// from each against its root hash, which is in turn checked against the transaction info.
+    fn check_state_samples(&mut self, num_samples: u64, num_keys_per_sample: usize) -> Result<()> {
+        let (start_version, end_version) = (self.report.start_version, self.report.end_version);
+        for i in 1..=num_samples {
+            if self.too_many_errors() {
+                break;
+            }
+            let target = start_version + (end_version - start_version) * i / num_samples;
+            let (version, root_hash) = match self.db.get_state_snapshot_before(target + 1) {
+                Ok(Some(snapshot)) => snapshot,
+                Ok(None) => continue,
+                Err(e) => {
+                    self.record(DbCheck::StateRoot, Some(target), Err(e));
+                    continue;
+                },
+            };
+            if version < start_version || self.report.sampled_state_versions.contains(&version) {
+                continue;
+            }
+            eprintln!("Checking state snapshot at version {}...", version);
+            self.report.sampled_state_versions.push(version);
+
+            let root_result =
+                self.db
+                    .ledger_store
+                    .get_transaction_info(version)
+                    .and_then(|txn_info| {
+                        ensure!(
+                            txn_info.state_checkpoint_hash() == Some(root_hash),
+                            "State root hash {} doesn't match the transaction info, {:?}.",
+                            root_hash,
+                            txn_info.state_checkpoint_hash(),
+                        );
+                        Ok(())
+                    });
+            self.record(DbCheck::StateRoot, Some(version), root_result);
+
+            let result = self.check_state_values(version, root_hash, num_keys_per_sample);
+            self.record(DbCheck::StateValue, Some(version), result);
+        }
+        Ok(())
+    }
+
+    fn check_state_values(
+        &mut self,
+        version: Version,
+        root_hash: HashValue,
+        num_keys: usize,
+    ) -> Result<()> {
+        let num_values = self.db.get_state_leaf_count(version)?;
+        let num_keys = std::cmp::min(num_keys, num_values);
+        for i in 0..num_keys {
+            let idx = i * num_values / num_keys;
+            let chunk = self.db.get_state_value_chunk_with_proof(version, idx, 1)?;
+            ensure!(
+                chunk.root_hash == root_hash,
+                "Root hash of state value {} is {}.",
+                idx,
+                chunk.root_hash,
+            );
+            let (key, value) = match chunk.raw_values.into_iter().next() {
+                Some(kv) => kv,
+                None => bail!("Missing state value {}.", idx),
+            };
+            let (value_by_key, proof) = self
+                .db
+                .get_state_value_with_proof_by_version_ext(&key, version)?;
+            ensure!(
+                value_by_key.as_ref() == Some(&value),
+                "State value {} read by key {:?} doesn't match the one read by index.",
+                idx,
+                key,
+            );
+            proof.verify(root_hash, key.hash(), value_by_key.as_ref())?;
+            self.report.num_state_values_checked += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        schema::{transaction::TransactionSchema, write_set::WriteSetSchema},
+        test_helper::{arb_blocks_to_commit_with_block_nums, update_in_memory_state},
+    };
+    use aptos_temppath::TempPath;
+    use aptos_types::{transaction::Transaction, write_set::WriteSet};
+    use proptest::prelude::*;
+
+    fn open_db(db_dir: &TempPath, enable_storage_sharding: bool) -> AptosDB {
+        if enable_storage_sharding {
+            AptosDB::new_for_test_with_sharding(db_dir, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD)
+        } else {
+            AptosDB::new_for_test(db_dir)
+        }
+    }
+
+    fn run_check(
+        db_dir: &TempPath,
+        enable_storage_sharding: bool,
+    ) -> (Result<()>, Vec<(String, u64)>) {
+        let report = TempPath::new();
+        let mut args = vec![
+            "check",
+            "--db-dir",
+            db_dir.path().to_str().unwrap(),
+            "--report",
+            report.path().to_str().unwrap(),
+        ];
+        if enable_storage_sharding {
+            args.push("--enable-storage-sharding");
+        }
+        let result = Cmd::try_parse_from(args).unwrap().run();
+
+        let report: serde_json::Value =
+            serde_json::from_slice(&fs::read(report.path()).unwrap()).unwrap();
+        assert_eq!(
+            report["num_versions_checked"].as_u64().unwrap(),
+            report["ledger_version"].as_u64().unwrap() + 1
+        );
+        assert!(!report["sampled_state_versions"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+        assert!(report["num_state_values_checked"].as_u64().unwrap() > 0);
+        let errors = report["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| {
+                (
+                    error["check"].as_str().unwrap().to_string(),
+                    error["version"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        (result, errors)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1))]
+
+        #[test]
+        fn test_check(input in arb_blocks_to_commit_with_block_nums(20, 30)) {
+            let (blocks, enable_storage_sharding) = input;
+            let tmp_dir = TempPath::new();
+            let db = open_db(&tmp_dir, enable_storage_sharding);
+            let mut in_memory_state = db.state_store.buffered_state().lock().current_state().clone();
+            let mut version = 0;
+            for (txns_to_commit, ledger_info_with_sigs) in blocks.iter() {
+                update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+                db.save_transactions_for_test(
+                    txns_to_commit,
+                    version,
+                    version.checked_sub(1),
+                    Some(ledger_info_with_sigs),
+                    true,
+                    in_memory_state.clone()
+                )
+                    .unwrap();
+                version += txns_to_commit.len() as u64;
+            }
+            drop(db);
+
+            // A healthy DB passes every check.
+            let (result, errors) = run_check(&tmp_dir, enable_storage_sharding);
+            prop_assert!(result.is_ok());
+            prop_assert!(errors.is_empty());
+
+            // Replace a transaction and a non-empty write set.
+            let db = open_db(&tmp_dir, enable_storage_sharding);
+            db.ledger_db
+                .transaction_db()
+                .put::<TransactionSchema>(&1, &Transaction::StateCheckpoint(HashValue::random()))
+                .unwrap();
+            let write_set_version = (0..version)
+                .find(|v| db.transaction_store.get_write_set(*v).unwrap() != WriteSet::default())
+                .unwrap();
+            db.ledger_db
+                .write_set_db()
+                .put::<WriteSetSchema>(&write_set_version, &WriteSet::default())
+                .unwrap();
+            drop(db);
+
+            let (result, errors) = run_check(&tmp_dir, enable_storage_sharding);
+            prop_assert!(result.is_err());
+            prop_assert_eq!(errors, vec![
+                ("transaction_info".to_string(), 1),
+                ("transaction_info".to_string(), write_set_version),
+            ]);
+        }
+    }
+}
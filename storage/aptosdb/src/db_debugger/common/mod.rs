@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    db_debugger::ShardingConfig, ledger_db::LedgerDb, state_merkle_db::StateMerkleDb,
-    STATE_MERKLE_DB_NAME,
+    db_debugger::ShardingConfig, db_options::OpenMode, ledger_db::LedgerDb,
+    state_merkle_db::StateMerkleDb, STATE_MERKLE_DB_NAME,
 };
 use anyhow::Result;
 use aptos_config::config::RocksdbConfigs;
@@ -31,7 +31,7 @@ impl DbDir {
                 enable_storage_sharding: self.sharding_config.enable_storage_sharding,
                 ..Default::default()
             },
-            false,
+            &OpenMode::ReadWrite,
             0,
         )
     }
@@ -43,7 +43,7 @@ impl DbDir {
                 enable_storage_sharding: self.sharding_config.enable_storage_sharding,
                 ..Default::default()
             },
-            true,
+            &OpenMode::ReadOnly,
         )
     }
 }
@@ -1,10 +1,12 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod check;
 pub mod checkpoint;
 mod common;
 mod examine;
 pub mod ledger;
+pub mod relocate_state_kv_shards;
 pub mod state_tree;
 pub mod truncate;
 
@@ -24,11 +26,15 @@ pub enum Cmd {
 
     Checkpoint(checkpoint::Cmd),
 
+    Check(check::Cmd),
+
     #[clap(subcommand)]
     Ledger(ledger::Cmd),
 
     Truncate(truncate::Cmd),
 
+    RelocateStateKvShards(relocate_state_kv_shards::Cmd),
+
     #[clap(subcommand)]
     Examine(examine::Cmd),
 }
@@ -38,8 +44,10 @@ impl Cmd {
         match self {
             Cmd::StateTree(cmd) => cmd.run(),
             Cmd::Checkpoint(cmd) => cmd.run(),
+            Cmd::Check(cmd) => cmd.run(),
             Cmd::Ledger(cmd) => cmd.run(),
             Cmd::Truncate(cmd) => cmd.run(),
+            Cmd::RelocateStateKvShards(cmd) => cmd.run(),
             Cmd::Examine(cmd) => cmd.run(),
         }
     }
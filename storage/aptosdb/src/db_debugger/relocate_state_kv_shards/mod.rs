@@ -0,0 +1,36 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::state_kv_db::StateKvDb;
+use anyhow::Result;
+use aptos_config::config::StateKvShardPathConfig;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(
+    about = "Move the state kv shards to the paths given by the state_kv_shard_paths config. The \
+    DB must not be open."
+)]
+pub struct Cmd {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+
+    /// Where to place the shards, in the form of `<shards>=<path>`, e.g. `0-7=/mnt/disk1`.
+    /// Shards not listed are moved back under the DB root.
+    #[clap(long)]
+    shard_path: Vec<StateKvShardPathConfig>,
+
+    /// Other paths the shards might currently be placed on, e.g. ones removed from the config.
+    #[clap(long, value_parser)]
+    search_path: Vec<PathBuf>,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        let relocated =
+            StateKvDb::relocate_shards(&self.db_dir, &self.shard_path, &self.search_path)?;
+        println!("Relocated state kv shards: {:?}", relocated);
+        Ok(())
+    }
+}
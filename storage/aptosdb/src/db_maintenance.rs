@@ -0,0 +1,229 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This file defines the on demand maintenance operations of AptosDB, i.e. manual compactions and
+//! immediate pruner passes, which operators trigger e.g. to reclaim the space of pruned data
+//! without waiting for RocksDB to compact it eventually.
+
+use crate::{
+    db_options::{
+        event_db_column_families, ledger_db_column_families, ledger_metadata_db_column_families,
+        state_kv_db_column_families, state_merkle_db_column_families,
+        transaction_accumulator_db_column_families, transaction_db_column_families,
+        transaction_info_db_column_families, write_set_db_column_families,
+    },
+    pruner::PrunerManager,
+    AptosDB, NUM_STATE_SHARDS,
+};
+use anyhow::Result;
+use aptos_logger::prelude::*;
+use aptos_schemadb::{ColumnFamilyName, DB};
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Progress of a pruner, see [`AptosDB::get_pruner_progress`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PrunerProgress {
+    pub name: String,
+    /// Versions before this one have been pruned.
+    pub progress: Version,
+    /// The pruner is pruning the versions before this one.
+    pub target_version: Version,
+}
+
+impl PrunerProgress {
+    fn new(name: &str, (progress, target_version): (Version, Version)) -> Self {
+        Self {
+            name: name.to_string(),
+            progress,
+            target_version,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.progress >= self.target_version
+    }
+}
+
+/// Progress of a manual compaction, see [`AptosDB::compact`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CompactionProgress {
+    pub running: bool,
+    pub num_compacted: usize,
+    pub num_column_families: usize,
+    /// The column family being compacted.
+    pub current_column_family: Option<String>,
+    /// The error the compaction failed with, if any.
+    pub error: Option<String>,
+}
+
+/// Status of the maintenance operations, as reported by the backup service.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MaintenanceStatus {
+    /// The progress of the last manual compaction, `None` if none was triggered since the node
+    /// started.
+    pub compaction: Option<CompactionProgress>,
+    pub pruners: Vec<PrunerProgress>,
+}
+
+impl AptosDB {
+    /// The names of all the column families of all the DB instances, in the form of
+    /// `<db name>/<column family name>`, in the order [`Self::compact`] compacts them.
+    pub fn column_families_to_compact(&self) -> Vec<String> {
+        self.dbs_and_column_families()
+            .into_iter()
+            .map(|(db_name, _db, cf_name)| format!("{}/{}", db_name, cf_name))
+            .collect()
+    }
+
+    /// Manually compacts all the column families of all the DB instances, one by one, which
+    /// reclaims the space of pruned data right away. `on_progress` is called with the number of
+    /// column families compacted so far and the name of the next one.
+    pub fn compact(&self, mut on_progress: impl FnMut(usize, &str)) -> Result<()> {
+        let column_families = self.dbs_and_column_families();
+        let num_column_families = column_families.len();
+        for (num_compacted, (db_name, db, cf_name)) in column_families.into_iter().enumerate() {
+            let name = format!("{}/{}", db_name, cf_name);
+            on_progress(num_compacted, &name);
+            let timer = Instant::now();
+            db.compact_cf(cf_name)?;
+            info!(
+                column_family = name,
+                progress = format!("{}/{}", num_compacted + 1, num_column_families),
+                seconds = timer.elapsed().as_secs_f64(),
+                "Compacted column family."
+            );
+        }
+        Ok(())
+    }
+
+    /// Makes the enabled pruners prune everything out of their prune windows right away, instead
+    /// of waiting for a full pruning batch to accumulate. Use [`Self::get_pruner_progress`] to
+    /// follow the progress.
+    pub fn trigger_pruning(&self) -> Result<()> {
+        if let Ok(latest_version) = self.ledger_store.get_latest_version() {
+            self.ledger_pruner
+                .force_set_pruner_target_db_version(latest_version);
+            self.state_store
+                .state_kv_pruner
+                .force_set_pruner_target_db_version(latest_version);
+        }
+
+        let state_db = &self.state_store.state_db;
+        if let Some(snapshot_version) = state_db
+            .state_merkle_db
+            .get_state_snapshot_version_before(Version::MAX)?
+        {
+            state_db
+                .state_merkle_pruner
+                .force_set_pruner_target_db_version(snapshot_version);
+            state_db
+                .epoch_snapshot_pruner
+                .force_set_pruner_target_db_version(snapshot_version);
+        }
+        info!("Triggered pruning.");
+        Ok(())
+    }
+
+    /// Returns the progress of each enabled pruner.
+    pub fn get_pruner_progress(&self) -> Vec<PrunerProgress> {
+        let state_db = &self.state_store.state_db;
+        [
+            ("ledger_pruner", self.ledger_pruner.get_pruning_progress()),
+            (
+                "state_kv_pruner",
+                self.state_store.state_kv_pruner.get_pruning_progress(),
+            ),
+            (
+                "state_merkle_pruner",
+                state_db.state_merkle_pruner.get_pruning_progress(),
+            ),
+            (
+                "epoch_snapshot_pruner",
+                state_db.epoch_snapshot_pruner.get_pruning_progress(),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, progress)| progress.map(|progress| PrunerProgress::new(name, progress)))
+        .collect()
+    }
+
+    fn dbs_and_column_families<'a>(&'a self) -> Vec<(String, &'a DB, ColumnFamilyName)> {
+        let mut result = Vec::new();
+        let mut add = |db_name: &str, db: &'a DB, cf_names: Vec<ColumnFamilyName>| {
+            result.extend(
+                cf_names
+                    .into_iter()
+                    .map(|cf_name| (db_name.to_string(), db, cf_name)),
+            )
+        };
+
+        let ledger_db = &self.ledger_db;
+        let state_kv_db = &self.state_kv_db;
+        if state_kv_db.enabled_sharding() {
+            add(
+                "ledger_metadata_db",
+                ledger_db.metadata_db(),
+                ledger_metadata_db_column_families(),
+            );
+            add(
+                "write_set_db",
+                ledger_db.write_set_db(),
+                write_set_db_column_families(),
+            );
+            add(
+                "transaction_info_db",
+                ledger_db.transaction_info_db(),
+                transaction_info_db_column_families(),
+            );
+            add(
+                "transaction_db",
+                ledger_db.transaction_db(),
+                transaction_db_column_families(),
+            );
+            add("event_db", ledger_db.event_db(), event_db_column_families());
+            add(
+                "transaction_accumulator_db",
+                ledger_db.transaction_accumulator_db(),
+                transaction_accumulator_db_column_families(),
+            );
+            add(
+                "state_kv_metadata_db",
+                state_kv_db.metadata_db(),
+                state_kv_db_column_families(),
+            );
+            for shard_id in 0..NUM_STATE_SHARDS {
+                add(
+                    &format!("state_kv_db_shard_{}", shard_id),
+                    state_kv_db.db_shard(shard_id as u8),
+                    state_kv_db_column_families(),
+                );
+            }
+        } else {
+            add(
+                "ledger_db",
+                ledger_db.metadata_db(),
+                ledger_db_column_families(),
+            );
+        }
+
+        let state_merkle_db = &self.state_store.state_db.state_merkle_db;
+        add(
+            "state_merkle_metadata_db",
+            state_merkle_db.metadata_db(),
+            state_merkle_db_column_families(),
+        );
+        if state_merkle_db.sharding_enabled() {
+            for shard_id in 0..NUM_STATE_SHARDS {
+                add(
+                    &format!("state_merkle_db_shard_{}", shard_id),
+                    state_merkle_db.db_shard(shard_id as u8),
+                    state_merkle_db_column_families(),
+                );
+            }
+        }
+
+        result
+    }
+}
@@ -2,12 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::schema::*;
-use aptos_config::config::RocksdbConfig;
+use anyhow::{ensure, Result};
+use aptos_config::config::{RocksdbCompactionStyle, RocksdbCompression, RocksdbConfig};
+use aptos_rocksdb_options::gen_rocksdb_options;
 use aptos_schemadb::{
-    BlockBasedOptions, Cache, ColumnFamilyDescriptor, ColumnFamilyName, DBCompressionType, Options,
-    SliceTransform, DEFAULT_COLUMN_FAMILY_NAME,
+    BlockBasedOptions, Cache, ColumnFamilyDescriptor, ColumnFamilyName, DBCompactionStyle,
+    DBCompressionType, Options, SliceTransform, DEFAULT_COLUMN_FAMILY_NAME,
 };
 use aptos_types::transaction::Version;
+use std::path::PathBuf;
 
 const VERSION_SIZE: usize = std::mem::size_of::<Version>();
 
@@ -19,9 +22,11 @@ pub(super) fn ledger_db_column_families() -> Vec<ColumnFamilyName> {
         EVENT_BY_KEY_CF_NAME,
         EVENT_BY_VERSION_CF_NAME,
         EVENT_CF_NAME,
+        LEDGER_CHECKSUM_CF_NAME,
         LEDGER_INFO_CF_NAME,
         STALE_STATE_VALUE_INDEX_CF_NAME,
         STATE_VALUE_CF_NAME,
+        STORAGE_FOOTPRINT_CF_NAME,
         TRANSACTION_CF_NAME,
         TRANSACTION_ACCUMULATOR_CF_NAME,
         TRANSACTION_BY_ACCOUNT_CF_NAME,
@@ -83,7 +88,9 @@ pub(super) fn ledger_metadata_db_column_families() -> Vec<ColumnFamilyName> {
         /* empty cf */ DEFAULT_COLUMN_FAMILY_NAME,
         DB_METADATA_CF_NAME,
         EPOCH_BY_VERSION_CF_NAME,
+        LEDGER_CHECKSUM_CF_NAME,
         LEDGER_INFO_CF_NAME,
+        STORAGE_FOOTPRINT_CF_NAME,
         VERSION_DATA_CF_NAME,
     ]
 }
@@ -124,7 +131,12 @@ where
     let mut cfds = Vec::with_capacity(cfs.len());
     for cf_name in cfs {
         let mut cf_opts = Options::default();
-        cf_opts.set_compression_type(DBCompressionType::Lz4);
+        let compression = rocksdb_config
+            .compression_per_cf
+            .get(cf_name)
+            .unwrap_or(&rocksdb_config.compression);
+        cf_opts.set_compression_type(to_db_compression_type(*compression));
+        cf_opts.set_compaction_style(to_db_compaction_style(rocksdb_config.compaction_style));
         cf_opts.set_block_based_table_factory(&table_options);
         cf_opts_post_processor(cf_name, &mut cf_opts);
         cfds.push(ColumnFamilyDescriptor::new((*cf_name).to_string(), cf_opts));
@@ -132,6 +144,45 @@ where
     cfds
 }
 
+fn to_db_compression_type(compression: RocksdbCompression) -> DBCompressionType {
+    match compression {
+        RocksdbCompression::None => DBCompressionType::None,
+        RocksdbCompression::Snappy => DBCompressionType::Snappy,
+        RocksdbCompression::Zlib => DBCompressionType::Zlib,
+        RocksdbCompression::Bz2 => DBCompressionType::Bz2,
+        RocksdbCompression::Lz4 => DBCompressionType::Lz4,
+        RocksdbCompression::Lz4hc => DBCompressionType::Lz4hc,
+        RocksdbCompression::Zstd => DBCompressionType::Zstd,
+    }
+}
+
+fn to_db_compaction_style(compaction_style: RocksdbCompactionStyle) -> DBCompactionStyle {
+    match compaction_style {
+        RocksdbCompactionStyle::Level => DBCompactionStyle::Level,
+        RocksdbCompactionStyle::Universal => DBCompactionStyle::Universal,
+        RocksdbCompactionStyle::Fifo => DBCompactionStyle::Fifo,
+    }
+}
+
+/// Fails if the compression is configured for column families the DB doesn't have, most likely
+/// because of a typo in the config.
+pub(super) fn ensure_known_cfs_in_config(
+    rocksdb_config: &RocksdbConfig,
+    cfs: &[ColumnFamilyName],
+    db_name: &str,
+) -> Result<()> {
+    for cf_name in rocksdb_config.compression_per_cf.keys() {
+        ensure!(
+            cfs.iter().any(|cf| *cf == cf_name.as_str()),
+            "Unknown column family {} in the compression config of {}, expected one of {:?}.",
+            cf_name,
+            db_name,
+            cfs,
+        );
+    }
+    Ok(())
+}
+
 fn with_state_key_extractor_processor(cf_name: ColumnFamilyName, cf_opts: &mut Options) {
     if cf_name == STATE_VALUE_CF_NAME {
         let prefix_extractor =
@@ -194,3 +245,44 @@ pub(super) fn gen_state_kv_cfds(rocksdb_config: &RocksdbConfig) -> Vec<ColumnFam
 fn state_key_extractor(state_value_raw_key: &[u8]) -> &[u8] {
     &state_value_raw_key[..(state_value_raw_key.len() - VERSION_SIZE)]
 }
+
+/// How the RocksDB instances backing AptosDB are opened.
+#[derive(Clone, Debug)]
+pub(crate) enum OpenMode {
+    ReadWrite,
+    ReadOnly,
+    /// Read only, following the primary instance opened by another process. Each RocksDB instance
+    /// keeps its own info logs under `secondary_root`, in a directory named after it.
+    Secondary {
+        secondary_root: PathBuf,
+    },
+}
+
+impl OpenMode {
+    pub fn from_readonly(readonly: bool) -> Self {
+        if readonly {
+            Self::ReadOnly
+        } else {
+            Self::ReadWrite
+        }
+    }
+
+    pub fn is_secondary(&self) -> bool {
+        matches!(self, Self::Secondary { .. })
+    }
+
+    pub fn secondary_path(&self, name: &str) -> PathBuf {
+        match self {
+            Self::Secondary { secondary_root } => secondary_root.join(name),
+            _ => unreachable!("Only a secondary instance has a secondary path."),
+        }
+    }
+}
+
+pub(super) fn gen_secondary_rocksdb_options(config: &RocksdbConfig) -> Options {
+    let mut db_opts = gen_rocksdb_options(config, /*readonly=*/ true);
+    // Required by RocksDB for secondary instances, which must keep all files open to follow the
+    // primary.
+    db_opts.set_max_open_files(-1);
+    db_opts
+}
@@ -15,8 +15,8 @@ use aptos_crypto::{
 };
 use aptos_infallible::Mutex;
 use aptos_storage_interface::{
-    cached_state_view::ShardedStateCache, state_delta::StateDelta, DbReader, DbWriter,
-    ExecutedTrees, MAX_REQUEST_LIMIT,
+    cached_state_view::ShardedStateCache, state_delta::StateDelta, state_iterator::StateIterator,
+    DbReader, DbWriter, ExecutedTrees, MAX_REQUEST_LIMIT,
 };
 use aptos_types::{
     access_path::AccessPath,
@@ -38,6 +38,7 @@ use aptos_types::{
         state_key_prefix::StateKeyPrefix,
         state_storage_usage::StateStorageUsage,
         state_value::{StateValue, StateValueChunkWithProof},
+        storage_footprint::{StorageFootprint, StorageFootprintOwner, StorageFootprintOwnerKind},
         table, ShardedStateUpdates,
     },
     transaction::{
@@ -48,11 +49,14 @@ use aptos_types::{
 };
 use dashmap::DashMap;
 use itertools::zip_eq;
-use move_core_types::move_resource::MoveStructType;
+use move_core_types::{
+    identifier::IdentStr, language_storage::TypeTag, move_resource::MoveStructType,
+};
 use std::{
     borrow::Borrow,
     collections::HashMap,
     mem::swap,
+    ops::Range,
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc,
@@ -410,6 +414,15 @@ impl DbReader for FakeAptosDB {
         (&self.inner as &dyn DbReader).get_epoch_ending_ledger_infos(start_epoch, end_epoch)
     }
 
+    fn get_epoch_ending_ledger_infos_bcs(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<Box<dyn Iterator<Item = Result<Vec<u8>>> + '_>> {
+        self.inner
+            .get_epoch_ending_ledger_infos_bcs(start_epoch, end_epoch)
+    }
+
     fn get_transactions(
         &self,
         start_version: Version,
@@ -741,6 +754,14 @@ impl DbReader for FakeAptosDB {
             .get_state_proof_by_version_ext(state_key, version)
     }
 
+    fn get_state_value_proofs(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<SparseMerkleProofExt>> {
+        self.inner.get_state_value_proofs(state_keys, version)
+    }
+
     fn get_state_value_with_proof_by_version_ext(
         &self,
         state_key: &StateKey,
@@ -821,6 +842,16 @@ impl DbReader for FakeAptosDB {
             .get_state_value_chunk_with_proof(version, start_idx, chunk_size)
     }
 
+    fn get_state_iterator(
+        &self,
+        version: Version,
+        start_hashed_key: HashValue,
+        key_prefix: Option<StateKeyPrefix>,
+    ) -> Result<StateIterator<'_>> {
+        self.inner
+            .get_state_iterator(version, start_hashed_key, key_prefix)
+    }
+
     fn is_state_merkle_pruner_enabled(&self) -> Result<bool> {
         self.inner.is_state_merkle_pruner_enabled()
     }
@@ -837,6 +868,14 @@ impl DbReader for FakeAptosDB {
         self.inner.get_ledger_prune_window()
     }
 
+    fn get_first_state_kv_version(&self) -> Result<Version> {
+        self.inner.get_first_state_kv_version()
+    }
+
+    fn get_state_kv_prune_window(&self) -> Result<usize> {
+        self.inner.get_state_kv_prune_window()
+    }
+
     fn get_table_info(&self, handle: table::TableHandle) -> Result<table::TableInfo> {
         self.inner.get_table_info(handle)
     }
@@ -845,9 +884,59 @@ impl DbReader for FakeAptosDB {
         self.inner.indexer_enabled()
     }
 
+    fn get_events_by_field(
+        &self,
+        event_type: &TypeTag,
+        field: &IdentStr,
+        value: &[u8],
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        self.inner.get_events_by_field(
+            event_type,
+            field,
+            value,
+            start_version,
+            limit,
+            ledger_version,
+        )
+    }
+
+    fn account_transaction_index_enabled(&self) -> bool {
+        self.inner.account_transaction_index_enabled()
+    }
+
+    fn get_account_transaction_versions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        self.inner
+            .get_account_transaction_versions(address, start_version, limit, ledger_version)
+    }
+
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         self.inner.get_state_storage_usage(version)
     }
+
+    fn get_corrupted_ledger_ranges(&self) -> Result<Vec<Range<Version>>> {
+        self.inner.get_corrupted_ledger_ranges()
+    }
+
+    fn get_storage_footprint(&self, owner: &StorageFootprintOwner) -> Result<StorageFootprint> {
+        self.inner.get_storage_footprint(owner)
+    }
+
+    fn get_top_storage_footprints(
+        &self,
+        kind: StorageFootprintOwnerKind,
+        limit: usize,
+    ) -> Result<Vec<(StorageFootprintOwner, StorageFootprint)>> {
+        self.inner.get_top_storage_footprints(kind, limit)
+    }
 }
 
 /// This is necessary for constructing the [ExecutedTrees] to serve [DbReader::get_latest_executed_trees]
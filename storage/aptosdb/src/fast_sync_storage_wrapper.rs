@@ -43,16 +43,34 @@ impl FastSyncStorageWrapper {
     /// Otherwise, we returns AptosDB directly and the FastSyncStorageWrapper is None
     pub fn initialize_dbs(config: &NodeConfig) -> Result<Either<AptosDB, Self>> {
         let mut db_dir = config.storage.dir();
-        let db_main = AptosDB::open(
+        let mut db_main = AptosDB::open(
             db_dir.as_path(),
             false,
             config.storage.storage_pruner_config,
-            config.storage.rocksdb_configs,
+            config.storage.rocksdb_configs_with_overrides(),
             config.storage.enable_indexer,
             config.storage.buffered_state_target_items,
             config.storage.max_num_nodes_per_lru_cache_shard,
         )
         .map_err(|err| anyhow!("fast sync DB failed to open {}", err))?;
+        if config.storage.cold_tier_config.enable {
+            db_main.enable_cold_tier(&config.storage.cold_tier_config)?;
+        }
+        if !config.storage.event_field_indices.is_empty() {
+            db_main.enable_event_field_index(&config.storage.event_field_indices)?;
+        }
+        if config.storage.enable_account_transaction_index {
+            db_main.enable_account_transaction_index()?;
+        }
+        if config.storage.group_commit_config.enable {
+            db_main.enable_group_commit(&config.storage.group_commit_config);
+        }
+        if config.storage.ledger_checksum_config.enable {
+            db_main.enable_ledger_checksum(&config.storage.ledger_checksum_config)?;
+        }
+        if config.storage.enable_storage_footprint {
+            db_main.enable_storage_footprint()?;
+        }
 
         // when the db is empty and configured to do fast sync, we will create a second DB
         if config
@@ -67,7 +85,7 @@ impl FastSyncStorageWrapper {
                 db_dir.as_path(),
                 false,
                 config.storage.storage_pruner_config,
-                config.storage.rocksdb_configs,
+                config.storage.rocksdb_configs_with_overrides(),
                 config.storage.enable_indexer,
                 config.storage.buffered_state_target_items,
                 config.storage.max_num_nodes_per_lru_cache_shard,
@@ -176,6 +194,18 @@ impl DbWriter for FastSyncStorageWrapper {
             sharded_state_cache,
         )
     }
+
+    fn flush_pending_commits(&self) -> Result<()> {
+        self.get_aptos_db_write_ref().flush_pending_commits()
+    }
+
+    fn repair_ledger_data(
+        &self,
+        output_list_with_proof: TransactionOutputListWithProof,
+    ) -> Result<()> {
+        self.get_aptos_db_write_ref()
+            .repair_ledger_data(output_list_with_proof)
+    }
 }
 
 impl DbReader for FastSyncStorageWrapper {
@@ -0,0 +1,193 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This file defines the group commit of AptosDB, which coalesces small commits, e.g. the chunks
+//! of state sync catching up, so that they are synced to disk together instead of one by one.
+//!
+//! The data of a commit is written to the DBs right away without syncing. The overall commit
+//! progress, which decides what survives a restart, is only persisted once the data of all the
+//! pending commits is synced, so a crash never leaves the progress ahead of the data on disk.
+//! Until then, the progress is kept in memory for the readers.
+
+use crate::{
+    ledger_db::LedgerDb,
+    ledger_store::LedgerStore,
+    metrics::{GROUP_COMMIT_LATENCY_SECONDS, GROUP_COMMIT_SIZE, OTHER_TIMERS_SECONDS},
+    schema::db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+    state_kv_db::StateKvDb,
+};
+use anyhow::Result;
+use aptos_config::config::GroupCommitConfig;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_schemadb::SchemaBatch;
+use aptos_types::transaction::Version;
+use std::{
+    sync::{mpsc, Arc},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+struct PendingCommits {
+    first_commit_time: Instant,
+    num_commits: usize,
+    num_transactions: usize,
+    last_version: Version,
+}
+
+struct GroupCommitState {
+    ledger_db: Arc<LedgerDb>,
+    state_kv_db: Arc<StateKvDb>,
+    ledger_store: Arc<LedgerStore>,
+    max_latency: Duration,
+    max_pending_transactions: usize,
+    pending: Mutex<Option<PendingCommits>>,
+}
+
+impl GroupCommitState {
+    fn commit(&self, last_version: Version, num_transactions: usize, force: bool) -> Result<()> {
+        let mut pending = self.pending.lock();
+        let commits = pending.get_or_insert_with(|| PendingCommits {
+            first_commit_time: Instant::now(),
+            num_commits: 0,
+            num_transactions: 0,
+            last_version,
+        });
+        commits.num_commits += 1;
+        commits.num_transactions += num_transactions;
+        commits.last_version = last_version;
+
+        if force
+            || commits.num_transactions >= self.max_pending_transactions
+            || commits.first_commit_time.elapsed() >= self.max_latency
+        {
+            self.flush(&mut pending)
+        } else {
+            self.ledger_store
+                .set_pending_commit_version(Some(last_version));
+            Ok(())
+        }
+    }
+
+    fn flush_if_due(&self) -> Result<()> {
+        let mut pending = self.pending.lock();
+        let due = pending.as_ref().map_or(false, |commits| {
+            commits.first_commit_time.elapsed() >= self.max_latency
+        });
+        if due {
+            self.flush(&mut pending)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&self, pending: &mut Option<PendingCommits>) -> Result<()> {
+        let commits = match pending.as_ref() {
+            Some(commits) => commits,
+            None => return Ok(()),
+        };
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["group_commit_flush"])
+            .start_timer();
+
+        // The data goes to disk before the progress covering it.
+        self.state_kv_db.flush_wal()?;
+        self.ledger_db.flush_wal()?;
+        let batch = SchemaBatch::new();
+        batch.put::<DbMetadataSchema>(
+            &DbMetadataKey::OverallCommitProgress,
+            &DbMetadataValue::Version(commits.last_version),
+        )?;
+        self.ledger_db.metadata_db().write_schemas(batch)?;
+        self.ledger_store.set_pending_commit_version(None);
+
+        GROUP_COMMIT_LATENCY_SECONDS.observe(commits.first_commit_time.elapsed().as_secs_f64());
+        GROUP_COMMIT_SIZE
+            .with_label_values(&["commits"])
+            .observe(commits.num_commits as f64);
+        GROUP_COMMIT_SIZE
+            .with_label_values(&["transactions"])
+            .observe(commits.num_transactions as f64);
+        *pending = None;
+        Ok(())
+    }
+}
+
+/// Tracks the commits pending to be synced to disk, and syncs them once there are enough of them
+/// or the oldest one has been pending for too long.
+pub(crate) struct GroupCommitter {
+    state: Arc<GroupCommitState>,
+    sender: Mutex<mpsc::Sender<()>>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl GroupCommitter {
+    pub fn new(
+        config: &GroupCommitConfig,
+        ledger_db: Arc<LedgerDb>,
+        state_kv_db: Arc<StateKvDb>,
+        ledger_store: Arc<LedgerStore>,
+    ) -> Self {
+        let max_latency = Duration::from_millis(config.max_latency_ms);
+        let state = Arc::new(GroupCommitState {
+            ledger_db,
+            state_kv_db,
+            ledger_store,
+            max_latency,
+            max_pending_transactions: config.max_pending_transactions,
+            pending: Mutex::new(None),
+        });
+
+        let (send, recv) = mpsc::channel();
+        let state_clone = Arc::clone(&state);
+        let join_handle = Some(
+            thread::Builder::new()
+                .name("group-commit".to_string())
+                .spawn(move || loop {
+                    match recv.recv_timeout(max_latency) {
+                        Ok(_) => break,
+                        Err(mpsc::RecvTimeoutError::Timeout) => (),
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                    if let Err(e) = state_clone.flush_if_due() {
+                        warn!(error = ?e, "Syncing pending commits failed.");
+                    }
+                })
+                .expect("Failed to spawn the group commit thread."),
+        );
+        Self {
+            state,
+            sender: Mutex::new(send),
+            join_handle,
+        }
+    }
+
+    /// Records a commit up to `last_version`, whose data is written to the DBs but not synced.
+    /// Syncs all the pending commits if `force`, or if they reach the limits of the config.
+    pub fn commit(
+        &self,
+        last_version: Version,
+        num_transactions: usize,
+        force: bool,
+    ) -> Result<()> {
+        self.state.commit(last_version, num_transactions, force)
+    }
+
+    /// Syncs all the pending commits.
+    pub fn flush(&self) -> Result<()> {
+        self.state.flush(&mut self.state.pending.lock())
+    }
+}
+
+impl Drop for GroupCommitter {
+    fn drop(&mut self) {
+        self.sender.lock().send(()).unwrap();
+        self.join_handle
+            .take()
+            .expect("Group commit thread must exist.")
+            .join()
+            .expect("Group commit thread should join peacefully.");
+        if let Err(e) = self.flush() {
+            warn!(error = ?e, "Syncing pending commits failed.");
+        }
+    }
+}
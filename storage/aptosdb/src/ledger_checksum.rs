@@ -0,0 +1,315 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This file defines the checksums of the ledger data of AptosDB, which detect the corruption on
+//! disk of the transactions, events, write sets and transaction infos, so that the corrupted
+//! versions can be fetched again from peers instead of resyncing the whole DB.
+//!
+//! A checksum covers the versions of a single commit, and is written to the ledger metadata DB
+//! together with the commit. The latest commits are verified when the checksums are enabled at
+//! startup, and the corrupted ranges are kept in memory until they are repaired.
+
+use crate::{
+    metrics::LEDGER_CORRUPTED_VERSIONS,
+    schema::ledger_checksum::{LedgerChecksum, LedgerChecksumSchema},
+    AptosDB,
+};
+use anyhow::{ensure, format_err, Result};
+use aptos_config::config::LedgerChecksumConfig;
+use aptos_crypto::{hash::DefaultHasher, HashValue};
+use aptos_logger::prelude::*;
+use aptos_schemadb::{ReadOptions, SchemaBatch};
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    contract_event::ContractEvent,
+    transaction::{
+        Transaction, TransactionInfo, TransactionOutputListWithProof, TransactionToCommit, Version,
+    },
+    write_set::WriteSet,
+};
+use std::ops::Range;
+
+fn ledger_checksum<'a>(
+    versions: impl IntoIterator<
+        Item = (
+            &'a Transaction,
+            &'a [ContractEvent],
+            &'a WriteSet,
+            &'a TransactionInfo,
+        ),
+    >,
+) -> Result<HashValue> {
+    let mut hasher = DefaultHasher::new(b"LedgerChecksum");
+    for (transaction, events, write_set, transaction_info) in versions {
+        hasher.update(&bcs::to_bytes(transaction)?);
+        hasher.update(&bcs::to_bytes(events)?);
+        hasher.update(&bcs::to_bytes(write_set)?);
+        hasher.update(&bcs::to_bytes(transaction_info)?);
+    }
+    Ok(hasher.finish())
+}
+
+impl AptosDB {
+    /// Writes the checksum of each commit from now on, and verifies the checksums of the latest
+    /// commits, whose corrupted ranges are then reported by
+    /// [`DbReader::get_corrupted_ledger_ranges`].
+    pub fn enable_ledger_checksum(&mut self, config: &LedgerChecksumConfig) -> Result<()> {
+        self.ledger_checksum_enabled = true;
+        let next_version = self.get_latest_version().map_or(0, |v| v + 1);
+        let start_version = if config.verify_window_on_startup == 0 {
+            0
+        } else {
+            next_version.saturating_sub(config.verify_window_on_startup)
+        };
+
+        let corrupted_ranges = self.verify_ledger_checksums(start_version, next_version)?;
+        if corrupted_ranges.is_empty() {
+            info!(
+                start_version = start_version,
+                end_version = next_version,
+                "Enabled AptosDB ledger checksums, no corruption found."
+            );
+        } else {
+            error!(
+                start_version = start_version,
+                end_version = next_version,
+                corrupted_ranges = ?corrupted_ranges,
+                "Enabled AptosDB ledger checksums, found corrupted ledger data."
+            );
+        }
+        self.set_corrupted_ledger_ranges(corrupted_ranges);
+        Ok(())
+    }
+
+    /// Verifies the checksums of the commits overlapping `[start_version, end_version)`, and
+    /// returns the version ranges of the ones that don't match or can't be read. Commits with
+    /// versions that are pruned or moved to the cold tier are skipped.
+    pub fn verify_ledger_checksums(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<Vec<Range<Version>>> {
+        let min_readable_version = self.ledger_pruner.get_min_readable_version().max(
+            self.cold_tier
+                .as_ref()
+                .map_or(0, |cold_tier| cold_tier.archived_version()),
+        );
+
+        // Starts from the commit covering `start_version`, if any.
+        let metadata_db = self.ledger_db.metadata_db();
+        let mut prev_iter = metadata_db.iter::<LedgerChecksumSchema>(ReadOptions::default())?;
+        prev_iter.seek_for_prev(&start_version)?;
+        let seek_version = prev_iter
+            .next()
+            .transpose()?
+            .map_or(start_version, |(first_version, _)| first_version);
+        let mut iter = metadata_db.iter::<LedgerChecksumSchema>(ReadOptions::default())?;
+        iter.seek(&seek_version)?;
+        let mut corrupted_ranges = Vec::new();
+        for item in iter {
+            let (first_version, expected) = item?;
+            if first_version >= end_version {
+                break;
+            }
+            let range = first_version..first_version + expected.num_versions;
+            if range.end <= start_version || range.start < min_readable_version {
+                continue;
+            }
+            match self.get_stored_ledger_checksum(range.clone()) {
+                Ok(checksum) if checksum == expected.checksum => (),
+                Ok(checksum) => {
+                    warn!(
+                        range = ?range,
+                        expected = %expected.checksum,
+                        actual = %checksum,
+                        "Ledger checksum mismatch."
+                    );
+                    corrupted_ranges.push(range);
+                },
+                Err(err) => {
+                    warn!(range = ?range, error = ?err, "Failed to read ledger data.");
+                    corrupted_ranges.push(range);
+                },
+            }
+        }
+        Ok(corrupted_ranges)
+    }
+
+    pub(crate) fn put_ledger_checksum(
+        &self,
+        first_version: Version,
+        txns_to_commit: &[TransactionToCommit],
+        batch: &SchemaBatch,
+    ) -> Result<()> {
+        if !self.ledger_checksum_enabled || txns_to_commit.is_empty() {
+            return Ok(());
+        }
+        let checksum = ledger_checksum(txns_to_commit.iter().map(|txn_to_commit| {
+            (
+                txn_to_commit.transaction(),
+                txn_to_commit.events(),
+                txn_to_commit.write_set(),
+                txn_to_commit.transaction_info(),
+            )
+        }))?;
+        batch.put::<LedgerChecksumSchema>(&first_version, &LedgerChecksum {
+            num_versions: txns_to_commit.len() as u64,
+            checksum,
+        })
+    }
+
+    pub(crate) fn get_corrupted_ledger_ranges_impl(&self) -> Vec<Range<Version>> {
+        self.corrupted_ledger_ranges.lock().clone()
+    }
+
+    pub(crate) fn repair_ledger_data_impl(
+        &self,
+        output_list_with_proof: TransactionOutputListWithProof,
+    ) -> Result<()> {
+        let first_version = output_list_with_proof
+            .first_transaction_output_version
+            .ok_or_else(|| format_err!("Nothing to repair."))?;
+        let num_versions = output_list_with_proof.transactions_and_outputs.len() as u64;
+        let end_version = first_version + num_versions;
+
+        let _lock = self
+            .ledger_commit_lock
+            .try_lock()
+            .map_err(|_| format_err!("Concurrent committing detected."))?;
+        self.error_if_ledger_pruned("Transaction", first_version)?;
+        ensure!(
+            self.cold_tier
+                .as_ref()
+                .map_or(true, |cold_tier| !cold_tier.contains(first_version)),
+            "Version {} is in the cold tier, which can't be repaired.",
+            first_version,
+        );
+        let ledger_info = self.get_latest_ledger_info()?;
+        ensure!(
+            end_version <= ledger_info.ledger_info().version() + 1,
+            "Versions [{}, {}) are not covered by the latest ledger info at version {}.",
+            first_version,
+            end_version,
+            ledger_info.ledger_info().version(),
+        );
+        output_list_with_proof.verify(ledger_info.ledger_info(), Some(first_version))?;
+
+        let versions: Vec<_> = output_list_with_proof
+            .transactions_and_outputs
+            .iter()
+            .zip(&output_list_with_proof.proof.transaction_infos)
+            .map(|((transaction, output), transaction_info)| {
+                (
+                    transaction,
+                    output.events(),
+                    output.write_set(),
+                    transaction_info,
+                )
+            })
+            .collect();
+
+        let transaction_batch = SchemaBatch::new();
+        let event_batch = SchemaBatch::new();
+        let write_set_batch = SchemaBatch::new();
+        let transaction_info_batch = SchemaBatch::new();
+        for (i, (transaction, events, write_set, transaction_info)) in versions.iter().enumerate() {
+            let version = first_version + i as u64;
+            self.transaction_store.put_transaction(
+                version,
+                transaction,
+                self.skip_index_and_usage,
+                &transaction_batch,
+            )?;
+            self.event_store.put_events(
+                version,
+                events,
+                self.skip_index_and_usage,
+                &event_batch,
+            )?;
+            self.transaction_store
+                .put_write_set(version, write_set, &write_set_batch)?;
+            self.ledger_store.put_transaction_info(
+                version,
+                transaction_info,
+                &transaction_info_batch,
+            )?;
+        }
+
+        // The stored checksums might be the corrupted part, so the ones of the commits fully
+        // covered by the verified data are rewritten as well.
+        let checksum_batch = SchemaBatch::new();
+        let mut iter = self
+            .ledger_db
+            .metadata_db()
+            .iter::<LedgerChecksumSchema>(ReadOptions::default())?;
+        iter.seek(&first_version)?;
+        for item in iter {
+            let (chunk_first_version, LedgerChecksum { num_versions, .. }) = item?;
+            let chunk_end_version = chunk_first_version + num_versions;
+            if chunk_end_version > end_version {
+                break;
+            }
+            let chunk = &versions[(chunk_first_version - first_version) as usize
+                ..(chunk_end_version - first_version) as usize];
+            checksum_batch.put::<LedgerChecksumSchema>(&chunk_first_version, &LedgerChecksum {
+                num_versions,
+                checksum: ledger_checksum(chunk.iter().copied())?,
+            })?;
+        }
+
+        self.ledger_db
+            .transaction_db()
+            .write_schemas(transaction_batch)?;
+        self.ledger_db.event_db().write_schemas(event_batch)?;
+        self.ledger_db
+            .write_set_db()
+            .write_schemas(write_set_batch)?;
+        self.ledger_db
+            .transaction_info_db()
+            .write_schemas(transaction_info_batch)?;
+        self.ledger_db.metadata_db().write_schemas(checksum_batch)?;
+
+        let still_corrupted = self.verify_ledger_checksums(first_version, end_version)?;
+        let mut corrupted_ranges = self.get_corrupted_ledger_ranges_impl();
+        // The commits overlapping the repaired versions have all been verified again above.
+        corrupted_ranges.retain(|range| range.end <= first_version || range.start >= end_version);
+        corrupted_ranges.extend(still_corrupted);
+        corrupted_ranges.sort_by_key(|range| range.start);
+        info!(
+            first_version = first_version,
+            end_version = end_version,
+            remaining_corrupted_ranges = ?corrupted_ranges,
+            "Repaired ledger data."
+        );
+        self.set_corrupted_ledger_ranges(corrupted_ranges);
+        Ok(())
+    }
+
+    fn get_stored_ledger_checksum(&self, range: Range<Version>) -> Result<HashValue> {
+        let versions = range
+            .map(|version| {
+                Ok((
+                    self.read_transaction(version)?,
+                    self.read_events(version)?,
+                    self.read_write_set(version)?,
+                    self.ledger_store.get_transaction_info(version)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        ledger_checksum(versions.iter().map(
+            |(transaction, events, write_set, transaction_info)| {
+                (transaction, events.as_slice(), write_set, transaction_info)
+            },
+        ))
+    }
+
+    fn set_corrupted_ledger_ranges(&self, corrupted_ranges: Vec<Range<Version>>) {
+        LEDGER_CORRUPTED_VERSIONS.set(
+            corrupted_ranges
+                .iter()
+                .map(|range| range.end - range.start)
+                .sum::<u64>() as i64,
+        );
+        *self.corrupted_ledger_ranges.lock() = corrupted_ranges;
+    }
+}
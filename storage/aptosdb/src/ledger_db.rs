@@ -6,11 +6,12 @@
 
 use crate::{
     db_options::{
-        event_db_column_families, gen_event_cfds, gen_ledger_cfds, gen_ledger_metadata_cfds,
-        gen_transaction_accumulator_cfds, gen_transaction_cfds, gen_transaction_info_cfds,
-        gen_write_set_cfds, ledger_db_column_families, ledger_metadata_db_column_families,
+        ensure_known_cfs_in_config, event_db_column_families, gen_event_cfds, gen_ledger_cfds,
+        gen_ledger_metadata_cfds, gen_secondary_rocksdb_options, gen_transaction_accumulator_cfds,
+        gen_transaction_cfds, gen_transaction_info_cfds, gen_write_set_cfds,
+        ledger_db_column_families, ledger_metadata_db_column_families,
         transaction_accumulator_db_column_families, transaction_db_column_families,
-        transaction_info_db_column_families, write_set_db_column_families,
+        transaction_info_db_column_families, write_set_db_column_families, OpenMode,
     },
     schema::db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
 };
@@ -79,9 +80,14 @@ impl LedgerDb {
     pub(crate) fn new<P: AsRef<Path>>(
         db_root_path: P,
         rocksdb_configs: RocksdbConfigs,
-        readonly: bool,
+        open_mode: &OpenMode,
     ) -> Result<Self> {
         let sharding = rocksdb_configs.enable_storage_sharding;
+        ensure_known_cfs_in_config(
+            &rocksdb_configs.ledger_db_config,
+            &ledger_db_column_families(),
+            LEDGER_DB_NAME,
+        )?;
         let ledger_metadata_db_path = Self::metadata_db_path(db_root_path.as_ref(), sharding);
         let ledger_metadata_db = Arc::new(Self::open_rocksdb(
             ledger_metadata_db_path.clone(),
@@ -91,7 +97,7 @@ impl LedgerDb {
                 LEDGER_DB_NAME
             },
             &rocksdb_configs.ledger_db_config,
-            readonly,
+            open_mode,
         )?);
 
         info!(
@@ -118,35 +124,35 @@ impl LedgerDb {
             ledger_db_folder.join(EVENT_DB_NAME),
             EVENT_DB_NAME,
             &rocksdb_configs.ledger_db_config,
-            readonly,
+            open_mode,
         )?);
 
         let transaction_accumulator_db = Arc::new(Self::open_rocksdb(
             ledger_db_folder.join(TRANSACTION_ACCUMULATOR_DB_NAME),
             TRANSACTION_ACCUMULATOR_DB_NAME,
             &rocksdb_configs.ledger_db_config,
-            readonly,
+            open_mode,
         )?);
 
         let transaction_db = Arc::new(Self::open_rocksdb(
             ledger_db_folder.join(TRANSACTION_DB_NAME),
             TRANSACTION_DB_NAME,
             &rocksdb_configs.ledger_db_config,
-            readonly,
+            open_mode,
         )?);
 
         let transaction_info_db = Arc::new(Self::open_rocksdb(
             ledger_db_folder.join(TRANSACTION_INFO_DB_NAME),
             TRANSACTION_INFO_DB_NAME,
             &rocksdb_configs.ledger_db_config,
-            readonly,
+            open_mode,
         )?);
 
         let write_set_db = Arc::new(Self::open_rocksdb(
             ledger_db_folder.join(WRITE_SET_DB_NAME),
             WRITE_SET_DB_NAME,
             &rocksdb_configs.ledger_db_config,
-            readonly,
+            open_mode,
         )?);
 
         // TODO(grao): Handle data inconsistency.
@@ -161,6 +167,21 @@ impl LedgerDb {
         })
     }
 
+    /// See [`DB::try_catch_up_with_primary`]. The metadata DB, holding the ledger infos, catches
+    /// up last, so the data they cover is always visible.
+    pub(crate) fn try_catch_up_with_primary(&self) -> Result<()> {
+        // Without sharding, all of them are the metadata DB.
+        if !Arc::ptr_eq(&self.event_db, &self.ledger_metadata_db) {
+            self.event_db.try_catch_up_with_primary()?;
+            self.transaction_accumulator_db
+                .try_catch_up_with_primary()?;
+            self.transaction_db.try_catch_up_with_primary()?;
+            self.transaction_info_db.try_catch_up_with_primary()?;
+            self.write_set_db.try_catch_up_with_primary()?;
+        }
+        self.ledger_metadata_db.try_catch_up_with_primary()
+    }
+
     pub(crate) fn create_checkpoint(
         db_root_path: impl AsRef<Path>,
         cp_root_path: impl AsRef<Path>,
@@ -170,7 +191,7 @@ impl LedgerDb {
             enable_storage_sharding: sharding,
             ..Default::default()
         };
-        let ledger_db = Self::new(db_root_path, rocksdb_configs, /*readonly=*/ false)?;
+        let ledger_db = Self::new(db_root_path, rocksdb_configs, &OpenMode::ReadWrite)?;
         let cp_ledger_db_folder = cp_root_path.as_ref().join(LEDGER_DB_FOLDER_NAME);
 
         info!(
@@ -208,6 +229,20 @@ impl LedgerDb {
         Ok(())
     }
 
+    /// Syncs the writes done by [`DB::write_schemas_relaxed`] to disk. The metadata DB, holding the
+    /// commit progress, syncs last.
+    pub(crate) fn flush_wal(&self) -> Result<()> {
+        // Without sharding, all of them are the metadata DB.
+        if !Arc::ptr_eq(&self.event_db, &self.ledger_metadata_db) {
+            self.event_db.flush_wal()?;
+            self.transaction_accumulator_db.flush_wal()?;
+            self.transaction_db.flush_wal()?;
+            self.transaction_info_db.flush_wal()?;
+            self.write_set_db.flush_wal()?;
+        }
+        self.ledger_metadata_db.flush_wal()
+    }
+
     // Only expect to be used by fast sync when it is finished.
     pub(crate) fn write_pruner_progress(&self, version: Version) -> Result<()> {
         info!("Fast sync is done, writing pruner progress {version} for all ledger sub pruners.");
@@ -289,22 +324,28 @@ impl LedgerDb {
         path: PathBuf,
         name: &str,
         db_config: &RocksdbConfig,
-        readonly: bool,
+        open_mode: &OpenMode,
     ) -> Result<DB> {
-        let db = if readonly {
-            DB::open_cf_readonly(
+        let db = match open_mode {
+            OpenMode::ReadWrite => DB::open_cf(
+                &gen_rocksdb_options(db_config, false),
+                path.clone(),
+                name,
+                Self::gen_cfds_by_name(db_config, name),
+            )?,
+            OpenMode::ReadOnly => DB::open_cf_readonly(
                 &gen_rocksdb_options(db_config, true),
                 path.clone(),
                 name,
                 Self::get_column_families_by_name(name),
-            )?
-        } else {
-            DB::open_cf(
-                &gen_rocksdb_options(db_config, false),
+            )?,
+            OpenMode::Secondary { .. } => DB::open_cf_as_secondary(
+                &gen_secondary_rocksdb_options(db_config),
                 path.clone(),
+                open_mode.secondary_path(name),
                 name,
-                Self::gen_cfds_by_name(db_config, name),
-            )?
+                Self::get_column_families_by_name(name),
+            )?,
         };
 
         info!("Opened {name} at {path:?}!");
@@ -46,29 +46,41 @@ pub struct LedgerStore {
     /// cache it in memory in order to avoid reading DB and deserializing the object frequently. It
     /// should be updated every time new ledger info and signatures are persisted.
     latest_ledger_info: ArcSwap<Option<LedgerInfoWithSignatures>>,
+
+    /// The latest version committed but whose commit progress is not persisted yet, with group
+    /// commit enabled (see `GroupCommitter`).
+    pending_commit_version: ArcSwap<Option<Version>>,
 }
 
 impl LedgerStore {
     pub fn new(ledger_db: Arc<LedgerDb>) -> Self {
         // Upon restart, read the latest ledger info and signatures and cache them in memory.
-        let ledger_info = {
-            let mut iter = ledger_db
-                .metadata_db()
-                .iter::<LedgerInfoSchema>(ReadOptions::default())
-                .expect("Constructing iterator should work.");
-            iter.seek_to_last();
-            iter.next()
-                .transpose()
-                .expect("Reading latest ledger info from DB should work.")
-                .map(|kv| kv.1)
-        };
+        let ledger_info = Self::read_latest_ledger_info(&ledger_db)
+            .expect("Reading latest ledger info from DB should work.");
 
         Self {
             ledger_db,
             latest_ledger_info: ArcSwap::from(Arc::new(ledger_info)),
+            pending_commit_version: ArcSwap::from(Arc::new(None)),
         }
     }
 
+    fn read_latest_ledger_info(ledger_db: &LedgerDb) -> Result<Option<LedgerInfoWithSignatures>> {
+        let mut iter = ledger_db
+            .metadata_db()
+            .iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        iter.seek_to_last();
+        Ok(iter.next().transpose()?.map(|kv| kv.1))
+    }
+
+    /// Refreshes the cached latest ledger info from the DB, for a secondary instance following the
+    /// writes of the primary.
+    pub fn reload_latest_ledger_info(&self) -> Result<()> {
+        self.latest_ledger_info
+            .store(Arc::new(Self::read_latest_ledger_info(&self.ledger_db)?));
+        Ok(())
+    }
+
     pub fn get_epoch(&self, version: Version) -> Result<u64> {
         let mut iter = self
             .ledger_db
@@ -136,6 +148,10 @@ impl LedgerStore {
             .ok_or_else(|| AptosDbError::NotFound(String::from("Genesis LedgerInfo")).into())
     }
 
+    pub(crate) fn set_pending_commit_version(&self, version: Option<Version>) {
+        self.pending_commit_version.store(Arc::new(version));
+    }
+
     pub fn set_latest_ledger_info(&self, ledger_info_with_sigs: LedgerInfoWithSignatures) {
         self.latest_ledger_info
             .store(Arc::new(Some(ledger_info_with_sigs)));
@@ -181,6 +197,9 @@ impl LedgerStore {
     }
 
     pub fn get_latest_version(&self) -> Result<Version> {
+        if let Some(version) = *self.pending_commit_version.load_full() {
+            return Ok(version);
+        }
         get_progress(
             self.ledger_db.metadata_db(),
             &DbMetadataKey::OverallCommitProgress,
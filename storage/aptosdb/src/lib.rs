@@ -17,6 +17,8 @@ pub mod fake_aptosdb;
 pub mod test_helper;
 
 pub mod backup;
+pub mod cold_tier;
+pub mod db_maintenance;
 pub mod errors;
 pub mod metrics;
 pub mod schema;
@@ -41,23 +43,28 @@ mod aptosdb_test;
 #[cfg(feature = "db-debugger")]
 pub mod db_debugger;
 pub mod fast_sync_storage_wrapper;
+mod group_commit;
+mod ledger_checksum;
+pub mod secondary_db;
 
 use crate::{
     backup::{backup_handler::BackupHandler, restore_handler::RestoreHandler, restore_utils},
+    cold_tier::{chain_cold_tier_iter, create_cold_storage, ColdTier, ColdTierArchiver},
     db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
     db_options::{
         event_db_column_families, ledger_db_column_families, ledger_metadata_db_column_families,
         state_kv_db_column_families, state_merkle_db_column_families,
         transaction_accumulator_db_column_families, transaction_db_column_families,
-        transaction_info_db_column_families, write_set_db_column_families,
+        transaction_info_db_column_families, write_set_db_column_families, OpenMode,
     },
     errors::AptosDbError,
     event_store::EventStore,
+    group_commit::GroupCommitter,
     ledger_db::{LedgerDb, LedgerDbSchemaBatches},
     ledger_store::LedgerStore,
     metrics::{
         API_LATENCY_SECONDS, COMMITTED_TXNS, LATEST_TXN_VERSION, LEDGER_VERSION, NEXT_BLOCK_EPOCH,
-        OTHER_TIMERS_SECONDS, ROCKSDB_PROPERTIES,
+        OTHER_TIMERS_SECONDS, ROCKSDB_CF_AMPLIFICATION, ROCKSDB_PROPERTIES,
     },
     pruner::{LedgerPrunerManager, PrunerManager, StateKvPrunerManager, StateMerklePrunerManager},
     schema::*,
@@ -68,9 +75,10 @@ use crate::{
     state_store::{buffered_state::BufferedState, StateStore},
     transaction_store::TransactionStore,
 };
-use anyhow::{bail, ensure, Result};
+use anyhow::{bail, ensure, format_err, Result};
 use aptos_config::config::{
-    PrunerConfig, RocksdbConfig, RocksdbConfigs, NO_OP_STORAGE_PRUNER_CONFIG,
+    ColdTierConfig, EventFieldIndexConfig, GroupCommitConfig, PrunerConfig, RocksdbConfig,
+    RocksdbConfigs, NO_OP_STORAGE_PRUNER_CONFIG,
 };
 #[cfg(any(test, feature = "fuzzing"))]
 use aptos_config::config::{
@@ -84,8 +92,9 @@ use aptos_logger::prelude::*;
 use aptos_metrics_core::TimerHelper;
 use aptos_schemadb::{SchemaBatch, DB};
 use aptos_storage_interface::{
-    cached_state_view::ShardedStateCache, state_delta::StateDelta, state_view::DbStateView,
-    DbReader, DbWriter, ExecutedTrees, Order, StateSnapshotReceiver, MAX_REQUEST_LIMIT,
+    cached_state_view::ShardedStateCache, state_delta::StateDelta, state_iterator::StateIterator,
+    state_view::DbStateView, DbReader, DbWriter, ExecutedTrees, Order, StateSnapshotReceiver,
+    MAX_REQUEST_LIMIT,
 };
 use aptos_types::{
     account_address::AccountAddress,
@@ -107,6 +116,7 @@ use aptos_types::{
         state_key_prefix::StateKeyPrefix,
         state_storage_usage::StateStorageUsage,
         state_value::{StateValue, StateValueChunkWithProof},
+        storage_footprint::{StorageFootprint, StorageFootprintOwner, StorageFootprintOwnerKind},
         table::{TableHandle, TableInfo},
         ShardedStateUpdates,
     },
@@ -119,6 +129,7 @@ use aptos_types::{
 };
 use aptos_vm::data_cache::AsMoveResolver;
 use arr_macro::arr;
+use move_core_types::{identifier::IdentStr, language_storage::TypeTag};
 use move_resource_viewer::MoveValueAnnotator;
 use once_cell::sync::Lazy;
 use rayon::prelude::*;
@@ -128,7 +139,8 @@ use std::{
     collections::HashMap,
     fmt::{Debug, Formatter},
     iter::Iterator,
-    path::Path,
+    ops::Range,
+    path::{Path, PathBuf},
     sync::{mpsc, Arc},
     thread,
     thread::JoinHandle,
@@ -203,19 +215,75 @@ fn set_property(cf_name: &str, db: &DB) -> Result<()> {
             .with_label_values(&[cf_name, aptos_rocksdb_property_name])
             .set(db.get_property(cf_name, rockdb_property_name)? as i64);
     }
-    Ok(())
+    set_amplification(cf_name, cf_name, db)
 }
 
 fn set_property_sharded(cf_name: &str, db: &DB, db_shard_id: usize) -> Result<()> {
+    let cf_label = format!("{}_{}", cf_name, db_shard_id);
     for (rockdb_property_name, aptos_rocksdb_property_name) in &*ROCKSDB_PROPERTY_MAP {
-        let cf_label = format!("{}_{}", cf_name, db_shard_id);
         ROCKSDB_PROPERTIES
             .with_label_values(&[&cf_label, aptos_rocksdb_property_name])
             .set(db.get_property(cf_name, rockdb_property_name)? as i64);
     }
+    set_amplification(&cf_label, cf_name, db)
+}
+
+fn set_amplification(cf_label: &str, cf_name: &str, db: &DB) -> Result<()> {
+    let cf_stats = db.get_string_property(cf_name, "rocksdb.cfstats-no-file-histogram")?;
+    if let Some((read_amp, write_amp)) = parse_amplification(&cf_stats) {
+        ROCKSDB_CF_AMPLIFICATION
+            .with_label_values(&[cf_label, "read"])
+            .set(read_amp);
+        ROCKSDB_CF_AMPLIFICATION
+            .with_label_values(&[cf_label, "write"])
+            .set(write_amp);
+    }
     Ok(())
 }
 
+/// Parses the read and write amplification out of the compaction stats of a column family, e.g.
+///
+/// ```text
+/// Level    Files   Size     Score Read(GB)  Rn(GB) Rnp1(GB) Write(GB) Wnew(GB) Moved(GB) W-Amp ...
+/// ---------------------------------------------------------------------------------------------...
+///   L0      2/0    1.52 KB   0.5      0.0     0.0      0.0       0.0      0.0       0.0   1.0 ...
+///   L1      1/0    2.10 KB   0.0      0.0     0.0      0.0       0.0      0.0       0.0   0.8 ...
+///  Sum      3/0    3.62 KB   0.0      0.0     0.0      0.0       0.0      0.0       0.0   1.6 ...
+/// ```
+///
+/// The write amplification is the one of the "Sum" row. The read amplification is estimated as
+/// the number of files a point lookup may check: every L0 file, plus one per other non-empty level.
+fn parse_amplification(cf_stats: &str) -> Option<(f64, f64)> {
+    let mut lines = cf_stats
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("Level"));
+    let header: Vec<&str> = lines.next()?.split_whitespace().collect();
+    let column = |name: &str| header.iter().position(|column| *column == name);
+    let (files_idx, size_idx, w_amp_idx) = (column("Files")?, column("Size")?, column("W-Amp")?);
+
+    let mut read_amp = 0.0;
+    for line in lines {
+        let mut row: Vec<&str> = line.split_whitespace().collect();
+        // The size is followed by its unit.
+        if row.len() > size_idx + 1 {
+            row.remove(size_idx + 1);
+        }
+        match row.first() {
+            Some(&"Sum") => return Some((read_amp, row.get(w_amp_idx)?.parse().ok()?)),
+            Some(level) if level.starts_with('L') => {
+                let num_files: u64 = row.get(files_idx)?.split('/').next()?.parse().ok()?;
+                if *level == "L0" {
+                    read_amp += num_files as f64;
+                } else if num_files > 0 {
+                    read_amp += 1.0;
+                }
+            },
+            _ => (),
+        }
+    }
+    None
+}
+
 fn update_rocksdb_properties(
     ledger_db: &LedgerDb,
     state_merkle_db: &StateMerkleDb,
@@ -288,6 +356,7 @@ impl RocksdbPropertyReporter {
         ledger_db: Arc<LedgerDb>,
         state_merkle_db: Arc<StateMerkleDb>,
         state_kv_db: Arc<StateKvDb>,
+        report_interval_ms: u64,
     ) -> Self {
         let (send, recv) = mpsc::channel();
         let join_handle = Some(thread::spawn(move || loop {
@@ -297,10 +366,8 @@ impl RocksdbPropertyReporter {
                     "Updating rocksdb property failed."
                 );
             }
-            // report rocksdb properties each 10 seconds
-            const TIMEOUT_MS: u64 = if cfg!(test) { 10 } else { 10000 };
-
-            match recv.recv_timeout(Duration::from_millis(TIMEOUT_MS)) {
+            let timeout_ms = if cfg!(test) { 10 } else { report_interval_ms };
+            match recv.recv_timeout(Duration::from_millis(timeout_ms)) {
                 Ok(_) => break,
                 Err(mpsc::RecvTimeoutError::Timeout) => (),
                 Err(mpsc::RecvTimeoutError::Disconnected) => break,
@@ -335,10 +402,15 @@ pub struct AptosDB {
     pub(crate) state_store: Arc<StateStore>,
     pub(crate) transaction_store: Arc<TransactionStore>,
     ledger_pruner: LedgerPrunerManager,
-    _rocksdb_property_reporter: RocksdbPropertyReporter,
+    _rocksdb_property_reporter: Option<RocksdbPropertyReporter>,
     ledger_commit_lock: std::sync::Mutex<()>,
     indexer: Option<Indexer>,
     skip_index_and_usage: bool,
+    cold_tier: Option<Arc<ColdTier>>,
+    _cold_tier_archiver: Option<ColdTierArchiver>,
+    group_committer: Option<GroupCommitter>,
+    ledger_checksum_enabled: bool,
+    corrupted_ledger_ranges: Mutex<Vec<Range<Version>>>,
 }
 
 impl AptosDB {
@@ -351,6 +423,7 @@ impl AptosDB {
         hack_for_tests: bool,
         empty_buffered_state_for_restore: bool,
         skip_index_and_usage: bool,
+        property_report_interval_ms: u64,
     ) -> Self {
         let ledger_db = Arc::new(ledger_db);
         let state_merkle_db = Arc::new(state_merkle_db);
@@ -389,14 +462,22 @@ impl AptosDB {
             state_store,
             transaction_store: Arc::new(TransactionStore::new(Arc::clone(&ledger_db))),
             ledger_pruner,
-            _rocksdb_property_reporter: RocksdbPropertyReporter::new(
-                ledger_db,
-                state_merkle_db,
-                state_kv_db,
-            ),
+            _rocksdb_property_reporter: (property_report_interval_ms > 0).then(|| {
+                RocksdbPropertyReporter::new(
+                    ledger_db,
+                    state_merkle_db,
+                    state_kv_db,
+                    property_report_interval_ms,
+                )
+            }),
             ledger_commit_lock: std::sync::Mutex::new(()),
             indexer: None,
             skip_index_and_usage,
+            cold_tier: None,
+            _cold_tier_archiver: None,
+            group_committer: None,
+            ledger_checksum_enabled: false,
+            corrupted_ledger_ranges: Mutex::new(Vec::new()),
         }
     }
 
@@ -417,7 +498,7 @@ impl AptosDB {
 
         let (ledger_db, state_merkle_db, state_kv_db) = Self::open_dbs(
             db_root_path.as_ref(),
-            rocksdb_configs,
+            rocksdb_configs.clone(),
             readonly,
             max_num_nodes_per_lru_cache_shard,
         )?;
@@ -431,6 +512,7 @@ impl AptosDB {
             readonly,
             empty_buffered_state_for_restore,
             rocksdb_configs.enable_storage_sharding,
+            rocksdb_configs.property_report_interval_ms,
         );
 
         if !readonly && enable_indexer {
@@ -482,29 +564,136 @@ impl AptosDB {
         )
     }
 
+    /// Opens the DB of another process, e.g. a running node, as RocksDB secondary instances, which
+    /// read the DB without impacting the other process. `secondary_root` holds the info logs of
+    /// the secondary instances. The DB only sees the writes made before it was opened, until
+    /// [`Self::try_catch_up_with_primary`] is called. See also
+    /// [`SecondaryAptosDB`](secondary_db::SecondaryAptosDB), which does so periodically.
+    pub fn open_as_secondary<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        secondary_root: PathBuf,
+        rocksdb_configs: RocksdbConfigs,
+        max_num_nodes_per_lru_cache_shard: usize,
+    ) -> Result<Self> {
+        let (ledger_db, state_merkle_db, state_kv_db) = Self::open_dbs_in_mode(
+            db_root_path,
+            rocksdb_configs.clone(),
+            &OpenMode::Secondary { secondary_root },
+            max_num_nodes_per_lru_cache_shard,
+        )?;
+
+        // Like a readonly DB, it doesn't replay the write sets after the latest snapshot.
+        Ok(Self::new_with_dbs(
+            ledger_db,
+            state_merkle_db,
+            state_kv_db,
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            0,     /* buffered_state_target_items */
+            true,  /* hack_for_tests */
+            false, /* empty_buffered_state_for_restore */
+            rocksdb_configs.enable_storage_sharding,
+            rocksdb_configs.property_report_interval_ms,
+        ))
+    }
+
+    /// Makes a DB opened by [`Self::open_as_secondary`] see the writes made since it was opened or
+    /// last caught up.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        // The ledger infos are caught up last, so the data they cover is always visible.
+        self.state_store
+            .state_db
+            .state_merkle_db
+            .try_catch_up_with_primary()?;
+        self.state_kv_db.try_catch_up_with_primary()?;
+        self.ledger_db.try_catch_up_with_primary()?;
+
+        self.ledger_store.reload_latest_ledger_info()?;
+        self.state_store.reset_to_latest_snapshot()
+    }
+
     pub fn open_dbs<P: AsRef<Path> + Clone>(
         db_root_path: P,
         rocksdb_configs: RocksdbConfigs,
         readonly: bool,
         max_num_nodes_per_lru_cache_shard: usize,
     ) -> Result<(LedgerDb, StateMerkleDb, StateKvDb)> {
-        let ledger_db = LedgerDb::new(db_root_path.as_ref(), rocksdb_configs, readonly)?;
+        Self::open_dbs_in_mode(
+            db_root_path,
+            rocksdb_configs,
+            &OpenMode::from_readonly(readonly),
+            max_num_nodes_per_lru_cache_shard,
+        )
+    }
+
+    fn open_dbs_in_mode<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        rocksdb_configs: RocksdbConfigs,
+        open_mode: &OpenMode,
+        max_num_nodes_per_lru_cache_shard: usize,
+    ) -> Result<(LedgerDb, StateMerkleDb, StateKvDb)> {
+        let ledger_db = LedgerDb::new(db_root_path.as_ref(), rocksdb_configs.clone(), open_mode)?;
         let state_kv_db = StateKvDb::new(
             db_root_path.as_ref(),
-            rocksdb_configs,
-            readonly,
+            rocksdb_configs.clone(),
+            open_mode,
             ledger_db.metadata_db_arc(),
         )?;
         let state_merkle_db = StateMerkleDb::new(
             db_root_path,
             rocksdb_configs,
-            readonly,
+            open_mode,
             max_num_nodes_per_lru_cache_shard,
         )?;
 
         Ok((ledger_db, state_merkle_db, state_kv_db))
     }
 
+    /// Moves the transactions, events and write sets falling out of the hot window to the cold
+    /// tier in the background, and serves reads of them from there.
+    pub fn enable_cold_tier(&mut self, config: &ColdTierConfig) -> Result<()> {
+        ensure!(
+            !self.ledger_pruner.is_pruner_enabled(),
+            "The cold tier requires the ledger pruner to be disabled."
+        );
+        let cold_tier = Arc::new(ColdTier::new(
+            &self.ledger_db,
+            create_cold_storage(&config.storage)?,
+            config.segment_size,
+        )?);
+        info!(
+            archived_version = cold_tier.archived_version(),
+            hot_window = config.hot_window,
+            "Enabled AptosDB cold tier."
+        );
+
+        self._cold_tier_archiver = Some(ColdTierArchiver::new(
+            Arc::clone(&cold_tier),
+            Arc::clone(&self.ledger_db),
+            Arc::clone(&self.ledger_store),
+            Arc::clone(&self.transaction_store),
+            Arc::clone(&self.event_store),
+            config.hot_window,
+        ));
+        self.cold_tier = Some(cold_tier);
+        Ok(())
+    }
+
+    /// Enables group commit, which syncs the commits without ledger infos to disk in groups,
+    /// see `GroupCommitConfig`.
+    pub fn enable_group_commit(&mut self, config: &GroupCommitConfig) {
+        info!(
+            max_latency_ms = config.max_latency_ms,
+            max_pending_transactions = config.max_pending_transactions,
+            "Enabled AptosDB group commit."
+        );
+        self.group_committer = Some(GroupCommitter::new(
+            config,
+            Arc::clone(&self.ledger_db),
+            Arc::clone(&self.state_kv_db),
+            Arc::clone(&self.ledger_store),
+        ));
+    }
+
     fn open_indexer(
         &mut self,
         db_root_path: impl AsRef<Path>,
@@ -546,6 +735,88 @@ impl AptosDB {
         Ok(())
     }
 
+    /// Indexes the events of the configured types by the configured fields in the internal
+    /// indexer, see [`DbReader::get_events_by_field`].
+    pub fn enable_event_field_index(&mut self, configs: &[EventFieldIndexConfig]) -> Result<()> {
+        let ledger_next_version = self.get_latest_version().map_or(0, |v| v + 1);
+        let mut next_version = self
+            .indexer
+            .as_mut()
+            .ok_or_else(|| format_err!("Indexer not enabled."))?
+            .enable_event_field_index(configs, ledger_next_version)?;
+
+        if next_version < ledger_next_version {
+            let indexer = self.indexer.as_ref().expect("Checked above.");
+            let state_view = DbStateView {
+                db: self.state_store.clone(),
+                version: Some(ledger_next_version - 1),
+            };
+            let resolver = state_view.as_move_resolver();
+            let annotator = MoveValueAnnotator::new(&resolver);
+
+            const BATCH_SIZE: Version = 10000;
+            while next_version < ledger_next_version {
+                info!(
+                    next_version = next_version,
+                    "Event field index catching up."
+                );
+                let end_version = std::cmp::min(ledger_next_version, next_version + BATCH_SIZE);
+                let events = (next_version..end_version)
+                    .map(|version| self.read_events(version))
+                    .collect::<Result<Vec<_>>>()?;
+                let events_ref: Vec<_> = events.iter().map(Vec::as_slice).collect();
+                indexer.index_events_with_annotator(&annotator, next_version, &events_ref)?;
+
+                next_version = end_version;
+            }
+        }
+        info!("Event field index caught up.");
+        Ok(())
+    }
+
+    /// Indexes the versions of the transactions of each account in the internal indexer, see
+    /// [`DbReader::get_account_transaction_versions`]. A new index starts from the first version
+    /// that isn't pruned.
+    pub fn enable_account_transaction_index(&mut self) -> Result<()> {
+        let ledger_next_version = self.get_latest_version().map_or(0, |v| v + 1);
+        let first_version = self.ledger_pruner.get_min_readable_version();
+        let mut next_version = self
+            .indexer
+            .as_mut()
+            .ok_or_else(|| format_err!("Indexer not enabled."))?
+            .enable_account_transaction_index(first_version)?;
+
+        let indexer = self.indexer.as_ref().expect("Checked above.");
+        const BATCH_SIZE: Version = 10000;
+        while next_version < ledger_next_version {
+            info!(
+                next_version = next_version,
+                "Account transaction index catching up."
+            );
+            let end_version = std::cmp::min(ledger_next_version, next_version + BATCH_SIZE);
+            let transactions = (next_version..end_version)
+                .map(|version| self.read_transaction(version))
+                .collect::<Result<Vec<_>>>()?;
+            let write_sets = (next_version..end_version)
+                .map(|version| self.read_write_set(version))
+                .collect::<Result<Vec<_>>>()?;
+            let transactions_ref: Vec<_> = transactions.iter().collect();
+            let write_sets_ref: Vec<_> = write_sets.iter().collect();
+            indexer.index_account_transactions(next_version, &transactions_ref, &write_sets_ref)?;
+
+            next_version = end_version;
+        }
+        info!("Account transaction index caught up.");
+        Ok(())
+    }
+
+    /// Maintains the storage footprint of the accounts, modules and tables, see
+    /// [`DbReader::get_storage_footprint`]. The footprint is rebuilt from the latest state
+    /// snapshot if it isn't up to date, e.g. the first time it's enabled.
+    pub fn enable_storage_footprint(&mut self) -> Result<()> {
+        self.state_store.enable_storage_footprint()
+    }
+
     #[cfg(any(test, feature = "fuzzing"))]
     fn new_without_pruner<P: AsRef<Path> + Clone>(
         db_root_path: P,
@@ -678,26 +949,7 @@ impl AptosDB {
         end_epoch: u64,
         limit: usize,
     ) -> Result<(Vec<LedgerInfoWithSignatures>, bool)> {
-        ensure!(
-            start_epoch <= end_epoch,
-            "Bad epoch range [{}, {})",
-            start_epoch,
-            end_epoch,
-        );
-        // Note that the latest epoch can be the same with the current epoch (in most cases), or
-        // current_epoch + 1 (when the latest ledger_info carries next validator set)
-
-        let latest_epoch = self
-            .ledger_store
-            .get_latest_ledger_info()?
-            .ledger_info()
-            .next_block_epoch();
-        ensure!(
-            end_epoch <= latest_epoch,
-            "Unable to provide epoch change ledger info for still open epoch. asked upper bound: {}, last sealed epoch: {}",
-            end_epoch,
-            latest_epoch - 1,  // okay to -1 because genesis LedgerInfo has .next_block_epoch() == 1
-        );
+        self.ensure_epoch_ending_range(start_epoch, end_epoch)?;
 
         let (paging_epoch, more) = if end_epoch - start_epoch > limit as u64 {
             (start_epoch + limit as u64, true)
@@ -720,6 +972,30 @@ impl AptosDB {
         Ok((lis, more))
     }
 
+    fn ensure_epoch_ending_range(&self, start_epoch: u64, end_epoch: u64) -> Result<()> {
+        ensure!(
+            start_epoch <= end_epoch,
+            "Bad epoch range [{}, {})",
+            start_epoch,
+            end_epoch,
+        );
+        // Note that the latest epoch can be the same with the current epoch (in most cases), or
+        // current_epoch + 1 (when the latest ledger_info carries next validator set)
+
+        let latest_epoch = self
+            .ledger_store
+            .get_latest_ledger_info()?
+            .ledger_info()
+            .next_block_epoch();
+        ensure!(
+            end_epoch <= latest_epoch,
+            "Unable to provide epoch change ledger info for still open epoch. asked upper bound: {}, last sealed epoch: {}",
+            end_epoch,
+            latest_epoch - 1,  // okay to -1 because genesis LedgerInfo has .next_block_epoch() == 1
+        );
+        Ok(())
+    }
+
     /// Returns the transaction with proof for a given version, or error if the transaction is not
     /// found.
     fn get_transaction_with_proof(
@@ -733,11 +1009,11 @@ impl AptosDB {
         let proof = self
             .ledger_store
             .get_transaction_info_with_proof(version, ledger_version)?;
-        let transaction = self.transaction_store.get_transaction(version)?;
+        let transaction = self.read_transaction(version)?;
 
         // If events were requested, also fetch those.
         let events = if fetch_events {
-            Some(self.event_store.get_events_by_version(version)?)
+            Some(self.read_events(version)?)
         } else {
             None
         };
@@ -759,6 +1035,7 @@ impl AptosDB {
             Arc::clone(&self.transaction_store),
             Arc::clone(&self.state_store),
             Arc::clone(&self.event_store),
+            self.cold_tier.clone(),
         )
     }
 
@@ -837,7 +1114,7 @@ impl AptosDB {
         let mut events_with_version = event_indices
             .into_iter()
             .map(|(seq, ver, idx)| {
-                let event = self.event_store.get_event_by_version_and_index(ver, idx)?;
+                let event = self.read_event(ver, idx)?;
                 let v0 = match &event {
                     ContractEvent::V1(event) => event,
                     ContractEvent::V2(_) => bail!("Unexpected module event"),
@@ -1019,6 +1296,8 @@ impl AptosDB {
             skip_index_and_usage,
         )?;
 
+        self.put_ledger_checksum(first_version, txns_to_commit, &ledger_metadata_batch)?;
+
         let last_version = first_version + txns_to_commit.len() as u64 - 1;
         ledger_metadata_batch
             .put::<DbMetadataSchema>(
@@ -1032,19 +1311,24 @@ impl AptosDB {
             .start_timer();
         rayon::scope(|s| {
             s.spawn(|_| {
-                self.ledger_db
-                    .metadata_db()
-                    .write_schemas(ledger_metadata_batch)
+                self.write_commit_batch(self.ledger_db.metadata_db(), ledger_metadata_batch)
                     .unwrap();
             });
             s.spawn(|_| {
-                self.state_kv_db
-                    .commit(
+                let result = if self.group_committer.is_some() {
+                    self.state_kv_db.commit_relaxed(
                         last_version,
                         state_kv_metadata_batch,
                         sharded_state_kv_batches,
                     )
-                    .unwrap();
+                } else {
+                    self.state_kv_db.commit(
+                        last_version,
+                        state_kv_metadata_batch,
+                        sharded_state_kv_batches,
+                    )
+                };
+                result.unwrap();
             });
         });
 
@@ -1079,7 +1363,7 @@ impl AptosDB {
         let _timer = OTHER_TIMERS_SECONDS
             .with_label_values(&["commit_events___commit"])
             .start_timer();
-        self.ledger_db.event_db().write_schemas(batch)
+        self.write_commit_batch(self.ledger_db.event_db(), batch)
     }
 
     fn commit_transactions(
@@ -1113,7 +1397,7 @@ impl AptosDB {
                 let _timer = OTHER_TIMERS_SECONDS
                     .with_label_values(&["commit_transactions___commit"])
                     .start_timer();
-                self.ledger_db.transaction_db().write_schemas(batch)
+                self.write_commit_batch(self.ledger_db.transaction_db(), batch)
             })
     }
 
@@ -1134,9 +1418,7 @@ impl AptosDB {
         let _timer = OTHER_TIMERS_SECONDS
             .with_label_values(&["commit_transaction_accumulator___commit"])
             .start_timer();
-        self.ledger_db
-            .transaction_accumulator_db()
-            .write_schemas(batch)?;
+        self.write_commit_batch(self.ledger_db.transaction_accumulator_db(), batch)?;
 
         Ok(root_hash)
     }
@@ -1169,7 +1451,7 @@ impl AptosDB {
         let _timer = OTHER_TIMERS_SECONDS
             .with_label_values(&["commit_transaction_infos___commit"])
             .start_timer();
-        self.ledger_db.transaction_info_db().write_schemas(batch)
+        self.write_commit_batch(self.ledger_db.transaction_info_db(), batch)
     }
 
     fn commit_write_sets(
@@ -1198,14 +1480,26 @@ impl AptosDB {
         let _timer = OTHER_TIMERS_SECONDS
             .with_label_values(&["commit_write_sets___commit"])
             .start_timer();
-        self.ledger_db.write_set_db().write_schemas(batch)
+        self.write_commit_batch(self.ledger_db.write_set_db(), batch)
+    }
+
+    /// Writes a batch of a commit, without syncing it if group commit is enabled, in which case
+    /// the group committer syncs it later.
+    fn write_commit_batch(&self, db: &DB, batch: SchemaBatch) -> Result<()> {
+        if self.group_committer.is_some() {
+            db.write_schemas_relaxed(batch)
+        } else {
+            db.write_schemas(batch)
+        }
     }
 
     fn commit_ledger_info(
         &self,
         last_version: Version,
+        num_txns: usize,
         new_root_hash: HashValue,
         ledger_info_with_sigs: Option<&LedgerInfoWithSignatures>,
+        force_sync: bool,
     ) -> Result<()> {
         let _timer = OTHER_TIMERS_SECONDS
             .with_label_values(&["commit_ledger_info"])
@@ -1236,6 +1530,18 @@ impl AptosDB {
             self.ledger_store.put_ledger_info(x, &ledger_batch)?;
         }
 
+        if let Some(group_committer) = &self.group_committer {
+            // The group committer persists the progress once the data of the commit is synced.
+            self.ledger_db
+                .metadata_db()
+                .write_schemas_relaxed(ledger_batch)?;
+            return group_committer.commit(
+                last_version,
+                num_txns,
+                force_sync || ledger_info_with_sigs.is_some(),
+            );
+        }
+
         ledger_batch.put::<DbMetadataSchema>(
             &DbMetadataKey::OverallCommitProgress,
             &DbMetadataValue::Version(last_version),
@@ -1272,8 +1578,16 @@ impl AptosDB {
             let _timer = OTHER_TIMERS_SECONDS
                 .with_label_values(&["indexer_index"])
                 .start_timer();
+            let transactions: Vec<_> = txns_to_commit.iter().map(|txn| txn.transaction()).collect();
             let write_sets: Vec<_> = txns_to_commit.iter().map(|txn| txn.write_set()).collect();
-            indexer.index(self.state_store.clone(), first_version, &write_sets)?;
+            let events: Vec<_> = txns_to_commit.iter().map(|txn| txn.events()).collect();
+            indexer.index(
+                self.state_store.clone(),
+                first_version,
+                &transactions,
+                &write_sets,
+                &events,
+            )?;
         }
 
         // Once everything is successfully persisted, update the latest in-memory ledger info.
@@ -1287,6 +1601,63 @@ impl AptosDB {
         Ok(())
     }
 
+    fn read_transaction(&self, version: Version) -> Result<Transaction> {
+        self.read_from_tiers(
+            version,
+            |cold_tier| cold_tier.get_transaction(version),
+            || self.transaction_store.get_transaction(version),
+        )
+    }
+
+    fn read_events(&self, version: Version) -> Result<Vec<ContractEvent>> {
+        self.read_from_tiers(
+            version,
+            |cold_tier| cold_tier.get_events(version),
+            || self.event_store.get_events_by_version(version),
+        )
+    }
+
+    fn read_event(&self, version: Version, index: u64) -> Result<ContractEvent> {
+        self.read_from_tiers(
+            version,
+            |cold_tier| cold_tier.get_event(version, index),
+            || {
+                self.event_store
+                    .get_event_by_version_and_index(version, index)
+            },
+        )
+    }
+
+    fn read_write_set(&self, version: Version) -> Result<WriteSet> {
+        self.read_from_tiers(
+            version,
+            |cold_tier| cold_tier.get_write_set(version),
+            || self.transaction_store.get_write_set(version),
+        )
+    }
+
+    /// Reads from the cold tier if the data at `version` has been moved there, otherwise from
+    /// local disk.
+    fn read_from_tiers<T>(
+        &self,
+        version: Version,
+        read_cold: impl Fn(&ColdTier) -> Result<T>,
+        read_hot: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        match &self.cold_tier {
+            Some(cold_tier) if cold_tier.contains(version) => read_cold(cold_tier),
+            // The data might have been moved after the check above.
+            Some(cold_tier) => read_hot().or_else(|err| {
+                if cold_tier.contains(version) {
+                    read_cold(cold_tier)
+                } else {
+                    Err(err)
+                }
+            }),
+            None => read_hot(),
+        }
+    }
+
     fn error_if_ledger_pruned(&self, data_type: &str, version: Version) -> Result<()> {
         let min_readable_version = self.ledger_pruner.get_min_readable_version();
         ensure!(
@@ -1353,6 +1724,41 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_epoch_ending_ledger_infos_bcs(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<Box<dyn Iterator<Item = Result<Vec<u8>>> + '_>> {
+        gauged_api("get_epoch_ending_ledger_infos_bcs", || {
+            self.ensure_epoch_ending_range(start_epoch, end_epoch)?;
+            let mut iter = self
+                .ledger_store
+                .get_epoch_ending_ledger_info_iter(start_epoch, end_epoch)?;
+            let mut next_epoch = start_epoch;
+            Ok(Box::new(std::iter::from_fn(move || {
+                if next_epoch >= end_epoch {
+                    return None;
+                }
+                let res = match iter.next() {
+                    Some(Ok(li)) => bcs::to_bytes(&li).map_err(Into::into),
+                    Some(Err(err)) => Err(err),
+                    None => Err(format_err!(
+                        "DB corruption: missing epoch ending ledger info for epoch {}",
+                        next_epoch,
+                    )),
+                };
+                // Stop after the first error.
+                next_epoch = if res.is_ok() {
+                    next_epoch + 1
+                } else {
+                    end_epoch
+                };
+                Some(res)
+            }))
+                as Box<dyn Iterator<Item = Result<Vec<u8>>> + '_>)
+        })
+    }
+
     fn get_prefixed_state_value_iterator(
         &self,
         key_prefix: &StateKeyPrefix,
@@ -1481,7 +1887,7 @@ impl DbReader for AptosDB {
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
 
             let txns = (start_version..start_version + limit)
-                .map(|version| self.transaction_store.get_transaction(version))
+                .map(|version| self.read_transaction(version))
                 .collect::<Result<Vec<_>>>()?;
             let txn_infos = (start_version..start_version + limit)
                 .map(|version| self.ledger_store.get_transaction_info(version))
@@ -1489,7 +1895,7 @@ impl DbReader for AptosDB {
             let events = if fetch_events {
                 Some(
                     (start_version..start_version + limit)
-                        .map(|version| self.event_store.get_events_by_version(version))
+                        .map(|version| self.read_events(version))
                         .collect::<Result<Vec<_>>>()?,
                 )
             } else {
@@ -1560,9 +1966,9 @@ impl DbReader for AptosDB {
             let (txn_infos, txns_and_outputs) = (start_version..start_version + limit)
                 .map(|version| {
                     let txn_info = self.ledger_store.get_transaction_info(version)?;
-                    let events = self.event_store.get_events_by_version(version)?;
-                    let write_set = self.transaction_store.get_write_set(version)?;
-                    let txn = self.transaction_store.get_transaction(version)?;
+                    let events = self.read_events(version)?;
+                    let write_set = self.read_write_set(version)?;
+                    let txn = self.read_transaction(version)?;
                     let txn_output = TransactionOutput::new(
                         write_set,
                         events,
@@ -1613,10 +2019,18 @@ impl DbReader for AptosDB {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
             self.error_if_ledger_pruned("Transaction", start_version)?;
 
-            let iter = self
-                .transaction_store
-                .get_transaction_iter(start_version, limit as usize)?;
-            Ok(Box::new(iter) as Box<dyn Iterator<Item = Result<Transaction>> + '_>)
+            chain_cold_tier_iter(
+                self.cold_tier.as_deref(),
+                start_version,
+                limit,
+                ColdTier::get_transaction,
+                |start_version, limit| {
+                    let iter = self
+                        .transaction_store
+                        .get_transaction_iter(start_version, limit)?;
+                    Ok(Box::new(iter) as Box<dyn Iterator<Item = Result<Transaction>> + '_>)
+                },
+            )
         })
     }
 
@@ -1645,13 +2059,21 @@ impl DbReader for AptosDB {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
             self.error_if_ledger_pruned("Transaction", start_version)?;
 
-            let iter = self
-                .event_store
-                .get_events_by_version_iter(start_version, limit as usize)?;
-            Ok(Box::new(iter)
-                as Box<
-                    dyn Iterator<Item = Result<Vec<ContractEvent>>> + '_,
-                >)
+            chain_cold_tier_iter(
+                self.cold_tier.as_deref(),
+                start_version,
+                limit,
+                ColdTier::get_events,
+                |start_version, limit| {
+                    let iter = self
+                        .event_store
+                        .get_events_by_version_iter(start_version, limit)?;
+                    Ok(Box::new(iter)
+                        as Box<
+                            dyn Iterator<Item = Result<Vec<ContractEvent>>> + '_,
+                        >)
+                },
+            )
         })
     }
 
@@ -1664,10 +2086,18 @@ impl DbReader for AptosDB {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
             self.error_if_ledger_pruned("Transaction", start_version)?;
 
-            let iter = self
-                .transaction_store
-                .get_write_set_iter(start_version, limit as usize)?;
-            Ok(Box::new(iter) as Box<dyn Iterator<Item = Result<WriteSet>> + '_>)
+            chain_cold_tier_iter(
+                self.cold_tier.as_deref(),
+                start_version,
+                limit,
+                ColdTier::get_write_set,
+                |start_version, limit| {
+                    let iter = self
+                        .transaction_store
+                        .get_write_set_iter(start_version, limit)?;
+                    Ok(Box::new(iter) as Box<dyn Iterator<Item = Result<WriteSet>> + '_>)
+                },
+            )
         })
     }
 
@@ -1769,6 +2199,18 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_value_proofs(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<SparseMerkleProofExt>> {
+        gauged_api("get_state_value_proofs", || {
+            self.error_if_state_merkle_pruned("State merkle", version)?;
+
+            self.state_store.get_state_value_proofs(state_keys, version)
+        })
+    }
+
     fn get_state_value_with_proof_by_version_ext(
         &self,
         state_store_key: &StateKey,
@@ -1971,6 +2413,22 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_iterator(
+        &self,
+        version: Version,
+        start_hashed_key: HashValue,
+        key_prefix: Option<StateKeyPrefix>,
+    ) -> Result<StateIterator<'_>> {
+        gauged_api("get_state_iterator", || {
+            self.error_if_state_merkle_pruned("State merkle", version)?;
+            Ok(StateIterator::new(
+                self.state_store
+                    .get_state_item_iter(version, start_hashed_key)?,
+                key_prefix,
+            ))
+        })
+    }
+
     fn is_state_merkle_pruner_enabled(&self) -> Result<bool> {
         gauged_api("is_state_merkle_pruner_enabled", || {
             Ok(self
@@ -2003,6 +2461,18 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_first_state_kv_version(&self) -> Result<Version> {
+        gauged_api("get_first_state_kv_version", || {
+            Ok(self.state_store.state_kv_pruner.get_min_readable_version())
+        })
+    }
+
+    fn get_state_kv_prune_window(&self) -> Result<usize> {
+        gauged_api("get_state_kv_prune_window", || {
+            Ok(self.state_store.state_kv_pruner.get_prune_window() as usize)
+        })
+    }
+
     fn get_table_info(&self, handle: TableHandle) -> Result<TableInfo> {
         gauged_api("get_table_info", || {
             self.get_table_info_option(handle)?
@@ -2015,6 +2485,66 @@ impl DbReader for AptosDB {
         self.indexer.is_some()
     }
 
+    fn get_events_by_field(
+        &self,
+        event_type: &TypeTag,
+        field: &IdentStr,
+        value: &[u8],
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        gauged_api("get_events_by_field", || {
+            error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+            self.error_if_ledger_pruned("Event", start_version)?;
+            let indexer = match &self.indexer {
+                Some(indexer) => indexer,
+                None => bail!("Indexer not enabled."),
+            };
+
+            indexer
+                .get_events_by_field(event_type, field, value, start_version, limit)?
+                .into_iter()
+                .take_while(|(version, _index)| *version <= ledger_version)
+                .map(|(version, index)| {
+                    Ok(EventWithVersion::new(
+                        version,
+                        self.read_event(version, index)?,
+                    ))
+                })
+                .collect()
+        })
+    }
+
+    fn account_transaction_index_enabled(&self) -> bool {
+        self.indexer
+            .as_ref()
+            .map_or(false, |indexer| indexer.account_transaction_index_enabled())
+    }
+
+    fn get_account_transaction_versions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        gauged_api("get_account_transaction_versions", || {
+            error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+            self.error_if_ledger_pruned("Transaction", start_version)?;
+            let indexer = match &self.indexer {
+                Some(indexer) => indexer,
+                None => bail!("Indexer not enabled."),
+            };
+
+            Ok(indexer
+                .get_account_transaction_versions(address, start_version, limit)?
+                .into_iter()
+                .take_while(|version| *version <= ledger_version)
+                .collect())
+        })
+    }
+
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         gauged_api("get_state_storage_usage", || {
             if let Some(v) = version {
@@ -2023,6 +2553,29 @@ impl DbReader for AptosDB {
             self.state_store.get_usage(version)
         })
     }
+
+    fn get_corrupted_ledger_ranges(&self) -> Result<Vec<Range<Version>>> {
+        gauged_api("get_corrupted_ledger_ranges", || {
+            Ok(self.get_corrupted_ledger_ranges_impl())
+        })
+    }
+
+    fn get_storage_footprint(&self, owner: &StorageFootprintOwner) -> Result<StorageFootprint> {
+        gauged_api("get_storage_footprint", || {
+            self.state_store.get_storage_footprint(owner)
+        })
+    }
+
+    fn get_top_storage_footprints(
+        &self,
+        kind: StorageFootprintOwnerKind,
+        limit: usize,
+    ) -> Result<Vec<(StorageFootprintOwner, StorageFootprint)>> {
+        gauged_api("get_top_storage_footprints", || {
+            error_if_too_many_requested(limit as u64, MAX_REQUEST_LIMIT)?;
+            self.state_store.get_top_storage_footprints(kind, limit)
+        })
+    }
 }
 
 impl DbWriter for AptosDB {
@@ -2076,15 +2629,25 @@ impl DbWriter for AptosDB {
             {
                 let mut buffered_state = self.state_store.buffered_state().lock();
                 let last_version = first_version + txns_to_commit.len() as u64 - 1;
-
-                self.commit_ledger_info(last_version, new_root_hash, ledger_info_with_sigs)?;
+                let sync_commit = sync_commit
+                    || txns_to_commit
+                        .last()
+                        .map_or(false, |txn_to_commit| txn_to_commit.is_reconfig());
+
+                self.commit_ledger_info(
+                    last_version,
+                    txns_to_commit.len(),
+                    new_root_hash,
+                    ledger_info_with_sigs,
+                    sync_commit,
+                )?;
 
                 if !txns_to_commit.is_empty() {
                     let _timer = OTHER_TIMERS_SECONDS.timer_with(&["buffered_state___update"]);
                     buffered_state.update(
                         state_updates_until_last_checkpoint,
                         latest_in_memory_state,
-                        sync_commit || txns_to_commit.last().unwrap().is_reconfig(),
+                        sync_commit,
                     )?;
                 }
             }
@@ -2223,6 +2786,22 @@ impl DbWriter for AptosDB {
             Ok(())
         })
     }
+
+    fn flush_pending_commits(&self) -> Result<()> {
+        gauged_api("flush_pending_commits", || match &self.group_committer {
+            Some(group_committer) => group_committer.flush(),
+            None => Ok(()),
+        })
+    }
+
+    fn repair_ledger_data(
+        &self,
+        output_list_with_proof: TransactionOutputListWithProof,
+    ) -> Result<()> {
+        gauged_api("repair_ledger_data", || {
+            self.repair_ledger_data_impl(output_list_with_proof)
+        })
+    }
 }
 
 // Convert requested range and order to a range in ascending order.
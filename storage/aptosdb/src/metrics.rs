@@ -3,8 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    exponential_buckets, register_histogram_vec, register_int_counter, register_int_gauge,
-    register_int_gauge_vec, HistogramVec, IntCounter, IntGauge, IntGaugeVec,
+    exponential_buckets, register_gauge_vec, register_histogram, register_histogram_vec,
+    register_int_counter, register_int_gauge, register_int_gauge_vec, GaugeVec, Histogram,
+    HistogramVec, IntCounter, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -52,6 +53,38 @@ pub static NEXT_BLOCK_EPOCH: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static GROUP_COMMIT_LATENCY_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        // metric name
+        "aptos_storage_group_commit_latency_seconds",
+        // metric description
+        "Time from the first commit of a group commit to the group being synced to disk.",
+        exponential_buckets(/*start=*/ 1e-4, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
+    )
+    .unwrap()
+});
+
+pub static GROUP_COMMIT_SIZE: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        // metric name
+        "aptos_storage_group_commit_size",
+        // metric description
+        "Number of commits and transactions synced to disk together by a group commit.",
+        // metric labels (dimensions)
+        &["kind"],
+        exponential_buckets(/*start=*/ 1.0, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
+    )
+    .unwrap()
+});
+
+pub static COLD_TIER_ARCHIVED_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_storage_cold_tier_archived_version",
+        "Transactions, events and write sets before this version are in the cold tier."
+    )
+    .unwrap()
+});
+
 pub static STATE_ITEMS: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!("aptos_storage_state_items", "Total number of state items.").unwrap()
 });
@@ -155,6 +188,18 @@ pub static ROCKSDB_PROPERTIES: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static ROCKSDB_CF_AMPLIFICATION: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        // metric name
+        "aptos_rocksdb_cf_amplification",
+        // metric description
+        "Read and write amplification of rocksdb column families",
+        // metric labels (dimensions)
+        &["cf_name", "kind"]
+    )
+    .unwrap()
+});
+
 // Async committer gauges:
 pub(crate) static LATEST_SNAPSHOT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
@@ -172,6 +217,14 @@ pub(crate) static LATEST_CHECKPOINT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+pub(crate) static LEDGER_CORRUPTED_VERSIONS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_storage_ledger_corrupted_versions",
+        "Number of versions whose ledger data failed the checksum verification and hasn't been repaired."
+    )
+    .unwrap()
+});
+
 // Backup progress gauges:
 
 pub(crate) static BACKUP_EPOCH_ENDING_EPOCH: Lazy<IntGauge> = Lazy::new(|| {
@@ -3,6 +3,7 @@
 
 use crate::schema::{
     db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+    ledger_checksum::LedgerChecksumSchema,
     version_data::VersionDataSchema,
 };
 use anyhow::{anyhow, Result};
@@ -48,6 +49,18 @@ impl LedgerMetadataPruner {
         for version in current_progress..target_version {
             batch.delete::<VersionDataSchema>(&version)?;
         }
+        // A checksum can't be verified once part of its versions is pruned.
+        let mut iter = self
+            .ledger_metadata_db
+            .iter::<LedgerChecksumSchema>(ReadOptions::default())?;
+        iter.seek(&current_progress)?;
+        for item in iter {
+            let (first_version, _) = item?;
+            if first_version >= target_version {
+                break;
+            }
+            batch.delete::<LedgerChecksumSchema>(&first_version)?;
+        }
         batch.put::<DbMetadataSchema>(
             &DbMetadataKey::LedgerPrunerProgress,
             &DbMetadataValue::Version(target_version),
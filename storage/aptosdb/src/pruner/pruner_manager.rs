@@ -35,6 +35,13 @@ pub trait PrunerManager: Sync {
 
     fn is_pruning_pending(&self) -> bool;
 
+    /// Sets pruner target version according to `latest_version` right away, instead of waiting
+    /// for a full pruning batch to accumulate.
+    fn force_set_pruner_target_db_version(&self, latest_version: Version);
+
+    /// Returns the progress and the target version of the pruner, if the pruner is enabled.
+    fn get_pruning_progress(&self) -> Option<(Version, Version)>;
+
     /// (For tests only.) Notifies the worker thread and waits for it to finish its job by polling
     /// an internal counter.
     #[cfg(test)]
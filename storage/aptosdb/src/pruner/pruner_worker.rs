@@ -99,6 +99,14 @@ impl PrunerWorker {
     pub fn is_pruning_pending(&self) -> bool {
         self.inner.pruner.is_pruning_pending()
     }
+
+    /// Returns the progress and the target version of the pruner.
+    pub fn get_progress(&self) -> (Version, Version) {
+        (
+            self.inner.pruner.progress(),
+            self.inner.pruner.target_version(),
+        )
+    }
 }
 
 impl Drop for PrunerWorker {
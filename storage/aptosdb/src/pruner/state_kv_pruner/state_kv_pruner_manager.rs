@@ -71,6 +71,18 @@ impl PrunerManager for StateKvPrunerManager {
             .map_or(false, |w| w.is_pruning_pending())
     }
 
+    fn force_set_pruner_target_db_version(&self, latest_version: Version) {
+        if self.is_pruner_enabled()
+            && latest_version.saturating_sub(self.prune_window) > self.get_min_readable_version()
+        {
+            self.set_pruner_target_db_version(latest_version);
+        }
+    }
+
+    fn get_pruning_progress(&self) -> Option<(Version, Version)> {
+        self.pruner_worker.as_ref().map(PrunerWorker::get_progress)
+    }
+
     #[cfg(test)]
     fn set_worker_target_version(&self, target_version: Version) {
         self.pruner_worker
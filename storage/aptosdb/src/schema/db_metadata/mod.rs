@@ -66,6 +66,8 @@ pub enum DbMetadataKey {
     EpochEndingStateMerkleShardPrunerProgress(ShardId),
     StateKvShardPrunerProgress(ShardId),
     StateMerkleShardRestoreProgress(ShardId, Version),
+    ColdTierProgress,
+    StorageFootprintProgress,
 }
 
 define_schema!(
@@ -0,0 +1,66 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the checksums of the ledger data of each
+//! commit, i.e. of the transactions, events, write sets and transaction infos of its versions.
+//!
+//! ```text
+//! |<-----key----->|<----------value---------->|
+//! | first_version | num_versions | checksum   |
+//! ```
+//!
+//! `Version` is serialized in big endian so that records in RocksDB will be in order of it's
+//! numeric value.
+
+use super::LEDGER_CHECKSUM_CF_NAME;
+use crate::schema::ensure_slice_len_eq;
+use anyhow::Result;
+use aptos_crypto::HashValue;
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::transaction::Version;
+use byteorder::{BigEndian, ReadBytesExt};
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest_derive::Arbitrary;
+use serde::{Deserialize, Serialize};
+use std::mem::size_of;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub struct LedgerChecksum {
+    pub num_versions: u64,
+    pub checksum: HashValue,
+}
+
+define_schema!(
+    LedgerChecksumSchema,
+    Version,
+    LedgerChecksum,
+    LEDGER_CHECKSUM_CF_NAME
+);
+
+impl KeyCodec<LedgerChecksumSchema> for Version {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Version>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<LedgerChecksumSchema> for LedgerChecksum {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
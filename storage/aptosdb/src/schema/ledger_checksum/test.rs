@@ -0,0 +1,16 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use aptos_types::transaction::Version;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_encode_decode(version in any::<Version>(), checksum in any::<LedgerChecksum>()) {
+        assert_encode_decode::<LedgerChecksumSchema>(&version, &checksum);
+    }
+}
+
+test_no_panic_decoding!(LedgerChecksumSchema);
@@ -13,12 +13,14 @@ pub(crate) mod event_accumulator;
 pub(crate) mod event_by_key;
 pub(crate) mod event_by_version;
 pub(crate) mod jellyfish_merkle_node;
+pub(crate) mod ledger_checksum;
 pub(crate) mod ledger_info;
 pub(crate) mod stale_node_index;
 pub(crate) mod stale_node_index_cross_epoch;
 pub(crate) mod stale_state_value_index;
 pub(crate) mod state_value;
 pub(crate) mod state_value_index;
+pub(crate) mod storage_footprint;
 pub(crate) mod transaction;
 pub(crate) mod transaction_accumulator;
 pub(crate) mod transaction_by_account;
@@ -37,12 +39,14 @@ pub const EVENT_BY_KEY_CF_NAME: ColumnFamilyName = "event_by_key";
 pub const EVENT_BY_VERSION_CF_NAME: ColumnFamilyName = "event_by_version";
 pub const EVENT_CF_NAME: ColumnFamilyName = "event";
 pub const JELLYFISH_MERKLE_NODE_CF_NAME: ColumnFamilyName = "jellyfish_merkle_node";
+pub const LEDGER_CHECKSUM_CF_NAME: ColumnFamilyName = "ledger_checksum";
 pub const LEDGER_INFO_CF_NAME: ColumnFamilyName = "ledger_info";
 pub const STALE_NODE_INDEX_CF_NAME: ColumnFamilyName = "stale_node_index";
 pub const STALE_NODE_INDEX_CROSS_EPOCH_CF_NAME: ColumnFamilyName = "stale_node_index_cross_epoch";
 pub const STALE_STATE_VALUE_INDEX_CF_NAME: ColumnFamilyName = "stale_state_value_index";
 pub const STATE_VALUE_CF_NAME: ColumnFamilyName = "state_value";
 pub const STATE_VALUE_INDEX_CF_NAME: ColumnFamilyName = "state_value_index";
+pub const STORAGE_FOOTPRINT_CF_NAME: ColumnFamilyName = "storage_footprint";
 pub const TRANSACTION_CF_NAME: ColumnFamilyName = "transaction";
 pub const TRANSACTION_ACCUMULATOR_CF_NAME: ColumnFamilyName = "transaction_accumulator";
 pub const TRANSACTION_BY_ACCOUNT_CF_NAME: ColumnFamilyName = "transaction_by_account";
@@ -86,6 +90,7 @@ pub mod fuzzing {
             assert_no_panic_decoding::<super::jellyfish_merkle_node::JellyfishMerkleNodeSchema>(
                 data,
             );
+            assert_no_panic_decoding::<super::ledger_checksum::LedgerChecksumSchema>(data);
             assert_no_panic_decoding::<super::ledger_info::LedgerInfoSchema>(data);
             assert_no_panic_decoding::<super::db_metadata::DbMetadataSchema>(data);
             assert_no_panic_decoding::<super::stale_node_index::StaleNodeIndexSchema>(data);
@@ -97,6 +102,7 @@ pub mod fuzzing {
             );
             assert_no_panic_decoding::<super::state_value::StateValueSchema>(data);
             assert_no_panic_decoding::<super::state_value_index::StateValueIndexSchema>(data);
+            assert_no_panic_decoding::<super::storage_footprint::StorageFootprintSchema>(data);
             assert_no_panic_decoding::<super::transaction::TransactionSchema>(data);
             assert_no_panic_decoding::<super::transaction_accumulator::TransactionAccumulatorSchema>(
                 data,
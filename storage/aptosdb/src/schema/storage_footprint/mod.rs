@@ -0,0 +1,62 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the storage footprint of the accounts,
+//! modules and tables, i.e. the number of state items attributed to each of them and their total
+//! size, as of the version in `DbMetadataKey::StorageFootprintProgress`.
+//!
+//! ```text
+//! |<--key-->|<-------value------->|
+//! |  owner  | storage footprint   |
+//! ```
+//!
+//! The owner is BCS serialized, so that the records of the same kind of owner are adjacent, and
+//! can be seeked to by the kind.
+
+use super::STORAGE_FOOTPRINT_CF_NAME;
+use anyhow::Result;
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, SeekKeyCodec, ValueCodec},
+};
+use aptos_types::state_store::storage_footprint::{
+    StorageFootprint, StorageFootprintOwner, StorageFootprintOwnerKind,
+};
+
+define_schema!(
+    StorageFootprintSchema,
+    StorageFootprintOwner,
+    StorageFootprint,
+    STORAGE_FOOTPRINT_CF_NAME
+);
+
+impl KeyCodec<StorageFootprintSchema> for StorageFootprintOwner {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+impl SeekKeyCodec<StorageFootprintSchema> for StorageFootprintOwnerKind {
+    fn encode_seek_key(&self) -> Result<Vec<u8>> {
+        // The variants are in the same order as the ones of `StorageFootprintOwner`, so this is
+        // the prefix of the keys of this kind.
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+}
+
+impl ValueCodec<StorageFootprintSchema> for StorageFootprint {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
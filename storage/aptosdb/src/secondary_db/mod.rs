@@ -0,0 +1,101 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This file defines [`SecondaryAptosDB`], giving read only access to the AptosDB of a running
+//! node from another process, e.g. an indexer or an analytics job, without impacting the node.
+
+use crate::AptosDB;
+use anyhow::Result;
+use aptos_config::config::{RocksdbConfigs, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_storage_interface::DbReader;
+use std::{
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc},
+    thread,
+    thread::JoinHandle,
+    time::Duration,
+};
+
+/// An [`AptosDB`] opened as RocksDB secondary instances, which catches up with the writes of the
+/// node every `catch_up_interval`.
+pub struct SecondaryAptosDB {
+    db: Arc<AptosDB>,
+    _catch_up_thread: CatchUpThread,
+}
+
+impl SecondaryAptosDB {
+    /// Opens the DB at `db_root_path`. `secondary_root` must be a directory owned by this process,
+    /// where the secondary instances keep their info logs.
+    pub fn open<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        secondary_root: PathBuf,
+        rocksdb_configs: RocksdbConfigs,
+        catch_up_interval: Duration,
+    ) -> Result<Self> {
+        let db = Arc::new(AptosDB::open_as_secondary(
+            db_root_path,
+            secondary_root,
+            rocksdb_configs,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?);
+        Ok(Self {
+            _catch_up_thread: CatchUpThread::new(Arc::clone(&db), catch_up_interval),
+            db,
+        })
+    }
+}
+
+impl DbReader for SecondaryAptosDB {
+    fn get_read_delegatee(&self) -> &dyn DbReader {
+        self.db.as_ref()
+    }
+}
+
+struct CatchUpThread {
+    sender: Mutex<mpsc::Sender<()>>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl CatchUpThread {
+    fn new(db: Arc<AptosDB>, interval: Duration) -> Self {
+        let (send, recv) = mpsc::channel();
+        let join_handle = Some(
+            thread::Builder::new()
+                .name("db-catch-up".to_string())
+                .spawn(move || loop {
+                    match recv.recv_timeout(interval) {
+                        Ok(_) => break,
+                        Err(mpsc::RecvTimeoutError::Timeout) => (),
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                    if let Err(e) = db.try_catch_up_with_primary() {
+                        warn!(
+                            error = ?e,
+                            "Catching up with the primary DB failed."
+                        );
+                    }
+                })
+                .expect("Failed to spawn the DB catch up thread."),
+        );
+        Self {
+            sender: Mutex::new(send),
+            join_handle,
+        }
+    }
+}
+
+impl Drop for CatchUpThread {
+    fn drop(&mut self) {
+        self.sender.lock().send(()).unwrap();
+        self.join_handle
+            .take()
+            .expect("DB catch up thread must exist.")
+            .join()
+            .expect("DB catch up thread should join peacefully.");
+    }
+}
+
+#[cfg(test)]
+mod test;
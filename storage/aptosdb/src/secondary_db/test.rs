@@ -0,0 +1,82 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    test_helper::{arb_blocks_to_commit_with_block_nums, update_in_memory_state},
+    AptosDB,
+};
+use aptos_config::config::{RocksdbConfigs, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD};
+use aptos_storage_interface::DbReader;
+use aptos_temppath::TempPath;
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(3))]
+
+    #[test]
+    fn test_secondary_catch_up((blocks, sharding) in arb_blocks_to_commit_with_block_nums(4, 10)) {
+        let tmp_dir = TempPath::new();
+        let secondary_dir = TempPath::new();
+        let db = if sharding {
+            AptosDB::new_for_test_with_sharding(&tmp_dir, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD)
+        } else {
+            AptosDB::new_for_test(&tmp_dir)
+        };
+        let mut in_memory_state = db.state_store.buffered_state().lock().current_state().clone();
+        let mut version = 0;
+        let mut commit = |(txns_to_commit, ledger_info_with_sigs): &(Vec<_>, _)| {
+            update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+            db.save_transactions_for_test(
+                txns_to_commit,
+                version,
+                version.checked_sub(1),
+                Some(ledger_info_with_sigs),
+                true, /* sync_commit */
+                in_memory_state.clone(),
+            )
+            .unwrap();
+            version += txns_to_commit.len() as u64;
+            version
+        };
+
+        let (first_blocks, remaining_blocks) = blocks.split_at(blocks.len() / 2);
+        let mut num_txns = 0;
+        for block in first_blocks {
+            num_txns = commit(block);
+        }
+
+        let secondary = AptosDB::open_as_secondary(
+            tmp_dir.path(),
+            secondary_dir.path().to_path_buf(),
+            RocksdbConfigs {
+                enable_storage_sharding: sharding,
+                ..Default::default()
+            },
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )
+        .unwrap();
+        prop_assert_eq!(secondary.get_latest_version().unwrap(), num_txns - 1);
+
+        for block in remaining_blocks {
+            num_txns = commit(block);
+        }
+        // Doesn't see the new writes until caught up.
+        prop_assert!(secondary.get_latest_version().unwrap() < num_txns - 1);
+        secondary.try_catch_up_with_primary().unwrap();
+
+        let latest_version = secondary.get_latest_version().unwrap();
+        prop_assert_eq!(latest_version, num_txns - 1);
+        prop_assert_eq!(
+            secondary.get_latest_ledger_info().unwrap(),
+            db.get_latest_ledger_info().unwrap()
+        );
+        prop_assert_eq!(
+            secondary.get_transactions(0, num_txns, latest_version, true).unwrap(),
+            db.get_transactions(0, num_txns, latest_version, true).unwrap()
+        );
+        prop_assert_eq!(
+            secondary.get_state_snapshot_before(num_txns).unwrap(),
+            db.get_state_snapshot_before(num_txns).unwrap()
+        );
+    }
+}
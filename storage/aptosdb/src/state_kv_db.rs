@@ -5,12 +5,15 @@
 
 use crate::{
     db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
-    db_options::{gen_state_kv_cfds, state_kv_db_column_families},
+    db_options::{
+        ensure_known_cfs_in_config, gen_secondary_rocksdb_options, gen_state_kv_cfds,
+        state_kv_db_column_families, OpenMode,
+    },
     utils::truncation_helper::{get_state_kv_commit_progress, truncate_state_kv_db_shards},
     NUM_STATE_SHARDS,
 };
-use anyhow::Result;
-use aptos_config::config::{RocksdbConfig, RocksdbConfigs};
+use anyhow::{bail, ensure, Result};
+use aptos_config::config::{RocksdbConfig, RocksdbConfigs, StateKvShardPathConfig};
 use aptos_experimental_runtimes::thread_manager::THREAD_MANAGER;
 use aptos_logger::prelude::info;
 use aptos_rocksdb_options::gen_rocksdb_options;
@@ -18,6 +21,7 @@ use aptos_schemadb::{SchemaBatch, DB};
 use aptos_types::transaction::Version;
 use arr_macro::arr;
 use std::{
+    fs,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -32,12 +36,10 @@ pub struct StateKvDb {
 }
 
 impl StateKvDb {
-    // TODO(grao): Support more flexible path to make it easier for people to put different shards
-    // on different disks.
     pub(crate) fn new<P: AsRef<Path>>(
         db_root_path: P,
         rocksdb_configs: RocksdbConfigs,
-        readonly: bool,
+        open_mode: &OpenMode,
         ledger_db: Arc<DB>,
     ) -> Result<Self> {
         let sharding = rocksdb_configs.enable_storage_sharding;
@@ -50,21 +52,33 @@ impl StateKvDb {
             });
         }
 
-        Self::open(db_root_path, rocksdb_configs.state_kv_db_config, readonly)
+        Self::open(
+            db_root_path,
+            rocksdb_configs.state_kv_db_config,
+            &rocksdb_configs.state_kv_shard_paths,
+            open_mode,
+        )
     }
 
     pub(crate) fn open<P: AsRef<Path>>(
         db_root_path: P,
         state_kv_db_config: RocksdbConfig,
-        readonly: bool,
+        shard_paths: &[StateKvShardPathConfig],
+        open_mode: &OpenMode,
     ) -> Result<Self> {
+        ensure_known_cfs_in_config(
+            &state_kv_db_config,
+            &state_kv_db_column_families(),
+            STATE_KV_DB_FOLDER_NAME,
+        )?;
+        Self::validate_shard_paths(db_root_path.as_ref(), shard_paths)?;
         let state_kv_metadata_db_path = Self::metadata_db_path(db_root_path.as_ref());
 
         let state_kv_metadata_db = Arc::new(Self::open_db(
             state_kv_metadata_db_path.clone(),
             STATE_KV_METADATA_DB_NAME,
             &state_kv_db_config,
-            readonly,
+            open_mode,
         )?);
 
         info!(
@@ -75,7 +89,12 @@ impl StateKvDb {
         let state_kv_db_shards = {
             let mut shard_id: usize = 0;
             arr![{
-                let db = Self::open_shard(db_root_path.as_ref(), shard_id as u8, &state_kv_db_config, readonly)?;
+                let db = Self::open_shard(
+                    Self::configured_db_shard_path(db_root_path.as_ref(), shard_paths, shard_id as u8),
+                    shard_id as u8,
+                    &state_kv_db_config,
+                    open_mode,
+                )?;
                 shard_id += 1;
                 Arc::new(db)
             }; 16]
@@ -87,6 +106,11 @@ impl StateKvDb {
             enabled_sharding: true,
         };
 
+        // A secondary instance can't write, and sees the shards ahead of the overall progress
+        // while the primary is committing.
+        if open_mode.is_secondary() {
+            return Ok(state_kv_db);
+        }
         if let Some(overall_kv_commit_progress) = get_state_kv_commit_progress(&state_kv_db)? {
             truncate_state_kv_db_shards(&state_kv_db, overall_kv_commit_progress, None)?;
         }
@@ -99,6 +123,37 @@ impl StateKvDb {
         version: Version,
         state_kv_metadata_batch: SchemaBatch,
         sharded_state_kv_batches: [SchemaBatch; NUM_STATE_SHARDS],
+    ) -> Result<()> {
+        self.commit_impl(
+            version,
+            state_kv_metadata_batch,
+            sharded_state_kv_batches,
+            true, /* sync */
+        )
+    }
+
+    /// Same as [`Self::commit`], but doesn't sync the writes to disk, see
+    /// [`DB::write_schemas_relaxed`] and [`Self::flush_wal`].
+    pub(crate) fn commit_relaxed(
+        &self,
+        version: Version,
+        state_kv_metadata_batch: SchemaBatch,
+        sharded_state_kv_batches: [SchemaBatch; NUM_STATE_SHARDS],
+    ) -> Result<()> {
+        self.commit_impl(
+            version,
+            state_kv_metadata_batch,
+            sharded_state_kv_batches,
+            false, /* sync */
+        )
+    }
+
+    fn commit_impl(
+        &self,
+        version: Version,
+        state_kv_metadata_batch: SchemaBatch,
+        sharded_state_kv_batches: [SchemaBatch; NUM_STATE_SHARDS],
+        sync: bool,
     ) -> Result<()> {
         THREAD_MANAGER.get_io_pool().scope(|s| {
             let mut batches = sharded_state_kv_batches.into_iter();
@@ -108,16 +163,35 @@ impl StateKvDb {
                     .expect("Not sufficient number of sharded state kv batches");
                 s.spawn(move |_| {
                     // TODO(grao): Consider propagating the error instead of panic, if necessary.
-                    self.commit_single_shard(version, shard_id as u8, state_kv_batch)
+                    self.commit_single_shard_impl(version, shard_id as u8, state_kv_batch, sync)
                         .unwrap_or_else(|err| panic!("Failed to commit shard {shard_id}: {err}."));
                 });
             }
         });
 
-        self.state_kv_metadata_db
-            .write_schemas(state_kv_metadata_batch)?;
+        state_kv_metadata_batch.put::<DbMetadataSchema>(
+            &DbMetadataKey::StateKvCommitProgress,
+            &DbMetadataValue::Version(version),
+        )?;
+        if sync {
+            self.state_kv_metadata_db
+                .write_schemas(state_kv_metadata_batch)
+        } else {
+            self.state_kv_metadata_db
+                .write_schemas_relaxed(state_kv_metadata_batch)
+        }
+    }
 
-        self.write_progress(version)
+    /// Syncs the writes done by [`Self::commit`] without `sync` to disk.
+    pub(crate) fn flush_wal(&self) -> Result<()> {
+        if !self.enabled_sharding {
+            // It's the ledger DB, which syncs on its own.
+            return Ok(());
+        }
+        for shard in &self.state_kv_db_shards {
+            shard.flush_wal()?;
+        }
+        self.state_kv_metadata_db.flush_wal()
     }
 
     pub(crate) fn write_progress(&self, version: Version) -> Result<()> {
@@ -138,7 +212,12 @@ impl StateKvDb {
         db_root_path: impl AsRef<Path>,
         cp_root_path: impl AsRef<Path>,
     ) -> Result<()> {
-        let state_kv_db = Self::open(db_root_path, RocksdbConfig::default(), false)?;
+        let state_kv_db = Self::open(
+            db_root_path,
+            RocksdbConfig::default(),
+            &[],
+            &OpenMode::ReadWrite,
+        )?;
         let cp_state_kv_db_path = cp_root_path.as_ref().join(STATE_KV_DB_FOLDER_NAME);
 
         info!("Creating state_kv_db checkpoint at: {cp_state_kv_db_path:?}");
@@ -163,6 +242,18 @@ impl StateKvDb {
         &self.state_kv_metadata_db
     }
 
+    /// See [`DB::try_catch_up_with_primary`]. Without sharding, this is the ledger DB, which
+    /// catches up on its own.
+    pub(crate) fn try_catch_up_with_primary(&self) -> Result<()> {
+        if !self.enabled_sharding {
+            return Ok(());
+        }
+        for shard in &self.state_kv_db_shards {
+            shard.try_catch_up_with_primary()?;
+        }
+        self.state_kv_metadata_db.try_catch_up_with_primary()
+    }
+
     pub(crate) fn db_shard(&self, shard_id: u8) -> &DB {
         &self.state_kv_db_shards[shard_id as usize]
     }
@@ -184,49 +275,199 @@ impl StateKvDb {
         version: Version,
         shard_id: u8,
         batch: SchemaBatch,
+    ) -> Result<()> {
+        self.commit_single_shard_impl(version, shard_id, batch, true /* sync */)
+    }
+
+    fn commit_single_shard_impl(
+        &self,
+        version: Version,
+        shard_id: u8,
+        batch: SchemaBatch,
+        sync: bool,
     ) -> Result<()> {
         batch.put::<DbMetadataSchema>(
             &DbMetadataKey::StateKvShardCommitProgress(shard_id as usize),
             &DbMetadataValue::Version(version),
         )?;
-        self.state_kv_db_shards[shard_id as usize].write_schemas(batch)
+        let shard = &self.state_kv_db_shards[shard_id as usize];
+        if sync {
+            shard.write_schemas(batch)
+        } else {
+            shard.write_schemas_relaxed(batch)
+        }
     }
 
-    fn open_shard<P: AsRef<Path>>(
-        db_root_path: P,
+    /// Moves the shards found under the DB root or any of `search_paths` to where `shard_paths`
+    /// places them, so that the DB can be opened with a changed `state_kv_shard_paths` config.
+    /// The DB must not be open. Returns the ids of the shards moved.
+    pub(crate) fn relocate_shards(
+        db_root_path: &Path,
+        shard_paths: &[StateKvShardPathConfig],
+        search_paths: &[PathBuf],
+    ) -> Result<Vec<u8>> {
+        let mut relocated = Vec::new();
+        for shard_id in 0..NUM_STATE_SHARDS as u8 {
+            let target = Self::configured_db_shard_path(db_root_path, shard_paths, shard_id);
+            if target.exists() {
+                continue;
+            }
+            let source = match Self::find_shard(db_root_path, shard_paths, search_paths, shard_id) {
+                Some(source) => source,
+                None => continue,
+            };
+            info!(
+                shard_id = shard_id,
+                source = source,
+                target = target,
+                "Relocating state kv shard."
+            );
+            Self::move_dir(&source, &target)?;
+            relocated.push(shard_id);
+        }
+        Ok(relocated)
+    }
+
+    /// Makes sure every shard is found where the config places it, instead of letting RocksDB
+    /// create an empty shard when a shard was configured elsewhere but not moved.
+    fn validate_shard_paths(
+        db_root_path: &Path,
+        shard_paths: &[StateKvShardPathConfig],
+    ) -> Result<()> {
+        // A new DB, nothing to validate.
+        if !Self::metadata_db_path(db_root_path).exists() {
+            return Ok(());
+        }
+        for shard_id in 0..NUM_STATE_SHARDS as u8 {
+            let path = Self::configured_db_shard_path(db_root_path, shard_paths, shard_id);
+            if path.exists() {
+                continue;
+            }
+            match Self::find_shard(db_root_path, shard_paths, &[], shard_id) {
+                Some(found) => bail!(
+                    "State kv shard {} is configured at {:?}, but found at {:?}. Run \
+                     `aptos-db-tool debug relocate-state-kv-shards` to move it.",
+                    shard_id,
+                    path,
+                    found
+                ),
+                None => bail!(
+                    "State kv shard {} is not found at {:?}, check state_kv_shard_paths.",
+                    shard_id,
+                    path
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks for an existing shard under the DB root, the configured shard paths and
+    /// `search_paths`.
+    fn find_shard(
+        db_root_path: &Path,
+        shard_paths: &[StateKvShardPathConfig],
+        search_paths: &[PathBuf],
+        shard_id: u8,
+    ) -> Option<PathBuf> {
+        std::iter::once(db_root_path)
+            .chain(
+                shard_paths
+                    .iter()
+                    .map(|shard_path| shard_path.path.as_path()),
+            )
+            .chain(search_paths.iter().map(PathBuf::as_path))
+            .map(|path| Self::db_shard_path(path, shard_id))
+            .find(|path| path.exists())
+    }
+
+    fn move_dir(source: &Path, target: &Path) -> Result<()> {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if fs::rename(source, target).is_ok() {
+            return Ok(());
+        }
+
+        // Across file systems, copy to a temporary directory first so that an interrupted copy
+        // doesn't leave a partial shard at the target.
+        let tmp = target.with_extension("relocating");
+        if tmp.exists() {
+            fs::remove_dir_all(&tmp)?;
+        }
+        Self::copy_dir(source, &tmp)?;
+        fs::rename(&tmp, target)?;
+        fs::remove_dir_all(source)?;
+        Ok(())
+    }
+
+    fn copy_dir(source: &Path, target: &Path) -> Result<()> {
+        fs::create_dir_all(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let target_path = target.join(entry.file_name());
+            if file_type.is_dir() {
+                Self::copy_dir(&entry.path(), &target_path)?;
+            } else {
+                ensure!(
+                    file_type.is_file(),
+                    "Unexpected file type of {:?}.",
+                    entry.path()
+                );
+                fs::copy(entry.path(), target_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn configured_db_shard_path(
+        db_root_path: &Path,
+        shard_paths: &[StateKvShardPathConfig],
+        shard_id: u8,
+    ) -> PathBuf {
+        let root = shard_paths
+            .iter()
+            .find(|shard_path| shard_path.shards.contains(&shard_id))
+            .map_or(db_root_path, |shard_path| shard_path.path.as_path());
+        Self::db_shard_path(root, shard_id)
+    }
+
+    fn open_shard(
+        path: PathBuf,
         shard_id: u8,
         state_kv_db_config: &RocksdbConfig,
-        readonly: bool,
+        open_mode: &OpenMode,
     ) -> Result<DB> {
         let db_name = format!("state_kv_db_shard_{}", shard_id);
-        Self::open_db(
-            Self::db_shard_path(db_root_path, shard_id),
-            &db_name,
-            state_kv_db_config,
-            readonly,
-        )
+        Self::open_db(path, &db_name, state_kv_db_config, open_mode)
     }
 
     fn open_db(
         path: PathBuf,
         name: &str,
         state_kv_db_config: &RocksdbConfig,
-        readonly: bool,
+        open_mode: &OpenMode,
     ) -> Result<DB> {
-        Ok(if readonly {
-            DB::open_cf_readonly(
+        Ok(match open_mode {
+            OpenMode::ReadWrite => DB::open_cf(
+                &gen_rocksdb_options(state_kv_db_config, false),
+                path,
+                name,
+                gen_state_kv_cfds(state_kv_db_config),
+            )?,
+            OpenMode::ReadOnly => DB::open_cf_readonly(
                 &gen_rocksdb_options(state_kv_db_config, true),
                 path,
                 name,
                 state_kv_db_column_families(),
-            )?
-        } else {
-            DB::open_cf(
-                &gen_rocksdb_options(state_kv_db_config, false),
+            )?,
+            OpenMode::Secondary { .. } => DB::open_cf_as_secondary(
+                &gen_secondary_rocksdb_options(state_kv_db_config),
                 path,
+                open_mode.secondary_path(name),
                 name,
-                gen_state_kv_cfds(state_kv_db_config),
-            )?
+                state_kv_db_column_families(),
+            )?,
         })
     }
 
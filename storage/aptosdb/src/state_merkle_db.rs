@@ -3,7 +3,10 @@
 
 use crate::{
     db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
-    db_options::{gen_state_merkle_cfds, state_merkle_db_column_families},
+    db_options::{
+        ensure_known_cfs_in_config, gen_secondary_rocksdb_options, gen_state_merkle_cfds,
+        state_merkle_db_column_families, OpenMode,
+    },
     lru_node_cache::LruNodeCache,
     metrics::NODE_CACHE_SECONDS,
     schema::jellyfish_merkle_node::JellyfishMerkleNodeSchema,
@@ -66,11 +69,16 @@ impl StateMerkleDb {
     pub(crate) fn new<P: AsRef<Path>>(
         db_root_path: P,
         rocksdb_configs: RocksdbConfigs,
-        readonly: bool,
+        open_mode: &OpenMode,
         max_nodes_per_lru_cache_shard: usize,
     ) -> Result<Self> {
         let sharding = rocksdb_configs.enable_storage_sharding;
         let state_merkle_db_config = rocksdb_configs.state_merkle_db_config;
+        ensure_known_cfs_in_config(
+            &state_merkle_db_config,
+            &state_merkle_db_column_families(),
+            STATE_MERKLE_DB_NAME,
+        )?;
         // TODO(grao): Currently when this value is set to 0 we disable both caches. This is
         // hacky, need to revisit.
         let enable_cache = max_nodes_per_lru_cache_shard > 0;
@@ -87,7 +95,7 @@ impl StateMerkleDb {
                 state_merkle_db_path,
                 STATE_MERKLE_DB_NAME,
                 &state_merkle_db_config,
-                readonly,
+                open_mode,
             )?);
             return Ok(Self {
                 state_merkle_metadata_db: Arc::clone(&db),
@@ -102,7 +110,7 @@ impl StateMerkleDb {
         Self::open(
             db_root_path,
             state_merkle_db_config,
-            readonly,
+            open_mode,
             enable_cache,
             version_caches,
             lru_cache,
@@ -167,7 +175,7 @@ impl StateMerkleDb {
         let state_merkle_db = Self::new(
             db_root_path,
             rocksdb_configs,
-            /*readonly=*/ false,
+            &OpenMode::ReadWrite,
             /*max_nodes_per_lru_cache_shard=*/ 0,
         )?;
         let cp_state_merkle_db_path = cp_root_path.as_ref().join(STATE_MERKLE_DB_FOLDER_NAME);
@@ -205,6 +213,16 @@ impl StateMerkleDb {
         Arc::clone(&self.state_merkle_metadata_db)
     }
 
+    /// See [`DB::try_catch_up_with_primary`].
+    pub(crate) fn try_catch_up_with_primary(&self) -> Result<()> {
+        if self.enable_sharding {
+            for shard in &self.state_merkle_db_shards {
+                shard.try_catch_up_with_primary()?;
+            }
+        }
+        self.state_merkle_metadata_db.try_catch_up_with_primary()
+    }
+
     pub(crate) fn db_shard(&self, shard_id: u8) -> &DB {
         &self.state_merkle_db_shards[shard_id as usize]
     }
@@ -247,6 +265,20 @@ impl StateMerkleDb {
         JellyfishMerkleTree::new(self).get_with_proof_ext(state_key.hash(), version)
     }
 
+    pub fn get_with_proof_ext_many(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<
+        Vec<(
+            Option<(HashValue, (StateKey, Version))>,
+            SparseMerkleProofExt,
+        )>,
+    > {
+        let keys: Vec<_> = state_keys.iter().map(|key| key.hash()).collect();
+        JellyfishMerkleTree::new(self).get_with_proof_ext_many(&keys, version)
+    }
+
     pub fn get_range_proof(
         &self,
         rightmost_key: HashValue,
@@ -540,7 +572,7 @@ impl StateMerkleDb {
     fn open<P: AsRef<Path>>(
         db_root_path: P,
         state_merkle_db_config: RocksdbConfig,
-        readonly: bool,
+        open_mode: &OpenMode,
         enable_cache: bool,
         version_caches: HashMap<Option<u8>, VersionedNodeCache>,
         lru_cache: LruNodeCache,
@@ -552,7 +584,7 @@ impl StateMerkleDb {
             state_merkle_metadata_db_path.clone(),
             STATE_MERKLE_METADATA_DB_NAME,
             &state_merkle_db_config,
-            readonly,
+            open_mode,
         )?);
 
         info!(
@@ -562,7 +594,7 @@ impl StateMerkleDb {
 
         let mut shard_id: usize = 0;
         let state_merkle_db_shards = arr![{
-            let db = Self::open_shard(db_root_path.as_ref(), shard_id as u8, &state_merkle_db_config, readonly)?;
+            let db = Self::open_shard(db_root_path.as_ref(), shard_id as u8, &state_merkle_db_config, open_mode)?;
             shard_id += 1;
             Arc::new(db)
         }; 16];
@@ -576,6 +608,11 @@ impl StateMerkleDb {
             lru_cache,
         };
 
+        // A secondary instance can't write, and sees the shards ahead of the overall progress
+        // while the primary is committing.
+        if open_mode.is_secondary() {
+            return Ok(state_merkle_db);
+        }
         if let Some(overall_state_merkle_commit_progress) =
             get_state_merkle_commit_progress(&state_merkle_db)?
         {
@@ -592,14 +629,14 @@ impl StateMerkleDb {
         db_root_path: P,
         shard_id: u8,
         state_merkle_db_config: &RocksdbConfig,
-        readonly: bool,
+        open_mode: &OpenMode,
     ) -> Result<DB> {
         let db_name = format!("state_merkle_db_shard_{}", shard_id);
         Self::open_db(
             Self::db_shard_path(db_root_path, shard_id),
             &db_name,
             state_merkle_db_config,
-            readonly,
+            open_mode,
         )
     }
 
@@ -607,22 +644,28 @@ impl StateMerkleDb {
         path: PathBuf,
         name: &str,
         state_merkle_db_config: &RocksdbConfig,
-        readonly: bool,
+        open_mode: &OpenMode,
     ) -> Result<DB> {
-        Ok(if readonly {
-            DB::open_cf_readonly(
+        Ok(match open_mode {
+            OpenMode::ReadWrite => DB::open_cf(
+                &gen_rocksdb_options(state_merkle_db_config, false),
+                path,
+                name,
+                gen_state_merkle_cfds(state_merkle_db_config),
+            )?,
+            OpenMode::ReadOnly => DB::open_cf_readonly(
                 &gen_rocksdb_options(state_merkle_db_config, true),
                 path,
                 name,
                 state_merkle_db_column_families(),
-            )?
-        } else {
-            DB::open_cf(
-                &gen_rocksdb_options(state_merkle_db_config, false),
+            )?,
+            OpenMode::Secondary { .. } => DB::open_cf_as_secondary(
+                &gen_secondary_rocksdb_options(state_merkle_db_config),
                 path,
+                open_mode.secondary_path(name),
                 name,
-                gen_state_merkle_cfds(state_merkle_db_config),
-            )?
+                state_merkle_db_column_families(),
+            )?,
         })
     }
 
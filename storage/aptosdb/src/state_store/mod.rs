@@ -17,7 +17,10 @@ use crate::{
     state_restore::{
         StateSnapshotProgress, StateSnapshotRestore, StateSnapshotRestoreMode, StateValueWriter,
     },
-    state_store::buffered_state::BufferedState,
+    state_store::{
+        buffered_state::BufferedState,
+        storage_footprint::{add_storage_footprint_deltas, StorageFootprintDeltas},
+    },
     utils::{
         iterators::PrefixedStateValueIterator,
         truncation_helper::{truncate_ledger_db, truncate_state_kv_db},
@@ -43,6 +46,7 @@ use aptos_storage_interface::{
     async_proof_fetcher::AsyncProofFetcher,
     cached_state_view::{CachedStateView, ShardedStateCache},
     state_delta::StateDelta,
+    state_iterator::StateItem,
     DbReader, StateSnapshotReceiver,
 };
 use aptos_types::{
@@ -60,11 +64,16 @@ use aptos_types::{
 };
 use claims::{assert_ge, assert_le};
 use rayon::prelude::*;
-use std::{collections::HashSet, ops::Deref, sync::Arc};
+use std::{
+    collections::HashSet,
+    ops::Deref,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 pub(crate) mod buffered_state;
 mod state_merkle_batch_committer;
 mod state_snapshot_committer;
+mod storage_footprint;
 
 #[cfg(test)]
 mod state_store_test;
@@ -95,6 +104,7 @@ pub(crate) struct StateStore {
     // write set stored in ledger_db.
     buffered_state: Mutex<BufferedState>,
     buffered_state_target_items: usize,
+    storage_footprint_enabled: AtomicBool,
 }
 
 impl Deref for StateStore {
@@ -167,6 +177,21 @@ impl DbReader for StateDb {
         Ok(proof)
     }
 
+    /// Returns the proofs of the given state keys at the given version, sharing the traversal of
+    /// the tree.
+    fn get_state_value_proofs(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<SparseMerkleProofExt>> {
+        Ok(self
+            .state_merkle_db
+            .get_with_proof_ext_many(state_keys, version)?
+            .into_iter()
+            .map(|(_, proof)| proof)
+            .collect())
+    }
+
     /// Get the state value with proof given the state key and version
     fn get_state_value_with_proof_by_version_ext(
         &self,
@@ -264,6 +289,15 @@ impl DbReader for StateStore {
             .get_state_proof_by_version_ext(state_key, version)
     }
 
+    /// Returns the proofs of the given state keys at the given version.
+    fn get_state_value_proofs(
+        &self,
+        state_keys: &[StateKey],
+        version: Version,
+    ) -> Result<Vec<SparseMerkleProofExt>> {
+        self.deref().get_state_value_proofs(state_keys, version)
+    }
+
     /// Get the state value with proof extension given the state key and version
     fn get_state_value_with_proof_by_version_ext(
         &self,
@@ -333,6 +367,7 @@ impl StateStore {
                 state_db,
                 buffered_state,
                 buffered_state_target_items,
+                storage_footprint_enabled: AtomicBool::new(false),
             }
         } else {
             let buffered_state = Mutex::new(
@@ -348,6 +383,7 @@ impl StateStore {
                 state_db,
                 buffered_state,
                 buffered_state_target_items,
+                storage_footprint_enabled: AtomicBool::new(false),
             }
         }
     }
@@ -575,6 +611,19 @@ impl StateStore {
         .expect("buffered state creation failed.");
     }
 
+    /// Points the buffered state at the latest snapshot on disk, without replaying the write sets
+    /// after it, which would require writing to the DB. For a secondary instance following the
+    /// writes of the primary.
+    pub fn reset_to_latest_snapshot(&self) -> Result<()> {
+        *self.buffered_state.lock() = Self::create_buffered_state_from_latest_snapshot(
+            &self.state_db,
+            self.buffered_state_target_items,
+            /*hack_for_tests=*/ true,
+            /*check_max_versions_after_snapshot=*/ false,
+        )?;
+        Ok(())
+    }
+
     pub fn buffered_state(&self) -> &Mutex<BufferedState> {
         &self.buffered_state
     }
@@ -819,8 +868,9 @@ impl StateStore {
         let _timer = OTHER_TIMERS_SECONDS
             .with_label_values(&["put_stats_and_indices__calculate_total_size"])
             .start_timer();
+        let track_storage_footprint = self.storage_footprint_enabled() && num_versions > 0;
         // calculate total state size in bytes
-        let usage_deltas: Vec<Vec<_>> = state_cache_with_version
+        let shard_deltas: Vec<(Vec<_>, StorageFootprintDeltas)> = state_cache_with_version
             .par_iter()
             .enumerate()
             .map(|(shard_id, cache)| {
@@ -830,6 +880,7 @@ impl StateStore {
                     )])
                     .start_timer();
                 let mut usage_delta = Vec::with_capacity(num_versions);
+                let mut storage_footprint_delta = StorageFootprintDeltas::new();
                 for (idx, kvs) in value_state_sets.iter().enumerate() {
                     let version = first_version + idx as Version;
                     let mut items_delta = 0;
@@ -862,6 +913,15 @@ impl StateStore {
                             None
                         };
 
+                        if track_storage_footprint {
+                            add_storage_footprint_deltas(
+                                &mut storage_footprint_delta,
+                                key,
+                                old_version_and_value_opt.as_ref().map(|(_, value)| value),
+                                value.as_ref(),
+                            );
+                        }
+
                         if let Some((old_version, old_value)) = old_version_and_value_opt {
                             let old_version = old_version
                                 .context("Must have old version in cache.")
@@ -884,9 +944,11 @@ impl StateStore {
                     usage_delta.push((items_delta, bytes_delta));
                 }
 
-                usage_delta
+                (usage_delta, storage_footprint_delta)
             })
             .collect();
+        let (usage_deltas, storage_footprint_deltas): (Vec<_>, Vec<_>) =
+            shard_deltas.into_iter().unzip();
 
         for i in 0..num_versions {
             let mut items_delta = 0;
@@ -922,6 +984,21 @@ impl StateStore {
             );
         }
 
+        if track_storage_footprint {
+            let mut deltas = StorageFootprintDeltas::new();
+            for shard_delta in storage_footprint_deltas {
+                for (owner, delta) in shard_delta {
+                    deltas.entry(owner).or_default().merge(delta);
+                }
+            }
+            self.put_storage_footprint_deltas(
+                deltas,
+                first_version,
+                first_version + num_versions as Version - 1,
+                batch,
+            )?;
+        }
+
         STATE_ITEMS.set(usage.items() as i64);
         TOTAL_STATE_BYTES.set(usage.bytes() as i64);
 
@@ -988,6 +1065,16 @@ impl StateStore {
         version: Version,
         start_hashed_key: HashValue,
     ) -> Result<impl Iterator<Item = Result<(StateKey, StateValue)>> + Send + Sync> {
+        Ok(self
+            .get_state_item_iter(version, start_hashed_key)?
+            .map(|res| res.map(|item| (item.key, item.value))))
+    }
+
+    pub fn get_state_item_iter(
+        self: &Arc<Self>,
+        version: Version,
+        start_hashed_key: HashValue,
+    ) -> Result<impl Iterator<Item = Result<StateItem>> + Send + Sync> {
         let store = Arc::clone(self);
         Ok(JellyfishMerkleIterator::new(
             Arc::clone(&self.state_merkle_db),
@@ -995,8 +1082,13 @@ impl StateStore {
             start_hashed_key,
         )?
         .map(move |res| match res {
-            Ok((_hashed_key, (key, version))) => {
-                Ok((key.clone(), store.expect_value_by_version(&key, version)?))
+            Ok((hashed_key, (key, version))) => {
+                let value = store.expect_value_by_version(&key, version)?;
+                Ok(StateItem {
+                    hashed_key,
+                    key,
+                    value,
+                })
             },
             Err(err) => Err(err),
         }))
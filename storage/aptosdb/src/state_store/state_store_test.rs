@@ -15,7 +15,8 @@ use aptos_jellyfish_merkle::{
     TreeReader,
 };
 use aptos_storage_interface::{
-    jmt_update_refs, jmt_updates, DbReader, DbWriter, StateSnapshotReceiver,
+    jmt_update_refs, jmt_updates, state_iterator::StateItem, DbReader, DbWriter,
+    StateSnapshotReceiver,
 };
 use aptos_temppath::TempPath;
 use aptos_types::{
@@ -164,6 +165,41 @@ fn test_state_store_reader_writer() {
     verify_value_and_proof(store, key3, Some(&value3), 1, root);
 }
 
+#[test]
+fn test_get_state_value_proofs() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let key1 = StateKey::raw(String::from("test_key1").into_bytes());
+    let key2 = StateKey::raw(String::from("test_key2").into_bytes());
+    let key3 = StateKey::raw(String::from("test_key3").into_bytes());
+
+    let value1 = StateValue::from(String::from("test_val1").into_bytes());
+    let value2 = StateValue::from(String::from("test_val2").into_bytes());
+
+    let root = put_value_set(
+        store,
+        vec![
+            (key1.clone(), value1.clone()),
+            (key2.clone(), value2.clone()),
+        ],
+        0, /* version */
+        None,
+    );
+
+    let keys_and_values = [(key2, Some(&value2)), (key3, None), (key1, Some(&value1))];
+    let keys: Vec<_> = keys_and_values.iter().map(|(key, _)| key.clone()).collect();
+    let proofs = store.get_state_value_proofs(&keys, 0).unwrap();
+    assert_eq!(proofs.len(), keys.len());
+    for ((key, value), proof) in keys_and_values.into_iter().zip(proofs) {
+        assert_eq!(
+            proof,
+            store.get_state_proof_by_version_ext(&key, 0).unwrap()
+        );
+        proof.verify(root, key.hash(), value).unwrap();
+    }
+}
+
 fn traverse_values(
     store: &StateStore,
     prefix: &StateKeyPrefix,
@@ -371,6 +407,59 @@ proptest! {
         }
     }
 
+    #[test]
+    fn test_get_state_iterator(
+        input in hash_map(any::<StateKey>(), any::<StateValue>(), 1..200),
+        resume_at in any::<prop::sample::Index>(),
+    ) {
+        let kvs: Vec<_> = input.into_iter().collect();
+
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        init_store(&db.state_store, kvs.clone().into_iter());
+        let version = kvs.len() as Version - 1;
+
+        let mut expected_items: Vec<_> = kvs
+            .iter()
+            .map(|(key, value)| StateItem {
+                hashed_key: key.hash(),
+                key: key.clone(),
+                value: value.clone(),
+            })
+            .collect();
+        expected_items.sort_unstable_by_key(|item| item.hashed_key);
+        let actual_items = db
+            .get_state_iterator(version, HashValue::zero(), None)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        prop_assert_eq!(&actual_items, &expected_items);
+
+        // Resumes after an item.
+        let resume_at = resume_at.index(expected_items.len());
+        if let Some(next_hashed_key) = expected_items[resume_at].next_hashed_key() {
+            let actual_items = db
+                .get_state_iterator(version, next_hashed_key, None)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            prop_assert_eq!(&actual_items[..], &expected_items[resume_at + 1..]);
+        }
+
+        // Only the keys with the prefix.
+        let key_prefix = StateKeyPrefix::new(StateKeyTag::AccessPath, vec![]);
+        let actual_items = db
+            .get_state_iterator(version, HashValue::zero(), Some(key_prefix.clone()))
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let expected_items: Vec<_> = expected_items
+            .into_iter()
+            .filter(|item| key_prefix.is_prefix(&item.key).unwrap())
+            .collect();
+        prop_assert_eq!(actual_items, expected_items);
+    }
+
     #[test]
     fn test_raw_restore(
         (input, batch1_size) in hash_map(any::<StateKey>(), any::<StateValue>(), 2..1000)
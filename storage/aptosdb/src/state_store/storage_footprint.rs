@@ -0,0 +1,309 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This file maintains the storage footprint of the accounts, modules and tables, i.e. the
+//! number of state items attributed to each of them and their total size, so that the biggest
+//! consumers of the state can be found without scanning it.
+//!
+//! The counters are updated together with the storage usage on each commit, and are rebuilt from
+//! the latest state snapshot when they are enabled on a DB whose counters are missing or not up
+//! to date, e.g. because they were disabled for a while.
+
+use crate::{
+    db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+    schema::storage_footprint::StorageFootprintSchema,
+    state_store::StateStore,
+    TransactionStore,
+};
+use anyhow::{ensure, format_err, Result};
+use aptos_crypto::HashValue;
+use aptos_logger::{
+    info,
+    prelude::{sample, SampleRate},
+    warn,
+};
+use aptos_schemadb::{ReadOptions, SchemaBatch};
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    state_store::{
+        state_key::StateKey,
+        state_value::StateValue,
+        storage_footprint::{
+            StorageFootprint, StorageFootprintDelta, StorageFootprintOwner,
+            StorageFootprintOwnerKind,
+        },
+    },
+    transaction::Version,
+    write_set::TransactionWrite,
+};
+use rayon::prelude::*;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
+
+/// Number of counters written per batch when rebuilding them.
+const REBUILD_BATCH_SIZE: usize = 10000;
+/// Number of versions whose write sets are read at once when catching up with the latest
+/// version after rebuilding from the snapshot.
+const REPLAY_BATCH_SIZE: u64 = 1000;
+
+pub(crate) type StorageFootprintDeltas = HashMap<StorageFootprintOwner, StorageFootprintDelta>;
+
+/// Adds the change of a state item to the deltas of its owners.
+pub(crate) fn add_storage_footprint_deltas(
+    deltas: &mut StorageFootprintDeltas,
+    key: &StateKey,
+    old_value: Option<&StateValue>,
+    new_value: Option<&StateValue>,
+) {
+    if old_value.is_none() && new_value.is_none() {
+        return;
+    }
+    for owner in StorageFootprintOwner::owners_of(key) {
+        let delta = deltas.entry(owner).or_default();
+        if let Some(old_value) = old_value {
+            delta.remove_item(key.size() + old_value.size());
+        }
+        if let Some(new_value) = new_value {
+            delta.add_item(key.size() + new_value.size());
+        }
+    }
+}
+
+impl StateStore {
+    /// Updates the storage footprint from now on. The counters are rebuilt first if they are not
+    /// up to date with the latest version, which scans the latest state snapshot.
+    pub fn enable_storage_footprint(self: &Arc<Self>) -> Result<()> {
+        let current_version = self.buffered_state.lock().current_state().current_version;
+        let progress = self.get_storage_footprint_progress()?;
+        if progress != current_version {
+            info!(
+                progress = progress,
+                current_version = current_version,
+                "Rebuilding the storage footprint."
+            );
+            self.rebuild_storage_footprint(current_version)?;
+        }
+        self.storage_footprint_enabled
+            .store(true, Ordering::Release);
+        info!(
+            version = current_version,
+            "Enabled AptosDB storage footprint."
+        );
+        Ok(())
+    }
+
+    pub(crate) fn storage_footprint_enabled(&self) -> bool {
+        self.storage_footprint_enabled.load(Ordering::Acquire)
+    }
+
+    pub fn get_storage_footprint(&self, owner: &StorageFootprintOwner) -> Result<StorageFootprint> {
+        self.ensure_storage_footprint_enabled()?;
+        Ok(self
+            .ledger_db
+            .metadata_db()
+            .get::<StorageFootprintSchema>(owner)?
+            .unwrap_or_default())
+    }
+
+    /// Returns the `limit` owners of the kind with the most bytes, in descending order. This
+    /// walks all the counters of the kind.
+    pub fn get_top_storage_footprints(
+        &self,
+        kind: StorageFootprintOwnerKind,
+        limit: usize,
+    ) -> Result<Vec<(StorageFootprintOwner, StorageFootprint)>> {
+        self.ensure_storage_footprint_enabled()?;
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut iter = self
+            .ledger_db
+            .metadata_db()
+            .iter::<StorageFootprintSchema>(ReadOptions::default())?;
+        iter.seek(&kind)?;
+        let mut top = BinaryHeap::with_capacity(limit + 1);
+        for item in iter {
+            let (owner, footprint) = item?;
+            if owner.kind() != kind {
+                break;
+            }
+            top.push(Reverse((footprint.bytes, footprint.items, owner)));
+            if top.len() > limit {
+                top.pop();
+            }
+        }
+        Ok(top
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((bytes, items, owner))| (owner, StorageFootprint { items, bytes }))
+            .collect())
+    }
+
+    /// Applies the deltas of `[first_version, last_version]` to the counters, in `batch`. The
+    /// deltas are dropped if the counters are not up to date with `first_version - 1`, in which
+    /// case they are rebuilt when the DB is opened next time.
+    pub(crate) fn put_storage_footprint_deltas(
+        &self,
+        deltas: StorageFootprintDeltas,
+        first_version: Version,
+        last_version: Version,
+        batch: &SchemaBatch,
+    ) -> Result<()> {
+        let progress = self.get_storage_footprint_progress()?;
+        if progress != first_version.checked_sub(1) {
+            sample!(
+                SampleRate::Duration(Duration::from_secs(60)),
+                warn!(
+                    progress = progress,
+                    first_version = first_version,
+                    "Storage footprint is not up to date, it will be rebuilt on restart."
+                )
+            );
+            return Ok(());
+        }
+
+        let metadata_db = self.ledger_db.metadata_db();
+        deltas.into_par_iter().try_for_each(|(owner, delta)| {
+            let mut footprint = metadata_db
+                .get::<StorageFootprintSchema>(&owner)?
+                .unwrap_or_default();
+            footprint.apply(delta);
+            if footprint.is_empty() {
+                batch.delete::<StorageFootprintSchema>(&owner)
+            } else {
+                batch.put::<StorageFootprintSchema>(&owner, &footprint)
+            }
+        })?;
+        batch.put::<DbMetadataSchema>(
+            &DbMetadataKey::StorageFootprintProgress,
+            &DbMetadataValue::Version(last_version),
+        )
+    }
+
+    fn ensure_storage_footprint_enabled(&self) -> Result<()> {
+        ensure!(
+            self.storage_footprint_enabled(),
+            "Storage footprint is not enabled."
+        );
+        Ok(())
+    }
+
+    fn get_storage_footprint_progress(&self) -> Result<Option<Version>> {
+        Ok(self
+            .ledger_db
+            .metadata_db()
+            .get::<DbMetadataSchema>(&DbMetadataKey::StorageFootprintProgress)?
+            .map(|v| v.expect_version()))
+    }
+
+    /// Recounts the footprints at the latest snapshot, then replays the write sets after it up to
+    /// `current_version`.
+    fn rebuild_storage_footprint(self: &Arc<Self>, current_version: Option<Version>) -> Result<()> {
+        let metadata_db = self.ledger_db.metadata_db();
+        let mut iter = metadata_db.iter::<StorageFootprintSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let mut batch = SchemaBatch::new();
+        // Invalidate the counters in the first batch, so that they are rebuilt again if
+        // interrupted.
+        batch.delete::<DbMetadataSchema>(&DbMetadataKey::StorageFootprintProgress)?;
+        let mut num_in_batch = 0;
+        for item in iter {
+            let (owner, _footprint) = item?;
+            batch.delete::<StorageFootprintSchema>(&owner)?;
+            num_in_batch += 1;
+            if num_in_batch == REBUILD_BATCH_SIZE {
+                metadata_db.write_schemas(std::mem::replace(&mut batch, SchemaBatch::new()))?;
+                num_in_batch = 0;
+            }
+        }
+        metadata_db.write_schemas(batch)?;
+
+        let Some(current_version) = current_version else {
+            // Empty DB, the counters start from genesis.
+            return Ok(());
+        };
+        let snapshot_version = self
+            .state_merkle_db
+            .get_state_snapshot_version_before(current_version + 1)?
+            .ok_or_else(|| {
+                format_err!(
+                    "No state snapshot to rebuild the storage footprint from, at or before version {}.",
+                    current_version,
+                )
+            })?;
+
+        let mut footprints: HashMap<StorageFootprintOwner, StorageFootprint> = HashMap::new();
+        let mut num_items = 0u64;
+        for item in self.get_state_item_iter(snapshot_version, HashValue::zero())? {
+            let item = item?;
+            let size = item.key.size() + item.value.size();
+            for owner in StorageFootprintOwner::owners_of(&item.key) {
+                let footprint = footprints.entry(owner).or_default();
+                footprint.items += 1;
+                footprint.bytes += size as u64;
+            }
+            num_items += 1;
+            if num_items % 1_000_000 == 0 {
+                info!(
+                    num_items = num_items,
+                    snapshot_version = snapshot_version,
+                    "Rebuilding the storage footprint."
+                );
+            }
+        }
+
+        let transaction_store = TransactionStore::new(Arc::clone(&self.ledger_db));
+        let mut deltas = StorageFootprintDeltas::new();
+        let mut begin_version = snapshot_version + 1;
+        while begin_version <= current_version {
+            let end_version = (begin_version + REPLAY_BATCH_SIZE).min(current_version + 1);
+            let write_sets = transaction_store.get_write_sets(begin_version, end_version)?;
+            for (i, write_set) in write_sets.iter().enumerate() {
+                let version = begin_version + i as Version;
+                for (key, write_op) in write_set.iter() {
+                    let old_value = self.state_db.get_state_value_by_version(key, version - 1)?;
+                    add_storage_footprint_deltas(
+                        &mut deltas,
+                        key,
+                        old_value.as_ref(),
+                        write_op.as_state_value().as_ref(),
+                    );
+                }
+            }
+            begin_version = end_version;
+        }
+        for (owner, delta) in deltas {
+            footprints.entry(owner).or_default().apply(delta);
+        }
+
+        let mut batch = SchemaBatch::new();
+        let mut num_in_batch = 0;
+        for (owner, footprint) in footprints {
+            if footprint.is_empty() {
+                continue;
+            }
+            batch.put::<StorageFootprintSchema>(&owner, &footprint)?;
+            num_in_batch += 1;
+            if num_in_batch == REBUILD_BATCH_SIZE {
+                metadata_db.write_schemas(std::mem::replace(&mut batch, SchemaBatch::new()))?;
+                num_in_batch = 0;
+            }
+        }
+        batch.put::<DbMetadataSchema>(
+            &DbMetadataKey::StorageFootprintProgress,
+            &DbMetadataValue::Version(current_version),
+        )?;
+        metadata_db.write_schemas(batch)?;
+        info!(
+            num_items = num_items,
+            snapshot_version = snapshot_version,
+            current_version = current_version,
+            "Rebuilt the storage footprint."
+        );
+        Ok(())
+    }
+}
@@ -12,8 +12,8 @@ use crate::{
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
         backup_service_client::BackupServiceClient, test_utils::tmp_db_with_random_content,
-        ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, ReplayConcurrencyLevelOpt,
-        RocksdbOpt, TrustedWaypointOpt,
+        ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, ParallelRestoreOpt,
+        ReplayConcurrencyLevelOpt, RocksdbOpt, TrustedWaypointOpt,
     },
 };
 use aptos_backup_service::start_backup_service;
@@ -87,6 +87,7 @@ fn end_to_end() {
                 rocksdb_opt: RocksdbOpt::default(),
                 concurrent_downloads: ConcurrentDownloadsOpt::default(),
                 replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                parallel_restore: ParallelRestoreOpt::default(),
             }
             .try_into()
             .unwrap(),
@@ -219,6 +220,7 @@ async fn test_trusted_waypoints_impl(
             rocksdb_opt: RocksdbOpt::default(),
             concurrent_downloads: ConcurrentDownloadsOpt::default(),
             replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+            parallel_restore: ParallelRestoreOpt::default(),
         }
         .try_into()
         .unwrap(),
@@ -240,6 +242,7 @@ async fn test_trusted_waypoints_impl(
             rocksdb_opt: RocksdbOpt::default(),
             concurrent_downloads: ConcurrentDownloadsOpt::default(),
             replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+            parallel_restore: ParallelRestoreOpt::default(),
         }
         .try_into()
         .unwrap(),
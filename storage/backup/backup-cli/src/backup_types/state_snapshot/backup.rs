@@ -5,7 +5,7 @@
 use crate::{
     backup_types::state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
     metadata::Metadata,
-    storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
+    storage::{BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName},
     utils::{
         backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
         should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
@@ -23,7 +23,9 @@ use aptos_types::{
 use bytes::Bytes;
 use clap::Parser;
 use once_cell::sync::Lazy;
-use std::{convert::TryInto, str::FromStr, sync::Arc};
+use std::{
+    collections::HashMap, convert::TryInto, ffi::OsStr, path::Path, str::FromStr, sync::Arc,
+};
 use tokio::{io::AsyncWriteExt, time::Instant};
 
 #[derive(Parser)]
@@ -33,12 +35,31 @@ pub struct StateSnapshotBackupOpt {
         help = "Epoch at the end of which a state snapshot is to be taken."
     )]
     pub epoch: u64,
+    #[clap(
+        long,
+        help = "Cut chunks at boundaries determined by the keys and name them after the hash of \
+        their content, so that chunks unchanged since the backup given by --dedup-base-manifest \
+        are referenced instead of being written again."
+    )]
+    pub dedup: bool,
+    #[clap(
+        long,
+        requires = "dedup",
+        help = "Manifest of a previous state snapshot backup taken with --dedup, whose chunks are \
+        reused if unchanged."
+    )]
+    pub dedup_base_manifest: Option<FileHandle>,
 }
 
 pub struct StateSnapshotBackupController {
     epoch: u64,
     version: Option<Version>, // initialize before using
     max_chunk_size: usize,
+    dedup: bool,
+    dedup_base_manifest: Option<FileHandle>,
+    /// Content addressed chunks which can be referenced instead of being written again, by hash.
+    known_chunks: HashMap<HashValue, FileHandle>,
+    num_reused_chunks: usize,
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
 }
@@ -54,6 +75,10 @@ impl StateSnapshotBackupController {
             epoch: opt.epoch,
             version: None,
             max_chunk_size: global_opt.max_chunk_size,
+            dedup: opt.dedup,
+            dedup_base_manifest: opt.dedup_base_manifest,
+            known_chunks: HashMap::new(),
+            num_reused_chunks: 0,
             client,
             storage,
         }
@@ -75,6 +100,14 @@ impl StateSnapshotBackupController {
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
             .await?;
+        if let Some(base_manifest) = &self.dedup_base_manifest {
+            let manifest: StateSnapshotBackup = self.storage.load_json_file(base_manifest).await?;
+            self.known_chunks = manifest
+                .chunks
+                .into_iter()
+                .filter_map(|chunk| chunk.blobs_hash.map(|hash| (hash, chunk.blobs)))
+                .collect();
+        }
 
         let mut chunks = vec![];
 
@@ -91,7 +124,9 @@ impl StateSnapshotBackupController {
 
         let start = Instant::now();
         while let Some(record_bytes) = state_snapshot_file.read_record_bytes().await? {
-            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
+            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size)
+                || (self.dedup && Self::is_chunk_boundary(Self::parse_key(&prev_record_bytes)?))
+            {
                 let chunk = self
                     .write_chunk(
                         &backup_handle,
@@ -133,6 +168,13 @@ impl StateSnapshotBackupController {
             )
             .await?;
         chunks.push(chunk);
+        if self.dedup {
+            info!(
+                num_chunks = chunks.len(),
+                num_reused_chunks = self.num_reused_chunks,
+                "Chunks deduplicated."
+            );
+        }
 
         self.write_manifest(&backup_handle, chunks).await
     }
@@ -163,6 +205,31 @@ impl StateSnapshotBackupController {
         format!("{}-.chunk", first_idx).try_into().unwrap()
     }
 
+    fn content_addressed_chunk_name(blobs_hash: HashValue) -> ShellSafeName {
+        format!("{:x}.chunk", blobs_hash).try_into().unwrap()
+    }
+
+    /// Whether the file is a chunk named by `content_addressed_chunk_name()`.
+    pub(crate) fn is_content_addressed_chunk(file_handle: &FileHandleRef) -> bool {
+        Path::new(file_handle)
+            .file_name()
+            .and_then(OsStr::to_str)
+            .and_then(|name| name.strip_suffix(".chunk"))
+            .map_or(false, |hash| HashValue::from_hex(hash).is_ok())
+    }
+
+    /// With deduplication on, a chunk ends after each key that's a boundary, so that chunks in
+    /// different snapshots share boundaries, and unchanged parts of the state result in identical
+    /// chunks. Keys are sorted by hash, hence the leading bytes of the hash aren't random across a
+    /// chunk, so the trailing ones are used.
+    fn is_chunk_boundary(key: HashValue) -> bool {
+        const AVG_CHUNK_RECORDS: u64 = if cfg!(test) { 4 } else { 1 << 12 };
+
+        let bytes: &[u8; HashValue::LENGTH] = key.as_ref();
+        let trailing = u64::from_be_bytes(bytes[HashValue::LENGTH - 8..].try_into().unwrap());
+        trailing % AVG_CHUNK_RECORDS == 0
+    }
+
     fn chunk_proof_name(first_idx: usize, last_idx: usize) -> ShellSafeName {
         format!("{}-{}.proof", first_idx, last_idx)
             .try_into()
@@ -190,7 +257,7 @@ impl StateSnapshotBackupController {
     }
 
     async fn write_chunk(
-        &self,
+        &mut self,
         backup_handle: &BackupHandleRef,
         chunk_bytes: &[u8],
         first_idx: usize,
@@ -198,12 +265,29 @@ impl StateSnapshotBackupController {
         first_key: HashValue,
         last_key: HashValue,
     ) -> Result<StateSnapshotChunk> {
-        let (chunk_handle, mut chunk_file) = self
-            .storage
-            .create_for_write(backup_handle, &Self::chunk_name(first_idx))
-            .await?;
-        chunk_file.write_all(chunk_bytes).await?;
-        chunk_file.shutdown().await?;
+        let blobs_hash = self.dedup.then(|| HashValue::sha3_256_of(chunk_bytes));
+        let chunk_handle = match blobs_hash {
+            Some(hash) if self.known_chunks.contains_key(&hash) => {
+                self.num_reused_chunks += 1;
+                self.known_chunks[&hash].clone()
+            },
+            _ => {
+                let chunk_name = match blobs_hash {
+                    Some(hash) => Self::content_addressed_chunk_name(hash),
+                    None => Self::chunk_name(first_idx),
+                };
+                let (chunk_handle, mut chunk_file) = self
+                    .storage
+                    .create_for_write(backup_handle, &chunk_name)
+                    .await?;
+                chunk_file.write_all(chunk_bytes).await?;
+                chunk_file.shutdown().await?;
+                if let Some(hash) = blobs_hash {
+                    self.known_chunks.insert(hash, chunk_handle.clone());
+                }
+                chunk_handle
+            },
+        };
         let (proof_handle, mut proof_file) = self
             .storage
             .create_for_write(backup_handle, &Self::chunk_proof_name(first_idx, last_idx))
@@ -224,6 +308,7 @@ impl StateSnapshotBackupController {
             first_key,
             last_key,
             blobs: chunk_handle,
+            blobs_hash,
             proof: proof_handle,
         })
     }
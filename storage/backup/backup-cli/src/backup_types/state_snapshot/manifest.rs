@@ -22,6 +22,11 @@ pub struct StateSnapshotChunk {
     /// Repeated `len(record) + record` where `record` is BCS serialized tuple
     /// `(key, state_value)`
     pub blobs: FileHandle,
+    /// Hash of the content of `blobs`, present if the chunk is content addressed, i.e. taken with
+    /// deduplication on. Such chunks can be shared by multiple state snapshot backups, and `blobs`
+    /// can be in a different backup than this manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blobs_hash: Option<HashValue>,
     /// BCS serialized `SparseMerkleRangeProof` that proves this chunk adds up to the root hash
     /// indicated in the backup (`StateSnapshotBackup::root_hash`).
     pub proof: FileHandle,
@@ -5,45 +5,45 @@
 use crate::{
     backup_types::state_snapshot::{
         backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        manifest::StateSnapshotBackup,
         restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
     },
-    storage::{local_fs::LocalFs, BackupStorage},
+    coordinators::backup::BackupGarbageCollector,
+    metadata::cache::MetadataCacheOpt,
+    storage::{local_fs::LocalFs, BackupStorage, FileHandle},
     utils::{
         backup_service_client::BackupServiceClient,
+        storage_ext::BackupStorageExt,
         test_utils::{start_local_backup_service, tmp_db_with_random_content},
-        ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, ReplayConcurrencyLevelOpt,
-        RocksdbOpt, TrustedWaypointOpt,
+        ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, ParallelRestoreOpt,
+        ReplayConcurrencyLevelOpt, RocksdbOpt, TrustedWaypointOpt,
     },
 };
+use anyhow::Result;
+use aptos_crypto::HashValue;
 use aptos_db::{state_restore::StateSnapshotRestoreMode, AptosDB};
 use aptos_storage_interface::DbReader;
 use aptos_temppath::TempPath;
+use aptos_types::transaction::Version;
 use std::{convert::TryInto, sync::Arc};
-use tokio::time::Duration;
+use tokio::{io::AsyncWriteExt, runtime::Runtime, time::Duration};
 
-#[test]
-fn end_to_end() {
-    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
-    let tgt_db_dir = TempPath::new();
-    tgt_db_dir.create_as_dir().unwrap();
-    let backup_dir = TempPath::new();
-    backup_dir.create_as_dir().unwrap();
-    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
-
-    let epoch = src_db
+/// Returns the epoch, version and state root hash of the latest epoch ending.
+fn latest_epoch_ending_state(db: &AptosDB) -> (u64, Version, HashValue) {
+    let epoch = db
         .get_latest_ledger_info()
         .unwrap()
         .ledger_info()
         .next_block_epoch()
         - 1;
-    let latest_epoch_ending_li = src_db
+    let latest_epoch_ending_li = db
         .get_epoch_ending_ledger_infos(epoch, epoch + 1)
         .unwrap()
         .ledger_info_with_sigs
         .pop()
         .unwrap();
     let version = latest_epoch_ending_li.ledger_info().version();
-    let state_root_hash = src_db
+    let state_root_hash = db
         .get_transactions(version, 1, version, false)
         .unwrap()
         .proof
@@ -52,26 +52,18 @@ fn end_to_end() {
         .unwrap()
         .state_checkpoint_hash()
         .unwrap();
+    (epoch, version, state_root_hash)
+}
 
-    let (rt, port) = start_local_backup_service(src_db);
-    let client = Arc::new(BackupServiceClient::new(format!(
-        "http://localhost:{}",
-        port
-    )));
-    let manifest_handle = rt
-        .block_on(
-            StateSnapshotBackupController::new(
-                StateSnapshotBackupOpt { epoch },
-                GlobalBackupOpt {
-                    max_chunk_size: 500,
-                },
-                client,
-                Arc::clone(&store),
-            )
-            .run(),
-        )
-        .unwrap();
-
+fn restore_and_verify(
+    rt: &Runtime,
+    store: Arc<dyn BackupStorage>,
+    manifest_handle: FileHandle,
+    version: Version,
+    state_root_hash: HashValue,
+) {
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
     rt.block_on(
         StateSnapshotRestoreController::new(
             StateSnapshotRestoreOpt {
@@ -88,6 +80,7 @@ fn end_to_end() {
                 rocksdb_opt: RocksdbOpt::default(),
                 concurrent_downloads: ConcurrentDownloadsOpt::default(),
                 replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                parallel_restore: ParallelRestoreOpt::default(),
             }
             .try_into()
             .unwrap(),
@@ -106,6 +99,132 @@ fn end_to_end() {
             .unwrap(),
         (version, state_root_hash)
     );
+}
+
+#[test]
+fn end_to_end() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let (epoch, version, state_root_hash) = latest_epoch_ending_state(&src_db);
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let manifest_handle = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt {
+                    epoch,
+                    dedup: false,
+                    dedup_base_manifest: None,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 500,
+                },
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+
+    restore_and_verify(&rt, store, manifest_handle, version, state_root_hash);
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+#[test]
+fn end_to_end_dedup() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+    let metadata_cache_dir = TempPath::new();
+    metadata_cache_dir.create_as_dir().unwrap();
+
+    let (epoch, version, state_root_hash) = latest_epoch_ending_state(&src_db);
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let backup = |dedup_base_manifest| {
+        rt.block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt {
+                    epoch,
+                    dedup: true,
+                    dedup_base_manifest,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 500,
+                },
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap()
+    };
+    let base_manifest_handle = backup(None);
+    let manifest_handle = backup(Some(base_manifest_handle.clone()));
+
+    // Nothing changed, so all chunks are reused.
+    let (base_manifest, manifest): (StateSnapshotBackup, StateSnapshotBackup) = rt
+        .block_on(async {
+            Result::<_>::Ok((
+                store.load_json_file(&base_manifest_handle).await?,
+                store.load_json_file(&manifest_handle).await?,
+            ))
+        })
+        .unwrap();
+    let blobs = |manifest: &StateSnapshotBackup| {
+        manifest
+            .chunks
+            .iter()
+            .map(|chunk| (chunk.blobs.clone(), chunk.blobs_hash))
+            .collect::<Vec<_>>()
+    };
+    assert!(manifest
+        .chunks
+        .iter()
+        .all(|chunk| chunk.blobs_hash.is_some()));
+    assert_eq!(blobs(&manifest), blobs(&base_manifest));
+
+    // A chunk left behind by a failed backup is garbage collected.
+    let orphan_chunk = rt
+        .block_on(async {
+            let backup_handle = store.create_backup(&"failed".parse()?).await?;
+            let name = format!("{:x}.chunk", HashValue::sha3_256_of(b"orphan")).parse()?;
+            let (file_handle, mut file) = store.create_for_write(&backup_handle, &name).await?;
+            file.shutdown().await?;
+            Result::<_>::Ok(file_handle)
+        })
+        .unwrap();
+    rt.block_on(
+        BackupGarbageCollector::new(
+            MetadataCacheOpt::new(Some(metadata_cache_dir.path())),
+            Arc::clone(&store),
+            1,
+            false,
+        )
+        .run(),
+    )
+    .unwrap();
+    let files = rt.block_on(store.list_backup_files()).unwrap();
+    assert!(!files.contains(&orphan_chunk));
+    assert!(manifest
+        .chunks
+        .iter()
+        .all(|chunk| files.contains(&chunk.blobs)));
+
+    restore_and_verify(&rt, store, manifest_handle, version, state_root_hash);
 
     rt.shutdown_timeout(Duration::from_secs(1));
 }
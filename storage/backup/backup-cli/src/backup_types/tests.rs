@@ -17,7 +17,7 @@ use crate::{
     utils::{
         backup_service_client::BackupServiceClient, test_utils::start_local_backup_service,
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, GlobalRestoreOptions,
-        ReplayConcurrencyLevelOpt, RocksdbOpt, TrustedWaypointOpt,
+        ParallelRestoreOpt, ReplayConcurrencyLevelOpt, RocksdbOpt, TrustedWaypointOpt,
     },
 };
 use aptos_db::{state_restore::StateSnapshotRestoreMode, AptosDB};
@@ -92,7 +92,11 @@ fn test_end_to_end_impl(d: TestData) {
     let state_snapshot_manifest = d.state_snapshot_epoch.map(|epoch| {
         rt.block_on(
             StateSnapshotBackupController::new(
-                StateSnapshotBackupOpt { epoch },
+                StateSnapshotBackupOpt {
+                    epoch,
+                    dedup: false,
+                    dedup_base_manifest: None,
+                },
                 global_backup_opt.clone(),
                 Arc::clone(&client),
                 Arc::clone(&store),
@@ -124,6 +128,7 @@ fn test_end_to_end_impl(d: TestData) {
         rocksdb_opt: RocksdbOpt::default(),
         concurrent_downloads: ConcurrentDownloadsOpt::default(),
         replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+        parallel_restore: ParallelRestoreOpt::default(),
     }
     .try_into()
     .unwrap();
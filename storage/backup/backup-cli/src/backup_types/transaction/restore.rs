@@ -18,6 +18,7 @@ use crate::{
     storage::{BackupStorage, FileHandle},
     utils::{
         error_notes::ErrorNotes,
+        memory_budget::{MemoryBudget, MemoryReservation},
         read_record_bytes::ReadRecordBytes,
         storage_ext::BackupStorageExt,
         stream::{StreamX, TryStreamX},
@@ -92,6 +93,8 @@ struct LoadedChunk {
     pub write_sets: Vec<WriteSet>,
     pub range_proof: TransactionAccumulatorRangeProof,
     pub ledger_info: LedgerInfoWithSignatures,
+    /// Released when the chunk is dropped, or saved to the DB.
+    pub memory_reservation: MemoryReservation,
 }
 
 impl LoadedChunk {
@@ -99,14 +102,17 @@ impl LoadedChunk {
         manifest: TransactionChunk,
         storage: &Arc<dyn BackupStorage>,
         epoch_history: Option<&Arc<EpochHistory>>,
+        memory_budget: &Arc<MemoryBudget>,
     ) -> Result<Self> {
         let mut file = BufReader::new(storage.open_for_read(&manifest.transactions).await?);
         let mut txns = Vec::new();
         let mut txn_infos = Vec::new();
         let mut event_vecs = Vec::new();
         let mut write_sets = Vec::new();
+        let mut num_bytes = 0;
 
         while let Some(record_bytes) = file.read_record_bytes().await? {
+            num_bytes += record_bytes.len();
             let (txn, txn_info, events, write_set): (_, _, _, WriteSet) =
                 bcs::from_bytes(&record_bytes)?;
             txns.push(txn);
@@ -122,6 +128,7 @@ impl LoadedChunk {
             manifest.last_version,
             txns.len(),
         );
+        let memory_reservation = memory_budget.reserve(num_bytes);
 
         let (range_proof, ledger_info) = storage
             .load_bcs_file::<(TransactionAccumulatorRangeProof, LedgerInfoWithSignatures)>(
@@ -156,6 +163,7 @@ impl LoadedChunk {
             range_proof,
             ledger_info,
             write_sets,
+            memory_reservation,
         })
     }
 
@@ -175,6 +183,7 @@ impl LoadedChunk {
             write_sets,
             range_proof: _,
             ledger_info: _,
+            memory_reservation: _,
         } = self;
 
         (txns, txn_infos, event_vecs, write_sets)
@@ -313,6 +322,7 @@ impl TransactionRestoreBatchController {
 
     fn loaded_chunk_stream(&self) -> Peekable<impl Stream<Item = Result<LoadedChunk>>> {
         let con = self.global_opt.concurrent_downloads;
+        let chunk_con = self.global_opt.concurrency;
 
         let manifest_handle_stream = stream::iter(self.manifest_handles.clone());
 
@@ -356,19 +366,31 @@ impl TransactionRestoreBatchController {
 
         let storage = self.storage.clone();
         let epoch_history = self.epoch_history.clone();
+        let memory_budget = MemoryBudget::new(self.global_opt.memory_budget);
         chunk_manifest_stream
             .and_then(move |chunk| {
                 let storage = storage.clone();
                 let epoch_history = epoch_history.clone();
-                future::ok(async move {
-                    tokio::task::spawn(async move {
-                        LoadedChunk::load(chunk, &storage, epoch_history.as_ref()).await
+                let memory_budget = memory_budget.clone();
+                async move {
+                    // Chunks wait for room one by one in order, see `MemoryBudget`.
+                    memory_budget.wait_for_room().await;
+                    Ok(async move {
+                        tokio::task::spawn(async move {
+                            LoadedChunk::load(
+                                chunk,
+                                &storage,
+                                epoch_history.as_ref(),
+                                &memory_budget,
+                            )
+                            .await
+                        })
+                        .err_into::<anyhow::Error>()
+                        .await
                     })
-                    .err_into::<anyhow::Error>()
-                    .await
-                })
+                }
             })
-            .try_buffered_x(con * 2, con)
+            .try_buffered_x(chunk_con * 2, chunk_con)
             .and_then(future::ready)
             .peekable()
     }
@@ -412,6 +434,7 @@ impl TransactionRestoreBatchController {
         let start = Instant::now();
 
         let restore_handler_clone = restore_handler.clone();
+        let restore_handler_for_commit = restore_handler.clone();
         // DB doesn't allow replaying anything before what's in DB already.
         // self.replay_from_version is from cli argument. However, in fact, we either not replay or replay
         // after current DB's version.
@@ -421,14 +444,29 @@ impl TransactionRestoreBatchController {
             next_expected_version,
         );
         let target_version = self.global_opt.target_version;
+        let con = self.global_opt.concurrency;
 
+        // Chunks are prepared for saving concurrently, and then committed in order.
         let mut txns_to_execute_stream = loaded_chunk_stream
             .and_then(move |chunk| {
                 let restore_handler = restore_handler_clone.clone();
                 future::ok(async move {
-                    let mut first_version = chunk.manifest.first_version;
-                    let mut last_version = chunk.manifest.last_version;
-                    let (mut txns, mut txn_infos, mut event_vecs, mut write_sets) = chunk.unpack();
+                    let LoadedChunk {
+                        manifest,
+                        mut txns,
+                        mut txn_infos,
+                        mut event_vecs,
+                        mut write_sets,
+                        range_proof,
+                        ledger_info: _,
+                        memory_reservation,
+                    } = chunk;
+                    let mut first_version = manifest.first_version;
+                    let mut last_version = manifest.last_version;
+                    // The roots of the frozen subtrees of the accumulator before the chunk are in
+                    // its range proof, so the chunk can be prepared before the previous chunks are
+                    // saved.
+                    let mut range_proof = Some(range_proof);
 
                     // remove the txns that exceeds the target_version to be restored
                     if target_version < last_version {
@@ -449,27 +487,53 @@ impl TransactionRestoreBatchController {
                         event_vecs.drain(..num_to_remove);
                         write_sets.drain(..num_to_remove);
                         first_version = global_first_version;
+                        // This is the first chunk, the frozen subtrees at global_first_version
+                        // are in the DB already.
+                        range_proof = None;
                     }
 
                     // identify txns to be saved before the first_to_replay version
-                    if first_version < first_to_replay {
+                    let prepared = if first_version < first_to_replay {
                         let num_to_save =
                             (min(first_to_replay, last_version + 1) - first_version) as usize;
                         let txns_to_save: Vec<_> = txns.drain(..num_to_save).collect();
                         let txn_infos_to_save: Vec<_> = txn_infos.drain(..num_to_save).collect();
                         let event_vecs_to_save: Vec<_> = event_vecs.drain(..num_to_save).collect();
                         let write_sets_to_save = write_sets.drain(..num_to_save).collect();
-                        tokio::task::spawn_blocking(move || {
-                            restore_handler.save_transactions(
+                        let prepared = tokio::task::spawn_blocking(move || {
+                            restore_handler.prepare_transactions(
                                 first_version,
                                 &txns_to_save,
                                 &txn_infos_to_save,
                                 &event_vecs_to_save,
                                 write_sets_to_save,
+                                range_proof
+                                    .as_ref()
+                                    .map(|proof| proof.left_siblings().as_slice()),
                             )
                         })
                         .await??;
-                        let last_saved = first_version + num_to_save as u64 - 1;
+                        Some((prepared, first_version + num_to_save as u64 - 1))
+                    } else {
+                        None
+                    };
+
+                    // create iterator of txn and its outputs to be replayed after the snapshot.
+                    let txns_to_replay = stream::iter(
+                        izip!(txns, txn_infos, write_sets, event_vecs).map(Result::<_>::Ok),
+                    );
+                    Ok((prepared, memory_reservation, txns_to_replay))
+                })
+            })
+            .try_buffered_x(con * 2, con)
+            .and_then(move |(prepared, memory_reservation, txns_to_replay)| {
+                let restore_handler = restore_handler_for_commit.clone();
+                async move {
+                    if let Some((prepared, last_saved)) = prepared {
+                        tokio::task::spawn_blocking(move || {
+                            restore_handler.commit_prepared_transactions(prepared)
+                        })
+                        .await??;
                         TRANSACTION_SAVE_VERSION.set(last_saved as i64);
                         info!(
                             version = last_saved,
@@ -479,14 +543,10 @@ impl TransactionRestoreBatchController {
                             "Transactions saved."
                         );
                     }
-
-                    // create iterator of txn and its outputs to be replayed after the snapshot.
-                    Ok(stream::iter(
-                        izip!(txns, txn_infos, write_sets, event_vecs).map(Result::<_>::Ok),
-                    ))
-                })
+                    drop(memory_reservation);
+                    Ok(txns_to_replay)
+                }
             })
-            .try_buffered_x(self.global_opt.concurrent_downloads, 1)
             .try_flatten()
             .peekable();
 
@@ -587,11 +647,21 @@ impl TransactionRestoreBatchController {
             Item = Result<(Transaction, TransactionInfo, WriteSet, Vec<ContractEvent>)>,
         >,
     ) -> Result<()> {
-        let (first_version, _) = self.replay_from_version.unwrap();
+        let (replay_from_version, _) = self.replay_from_version.unwrap();
         restore_handler.reset_state_store();
         let replay_start = Instant::now();
         let db = DbReaderWriter::from_arc(Arc::clone(&restore_handler.aptosdb));
         let chunk_replayer = Arc::new(ChunkExecutor::<AptosVM>::new(db));
+        // The stream starts after the latest version in the DB, so the transactions committed by
+        // an interrupted run are not replayed again.
+        let first_version = chunk_replayer.resume_replay()?;
+        if first_version > replay_from_version {
+            info!(
+                replay_from_version = replay_from_version,
+                first_version = first_version,
+                "Resuming transaction replay."
+            );
+        }
         let db_commit_stream = txns_to_execute_stream
             .try_chunks(BATCH_SIZE)
             .err_into::<anyhow::Error>()
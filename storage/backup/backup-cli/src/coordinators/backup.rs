@@ -5,7 +5,10 @@
 use crate::{
     backup_types::{
         epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
-        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        state_snapshot::{
+            backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+            manifest::StateSnapshotBackup,
+        },
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
     metadata,
@@ -15,13 +18,13 @@ use crate::{
     },
     storage::{BackupStorage, FileHandle},
     utils::{
-        backup_service_client::BackupServiceClient, unix_timestamp_sec, ConcurrentDownloadsOpt,
-        GlobalBackupOpt,
+        backup_service_client::BackupServiceClient, storage_ext::BackupStorageExt,
+        unix_timestamp_sec, ConcurrentDownloadsOpt, GlobalBackupOpt,
     },
 };
 use anyhow::{anyhow, ensure, Result};
 use aptos_db::backup::backup_handler::DbState;
-use aptos_infallible::duration_since_epoch;
+use aptos_infallible::{duration_since_epoch, Mutex};
 use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
 use clap::Parser;
@@ -69,6 +72,13 @@ pub struct BackupCoordinatorOpt {
         many small files. "
     )]
     pub transaction_batch_size: usize,
+    #[clap(
+        long,
+        help = "Take state snapshots with deduplication on, each reusing the unchanged chunks of \
+        the previous snapshot, see `--dedup` of `backup one state-snapshot`. Chunks left behind by \
+        failed snapshots can be removed by `backup-maintenance gc`."
+    )]
+    pub dedup_state_snapshots: bool,
     #[clap(flatten)]
     pub concurrent_downloads: ConcurrentDownloadsOpt,
 }
@@ -90,6 +100,9 @@ pub struct BackupCoordinator {
     metadata_cache_opt: MetadataCacheOpt,
     state_snapshot_interval_epochs: usize,
     transaction_batch_size: usize,
+    dedup_state_snapshots: bool,
+    /// Manifest of the latest state snapshot, the base of the next one if deduplicating.
+    last_state_snapshot_manifest: Mutex<Option<FileHandle>>,
     concurrent_downloads: usize,
 }
 
@@ -108,19 +121,24 @@ impl BackupCoordinator {
             metadata_cache_opt: opt.metadata_cache_opt,
             state_snapshot_interval_epochs: opt.state_snapshot_interval_epochs,
             transaction_batch_size: opt.transaction_batch_size,
+            dedup_state_snapshots: opt.dedup_state_snapshots,
+            last_state_snapshot_manifest: Mutex::new(None),
             concurrent_downloads: opt.concurrent_downloads.get(),
         }
     }
 
     pub async fn run(&self) -> Result<()> {
         // Connect to both the local node and the backup storage.
-        let backup_state = metadata::cache::sync_and_load(
+        let metadata_view = metadata::cache::sync_and_load(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
             self.concurrent_downloads,
         )
-        .await?
-        .get_storage_state()?;
+        .await?;
+        let backup_state = metadata_view.get_storage_state()?;
+        *self.last_state_snapshot_manifest.lock() = metadata_view
+            .select_state_snapshot(Version::MAX)?
+            .map(|meta| meta.manifest);
 
         // On new DbState retrieved:
         // `watch_db_state` informs `backup_epoch_endings` via channel 1,
@@ -255,14 +273,24 @@ impl BackupCoordinator {
             return Ok(last_snapshot_epoch_in_backup);
         }
 
-        StateSnapshotBackupController::new(
-            StateSnapshotBackupOpt { epoch },
+        let dedup_base_manifest = if self.dedup_state_snapshots {
+            self.last_state_snapshot_manifest.lock().clone()
+        } else {
+            None
+        };
+        let manifest = StateSnapshotBackupController::new(
+            StateSnapshotBackupOpt {
+                epoch,
+                dedup: self.dedup_state_snapshots,
+                dedup_base_manifest,
+            },
             self.global_opt.clone(),
             Arc::clone(&self.client),
             Arc::clone(&self.storage),
         )
         .run()
         .await?;
+        *self.last_state_snapshot_manifest.lock() = Some(manifest);
 
         Ok(Some(epoch))
     }
@@ -467,6 +495,76 @@ impl BackupCompactor {
     }
 }
 
+/// Deletes the content addressed state snapshot chunks (see `StateSnapshotBackupOpt::dedup`) not
+/// referenced by any state snapshot backup in the metadata, for example the ones written by failed
+/// backups.
+///
+/// Must not run concurrently with a state snapshot backup, whose newly written chunks are not
+/// referenced until it finishes.
+pub struct BackupGarbageCollector {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    concurrent_downloads: usize,
+    dry_run: bool,
+}
+
+impl BackupGarbageCollector {
+    pub fn new(
+        metadata_cache_opt: MetadataCacheOpt,
+        storage: Arc<dyn BackupStorage>,
+        concurrent_downloads: usize,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            storage,
+            metadata_cache_opt,
+            concurrent_downloads,
+            dry_run,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        info!("Backup garbage collection started.");
+        let metaview = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+
+        let mut referenced = HashSet::new();
+        for meta in metaview.all_state_snapshots() {
+            let manifest: StateSnapshotBackup = self.storage.load_json_file(&meta.manifest).await?;
+            referenced.extend(manifest.chunks.into_iter().map(|chunk| chunk.blobs));
+        }
+
+        let unreferenced = self
+            .storage
+            .list_backup_files()
+            .await?
+            .into_iter()
+            .filter(|file| {
+                StateSnapshotBackupController::is_content_addressed_chunk(file)
+                    && !referenced.contains(file)
+            })
+            .collect::<Vec<_>>();
+        info!(
+            num_unreferenced = unreferenced.len(),
+            dry_run = self.dry_run,
+            "Found unreferenced state snapshot chunks."
+        );
+        if self.dry_run {
+            return Ok(());
+        }
+        for file in unreferenced {
+            info!(file = file, "Delete unreferenced chunk.");
+            self.storage.delete_file(&file).await?;
+        }
+
+        Ok(())
+    }
+}
+
 trait Worker<'a, S, Fut: Future<Output = Result<S>> + 'a>:
     Fn(&'a BackupCoordinator, S, DbState) -> Fut
 {
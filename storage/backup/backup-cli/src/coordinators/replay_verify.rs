@@ -10,7 +10,7 @@ use crate::{
     metadata,
     metadata::cache::MetadataCacheOpt,
     storage::BackupStorage,
-    utils::{GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt},
+    utils::{GlobalRestoreOptions, ParallelRestoreOpt, RestoreRunMode, TrustedWaypointOpt},
 };
 use anyhow::Result;
 use aptos_db::backup::restore_handler::RestoreHandler;
@@ -158,6 +158,8 @@ impl ReplayVerifyCoordinator {
             run_mode,
             concurrent_downloads: self.concurrent_downloads,
             replay_concurrency_level: 0, // won't replay, doesn't matter
+            concurrency: self.concurrent_downloads,
+            memory_budget: ParallelRestoreOpt::default().memory_budget,
         };
         if !skip_snapshot {
             if let Some(backup) = state_snapshot {
@@ -192,6 +194,15 @@ impl ReplayVerifyCoordinator {
         .await?;
 
         if self.verify_execution_mode.seen_error() {
+            for mismatch in self.verify_execution_mode.mismatches() {
+                error!(
+                    version = mismatch.version,
+                    fields = ?mismatch.mismatching_fields,
+                    num_write_ops_diff = mismatch.write_ops_diff.len(),
+                    num_events_diff = mismatch.events_diff.len(),
+                    "Transaction mismatch."
+                );
+            }
             Err(ReplayError::TxnMismatch)
         } else {
             Ok(())
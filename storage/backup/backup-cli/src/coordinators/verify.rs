@@ -14,7 +14,10 @@ use crate::{
         VERIFY_COORDINATOR_FAIL_TS, VERIFY_COORDINATOR_START_TS, VERIFY_COORDINATOR_SUCC_TS,
     },
     storage::BackupStorage,
-    utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt},
+    utils::{
+        unix_timestamp_sec, GlobalRestoreOptions, ParallelRestoreOpt, RestoreRunMode,
+        TrustedWaypointOpt,
+    },
 };
 use anyhow::Result;
 use aptos_db::state_restore::StateSnapshotRestoreMode;
@@ -102,6 +105,8 @@ impl VerifyCoordinator {
             run_mode: Arc::new(RestoreRunMode::Verify),
             concurrent_downloads: self.concurrent_downloads,
             replay_concurrency_level: 0, // won't replay, doesn't matter
+            concurrency: self.concurrent_downloads,
+            memory_budget: ParallelRestoreOpt::default().memory_budget,
         };
 
         let epoch_history = if self.skip_epoch_endings {
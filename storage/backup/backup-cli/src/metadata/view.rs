@@ -118,6 +118,10 @@ impl MetadataView {
             .map(Clone::clone))
     }
 
+    pub fn all_state_snapshots(&self) -> &[StateSnapshotBackupMeta] {
+        &self.state_snapshot_backups
+    }
+
     pub fn expect_state_snapshot(&self, version: Version) -> Result<StateSnapshotBackupMeta> {
         self.state_snapshot_backups
             .iter()
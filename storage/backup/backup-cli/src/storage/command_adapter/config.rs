@@ -70,6 +70,15 @@ pub struct Commands {
     pub list_metadata_files: String,
     /// Command line to backup one metadata file to a metadata backup folder
     pub backup_metadata_file: Option<String>,
+    /// Command line to list all files created by `create_for_write`, across all backups. Only
+    /// needed for garbage collection.
+    /// expected stdout to stream out lines of file handles.
+    pub list_backup_files: Option<String>,
+    /// Command line to delete a file created by `create_for_write`. Only needed for garbage
+    /// collection.
+    /// input env vars:
+    ///     $FILE_HANDLE
+    pub delete_file: Option<String>,
 }
 
 #[derive(Clone, Default, Deserialize)]
@@ -190,4 +190,36 @@ impl BackupStorage for CommandAdapter {
         file_handle.truncate(file_handle.trim_end().len());
         Ok(file_handle)
     }
+
+    async fn list_backup_files(&self) -> Result<Vec<FileHandle>> {
+        let command = self
+            .config
+            .commands
+            .list_backup_files
+            .as_ref()
+            .ok_or_else(|| format_err!("list_backup_files command not defined."))?;
+        let child = self.cmd(command, vec![]).spawn()?;
+
+        let mut buf = FileHandle::new();
+        child
+            .into_data_source()
+            .read_to_string(&mut buf)
+            .await
+            .err_notes((file!(), line!(), &buf))?;
+        Ok(buf.lines().map(str::to_string).collect())
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let command = self
+            .config
+            .commands
+            .delete_file
+            .as_ref()
+            .ok_or_else(|| format_err!("delete_file command not defined."))?;
+        let child = self
+            .cmd(command, vec![EnvVar::file_handle(file_handle.to_string())])
+            .spawn()?;
+        child.join().await?;
+        Ok(())
+    }
 }
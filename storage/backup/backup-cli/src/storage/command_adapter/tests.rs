@@ -6,8 +6,8 @@ use super::*;
 use crate::storage::{
     command_adapter::config::Commands,
     test_util::{
-        arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
-        test_write_and_read_impl,
+        arb_backups, arb_metadata_files, test_list_and_delete_backup_files_impl,
+        test_save_and_list_metadata_files_impl, test_write_and_read_impl,
     },
 };
 use aptos_temppath::TempPath;
@@ -31,6 +31,8 @@ commands:
   save_metadata_line: 'cd "$FOLDER" && mkdir -p metadata && cd metadata && FILE_HANDLE="metadata/$FILE_NAME" && echo "$FILE_HANDLE" && echo "$FILE_HANDLE" && exec 1>&- && cat > $FILE_NAME'
   list_metadata_files: 'cd "$FOLDER" && (test -d metadata && cd metadata && ls -1 || exec) | while read f; do echo metadata/$f; done'
   backup_metadata_file: 'cd "$FOLDER" && mkdir -p metadata_backup && mv metadata/$FILE_NAME metadata_backup/$FILE_NAME'
+  list_backup_files: 'cd "$FOLDER" && find . -mindepth 2 -maxdepth 2 -type f -not -path "./metadata/*" -not -path "./metadata_backup/*" | sed -e "s#^\./##"'
+  delete_file: 'rm "$FOLDER/$FILE_HANDLE"'
 "#, tmpdir.path().to_str().unwrap()),
     ).unwrap();
 
@@ -59,6 +61,14 @@ proptest! {
         let tmpdir = TempPath::new();
         block_on(test_save_and_list_metadata_files_impl(get_store(&tmpdir), input));
     }
+
+    #[test]
+    fn test_list_and_delete_backup_files(
+        backups in arb_backups()
+    ) {
+        let tmpdir = TempPath::new();
+        block_on(test_list_and_delete_backup_files_impl(get_store(&tmpdir), backups));
+    }
 }
 
 fn dummy_store(cmd: &str) -> CommandAdapter {
@@ -70,6 +80,8 @@ fn dummy_store(cmd: &str) -> CommandAdapter {
             save_metadata_line: cmd.to_string(),
             list_metadata_files: cmd.to_string(),
             backup_metadata_file: Some(cmd.to_string()),
+            list_backup_files: Some(cmd.to_string()),
+            delete_file: Some(cmd.to_string()),
         },
         env_vars: Vec::new(),
     })
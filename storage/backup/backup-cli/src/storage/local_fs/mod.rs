@@ -22,7 +22,7 @@ use std::{
     str::FromStr,
 };
 use tokio::{
-    fs::{create_dir_all, read_dir, rename, OpenOptions},
+    fs::{create_dir_all, read_dir, remove_file, rename, OpenOptions},
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
 };
 
@@ -180,4 +180,34 @@ impl BackupStorage for LocalFs {
             .path_to_string()?;
         Ok(fh)
     }
+
+    async fn list_backup_files(&self) -> Result<Vec<FileHandle>> {
+        let mut res = Vec::new();
+        let mut backups = read_dir(&self.dir).await.err_notes(&self.dir)?;
+        while let Some(backup) = backups.next_entry().await.err_notes(&self.dir)? {
+            let backup_name = backup.file_name();
+            if backup_name == Self::METADATA_DIR
+                || backup_name == Self::METADATA_BACKUP_DIR
+                || !backup.file_type().await?.is_dir()
+            {
+                continue;
+            }
+            let dir = backup.path();
+            let mut entries = read_dir(&dir).await.err_notes(&dir)?;
+            while let Some(entry) = entries.next_entry().await.err_notes(&dir)? {
+                res.push(
+                    Path::new(&backup_name)
+                        .join(entry.file_name())
+                        .path_to_string()?,
+                )
+            }
+        }
+        Ok(res)
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let path = self.dir.join(file_handle);
+        remove_file(&path).await.err_notes(&path)?;
+        Ok(())
+    }
 }
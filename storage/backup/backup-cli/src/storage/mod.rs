@@ -4,6 +4,7 @@
 
 pub mod command_adapter;
 pub mod local_fs;
+pub mod object_store;
 
 #[cfg(test)]
 mod test_util;
@@ -13,6 +14,7 @@ mod tests;
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    object_store::{Gcs, GcsOpt, ObjectStoreStorage, S3Opt, S3},
 };
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
@@ -185,6 +187,11 @@ pub trait BackupStorage: Send + Sync {
         name: &ShellSafeName,
         lines: &[TextLine],
     ) -> Result<FileHandle>;
+    /// Lists all files created by `create_for_write`, across all backups, for garbage collection.
+    /// Metadata files are not included.
+    async fn list_backup_files(&self) -> Result<Vec<FileHandle>>;
+    /// Deletes a file created by `create_for_write`.
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()>;
 }
 
 #[derive(Parser)]
@@ -198,6 +205,18 @@ pub enum StorageOpt {
     https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/command_adapter/sample_configs/"
     )]
     CommandAdapter(CommandAdapterOpt),
+    #[clap(
+        about = "Select the S3 backup storage type, which talks to AWS S3 or another S3 compatible \
+    object store (e.g. MinIO, Cloudflare R2) directly. See sample configs here: \
+    https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/object_store/sample_configs/"
+    )]
+    S3(S3Opt),
+    #[clap(
+        about = "Select the GCS backup storage type, which talks to Google Cloud Storage directly. \
+    See a sample config here: \
+    https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/object_store/sample_configs/"
+    )]
+    Gcs(GcsOpt),
 }
 
 impl StorageOpt {
@@ -205,6 +224,10 @@ impl StorageOpt {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+            StorageOpt::S3(opt) => Arc::new(ObjectStoreStorage::new(S3::new_with_opt(opt).await?)),
+            StorageOpt::Gcs(opt) => {
+                Arc::new(ObjectStoreStorage::new(Gcs::new_with_opt(opt).await?))
+            },
         })
     }
 }
@@ -213,7 +236,7 @@ impl StorageOpt {
 #[clap(group(
     ArgGroup::new("storage")
     .required(true)
-    .args(&["local_fs_dir", "command_adapter_config"]),
+    .args(&["local_fs_dir", "command_adapter_config", "s3_config", "gcs_config"]),
 ))]
 pub struct DBToolStorageOpt {
     #[clap(
@@ -229,12 +252,31 @@ pub struct DBToolStorageOpt {
     https://github.com/aptos-labs/aptos-networks/tree/main/testnet/backups "
     )]
     command_adapter_config: Option<CommandAdapterOpt>,
+    #[clap(
+        long,
+        help = "Select the S3 backup storage type, which talks to AWS S3 or another S3 compatible \
+    object store (e.g. MinIO, Cloudflare R2) directly, configured by this config file. See sample \
+    configs here: \
+    https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/object_store/sample_configs/"
+    )]
+    s3_config: Option<S3Opt>,
+    #[clap(
+        long,
+        help = "Select the GCS backup storage type, which talks to Google Cloud Storage directly, \
+    configured by this config file. See a sample config here: \
+    https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/object_store/sample_configs/"
+    )]
+    gcs_config: Option<GcsOpt>,
 }
 
 impl DBToolStorageOpt {
     pub async fn init_storage(self) -> Result<Arc<dyn BackupStorage>> {
-        Ok(if self.local_fs_dir.is_some() {
-            Arc::new(LocalFs::new_with_opt(self.local_fs_dir.unwrap()))
+        Ok(if let Some(opt) = self.local_fs_dir {
+            Arc::new(LocalFs::new_with_opt(opt))
+        } else if let Some(opt) = self.s3_config {
+            Arc::new(ObjectStoreStorage::new(S3::new_with_opt(opt).await?))
+        } else if let Some(opt) = self.gcs_config {
+            Arc::new(ObjectStoreStorage::new(Gcs::new_with_opt(opt).await?))
         } else {
             Arc::new(CommandAdapter::new_with_opt(self.command_adapter_config.unwrap()).await?)
         })
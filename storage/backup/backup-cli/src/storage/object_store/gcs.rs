@@ -0,0 +1,453 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::object_store::{
+    ensure_success, full_key, into_reader, load_config, read_part, send_with_retries, uri_encode,
+    ObjectStore,
+};
+use anyhow::{ensure, format_err, Result};
+use async_trait::async_trait;
+use clap::Parser;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::{io::AsyncRead, sync::Mutex};
+
+#[derive(Parser, Clone, Debug, Serialize, Deserialize)]
+pub struct GcsOpt {
+    #[clap(long = "config", help = "Config file for the GCS backup store.")]
+    config: PathBuf,
+}
+
+impl FromStr for GcsOpt {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(GcsOpt {
+            config: PathBuf::from(s),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GcsConfig {
+    pub bucket: String,
+    /// Everything is stored under this prefix of the bucket.
+    #[serde(default)]
+    pub prefix: String,
+    /// Key file of the service account to authenticate as. If not set, the file pointed to by the
+    /// `GOOGLE_APPLICATION_CREDENTIALS` env var is used, and if that's not set either, the
+    /// credentials of the instance are fetched from the metadata server (which works on GCE, and
+    /// on GKE with workload identity).
+    pub credentials_file: Option<PathBuf>,
+    /// Files larger than this are uploaded in chunks of this size with a resumable upload, which
+    /// is also about the memory used by each file being uploaded. Rounded up to a multiple of
+    /// 256KB, as required by GCS.
+    #[serde(default = "GcsConfig::default_chunk_size")]
+    pub chunk_size: usize,
+    #[serde(default = "GcsConfig::default_max_retries")]
+    pub max_retries: usize,
+    #[serde(default = "GcsConfig::default_endpoint")]
+    pub endpoint: String,
+}
+
+impl GcsConfig {
+    const CHUNK_SIZE_QUANTUM: usize = 256 << 10;
+
+    fn default_chunk_size() -> usize {
+        64 << 20
+    }
+
+    fn default_max_retries() -> usize {
+        5
+    }
+
+    fn default_endpoint() -> String {
+        "https://storage.googleapis.com".to_string()
+    }
+}
+
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Serialize)]
+struct JwtClaims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+enum TokenSource {
+    ServiceAccount(ServiceAccountKey),
+    MetadataServer,
+}
+
+struct AccessToken {
+    token: String,
+    expires_at: Instant,
+}
+
+/// Fetches OAuth2 access tokens, and caches them until about to expire.
+struct TokenProvider {
+    source: TokenSource,
+    client: reqwest::Client,
+    max_retries: usize,
+    token: Mutex<Option<AccessToken>>,
+}
+
+impl TokenProvider {
+    /// Tokens are refreshed this long before they expire.
+    const EXPIRY_MARGIN: Duration = Duration::from_secs(300);
+    const METADATA_SERVER_TOKEN_URL: &'static str =
+        "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+    const SCOPE: &'static str = "https://www.googleapis.com/auth/devstorage.read_write";
+
+    async fn new(config: &GcsConfig, client: reqwest::Client) -> Result<Self> {
+        let credentials_file = config
+            .credentials_file
+            .clone()
+            .or_else(|| std::env::var_os("GOOGLE_APPLICATION_CREDENTIALS").map(PathBuf::from));
+        let source = match credentials_file {
+            Some(path) => {
+                TokenSource::ServiceAccount(serde_json::from_slice(&tokio::fs::read(&path).await?)?)
+            },
+            None => TokenSource::MetadataServer,
+        };
+        Ok(Self {
+            source,
+            client,
+            max_retries: config.max_retries,
+            token: Mutex::new(None),
+        })
+    }
+
+    async fn token(&self) -> Result<String> {
+        let mut cached = self.token.lock().await;
+        if let Some(token) = &*cached {
+            if token.expires_at > Instant::now() + Self::EXPIRY_MARGIN {
+                return Ok(token.token.clone());
+            }
+        }
+
+        let fetched_at = Instant::now();
+        let response = self.fetch_token().await?;
+        *cached = Some(AccessToken {
+            token: response.access_token.clone(),
+            expires_at: fetched_at + Duration::from_secs(response.expires_in),
+        });
+        Ok(response.access_token)
+    }
+
+    async fn fetch_token(&self) -> Result<TokenResponse> {
+        let response = match &self.source {
+            TokenSource::ServiceAccount(key) => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                let claims = JwtClaims {
+                    iss: &key.client_email,
+                    scope: Self::SCOPE,
+                    aud: &key.token_uri,
+                    iat: now,
+                    exp: now + 3600,
+                };
+                let assertion = jsonwebtoken::encode(
+                    &Header::new(Algorithm::RS256),
+                    &claims,
+                    &EncodingKey::from_rsa_pem(key.private_key.as_bytes())?,
+                )?;
+                let assertion = assertion.as_str();
+                send_with_retries(self.max_retries, || async move {
+                    Ok(self.client.post(&key.token_uri).form(&[
+                        ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                        ("assertion", assertion),
+                    ]))
+                })
+                .await?
+            },
+            TokenSource::MetadataServer => {
+                send_with_retries(self.max_retries, || async move {
+                    Ok(self
+                        .client
+                        .get(Self::METADATA_SERVER_TOKEN_URL)
+                        .header("Metadata-Flavor", "Google"))
+                })
+                .await?
+            },
+        };
+        Ok(ensure_success(response).await?.json().await?)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListObjectsResponse {
+    #[serde(default)]
+    items: Vec<ObjectResource>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ObjectResource {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RewriteResponse {
+    done: bool,
+    rewrite_token: Option<String>,
+}
+
+/// An `ObjectStore` speaking the GCS JSON API.
+pub struct Gcs {
+    config: GcsConfig,
+    chunk_size: usize,
+    token_provider: TokenProvider,
+    client: reqwest::Client,
+}
+
+impl Gcs {
+    pub async fn new(config: GcsConfig) -> Result<Self> {
+        ensure!(config.chunk_size > 0, "GCS chunk_size must be positive.");
+        let quantum = GcsConfig::CHUNK_SIZE_QUANTUM;
+        let chunk_size = (config.chunk_size + quantum - 1) / quantum * quantum;
+        let client = reqwest::Client::new();
+        Ok(Self {
+            token_provider: TokenProvider::new(&config, client.clone()).await?,
+            config,
+            chunk_size,
+            client,
+        })
+    }
+
+    pub async fn new_with_opt(opt: GcsOpt) -> Result<Self> {
+        Self::new(load_config(&opt.config).await?).await
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        full_key(&self.config.prefix, key)
+    }
+
+    fn bucket_url(&self) -> String {
+        format!(
+            "{}/storage/v1/b/{}/o",
+            self.config.endpoint,
+            uri_encode(&self.config.bucket, true)
+        )
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        format!(
+            "{}/{}",
+            self.bucket_url(),
+            uri_encode(&self.full_key(key), true)
+        )
+    }
+
+    fn upload_url(&self, upload_type: &str, key: &str) -> String {
+        format!(
+            "{}/upload/storage/v1/b/{}/o?uploadType={}&name={}",
+            self.config.endpoint,
+            uri_encode(&self.config.bucket, true),
+            upload_type,
+            uri_encode(&self.full_key(key), true)
+        )
+    }
+
+    /// Sends an authenticated request, with retries. `build_request` is called for each attempt.
+    async fn request(
+        &self,
+        build_request: impl Fn() -> RequestBuilder + Send + Sync,
+    ) -> Result<Response> {
+        let build_request = &build_request;
+        send_with_retries(self.config.max_retries, || async move {
+            Ok(build_request().bearer_auth(self.token_provider.token().await?))
+        })
+        .await
+    }
+
+    /// Uploads the content in chunks, see
+    /// <https://cloud.google.com/storage/docs/performing-resumable-uploads>.
+    async fn resumable_upload(
+        &self,
+        key: &str,
+        first_chunk: Vec<u8>,
+        content: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<()> {
+        let upload_url = self.upload_url("resumable", key);
+        let response = ensure_success(
+            self.request(|| self.client.post(&upload_url).header("Content-Length", 0))
+                .await?,
+        )
+        .await?;
+        let session_url = response
+            .headers()
+            .get("location")
+            .ok_or_else(|| format_err!("No resumable upload session URL for {}.", key))?
+            .to_str()?
+            .to_string();
+
+        let mut offset = 0;
+        let mut chunk = first_chunk;
+        loop {
+            // Only the last chunk states the total size.
+            let next_chunk = read_part(content, self.chunk_size).await?;
+            let end = offset + chunk.len();
+            let content_range = if next_chunk.is_empty() {
+                format!("bytes {}-{}/{}", offset, end - 1, end)
+            } else {
+                format!("bytes {}-{}/*", offset, end - 1)
+            };
+            let response = self
+                .request(|| {
+                    self.client
+                        .put(&session_url)
+                        .header("Content-Range", &content_range)
+                        .body(chunk.clone())
+                })
+                .await?;
+            if next_chunk.is_empty() {
+                ensure_success(response).await?;
+                return Ok(());
+            }
+            ensure!(
+                response.status() == StatusCode::PERMANENT_REDIRECT,
+                "Unexpected status {} uploading {} at offset {}.",
+                response.status(),
+                key,
+                offset,
+            );
+            offset = end;
+            chunk = next_chunk;
+        }
+    }
+}
+
+#[async_trait]
+impl ObjectStore for Gcs {
+    async fn put_object(
+        &self,
+        key: &str,
+        mut content: Box<dyn AsyncRead + Send + Unpin>,
+    ) -> Result<()> {
+        let first_chunk = read_part(content.as_mut(), self.chunk_size).await?;
+        if first_chunk.len() < self.chunk_size {
+            let upload_url = self.upload_url("media", key);
+            ensure_success(
+                self.request(|| self.client.post(&upload_url).body(first_chunk.clone()))
+                    .await?,
+            )
+            .await?;
+            Ok(())
+        } else {
+            self.resumable_upload(key, first_chunk, content.as_mut())
+                .await
+        }
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let object_url = self.object_url(key);
+        let response = ensure_success(
+            self.request(|| self.client.get(&object_url).query(&[("alt", "media")]))
+                .await?,
+        )
+        .await?;
+        Ok(into_reader(response))
+    }
+
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let bucket_url = self.bucket_url();
+        let full_prefix = self.full_key(prefix);
+        let mut keys = vec![];
+        let mut page_token = None;
+        loop {
+            let response: ListObjectsResponse = ensure_success(
+                self.request(|| {
+                    let mut request = self.client.get(&bucket_url).query(&[
+                        ("prefix", full_prefix.as_str()),
+                        ("fields", "items(name),nextPageToken"),
+                    ]);
+                    if let Some(token) = &page_token {
+                        request = request.query(&[("pageToken", token)]);
+                    }
+                    request
+                })
+                .await?,
+            )
+            .await?
+            .json()
+            .await?;
+            for object in response.items {
+                let key = object
+                    .name
+                    .strip_prefix(&self.full_key(""))
+                    .ok_or_else(|| format_err!("Unexpected object {} listed.", object.name))?;
+                keys.push(key.to_string());
+            }
+            page_token = response.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn move_object(&self, from_key: &str, to_key: &str) -> Result<()> {
+        let from_url = self.object_url(from_key);
+        let rewrite_url = format!(
+            "{}/rewriteTo/b/{}/o/{}",
+            from_url,
+            uri_encode(&self.config.bucket, true),
+            uri_encode(&self.full_key(to_key), true)
+        );
+        // Large objects take multiple calls to be copied.
+        let mut rewrite_token = None;
+        loop {
+            let response: RewriteResponse = ensure_success(
+                self.request(|| {
+                    let mut request = self
+                        .client
+                        .request(Method::POST, &rewrite_url)
+                        .header("Content-Length", 0);
+                    if let Some(token) = &rewrite_token {
+                        request = request.query(&[("rewriteToken", token)]);
+                    }
+                    request
+                })
+                .await?,
+            )
+            .await?
+            .json()
+            .await?;
+            if response.done {
+                break;
+            }
+            rewrite_token = response.rewrite_token;
+        }
+        ensure_success(self.request(|| self.client.delete(&from_url)).await?).await?;
+        Ok(())
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<()> {
+        let url = self.object_url(key);
+        ensure_success(self.request(|| self.client.delete(&url)).await?).await?;
+        Ok(())
+    }
+}
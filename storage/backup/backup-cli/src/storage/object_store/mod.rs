@@ -0,0 +1,348 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Backup storages talking to object stores directly through their HTTP APIs, without shelling
+//! out to command line tools. See [`S3`] for S3 compatible stores (AWS S3, MinIO, Cloudflare R2
+//! etc.) and [`Gcs`] for Google Cloud Storage.
+//!
+//! Objects are laid out the same way as in the `LocalFs` storage, under a configurable prefix:
+//! files of a backup are under `<backup name>/`, metadata files under `metadata/`, and backed up
+//! metadata files under `metadata_backup/`.
+
+mod gcs;
+mod s3;
+
+#[cfg(test)]
+mod tests;
+
+pub use crate::storage::object_store::{
+    gcs::{Gcs, GcsConfig, GcsOpt},
+    s3::{S3Config, S3Opt, S3},
+};
+use crate::{
+    storage::{
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::error_notes::ErrorNotes,
+};
+use anyhow::{bail, format_err, Result};
+use aptos_logger::prelude::*;
+use async_trait::async_trait;
+use futures::{ready, Future, TryStreamExt};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::{
+    ffi::OsStr,
+    io,
+    path::Path,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, DuplexStream},
+    task::JoinHandle,
+};
+use tokio_io_timeout::TimeoutReader;
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+/// Operations of an object store, on keys relative to the configured prefix.
+#[async_trait]
+pub trait ObjectStore: Send + Sync + 'static {
+    /// Uploads everything read from `content` as the object `key`, in multiple parts if it's
+    /// large.
+    async fn put_object(&self, key: &str, content: Box<dyn AsyncRead + Send + Unpin>)
+        -> Result<()>;
+
+    async fn get_object(&self, key: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>>;
+
+    /// Returns the keys of all objects whose keys start with `prefix`.
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<String>>;
+
+    async fn move_object(&self, from_key: &str, to_key: &str) -> Result<()>;
+
+    async fn delete_object(&self, key: &str) -> Result<()>;
+}
+
+/// A `BackupStorage` on top of an `ObjectStore`.
+pub struct ObjectStoreStorage<S> {
+    store: Arc<S>,
+}
+
+impl<S: ObjectStore> ObjectStoreStorage<S> {
+    const METADATA_BACKUP_DIR: &'static str = "metadata_backup";
+    const METADATA_DIR: &'static str = "metadata";
+    /// Size of the pipe between a writer returned by `create_for_write()` and the upload.
+    const PIPE_SIZE: usize = 1 << 20;
+
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: ObjectStore> BackupStorage for ObjectStoreStorage<S> {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no directories in an object store, nothing to create.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let (writer, reader) = tokio::io::duplex(Self::PIPE_SIZE);
+        let store = Arc::clone(&self.store);
+        let key = file_handle.clone();
+        let upload = tokio::spawn(async move { store.put_object(&key, Box::new(reader)).await });
+        Ok((file_handle, Box::new(UploadSink::new(writer, upload))))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        self.store.get_object(file_handle).await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        self.store
+            .list_objects(&format!("{}/", Self::METADATA_DIR))
+            .await
+    }
+
+    /// file_handle are expected to be the return results from list_metadata_files
+    /// file_handle is a path with `metadata` in the path, Ex: metadata/epoch_ending_1.meta
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let name = Path::new(file_handle)
+            .file_name()
+            .and_then(OsStr::to_str)
+            .ok_or_else(|| format_err!("cannot extract filename from {}", file_handle))?;
+        self.store
+            .move_object(
+                file_handle,
+                &format!("{}/{}", Self::METADATA_BACKUP_DIR, name),
+            )
+            .await
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        let file_handle = format!("{}/{}", Self::METADATA_DIR, name.as_ref());
+        let content = lines
+            .iter()
+            .map(|e| e.as_ref())
+            .collect::<Vec<&str>>()
+            .join("");
+        self.store
+            .put_object(
+                &file_handle,
+                Box::new(io::Cursor::new(content.into_bytes())),
+            )
+            .await
+            .err_notes(name)?;
+        Ok(file_handle)
+    }
+
+    async fn list_backup_files(&self) -> Result<Vec<FileHandle>> {
+        let metadata_prefixes = [
+            format!("{}/", Self::METADATA_DIR),
+            format!("{}/", Self::METADATA_BACKUP_DIR),
+        ];
+        Ok(self
+            .store
+            .list_objects("")
+            .await?
+            .into_iter()
+            .filter(|key| !metadata_prefixes.iter().any(|p| key.starts_with(p)))
+            .collect())
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.store.delete_object(file_handle).await
+    }
+}
+
+/// Feeds what's written into an upload running in the background. Shutting it down waits for the
+/// upload to finish, and fails if the upload failed. Dropping it without shutting it down cancels
+/// the upload, so a partially written file is never uploaded.
+struct UploadSink {
+    writer: DuplexStream,
+    writer_shut_down: bool,
+    upload: JoinHandle<Result<()>>,
+}
+
+impl UploadSink {
+    fn new(writer: DuplexStream, upload: JoinHandle<Result<()>>) -> Self {
+        Self {
+            writer,
+            writer_shut_down: false,
+            upload,
+        }
+    }
+
+    fn poll_upload(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.upload).poll(cx).map(|res| {
+            res.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+        })
+    }
+
+    /// When the upload fails, the pipe breaks. Reports the cause instead.
+    fn on_write_error(&mut self, cx: &mut Context<'_>, error: io::Error) -> Poll<io::Error> {
+        if error.kind() == io::ErrorKind::BrokenPipe {
+            self.poll_upload(cx).map(|res| res.err().unwrap_or(error))
+        } else {
+            Poll::Ready(error)
+        }
+    }
+}
+
+impl AsyncWrite for UploadSink {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match Pin::new(&mut self.writer).poll_write(cx, buf) {
+            Poll::Ready(Err(e)) => self.on_write_error(cx, e).map(Err),
+            res => res,
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match Pin::new(&mut self.writer).poll_flush(cx) {
+            Poll::Ready(Err(e)) => self.on_write_error(cx, e).map(Err),
+            res => res,
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if !self.writer_shut_down {
+            // The upload sees the end of the content.
+            ready!(Pin::new(&mut self.writer).poll_shutdown(cx))?;
+            self.writer_shut_down = true;
+        }
+        self.poll_upload(cx)
+    }
+}
+
+impl Drop for UploadSink {
+    fn drop(&mut self) {
+        // Aborted before dropping the writer, which the upload would take as the end of the
+        // content.
+        if !self.writer_shut_down {
+            self.upload.abort();
+        }
+    }
+}
+
+/// Reads up to `size` bytes, fewer only if the end of `content` is hit.
+async fn read_part(content: &mut (dyn AsyncRead + Send + Unpin), size: usize) -> Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(size);
+    content.take(size as u64).read_to_end(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Sends the request built by `build_request`, retrying with exponential backoff upon connection
+/// errors, timeouts and server errors, at most `max_retries` times. A request is built for each
+/// attempt, because signatures and tokens can expire. Other responses are returned as they are,
+/// see `ensure_success()`.
+async fn send_with_retries<F, Fut>(max_retries: usize, build_request: F) -> Result<Response>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<RequestBuilder>>,
+{
+    const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+    let mut backoff = Duration::from_millis(100);
+    let mut num_retries = 0;
+    loop {
+        let error = match build_request().await?.send().await {
+            Ok(response) => {
+                let status = response.status();
+                if !(status.is_server_error()
+                    || status == StatusCode::TOO_MANY_REQUESTS
+                    || status == StatusCode::REQUEST_TIMEOUT)
+                {
+                    return Ok(response);
+                }
+                format_err!("{} {}", response.url().path(), status)
+            },
+            Err(e) => e.into(),
+        };
+        if num_retries >= max_retries {
+            return Err(error.context(format!("Giving up after {} retries.", num_retries)));
+        }
+        warn!(error = ?error, backoff = ?backoff, "Object store request failed, retrying.");
+        tokio::time::sleep(backoff).await;
+        backoff = std::cmp::min(backoff * 2, MAX_BACKOFF);
+        num_retries += 1;
+    }
+}
+
+/// Fails with the error message in the body unless the response is a success.
+async fn ensure_success(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        let path = response.url().path().to_string();
+        let body = response.text().await.unwrap_or_default();
+        bail!("{} {}: {}", path, status, body)
+    }
+}
+
+/// Streams the body of the response.
+fn into_reader(response: Response) -> Box<dyn AsyncRead + Send + Unpin> {
+    const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+    let reader = response
+        .bytes_stream()
+        .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
+        .into_async_read()
+        .compat();
+    let mut reader_with_read_timeout = TimeoutReader::new(reader);
+    reader_with_read_timeout.set_timeout(Some(READ_TIMEOUT));
+    Box::new(Box::pin(reader_with_read_timeout))
+}
+
+/// Prepends the configured prefix to a key.
+fn full_key(prefix: &str, key: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}/{}", prefix, key)
+    }
+}
+
+/// Percent-encodes everything but the unreserved characters in RFC 3986 (and '/', unless
+/// `encode_slash`), as required in object keys in URLs and in AWS signatures.
+fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            },
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+async fn load_config<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = tokio::fs::read(path).await.err_notes(path)?;
+    Ok(serde_yaml::from_slice(&content)?)
+}
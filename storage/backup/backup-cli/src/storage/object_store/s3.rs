@@ -0,0 +1,491 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::object_store::{
+    ensure_success, full_key, into_reader, load_config, read_part, send_with_retries, uri_encode,
+    ObjectStore,
+};
+use anyhow::{ensure, format_err, Result};
+use aptos_logger::prelude::*;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use futures::future;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{Method, Response};
+use ring::{digest, hmac};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, str::FromStr};
+use tokio::io::AsyncRead;
+use url::Url;
+
+#[derive(Parser, Clone, Debug, Serialize, Deserialize)]
+pub struct S3Opt {
+    #[clap(long = "config", help = "Config file for the S3 backup store.")]
+    config: PathBuf,
+}
+
+impl FromStr for S3Opt {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(S3Opt {
+            config: PathBuf::from(s),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Config {
+    /// E.g. "https://s3.us-west-2.amazonaws.com" for AWS, "http://localhost:9000" for MinIO, or
+    /// "https://<account id>.r2.cloudflarestorage.com" for R2.
+    pub endpoint: String,
+    /// "auto" for R2.
+    pub region: String,
+    pub bucket: String,
+    /// Everything is stored under this prefix of the bucket.
+    #[serde(default)]
+    pub prefix: String,
+    /// Addresses the bucket as a subdomain of the endpoint ("<bucket>.<endpoint host>") instead of
+    /// a path ("<endpoint host>/<bucket>"), which not all S3 compatible stores support.
+    #[serde(default)]
+    pub virtual_hosted_style: bool,
+    /// Credentials, read from the `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and
+    /// `AWS_SESSION_TOKEN` env vars if not set.
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub session_token: Option<String>,
+    /// Files larger than this are uploaded in parts of this size, which is also about the memory
+    /// used by each file being uploaded. S3 requires parts to be at least 5MB, and allows at most
+    /// 10000 parts.
+    #[serde(default = "S3Config::default_part_size")]
+    pub part_size: usize,
+    #[serde(default = "S3Config::default_max_retries")]
+    pub max_retries: usize,
+}
+
+impl S3Config {
+    const MIN_PART_SIZE: usize = 5 << 20;
+
+    fn default_part_size() -> usize {
+        64 << 20
+    }
+
+    fn default_max_retries() -> usize {
+        5
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct S3Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl S3Credentials {
+    fn from_config_or_env(config: &S3Config) -> Result<Self> {
+        let from_env = |name: &str| std::env::var(name).ok();
+        Ok(Self {
+            access_key_id: config
+                .access_key_id
+                .clone()
+                .or_else(|| from_env("AWS_ACCESS_KEY_ID"))
+                .ok_or_else(|| format_err!("S3 access_key_id is not set."))?,
+            secret_access_key: config
+                .secret_access_key
+                .clone()
+                .or_else(|| from_env("AWS_SECRET_ACCESS_KEY"))
+                .ok_or_else(|| format_err!("S3 secret_access_key is not set."))?,
+            session_token: config
+                .session_token
+                .clone()
+                .or_else(|| from_env("AWS_SESSION_TOKEN")),
+        })
+    }
+}
+
+/// An `ObjectStore` speaking the S3 API, authenticated with AWS signature version 4.
+pub struct S3 {
+    config: S3Config,
+    credentials: S3Credentials,
+    endpoint: Url,
+    client: reqwest::Client,
+}
+
+impl S3 {
+    pub fn new(config: S3Config) -> Result<Self> {
+        ensure!(
+            config.part_size >= S3Config::MIN_PART_SIZE,
+            "S3 part_size must be at least {} bytes.",
+            S3Config::MIN_PART_SIZE,
+        );
+        let credentials = S3Credentials::from_config_or_env(&config)?;
+        let endpoint = Url::parse(&config.endpoint)?;
+        ensure!(
+            endpoint.host_str().is_some(),
+            "S3 endpoint {} has no host.",
+            config.endpoint,
+        );
+        Ok(Self {
+            config,
+            credentials,
+            endpoint,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    pub async fn new_with_opt(opt: S3Opt) -> Result<Self> {
+        Self::new(load_config(&opt.config).await?)
+    }
+
+    /// Returns the host and the URI encoded path of an object, or of the bucket if `key` is empty.
+    fn host_and_path(&self, key: &str) -> (String, String) {
+        let mut host = self
+            .endpoint
+            .host_str()
+            .expect("Checked on creation.")
+            .to_string();
+        if let Some(port) = self.endpoint.port() {
+            host = format!("{}:{}", host, port);
+        }
+        let mut path = self.endpoint.path().trim_end_matches('/').to_string();
+        if self.config.virtual_hosted_style {
+            host = format!("{}.{}", self.config.bucket, host);
+        } else {
+            path = format!("{}/{}", path, uri_encode(&self.config.bucket, true));
+        }
+        if !key.is_empty() || self.config.virtual_hosted_style {
+            path = format!("{}/{}", path, uri_encode(key, false));
+        }
+        (host, path)
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, String)],
+        body: Vec<u8>,
+    ) -> Result<Response> {
+        let (host, path) = self.host_and_path(key);
+        let mut query = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", uri_encode(k, true), uri_encode(v, true)))
+            .collect::<Vec<_>>();
+        query.sort();
+        let query = query.join("&");
+        let url = if query.is_empty() {
+            format!("{}://{}{}", self.endpoint.scheme(), host, path)
+        } else {
+            format!("{}://{}{}?{}", self.endpoint.scheme(), host, path, query)
+        };
+        let payload_hash = hex::encode(digest::digest(&digest::SHA256, &body));
+
+        let response = send_with_retries(self.config.max_retries, || {
+            let signed_headers = sign(
+                &self.credentials,
+                &self.config.region,
+                method.as_str(),
+                &host,
+                &path,
+                &query,
+                headers,
+                &payload_hash,
+                Utc::now(),
+            );
+            let mut request = self.client.request(method.clone(), &url).body(body.clone());
+            for (name, value) in headers {
+                request = request.header(*name, value);
+            }
+            for (name, value) in signed_headers {
+                request = request.header(name, value);
+            }
+            future::ready(Ok(request))
+        })
+        .await?;
+        ensure_success(response).await
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        full_key(&self.config.prefix, key)
+    }
+
+    async fn upload_parts(
+        &self,
+        full_key: &str,
+        upload_id: &str,
+        first_part: Vec<u8>,
+        content: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<()> {
+        let mut parts = String::new();
+        let mut part = first_part;
+        let mut part_number = 1;
+        while !part.is_empty() {
+            let part_number_str = part_number.to_string();
+            let response = self
+                .request(
+                    Method::PUT,
+                    full_key,
+                    &[("partNumber", &part_number_str), ("uploadId", upload_id)],
+                    &[],
+                    part,
+                )
+                .await?;
+            let etag = response
+                .headers()
+                .get("etag")
+                .ok_or_else(|| format_err!("No ETag for part {} of {}.", part_number, full_key))?
+                .to_str()?
+                .to_string();
+            parts.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                part_number, etag
+            ));
+            part_number += 1;
+            part = read_part(content, self.config.part_size).await?;
+        }
+
+        let body = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            parts
+        );
+        let response = self
+            .request(
+                Method::POST,
+                full_key,
+                &[("uploadId", upload_id)],
+                &[],
+                body.into_bytes(),
+            )
+            .await?;
+        // Errors can be reported in the body of a 200 response.
+        let body = response.text().await?;
+        ensure!(
+            !body.contains("<Error>"),
+            "Failed to complete the upload of {}: {}",
+            full_key,
+            body,
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ObjectStore for S3 {
+    async fn put_object(
+        &self,
+        key: &str,
+        mut content: Box<dyn AsyncRead + Send + Unpin>,
+    ) -> Result<()> {
+        let full_key = self.full_key(key);
+        let first_part = read_part(content.as_mut(), self.config.part_size).await?;
+        if first_part.len() < self.config.part_size {
+            self.request(Method::PUT, &full_key, &[], &[], first_part)
+                .await?;
+            return Ok(());
+        }
+
+        let response = self
+            .request(Method::POST, &full_key, &[("uploads", "")], &[], vec![])
+            .await?;
+        let body = response.text().await?;
+        let upload_id = xml_values(&body, "UploadId")
+            .pop()
+            .ok_or_else(|| format_err!("No UploadId in response: {}", body))?;
+        let res = self
+            .upload_parts(&full_key, &upload_id, first_part, content.as_mut())
+            .await;
+        if res.is_err() {
+            // Otherwise the uploaded parts are kept, and charged for.
+            if let Err(e) = self
+                .request(
+                    Method::DELETE,
+                    &full_key,
+                    &[("uploadId", &upload_id)],
+                    &[],
+                    vec![],
+                )
+                .await
+            {
+                warn!(error = ?e, key = full_key, "Failed to abort multipart upload.");
+            }
+        }
+        res
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let response = self
+            .request(Method::GET, &self.full_key(key), &[], &[], vec![])
+            .await?;
+        Ok(into_reader(response))
+    }
+
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let full_prefix = self.full_key(prefix);
+        let mut keys = vec![];
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", full_prefix.as_str())];
+            if let Some(token) = &continuation_token {
+                query.push(("continuation-token", token.as_str()));
+            }
+            let body = self
+                .request(Method::GET, "", &query, &[], vec![])
+                .await?
+                .text()
+                .await?;
+            for full_key in xml_values(&body, "Key") {
+                let key = full_key
+                    .strip_prefix(&self.full_key(""))
+                    .ok_or_else(|| format_err!("Unexpected key {} listed.", full_key))?;
+                keys.push(key.to_string());
+            }
+            continuation_token = xml_values(&body, "NextContinuationToken").pop();
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn move_object(&self, from_key: &str, to_key: &str) -> Result<()> {
+        let from_full_key = self.full_key(from_key);
+        let copy_source = format!(
+            "/{}/{}",
+            self.config.bucket,
+            uri_encode(&from_full_key, false)
+        );
+        let body = self
+            .request(
+                Method::PUT,
+                &self.full_key(to_key),
+                &[],
+                &[("x-amz-copy-source", copy_source)],
+                vec![],
+            )
+            .await?
+            .text()
+            .await?;
+        ensure!(
+            !body.contains("<Error>"),
+            "Failed to copy {} to {}: {}",
+            from_key,
+            to_key,
+            body,
+        );
+        self.request(Method::DELETE, &from_full_key, &[], &[], vec![])
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<()> {
+        self.request(Method::DELETE, &self.full_key(key), &[], &[], vec![])
+            .await?;
+        Ok(())
+    }
+}
+
+/// Signs a request with AWS signature version 4, see
+/// <https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html>. `path` and
+/// `query` must be URI encoded, and `headers` are the headers to be sent besides the ones
+/// returned, with lowercase names. Returns the headers to add to the request.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn sign(
+    credentials: &S3Credentials,
+    region: &str,
+    method: &str,
+    host: &str,
+    path: &str,
+    query: &str,
+    headers: &[(&str, String)],
+    payload_hash: &str,
+    now: DateTime<Utc>,
+) -> Vec<(&'static str, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let mut added_headers = vec![
+        ("x-amz-content-sha256", payload_hash.to_string()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(session_token) = &credentials.session_token {
+        added_headers.push(("x-amz-security-token", session_token.clone()));
+    }
+    let mut signed_headers = headers
+        .iter()
+        .map(|(name, value)| (*name, value.trim()))
+        .chain(
+            added_headers
+                .iter()
+                .map(|(name, value)| (*name, value.trim())),
+        )
+        .chain(std::iter::once(("host", host)))
+        .collect::<Vec<_>>();
+    signed_headers.sort();
+    let canonical_headers = signed_headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect::<String>();
+    let signed_header_names = signed_headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method, path, query, canonical_headers, signed_header_names, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(digest::digest(
+            &digest::SHA256,
+            canonical_request.as_bytes()
+        )),
+    );
+
+    let hmac_sha256 = |key: &[u8], data: &str| {
+        hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data.as_bytes())
+    };
+    let mut signing_key = hmac_sha256(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        &date,
+    );
+    for data in [region, "s3", "aws4_request"] {
+        signing_key = hmac_sha256(signing_key.as_ref(), data);
+    }
+    let signature = hex::encode(hmac_sha256(signing_key.as_ref(), &string_to_sign));
+
+    added_headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credentials.access_key_id, scope, signed_header_names, signature
+        ),
+    ));
+    added_headers
+}
+
+/// Returns the unescaped text content of all `tag` elements. The S3 API responds with simple
+/// enough XML documents for this to work.
+pub(super) fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<(\w+)>([^<]*)</(\w+)>").unwrap());
+    TAG.captures_iter(xml)
+        .filter(|captures| &captures[1] == tag && &captures[3] == tag)
+        .map(|captures| {
+            captures[2]
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&")
+        })
+        .collect()
+}
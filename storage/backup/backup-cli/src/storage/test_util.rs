@@ -12,7 +12,10 @@ use proptest::{
     collection::{hash_map, vec},
     prelude::*,
 };
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 fn to_file_name(backup_name: &str, file_name: &str) -> String {
@@ -48,6 +51,51 @@ pub async fn test_write_and_read_impl(
     }
 }
 
+pub async fn test_list_and_delete_backup_files_impl(
+    store: Box<dyn BackupStorage>,
+    backups: HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>,
+) {
+    let mut expected = HashSet::new();
+    for (backup_name, files) in &backups {
+        let backup_handle = store.create_backup(backup_name).await.unwrap();
+        for (name, content) in files {
+            let (handle, mut file) = store.create_for_write(&backup_handle, name).await.unwrap();
+            file.write_all(content).await.unwrap();
+            file.shutdown().await.unwrap();
+            expected.insert(handle);
+        }
+    }
+    // Metadata files are not listed.
+    store
+        .save_metadata_line(
+            &"metadata_file".parse().unwrap(),
+            &TextLine::new("line").unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let listed: HashSet<_> = store
+        .list_backup_files()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(listed, expected);
+
+    let to_delete: Vec<_> = expected.iter().step_by(2).cloned().collect();
+    for handle in &to_delete {
+        store.delete_file(handle).await.unwrap();
+        expected.remove(handle);
+    }
+    let listed: HashSet<_> = store
+        .list_backup_files()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(listed, expected);
+}
+
 pub fn arb_backups(
 ) -> impl Strategy<Value = HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>> {
     hash_map(
@@ -5,7 +5,7 @@
 use crate::utils::error_notes::ErrorNotes;
 use anyhow::Result;
 use aptos_crypto::HashValue;
-use aptos_db::backup::backup_handler::DbState;
+use aptos_db::{backup::backup_handler::DbState, db_maintenance::MaintenanceStatus};
 use aptos_types::transaction::Version;
 use clap::Parser;
 use futures::TryStreamExt;
@@ -70,6 +70,17 @@ impl BackupServiceClient {
         Ok(Box::pin(reader_with_read_timeout))
     }
 
+    async fn post(&self, path: &str) -> Result<()> {
+        let url = format!("{}/{}", self.address, path);
+        let timeout = Duration::from_secs(Self::TIMEOUT_SECS);
+        tokio::time::timeout(timeout, self.client.post(&url).send())
+            .await?
+            .err_notes(&url)?
+            .error_for_status()
+            .err_notes(&url)?;
+        Ok(())
+    }
+
     pub async fn get_db_state(&self) -> Result<Option<DbState>> {
         let mut buf = Vec::new();
         self.get("db_state").await?.read_to_end(&mut buf).await?;
@@ -133,4 +144,23 @@ impl BackupServiceClient {
         ))
         .await
     }
+
+    /// Starts a manual compaction of the DB. Fails if one is already running.
+    pub async fn trigger_compaction(&self) -> Result<()> {
+        self.post("maintenance/compact").await
+    }
+
+    /// Makes the pruners prune everything out of their prune windows right away.
+    pub async fn trigger_pruning(&self) -> Result<()> {
+        self.post("maintenance/prune").await
+    }
+
+    pub async fn get_maintenance_status(&self) -> Result<MaintenanceStatus> {
+        let mut buf = Vec::new();
+        self.get("maintenance/status")
+            .await?
+            .read_to_end(&mut buf)
+            .await?;
+        Ok(serde_json::from_slice(&buf)?)
+    }
 }
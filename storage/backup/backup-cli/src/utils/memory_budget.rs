@@ -0,0 +1,64 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::sync::Notify;
+
+/// Bounds the bytes of loaded data held in memory.
+///
+/// The size of a chunk is unknown until it's loaded, so instead of reserving the size before
+/// loading, one waits for room in the budget before starting to load, and then accounts for the
+/// loaded bytes until they are released. Memory usage is bounded by the budget plus what's being
+/// loaded at the same time. Waiting in the order of the chunks makes sure the oldest chunk, which
+/// is the one to be released first, is never blocked by newer ones.
+pub(crate) struct MemoryBudget {
+    limit: usize,
+    used: AtomicUsize,
+    released: Notify,
+}
+
+impl MemoryBudget {
+    pub fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            limit,
+            used: AtomicUsize::new(0),
+            released: Notify::new(),
+        })
+    }
+
+    /// Waits until the bytes held are below the limit.
+    pub async fn wait_for_room(&self) {
+        loop {
+            // Created before checking, so a release in between is not missed.
+            let released = self.released.notified();
+            if self.used.load(Ordering::Acquire) < self.limit {
+                return;
+            }
+            released.await;
+        }
+    }
+
+    /// Accounts for `bytes` held until the returned reservation is dropped.
+    pub fn reserve(self: &Arc<Self>, bytes: usize) -> MemoryReservation {
+        self.used.fetch_add(bytes, Ordering::AcqRel);
+        MemoryReservation {
+            budget: Arc::clone(self),
+            bytes,
+        }
+    }
+}
+
+pub(crate) struct MemoryReservation {
+    budget: Arc<MemoryBudget>,
+    bytes: usize,
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.budget.used.fetch_sub(self.bytes, Ordering::AcqRel);
+        self.budget.released.notify_waiters();
+    }
+}
@@ -4,6 +4,7 @@
 
 pub mod backup_service_client;
 pub(crate) mod error_notes;
+pub(crate) mod memory_budget;
 pub mod read_record_bytes;
 pub mod storage_ext;
 pub(crate) mod stream;
@@ -108,6 +109,7 @@ impl From<RocksdbOpt> for RocksdbConfigs {
                 max_background_jobs: opt.max_background_jobs,
                 ..Default::default()
             },
+            ..Default::default()
         }
     }
 }
@@ -149,6 +151,9 @@ pub struct GlobalRestoreOpt {
 
     #[clap(flatten)]
     pub replay_concurrency_level: ReplayConcurrencyLevelOpt,
+
+    #[clap(flatten)]
+    pub parallel_restore: ParallelRestoreOpt,
 }
 
 pub enum RestoreRunMode {
@@ -270,6 +275,10 @@ pub struct GlobalRestoreOptions {
     pub run_mode: Arc<RestoreRunMode>,
     pub concurrent_downloads: usize,
     pub replay_concurrency_level: usize,
+    /// Number of transaction chunks loaded and prepared for saving at the same time.
+    pub concurrency: usize,
+    /// Maximum bytes of loaded transaction chunks held in memory.
+    pub memory_budget: usize,
 }
 
 impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
@@ -279,6 +288,8 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
         let target_version = opt.target_version.unwrap_or(Version::max_value());
         let concurrent_downloads = opt.concurrent_downloads.get();
         let replay_concurrency_level = opt.replay_concurrency_level.get();
+        let concurrency = opt.parallel_restore.concurrency();
+        let memory_budget = opt.parallel_restore.memory_budget;
         let run_mode = if let Some(db_dir) = &opt.db_dir {
             // for restore, we can always start state store with empty buffered_state since we will restore
             let restore_handler = Arc::new(AptosDB::open_kv_only(
@@ -302,6 +313,8 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
             run_mode: Arc::new(run_mode),
             concurrent_downloads,
             replay_concurrency_level,
+            concurrency,
+            memory_budget,
         })
     }
 }
@@ -380,6 +393,44 @@ impl ReplayConcurrencyLevelOpt {
     }
 }
 
+#[derive(Clone, Copy, Parser)]
+pub struct ParallelRestoreOpt {
+    #[clap(
+        long,
+        help = "Number of transaction chunks downloaded, verified and prepared for saving to the DB \
+        at the same time. Chunks are still committed to the DB in order. [Defaults to number of CPUs]"
+    )]
+    concurrency: Option<usize>,
+
+    // Defaults to 4GB.
+    #[clap(
+        long,
+        default_value_t = 4294967296,
+        help = "Maximum bytes of downloaded transaction chunks held in memory, waiting to be \
+        verified and saved. No more chunks are downloaded until there is room. Chunks being \
+        downloaded are not counted, so up to --concurrency more chunks can be held."
+    )]
+    pub memory_budget: usize,
+}
+
+impl ParallelRestoreOpt {
+    pub fn concurrency(&self) -> usize {
+        let ret = self.concurrency.unwrap_or_else(num_cpus::get);
+        info!(
+            concurrency = ret,
+            memory_budget = self.memory_budget,
+            "Determined concurrency level for restoring transactions."
+        );
+        ret
+    }
+}
+
+impl Default for ParallelRestoreOpt {
+    fn default() -> Self {
+        Self::parse_from(vec!["exe"])
+    }
+}
+
 pub(crate) fn should_cut_chunk(chunk: &[u8], record: &[u8], max_chunk_size: usize) -> bool {
     !chunk.is_empty() && chunk.len() + record.len() + size_of::<u32>() > max_chunk_size
 }
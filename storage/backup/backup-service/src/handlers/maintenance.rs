@@ -0,0 +1,119 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::handlers::utils::{handle_rejection, unwrap_or_500, LATENCY_HISTOGRAM};
+use anyhow::Result;
+use aptos_db::{
+    db_maintenance::{CompactionProgress, MaintenanceStatus},
+    AptosDB,
+};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use std::{sync::Arc, thread};
+use warp::{filters::BoxedFilter, http::StatusCode, reply::Reply, Filter};
+
+static MAINTENANCE: &str = "maintenance";
+static COMPACT: &str = "compact";
+static PRUNE: &str = "prune";
+static STATUS: &str = "status";
+
+/// Routes to trigger manual compactions and immediate pruner passes on the DB, and to follow
+/// their progress.
+pub(crate) fn get_maintenance_routes(db: Arc<AptosDB>) -> BoxedFilter<(impl Reply,)> {
+    let compaction = Arc::new(Mutex::new(None));
+
+    // POST maintenance/compact
+    let (d, c) = (db.clone(), compaction.clone());
+    let compact = warp::post()
+        .and(warp::path::end())
+        .map(move || start_compaction(&d, &c))
+        .map(unwrap_or_500)
+        .recover(handle_rejection);
+
+    // POST maintenance/prune
+    let d = db.clone();
+    let prune = warp::post()
+        .and(warp::path::end())
+        .map(move || -> Result<Box<dyn Reply>> {
+            d.trigger_pruning()?;
+            Ok(Box::new(StatusCode::ACCEPTED))
+        })
+        .map(unwrap_or_500)
+        .recover(handle_rejection);
+
+    // GET maintenance/status
+    let status = warp::get()
+        .and(warp::path::end())
+        .map(move || {
+            let status = MaintenanceStatus {
+                compaction: compaction.lock().clone(),
+                pruners: db.get_pruner_progress(),
+            };
+            Box::new(warp::reply::json(&status)) as Box<dyn Reply>
+        })
+        .recover(handle_rejection);
+
+    warp::path(MAINTENANCE)
+        .and(
+            warp::path(COMPACT)
+                .and(compact)
+                .or(warp::path(PRUNE).and(prune))
+                .or(warp::path(STATUS).and(status)),
+        )
+        .with(warp::log::custom(|info| {
+            let endpoint = info.path().split('/').nth(2).unwrap_or("-");
+            LATENCY_HISTOGRAM
+                .with_label_values(&[endpoint, info.status().as_str()])
+                .observe(info.elapsed().as_secs_f64())
+        }))
+        .boxed()
+}
+
+/// Starts compacting the DB in the background, unless a compaction is already running.
+fn start_compaction(
+    db: &Arc<AptosDB>,
+    compaction: &Arc<Mutex<Option<CompactionProgress>>>,
+) -> Result<Box<dyn Reply>> {
+    {
+        let mut progress = compaction.lock();
+        if progress.as_ref().map_or(false, |p| p.running) {
+            return Ok(Box::new(StatusCode::CONFLICT));
+        }
+        *progress = Some(CompactionProgress {
+            running: true,
+            num_compacted: 0,
+            num_column_families: db.column_families_to_compact().len(),
+            current_column_family: None,
+            error: None,
+        });
+    }
+
+    let (db, compaction) = (Arc::clone(db), Arc::clone(compaction));
+    thread::Builder::new()
+        .name("db_compaction".into())
+        .spawn(move || {
+            info!("Manual DB compaction started.");
+            let result = db.compact(|num_compacted, column_family| {
+                let mut progress = compaction.lock();
+                let progress = progress.as_mut().expect("Set before spawning.");
+                progress.num_compacted = num_compacted;
+                progress.current_column_family = Some(column_family.to_string());
+            });
+
+            let mut progress = compaction.lock();
+            let progress = progress.as_mut().expect("Set before spawning.");
+            progress.running = false;
+            progress.current_column_family = None;
+            match result {
+                Ok(()) => {
+                    progress.num_compacted = progress.num_column_families;
+                    info!("Manual DB compaction finished.");
+                },
+                Err(err) => {
+                    error!(error = ?err, "Manual DB compaction failed.");
+                    progress.error = Some(format!("{:#}", err));
+                },
+            }
+        })?;
+    Ok(Box::new(StatusCode::ACCEPTED))
+}
@@ -2,6 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod maintenance;
 mod utils;
 
 use crate::handlers::utils::{
@@ -11,6 +12,7 @@ use crate::handlers::utils::{
 use aptos_crypto::hash::HashValue;
 use aptos_db::backup::backup_handler::BackupHandler;
 use aptos_types::transaction::Version;
+pub(crate) use maintenance::get_maintenance_routes;
 use warp::{filters::BoxedFilter, reply::Reply, Filter};
 
 static DB_STATE: &str = "db_state";
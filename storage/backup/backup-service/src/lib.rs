@@ -4,15 +4,16 @@
 
 mod handlers;
 
-use crate::handlers::get_routes;
+use crate::handlers::{get_maintenance_routes, get_routes};
 use aptos_db::AptosDB;
 use aptos_logger::prelude::*;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::runtime::Runtime;
+use warp::Filter;
 
 pub fn start_backup_service(address: SocketAddr, db: Arc<AptosDB>) -> Runtime {
     let backup_handler = db.get_backup_handler();
-    let routes = get_routes(backup_handler);
+    let routes = get_maintenance_routes(db).or(get_routes(backup_handler));
 
     let runtime = aptos_runtimes::spawn_named_runtime("backup".into(), None);
 
@@ -35,6 +36,7 @@ mod tests {
     use super::*;
     use aptos_config::utils::get_available_port;
     use aptos_crypto::hash::HashValue;
+    use aptos_db::db_maintenance::MaintenanceStatus;
     use aptos_temppath::TempPath;
     use reqwest::blocking::get;
     use std::net::{IpAddr, Ipv4Addr};
@@ -90,4 +92,50 @@ mod tests {
         let res = get(format!("http://127.0.0.1:{}/state_snapshot/1", port));
         assert!(res.is_err() || res.unwrap().bytes().is_err());
     }
+
+    #[test]
+    fn maintenance() {
+        let tmpdir = TempPath::new();
+        let db = Arc::new(AptosDB::new_for_test(&tmpdir));
+        let port = get_available_port();
+        let _rt = start_backup_service(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port), db);
+        let client = reqwest::blocking::Client::new();
+        let get_status = || -> MaintenanceStatus {
+            get(format!("http://127.0.0.1:{}/maintenance/status", port))
+                .unwrap()
+                .json()
+                .unwrap()
+        };
+
+        // Nothing triggered yet.
+        let status = get_status();
+        assert!(status.compaction.is_none());
+        assert!(status.pruners.is_empty());
+
+        // Triggers are POST only.
+        let resp = get(format!("http://127.0.0.1:{}/maintenance/compact", port)).unwrap();
+        assert_eq!(resp.status(), 400);
+
+        let resp = client
+            .post(format!("http://127.0.0.1:{}/maintenance/prune", port))
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 202);
+
+        let resp = client
+            .post(format!("http://127.0.0.1:{}/maintenance/compact", port))
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 202);
+        let compaction = loop {
+            let compaction = get_status().compaction.unwrap();
+            if !compaction.running {
+                break compaction;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert!(compaction.error.is_none());
+        assert!(compaction.num_column_families > 0);
+        assert_eq!(compaction.num_compacted, compaction.num_column_families);
+    }
 }
@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 use anyhow::Result;
 use aptos_backup_cli::{
-    coordinators::backup::BackupCompactor, metadata::cache::MetadataCacheOpt,
-    storage::DBToolStorageOpt, utils::ConcurrentDownloadsOpt,
+    coordinators::backup::{BackupCompactor, BackupGarbageCollector},
+    metadata::cache::MetadataCacheOpt,
+    storage::DBToolStorageOpt,
+    utils::ConcurrentDownloadsOpt,
 };
 use clap::{Parser, Subcommand};
 
@@ -14,6 +16,11 @@ pub enum Command {
     Compact(CompactionOpt),
     #[clap(about = "Cleanup the backup metadata files")]
     Cleanup(CleanupOpt),
+    #[clap(
+        about = "Delete deduplicated state snapshot chunks not referenced by any backup. \
+        Do not run while a state snapshot backup is in progress."
+    )]
+    Gc(GcOpt),
 }
 
 #[derive(Parser)]
@@ -48,6 +55,19 @@ pub struct CleanupOpt {
     pub storage: DBToolStorageOpt,
 }
 
+#[derive(Parser)]
+pub struct GcOpt {
+    #[clap(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+    #[clap(flatten)]
+    pub storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    pub concurrent_downloads: ConcurrentDownloadsOpt,
+    /// Only report the unreferenced chunks, without deleting them
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
 impl Command {
     pub async fn run(self) -> Result<()> {
         match self {
@@ -63,6 +83,15 @@ impl Command {
                 );
                 compactor.run().await?
             },
+            Command::Gc(opt) => {
+                let gc = BackupGarbageCollector::new(
+                    opt.metadata_cache_opt,
+                    opt.storage.init_storage().await?,
+                    opt.concurrent_downloads.get(),
+                    opt.dry_run,
+                );
+                gc.run().await?
+            },
             Command::Cleanup(_) => {
                 // TODO: add cleanup logic for removing obsolete metadata files
             },
@@ -0,0 +1,126 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Result};
+use aptos_backup_cli::utils::backup_service_client::{BackupServiceClient, BackupServiceClientOpt};
+use aptos_db::db_maintenance::MaintenanceStatus;
+use clap::{Parser, Subcommand};
+use std::time::Duration;
+
+/// Trigger maintenance operations on the DB of a running node, via its backup service
+#[derive(Subcommand)]
+pub enum Command {
+    #[clap(
+        about = "Manually compact all the column families of the DB, reclaiming the space \
+    of pruned data."
+    )]
+    Compact(Opt),
+    #[clap(about = "Make the pruners prune everything out of their prune windows right away.")]
+    Prune(Opt),
+    #[clap(about = "Show the progress of the last manual compaction and of the pruners.")]
+    Status(StatusOpt),
+}
+
+#[derive(Parser)]
+pub struct Opt {
+    #[clap(flatten)]
+    pub client: BackupServiceClientOpt,
+    /// Return right away, instead of reporting the progress until the operation finishes
+    #[clap(long)]
+    pub no_wait: bool,
+    /// Seconds between progress reports
+    #[clap(long, default_value_t = 10)]
+    pub poll_interval_secs: u64,
+}
+
+#[derive(Parser)]
+pub struct StatusOpt {
+    #[clap(flatten)]
+    pub client: BackupServiceClientOpt,
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            Command::Compact(opt) => {
+                let client = BackupServiceClient::new_with_opt(opt.client);
+                client.trigger_compaction().await?;
+                println!("Compaction started.");
+                if !opt.no_wait {
+                    wait(&client, opt.poll_interval_secs, |status| {
+                        status.compaction.as_ref().map_or(true, |c| !c.running)
+                    })
+                    .await?;
+                }
+            },
+            Command::Prune(opt) => {
+                let client = BackupServiceClient::new_with_opt(opt.client);
+                client.trigger_pruning().await?;
+                println!("Pruning triggered.");
+                if !opt.no_wait {
+                    wait(&client, opt.poll_interval_secs, |status| {
+                        status.pruners.iter().all(|p| p.is_done())
+                    })
+                    .await?;
+                }
+            },
+            Command::Status(opt) => {
+                let client = BackupServiceClient::new_with_opt(opt.client);
+                print_status(&client.get_maintenance_status().await?);
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Reports the progress until `is_done` returns true.
+async fn wait(
+    client: &BackupServiceClient,
+    poll_interval_secs: u64,
+    is_done: impl Fn(&MaintenanceStatus) -> bool,
+) -> Result<()> {
+    loop {
+        let status = client.get_maintenance_status().await?;
+        print_status(&status);
+        if let Some(error) = status.compaction.as_ref().and_then(|c| c.error.as_ref()) {
+            bail!("Compaction failed: {}", error);
+        }
+        if is_done(&status) {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(poll_interval_secs)).await;
+    }
+}
+
+fn print_status(status: &MaintenanceStatus) {
+    match &status.compaction {
+        Some(compaction) => println!(
+            "Compaction: {}, {}/{} column families compacted{}{}",
+            if compaction.running {
+                "running"
+            } else {
+                "finished"
+            },
+            compaction.num_compacted,
+            compaction.num_column_families,
+            compaction
+                .current_column_family
+                .as_ref()
+                .map_or(String::new(), |cf| format!(", compacting {}", cf)),
+            compaction
+                .error
+                .as_ref()
+                .map_or(String::new(), |error| format!(", error: {}", error)),
+        ),
+        None => println!("Compaction: not triggered since the node started"),
+    }
+    if status.pruners.is_empty() {
+        println!("Pruners: none enabled");
+    }
+    for pruner in &status.pruners {
+        println!(
+            "{}: pruned before version {}, target version {}",
+            pruner.name, pruner.progress, pruner.target_version
+        );
+    }
+}
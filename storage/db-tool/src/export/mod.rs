@@ -0,0 +1,328 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Exports the transactions, events and write sets of a version range into flat files with a
+//! stable schema, so that the history can be loaded into data warehouses without running an
+//! indexer.
+//!
+//! The output directory contains a `manifest.json` describing the schema and the files, and a
+//! directory per table, with one file per `--versions-per-file` versions.
+
+#[cfg(feature = "parquet")]
+mod parquet;
+pub mod schema;
+mod writer;
+
+use crate::export::{
+    schema::{Column, EventRow, Table, TransactionRow, WriteSetChangeRow, SCHEMA_VERSION},
+    writer::TableWriter,
+};
+use anyhow::{ensure, Result};
+use aptos_backup_cli::utils::RocksdbOpt;
+use aptos_config::config::{
+    BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_logger::info;
+use aptos_storage_interface::{DbReader, MAX_REQUEST_LIMIT};
+use aptos_types::transaction::{Transaction, Version};
+use clap::{Parser, ValueEnum};
+use itertools::multizip;
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// One JSON object per line.
+    Jsonl,
+    /// Snappy compressed Parquet. Only supported if the db tool is built with the `parquet`
+    /// feature.
+    Parquet,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Jsonl => "jsonl",
+            Format::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportManifest {
+    pub schema_version: &'static str,
+    pub format: Format,
+    pub start_version: Version,
+    /// Exclusive.
+    pub end_version: Version,
+    pub tables: Vec<TableManifest>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TableManifest {
+    pub name: &'static str,
+    pub columns: &'static [Column],
+    pub files: Vec<FileManifest>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileManifest {
+    /// Relative to the output directory.
+    pub path: String,
+    pub start_version: Version,
+    /// Exclusive.
+    pub end_version: Version,
+    pub num_rows: u64,
+}
+
+/// Export the transactions, events and write sets of a version range into JSONL or Parquet
+/// files with a stable schema
+#[derive(Parser)]
+pub struct Opt {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+    #[clap(flatten)]
+    rocksdb_opt: RocksdbOpt,
+    #[clap(long, help = "The first version to export.")]
+    start_version: Version,
+    #[clap(
+        long,
+        help = "The version to stop at (exclusive). Defaults to the latest version in the DB."
+    )]
+    end_version: Option<Version>,
+    #[clap(long, value_enum, default_value_t = Format::Jsonl)]
+    format: Format,
+    #[clap(long, default_value_t = 100000, help = "Number of versions per file.")]
+    versions_per_file: u64,
+    #[clap(long, value_parser)]
+    output_dir: PathBuf,
+}
+
+impl Opt {
+    pub fn run(self) -> Result<()> {
+        let db = AptosDB::open(
+            self.db_dir,
+            true,                        /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            self.rocksdb_opt.into(),
+            false,
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+        let end_version = match self.end_version {
+            Some(end_version) => end_version,
+            None => db.get_latest_version()? + 1,
+        };
+
+        let manifest = export_ledger(
+            &db,
+            self.start_version,
+            end_version,
+            self.format,
+            self.versions_per_file,
+            &self.output_dir,
+        )?;
+        info!(
+            output_dir = ?self.output_dir,
+            manifest = ?manifest,
+            "Export finished."
+        );
+        Ok(())
+    }
+}
+
+/// Exports the versions in `[start_version, end_version)` into `output_dir`, and writes the
+/// manifest of the export, which is also returned.
+pub fn export_ledger(
+    db: &dyn DbReader,
+    start_version: Version,
+    end_version: Version,
+    format: Format,
+    versions_per_file: u64,
+    output_dir: &Path,
+) -> Result<ExportManifest> {
+    ensure!(
+        start_version < end_version,
+        "Nothing to export in [{}, {}).",
+        start_version,
+        end_version
+    );
+    ensure!(versions_per_file > 0, "versions_per_file must be positive.");
+    let latest_version = db.get_latest_version()?;
+    ensure!(
+        end_version <= latest_version + 1,
+        "End version {} is beyond the latest version {}.",
+        end_version,
+        latest_version,
+    );
+
+    for table_name in [
+        TransactionRow::NAME,
+        EventRow::NAME,
+        WriteSetChangeRow::NAME,
+    ] {
+        fs::create_dir_all(output_dir.join(table_name))?;
+    }
+
+    let mut exporter = Exporter {
+        db,
+        format,
+        output_dir,
+        // Versions before the first block (e.g. genesis) have no timestamp.
+        timestamp_usecs: db.get_block_timestamp(start_version).ok(),
+        transaction_files: Vec::new(),
+        event_files: Vec::new(),
+        write_set_change_files: Vec::new(),
+    };
+    let mut file_start_version = start_version;
+    while file_start_version < end_version {
+        let file_end_version =
+            end_version.min(file_start_version.saturating_add(versions_per_file));
+        exporter.export_files(file_start_version, file_end_version)?;
+        info!(
+            start_version = file_start_version,
+            end_version = file_end_version,
+            "Exported versions."
+        );
+        file_start_version = file_end_version;
+    }
+
+    let manifest = ExportManifest {
+        schema_version: SCHEMA_VERSION,
+        format,
+        start_version,
+        end_version,
+        tables: vec![
+            TableManifest {
+                name: TransactionRow::NAME,
+                columns: TransactionRow::COLUMNS,
+                files: exporter.transaction_files,
+            },
+            TableManifest {
+                name: EventRow::NAME,
+                columns: EventRow::COLUMNS,
+                files: exporter.event_files,
+            },
+            TableManifest {
+                name: WriteSetChangeRow::NAME,
+                columns: WriteSetChangeRow::COLUMNS,
+                files: exporter.write_set_change_files,
+            },
+        ],
+    };
+    fs::write(
+        output_dir.join(MANIFEST_FILE_NAME),
+        serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    Ok(manifest)
+}
+
+struct Exporter<'a> {
+    db: &'a dyn DbReader,
+    format: Format,
+    output_dir: &'a Path,
+    /// The timestamp of the latest block seen.
+    timestamp_usecs: Option<u64>,
+    transaction_files: Vec<FileManifest>,
+    event_files: Vec<FileManifest>,
+    write_set_change_files: Vec<FileManifest>,
+}
+
+impl<'a> Exporter<'a> {
+    /// Exports `[start_version, end_version)` into one file per table.
+    fn export_files(&mut self, start_version: Version, end_version: Version) -> Result<()> {
+        let file_name = format!(
+            "{}-{}.{}",
+            start_version,
+            end_version - 1,
+            self.format.extension()
+        );
+        let path = |table_name: &str| format!("{}/{}", table_name, file_name);
+        let transaction_path = path(TransactionRow::NAME);
+        let event_path = path(EventRow::NAME);
+        let write_set_change_path = path(WriteSetChangeRow::NAME);
+
+        let mut transaction_writer =
+            TableWriter::create(self.format, &self.output_dir.join(&transaction_path))?;
+        let mut event_writer =
+            TableWriter::create(self.format, &self.output_dir.join(&event_path))?;
+        let mut write_set_change_writer =
+            TableWriter::create(self.format, &self.output_dir.join(&write_set_change_path))?;
+
+        let mut batch_start_version = start_version;
+        while batch_start_version < end_version {
+            let limit = MAX_REQUEST_LIMIT.min(end_version - batch_start_version);
+            let iter = multizip((
+                self.db
+                    .get_transaction_iterator(batch_start_version, limit)?,
+                self.db
+                    .get_transaction_info_iterator(batch_start_version, limit)?,
+                self.db.get_events_iterator(batch_start_version, limit)?,
+                self.db.get_write_set_iterator(batch_start_version, limit)?,
+            ));
+            let mut version = batch_start_version;
+            for (transaction, transaction_info, events, write_set) in iter {
+                let (transaction, transaction_info, events, write_set) =
+                    (transaction?, transaction_info?, events?, write_set?);
+                if let Transaction::BlockMetadata(block_metadata) = &transaction {
+                    self.timestamp_usecs = Some(block_metadata.timestamp_usecs());
+                }
+
+                for (event_index, event) in events.iter().enumerate() {
+                    event_writer.write(EventRow::new(version, event_index, event))?;
+                }
+                let mut num_write_set_changes = 0;
+                for (change_index, (state_key, write_op)) in write_set.iter().enumerate() {
+                    write_set_change_writer.write(WriteSetChangeRow::new(
+                        version,
+                        change_index,
+                        state_key,
+                        write_op,
+                    ))?;
+                    num_write_set_changes += 1;
+                }
+                transaction_writer.write(TransactionRow::new(
+                    version,
+                    &transaction,
+                    &transaction_info,
+                    self.timestamp_usecs,
+                    events.len(),
+                    num_write_set_changes,
+                )?)?;
+                version += 1;
+            }
+            ensure!(
+                version == batch_start_version + limit,
+                "Expected versions [{}, {}), the DB only returned up to {}.",
+                batch_start_version,
+                batch_start_version + limit,
+                version,
+            );
+            batch_start_version = version;
+        }
+
+        let file = |path, num_rows| FileManifest {
+            path,
+            start_version,
+            end_version,
+            num_rows,
+        };
+        self.transaction_files
+            .push(file(transaction_path, transaction_writer.finish()?));
+        self.event_files
+            .push(file(event_path, event_writer.finish()?));
+        self.write_set_change_files.push(file(
+            write_set_change_path,
+            write_set_change_writer.finish()?,
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::export::schema::{Column, ColumnType, Value};
+use anyhow::{ensure, format_err, Result};
+use parquet::{
+    basic::Compression,
+    data_type::{BoolType, ByteArray, ByteArrayType, Int64Type},
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::parser::parse_message_type,
+};
+use std::{fs::File, sync::Arc};
+
+/// Number of rows buffered in memory before a Parquet row group is written.
+const PARQUET_ROW_GROUP_SIZE: usize = 10000;
+
+enum ColumnValues {
+    Int64(Vec<i64>),
+    Bool(Vec<bool>),
+    ByteArray(Vec<ByteArray>),
+}
+
+/// The values of a column in the current row group. The definition levels tell the nulls apart,
+/// and are only kept for the nullable columns.
+struct ColumnBuffer {
+    values: ColumnValues,
+    def_levels: Option<Vec<i16>>,
+}
+
+impl ColumnBuffer {
+    fn new(column: &Column) -> Self {
+        let values = match column.column_type {
+            ColumnType::U64 => ColumnValues::Int64(Vec::new()),
+            ColumnType::Bool => ColumnValues::Bool(Vec::new()),
+            ColumnType::String | ColumnType::Bytes => ColumnValues::ByteArray(Vec::new()),
+        };
+        Self {
+            values,
+            def_levels: column.nullable.then(Vec::new),
+        }
+    }
+
+    fn push(&mut self, value: Value) -> Result<()> {
+        let is_some = match (&mut self.values, value) {
+            // UINT_64 is stored as INT64 in Parquet, with the same bits.
+            (ColumnValues::Int64(values), Value::U64(v)) => {
+                v.map(|v| values.push(v as i64)).is_some()
+            },
+            (ColumnValues::Bool(values), Value::Bool(v)) => v.map(|v| values.push(v)).is_some(),
+            (ColumnValues::ByteArray(values), Value::String(v)) => v
+                .map(|v| values.push(ByteArray::from(v.into_bytes())))
+                .is_some(),
+            (ColumnValues::ByteArray(values), Value::Bytes(v)) => {
+                v.map(|v| values.push(ByteArray::from(v))).is_some()
+            },
+            (_, value) => return Err(format_err!("Unexpected value type: {:?}", value)),
+        };
+        match &mut self.def_levels {
+            Some(def_levels) => def_levels.push(is_some as i16),
+            None => ensure!(is_some, "Null value in a non-nullable column."),
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct ParquetWriter {
+    writer: SerializedFileWriter<File>,
+    columns: &'static [Column],
+    buffers: Vec<ColumnBuffer>,
+    num_buffered_rows: usize,
+}
+
+impl ParquetWriter {
+    pub fn new(file: File, table_name: &str, columns: &'static [Column]) -> Result<Self> {
+        let fields: String = columns
+            .iter()
+            .map(|column| {
+                let repetition = if column.nullable {
+                    "OPTIONAL"
+                } else {
+                    "REQUIRED"
+                };
+                let physical_type = match column.column_type {
+                    ColumnType::U64 => "INT64 {} (UINT_64)",
+                    ColumnType::Bool => "BOOLEAN {}",
+                    ColumnType::String => "BYTE_ARRAY {} (UTF8)",
+                    ColumnType::Bytes => "BYTE_ARRAY {}",
+                }
+                .replace("{}", column.name);
+                format!("{} {};\n", repetition, physical_type)
+            })
+            .collect();
+        let schema = parse_message_type(&format!("message {} {{\n{}}}", table_name, fields))?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        Ok(Self {
+            writer: SerializedFileWriter::new(file, Arc::new(schema), Arc::new(props))?,
+            columns,
+            buffers: columns.iter().map(ColumnBuffer::new).collect(),
+            num_buffered_rows: 0,
+        })
+    }
+
+    pub fn write(&mut self, values: Vec<Value>) -> Result<()> {
+        for (buffer, value) in self.buffers.iter_mut().zip(values) {
+            buffer.push(value)?;
+        }
+        self.num_buffered_rows += 1;
+        if self.num_buffered_rows >= PARQUET_ROW_GROUP_SIZE {
+            self.flush_row_group()?;
+        }
+        Ok(())
+    }
+
+    fn flush_row_group(&mut self) -> Result<()> {
+        if self.num_buffered_rows == 0 {
+            return Ok(());
+        }
+        let buffers = std::mem::replace(
+            &mut self.buffers,
+            self.columns.iter().map(ColumnBuffer::new).collect(),
+        );
+        let mut row_group_writer = self.writer.next_row_group()?;
+        for buffer in buffers {
+            let mut column_writer = row_group_writer
+                .next_column()?
+                .ok_or_else(|| format_err!("Fewer columns in the Parquet schema than buffered."))?;
+            let def_levels = buffer.def_levels.as_deref();
+            match buffer.values {
+                ColumnValues::Int64(values) => {
+                    column_writer
+                        .typed::<Int64Type>()
+                        .write_batch(&values, def_levels, None)?;
+                },
+                ColumnValues::Bool(values) => {
+                    column_writer
+                        .typed::<BoolType>()
+                        .write_batch(&values, def_levels, None)?;
+                },
+                ColumnValues::ByteArray(values) => {
+                    column_writer
+                        .typed::<ByteArrayType>()
+                        .write_batch(&values, def_levels, None)?;
+                },
+            }
+            column_writer.close()?;
+        }
+        row_group_writer.close()?;
+        self.num_buffered_rows = 0;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.flush_row_group()?;
+        self.writer.close()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,340 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The tables of the export and their columns. Columns may be added in later versions of the
+//! schema, but existing ones are never renamed, removed or retyped within a major version.
+
+use anyhow::Result;
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    access_path::Path,
+    contract_event::ContractEvent,
+    state_store::state_key::{StateKey, StateKeyInner},
+    transaction::{Transaction, TransactionInfo, TransactionPayload, Version},
+    write_set::WriteOp,
+};
+use serde::Serialize;
+
+/// Bumped when a column is added (minor) or changed incompatibly (major).
+pub const SCHEMA_VERSION: &str = "1.0";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    /// An unsigned 64 bit integer.
+    U64,
+    Bool,
+    /// A UTF-8 string.
+    String,
+    /// Raw bytes, hex encoded with a `0x` prefix in JSONL.
+    Bytes,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct Column {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub column_type: ColumnType,
+    pub nullable: bool,
+}
+
+const fn column(name: &'static str, column_type: ColumnType, nullable: bool) -> Column {
+    Column {
+        name,
+        column_type,
+        nullable,
+    }
+}
+
+/// A value of a row, in the order of the columns of its table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    U64(Option<u64>),
+    Bool(Option<bool>),
+    String(Option<String>),
+    Bytes(Option<Vec<u8>>),
+}
+
+/// A table of the export.
+pub trait Table {
+    const NAME: &'static str;
+    const COLUMNS: &'static [Column];
+
+    /// The values of a row, one for each column.
+    fn into_values(self) -> Vec<Value>;
+}
+
+/// A row of the `transactions` table, one per version.
+pub struct TransactionRow {
+    pub version: Version,
+    pub hash: HashValue,
+    pub transaction_type: &'static str,
+    pub sender: Option<String>,
+    pub sequence_number: Option<u64>,
+    pub entry_function: Option<String>,
+    /// The timestamp of the block the transaction is in, if known.
+    pub timestamp_usecs: Option<u64>,
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: u64,
+    pub num_events: u64,
+    pub num_write_set_changes: u64,
+    pub state_change_hash: HashValue,
+    pub event_root_hash: HashValue,
+    pub state_checkpoint_hash: Option<HashValue>,
+    /// The BCS bytes of the transaction.
+    pub transaction_bcs: Vec<u8>,
+}
+
+impl TransactionRow {
+    pub fn new(
+        version: Version,
+        transaction: &Transaction,
+        transaction_info: &TransactionInfo,
+        timestamp_usecs: Option<u64>,
+        num_events: usize,
+        num_write_set_changes: usize,
+    ) -> Result<Self> {
+        let (transaction_type, sender, sequence_number, entry_function) = match transaction {
+            Transaction::UserTransaction(txn) => {
+                let entry_function = match txn.payload() {
+                    TransactionPayload::EntryFunction(entry_function) => Some(format!(
+                        "{}::{}::{}",
+                        entry_function.module().address().to_standard_string(),
+                        entry_function.module().name(),
+                        entry_function.function(),
+                    )),
+                    _ => None,
+                };
+                (
+                    "user",
+                    Some(txn.sender().to_standard_string()),
+                    Some(txn.sequence_number()),
+                    entry_function,
+                )
+            },
+            Transaction::GenesisTransaction(_) => ("genesis", None, None, None),
+            Transaction::BlockMetadata(_) => ("block_metadata", None, None, None),
+            Transaction::StateCheckpoint(_) => ("state_checkpoint", None, None, None),
+        };
+        Ok(Self {
+            version,
+            hash: transaction.hash(),
+            transaction_type,
+            sender,
+            sequence_number,
+            entry_function,
+            timestamp_usecs,
+            success: transaction_info.status().is_success(),
+            vm_status: format!("{:?}", transaction_info.status()),
+            gas_used: transaction_info.gas_used(),
+            num_events: num_events as u64,
+            num_write_set_changes: num_write_set_changes as u64,
+            state_change_hash: transaction_info.state_change_hash(),
+            event_root_hash: transaction_info.event_root_hash(),
+            state_checkpoint_hash: transaction_info.state_checkpoint_hash(),
+            transaction_bcs: bcs::to_bytes(transaction)?,
+        })
+    }
+}
+
+impl Table for TransactionRow {
+    const COLUMNS: &'static [Column] = &[
+        column("version", ColumnType::U64, false),
+        column("hash", ColumnType::String, false),
+        column("transaction_type", ColumnType::String, false),
+        column("sender", ColumnType::String, true),
+        column("sequence_number", ColumnType::U64, true),
+        column("entry_function", ColumnType::String, true),
+        column("timestamp_usecs", ColumnType::U64, true),
+        column("success", ColumnType::Bool, false),
+        column("vm_status", ColumnType::String, false),
+        column("gas_used", ColumnType::U64, false),
+        column("num_events", ColumnType::U64, false),
+        column("num_write_set_changes", ColumnType::U64, false),
+        column("state_change_hash", ColumnType::String, false),
+        column("event_root_hash", ColumnType::String, false),
+        column("state_checkpoint_hash", ColumnType::String, true),
+        column("transaction_bcs", ColumnType::Bytes, false),
+    ];
+    const NAME: &'static str = "transactions";
+
+    fn into_values(self) -> Vec<Value> {
+        vec![
+            Value::U64(Some(self.version)),
+            Value::String(Some(self.hash.to_hex_literal())),
+            Value::String(Some(self.transaction_type.to_string())),
+            Value::String(self.sender),
+            Value::U64(self.sequence_number),
+            Value::String(self.entry_function),
+            Value::U64(self.timestamp_usecs),
+            Value::Bool(Some(self.success)),
+            Value::String(Some(self.vm_status)),
+            Value::U64(Some(self.gas_used)),
+            Value::U64(Some(self.num_events)),
+            Value::U64(Some(self.num_write_set_changes)),
+            Value::String(Some(self.state_change_hash.to_hex_literal())),
+            Value::String(Some(self.event_root_hash.to_hex_literal())),
+            Value::String(self.state_checkpoint_hash.map(|hash| hash.to_hex_literal())),
+            Value::Bytes(Some(self.transaction_bcs)),
+        ]
+    }
+}
+
+/// A row of the `events` table, one per event.
+pub struct EventRow {
+    pub version: Version,
+    pub event_index: u64,
+    pub type_tag: String,
+    /// Only set for the events with a handle (V1 events).
+    pub account_address: Option<String>,
+    pub creation_number: Option<u64>,
+    pub sequence_number: Option<u64>,
+    pub data: Vec<u8>,
+}
+
+impl EventRow {
+    pub fn new(version: Version, event_index: usize, event: &ContractEvent) -> Self {
+        let (account_address, creation_number, sequence_number) = match event {
+            ContractEvent::V1(v1) => (
+                Some(v1.key().get_creator_address().to_standard_string()),
+                Some(v1.key().get_creation_number()),
+                Some(v1.sequence_number()),
+            ),
+            ContractEvent::V2(_) => (None, None, None),
+        };
+        Self {
+            version,
+            event_index: event_index as u64,
+            type_tag: event.type_tag().to_canonical_string(),
+            account_address,
+            creation_number,
+            sequence_number,
+            data: event.event_data().to_vec(),
+        }
+    }
+}
+
+impl Table for EventRow {
+    const COLUMNS: &'static [Column] = &[
+        column("version", ColumnType::U64, false),
+        column("event_index", ColumnType::U64, false),
+        column("type_tag", ColumnType::String, false),
+        column("account_address", ColumnType::String, true),
+        column("creation_number", ColumnType::U64, true),
+        column("sequence_number", ColumnType::U64, true),
+        column("data", ColumnType::Bytes, false),
+    ];
+    const NAME: &'static str = "events";
+
+    fn into_values(self) -> Vec<Value> {
+        vec![
+            Value::U64(Some(self.version)),
+            Value::U64(Some(self.event_index)),
+            Value::String(Some(self.type_tag)),
+            Value::String(self.account_address),
+            Value::U64(self.creation_number),
+            Value::U64(self.sequence_number),
+            Value::Bytes(Some(self.data)),
+        ]
+    }
+}
+
+/// A row of the `write_set_changes` table, one per state key written.
+pub struct WriteSetChangeRow {
+    pub version: Version,
+    pub change_index: u64,
+    pub state_key_hash: HashValue,
+    /// `access_path`, `table_item` or `raw`.
+    pub state_key_type: &'static str,
+    /// The account of an access path.
+    pub address: Option<String>,
+    /// The resource type or module of an access path, e.g. `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`.
+    pub path: Option<String>,
+    pub table_handle: Option<String>,
+    /// The key of a table item, or the bytes of a raw state key.
+    pub key: Option<Vec<u8>>,
+    /// `creation`, `modification` or `deletion`.
+    pub op: &'static str,
+    pub value: Option<Vec<u8>>,
+}
+
+impl WriteSetChangeRow {
+    pub fn new(
+        version: Version,
+        change_index: usize,
+        state_key: &StateKey,
+        write_op: &WriteOp,
+    ) -> Self {
+        let (state_key_type, address, path, table_handle, key) = match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => (
+                "access_path",
+                Some(access_path.address.to_standard_string()),
+                Some(
+                    bcs::from_bytes::<Path>(&access_path.path)
+                        .map_or_else(|_| hex::encode(&access_path.path), |path| path.to_string()),
+                ),
+                None,
+                None,
+            ),
+            StateKeyInner::TableItem { handle, key } => (
+                "table_item",
+                None,
+                None,
+                Some(handle.0.to_standard_string()),
+                Some(key.clone()),
+            ),
+            StateKeyInner::Raw(bytes) => ("raw", None, None, None, Some(bytes.clone())),
+        };
+        let op = if write_op.is_deletion() {
+            "deletion"
+        } else if write_op.is_creation() {
+            "creation"
+        } else {
+            "modification"
+        };
+        Self {
+            version,
+            change_index: change_index as u64,
+            state_key_hash: state_key.hash(),
+            state_key_type,
+            address,
+            path,
+            table_handle,
+            key,
+            op,
+            value: write_op.bytes().map(|bytes| bytes.to_vec()),
+        }
+    }
+}
+
+impl Table for WriteSetChangeRow {
+    const COLUMNS: &'static [Column] = &[
+        column("version", ColumnType::U64, false),
+        column("change_index", ColumnType::U64, false),
+        column("state_key_hash", ColumnType::String, false),
+        column("state_key_type", ColumnType::String, false),
+        column("address", ColumnType::String, true),
+        column("path", ColumnType::String, true),
+        column("table_handle", ColumnType::String, true),
+        column("key", ColumnType::Bytes, true),
+        column("op", ColumnType::String, false),
+        column("value", ColumnType::Bytes, true),
+    ];
+    const NAME: &'static str = "write_set_changes";
+
+    fn into_values(self) -> Vec<Value> {
+        vec![
+            Value::U64(Some(self.version)),
+            Value::U64(Some(self.change_index)),
+            Value::String(Some(self.state_key_hash.to_hex_literal())),
+            Value::String(Some(self.state_key_type.to_string())),
+            Value::String(self.address),
+            Value::String(self.path),
+            Value::String(self.table_handle),
+            Value::Bytes(self.key),
+            Value::String(Some(self.op.to_string())),
+            Value::Bytes(self.value),
+        ]
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(feature = "parquet")]
+use crate::export::parquet::ParquetWriter;
+use crate::export::{
+    schema::{Table, Value},
+    Format,
+};
+#[cfg(not(feature = "parquet"))]
+use anyhow::bail;
+use anyhow::{ensure, Result};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    marker::PhantomData,
+    path::Path,
+};
+
+/// Writes the rows of a table into a single file.
+pub(crate) struct TableWriter<T> {
+    inner: Inner,
+    num_rows: u64,
+    _table: PhantomData<T>,
+}
+
+enum Inner {
+    Jsonl(BufWriter<File>),
+    #[cfg(feature = "parquet")]
+    Parquet(ParquetWriter),
+}
+
+impl<T: Table> TableWriter<T> {
+    pub fn create(format: Format, path: &Path) -> Result<Self> {
+        let inner = match format {
+            Format::Jsonl => Inner::Jsonl(BufWriter::new(File::create(path)?)),
+            #[cfg(feature = "parquet")]
+            Format::Parquet => Inner::Parquet(ParquetWriter::new(
+                File::create(path)?,
+                T::NAME,
+                T::COLUMNS,
+            )?),
+            #[cfg(not(feature = "parquet"))]
+            Format::Parquet => bail!(
+                "Parquet is not supported, the db tool needs to be built with the parquet feature."
+            ),
+        };
+        Ok(Self {
+            inner,
+            num_rows: 0,
+            _table: PhantomData,
+        })
+    }
+
+    pub fn write(&mut self, row: T) -> Result<()> {
+        let values = row.into_values();
+        ensure!(
+            values.len() == T::COLUMNS.len(),
+            "Table {} has {} columns, got {} values.",
+            T::NAME,
+            T::COLUMNS.len(),
+            values.len(),
+        );
+        match &mut self.inner {
+            Inner::Jsonl(writer) => {
+                let object: serde_json::Map<_, _> = T::COLUMNS
+                    .iter()
+                    .zip(values)
+                    .map(|(column, value)| (column.name.to_string(), to_json(value)))
+                    .collect();
+                serde_json::to_writer(&mut *writer, &object)?;
+                writer.write_all(b"\n")?;
+            },
+            #[cfg(feature = "parquet")]
+            Inner::Parquet(writer) => writer.write(values)?,
+        }
+        self.num_rows += 1;
+        Ok(())
+    }
+
+    /// Flushes the file and returns the number of rows written.
+    pub fn finish(self) -> Result<u64> {
+        match self.inner {
+            Inner::Jsonl(mut writer) => writer.flush()?,
+            #[cfg(feature = "parquet")]
+            Inner::Parquet(writer) => writer.finish()?,
+        }
+        Ok(self.num_rows)
+    }
+}
+
+fn to_json(value: Value) -> serde_json::Value {
+    match value {
+        Value::U64(v) => v.map_or(serde_json::Value::Null, Into::into),
+        Value::Bool(v) => v.map_or(serde_json::Value::Null, Into::into),
+        Value::String(v) => v.map_or(serde_json::Value::Null, Into::into),
+        Value::Bytes(v) => v.map_or(serde_json::Value::Null, |bytes| {
+            format!("0x{}", hex::encode(bytes)).into()
+        }),
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Result};
+use aptos_backup_cli::utils::RocksdbOpt;
+use aptos_config::config::{
+    BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_logger::info;
+use aptos_storage_interface::DbReader;
+use clap::Parser;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Export the epoch ending ledger infos, i.e. the epoch change proofs, into a single file
+///
+/// Each ledger info is written as its BCS bytes prefixed by their length as a big endian u32, the
+/// same record format as the epoch ending backups.
+#[derive(Parser)]
+pub struct Opt {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+    #[clap(flatten)]
+    rocksdb_opt: RocksdbOpt,
+    #[clap(long, default_value_t = 0, help = "The first epoch to export.")]
+    start_epoch: u64,
+    #[clap(
+        long,
+        help = "The epoch to stop at (exclusive). Defaults to exporting up to the latest ended \
+        epoch."
+    )]
+    end_epoch: Option<u64>,
+    #[clap(long, value_parser)]
+    output_file: PathBuf,
+}
+
+impl Opt {
+    pub fn run(self) -> Result<()> {
+        let db = AptosDB::open(
+            self.db_dir,
+            true,                        /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            self.rocksdb_opt.into(),
+            false,
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+        let end_epoch = match self.end_epoch {
+            Some(end_epoch) => end_epoch,
+            None => db
+                .get_latest_ledger_info()?
+                .ledger_info()
+                .next_block_epoch(),
+        };
+
+        let num_exported =
+            export_epoch_ending_ledger_infos(&db, self.start_epoch, end_epoch, &self.output_file)?;
+        info!(
+            start_epoch = self.start_epoch,
+            end_epoch = end_epoch,
+            num_exported = num_exported,
+            output_file = ?self.output_file,
+            "Export finished."
+        );
+        Ok(())
+    }
+}
+
+/// Writes the epoch ending ledger infos of `[start_epoch, end_epoch)` into `output_file`, and
+/// returns the number written.
+pub fn export_epoch_ending_ledger_infos(
+    db: &dyn DbReader,
+    start_epoch: u64,
+    end_epoch: u64,
+    output_file: &Path,
+) -> Result<u64> {
+    let mut writer = BufWriter::new(File::create(output_file)?);
+    let mut num_exported = 0;
+    for record_bytes in db.get_epoch_ending_ledger_infos_bcs(start_epoch, end_epoch)? {
+        let record_bytes = record_bytes?;
+        writer.write_all(&(record_bytes.len() as u32).to_be_bytes())?;
+        writer.write_all(&record_bytes)?;
+        num_exported += 1;
+    }
+    writer.flush()?;
+    ensure!(
+        num_exported == end_epoch - start_epoch,
+        "Expected {} epoch ending ledger infos, exported {}.",
+        end_epoch - start_epoch,
+        num_exported,
+    );
+    Ok(num_exported)
+}
@@ -0,0 +1,93 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_backup_cli::utils::RocksdbOpt;
+use aptos_config::config::{
+    BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_logger::info;
+use aptos_storage_interface::state_snapshot_export::{
+    export_state_diff, export_state_snapshot, export_state_snapshot_for_bootstrap,
+};
+use aptos_types::transaction::Version;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Export the state at a version, or the state changes between two versions, into a portable
+/// format with chunked proofs
+#[derive(Parser)]
+pub struct Opt {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+    #[clap(flatten)]
+    rocksdb_opt: RocksdbOpt,
+    #[clap(
+        long,
+        help = "The version to export the state at. For a full snapshot, it must have a state \
+        snapshot in the DB."
+    )]
+    version: Version,
+    #[clap(
+        long,
+        help = "If set, only export the state changes after this version, instead of the full \
+        snapshot."
+    )]
+    base_version: Option<Version>,
+    #[clap(
+        long,
+        conflicts_with = "base_version",
+        help = "Include what's needed to bootstrap a node from the snapshot. The version must be \
+        the last version of an epoch."
+    )]
+    for_bootstrap: bool,
+    #[clap(
+        long,
+        default_value_t = 10000,
+        help = "Number of state values (or versions, when exporting changes) per chunk."
+    )]
+    chunk_size: usize,
+    #[clap(long, value_parser)]
+    output_dir: PathBuf,
+}
+
+impl Opt {
+    pub fn run(self) -> Result<()> {
+        let db = AptosDB::open(
+            self.db_dir,
+            true,                        /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            self.rocksdb_opt.into(),
+            false,
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+
+        let manifest = if let Some(base_version) = self.base_version {
+            export_state_diff(
+                &db,
+                base_version,
+                self.version,
+                self.chunk_size,
+                &self.output_dir,
+            )?
+        } else if self.for_bootstrap {
+            export_state_snapshot_for_bootstrap(
+                &db,
+                self.version,
+                self.chunk_size,
+                &self.output_dir,
+            )?
+        } else {
+            export_state_snapshot(&db, self.version, self.chunk_size, &self.output_dir)?
+        };
+        info!(
+            output_dir = ?self.output_dir,
+            ledger_version = manifest.ledger_version,
+            "State exported."
+        );
+        Ok(())
+    }
+}
@@ -5,7 +5,11 @@ extern crate core;
 
 mod backup;
 mod backup_maintenance;
+mod db_maintenance;
 mod debugger;
+pub mod export;
+mod export_epoch_ending;
+mod export_state;
 mod replay_verify;
 pub mod restore;
 #[cfg(test)]
@@ -13,6 +17,7 @@ mod tests;
 mod utils;
 
 use anyhow::Result;
+use aptos_db::db_debugger::check;
 use clap::Parser;
 
 #[derive(Parser)]
@@ -27,6 +32,12 @@ pub enum DBTool {
     Debug(debugger::Command),
     #[clap(subcommand)]
     BackupMaintenance(backup_maintenance::Command),
+    #[clap(subcommand)]
+    DbMaintenance(db_maintenance::Command),
+    Export(export::Opt),
+    ExportEpochEnding(export_epoch_ending::Opt),
+    ExportState(export_state::Opt),
+    Check(check::Cmd),
 }
 
 impl DBTool {
@@ -36,7 +47,12 @@ impl DBTool {
             DBTool::Restore(cmd) => cmd.run().await,
             DBTool::ReplayVerify(cmd) => cmd.run().await,
             DBTool::BackupMaintenance(cmd) => cmd.run().await,
+            DBTool::DbMaintenance(cmd) => cmd.run().await,
             DBTool::Debug(cmd) => cmd.run(),
+            DBTool::Export(cmd) => cmd.run(),
+            DBTool::ExportEpochEnding(cmd) => cmd.run(),
+            DBTool::ExportState(cmd) => cmd.run(),
+            DBTool::Check(cmd) => cmd.run(),
         }
     }
 }
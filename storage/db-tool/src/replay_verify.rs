@@ -13,11 +13,11 @@ use aptos_config::config::{
     NO_OP_STORAGE_PRUNER_CONFIG,
 };
 use aptos_db::{AptosDB, GetRestoreHandler};
-use aptos_executor_types::VerifyExecutionMode;
+use aptos_executor_types::{FeatureOverrides, VerifyExecutionMode};
 use aptos_logger::info;
 use aptos_types::transaction::Version;
 use clap::Parser;
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
 
 /// Read the backup files, replay them and verify the modules
 #[derive(Parser)]
@@ -57,6 +57,22 @@ pub struct Opt {
     txns_to_skip: Vec<Version>,
     #[clap(long, help = "Do not quit right away when a replay issue is detected.")]
     lazy_quit: bool,
+    #[clap(
+        long,
+        value_parser,
+        help = "Write every mismatch found to this file as JSON lines, instead of quitting \
+        on the first one. Implies --lazy-quit."
+    )]
+    audit_report: Option<PathBuf>,
+    #[clap(
+        long,
+        value_parser,
+        help = "JSON file of the feature flags to enable or disable on top of the on-chain ones \
+        when re-executing, keyed by the first version each override applies to, e.g. \
+        {\"1000\": {\"enable\": [\"RESOURCE_GROUPS\"], \"disable\": []}}. Each override applies \
+        until the version of the next one."
+    )]
+    feature_overrides: Option<PathBuf>,
 }
 
 const RETRY_ATTEMPT: u8 = 5;
@@ -73,6 +89,17 @@ impl Opt {
             DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         )?)
         .get_restore_handler();
+        // Created once, so that the audit report is shared by all the attempts.
+        let mut verify_execution_mode =
+            VerifyExecutionMode::verify_except(self.txns_to_skip).set_lazy_quit(self.lazy_quit);
+        if let Some(audit_report) = &self.audit_report {
+            verify_execution_mode = verify_execution_mode.set_audit_report(audit_report)?;
+        }
+        if let Some(feature_overrides) = &self.feature_overrides {
+            let overrides: BTreeMap<Version, FeatureOverrides> =
+                serde_json::from_slice(&std::fs::read(feature_overrides)?)?;
+            verify_execution_mode = verify_execution_mode.set_feature_overrides(overrides);
+        }
         let mut attempt = 0;
         while attempt < RETRY_ATTEMPT {
             let ret = ReplayVerifyCoordinator::new(
@@ -85,8 +112,7 @@ impl Opt {
                 self.start_version.unwrap_or(0),
                 self.end_version.unwrap_or(Version::MAX),
                 self.validate_modules,
-                VerifyExecutionMode::verify_except(self.txns_to_skip.clone())
-                    .set_lazy_quit(self.lazy_quit),
+                verify_execution_mode.clone(),
             )?
             .run()
             .await;
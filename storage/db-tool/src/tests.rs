@@ -47,6 +47,56 @@ fn test_various_cmd_parsing() {
     ]);
 
     run_cmd(&["aptos-db-tool", "backup", "verify", "--local-fs-dir", "."]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "export-state",
+        "--db-dir",
+        ".",
+        "--version",
+        "100",
+        "--base-version",
+        "50",
+        "--output-dir",
+        ".",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "export",
+        "--db-dir",
+        ".",
+        "--start-version",
+        "0",
+        "--end-version",
+        "100",
+        "--format",
+        "jsonl",
+        "--versions-per-file",
+        "10",
+        "--output-dir",
+        ".",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "export-epoch-ending",
+        "--db-dir",
+        ".",
+        "--start-epoch",
+        "1",
+        "--output-file",
+        "epoch_ending.bcs",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "check",
+        "--db-dir",
+        ".",
+        "--start-version",
+        "100",
+        "--num-state-samples",
+        "3",
+        "--report",
+        "report.json",
+    ]);
     run_cmd(&[
         "aptos-db-tool",
         "replay-verify",
@@ -638,4 +688,213 @@ mod dbtool_tests {
 
         size
     }
+
+    #[test]
+    fn test_export_ledger() {
+        use crate::export::{export_ledger, Format, MANIFEST_FILE_NAME};
+        use aptos_storage_interface::DbReader;
+        #[cfg(feature = "parquet")]
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use std::io::{BufRead, BufReader};
+
+        let db_dir = TempPath::new();
+        let db = test_execution_with_storage_impl_inner(false, db_dir.path());
+        let end_version = db.get_latest_version().unwrap() + 1;
+
+        let expected_num_rows = |table: &str| -> u64 {
+            let mut num_rows = 0;
+            for version in 0..end_version {
+                num_rows += match table {
+                    "transactions" => 1,
+                    "events" => db
+                        .get_events_iterator(version, 1)
+                        .unwrap()
+                        .next()
+                        .unwrap()
+                        .unwrap()
+                        .len() as u64,
+                    _ => db
+                        .get_write_set_iterator(version, 1)
+                        .unwrap()
+                        .next()
+                        .unwrap()
+                        .unwrap()
+                        .iter()
+                        .count() as u64,
+                };
+            }
+            num_rows
+        };
+
+        let formats = [
+            Format::Jsonl,
+            #[cfg(feature = "parquet")]
+            Format::Parquet,
+        ];
+        for format in formats {
+            let output_dir = TempPath::new();
+            output_dir.create_as_dir().unwrap();
+            let manifest =
+                export_ledger(db.as_ref(), 0, end_version, format, 7, output_dir.path()).unwrap();
+            assert!(output_dir.path().join(MANIFEST_FILE_NAME).exists());
+
+            for table in manifest.tables {
+                assert_eq!(table.files.len() as u64, (end_version + 6) / 7);
+                assert_eq!(table.files.first().unwrap().start_version, 0);
+                assert_eq!(table.files.last().unwrap().end_version, end_version);
+
+                let mut num_rows = 0;
+                for file in &table.files {
+                    let path = output_dir.path().join(&file.path);
+                    let num_rows_in_file = match format {
+                        Format::Jsonl => {
+                            let mut n = 0;
+                            for line in BufReader::new(fs::File::open(&path).unwrap()).lines() {
+                                let row: serde_json::Map<String, serde_json::Value> =
+                                    serde_json::from_str(&line.unwrap()).unwrap();
+                                assert_eq!(row.len(), table.columns.len());
+                                let version = row["version"].as_u64().unwrap();
+                                assert!(
+                                    version >= file.start_version && version < file.end_version
+                                );
+                                n += 1;
+                            }
+                            n
+                        },
+                        #[cfg(feature = "parquet")]
+                        Format::Parquet => {
+                            let reader =
+                                SerializedFileReader::new(fs::File::open(&path).unwrap()).unwrap();
+                            let file_metadata = reader.metadata().file_metadata();
+                            assert_eq!(
+                                file_metadata.schema_descr().num_columns(),
+                                table.columns.len()
+                            );
+                            file_metadata.num_rows() as u64
+                        },
+                        #[cfg(not(feature = "parquet"))]
+                        Format::Parquet => unreachable!(),
+                    };
+                    assert_eq!(num_rows_in_file, file.num_rows);
+                    num_rows += num_rows_in_file;
+                }
+                assert_eq!(num_rows, expected_num_rows(table.name));
+            }
+        }
+    }
+
+    #[test]
+    fn test_export_epoch_ending_ledger_infos() {
+        use crate::export_epoch_ending::export_epoch_ending_ledger_infos;
+        use aptos_storage_interface::DbReader;
+        use aptos_types::ledger_info::LedgerInfoWithSignatures;
+
+        let db_dir = TempPath::new();
+        let db = test_execution_with_storage_impl_inner(false, db_dir.path());
+        let end_epoch = db
+            .get_latest_ledger_info()
+            .unwrap()
+            .ledger_info()
+            .next_block_epoch();
+        let expected = db
+            .get_epoch_ending_ledger_infos(0, end_epoch)
+            .unwrap()
+            .ledger_info_with_sigs;
+
+        let output_file = TempPath::new();
+        assert_eq!(
+            export_epoch_ending_ledger_infos(db.as_ref(), 0, end_epoch, output_file.path())
+                .unwrap(),
+            end_epoch
+        );
+        let bytes = fs::read(output_file.path()).unwrap();
+        let mut remaining = bytes.as_slice();
+        let mut exported = Vec::new();
+        while !remaining.is_empty() {
+            let (size, rest) = remaining.split_at(4);
+            let size = u32::from_be_bytes(size.try_into().unwrap()) as usize;
+            let (record, rest) = rest.split_at(size);
+            exported.push(bcs::from_bytes::<LedgerInfoWithSignatures>(record).unwrap());
+            remaining = rest;
+        }
+        assert_eq!(exported, expected);
+
+        // Epochs that haven't ended can't be exported.
+        assert!(export_epoch_ending_ledger_infos(
+            db.as_ref(),
+            0,
+            end_epoch + 1,
+            output_file.path()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_export_and_import_state_snapshot() {
+        use aptos_crypto::HashValue;
+        use aptos_db::AptosDB;
+        use aptos_storage_interface::{
+            state_snapshot_export::export_state_snapshot_for_bootstrap,
+            state_snapshot_import::import_state_snapshot, DbReader, DbReaderWriter,
+        };
+        use aptos_types::{block_info::BlockInfo, ledger_info::LedgerInfo, waypoint::Waypoint};
+
+        let db_dir = TempPath::new();
+        let db = test_execution_with_storage_impl_inner(false, db_dir.path());
+        let end_epoch = db
+            .get_latest_ledger_info()
+            .unwrap()
+            .ledger_info()
+            .next_block_epoch();
+        let epoch_ending_ledger_infos = db
+            .get_epoch_ending_ledger_infos(0, end_epoch)
+            .unwrap()
+            .ledger_info_with_sigs;
+        let genesis_waypoint =
+            Waypoint::new_epoch_boundary(epoch_ending_ledger_infos[0].ledger_info()).unwrap();
+        let ledger_info = epoch_ending_ledger_infos.last().unwrap();
+        let version = ledger_info.ledger_info().version();
+
+        let snapshot_dir = TempPath::new();
+        export_state_snapshot_for_bootstrap(db.as_ref(), version, 7, snapshot_dir.path()).unwrap();
+        let read_file = |file_name: &str| -> anyhow::Result<Vec<u8>> {
+            Ok(fs::read(snapshot_dir.path().join(file_name))?)
+        };
+
+        let new_db_dir = TempPath::new();
+        let new_db = DbReaderWriter::new(AptosDB::new_for_test(new_db_dir.path()));
+        assert_eq!(
+            import_state_snapshot(&new_db, genesis_waypoint, read_file).unwrap(),
+            version
+        );
+        assert_eq!(
+            &new_db.reader.get_latest_ledger_info().unwrap(),
+            ledger_info
+        );
+        let num_values = db.get_state_leaf_count(version).unwrap();
+        assert_eq!(
+            new_db.reader.get_state_leaf_count(version).unwrap(),
+            num_values
+        );
+        assert_eq!(
+            new_db
+                .reader
+                .get_state_value_chunk_with_proof(version, 0, num_values)
+                .unwrap()
+                .raw_values,
+            db.get_state_value_chunk_with_proof(version, 0, num_values)
+                .unwrap()
+                .raw_values
+        );
+
+        // Only an empty DB can be bootstrapped.
+        assert!(import_state_snapshot(&new_db, genesis_waypoint, read_file).is_err());
+
+        // The snapshot must be verified against the waypoint.
+        let other_db_dir = TempPath::new();
+        let other_db = DbReaderWriter::new(AptosDB::new_for_test(other_db_dir.path()));
+        let wrong_waypoint =
+            Waypoint::new_any(&LedgerInfo::new(BlockInfo::empty(), HashValue::zero()));
+        assert!(import_state_snapshot(&other_db, wrong_waypoint, read_file).is_err());
+    }
 }
@@ -9,42 +9,53 @@ use crate::{
     db::INDEX_DB_NAME,
     metadata::{MetadataKey, MetadataValue},
     schema::{
-        column_families, indexer_metadata::IndexerMetadataSchema, table_info::TableInfoSchema,
+        account_transactions::AccountTransactionsSchema, column_families,
+        event_by_field::EventByFieldSchema, indexer_metadata::IndexerMetadataSchema,
+        table_info::TableInfoSchema,
     },
 };
 use anyhow::{bail, ensure, Result};
-use aptos_config::config::RocksdbConfig;
-use aptos_logger::warn;
+use aptos_config::config::{EventFieldIndexConfig, RocksdbConfig};
+use aptos_crypto::HashValue;
+use aptos_logger::{info, warn};
 use aptos_rocksdb_options::gen_rocksdb_options;
-use aptos_schemadb::{SchemaBatch, DB};
+use aptos_schemadb::{ReadOptions, SchemaBatch, DB};
 use aptos_storage_interface::{state_view::DbStateView, DbReader};
 use aptos_types::{
     access_path::Path,
     account_address::AccountAddress,
+    contract_event::ContractEvent,
     state_store::{
         state_key::{StateKey, StateKeyInner},
         table::{TableHandle, TableInfo},
     },
-    transaction::{AtomicVersion, Version},
+    transaction::{AtomicVersion, Transaction, Version},
     write_set::{WriteOp, WriteSet},
 };
 use aptos_vm::data_cache::AsMoveResolver;
 use bytes::Bytes;
 use move_core_types::{
     ident_str,
+    identifier::{IdentStr, Identifier},
     language_storage::{StructTag, TypeTag},
     resolver::MoveResolver,
 };
 use move_resource_viewer::{AnnotatedMoveValue, MoveValueAnnotator};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     convert::TryInto,
+    str::FromStr,
     sync::{atomic::Ordering, Arc},
 };
 #[derive(Debug)]
 pub struct Indexer {
     db: DB,
     next_version: AtomicVersion,
+    /// The indexed fields of each indexed event type.
+    event_field_indices: HashMap<TypeTag, Vec<Identifier>>,
+    event_index_next_version: AtomicVersion,
+    account_transaction_index_enabled: bool,
+    account_transaction_index_next_version: AtomicVersion,
 }
 
 impl Indexer {
@@ -68,14 +79,75 @@ impl Indexer {
         Ok(Self {
             db,
             next_version: AtomicVersion::new(next_version),
+            event_field_indices: HashMap::new(),
+            event_index_next_version: AtomicVersion::new(0),
+            account_transaction_index_enabled: false,
+            account_transaction_index_next_version: AtomicVersion::new(0),
         })
     }
 
+    /// Starts indexing the events of the configured types by the configured fields. Returns the
+    /// version from which the events need to be passed to [`Self::index_events_with_annotator`]
+    /// to catch up. Events committed while no event field index was enabled are not indexed.
+    pub fn enable_event_field_index(
+        &mut self,
+        configs: &[EventFieldIndexConfig],
+        ledger_next_version: Version,
+    ) -> Result<Version> {
+        for config in configs {
+            let event_type = TypeTag::Struct(Box::new(StructTag::from_str(&config.event_type)?));
+            let field = Identifier::new(config.field.as_str())?;
+            self.event_field_indices
+                .entry(event_type)
+                .or_default()
+                .push(field);
+        }
+
+        let next_version = match self
+            .db
+            .get::<IndexerMetadataSchema>(&MetadataKey::EventIndexLatestVersion)?
+        {
+            Some(latest_version) => latest_version.expect_version() + 1,
+            None => ledger_next_version,
+        };
+        info!(
+            num_indices = configs.len(),
+            next_version = next_version,
+            "Enabled the event field index.",
+        );
+        self.event_index_next_version
+            .store(next_version, Ordering::Relaxed);
+        Ok(next_version)
+    }
+
+    /// Starts indexing the versions of the transactions of each account. Returns the version from
+    /// which the transactions need to be passed to [`Self::index_account_transactions`] to catch
+    /// up, which is `first_version` if the index is new.
+    pub fn enable_account_transaction_index(&mut self, first_version: Version) -> Result<Version> {
+        self.account_transaction_index_enabled = true;
+        let next_version = match self
+            .db
+            .get::<IndexerMetadataSchema>(&MetadataKey::AccountTransactionIndexLatestVersion)?
+        {
+            Some(latest_version) => latest_version.expect_version() + 1,
+            None => first_version,
+        };
+        info!(
+            next_version = next_version,
+            "Enabled the account transaction index.",
+        );
+        self.account_transaction_index_next_version
+            .store(next_version, Ordering::Relaxed);
+        Ok(next_version)
+    }
+
     pub fn index(
         &self,
         db_reader: Arc<dyn DbReader>,
         first_version: Version,
+        transactions: &[&Transaction],
         write_sets: &[&WriteSet],
+        events: &[&[ContractEvent]],
     ) -> Result<()> {
         let last_version = first_version + write_sets.len() as Version;
         let state_view = DbStateView {
@@ -84,7 +156,9 @@ impl Indexer {
         };
         let resolver = state_view.as_move_resolver();
         let annotator = MoveValueAnnotator::new(&resolver);
-        self.index_with_annotator(&annotator, first_version, write_sets)
+        self.index_with_annotator(&annotator, first_version, write_sets)?;
+        self.index_events_with_annotator(&annotator, first_version, events)?;
+        self.index_account_transactions(first_version, transactions, write_sets)
     }
 
     pub fn index_with_annotator<R: MoveResolver>(
@@ -142,13 +216,241 @@ impl Indexer {
         Ok(())
     }
 
+    pub fn index_events_with_annotator<R: MoveResolver>(
+        &self,
+        annotator: &MoveValueAnnotator<R>,
+        first_version: Version,
+        events: &[&[ContractEvent]],
+    ) -> Result<()> {
+        if self.event_field_indices.is_empty() {
+            return Ok(());
+        }
+        let next_version = self.event_index_next_version();
+        ensure!(
+            first_version <= next_version,
+            "Event field index expects to see continuous transaction versions. Expecting: {}, got: {}",
+            next_version,
+            first_version,
+        );
+        let end_version = first_version + events.len() as Version;
+        if end_version <= next_version {
+            return Ok(());
+        }
+
+        let mut batch = SchemaBatch::new();
+        for (version, events) in (first_version..end_version)
+            .zip(events)
+            .skip((next_version - first_version) as usize)
+        {
+            for (idx, event) in events.iter().enumerate() {
+                let fields = match self.event_field_indices.get(event.type_tag()) {
+                    Some(fields) => fields,
+                    None => continue,
+                };
+                let struct_value =
+                    match annotator.view_value(event.type_tag(), event.event_data())? {
+                        AnnotatedMoveValue::Struct(struct_value) => struct_value,
+                        value => bail!("Event is not a struct. {:?}", value),
+                    };
+                for (name, value) in &struct_value.value {
+                    if !fields.contains(name) {
+                        continue;
+                    }
+                    if let Some(value) = Self::encode_field_value(value)? {
+                        let field_key = Self::event_field_key(event.type_tag(), name, &value)?;
+                        batch.put::<EventByFieldSchema>(&(field_key, version, idx as u64), &())?;
+                    }
+                }
+            }
+        }
+        batch.put::<IndexerMetadataSchema>(
+            &MetadataKey::EventIndexLatestVersion,
+            &MetadataValue::Version(end_version - 1),
+        )?;
+        self.db.write_schemas(batch)?;
+        self.event_index_next_version
+            .store(end_version, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    pub fn index_account_transactions(
+        &self,
+        first_version: Version,
+        transactions: &[&Transaction],
+        write_sets: &[&WriteSet],
+    ) -> Result<()> {
+        if !self.account_transaction_index_enabled {
+            return Ok(());
+        }
+        ensure!(
+            transactions.len() == write_sets.len(),
+            "Got {} transactions and {} write sets.",
+            transactions.len(),
+            write_sets.len(),
+        );
+        let next_version = self.account_transaction_index_next_version();
+        ensure!(
+            first_version <= next_version,
+            "Account transaction index expects to see continuous transaction versions. Expecting: {}, got: {}",
+            next_version,
+            first_version,
+        );
+        let end_version = first_version + transactions.len() as Version;
+        if end_version <= next_version {
+            return Ok(());
+        }
+
+        let mut batch = SchemaBatch::new();
+        for ((version, transaction), write_set) in (first_version..end_version)
+            .zip(transactions)
+            .zip(write_sets)
+            .skip((next_version - first_version) as usize)
+        {
+            for address in Self::accounts_of_transaction(transaction, write_set) {
+                batch.put::<AccountTransactionsSchema>(&(address, version), &())?;
+            }
+        }
+        batch.put::<IndexerMetadataSchema>(
+            &MetadataKey::AccountTransactionIndexLatestVersion,
+            &MetadataValue::Version(end_version - 1),
+        )?;
+        self.db.write_schemas(batch)?;
+        self.account_transaction_index_next_version
+            .store(end_version, Ordering::Relaxed);
+
+        Ok(())
+    }
+
     pub fn next_version(&self) -> Version {
         self.next_version.load(Ordering::Relaxed)
     }
 
+    pub fn event_index_next_version(&self) -> Version {
+        self.event_index_next_version.load(Ordering::Relaxed)
+    }
+
+    pub fn account_transaction_index_enabled(&self) -> bool {
+        self.account_transaction_index_enabled
+    }
+
+    pub fn account_transaction_index_next_version(&self) -> Version {
+        self.account_transaction_index_next_version
+            .load(Ordering::Relaxed)
+    }
+
     pub fn get_table_info(&self, handle: TableHandle) -> Result<Option<TableInfo>> {
         self.db.get::<TableInfoSchema>(&handle)
     }
+
+    /// Returns the (version, index) of up to `limit` events of type `event_type` whose `field`
+    /// has the value of which `value` is the BCS bytes, starting from `start_version`.
+    pub fn get_events_by_field(
+        &self,
+        event_type: &TypeTag,
+        field: &IdentStr,
+        value: &[u8],
+        start_version: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, u64)>> {
+        ensure!(
+            self.event_field_indices
+                .get(event_type)
+                .map_or(false, |fields| fields
+                    .iter()
+                    .any(|f| f.as_ident_str() == field)),
+            "Field {} of event type {} is not indexed.",
+            field,
+            event_type,
+        );
+        let field_key = Self::event_field_key(event_type, field, value)?;
+
+        let mut iter = self.db.iter::<EventByFieldSchema>(ReadOptions::default())?;
+        iter.seek(&(field_key, start_version, 0))?;
+        let mut result = Vec::new();
+        for res in iter {
+            let ((key, version, index), ()) = res?;
+            if key != field_key || result.len() as u64 >= limit {
+                break;
+            }
+            result.push((version, index));
+        }
+        Ok(result)
+    }
+
+    /// Returns the versions of up to `limit` transactions of `address`, starting from
+    /// `start_version`.
+    pub fn get_account_transaction_versions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+    ) -> Result<Vec<Version>> {
+        ensure!(
+            self.account_transaction_index_enabled,
+            "Account transaction index not enabled."
+        );
+
+        let mut iter = self
+            .db
+            .iter::<AccountTransactionsSchema>(ReadOptions::default())?;
+        iter.seek(&(address, start_version))?;
+        let mut result = Vec::new();
+        for res in iter {
+            let ((account, version), ()) = res?;
+            if account != address || result.len() as u64 >= limit {
+                break;
+            }
+            result.push(version);
+        }
+        Ok(result)
+    }
+
+    /// The accounts that signed the transaction (including the fee payer), and the accounts whose
+    /// resources or modules it wrote.
+    fn accounts_of_transaction(
+        transaction: &Transaction,
+        write_set: &WriteSet,
+    ) -> BTreeSet<AccountAddress> {
+        let mut accounts = BTreeSet::new();
+        if let Transaction::UserTransaction(txn) = transaction {
+            accounts.insert(txn.sender());
+            let authenticator = txn.authenticator_ref();
+            accounts.extend(authenticator.secondary_signer_addreses());
+            accounts.extend(authenticator.fee_payer_address());
+        }
+        for (state_key, _write_op) in write_set.iter() {
+            if let StateKeyInner::AccessPath(access_path) = state_key.inner() {
+                accounts.insert(access_path.address);
+            }
+        }
+        accounts
+    }
+
+    fn event_field_key(event_type: &TypeTag, field: &IdentStr, value: &[u8]) -> Result<HashValue> {
+        Ok(HashValue::sha3_256_of(&bcs::to_bytes(&(
+            event_type,
+            field.as_str(),
+            value,
+        ))?))
+    }
+
+    /// The BCS bytes of the value of an indexed field, or `None` if the field is not of a
+    /// primitive type.
+    fn encode_field_value(value: &AnnotatedMoveValue) -> Result<Option<Vec<u8>>> {
+        Ok(Some(match value {
+            AnnotatedMoveValue::U8(v) => bcs::to_bytes(v)?,
+            AnnotatedMoveValue::U16(v) => bcs::to_bytes(v)?,
+            AnnotatedMoveValue::U32(v) => bcs::to_bytes(v)?,
+            AnnotatedMoveValue::U64(v) => bcs::to_bytes(v)?,
+            AnnotatedMoveValue::U128(v) => bcs::to_bytes(v)?,
+            AnnotatedMoveValue::U256(v) => bcs::to_bytes(v)?,
+            AnnotatedMoveValue::Bool(v) => bcs::to_bytes(v)?,
+            AnnotatedMoveValue::Address(v) => bcs::to_bytes(v)?,
+            AnnotatedMoveValue::Bytes(v) => bcs::to_bytes(v)?,
+            AnnotatedMoveValue::Vector(..) | AnnotatedMoveValue::Struct(_) => return Ok(None),
+        }))
+    }
 }
 
 struct TableInfoParser<'a, R> {
@@ -0,0 +1,71 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema indexing the versions of the transactions that
+//! were signed by or touched the state of an account.
+//!
+//! ```text
+//! |<--------key-------->|
+//! | address | txn_version |
+//! ```
+//!
+//! `txn_version` is serialized in big endian so that records in RocksDB will be in order of its
+//! numeric value.
+
+use crate::schema::ACCOUNT_TRANSACTIONS_CF_NAME;
+use anyhow::{ensure, Result};
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::{account_address::AccountAddress, transaction::Version};
+use byteorder::{BigEndian, ReadBytesExt};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(
+    AccountTransactionsSchema,
+    Key,
+    (),
+    ACCOUNT_TRANSACTIONS_CF_NAME
+);
+
+type Key = (AccountAddress, Version);
+
+impl KeyCodec<AccountTransactionsSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (address, version) = *self;
+
+        let mut encoded = address.to_vec();
+        encoded.extend_from_slice(&version.to_be_bytes());
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        const VERSION_SIZE: usize = size_of::<Version>();
+        ensure!(
+            data.len() == AccountAddress::LENGTH + VERSION_SIZE,
+            "Unexpected key length: {}",
+            data.len(),
+        );
+
+        let address = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let version = (&data[AccountAddress::LENGTH..]).read_u64::<BigEndian>()?;
+
+        Ok((address, version))
+    }
+}
+
+impl ValueCodec<AccountTransactionsSchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure!(data.is_empty(), "Unexpected value length: {}", data.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
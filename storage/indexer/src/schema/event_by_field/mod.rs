@@ -0,0 +1,72 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema indexing events by the value of a field, for the
+//! event types and fields configured.
+//!
+//! ```text
+//! |<----------------key---------------->|
+//! | field_key | txn_version | event_idx |
+//! ```
+//!
+//! `field_key` is the hash of the event type, the field name and the BCS bytes of the field
+//! value. `txn_version` and `event_idx` are serialized in big endian so that records in RocksDB
+//! will be in order of their numeric values.
+
+use crate::schema::EVENT_BY_FIELD_CF_NAME;
+use anyhow::{ensure, Result};
+use aptos_crypto::HashValue;
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::transaction::Version;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::mem::size_of;
+
+define_schema!(EventByFieldSchema, Key, (), EVENT_BY_FIELD_CF_NAME);
+
+type Index = u64;
+type Key = (HashValue, Version, Index);
+
+impl KeyCodec<EventByFieldSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (field_key, version, index) = *self;
+
+        let mut encoded = field_key.to_vec();
+        encoded.extend_from_slice(&version.to_be_bytes());
+        encoded.extend_from_slice(&index.to_be_bytes());
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        const VERSION_SIZE: usize = size_of::<Version>();
+        const INDEX_SIZE: usize = size_of::<Index>();
+        ensure!(
+            data.len() == HashValue::LENGTH + VERSION_SIZE + INDEX_SIZE,
+            "Unexpected key length: {}",
+            data.len(),
+        );
+
+        let field_key = HashValue::from_slice(&data[..HashValue::LENGTH])?;
+        let version = (&data[HashValue::LENGTH..]).read_u64::<BigEndian>()?;
+        let index = (&data[HashValue::LENGTH + VERSION_SIZE..]).read_u64::<BigEndian>()?;
+
+        Ok((field_key, version, index))
+    }
+}
+
+impl ValueCodec<EventByFieldSchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure!(data.is_empty(), "Unexpected value length: {}", data.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,19 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        field_key in any::<HashValue>(),
+        version in any::<Version>(),
+        index in any::<Index>(),
+    ) {
+        assert_encode_decode::<EventByFieldSchema>(&(field_key, version, index), &());
+    }
+}
+
+test_no_panic_decoding!(EventByFieldSchema);
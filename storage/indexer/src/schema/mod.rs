@@ -6,12 +6,16 @@
 //!
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
+pub(crate) mod account_transactions;
+pub(crate) mod event_by_field;
 pub(crate) mod indexer_metadata;
 pub(crate) mod table_info;
 
 use aptos_schemadb::ColumnFamilyName;
 
+pub const ACCOUNT_TRANSACTIONS_CF_NAME: ColumnFamilyName = "account_transactions";
 pub const DEFAULT_COLUMN_FAMILY_NAME: ColumnFamilyName = "default";
+pub const EVENT_BY_FIELD_CF_NAME: ColumnFamilyName = "event_by_field";
 pub const INDEXER_METADATA_CF_NAME: ColumnFamilyName = "indexer_metadata";
 pub const TABLE_INFO_CF_NAME: ColumnFamilyName = "table_info";
 
@@ -20,5 +24,7 @@ pub fn column_families() -> Vec<ColumnFamilyName> {
         /* empty cf */ DEFAULT_COLUMN_FAMILY_NAME,
         INDEXER_METADATA_CF_NAME,
         TABLE_INFO_CF_NAME,
+        EVENT_BY_FIELD_CF_NAME,
+        ACCOUNT_TRANSACTIONS_CF_NAME,
     ]
 }
@@ -651,6 +651,42 @@ fn many_keys_get_proof_and_verify_tree_root(seed: &[u8], num_keys: usize) {
     }
 }
 
+#[test]
+fn test_get_with_proof_ext_many() {
+    let mut rng: StdRng = StdRng::from_seed([0u8; 32]);
+    let db = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::new(&db);
+
+    let values: Vec<_> = (0..100).map(|_| gen_value()).collect();
+    let kvs: Vec<_> = values
+        .iter()
+        .map(|value| (HashValue::random_with_rng(&mut rng), Some(value)))
+        .collect();
+    let (_root, batch) = tree
+        .put_value_set_test(kvs.clone(), 0 /* version */)
+        .unwrap();
+    db.write_tree_update_batch(batch).unwrap();
+
+    // Existent keys, nonexistent keys and duplicates, in no particular order.
+    let mut keys: Vec<_> = kvs.iter().map(|(key, _)| *key).collect();
+    keys.extend((0..10).map(|_| HashValue::random_with_rng(&mut rng)));
+    keys.push(keys[0]);
+    keys.reverse();
+
+    let results = tree.get_with_proof_ext_many(&keys, 0).unwrap();
+    assert_eq!(results.len(), keys.len());
+    for (key, result) in keys.iter().zip(results) {
+        assert_eq!(result, tree.get_with_proof_ext(*key, 0).unwrap());
+    }
+    let err = tree
+        .get_with_proof_ext_many(&keys, 1)
+        .err()
+        .unwrap()
+        .downcast::<MissingRootError>()
+        .unwrap();
+    assert_eq!(err.version, 1);
+}
+
 fn many_keys_deletion(seed: &[u8], num_keys: usize) {
     assert!(seed.len() < 32);
     let mut actual_seed = [0u8; 32];
@@ -87,6 +87,7 @@ use crate::metrics::{APTOS_JELLYFISH_LEAF_COUNT, APTOS_JELLYFISH_LEAF_DELETION_C
 use anyhow::{bail, ensure, format_err, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_experimental_runtimes::thread_manager::THREAD_MANAGER;
+use aptos_infallible::Mutex;
 use aptos_types::{
     nibble::{nibble_path::NibblePath, Nibble, ROOT_NIBBLE_HEIGHT},
     proof::{SparseMerkleProof, SparseMerkleProofExt, SparseMerkleRangeProof},
@@ -299,6 +300,41 @@ impl<'a, K> std::iter::Iterator for NibbleRangeIterator<'a, K> {
     }
 }
 
+/// A [`TreeReader`] remembering the nodes it has read, so that the nodes visited by multiple
+/// queries are read from the underlying reader only once.
+struct CachingTreeReader<'a, R, K> {
+    reader: &'a R,
+    cache: Mutex<HashMap<NodeKey, Option<Node<K>>>>,
+}
+
+impl<'a, R, K> CachingTreeReader<'a, R, K> {
+    fn new(reader: &'a R) -> Self {
+        Self {
+            reader,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<'a, R, K> TreeReader<K> for CachingTreeReader<'a, R, K>
+where
+    R: TreeReader<K>,
+    K: Key,
+{
+    fn get_node_option(&self, node_key: &NodeKey, tag: &str) -> Result<Option<Node<K>>> {
+        if let Some(node) = self.cache.lock().get(node_key) {
+            return Ok(node.clone());
+        }
+        let node = self.reader.get_node_option(node_key, tag)?;
+        self.cache.lock().insert(node_key.clone(), node.clone());
+        Ok(node)
+    }
+
+    fn get_rightmost_leaf(&self, version: Version) -> Result<Option<(NodeKey, LeafNode<K>)>> {
+        self.reader.get_rightmost_leaf(version)
+    }
+}
+
 /// The Jellyfish Merkle tree data structure. See [`crate`] for description.
 pub struct JellyfishMerkleTree<'a, R, K> {
     reader: &'a R,
@@ -786,6 +822,21 @@ where
         bail!("Jellyfish Merkle tree has cyclic graph inside.");
     }
 
+    /// Returns the values (if applicable) and the corresponding merkle proofs of multiple keys, in
+    /// the same order as `keys`. The nodes shared by the paths to the keys, e.g. the root and the
+    /// top levels, are read only once.
+    pub fn get_with_proof_ext_many(
+        &self,
+        keys: &[HashValue],
+        version: Version,
+    ) -> Result<Vec<(Option<(HashValue, (K, Version))>, SparseMerkleProofExt)>> {
+        let reader = CachingTreeReader::new(self.reader);
+        let tree = JellyfishMerkleTree::new(&reader);
+        keys.iter()
+            .map(|key| tree.get_with_proof_ext(*key, version))
+            .collect()
+    }
+
     /// Gets the proof that shows a list of keys up to `rightmost_key_to_prove` exist at `version`.
     pub fn get_range_proof(
         &self,
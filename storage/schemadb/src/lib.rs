@@ -36,8 +36,8 @@ use iterator::{ScanDirection, SchemaIterator};
 use rand::Rng;
 /// Type alias to `rocksdb::ReadOptions`. See [`rocksdb doc`](https://github.com/pingcap/rust-rocksdb/blob/master/src/rocksdb_options.rs)
 pub use rocksdb::{
-    BlockBasedOptions, Cache, ColumnFamilyDescriptor, DBCompressionType, Options, ReadOptions,
-    SliceTransform, DEFAULT_COLUMN_FAMILY_NAME,
+    BlockBasedOptions, Cache, ColumnFamilyDescriptor, DBCompactionStyle, DBCompressionType,
+    Options, ReadOptions, SliceTransform, DEFAULT_COLUMN_FAMILY_NAME,
 };
 use std::{collections::HashMap, iter::Iterator, path::Path};
 
@@ -169,6 +169,13 @@ impl DB {
         Ok(Self::log_construct(name, inner))
     }
 
+    /// Makes a DB opened by `open_cf_as_secondary` see the writes of the primary since it was
+    /// opened or last caught up.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.inner.try_catch_up_with_primary()?;
+        Ok(())
+    }
+
     fn log_construct(name: &str, inner: rocksdb::DB) -> DB {
         info!(rocksdb_name = name, "Opened RocksDB.");
         DB {
@@ -228,6 +235,16 @@ impl DB {
 
     /// Writes a group of records wrapped in a [`SchemaBatch`].
     pub fn write_schemas(&self, batch: SchemaBatch) -> Result<()> {
+        self.write_schemas_opt(batch, &default_write_options())
+    }
+
+    /// Same as [`Self::write_schemas`], but doesn't sync the write to disk, i.e. the records can
+    /// be lost if the machine crashes before a later synced write or [`Self::flush_wal`].
+    pub fn write_schemas_relaxed(&self, batch: SchemaBatch) -> Result<()> {
+        self.write_schemas_opt(batch, &rocksdb::WriteOptions::default())
+    }
+
+    fn write_schemas_opt(&self, batch: SchemaBatch, opts: &rocksdb::WriteOptions) -> Result<()> {
         // Function to determine if the counter should be sampled based on a sampling percentage
         fn should_sample(sampling_percentage: usize) -> bool {
             // Generate a random number between 0 and 100
@@ -256,7 +273,7 @@ impl DB {
         }
         let serialized_size = db_batch.size_in_bytes();
 
-        self.inner.write_opt(db_batch, &default_write_options())?;
+        self.inner.write_opt(db_batch, opts)?;
 
         // Bump counters only after DB write succeeds.
         if sampled_kv_bytes {
@@ -312,6 +329,39 @@ impl DB {
             })
     }
 
+    pub fn get_string_property(&self, cf_name: &str, property_name: &str) -> Result<String> {
+        self.inner
+            .property_value_cf(self.get_cf_handle(cf_name)?, property_name)?
+            .ok_or_else(|| {
+                format_err!(
+                    "Unable to get property \"{}\" of  column family \"{}\".",
+                    property_name,
+                    cf_name,
+                )
+            })
+    }
+
+    /// Compacts all the data of the column family down to the bottommost level, dropping the
+    /// deleted entries. Blocks until the compaction is done.
+    pub fn compact_cf(&self, cf_name: &str) -> Result<()> {
+        let mut opts = rocksdb::CompactOptions::default();
+        opts.set_bottommost_level_compaction(rocksdb::BottommostLevelCompaction::Force);
+        self.inner.compact_range_cf_opt(
+            self.get_cf_handle(cf_name)?,
+            None::<&[u8]>,
+            None::<&[u8]>,
+            &opts,
+        );
+        Ok(())
+    }
+
+    /// Syncs the write-ahead log to disk, persisting the writes done by
+    /// [`Self::write_schemas_relaxed`] so far.
+    pub fn flush_wal(&self) -> Result<()> {
+        self.inner.flush_wal(true /* sync */)?;
+        Ok(())
+    }
+
     /// Creates new physical DB checkpoint in directory specified by `path`.
     pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         rocksdb::checkpoint::Checkpoint::new(&self.inner)?.create_checkpoint(path)?;
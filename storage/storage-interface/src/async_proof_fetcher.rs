@@ -12,6 +12,7 @@ use aptos_types::{
 };
 use aptos_vm::AptosVM;
 use crossbeam_channel::{unbounded, Receiver, Sender};
+use dashmap::DashSet;
 use once_cell::sync::Lazy;
 use std::{
     collections::HashMap,
@@ -31,6 +32,9 @@ static IO_POOL: Lazy<ThreadPool> = Lazy::new(|| {
     )
 });
 
+/// Number of prefetched proofs read together, see `DbReader::get_state_value_proofs()`.
+const PREFETCH_BATCH_SIZE: usize = 64;
+
 struct Proof {
     state_key_hash: HashValue,
     proof: SparseMerkleProofExt,
@@ -41,6 +45,9 @@ pub struct AsyncProofFetcher {
     data_sender: Sender<Proof>,
     data_receiver: Receiver<Proof>,
     num_proofs_to_read: AtomicUsize,
+    /// Hashes of the keys whose proofs are scheduled since the last `wait()`, so that the proof
+    /// of a prefetched key is not read again when its value is fetched.
+    scheduled_keys: DashSet<HashValue>,
 }
 
 impl AsyncProofFetcher {
@@ -52,6 +59,7 @@ impl AsyncProofFetcher {
             data_sender,
             data_receiver,
             num_proofs_to_read: AtomicUsize::new(0),
+            scheduled_keys: DashSet::new(),
         }
     }
 
@@ -79,6 +87,57 @@ impl AsyncProofFetcher {
         Ok(version_and_value_opt)
     }
 
+    /// Schedules the proof reads of `state_keys` ahead of the fetches of their values, e.g. for
+    /// the keys read by the previous chunk, which the next one is likely to read as well. The
+    /// proofs are returned by `get_proof_cache()` together with the others, whether the values
+    /// end up being fetched or not.
+    pub fn prefetch_proofs(
+        &self,
+        state_keys: impl IntoIterator<Item = StateKey>,
+        version: Version,
+        root_hash: Option<HashValue>,
+    ) {
+        let _timer = TIMER
+            .with_label_values(&["async_proof_fetcher_prefetch"])
+            .start_timer();
+        let state_keys: Vec<_> = state_keys
+            .into_iter()
+            .filter(|state_key| self.scheduled_keys.insert(state_key.hash()))
+            .collect();
+        for batch in state_keys.chunks(PREFETCH_BATCH_SIZE) {
+            self.num_proofs_to_read
+                .fetch_add(batch.len(), Ordering::SeqCst);
+            let batch = batch.to_vec();
+            let reader = self.reader.clone();
+            let data_sender = self.data_sender.clone();
+            IO_POOL.execute(move || {
+                let proofs = reader
+                    .get_state_value_proofs(&batch, version)
+                    .expect("Proof reading should succeed.");
+                for (state_key, proof) in batch.into_iter().zip(proofs) {
+                    // The value is needed to verify the proof.
+                    let value_hash = root_hash.map(|_| {
+                        reader
+                            .get_state_value_by_version(&state_key, version)
+                            .expect("State value reading should succeed.")
+                            .map(|value| value.hash())
+                    });
+                    Self::verify_and_send_proof(
+                        &data_sender,
+                        state_key,
+                        version,
+                        root_hash,
+                        value_hash.flatten(),
+                        proof,
+                    );
+                }
+                // NOTE: Drop the reader here to make sure reader has shorter lifetime than the
+                // async proof fetcher.
+                drop(reader);
+            });
+        }
+    }
+
     pub fn get_proof_cache(&self) -> HashMap<HashValue, SparseMerkleProofExt> {
         self.wait()
     }
@@ -102,6 +161,7 @@ impl AsyncProofFetcher {
             proofs.insert(state_key_hash, proof);
         }
         self.num_proofs_to_read.store(0, Ordering::SeqCst);
+        self.scheduled_keys.clear();
         proofs
     }
 
@@ -116,6 +176,10 @@ impl AsyncProofFetcher {
         let _timer = TIMER
             .with_label_values(&["schedule_async_proof_read"])
             .start_timer();
+        if !self.scheduled_keys.insert(state_key.hash()) {
+            // Already prefetched.
+            return;
+        }
         self.num_proofs_to_read.fetch_add(1, Ordering::SeqCst);
         let reader = self.reader.clone();
         let data_sender = self.data_sender.clone();
@@ -126,34 +190,52 @@ impl AsyncProofFetcher {
             // NOTE: Drop the reader here to make sure reader has shorter lifetime than the async
             // proof fetcher.
             drop(reader);
-            if let Some(root_hash) = root_hash {
-                proof
-                    .verify_by_hash(root_hash, state_key.hash(), value_hash)
-                    .map_err(|err| {
-                        anyhow!(
-                            "Proof is invalid for key {:?} with state root hash {:?}, at version {}: {}.",
-                            state_key,
-                            root_hash,
-                            version,
-                            err
-                        )
-                    })
-                    .expect("Failed to verify proof.");
-            }
-            match data_sender.send(Proof {
-                state_key_hash: state_key.hash(),
+            Self::verify_and_send_proof(
+                &data_sender,
+                state_key,
+                version,
+                root_hash,
+                value_hash,
                 proof,
-            }) {
-                Ok(_) => {}
-                Err(_) => {
-                    sample!(
-                        SampleRate::Duration(Duration::from_secs(5)),
-                        error!("Failed to send proof, something is wrong in execution.")
-                    );
-                }
-            }
+            );
         });
     }
+
+    fn verify_and_send_proof(
+        data_sender: &Sender<Proof>,
+        state_key: StateKey,
+        version: Version,
+        root_hash: Option<HashValue>,
+        value_hash: Option<HashValue>,
+        proof: SparseMerkleProofExt,
+    ) {
+        if let Some(root_hash) = root_hash {
+            proof
+                .verify_by_hash(root_hash, state_key.hash(), value_hash)
+                .map_err(|err| {
+                    anyhow!(
+                        "Proof is invalid for key {:?} with state root hash {:?}, at version {}: {}.",
+                        state_key,
+                        root_hash,
+                        version,
+                        err
+                    )
+                })
+                .expect("Failed to verify proof.");
+        }
+        match data_sender.send(Proof {
+            state_key_hash: state_key.hash(),
+            proof,
+        }) {
+            Ok(_) => {},
+            Err(_) => {
+                sample!(
+                    SampleRate::Duration(Duration::from_secs(5)),
+                    error!("Failed to send proof, something is wrong in execution.")
+                );
+            },
+        }
+    }
 }
 
 #[cfg(test)]
@@ -184,4 +266,30 @@ mod tests {
         assert_eq!(proofs.len(), 10);
         assert_eq_unordered!(proofs.into_keys().collect::<Vec<_>>(), expected_key_hashes);
     }
+
+    #[test]
+    fn test_prefetch() {
+        let fetcher = AsyncProofFetcher::new(Arc::new(MockDbReaderWriter));
+        let state_keys: Vec<StateKey> = (0..200)
+            .map(|i| StateKey::raw(format!("test_key_{}", i).into_bytes()))
+            .collect();
+
+        // Prefetches the first 150 keys, and fetches the last 100, half of them prefetched.
+        fetcher.prefetch_proofs(state_keys[..150].iter().cloned(), 0, None);
+        for state_key in &state_keys[100..] {
+            fetcher
+                .fetch_state_value_with_version_and_schedule_proof_read(state_key, 0, None)
+                .expect("Should not fail.");
+        }
+
+        let proofs = fetcher.get_proof_cache();
+        assert_eq_unordered!(
+            proofs.into_keys().collect::<Vec<_>>(),
+            state_keys.iter().map(CryptoHash::hash).collect::<Vec<_>>()
+        );
+
+        // Keys are prefetched again after the proofs have been returned.
+        fetcher.prefetch_proofs(state_keys[..10].iter().cloned(), 0, None);
+        assert_eq!(fetcher.get_proof_cache().len(), 10);
+    }
 }
@@ -78,6 +78,13 @@ impl ShardedStateCache {
     pub fn par_iter(&self) -> impl IndexedParallelIterator<Item = &StateCacheShard> {
         self.shards.par_iter()
     }
+
+    /// The keys in the cache, i.e. the keys read through the view the cache came from.
+    pub fn keys(&self) -> impl Iterator<Item = StateKey> + '_ {
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.iter().map(|entry| entry.key().clone()))
+    }
 }
 
 /// `CachedStateView` is like a snapshot of the global state comprised of state view at two
@@ -135,6 +142,10 @@ pub struct CachedStateView {
     /// in JMT node.
     sharded_state_cache: ShardedStateCache,
     proof_fetcher: Arc<AsyncProofFetcher>,
+
+    /// Values returned in place of the actual state, see `override_state_value()`. Kept apart
+    /// from `sharded_state_cache`, which goes into the state checkpoint and usage computation.
+    state_overrides: DashMap<StateKey, Option<StateValue>>,
 }
 
 impl Debug for CachedStateView {
@@ -166,6 +177,7 @@ impl CachedStateView {
             speculative_state,
             sharded_state_cache: ShardedStateCache::default(),
             proof_fetcher,
+            state_overrides: DashMap::new(),
         })
     }
 
@@ -189,6 +201,33 @@ impl CachedStateView {
         Ok(())
     }
 
+    /// Schedules the proof reads of `state_keys` ahead of execution, see
+    /// `AsyncProofFetcher::prefetch_proofs()`. Keys in the speculative state don't need proofs
+    /// from the DB and are skipped.
+    pub fn prefetch_proofs<'a>(&self, state_keys: impl IntoIterator<Item = &'a StateKey>) {
+        if let Some((version, root_hash)) = self.snapshot {
+            let state_keys = state_keys
+                .into_iter()
+                .filter(|state_key| {
+                    matches!(
+                        self.speculative_state.get(state_key.hash()),
+                        StateStoreStatus::ExistsInDB | StateStoreStatus::Unknown
+                    )
+                })
+                .cloned();
+            self.proof_fetcher
+                .prefetch_proofs(state_keys, version, Some(root_hash));
+        }
+    }
+
+    /// Makes the view return `value` for `state_key` regardless of the actual state, e.g. to
+    /// re-execute transactions under a different on-chain config. The override is only seen by
+    /// readers of the view and doesn't go into the state cache, so the state computed from the
+    /// execution results is not affected.
+    pub fn override_state_value(&self, state_key: StateKey, value: Option<StateValue>) {
+        self.state_overrides.insert(state_key, value);
+    }
+
     pub fn into_state_cache(self) -> StateCache {
         StateCache {
             frozen_base: self.speculative_state,
@@ -243,6 +282,9 @@ impl TStateView for CachedStateView {
 
     fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
         let _timer = TIMER.with_label_values(&["get_state_value"]).start_timer();
+        if let Some(value_opt) = self.state_overrides.get(state_key) {
+            return Ok(value_opt.clone());
+        }
         // First check if the cache has the state value.
         if let Some(version_and_value_opt) = self
             .sharded_state_cache
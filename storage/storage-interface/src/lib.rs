@@ -2,7 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::cached_state_view::ShardedStateCache;
+use crate::{cached_state_view::ShardedStateCache, state_iterator::StateIterator};
 use anyhow::{anyhow, format_err, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_types::{
@@ -26,6 +26,7 @@ use aptos_types::{
         state_key_prefix::StateKeyPrefix,
         state_storage_usage::StateStorageUsage,
         state_value::{StateValue, StateValueChunkWithProof},
+        storage_footprint::{StorageFootprint, StorageFootprintOwner, StorageFootprintOwnerKind},
         table::{TableHandle, TableInfo},
         ShardedStateUpdates,
     },
@@ -35,8 +36,9 @@ use aptos_types::{
     },
     write_set::WriteSet,
 };
+use move_core_types::{identifier::IdentStr, language_storage::TypeTag};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, ops::Range, sync::Arc};
 use thiserror::Error;
 
 pub mod async_proof_fetcher;
@@ -46,6 +48,9 @@ mod metrics;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod mock;
 pub mod state_delta;
+pub mod state_iterator;
+pub mod state_snapshot_export;
+pub mod state_snapshot_import;
 pub mod state_view;
 
 use crate::state_delta::StateDelta;
@@ -133,6 +138,15 @@ pub trait DbReader: Send + Sync {
             end_epoch: u64,
         ) -> Result<EpochChangeProof>;
 
+        /// Returns the BCS bytes of the epoch ending ledger infos of `[start_epoch, end_epoch)`,
+        /// without the paging of `get_epoch_ending_ledger_infos`, e.g. to export all the epoch
+        /// changes.
+        fn get_epoch_ending_ledger_infos_bcs(
+            &self,
+            start_epoch: u64,
+            end_epoch: u64,
+        ) -> Result<Box<dyn Iterator<Item = Result<Vec<u8>>> + '_>>;
+
         /// See [AptosDB::get_transactions].
         ///
         /// [AptosDB::get_transactions]: ../aptosdb/struct.AptosDB.html#method.get_transactions
@@ -353,6 +367,15 @@ pub trait DbReader: Send + Sync {
             version: Version,
         ) -> Result<SparseMerkleProofExt>;
 
+        /// Returns the proofs of the given state keys at the given version, in the same order as
+        /// the keys. Equivalent to calling `get_state_proof_by_version_ext()` for each key, but the
+        /// nodes shared by the paths to the keys are read only once.
+        fn get_state_value_proofs(
+            &self,
+            state_keys: &[StateKey],
+            version: Version,
+        ) -> Result<Vec<SparseMerkleProofExt>>;
+
         /// Gets a state value by state key along with the proof, out of the ledger state indicated by the state
         /// Merkle tree root with a sparse merkle proof proving state tree root.
         /// See [AptosDB::get_account_state_with_proof_by_version].
@@ -421,6 +444,18 @@ pub trait DbReader: Send + Sync {
             chunk_size: usize,
         ) -> Result<StateValueChunkWithProof>;
 
+        /// Returns an iterator over the state keys and values at `version`, which must have a
+        /// state snapshot (e.g. the latest state checkpoint version), starting from the key whose
+        /// hash is `start_hashed_key` or the next one (`HashValue::zero()` to start from the
+        /// beginning). With `key_prefix`, only the keys with the prefix are returned, which still
+        /// walks all the keys. See [`state_iterator`].
+        fn get_state_iterator(
+            &self,
+            version: Version,
+            start_hashed_key: HashValue,
+            key_prefix: Option<StateKeyPrefix>,
+        ) -> Result<StateIterator<'_>>;
+
         /// Returns if the state store pruner is enabled.
         fn is_state_merkle_pruner_enabled(&self) -> Result<bool>;
 
@@ -433,14 +468,63 @@ pub trait DbReader: Send + Sync {
         /// Get the ledger prune window config value.
         fn get_ledger_prune_window(&self) -> Result<usize>;
 
+        /// Returns the first version at which the state values are not pruned.
+        fn get_first_state_kv_version(&self) -> Result<Version>;
+
+        /// Get the state kv prune window config value.
+        fn get_state_kv_prune_window(&self) -> Result<usize>;
+
         /// Get table info from the internal indexer.
         fn get_table_info(&self, handle: TableHandle) -> Result<TableInfo>;
 
         /// Returns whether the internal indexer DB has been enabled or not
         fn indexer_enabled(&self) -> bool;
 
+        /// Returns up to `limit` events of type `event_type` whose `field` has the value of which
+        /// `value` is the BCS bytes, from `start_version` up to `ledger_version`. The field must
+        /// be configured in the event field index of the internal indexer.
+        fn get_events_by_field(
+            &self,
+            event_type: &TypeTag,
+            field: &IdentStr,
+            value: &[u8],
+            start_version: Version,
+            limit: u64,
+            ledger_version: Version,
+        ) -> Result<Vec<EventWithVersion>>;
+
+        /// Returns whether the account transaction index of the internal indexer is enabled.
+        fn account_transaction_index_enabled(&self) -> bool;
+
+        /// Returns the versions of up to `limit` transactions that were signed by `address` or
+        /// wrote its resources or modules, in ascending order from `start_version` up to
+        /// `ledger_version`. Requires the account transaction index of the internal indexer.
+        fn get_account_transaction_versions(
+            &self,
+            address: AccountAddress,
+            start_version: Version,
+            limit: u64,
+            ledger_version: Version,
+        ) -> Result<Vec<Version>>;
+
         /// Returns state storage usage at the end of an epoch.
         fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage>;
+
+        /// Returns the version ranges whose ledger data failed the checksum verification when the
+        /// DB was opened and hasn't been repaired since, see [`DbWriter::repair_ledger_data`].
+        fn get_corrupted_ledger_ranges(&self) -> Result<Vec<Range<Version>>>;
+
+        /// Returns the number of state items attributed to `owner` and their total size, as of the
+        /// latest committed version. Requires the storage footprint to be enabled.
+        fn get_storage_footprint(&self, owner: &StorageFootprintOwner) -> Result<StorageFootprint>;
+
+        /// Returns up to `limit` owners of the kind with the largest storage footprint in bytes, in
+        /// descending order. Requires the storage footprint to be enabled.
+        fn get_top_storage_footprints(
+            &self,
+            kind: StorageFootprintOwnerKind,
+            limit: usize,
+        ) -> Result<Vec<(StorageFootprintOwner, StorageFootprint)>>;
     ); // end delegated
 
     /// Returns the latest ledger info.
@@ -551,6 +635,23 @@ pub trait DbWriter: Send + Sync {
     ) -> Result<()> {
         unimplemented!()
     }
+
+    /// Syncs the commits coalesced by group commit to disk right away, instead of waiting for the
+    /// group to fill up or time out. A no-op if group commit is not enabled.
+    fn flush_pending_commits(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Overwrites the transactions, events, write sets and transaction infos of the versions in
+    /// `output_list_with_proof`, e.g. fetched from peers, after verifying it against the latest
+    /// ledger info. Used to repair the ranges returned by
+    /// [`DbReader::get_corrupted_ledger_ranges`].
+    fn repair_ledger_data(
+        &self,
+        output_list_with_proof: TransactionOutputListWithProof,
+    ) -> Result<()> {
+        unimplemented!()
+    }
 }
 
 #[derive(Clone)]
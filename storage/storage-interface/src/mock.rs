@@ -37,6 +37,17 @@ impl DbReader for MockDbReaderWriter {
         Ok(SparseMerkleProofExt::new(None, vec![]))
     }
 
+    fn get_state_value_proofs(
+        &self,
+        state_keys: &[StateKey],
+        _version: Version,
+    ) -> Result<Vec<SparseMerkleProofExt>> {
+        Ok(state_keys
+            .iter()
+            .map(|_| SparseMerkleProofExt::new(None, vec![]))
+            .collect())
+    }
+
     fn get_state_value_by_version(
         &self,
         state_key: &StateKey,
@@ -0,0 +1,94 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A stable API to walk the whole state at a version, e.g. for analytics tools, without depending
+//! on the internals of AptosDB.
+//!
+//! ```ignore
+//! let version = db
+//!     .get_latest_state_checkpoint_version()?
+//!     .expect("No state checkpoint.");
+//! for item in db.get_state_iterator(version, HashValue::zero(), None)? {
+//!     let StateItem { key, value, .. } = item?;
+//!     // ...
+//! }
+//! ```
+//!
+//! The items come in the order of the hashes of the keys, which is the order of the leaves of the
+//! state merkle tree. A walk interrupted after an item can be resumed by starting the next one
+//! from the successor of its hashed key, see [`StateItem::next_hashed_key`].
+
+use anyhow::Result;
+use aptos_crypto::HashValue;
+use aptos_types::state_store::{
+    state_key::StateKey, state_key_prefix::StateKeyPrefix, state_value::StateValue,
+};
+
+/// A state key and its value, as returned by a [`StateIterator`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StateItem {
+    /// The hash of `key`, by which the items are ordered.
+    pub hashed_key: HashValue,
+    pub key: StateKey,
+    pub value: StateValue,
+}
+
+impl StateItem {
+    /// The hashed key to resume a walk from after this item, `None` if this is the last possible
+    /// one.
+    pub fn next_hashed_key(&self) -> Option<HashValue> {
+        let mut bytes = self.hashed_key.to_vec();
+        for byte in bytes.iter_mut().rev() {
+            if *byte == u8::MAX {
+                *byte = 0;
+            } else {
+                *byte += 1;
+                return Some(HashValue::from_slice(&bytes).expect("Must be a valid hash."));
+            }
+        }
+        None
+    }
+}
+
+/// Iterates over the state keys and values at a version, in the order of the hashes of the keys.
+/// See [`crate::DbReader::get_state_iterator`].
+pub struct StateIterator<'a> {
+    inner: Box<dyn Iterator<Item = Result<StateItem>> + Send + 'a>,
+    key_prefix: Option<StateKeyPrefix>,
+}
+
+impl<'a> StateIterator<'a> {
+    /// Wraps `inner`, which yields all the items in the order of their hashed keys, only keeping
+    /// the ones with `key_prefix` if given.
+    pub fn new(
+        inner: impl Iterator<Item = Result<StateItem>> + Send + 'a,
+        key_prefix: Option<StateKeyPrefix>,
+    ) -> Self {
+        Self {
+            inner: Box::new(inner),
+            key_prefix,
+        }
+    }
+}
+
+impl Iterator for StateIterator<'_> {
+    type Item = Result<StateItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let item = match self.inner.next()? {
+                Ok(item) => item,
+                Err(err) => return Some(Err(err)),
+            };
+            let key_prefix = match &self.key_prefix {
+                Some(key_prefix) => key_prefix,
+                None => return Some(Ok(item)),
+            };
+            match key_prefix.is_prefix(&item.key) {
+                Ok(true) => return Some(Ok(item)),
+                Ok(false) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
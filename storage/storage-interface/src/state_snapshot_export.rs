@@ -0,0 +1,319 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Exports the state at a version, or the state changes between two versions, into a portable
+//! format that can be consumed and verified without access to AptosDB.
+//!
+//! An export is a directory holding:
+//!
+//! - `manifest.json`: a [`StateExportManifest`], describing the export and listing its chunks.
+//! - `ledger_info.bcs`: the BCS encoded `LedgerInfoWithSignatures` that all proofs in the export
+//!   are relative to.
+//! - For a state snapshot, `txn_info.bcs`: the BCS encoded `TransactionInfoWithProof` at the
+//!   snapshot version, whose state checkpoint hash is the root hash of the snapshot; and
+//!   `state_chunk_<n>.bcs` files: each a BCS encoded `StateValueChunkWithProof`, whose range proof
+//!   is relative to that root hash.
+//! - For a state diff, `diff_chunk_<n>.bcs` files: each a BCS encoded [`StateDiffChunk`], holding
+//!   the write sets of consecutive versions, together with their transaction infos and the
+//!   accumulator range proof relative to the ledger info.
+//!
+//! A state snapshot exported for bootstrapping a node (see [`export_state_snapshot_for_bootstrap`])
+//! is taken at the end of an epoch, and its ledger info is the epoch ending one at the snapshot
+//! version. It additionally holds:
+//!
+//! - `epoch_change_proof.bcs`: the BCS encoded `EpochChangeProof` from genesis up to the ledger
+//!   info, allowing to verify it starting from any waypoint.
+//! - `txn_output.bcs`: the BCS encoded `TransactionOutputListWithProof` of the transaction at the
+//!   snapshot version, which is needed to initialize the transaction accumulator.
+//!
+//! Chunks can be verified independently of each other, which allows processing them in parallel.
+
+use crate::{DbReader, MAX_REQUEST_LIMIT};
+use anyhow::{ensure, format_err, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoListWithProof,
+    transaction::{TransactionInfo, Version},
+    write_set::WriteSet,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Bumped on every incompatible change of the format.
+pub const STATE_EXPORT_FORMAT_VERSION: u32 = 1;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+pub const LEDGER_INFO_FILE_NAME: &str = "ledger_info.bcs";
+pub const TXN_INFO_FILE_NAME: &str = "txn_info.bcs";
+pub const EPOCH_CHANGE_PROOF_FILE_NAME: &str = "epoch_change_proof.bcs";
+pub const TXN_OUTPUT_FILE_NAME: &str = "txn_output.bcs";
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateExportManifest {
+    pub format_version: u32,
+    /// Version of the ledger info all proofs are relative to.
+    pub ledger_version: Version,
+    pub content: StateExportContent,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateExportContent {
+    /// All state values at `version`.
+    Snapshot {
+        version: Version,
+        root_hash: HashValue,
+        num_state_values: usize,
+        chunks: Vec<StateExportChunk>,
+    },
+    /// The write sets of versions in `(base_version, version]`, which applied to the state at
+    /// `base_version` result in the state at `version`.
+    Diff {
+        base_version: Version,
+        version: Version,
+        chunks: Vec<StateExportChunk>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateExportChunk {
+    /// The index of the first state value for a snapshot, the first version for a diff.
+    pub first: u64,
+    /// The number of state values for a snapshot, the number of versions for a diff.
+    pub len: u64,
+    pub file_name: String,
+}
+
+/// The write sets of a range of consecutive versions.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateDiffChunk {
+    pub first_version: Version,
+    pub write_sets: Vec<WriteSet>,
+    pub txn_infos_with_proof: TransactionInfoListWithProof,
+}
+
+impl StateDiffChunk {
+    /// Verifies the write sets against the transaction infos, and the transaction infos against
+    /// the ledger info of the export.
+    pub fn verify(&self, ledger_info: &LedgerInfoWithSignatures) -> Result<()> {
+        let txn_infos = &self.txn_infos_with_proof.transaction_infos;
+        ensure!(
+            self.write_sets.len() == txn_infos.len(),
+            "Number of write sets ({}) doesn't match number of transaction infos ({}).",
+            self.write_sets.len(),
+            txn_infos.len(),
+        );
+        self.txn_infos_with_proof
+            .verify(ledger_info.ledger_info(), Some(self.first_version))?;
+        for (version, (write_set, txn_info)) in
+            (self.first_version..).zip(self.write_sets.iter().zip(txn_infos))
+        {
+            ensure!(
+                CryptoHash::hash(write_set) == txn_info.state_change_hash(),
+                "Write set at version {} doesn't match the transaction info.",
+                version,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Exports all state values at `version` into `out_dir`, `chunk_size` values per chunk. `version`
+/// must have a state snapshot in the DB.
+pub fn export_state_snapshot(
+    db: &dyn DbReader,
+    version: Version,
+    chunk_size: usize,
+    out_dir: &Path,
+) -> Result<StateExportManifest> {
+    fs::create_dir_all(out_dir)?;
+    let ledger_info = get_ledger_info(db, version)?;
+    export_state_snapshot_impl(db, version, &ledger_info, chunk_size, out_dir)
+}
+
+/// Exports all state values at `version` into `out_dir`, together with what's needed to bootstrap
+/// a node from them. `version` must be the last version of an epoch.
+pub fn export_state_snapshot_for_bootstrap(
+    db: &dyn DbReader,
+    version: Version,
+    chunk_size: usize,
+    out_dir: &Path,
+) -> Result<StateExportManifest> {
+    fs::create_dir_all(out_dir)?;
+    let ledger_info = db.get_epoch_ending_ledger_info(version)?;
+    ensure!(
+        ledger_info.ledger_info().version() == version,
+        "Version {} is not the last version of an epoch.",
+        version,
+    );
+
+    let end_epoch = ledger_info.ledger_info().epoch() + 1;
+    let mut epoch_ending_ledger_infos = vec![];
+    while (epoch_ending_ledger_infos.len() as u64) < end_epoch {
+        let proof =
+            db.get_epoch_ending_ledger_infos(epoch_ending_ledger_infos.len() as u64, end_epoch)?;
+        ensure!(
+            !proof.ledger_info_with_sigs.is_empty(),
+            "Missing epoch ending ledger infos."
+        );
+        epoch_ending_ledger_infos.extend(proof.ledger_info_with_sigs);
+    }
+    write_bcs_file(
+        out_dir,
+        EPOCH_CHANGE_PROOF_FILE_NAME,
+        &EpochChangeProof::new(epoch_ending_ledger_infos, false /* more */),
+    )?;
+    write_bcs_file(
+        out_dir,
+        TXN_OUTPUT_FILE_NAME,
+        &db.get_transaction_outputs(version, 1, version)?,
+    )?;
+
+    export_state_snapshot_impl(db, version, &ledger_info, chunk_size, out_dir)
+}
+
+fn export_state_snapshot_impl(
+    db: &dyn DbReader,
+    version: Version,
+    ledger_info: &LedgerInfoWithSignatures,
+    chunk_size: usize,
+    out_dir: &Path,
+) -> Result<StateExportManifest> {
+    ensure!(chunk_size > 0, "Chunk size must be positive.");
+    write_bcs_file(out_dir, LEDGER_INFO_FILE_NAME, ledger_info)?;
+    let ledger_version = ledger_info.ledger_info().version();
+
+    let txn_info_with_proof = db
+        .get_transaction_by_version(version, ledger_version, false)?
+        .proof;
+    let root_hash = txn_info_with_proof
+        .transaction_info()
+        .state_checkpoint_hash()
+        .ok_or_else(|| format_err!("No state checkpoint at version {}.", version))?;
+    write_bcs_file(out_dir, TXN_INFO_FILE_NAME, &txn_info_with_proof)?;
+
+    let num_state_values = db.get_state_leaf_count(version)?;
+    let mut chunks = vec![];
+    let mut start_idx = 0;
+    while start_idx < num_state_values {
+        let chunk = db.get_state_value_chunk_with_proof(version, start_idx, chunk_size)?;
+        ensure!(
+            chunk.root_hash == root_hash,
+            "Root hash of chunk at index {} doesn't match the state checkpoint hash.",
+            start_idx,
+        );
+        let len = chunk.raw_values.len();
+        ensure!(len > 0, "Empty state chunk at index {}.", start_idx);
+
+        let file_name = format!("state_chunk_{}.bcs", chunks.len());
+        write_bcs_file(out_dir, &file_name, &chunk)?;
+        chunks.push(StateExportChunk {
+            first: start_idx as u64,
+            len: len as u64,
+            file_name,
+        });
+        start_idx += len;
+    }
+
+    write_manifest(out_dir, StateExportManifest {
+        format_version: STATE_EXPORT_FORMAT_VERSION,
+        ledger_version,
+        content: StateExportContent::Snapshot {
+            version,
+            root_hash,
+            num_state_values,
+            chunks,
+        },
+    })
+}
+
+/// Exports the write sets of versions in `(base_version, version]` into `out_dir`, `chunk_size`
+/// versions per chunk.
+pub fn export_state_diff(
+    db: &dyn DbReader,
+    base_version: Version,
+    version: Version,
+    chunk_size: usize,
+    out_dir: &Path,
+) -> Result<StateExportManifest> {
+    ensure!(
+        chunk_size > 0 && chunk_size as u64 <= MAX_REQUEST_LIMIT,
+        "Chunk size must be in [1, {}].",
+        MAX_REQUEST_LIMIT,
+    );
+    ensure!(
+        base_version < version,
+        "Base version {} must be smaller than version {}.",
+        base_version,
+        version,
+    );
+    fs::create_dir_all(out_dir)?;
+    let ledger_info = get_ledger_info(db, version)?;
+    write_bcs_file(out_dir, LEDGER_INFO_FILE_NAME, &ledger_info)?;
+    let ledger_version = ledger_info.ledger_info().version();
+
+    let mut chunks = vec![];
+    let mut first_version = base_version + 1;
+    while first_version <= version {
+        let len = std::cmp::min(chunk_size as u64, version - first_version + 1);
+        let write_sets = db
+            .get_write_set_iterator(first_version, len)?
+            .collect::<Result<Vec<_>>>()?;
+        let txn_infos = db
+            .get_transaction_info_iterator(first_version, len)?
+            .collect::<Result<Vec<TransactionInfo>>>()?;
+        let proof =
+            db.get_transaction_accumulator_range_proof(first_version, len, ledger_version)?;
+        let chunk = StateDiffChunk {
+            first_version,
+            write_sets,
+            txn_infos_with_proof: TransactionInfoListWithProof::new(proof, txn_infos),
+        };
+
+        let file_name = format!("diff_chunk_{}.bcs", chunks.len());
+        write_bcs_file(out_dir, &file_name, &chunk)?;
+        chunks.push(StateExportChunk {
+            first: first_version,
+            len,
+            file_name,
+        });
+        first_version += len;
+    }
+
+    write_manifest(out_dir, StateExportManifest {
+        format_version: STATE_EXPORT_FORMAT_VERSION,
+        ledger_version,
+        content: StateExportContent::Diff {
+            base_version,
+            version,
+            chunks,
+        },
+    })
+}
+
+/// Returns the latest ledger info, which must cover `version`.
+fn get_ledger_info(db: &dyn DbReader, version: Version) -> Result<LedgerInfoWithSignatures> {
+    let ledger_info = db.get_latest_ledger_info()?;
+    ensure!(
+        version <= ledger_info.ledger_info().version(),
+        "Version {} is newer than the latest ledger info at version {}.",
+        version,
+        ledger_info.ledger_info().version(),
+    );
+    Ok(ledger_info)
+}
+
+fn write_bcs_file<T: Serialize>(out_dir: &Path, file_name: &str, value: &T) -> Result<()> {
+    fs::write(out_dir.join(file_name), bcs::to_bytes(value)?)?;
+    Ok(())
+}
+
+fn write_manifest(out_dir: &Path, manifest: StateExportManifest) -> Result<StateExportManifest> {
+    fs::write(
+        out_dir.join(MANIFEST_FILE_NAME),
+        serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    Ok(manifest)
+}
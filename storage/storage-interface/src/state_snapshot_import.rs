@@ -0,0 +1,120 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Initializes an empty DB from a state snapshot exported for bootstrapping, see
+//! [`crate::state_snapshot_export::export_state_snapshot_for_bootstrap`].
+
+use crate::{
+    state_snapshot_export::{
+        StateExportContent, StateExportManifest, EPOCH_CHANGE_PROOF_FILE_NAME,
+        LEDGER_INFO_FILE_NAME, MANIFEST_FILE_NAME, STATE_EXPORT_FORMAT_VERSION,
+        TXN_OUTPUT_FILE_NAME,
+    },
+    DbReaderWriter,
+};
+use anyhow::{bail, ensure, format_err, Result};
+use aptos_logger::prelude::*;
+use aptos_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::state_value::StateValueChunkWithProof,
+    transaction::{TransactionOutputListWithProof, Version},
+    trusted_state::TrustedState,
+    waypoint::Waypoint,
+};
+use serde::de::DeserializeOwned;
+
+/// Verifies the snapshot against `waypoint` and saves it into the empty `db`. `read_file` returns
+/// the content of a file of the snapshot, given its name. Returns the snapshot version.
+pub fn import_state_snapshot(
+    db: &DbReaderWriter,
+    waypoint: Waypoint,
+    read_file: impl Fn(&str) -> Result<Vec<u8>>,
+) -> Result<Version> {
+    ensure!(
+        db.reader.get_latest_ledger_info_option()?.is_none(),
+        "Can only bootstrap an empty DB from a state snapshot."
+    );
+
+    let manifest: StateExportManifest = serde_json::from_slice(&read_file(MANIFEST_FILE_NAME)?)?;
+    ensure!(
+        manifest.format_version == STATE_EXPORT_FORMAT_VERSION,
+        "Unsupported state snapshot format version {}, expected {}.",
+        manifest.format_version,
+        STATE_EXPORT_FORMAT_VERSION,
+    );
+    let (version, root_hash, chunks) = match manifest.content {
+        StateExportContent::Snapshot {
+            version,
+            root_hash,
+            chunks,
+            ..
+        } => (version, root_hash, chunks),
+        StateExportContent::Diff { .. } => bail!("Can't bootstrap from a state diff."),
+    };
+
+    // Verify the ledger info against the waypoint, and the transaction at the snapshot version
+    // against the ledger info.
+    let ledger_info: LedgerInfoWithSignatures = read_bcs_file(&read_file, LEDGER_INFO_FILE_NAME)?;
+    let epoch_change_proof: EpochChangeProof =
+        read_bcs_file(&read_file, EPOCH_CHANGE_PROOF_FILE_NAME)?;
+    ensure!(
+        ledger_info.ledger_info().version() == version && ledger_info.ledger_info().ends_epoch(),
+        "The ledger info must end the epoch at the snapshot version {}.",
+        version,
+    );
+    if waypoint.version() == version {
+        waypoint.verify(ledger_info.ledger_info())?;
+    } else {
+        TrustedState::from_epoch_waypoint(waypoint)
+            .verify_and_ratchet_inner(&ledger_info, &epoch_change_proof)?;
+    }
+
+    let output_with_proof: TransactionOutputListWithProof =
+        read_bcs_file(&read_file, TXN_OUTPUT_FILE_NAME)?;
+    output_with_proof.verify(ledger_info.ledger_info(), Some(version))?;
+    let txn_info = output_with_proof
+        .proof
+        .transaction_infos
+        .first()
+        .ok_or_else(|| format_err!("Missing the transaction info at version {}.", version))?;
+    ensure!(
+        txn_info.state_checkpoint_hash() == Some(root_hash),
+        "The state root hash {} doesn't match the transaction info at version {}.",
+        root_hash,
+        version,
+    );
+
+    // The receiver verifies every chunk against the root hash.
+    let mut receiver = db.writer.get_state_snapshot_receiver(version, root_hash)?;
+    let num_chunks = chunks.len();
+    for (idx, chunk) in chunks.into_iter().enumerate() {
+        let chunk: StateValueChunkWithProof = read_bcs_file(&read_file, &chunk.file_name)?;
+        receiver.add_chunk(chunk.raw_values, chunk.proof)?;
+        info!(
+            chunk = idx,
+            num_chunks = num_chunks,
+            "State snapshot chunk imported."
+        );
+    }
+    receiver.finish_box()?;
+
+    // Only save the epoch ending ledger infos verified above.
+    let mut ledger_infos = epoch_change_proof.ledger_info_with_sigs;
+    ledger_infos.retain(|li| li.ledger_info().version() >= waypoint.version());
+    if ledger_infos.last() != Some(&ledger_info) {
+        ledger_infos.push(ledger_info);
+    }
+    db.writer
+        .finalize_state_snapshot(version, output_with_proof, &ledger_infos)?;
+
+    info!(version = version, "Bootstrapped DB from state snapshot.");
+    Ok(version)
+}
+
+fn read_bcs_file<T: DeserializeOwned>(
+    read_file: &impl Fn(&str) -> Result<Vec<u8>>,
+    file_name: &str,
+) -> Result<T> {
+    Ok(bcs::from_bytes(&read_file(file_name)?)?)
+}
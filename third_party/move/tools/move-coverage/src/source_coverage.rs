@@ -26,6 +26,7 @@ use std::{
 #[derive(Clone, Debug, Serialize)]
 pub struct FunctionSourceCoverage {
     pub fn_is_native: bool,
+    pub covered_locations: Vec<Loc>,
     pub uncovered_locations: Vec<Loc>,
 }
 
@@ -80,6 +81,7 @@ impl<'a> SourceCoverageBuilder<'a> {
                 let coverage = match &function_def.code {
                     None => Some(FunctionSourceCoverage {
                         fn_is_native: true,
+                        covered_locations: Vec::new(),
                         uncovered_locations: Vec::new(),
                     }),
                     Some(code_unit) => {
@@ -94,29 +96,32 @@ impl<'a> SourceCoverageBuilder<'a> {
 
                                 FunctionSourceCoverage {
                                     fn_is_native: false,
+                                    covered_locations: Vec::new(),
                                     uncovered_locations,
                                 }
                             },
                             Some(function_coverage) => {
-                                let uncovered_locations: Vec<_> = (0..code_unit.code.len())
-                                    .flat_map(|code_offset| {
-                                        if !function_coverage.contains_key(&(code_offset as u64)) {
-                                            Some(
-                                                source_map
-                                                    .get_code_location(
-                                                        function_def_idx,
-                                                        code_offset as CodeOffset,
-                                                    )
-                                                    .unwrap(),
-                                            )
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .collect();
+                                let (covered_offsets, uncovered_offsets): (Vec<_>, Vec<_>) =
+                                    (0..code_unit.code.len()).partition(|code_offset| {
+                                        function_coverage.contains_key(&(*code_offset as u64))
+                                    });
+                                let locations = |code_offsets: Vec<usize>| -> Vec<Loc> {
+                                    code_offsets
+                                        .into_iter()
+                                        .map(|code_offset| {
+                                            source_map
+                                                .get_code_location(
+                                                    function_def_idx,
+                                                    code_offset as CodeOffset,
+                                                )
+                                                .unwrap()
+                                        })
+                                        .collect()
+                                };
                                 FunctionSourceCoverage {
                                     fn_is_native: false,
-                                    uncovered_locations,
+                                    covered_locations: locations(covered_offsets),
+                                    uncovered_locations: locations(uncovered_offsets),
                                 }
                             },
                         })
@@ -223,6 +228,70 @@ impl<'a> SourceCoverageBuilder<'a> {
 
         SourceCoverage { annotated_lines }
     }
+
+    /// Computes which lines of the source file hold code, and whether any of it was executed.
+    /// A line is attributed the code whose location starts on it.
+    pub fn compute_line_coverage(&self, file_path: &Path) -> LineCoverage {
+        let file_contents = fs::read_to_string(file_path).unwrap();
+        assert!(
+            self.source_map.check(&file_contents),
+            "File contents out of sync with source map"
+        );
+        let mut files = Files::new();
+        let file_id = files.add(file_path.as_os_str().to_os_string(), file_contents);
+
+        let mut lines = BTreeMap::new();
+        for fn_cov in self.uncovered_locations.values() {
+            let locations = fn_cov
+                .covered_locations
+                .iter()
+                .map(|loc| (loc, true))
+                .chain(fn_cov.uncovered_locations.iter().map(|loc| (loc, false)));
+            for (loc, covered) in locations {
+                let line = files.location(file_id, loc.start()).unwrap().line.0 + 1;
+                *lines.entry(line).or_insert(false) |= covered;
+            }
+        }
+        LineCoverage { lines }
+    }
+}
+
+/// The lines of a source file holding code, numbered from 1, and whether they were executed
+#[derive(Debug, Serialize)]
+pub struct LineCoverage {
+    pub lines: BTreeMap<u32, bool>,
+}
+
+impl LineCoverage {
+    pub fn lines_found(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|covered| **covered).count()
+    }
+
+    /// Writes the coverage of the file as an LCOV record, the format read by most coverage
+    /// dashboards. The executed lines are counted as hit once.
+    pub fn output_lcov<W: Write>(
+        &self,
+        source_path: &Path,
+        output_writer: &mut W,
+    ) -> io::Result<()> {
+        writeln!(output_writer, "SF:{}", source_path.display())?;
+        for (line, covered) in &self.lines {
+            writeln!(output_writer, "DA:{},{}", line, u8::from(*covered))?;
+        }
+        writeln!(output_writer, "LF:{}", self.lines_found())?;
+        writeln!(output_writer, "LH:{}", self.lines_hit())?;
+        writeln!(output_writer, "end_of_record")
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
 }
 
 impl SourceCoverage {
@@ -238,8 +307,48 @@ impl SourceCoverage {
         }
         Ok(())
     }
+
+    /// Writes the source as an HTML page, with the uncovered code highlighted in red
+    pub fn output_html<W: Write>(&self, title: &str, output_writer: &mut W) -> io::Result<()> {
+        writeln!(
+            output_writer,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+            <style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n<pre>",
+            escape_html(title),
+            HTML_STYLE,
+            escape_html(title)
+        )?;
+        for (line_number, line) in self.annotated_lines.iter().enumerate() {
+            write!(
+                output_writer,
+                "<span class=\"line-number\">{:>5}</span> ",
+                line_number + 1
+            )?;
+            for string_segment in line.iter() {
+                match string_segment {
+                    StringSegment::Covered(s) => write!(output_writer, "{}", escape_html(s))?,
+                    StringSegment::Uncovered(s) => write!(
+                        output_writer,
+                        "<span class=\"uncovered\">{}</span>",
+                        escape_html(s)
+                    )?,
+                }
+            }
+            writeln!(output_writer)?;
+        }
+        writeln!(output_writer, "</pre>\n</body>\n</html>")
+    }
 }
 
+/// The style of the HTML coverage pages
+pub const HTML_STYLE: &str = "body { font-family: sans-serif; }
+pre { font-family: monospace; }
+.line-number { color: #999; user-select: none; }
+.uncovered { background-color: #fdd; color: #a00; font-weight: bold; }
+table { border-collapse: collapse; }
+td, th { padding: 4px 12px; text-align: left; }
+";
+
 fn merge_spans(cov: FunctionSourceCoverage) -> Vec<Span> {
     if cov.uncovered_locations.is_empty() {
         return vec![];
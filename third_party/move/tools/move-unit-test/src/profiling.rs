@@ -0,0 +1,40 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module manages an optional hook for profiling unit tests. Since the gas meters of
+//! custom Move environments live outside of the Move repo, the hook is given everything
+//! needed to re-execute a test under its own gas meter, after the test has run.
+
+use move_core_types::{identifier::IdentStr, language_storage::ModuleId, resolver::MoveResolver};
+use move_vm_runtime::{move_vm::MoveVM, native_extensions::NativeContextExtensions};
+use once_cell::sync::Lazy;
+use std::sync::{Arc, Mutex};
+
+/// A hook which is called with the VM, the starting storage state, a fresh set of native
+/// extensions, the module and name of the test function, and the serialized arguments of the
+/// test.
+pub type ProfilingHook = dyn for<'a> Fn(
+        &MoveVM,
+        &'a dyn MoveResolver,
+        NativeContextExtensions<'a>,
+        &ModuleId,
+        &IdentStr,
+        Vec<Vec<u8>>,
+    ) + Send
+    + Sync;
+
+static PROFILING_HOOK: Lazy<Mutex<Option<Arc<ProfilingHook>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Sets a hook which is called to profile every unit test that is run. This needs to be called
+/// before `cli::run_move_unit_tests`.
+pub fn set_profiling_hook(p: Box<ProfilingHook>) {
+    *PROFILING_HOOK.lock().unwrap() = Some(Arc::from(p))
+}
+
+/// Returns the profiling hook, if one is set.
+///
+/// The hook is cloned out of the lock so that tests running in parallel can be profiled
+/// concurrently.
+pub(crate) fn profiling_hook() -> Option<Arc<ProfilingHook>> {
+    PROFILING_HOOK.lock().unwrap().clone()
+}
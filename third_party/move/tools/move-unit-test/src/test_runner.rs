@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    extensions, format_module_id,
+    extensions, format_module_id, profiling,
     test_reporter::{
         FailureReason, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
     },
@@ -295,10 +295,23 @@ impl SharedTestingConfig {
                 .unwrap()
                 .into(),
         );
-        match session.finish_with_extensions() {
+        let result = match session.finish_with_extensions() {
             Ok((cs, extensions)) => (Ok(cs), Ok(extensions), return_result, test_run_info),
             Err(err) => (Err(err.clone()), Err(err), return_result, test_run_info),
+        };
+
+        if let Some(hook) = profiling::profiling_hook() {
+            hook(
+                &move_vm,
+                &self.starting_storage_state,
+                extensions::new_extensions(),
+                &test_plan.module_id,
+                IdentStr::new(function_name).unwrap(),
+                serialize_values(test_info.arguments.iter()),
+            );
         }
+
+        result
     }
 
     fn exec_module_tests_move_vm_and_stackless_vm(
@@ -5,7 +5,7 @@ use crate::on_chain_config::OnChainConfig;
 use serde::{Deserialize, Serialize};
 
 /// The feature flags define in the Move source. This must stay aligned with the constants there.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[allow(non_camel_case_types)]
 pub enum FeatureFlag {
     CODE_DEPENDENCY_CHECK = 1,
@@ -73,6 +73,25 @@ impl Features {
         byte_index < self.features.len() && (self.features[byte_index] & bit_mask != 0)
     }
 
+    pub fn enable(&mut self, flag: FeatureFlag) {
+        let val = flag as u64;
+        let byte_index = (val / 8) as usize;
+        let bit_mask = 1 << (val % 8);
+        if self.features.len() <= byte_index {
+            self.features.resize(byte_index + 1, 0);
+        }
+        self.features[byte_index] |= bit_mask;
+    }
+
+    pub fn disable(&mut self, flag: FeatureFlag) {
+        let val = flag as u64;
+        let byte_index = (val / 8) as usize;
+        let bit_mask = 1 << (val % 8);
+        if byte_index < self.features.len() {
+            self.features[byte_index] &= !bit_mask;
+        }
+    }
+
     pub fn are_resource_groups_enabled(&self) -> bool {
         self.is_enabled(FeatureFlag::RESOURCE_GROUPS)
     }
@@ -10,6 +10,7 @@ pub mod state_key;
 pub mod state_key_prefix;
 pub mod state_storage_usage;
 pub mod state_value;
+pub mod storage_footprint;
 pub mod table;
 
 pub type ShardedStateUpdates = [HashMap<StateKey, Option<StateValue>>; 16];
@@ -0,0 +1,157 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::arc_with_non_send_sync)]
+
+use crate::{
+    access_path::Path,
+    state_store::{
+        state_key::{StateKey, StateKeyInner},
+        table::TableHandle,
+    },
+};
+use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
+use serde::{Deserialize, Serialize};
+
+/// What the state items are attributed to in the storage footprint accounting.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
+pub enum StorageFootprintOwner {
+    /// The resources, resource groups and modules stored under an account.
+    Account(AccountAddress),
+    /// The code of a module, and the resources of the types it declares, under any account.
+    Module(ModuleId),
+    /// The items of a table.
+    Table(TableHandle),
+}
+
+impl StorageFootprintOwner {
+    /// Returns the owners the state key is attributed to. A resource counts toward both the
+    /// account it's stored under and the module declaring its type. Resources in a resource
+    /// group count toward the module declaring the group. Raw state keys are not attributed.
+    pub fn owners_of(state_key: &StateKey) -> Vec<Self> {
+        match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => {
+                let module = match bcs::from_bytes::<Path>(&access_path.path) {
+                    Ok(Path::Code(module_id)) => Some(module_id),
+                    Ok(Path::Resource(struct_tag)) | Ok(Path::ResourceGroup(struct_tag)) => {
+                        Some(struct_tag.module_id())
+                    },
+                    Err(_) => None,
+                };
+                std::iter::once(Self::Account(access_path.address))
+                    .chain(module.map(Self::Module))
+                    .collect()
+            },
+            StateKeyInner::TableItem { handle, .. } => vec![Self::Table(*handle)],
+            StateKeyInner::Raw(_) => vec![],
+        }
+    }
+
+    pub fn kind(&self) -> StorageFootprintOwnerKind {
+        match self {
+            Self::Account(_) => StorageFootprintOwnerKind::Account,
+            Self::Module(_) => StorageFootprintOwnerKind::Module,
+            Self::Table(_) => StorageFootprintOwnerKind::Table,
+        }
+    }
+}
+
+/// The kind of a [`StorageFootprintOwner`]. The variants must stay in the same order, since the
+/// serialized kind is the prefix of the serialized owners of the kind.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum StorageFootprintOwnerKind {
+    Account,
+    Module,
+    Table,
+}
+
+/// The number of state items attributed to an owner and their total size in bytes, counted the
+/// same way as the [`StateStorageUsage`](super::state_storage_usage::StateStorageUsage).
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
+pub struct StorageFootprint {
+    pub items: u64,
+    pub bytes: u64,
+}
+
+impl StorageFootprint {
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    /// Applies a delta, saturating at 0 since the counters are approximate, e.g. when they were
+    /// rebuilt from a snapshot missing some items.
+    pub fn apply(&mut self, delta: StorageFootprintDelta) {
+        self.items = self.items.saturating_add_signed(delta.items);
+        self.bytes = self.bytes.saturating_add_signed(delta.bytes);
+    }
+}
+
+/// The change of a [`StorageFootprint`] over some versions.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StorageFootprintDelta {
+    pub items: i64,
+    pub bytes: i64,
+}
+
+impl StorageFootprintDelta {
+    pub fn add_item(&mut self, bytes: usize) {
+        self.items += 1;
+        self.bytes += bytes as i64;
+    }
+
+    pub fn remove_item(&mut self, bytes: usize) {
+        self.items -= 1;
+        self.bytes -= bytes as i64;
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.items += other.items;
+        self.bytes += other.bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_path::AccessPath;
+    use move_core_types::{identifier::Identifier, language_storage::StructTag};
+
+    #[test]
+    fn test_owners_of() {
+        let address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let module_id = ModuleId::new(AccountAddress::ONE, Identifier::new("coin").unwrap());
+        let struct_tag = StructTag {
+            address: AccountAddress::ONE,
+            module: Identifier::new("coin").unwrap(),
+            name: Identifier::new("CoinStore").unwrap(),
+            type_params: vec![],
+        };
+
+        assert_eq!(
+            StorageFootprintOwner::owners_of(&StateKey::access_path(
+                AccessPath::resource_access_path(address, struct_tag).unwrap()
+            )),
+            vec![
+                StorageFootprintOwner::Account(address),
+                StorageFootprintOwner::Module(module_id.clone()),
+            ],
+        );
+        assert_eq!(
+            StorageFootprintOwner::owners_of(&StateKey::access_path(AccessPath::code_access_path(
+                module_id.clone()
+            ))),
+            vec![
+                StorageFootprintOwner::Account(AccountAddress::ONE),
+                StorageFootprintOwner::Module(module_id),
+            ],
+        );
+        let handle = TableHandle(address);
+        assert_eq!(
+            StorageFootprintOwner::owners_of(&StateKey::table_item(handle, vec![1, 2, 3])),
+            vec![StorageFootprintOwner::Table(handle)],
+        );
+        assert!(StorageFootprintOwner::owners_of(&StateKey::raw(vec![1])).is_empty());
+    }
+}
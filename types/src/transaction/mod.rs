@@ -431,6 +431,30 @@ impl RawTransaction {
         self.sender
     }
 
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    pub fn payload(&self) -> &TransactionPayload {
+        &self.payload
+    }
+
+    pub fn max_gas_amount(&self) -> u64 {
+        self.max_gas_amount
+    }
+
+    pub fn gas_unit_price(&self) -> u64 {
+        self.gas_unit_price
+    }
+
+    pub fn expiration_timestamp_secs(&self) -> u64 {
+        self.expiration_timestamp_secs
+    }
+
+    pub fn chain_id(&self) -> ChainId {
+        self.chain_id
+    }
+
     /// Return the signing message for creating transaction signature.
     pub fn signing_message(&self) -> Result<Vec<u8>, CryptoMaterialError> {
         signing_message(self)